@@ -91,4 +91,54 @@ def benchmark_call_def_8name():
     return y
 
 
+def benchmark_dict_int_update():
+    d = {}
+    for x in range(REPEAT_100M):
+        k = x % 1000
+        d[k] = d.get(k, 0) + 1
+    return len(d)
+
+
+def benchmark_dict_str_update():
+    keys = ["key_" + str(i) for i in range(1000)]
+    d = {}
+    for x in range(REPEAT_100M):
+        k = keys[x % 1000]
+        d[k] = d.get(k, 0) + 1
+    return len(d)
+
+
+def benchmark_str_repeated():
+    n = 0
+    for x in range(REPEAT_100M):
+        n += len(str(x % 1000))
+    return n
+
+
+def benchmark_list_append():
+    n = 0
+    for _x in range(REPEAT_100M // 1000):
+        xs = []
+        for x in range(1000):
+            xs.append(x)
+        n += len(xs)
+    return n
+
+
+def benchmark_str_format():
+    y = ""
+    for x in range(REPEAT_100M):
+        y = "{}".format(x)
+    return y
+
+
+def benchmark_str_index():
+    # `index` is also a list method, so the call site is compiled for lists.
+    y = 0
+    s = "abc"
+    for _x in range(REPEAT_100M):
+        y = s.index("b")
+    return y
+
+
 print(benchmark_call_def_1name())
@@ -22,6 +22,7 @@ import subprocess
 import tempfile
 import time
 from pathlib import Path
+from statistics import mean, median
 
 
 def compile_starlark():
@@ -69,9 +70,8 @@ def cmd(args):
 
 
 def absh(a, b, repeat):
-    a_time = 0
-    b_time = 0
-    runs = 0
+    a_times = []
+    b_times = []
 
     # Run a/b repeatedly, ignoring the first loop around
     for i in range(repeat + 1):
@@ -82,13 +82,12 @@ def absh(a, b, repeat):
         end_time = time.time()
 
         if i != 0:
-            a_time += middle_time - start_time
-            b_time += end_time - middle_time
-            runs += 1
+            a_times.append(middle_time - start_time)
+            b_times.append(end_time - middle_time)
         print(".", end="", flush=True)
 
     print("")
-    return (a_time / runs, b_time / runs)
+    return (a_times, b_times)
 
 
 def main():
@@ -99,6 +98,11 @@ def main():
         type=int,
         help="How many times to repeat",
     )
+    parser.add_argument(
+        "--baseline",
+        type=str,
+        help="Starlark binary to compare against, instead of Python",
+    )
     parser.add_argument(
         "benchmarks",
         nargs="*",
@@ -113,8 +117,26 @@ def main():
         for name, file in benchmarks.items():
             if len(args.benchmarks) == 0 or name in args.benchmarks:
                 print("Benchmarking: " + name + " ", end="", flush=True)
-                (py, st) = absh(("python3", file), (starlark, file), repeat=args.repeat)
-                print("Python3 {:.2f}s, Starlark Rust {:.2f}s".format(py, st))
+                if args.baseline is None:
+                    (py, st) = absh(
+                        ("python3", file), (starlark, file), repeat=args.repeat
+                    )
+                    print(
+                        "Python3 {:.2f}s, Starlark Rust {:.2f}s".format(
+                            mean(py), mean(st)
+                        )
+                    )
+                else:
+                    # Compare the median and the fastest run, which are less
+                    # sensitive to a noisy machine than the mean.
+                    (base, st) = absh(
+                        (args.baseline, file), (starlark, file), repeat=args.repeat
+                    )
+                    print(
+                        "Baseline {:.2f}s (min {:.2f}s), Starlark Rust {:.2f}s (min {:.2f}s)".format(
+                            median(base), min(base), median(st), min(st)
+                        )
+                    )
 
 
 if __name__ == "__main__":
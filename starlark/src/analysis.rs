@@ -20,6 +20,9 @@
 use std::collections::HashSet;
 
 pub use lint_message::LintMessage;
+pub use top_level::TopLevelPolicy;
+pub use typecheck::typecheck;
+pub use typecheck::TypeError;
 pub use types::EvalMessage;
 pub use types::EvalSeverity;
 pub use types::Lint;
@@ -35,6 +38,8 @@ mod incompatible;
 mod lint_message;
 mod names;
 mod performance;
+mod top_level;
+mod typecheck;
 mod types;
 mod underscore;
 mod unused_loads;
@@ -307,4 +312,29 @@ def good2() -> str:
         assert_eq!(res.len(), 1);
         assert!(res[0].problem.contains("bad1"));
     }
+
+    #[test]
+    fn test_lint_order_stable() {
+        let src = r#"
+load("a", "z", "y", "x")
+def f(c, b, a):
+    q = 1
+    p = 2
+    d = {"k": 1, "k": 2}
+    return undefined1 + undefined2
+"#;
+        let globals = HashSet::new();
+        let render = || {
+            module(src)
+                .lint(Some(&globals))
+                .iter()
+                .map(|l| l.to_string())
+                .collect::<Vec<_>>()
+        };
+        let first = render();
+        assert!(first.len() > 5, "{first:?}");
+        for _ in 0..10 {
+            assert_eq!(first, render());
+        }
+    }
 }
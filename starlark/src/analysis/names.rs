@@ -484,6 +484,7 @@ impl<'a> State<'a> {
                 self.assign_as_expr(lhs);
                 self.assign(lhs);
             }
+            Stmt::Del(x) => self.assign_as_expr(x),
             Stmt::Statements(xs) => {
                 for x in xs {
                     self.stmt(x)
@@ -0,0 +1,300 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use starlark_syntax::syntax::ast::AstExpr;
+use starlark_syntax::syntax::ast::AstLiteral;
+use starlark_syntax::syntax::ast::AstStmt;
+use starlark_syntax::syntax::ast::Expr;
+use starlark_syntax::syntax::ast::Stmt;
+use starlark_syntax::syntax::module::AstModuleFields;
+use thiserror::Error;
+
+use crate::analysis::types::LintT;
+use crate::analysis::types::LintWarning;
+use crate::analysis::EvalSeverity;
+use crate::analysis::Lint;
+use crate::codemap::CodeMap;
+use crate::syntax::AstModule;
+
+/// What a module may contain at the top level, checked by [`TopLevelPolicy::check`].
+///
+/// The two policies follow Bazel and Buck, which distinguish files declaring
+/// targets from files defining the functions those use. Neither allows
+/// `for`, `if` or `del` statements at the top level.
+#[derive(Debug, Clone)]
+pub enum TopLevelPolicy {
+    /// Files like `BUILD`: `load`, assignments and calls are allowed,
+    /// but `def` is not.
+    Build,
+    /// Files like `.bzl` libraries: only `load`, `def`, plain assignments and
+    /// a docstring are allowed, plus calls to the functions named in
+    /// `allowed_calls`. The same applies to calls made while evaluating
+    /// an assignment or the arguments of an allowed call, except inside `lambda`.
+    /// A method call is named by its full dotted path, e.g. `native.register_toolchains`.
+    Library {
+        /// Functions which may be called at the top level.
+        allowed_calls: Vec<String>,
+    },
+}
+
+#[derive(Error, Debug)]
+pub(crate) enum TopLevelWarning {
+    #[error("`def` is not allowed in this file")]
+    Def,
+    #[error("`{0}` is not allowed at the top level")]
+    Statement(&'static str),
+    #[error("Call to `{0}` is not allowed at the top level")]
+    Call(String),
+    #[error("Only `load`, `def`, assignments and allowed calls are permitted at the top level")]
+    Expression,
+}
+
+impl LintWarning for TopLevelWarning {
+    fn severity(&self) -> EvalSeverity {
+        EvalSeverity::Error
+    }
+
+    fn short_name(&self) -> &'static str {
+        match self {
+            TopLevelWarning::Def => "top-level-def",
+            TopLevelWarning::Statement(..) => "top-level-statement",
+            TopLevelWarning::Call(..) => "top-level-call",
+            TopLevelWarning::Expression => "top-level-expression",
+        }
+    }
+}
+
+impl TopLevelPolicy {
+    /// Report every top-level statement of `module` which this policy forbids,
+    /// pointing at the statement. Lints can be suppressed like any other.
+    pub fn check(&self, module: &AstModule) -> Vec<Lint> {
+        let mut res = Vec::new();
+        self.check_stmt(module.codemap(), module.statement(), &mut res);
+        res.into_iter()
+            .map(LintT::erase)
+            .filter(|issue| !module.is_suppressed(&issue.short_name, issue.location.span))
+            .collect()
+    }
+
+    fn check_stmt(&self, codemap: &CodeMap, x: &AstStmt, res: &mut Vec<LintT<TopLevelWarning>>) {
+        let problem = match &**x {
+            Stmt::Statements(xs) => {
+                for x in xs {
+                    self.check_stmt(codemap, x, res);
+                }
+                return;
+            }
+            Stmt::For(..) => Some(TopLevelWarning::Statement("for")),
+            Stmt::If(..) | Stmt::IfElse(..) => Some(TopLevelWarning::Statement("if")),
+            Stmt::Del(..) => Some(TopLevelWarning::Statement("del")),
+            Stmt::Def(..) => match self {
+                TopLevelPolicy::Build => Some(TopLevelWarning::Def),
+                TopLevelPolicy::Library { .. } => None,
+            },
+            Stmt::AssignModify(..) => match self {
+                TopLevelPolicy::Build => None,
+                TopLevelPolicy::Library { .. } => {
+                    Some(TopLevelWarning::Statement("augmented assignment"))
+                }
+            },
+            Stmt::Expression(e) => match self {
+                TopLevelPolicy::Build => None,
+                TopLevelPolicy::Library { allowed_calls } => {
+                    match Self::check_library_expr(e, allowed_calls) {
+                        Some(problem) => Some(problem),
+                        None => {
+                            Self::check_library_calls(codemap, e, allowed_calls, res);
+                            None
+                        }
+                    }
+                }
+            },
+            Stmt::Assign(assign) => {
+                if let TopLevelPolicy::Library { allowed_calls } = self {
+                    Self::check_library_calls(codemap, &assign.rhs, allowed_calls, res);
+                }
+                None
+            }
+            Stmt::Load(..) | Stmt::Pass | Stmt::Break | Stmt::Continue | Stmt::Return(..) => None,
+        };
+        if let Some(problem) = problem {
+            res.push(LintT::new(codemap, x.span, problem));
+        }
+    }
+
+    fn check_library_expr(e: &AstExpr, allowed_calls: &[String]) -> Option<TopLevelWarning> {
+        match &**e {
+            Expr::Literal(AstLiteral::String(_)) => None,
+            Expr::Call(f, _) => match call_name(f) {
+                Some(name) if allowed_calls.contains(&name) => None,
+                Some(name) => Some(TopLevelWarning::Call(name)),
+                None => Some(TopLevelWarning::Expression),
+            },
+            _ => Some(TopLevelWarning::Expression),
+        }
+    }
+
+    /// Report the calls in `e` which are not allowed, pointing at each call.
+    fn check_library_calls(
+        codemap: &CodeMap,
+        e: &AstExpr,
+        allowed_calls: &[String],
+        res: &mut Vec<LintT<TopLevelWarning>>,
+    ) {
+        match &**e {
+            // The body runs when the lambda is called, not at the top level.
+            Expr::Lambda(..) => return,
+            Expr::Call(f, _) => {
+                let problem = match call_name(f) {
+                    Some(name) if allowed_calls.contains(&name) => None,
+                    Some(name) => Some(TopLevelWarning::Call(name)),
+                    None => Some(TopLevelWarning::Expression),
+                };
+                if let Some(problem) = problem {
+                    res.push(LintT::new(codemap, e.span, problem));
+                }
+            }
+            _ => {}
+        }
+        e.visit_expr(|x| Self::check_library_calls(codemap, x, allowed_calls, res));
+    }
+}
+
+/// `f` or `a.b.f` for a function called as such.
+fn call_name(f: &AstExpr) -> Option<String> {
+    match &**f {
+        Expr::Identifier(x) => Some(x.node.ident.clone()),
+        Expr::Dot(x, attr) => Some(format!("{}.{}", call_name(x)?, attr.node)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::Dialect;
+
+    fn check(policy: &TopLevelPolicy, program: &str) -> Vec<String> {
+        let module =
+            AstModule::parse("X", program.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+        policy
+            .check(&module)
+            .into_iter()
+            .map(|l| format!("{}: {}", l.short_name, l.original.trim_end()))
+            .collect()
+    }
+
+    #[test]
+    fn test_build_policy() {
+        let policy = TopLevelPolicy::Build;
+        assert_eq!(
+            Vec::<String>::new(),
+            check(
+                &policy,
+                r#"
+load("//rules.bzl", "cc_library")
+SRCS = ["a.c"]
+SRCS += ["b.c"]
+cc_library(name = "lib", srcs = SRCS)
+native.exports_files(["a.c"])
+"#
+            )
+        );
+        assert_eq!(
+            vec![
+                "top-level-def: def helper():\n    pass",
+                "top-level-statement: for x in []:\n    cc_library(name = x)",
+                "top-level-statement: if True:\n    pass",
+            ],
+            check(
+                &policy,
+                r#"
+def helper():
+    pass
+for x in []:
+    cc_library(name = x)
+if True:
+    pass
+"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_library_policy() {
+        let policy = TopLevelPolicy::Library {
+            allowed_calls: vec!["provider_check".to_owned(), "native.register".to_owned()],
+        };
+        assert_eq!(
+            Vec::<String>::new(),
+            check(
+                &policy,
+                r#"
+"""Rules for C."""
+load("//defs.bzl", "provider_check")
+VERSION = "1.0"
+def cc_library(name):
+    print(name)
+provider_check(VERSION)
+native.register("x")
+INFO = provider_check(VERSION, [native.register("y")])
+F = lambda: print("x")
+"#
+            )
+        );
+        assert_eq!(
+            vec![
+                "top-level-call: print(\"loaded\")",
+                "top-level-call: native.other()",
+                "top-level-expression: VERSION + 1",
+                "top-level-statement: VERSION += \"x\"",
+                "top-level-statement: for x in []:\n    pass",
+                "top-level-call: print(\"x\")",
+                "top-level-call: native.other()",
+                "top-level-call: print(1)",
+            ],
+            check(
+                &policy,
+                r#"
+VERSION = "1.0"
+print("loaded")
+native.other()
+VERSION + 1
+VERSION += "x"
+for x in []:
+    pass
+X = print("x")
+Y = [1, native.other()]
+provider_check(print(1))
+"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_suppressed() {
+        assert_eq!(
+            Vec::<String>::new(),
+            check(
+                &TopLevelPolicy::Library {
+                    allowed_calls: Vec::new()
+                },
+                "print(1) # starlark-lint-disable top-level-call"
+            )
+        );
+    }
+}
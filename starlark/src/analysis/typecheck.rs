@@ -0,0 +1,173 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Check type annotations without evaluating the module.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Display;
+
+use dupe::Dupe;
+use starlark_syntax::syntax::module::AstModuleFields;
+
+use crate::codemap::FileSpan;
+use crate::codemap::Span;
+use crate::environment::Globals;
+use crate::syntax::AstModule;
+use crate::typing::oracle::ctx::incompatible_types;
+use crate::typing::typecheck::typecheck_module;
+
+/// A problem found by [`typecheck`].
+#[derive(Debug)]
+pub struct TypeError {
+    /// Which code location does this error refer to.
+    pub location: FileSpan,
+    /// Type required by an annotation, if the error is a type mismatch.
+    pub expected: Option<String>,
+    /// Type of the expression, if the error is a type mismatch.
+    pub actual: Option<String>,
+    /// A description of the underlying problem.
+    pub problem: String,
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.problem)
+    }
+}
+
+impl TypeError {
+    fn new(module: &AstModule, error: crate::Error) -> TypeError {
+        let location = match error.span() {
+            Some(span) => span.clone(),
+            None => FileSpan {
+                file: module.codemap().dupe(),
+                span: Span::default(),
+            },
+        };
+        let (expected, actual) = match incompatible_types(&error) {
+            Some((expected, actual)) => (Some(expected.to_owned()), Some(actual.to_owned())),
+            None => (None, None),
+        };
+        TypeError {
+            location,
+            expected,
+            actual,
+            problem: error.without_diagnostic().to_string(),
+        }
+    }
+}
+
+/// Check type annotations of a module without evaluating it.
+///
+/// Unlike [`AstModuleTypecheck`](crate::typing::AstModuleTypecheck), which only checks
+/// function bodies, top-level statements are checked too, so a literal passed to a parameter
+/// of the wrong type is reported wherever the call is. Types of loaded symbols are unknown
+/// and never produce errors. This is not a complete type system: the checks catch obvious
+/// mismatches early, and evaluation with types enabled still checks the rest.
+pub fn typecheck(module: &AstModule, globals: &Globals) -> Vec<TypeError> {
+    let (errors, _, _, _) = typecheck_module(module.clone(), globals, &HashMap::new(), true);
+    errors
+        .into_iter()
+        .map(|e| TypeError::new(module, e))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::typecheck;
+    use crate::environment::Globals;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+
+    fn check(program: &str) -> Vec<String> {
+        let module =
+            AstModule::parse("t.star", program.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+        typecheck(&module, &Globals::standard())
+            .into_iter()
+            .map(|e| {
+                format!(
+                    "{} {}: {:?} {:?}",
+                    e.location.resolve_span(),
+                    e.location.source_span(),
+                    e.expected,
+                    e.actual
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_typecheck_literal_args_and_returns() {
+        let program = r#"
+load("lib.star", "unknown")
+
+def f(x: int) -> str:
+    return 1
+
+def g(xs: list[str]) -> None:
+    f("y")
+
+f("x")
+g([1])
+f(unknown)
+y: int = "s"
+"#;
+        assert_eq!(
+            check(program),
+            vec![
+                r#"5:5-13 return 1: Some("str") Some("int")"#,
+                r#"8:7-10 "y": Some("int") Some("str")"#,
+                r#"10:3-6 "x": Some("int") Some("str")"#,
+                r#"11:3-6 [1]: Some("list[str]") Some("list[int]")"#,
+                r#"13:4-7 int: Some("int") Some("str")"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typecheck_ok() {
+        let program = r#"
+def f(x: int, *, y: str = "") -> list[str]:
+    return [y] * x
+
+z = f(1, y = "a")
+f(len(z))
+"#;
+        assert_eq!(check(program), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_typecheck_other_errors() {
+        let errors = typecheck(
+            &AstModule::parse(
+                "t.star",
+                "def f(x: int):\n    pass\nf(1, 2)\n".to_owned(),
+                &Dialect::AllOptionsInternal,
+            )
+            .unwrap(),
+            &Globals::standard(),
+        );
+        assert_eq!(1, errors.len());
+        assert_eq!(None, errors[0].expected);
+        assert!(
+            errors[0].to_string().contains("Too many positional"),
+            "{}",
+            errors[0]
+        );
+    }
+}
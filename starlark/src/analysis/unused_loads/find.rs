@@ -85,6 +85,7 @@ pub(crate) fn find_unused_loads(
         ScopeResolverGlobals::unknown(),
         codemap,
         &dialect,
+        false,
     )?;
 
     let mut loads = Vec::new();
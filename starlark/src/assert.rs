@@ -42,5 +42,8 @@
 
 mod assert;
 mod conformance;
+mod test_runner;
 
 pub use assert::*;
+pub use test_runner::TestResult;
+pub use test_runner::TestRunner;
@@ -36,7 +36,7 @@ use crate::environment::Globals;
 use crate::environment::GlobalsBuilder;
 use crate::environment::Module;
 use crate::eval::Evaluator;
-use crate::eval::ReturnFileLoader;
+use crate::eval::FileLoader;
 use crate::stdlib::PrintHandler;
 use crate::syntax::AstModule;
 use crate::syntax::Dialect;
@@ -55,6 +55,9 @@ fn mk_environment() -> GlobalsBuilder {
 
 static GLOBALS: Lazy<Globals> = Lazy::new(|| mk_environment().build());
 
+static TEST_FUNCTIONS: Lazy<Globals> =
+    Lazy::new(|| GlobalsBuilder::new().with(test_functions).build());
+
 static ASSERTS_STAR: Lazy<FrozenModule> = Lazy::new(|| {
     let g = GlobalsBuilder::new()
         .with_namespace("asserts", asserts_star)
@@ -70,7 +73,7 @@ static ASSERTS_STAR: Lazy<FrozenModule> = Lazy::new(|| {
     m.freeze().unwrap()
 });
 
-fn assert_equals<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType> {
+pub(crate) fn assert_equals<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType> {
     if !a.equals(b)? {
         Err(anyhow::anyhow!("assert_eq: expected {}, got {}", a, b).into())
     } else {
@@ -78,7 +81,7 @@ fn assert_equals<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType> {
     }
 }
 
-fn assert_different<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType> {
+pub(crate) fn assert_different<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType> {
     if a.equals(b)? {
         Err(anyhow::anyhow!("assert_ne: but {} == {}", a, b).into())
     } else {
@@ -86,7 +89,7 @@ fn assert_different<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType
     }
 }
 
-fn assert_less_than<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType> {
+pub(crate) fn assert_less_than<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType> {
     if a.compare(b)? != std::cmp::Ordering::Less {
         Err(anyhow::anyhow!("assert_lt: but {} >= {}", a, b).into())
     } else {
@@ -221,6 +224,27 @@ pub struct Assert<'a> {
     // but if you know how to do it, show me how.
     print_handler: Option<&'a (dyn PrintHandler + 'a)>,
     static_typechecking: bool,
+    setup_module: Box<dyn Fn(&Module)>,
+    loader: Option<&'a dyn FileLoader>,
+}
+
+/// Load the modules added to [`Assert`], falling back to its loader.
+struct AssertLoader<'a> {
+    modules: &'a HashMap<String, FrozenModule>,
+    fallback: Option<&'a dyn FileLoader>,
+}
+
+impl<'a> FileLoader for AssertLoader<'a> {
+    fn load(&self, path: &str) -> anyhow::Result<FrozenModule> {
+        match (self.modules.get(path), self.fallback) {
+            (Some(module), _) => Ok(module.dupe()),
+            (None, Some(fallback)) => fallback.load(path),
+            (None, None) => Err(anyhow::anyhow!(
+                "Assert does not know the module `{}`",
+                path
+            )),
+        }
+    }
 }
 
 /// Construction and state management.
@@ -239,9 +263,72 @@ impl<'a> Assert<'a> {
             setup_eval: Box::new(|_| ()),
             print_handler: None,
             static_typechecking: true,
+            setup_module: Box::new(|_| ()),
+            loader: None,
         }
     }
 
+    /// Create an assert object which will use the given [`Dialect`] and [`Globals`],
+    /// so that an embedding can test its own extensions with the same assertions
+    /// used by this crate.
+    ///
+    /// The assertion functions (`assert_eq`, `assert_true` and so on) are added
+    /// to `globals`. Panics if `globals` already defines one of them.
+    ///
+    /// ```
+    /// use starlark::assert::Assert;
+    /// use starlark::environment::GlobalsBuilder;
+    /// use starlark::starlark_module;
+    /// use starlark::syntax::Dialect;
+    ///
+    /// #[starlark_module]
+    /// fn my_api(builder: &mut GlobalsBuilder) {
+    ///     fn double(x: i32) -> anyhow::Result<i32> {
+    ///         Ok(x * 2)
+    ///     }
+    /// }
+    ///
+    /// let a = Assert::with_dialect_and_globals(
+    ///     &Dialect::Standard,
+    ///     GlobalsBuilder::standard().with(my_api).build(),
+    /// );
+    /// a.pass("assert_eq(double(21), 42)");
+    /// // Only the given globals are available.
+    /// a.fail("pprint(1)", "Variable `pprint` not found");
+    /// // And only the given dialect is accepted.
+    /// a.fail("x = f'{1}'", "must enable f-strings");
+    /// ```
+    pub fn with_dialect_and_globals(dialect: &Dialect, globals: Globals) -> Self {
+        Self::new().with_dialect(dialect).with_globals(globals)
+    }
+
+    /// Use the given [`Globals`] in future tests, plus the assertion functions
+    /// (`assert_eq`, `assert_true` and so on).
+    /// Panics if `globals` already defines one of them.
+    ///
+    /// This is the recommended way to test the functions of a
+    /// [`#[starlark_module]`](crate::starlark_module), see the example in the
+    /// [crate documentation](crate#test-rust-functions).
+    pub fn with_globals(mut self, globals: Globals) -> Self {
+        let globals = Globals::union(&[globals, Lazy::force(&TEST_FUNCTIONS).dupe()])
+            .expect("globals conflict with the assertion functions");
+        self.globals(globals);
+        self
+    }
+
+    /// Use the given [`Dialect`] in future tests.
+    pub fn with_dialect(mut self, dialect: &Dialect) -> Self {
+        self.dialect(dialect);
+        self
+    }
+
+    /// Resolve the `load` statements of future tests with `loader`,
+    /// for modules not added with [`module`](Assert::module) or [`module_add`](Assert::module_add).
+    pub fn with_loader(mut self, loader: &'a dyn FileLoader) -> Self {
+        self.loader = Some(loader);
+        self
+    }
+
     /// Disable garbage collection on the tests.
     pub fn disable_gc(&mut self) {
         self.gc_strategy = Some(GcStrategy::Never)
@@ -252,6 +339,19 @@ impl<'a> Assert<'a> {
         self.setup_eval = Box::new(setup);
     }
 
+    /// Configure a callback which is used to set up the module before each evaluation,
+    /// e.g. to define variables the tests can use.
+    ///
+    /// ```
+    /// # use starlark::assert::Assert;
+    /// let mut a = Assert::new();
+    /// a.setup_module(|module| module.set("answer", module.heap().alloc(42)));
+    /// a.eq("answer", "42");
+    /// ```
+    pub fn setup_module(&mut self, setup: impl Fn(&Module) + 'static) {
+        self.setup_module = Box::new(setup);
+    }
+
     /// Configure the handler for `print` function.
     pub fn set_print_handler(&mut self, handler: &'a (dyn PrintHandler + 'a)) {
         self.print_handler = Some(handler);
@@ -283,11 +383,11 @@ impl<'a> Assert<'a> {
         module: &'v Module,
         gc: GcStrategy,
     ) -> crate::Result<Value<'v>> {
-        let mut modules = HashMap::with_capacity(self.modules.len());
-        for (k, v) in &self.modules {
-            modules.insert(k.as_str(), v);
-        }
-        let loader = ReturnFileLoader { modules: &modules };
+        let loader = AssertLoader {
+            modules: &self.modules,
+            fallback: self.loader,
+        };
+        (self.setup_module)(module);
         let ast = AstModule::parse(path, program.to_owned(), &self.dialect)?;
         let gc_always = |_span: FileSpanRef, eval: &mut Evaluator| {
             eval.trigger_gc();
@@ -324,6 +424,13 @@ impl<'a> Assert<'a> {
         }
     }
 
+    /// Run `program` once, with the default GC, returning its error rather than panicking.
+    pub(crate) fn execute_result(&self, program: &str) -> crate::Result<()> {
+        let module = Module::new();
+        self.execute("assert.bzl", program, &module, GcStrategy::Auto)
+            .map(|_| ())
+    }
+
     fn execute_unwrap<'v>(
         &self,
         func: &str,
@@ -417,6 +524,8 @@ impl<'a> Assert<'a> {
     }
 
     /// Set the [`Globals`] that future tests have access to.
+    /// These replace the defaults, including the assertion functions;
+    /// use [`with_dialect_and_globals`](Assert::with_dialect_and_globals) to keep them.
     pub fn globals(&mut self, x: Globals) {
         self.globals = x;
     }
@@ -561,6 +670,94 @@ impl<'a> Assert<'a> {
         })
     }
 
+    /// A program whose result must report its attributes consistently: `dir`
+    /// lists each name once and in order, every listed name is found by
+    /// `hasattr` and `getattr`, and an unlisted name is found by neither.
+    ///
+    /// ```
+    /// # use starlark::assert::Assert;
+    /// Assert::new().attrs_consistent("struct(x = 1)");
+    /// ```
+    pub fn attrs_consistent(&self, program: &str) {
+        self.with_gc(|gc| {
+            let env = Module::new();
+            let v = self.execute_unwrap("attrs_consistent", "assert.bzl", program, &env, gc);
+            let heap = env.heap();
+            let names = v.dir_attr();
+            if !names.windows(2).all(|w| w[0] < w[1]) {
+                panic!(
+                    "starlark::assert::attrs_consistent, dir() is not sorted or has duplicates!\nCode:\n{}\nNames:\n{:?}",
+                    program, names
+                );
+            }
+            for name in &names {
+                if !v.has_attr(name, heap) {
+                    panic!(
+                        "starlark::assert::attrs_consistent, `{}` is in dir() but not hasattr()!\nCode:\n{}",
+                        name, program
+                    );
+                }
+                if !matches!(v.get_attr(name, heap), Ok(Some(_))) {
+                    panic!(
+                        "starlark::assert::attrs_consistent, `{}` is in dir() but not getattr()!\nCode:\n{}",
+                        name, program
+                    );
+                }
+            }
+            let missing = "__attrs_consistent_missing__";
+            if v.has_attr(missing, heap) || !matches!(v.get_attr(missing, heap), Ok(None)) {
+                panic!(
+                    "starlark::assert::attrs_consistent, `{}` is not in dir() but is an attribute!\nCode:\n{}",
+                    missing, program
+                );
+            }
+        })
+    }
+
+    /// A program that must produce identical output on every run. The program
+    /// is evaluated under every GC strategy, each time with a different amount
+    /// of unrelated data allocated on the heap first, and the `repr` of the
+    /// result and of every module variable (in definition order) must match
+    /// between runs. Use it to check that nothing observable depends on hash
+    /// seeds or allocation addresses.
+    ///
+    /// ```
+    /// # use starlark::assert::Assert;
+    /// Assert::new().deterministic(
+    ///     r#"
+    /// d = {"b": 1, "a": 2}
+    /// names = dir(d)
+    /// str(d)
+    /// "#,
+    /// );
+    /// ```
+    pub fn deterministic(&self, program: &str) {
+        let mut outputs = Vec::new();
+        for gc in [GcStrategy::Auto, GcStrategy::Never, GcStrategy::Always] {
+            for padding in [0, 1000] {
+                let env = Module::new();
+                for i in 0..padding {
+                    env.heap().alloc_str(&format!("padding{}", i));
+                }
+                let v = self.execute_unwrap("deterministic", "assert.bzl", program, &env, gc);
+                let mut out = format!("{}\n", v.to_repr());
+                for name in env.names() {
+                    let value = env.get(name.as_str()).unwrap();
+                    out.push_str(&format!("{} = {}\n", name.as_str(), value.to_repr()));
+                }
+                outputs.push(out);
+            }
+        }
+        for out in &outputs[1..] {
+            if out != &outputs[0] {
+                panic!(
+                    "starlark::assert::deterministic, output differs between runs!\nCode:\n{}\nOutput 1:\n{}\nOutput 2:\n{}",
+                    program, outputs[0], out
+                );
+            }
+        }
+    }
+
     /// Two programs that must evaluate to the same (non-error) result.
     ///
     /// ```
@@ -583,11 +780,76 @@ impl<'a> Assert<'a> {
     }
 }
 
+/// Parsing tests.
+impl<'a> Assert<'a> {
+    /// A program that must fail to parse with the current [`Dialect`], with an error
+    /// message containing `msg`. Nothing is evaluated. The program is parsed both
+    /// with Unix and Windows newlines. Returns the error from the Unix variant.
+    ///
+    /// ```
+    /// # use starlark::assert::Assert;
+    /// # use starlark::syntax::Dialect;
+    /// let mut a = Assert::new();
+    /// a.dialect(&Dialect {
+    ///     enable_lambda: false,
+    ///     ..Dialect::Standard
+    /// });
+    /// a.parse_fail("f = lambda x: x", "`lambda` is not allowed");
+    /// ```
+    pub fn parse_fail(&self, program: &str, msg: &str) -> crate::Error {
+        let check = |program: &str| {
+            let res = AstModule::parse("assert.bzl", program.to_owned(), &self.dialect);
+            let Err(e) = res else {
+                panic!(
+                    "starlark::assert::parse_fail, didn't fail!\nCode:\n{}",
+                    program
+                );
+            };
+            let err_msg = format!("{:#}", e.without_diagnostic());
+            if !err_msg.contains(msg) {
+                panic!(
+                    "starlark::assert::parse_fail, failed with the wrong message!\nCode:\n{}\nError:\n{:#}\nMissing:\n{}",
+                    program, e, msg
+                );
+            }
+            e
+        };
+        let err = check(program);
+        check(&program.replace('\n', "\r\n"));
+        err
+    }
+}
+
+/// See [`Assert::parse_fail`], parsing with `dialect`.
+pub fn parse_fail(program: &str, dialect: &Dialect, msg: &str) -> crate::Error {
+    Assert::new().with_dialect(dialect).parse_fail(program, msg)
+}
+
+/// See [`Assert::deterministic`].
+pub fn deterministic(program: &str) {
+    Assert::new().deterministic(program)
+}
+
 /// See [`Assert::eq`].
 pub fn eq(lhs: &str, rhs: &str) {
     Assert::new().eq(lhs, rhs)
 }
 
+/// See [`Assert::attrs_consistent`].
+pub fn attrs_consistent(program: &str) {
+    Assert::new().attrs_consistent(program)
+}
+
+/// See [`Assert::conformance`].
+pub fn conformance(code: &str) {
+    Assert::new().conformance(code)
+}
+
+/// See [`Assert::conformance_strict`].
+pub fn conformance_strict(code: &str) {
+    Assert::new().conformance_strict(code)
+}
+
 /// See [`Assert::fail`].
 pub fn fail(program: &str, msg: &str) -> crate::Error {
     Assert::new().fail(program, msg)
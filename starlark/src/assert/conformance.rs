@@ -28,11 +28,37 @@ use itertools::Itertools;
 use crate::assert::assert::Assert;
 
 impl<'a> Assert<'a> {
-    /// Run a conformance test, e.g. the Go Starlark tests
+    /// Run a conformance test, e.g. the Go Starlark tests.
+    ///
+    /// The test is a sequence of chunks separated by lines consisting of `---`.
+    /// A chunk with a line containing `###` must fail on that line, any other chunk must pass.
+    /// The text after `###` is the expected error, which is not checked,
+    /// since the messages of other implementations differ from ours.
     pub fn conformance(&self, code: &str) {
         self.conformance_except(code, &[])
     }
 
+    /// Like [`conformance`](Assert::conformance), but also check the error
+    /// contains the text after `###`, with surrounding quotes removed.
+    /// Use this for conformance files written against this implementation.
+    ///
+    /// ```
+    /// # use starlark::assert::Assert;
+    /// Assert::new().conformance_strict(
+    ///     r#"
+    /// assert_eq(1 + 2, 3)
+    /// ---
+    /// x = [1]
+    /// x[1] ### "out of bound"
+    /// "#,
+    /// );
+    /// ```
+    pub fn conformance_strict(&self, code: &str) {
+        for x in ConformanceTest::parse(code) {
+            x.test(self, true)
+        }
+    }
+
     /// Run a conformance test, but where some test cases are allowed to fail.
     /// The `except` argument represents a list of these permissible failures,
     /// in the order they occur in the conformance test set,
@@ -48,13 +74,60 @@ impl<'a> Assert<'a> {
                 }
             }
 
-            x.test(self)
+            x.test(self, false)
         }
 
         if let Some(missed) = except.next() {
             panic!("Exception given but not used, `{}`", missed);
         }
     }
+
+    /// Like [`conformance`](Assert::conformance), but the test cases identified by
+    /// `expected_failures` must fail, either passing when an error is expected or
+    /// failing elsewhere. Use it to record where this implementation knowingly
+    /// diverges from a test suite written for another one.
+    /// As for [`conformance_except`](Assert::conformance_except), each entry is a substring
+    /// of a test case, in the order they occur. A test case which starts to pass
+    /// must be removed from the list.
+    ///
+    /// ```
+    /// # use starlark::assert::Assert;
+    /// Assert::new().conformance_expect_failures(
+    ///     r#"
+    /// assert_eq(1 + 2, 3)
+    /// ---
+    /// # Written for an implementation which rejects `{} | {}`.
+    /// {} | {} ### "unknown binary op"
+    /// "#,
+    ///     &["{} | {}"],
+    /// );
+    /// ```
+    pub fn conformance_expect_failures(&self, code: &str, expected_failures: &[&str]) {
+        let mut expected_failures = expected_failures.iter().peekable();
+
+        for x in ConformanceTest::parse(code) {
+            match expected_failures.peek() {
+                Some(e) if x.code.contains(**e) => {
+                    if x.passes(self) {
+                        panic!(
+                            "starlark::assert::conformance, expected failure passed, remove `{}`!\nCode:\n{}\n",
+                            e, &x.code
+                        );
+                    }
+                    expected_failures.next();
+                }
+                _ => x.test(self, false),
+            }
+        }
+
+        if let Some(missed) = expected_failures.next() {
+            panic!("Expected failure given but not used, `{}`", missed);
+        }
+    }
+}
+
+fn get_line(err: &crate::Error) -> Option<usize> {
+    err.span().map(|span| span.resolve_span().begin.line + 1)
 }
 
 /// Describe a conformance test
@@ -86,19 +159,33 @@ impl ConformanceTest {
             .collect()
     }
 
-    fn test(&self, assert: &Assert) {
-        fn get_line(err: &crate::Error) -> Option<usize> {
-            err.span().map(|span| span.resolve_span().begin.line + 1)
+    /// Like [`test`](ConformanceTest::test) without checking the message,
+    /// but run once and report the outcome rather than panicking.
+    fn passes(&self, assert: &Assert) -> bool {
+        match (&self.error, assert.execute_result(&self.code)) {
+            (None, res) => res.is_ok(),
+            (Some(_), Ok(())) => false,
+            (Some((line, _)), Err(err)) => get_line(&err) == Some(*line),
         }
+    }
 
+    fn test(&self, assert: &Assert, check_message: bool) {
         match &self.error {
             None => {
                 assert.pass(&self.code);
             }
-            Some((line, _msg)) => {
-                // We don't actually check error messages, since these tests were taken from upstream
+            Some((line, msg)) => {
+                // Unless asked, we don't check error messages, since most tests were taken from upstream
                 // and our error messages are different
-                let err = assert.fail(&self.code, "");
+                let msg = if check_message {
+                    let msg = msg.trim();
+                    msg.strip_prefix('"')
+                        .and_then(|m| m.strip_suffix('"'))
+                        .unwrap_or(msg)
+                } else {
+                    ""
+                };
+                let err = assert.fail(&self.code, msg);
                 let got = get_line(&err);
                 if got != Some(*line) {
                     panic!(
@@ -0,0 +1,394 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Discover and run the `test_*` functions defined in a Starlark file.
+
+use dupe::Dupe;
+use once_cell::sync::Lazy;
+use starlark_derive::starlark_module;
+
+use crate as starlark;
+use crate::assert::assert::assert_different;
+use crate::assert::assert::assert_equals;
+use crate::assert::assert::assert_less_than;
+use crate::codemap::FileSpan;
+use crate::environment::FrozenModule;
+use crate::environment::Globals;
+use crate::environment::GlobalsBuilder;
+use crate::environment::Module;
+use crate::eval::Evaluator;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+use crate::values::none::NoneType;
+use crate::values::Value;
+
+static TEST_ASSERTS: Lazy<Globals> = Lazy::new(|| {
+    GlobalsBuilder::new()
+        .with_namespace("asserts", test_asserts)
+        .build()
+});
+
+/// The `asserts` namespace available to the tests run by [`TestRunner`].
+#[starlark_module]
+fn test_asserts(builder: &mut GlobalsBuilder) {
+    /// Fail unless `a == b`.
+    fn eq<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType> {
+        assert_equals(a, b)
+    }
+
+    /// Fail if `a == b`.
+    fn ne<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType> {
+        assert_different(a, b)
+    }
+
+    /// Fail unless `a < b`.
+    fn lt<'v>(a: Value<'v>, b: Value<'v>) -> starlark::Result<NoneType> {
+        assert_less_than(a, b)
+    }
+
+    /// Fail unless `x` is truthy.
+    fn r#true(x: Value) -> anyhow::Result<NoneType> {
+        if !x.to_bool() {
+            return Err(anyhow::anyhow!("asserts.true: got {}", x));
+        }
+        Ok(NoneType)
+    }
+
+    /// Fail unless `x` is falsy.
+    fn r#false(x: Value) -> anyhow::Result<NoneType> {
+        if x.to_bool() {
+            return Err(anyhow::anyhow!("asserts.false: got {}", x));
+        }
+        Ok(NoneType)
+    }
+
+    /// Fail unless calling `f` with no arguments fails
+    /// with an error message containing `msg`.
+    fn fails<'v>(
+        f: Value<'v>,
+        msg: &str,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> anyhow::Result<NoneType> {
+        match f.invoke_pos(&[], eval) {
+            Ok(_) => Err(anyhow::anyhow!("asserts.fails: didn't fail")),
+            Err(e) => {
+                let got = e.without_diagnostic().to_string();
+                if got.contains(msg) {
+                    Ok(NoneType)
+                } else {
+                    Err(anyhow::anyhow!(
+                        "asserts.fails: expected error containing `{}`, got `{}`",
+                        msg,
+                        got
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Fail unless `x in xs`.
+    fn contains<'v>(xs: Value<'v>, x: Value<'v>) -> starlark::Result<NoneType> {
+        if !xs.is_in(x)? {
+            return Err(anyhow::anyhow!("asserts.contains: expected {} to be in {}", x, xs).into());
+        }
+        Ok(NoneType)
+    }
+
+    /// Fail if `x in xs`.
+    fn not_contains<'v>(xs: Value<'v>, x: Value<'v>) -> starlark::Result<NoneType> {
+        if xs.is_in(x)? {
+            return Err(anyhow::anyhow!(
+                "asserts.not_contains: expected {} not to be in {}",
+                x,
+                xs
+            )
+            .into());
+        }
+        Ok(NoneType)
+    }
+
+    /// Fail unless `xs` has length `n`.
+    fn len<'v>(xs: Value<'v>, n: i32) -> starlark::Result<NoneType> {
+        let len = xs.length()?;
+        if len != n {
+            return Err(anyhow::anyhow!(
+                "asserts.len: expected length {}, got {} for {}",
+                n,
+                len,
+                xs
+            )
+            .into());
+        }
+        Ok(NoneType)
+    }
+
+    /// Fail unless `xs` is empty.
+    fn empty<'v>(xs: Value<'v>) -> starlark::Result<NoneType> {
+        if xs.length()? != 0 {
+            return Err(anyhow::anyhow!("asserts.empty: expected empty, got {}", xs).into());
+        }
+        Ok(NoneType)
+    }
+}
+
+/// The outcome of a single test function run by [`TestRunner`].
+#[derive(Debug)]
+pub struct TestResult {
+    /// Name of the test function.
+    pub name: String,
+    /// The error the test failed with, or `None` if it passed.
+    pub error: Option<crate::Error>,
+}
+
+impl TestResult {
+    /// Did the test pass.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Location of the failure, usually the failing assertion.
+    pub fn span(&self) -> Option<&FileSpan> {
+        self.error.as_ref()?.span()
+    }
+}
+
+/// Run the test functions of a Starlark file.
+///
+/// The file is evaluated and frozen, then every top-level function whose name starts
+/// with `test_` is called with no arguments, each in a fresh module, in the order
+/// they are defined. An `asserts` namespace with `eq`, `ne`, `lt`, `true`, `false`,
+/// `fails`, `contains`, `not_contains`, `len` and `empty` is available to the tests.
+///
+/// ```
+/// use starlark::assert::TestRunner;
+/// use starlark::syntax::AstModule;
+/// use starlark::syntax::Dialect;
+///
+/// let ast = AstModule::parse(
+///     "my_test.star",
+///     r#"
+/// def test_add():
+///     asserts.eq(1 + 2, 3)
+///
+/// def test_index():
+///     asserts.fails(lambda: [][0], "out of bound")
+///
+/// def test_broken():
+///     asserts.eq(1, 2)
+/// "#
+///     .to_owned(),
+///     &Dialect::Extended,
+/// )
+/// .unwrap();
+/// let results = TestRunner::new().run(ast).unwrap();
+/// let outcomes: Vec<_> = results.iter().map(|r| (r.name.as_str(), r.passed())).collect();
+/// assert_eq!(
+///     outcomes,
+///     [("test_add", true), ("test_index", true), ("test_broken", false)]
+/// );
+/// ```
+pub struct TestRunner {
+    dialect: Dialect,
+    globals: Globals,
+    prelude: Vec<FrozenModule>,
+}
+
+impl TestRunner {
+    /// Create a runner using the extended dialect and all library extensions.
+    pub fn new() -> Self {
+        Self::with_dialect_and_globals(&Dialect::Extended, Globals::extended_internal())
+            .expect("extended globals do not define `asserts`")
+    }
+
+    /// Create a runner using the given [`Dialect`] and [`Globals`].
+    /// The `asserts` namespace is added to `globals`, which is an error if they already define it.
+    pub fn with_dialect_and_globals(dialect: &Dialect, globals: Globals) -> anyhow::Result<Self> {
+        let globals = Globals::union(&[globals, Lazy::force(&TEST_ASSERTS).dupe()])?;
+        Ok(Self {
+            dialect: dialect.clone(),
+            globals,
+            prelude: Vec::new(),
+        })
+    }
+
+    /// The dialect test files are parsed with.
+    pub fn dialect(&self) -> &Dialect {
+        &self.dialect
+    }
+
+    /// Make the public symbols of `module` available to the test files.
+    pub fn prelude(&mut self, module: FrozenModule) {
+        self.prelude.push(module);
+    }
+
+    fn new_module(&self) -> Module {
+        let module = Module::new();
+        for p in &self.prelude {
+            module.import_public_symbols(p);
+        }
+        module
+    }
+
+    /// Evaluate the test file and run its tests.
+    /// Returns an error if the file itself fails to evaluate.
+    pub fn run(&self, ast: AstModule) -> crate::Result<Vec<TestResult>> {
+        let module = self.new_module();
+        Evaluator::new(&module).eval_module(ast, &self.globals)?;
+        let module = module.freeze()?;
+
+        let mut results = Vec::new();
+        for name in module.names() {
+            let name = name.as_str();
+            if !name.starts_with("test_") {
+                continue;
+            }
+            let test = module.get(name).map_err(crate::Error::new_other)?;
+            if test.value().get_type() != "function" {
+                continue;
+            }
+            let env = self.new_module();
+            let mut eval = Evaluator::new(&env);
+            let error = eval.eval_function(test.value(), &[], &[]).err();
+            results.push(TestResult {
+                name: name.to_owned(),
+                error,
+            });
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert::TestRunner;
+    use crate::environment::GlobalsBuilder;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+
+    fn run(program: &str) -> Vec<(String, Option<String>)> {
+        let ast = AstModule::parse("test.star", program.to_owned(), &Dialect::Extended).unwrap();
+        TestRunner::new()
+            .run(ast)
+            .unwrap()
+            .into_iter()
+            .map(|r| {
+                let error = r.error.map(|e| e.without_diagnostic().to_string());
+                (r.name, error)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_runner_discovers_tests() {
+        let results = run(r#"
+def helper():
+    fail("not a test")
+
+test_value = 1
+
+def test_b():
+    asserts.len([1, 2], 2)
+
+def test_a():
+    asserts.contains({"x": 1}, "x")
+    asserts.empty([])
+"#);
+        assert_eq!(
+            results,
+            [("test_b".to_owned(), None), ("test_a".to_owned(), None)]
+        );
+    }
+
+    #[test]
+    fn test_runner_reports_failures() {
+        let results = run(r#"
+def test_eq():
+    asserts.eq([1], [2])
+
+def test_fails():
+    asserts.fails(lambda: fail("boom"), "bang")
+
+def test_pass():
+    asserts.fails(lambda: fail("boom"), "boom")
+"#);
+        assert_eq!(
+            results,
+            [
+                (
+                    "test_eq".to_owned(),
+                    Some("assert_eq: expected [1], got [2]".to_owned())
+                ),
+                (
+                    "test_fails".to_owned(),
+                    Some(
+                        "asserts.fails: expected error containing `bang`, got `fail: boom`"
+                            .to_owned()
+                    )
+                ),
+                ("test_pass".to_owned(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_runner_failure_span() {
+        let ast = AstModule::parse(
+            "test.star",
+            "def test_x():\n    x = 1\n    asserts.true(x == 2)\n".to_owned(),
+            &Dialect::Extended,
+        )
+        .unwrap();
+        let results = TestRunner::new().run(ast).unwrap();
+        let span = results[0].span().unwrap().resolve_span();
+        assert_eq!(span.begin.line, 2);
+    }
+
+    #[test]
+    fn test_runner_isolates_tests() {
+        let results = run(r#"
+xs = []
+
+def test_mutate():
+    xs.append(1)
+
+def test_read():
+    asserts.empty(xs)
+"#);
+        assert_eq!(
+            results[0].1.as_deref(),
+            Some("Cannot mutate frozen value of type `list`")
+        );
+        assert_eq!(results[1], ("test_read".to_owned(), None));
+    }
+
+    #[test]
+    fn test_runner_module_error() {
+        let ast =
+            AstModule::parse("test.star", "fail('bad')".to_owned(), &Dialect::Extended).unwrap();
+        assert!(TestRunner::new().run(ast).is_err());
+    }
+
+    #[test]
+    fn test_runner_asserts_defined() {
+        let mut globals = GlobalsBuilder::standard();
+        globals.set("asserts", 1);
+        let err = TestRunner::with_dialect_and_globals(&Dialect::Extended, globals.build())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("`asserts`"), "{err}");
+    }
+}
@@ -18,7 +18,12 @@
 //! Provides debug-related functionality and utilities.
 mod adapter;
 
+mod breakpoint;
 mod evaluate;
 mod inspect;
 
 pub use adapter::*;
+pub use breakpoint::BreakpointAction;
+pub use breakpoint::BreakpointContext;
+pub use breakpoint::DebugAction;
+pub(crate) use breakpoint::StmtBreakpoints;
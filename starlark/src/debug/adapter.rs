@@ -389,6 +389,18 @@ pub trait DapAdapter: Debug + Send + 'static {
     /// See <https://microsoft.github.io/debug-adapter-protocol/specification#Requests_Continue>
     fn continue_(&self) -> anyhow::Result<()>;
 
+    /// Pauses execution before the next statement is evaluated.
+    ///
+    /// The client is notified with [`DapAdapterClient::event_stopped`] once paused.
+    /// The default implementation reports that pausing is not supported.
+    ///
+    /// See <https://microsoft.github.io/debug-adapter-protocol/specification#Requests_Pause>
+    fn pause(&self) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!(
+            "Pause is not supported by this debug adapter"
+        ))
+    }
+
     /// Continues execution until some condition.
     ///
     /// See <https://microsoft.github.io/debug-adapter-protocol/specification#Requests_Next>
@@ -17,6 +17,7 @@
 
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::sync::mpsc::channel;
@@ -60,6 +61,7 @@ pub(crate) fn prepare_dap_adapter(
         client,
         breakpoints: Arc::new(Mutex::new(BreakpointConfig::new())),
         disable_breakpoints: Arc::new(0usize.into()),
+        pause_requested: AtomicBool::new(false),
     });
 
     (
@@ -115,6 +117,8 @@ impl<'a, 'e: 'a> BeforeStmtFuncDyn<'a, 'e> for DapAdapterEvalHookImpl {
     ) -> crate::Result<()> {
         let stop = if self.state.disable_breakpoints.load(Ordering::SeqCst) > 0 {
             false
+        } else if self.state.pause_requested.swap(false, Ordering::SeqCst) {
+            true
         } else {
             let breaks = self.state.breakpoints.lock().unwrap();
             let breakpoint = breaks.at(span_loc);
@@ -239,6 +243,8 @@ struct SharedAdapterState {
     breakpoints: Arc<Mutex<BreakpointConfig>>,
     // Set while we are doing evaluate calls (>= 1 means disable)
     disable_breakpoints: Arc<AtomicUsize>,
+    // Set by `pause`, cleared when the evaluation stops before the next statement.
+    pause_requested: AtomicBool,
 }
 
 #[derive(Debug, Clone, Copy, Dupe)]
@@ -359,6 +365,11 @@ impl DapAdapter for DapAdapterImpl {
         }))
     }
 
+    fn pause(&self) -> anyhow::Result<()> {
+        self.state.pause_requested.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
     fn continue_(&self) -> anyhow::Result<()> {
         self.inject_next(Next::Continue);
         Ok(())
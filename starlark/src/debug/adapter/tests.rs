@@ -310,6 +310,48 @@ print(x)
         })
     }
 
+    #[test]
+    fn test_pause() -> crate::Result<()> {
+        if is_wasm() {
+            return Ok(());
+        }
+
+        let file_contents = "
+def adjust(y):
+    y[0] += 1
+    y[1] += 1
+x = [1, 2]
+adjust(x) # line 6
+print(x)
+        ";
+        dap_test_template(|s, controller, adapter, eval_hook| {
+            let ast = AstModule::parse(
+                "test.bzl",
+                file_contents.to_owned(),
+                &Dialect::AllOptionsInternal,
+            )?;
+            let breakpoints =
+                resolve_breakpoints(&breakpoints_args("test.bzl", &[(6, None)]), &ast)?;
+            adapter.set_breakpoints("test.bzl", &breakpoints)?;
+            let eval_result =
+                s.spawn(move || -> crate::Result<_> { eval_with_hook(ast, eval_hook) });
+            controller.wait_for_eval_stopped(1, TIMEOUT);
+            // TODO(cjhopman): we currently hit breakpoints on top-level statements twice (once for the gc bytecode, once for the actual statement).
+            adapter.continue_()?;
+            controller.wait_for_eval_stopped(2, TIMEOUT);
+            // Requested while stopped, so the pause takes effect at the first
+            // statement evaluated after resuming, the first one of `adjust`.
+            adapter.pause()?;
+            adapter.continue_()?;
+            controller.wait_for_eval_stopped(3, TIMEOUT);
+            assert_eq!("1", adapter.evaluate("y[0]")?.result);
+            assert_eq!("2", adapter.evaluate("y[1]")?.result);
+            adapter.continue_()?;
+            join_timeout(eval_result, TIMEOUT)?;
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_step_over() -> crate::Result<()> {
         if is_wasm() {
@@ -0,0 +1,584 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Breakpoints and stepping on an [`Evaluator`] from Rust, without a debug adapter.
+
+use dupe::Dupe;
+use starlark_syntax::syntax::ast::StmtP;
+use starlark_syntax::syntax::module::AstModuleFields;
+
+use crate::codemap::FileSpan;
+use crate::codemap::FileSpanRef;
+use crate::collections::SmallMap;
+use crate::eval::runtime::before_stmt::BeforeStmtFuncDyn;
+use crate::eval::CallStack;
+use crate::eval::Evaluator;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+use crate::values::Value;
+
+#[derive(Debug, thiserror::Error)]
+enum BreakpointError {
+    #[error("Evaluation aborted at breakpoint at {0}")]
+    Aborted(FileSpan),
+    #[error("Breakpoint condition must be an expression")]
+    ConditionNotExpression,
+}
+
+/// What the evaluation should do after a breakpoint handler returns.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum BreakpointAction {
+    /// Resume the evaluation.
+    Continue,
+    /// Stop the evaluation with an error.
+    Abort,
+}
+
+/// What the evaluation should do after a debug callback returns,
+/// see [`Evaluator::set_debug_callback`].
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum DebugAction {
+    /// Resume the evaluation until the next breakpoint.
+    Continue,
+    /// Stop at the next statement.
+    StepIn,
+    /// Stop at the next statement of the current function or its callers.
+    StepOver,
+    /// Stop at the next statement of the callers of the current function.
+    StepOut,
+    /// Stop the evaluation with an error.
+    Abort,
+}
+
+impl From<BreakpointAction> for DebugAction {
+    fn from(action: BreakpointAction) -> Self {
+        match action {
+            BreakpointAction::Continue => DebugAction::Continue,
+            BreakpointAction::Abort => DebugAction::Abort,
+        }
+    }
+}
+
+/// State of the evaluation paused at a breakpoint, passed to the handler
+/// registered with [`Evaluator::set_breakpoint_handler`]
+/// or [`Evaluator::set_debug_callback`].
+pub struct BreakpointContext<'x, 'v, 'a, 'e: 'a> {
+    eval: &'x mut Evaluator<'v, 'a, 'e>,
+    location: FileSpan,
+}
+
+impl<'x, 'v, 'a, 'e: 'a> BreakpointContext<'x, 'v, 'a, 'e> {
+    /// The statement which is about to be executed.
+    pub fn location(&self) -> &FileSpan {
+        &self.location
+    }
+
+    /// Value of a variable in scope, as reported by [`Evaluator::local_variables`].
+    pub fn local(&self, name: &str) -> Option<Value<'v>> {
+        self.eval.local_variables().get(name).copied()
+    }
+
+    /// All the variables in scope, see [`Evaluator::local_variables`].
+    pub fn local_variables(&self) -> SmallMap<String, Value<'v>> {
+        self.eval.local_variables()
+    }
+
+    /// The call stack of the paused evaluation, innermost frame last.
+    pub fn call_stack(&self) -> CallStack {
+        self.eval.call_stack()
+    }
+
+    /// Evaluate Starlark code in the paused frame, see [`Evaluator::eval_statements`].
+    pub fn evaluate(&mut self, code: &str) -> crate::Result<Value<'v>> {
+        let ast = AstModule::parse("breakpoint", code.to_owned(), &Dialect::AllOptionsInternal)?;
+        self.eval.eval_statements(ast)
+    }
+}
+
+struct Breakpoint {
+    filename: String,
+    /// 1-based.
+    line: usize,
+    condition: Option<AstModule>,
+    /// Set after the condition failed to evaluate.
+    disabled: bool,
+}
+
+type BreakpointHandler<'a, 'e> =
+    Box<dyn for<'v> FnMut(&mut BreakpointContext<'_, 'v, 'a, 'e>) -> DebugAction + 'a>;
+
+type ConditionErrorHandler<'a> = Box<dyn FnMut(&FileSpan, crate::Error) + 'a>;
+
+/// Where to stop next, other than at breakpoints.
+#[derive(Clone, Copy, Dupe)]
+enum Step {
+    /// Only at breakpoints.
+    None,
+    /// At any statement.
+    In,
+    /// At a statement with a call stack at most this deep.
+    Depth(usize),
+}
+
+/// Breakpoints and the handler, stored in the [`Evaluator`].
+pub(crate) struct StmtBreakpoints<'a, 'e: 'a> {
+    breakpoints: Vec<Breakpoint>,
+    handler: Option<BreakpointHandler<'a, 'e>>,
+    /// Called when a condition fails.
+    condition_error_handler: Option<ConditionErrorHandler<'a>>,
+    /// Failed conditions, when there is no `condition_error_handler`.
+    condition_errors: Vec<(FileSpan, crate::Error)>,
+    step: Step,
+}
+
+impl<'a, 'e: 'a> StmtBreakpoints<'a, 'e> {
+    fn before_stmt<'v>(
+        &mut self,
+        span: FileSpanRef,
+        eval: &mut Evaluator<'v, 'a, 'e>,
+    ) -> crate::Result<()> {
+        let mut line = None;
+        let mut hit = false;
+        for breakpoint in &mut self.breakpoints {
+            if breakpoint.disabled || breakpoint.filename != span.filename() {
+                continue;
+            }
+            let line = *line.get_or_insert_with(|| span.file.find_line(span.span.begin()) + 1);
+            if breakpoint.line != line {
+                continue;
+            }
+            match &breakpoint.condition {
+                None => hit = true,
+                Some(condition) => match eval.eval_statements(condition.clone()) {
+                    Ok(v) => hit |= v.to_bool(),
+                    Err(e) => {
+                        // Report once, then never evaluate this condition again.
+                        // The evaluation itself is not affected.
+                        breakpoint.disabled = true;
+                        let location = span.to_file_span();
+                        match &mut self.condition_error_handler {
+                            Some(handler) => handler(&location, e),
+                            None => self.condition_errors.push((location, e)),
+                        }
+                    }
+                },
+            }
+        }
+
+        let depth = eval.call_stack_count();
+        hit |= match self.step {
+            Step::None => false,
+            Step::In => true,
+            Step::Depth(max) => depth <= max,
+        };
+
+        if hit {
+            if let Some(handler) = &mut self.handler {
+                let location = span.to_file_span();
+                let mut context = BreakpointContext {
+                    eval,
+                    location: location.dupe(),
+                };
+                self.step = match handler(&mut context) {
+                    DebugAction::Continue => Step::None,
+                    DebugAction::StepIn => Step::In,
+                    DebugAction::StepOver => Step::Depth(depth),
+                    DebugAction::StepOut => Step::Depth(depth.saturating_sub(1)),
+                    DebugAction::Abort => {
+                        return Err(crate::Error::new_other(BreakpointError::Aborted(location)));
+                    }
+                };
+            }
+        }
+        Ok(())
+    }
+}
+
+struct StmtBreakpointsHook;
+
+impl<'a, 'e: 'a> BeforeStmtFuncDyn<'a, 'e> for StmtBreakpointsHook {
+    fn call<'v>(
+        &mut self,
+        span: FileSpanRef,
+        eval: &mut Evaluator<'v, 'a, 'e>,
+    ) -> crate::Result<()> {
+        // Taken out for the duration of the call, so code evaluated
+        // by conditions and handlers does not hit breakpoints.
+        let Some(mut breakpoints) = eval.stmt_breakpoints.take() else {
+            return Ok(());
+        };
+        let res = breakpoints.before_stmt(span, eval);
+        eval.stmt_breakpoints = Some(breakpoints);
+        res
+    }
+
+    fn statements_only(&self) -> bool {
+        true
+    }
+}
+
+impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
+    fn stmt_breakpoints(&mut self) -> &mut StmtBreakpoints<'a, 'e> {
+        if self.stmt_breakpoints.is_none() {
+            self.before_stmt((Box::new(StmtBreakpointsHook) as Box<dyn BeforeStmtFuncDyn>).into());
+        }
+        self.stmt_breakpoints.get_or_insert_with(|| {
+            Box::new(StmtBreakpoints {
+                breakpoints: Vec::new(),
+                handler: None,
+                condition_error_handler: None,
+                condition_errors: Vec::new(),
+                step: Step::None,
+            })
+        })
+    }
+
+    /// Stop before each statement starting on the 1-based `line` of `filename`,
+    /// and call the handler set with [`set_breakpoint_handler`](Evaluator::set_breakpoint_handler).
+    ///
+    /// Breakpoints must be set before the code is evaluated.
+    pub fn set_breakpoint(&mut self, filename: &str, line: usize) {
+        self.stmt_breakpoints().breakpoints.push(Breakpoint {
+            filename: filename.to_owned(),
+            line,
+            condition: None,
+            disabled: false,
+        });
+    }
+
+    /// Like [`set_breakpoint`](Evaluator::set_breakpoint), but only stop when `condition`,
+    /// a Starlark expression evaluated in the paused frame, is true.
+    ///
+    /// The condition is evaluated like [`eval_statements`](Evaluator::eval_statements),
+    /// so it does not affect garbage collection, coverage or tracing.
+    ///
+    /// If the condition fails to evaluate, the breakpoint is disabled and the error
+    /// is passed to the handler set with
+    /// [`set_breakpoint_condition_error_handler`](Evaluator::set_breakpoint_condition_error_handler),
+    /// or kept for [`take_breakpoint_condition_errors`](Evaluator::take_breakpoint_condition_errors).
+    /// The evaluation continues.
+    pub fn set_breakpoint_if(
+        &mut self,
+        filename: &str,
+        line: usize,
+        condition: &str,
+    ) -> crate::Result<()> {
+        let condition = AstModule::parse(
+            "breakpoint condition",
+            condition.to_owned(),
+            &Dialect::AllOptionsInternal,
+        )?;
+        if !matches!(condition.statement().node, StmtP::Expression(_)) {
+            return Err(crate::Error::new_other(
+                BreakpointError::ConditionNotExpression,
+            ));
+        }
+        self.stmt_breakpoints().breakpoints.push(Breakpoint {
+            filename: filename.to_owned(),
+            line,
+            condition: Some(condition),
+            disabled: false,
+        });
+        Ok(())
+    }
+
+    /// Set the function called with the location of the breakpoint and the error
+    /// when a condition of [`set_breakpoint_if`](Evaluator::set_breakpoint_if) fails to evaluate.
+    pub fn set_breakpoint_condition_error_handler(
+        &mut self,
+        handler: impl FnMut(&FileSpan, crate::Error) + 'a,
+    ) {
+        self.stmt_breakpoints().condition_error_handler = Some(Box::new(handler));
+    }
+
+    /// The conditions of [`set_breakpoint_if`](Evaluator::set_breakpoint_if) which failed
+    /// to evaluate since the last call, with the location of their breakpoint,
+    /// unless a handler was set with
+    /// [`set_breakpoint_condition_error_handler`](Evaluator::set_breakpoint_condition_error_handler).
+    pub fn take_breakpoint_condition_errors(&mut self) -> Vec<(FileSpan, crate::Error)> {
+        match &mut self.stmt_breakpoints {
+            Some(breakpoints) => std::mem::take(&mut breakpoints.condition_errors),
+            None => Vec::new(),
+        }
+    }
+
+    /// Set the function called when a breakpoint is hit. If it returns
+    /// [`BreakpointAction::Abort`], the evaluation fails.
+    pub fn set_breakpoint_handler(
+        &mut self,
+        handler: impl for<'v1> FnMut(&mut BreakpointContext<'_, 'v1, 'a, 'e>) -> BreakpointAction + 'a,
+    ) {
+        let mut handler = handler;
+        self.stmt_breakpoints().handler = Some(Box::new(move |ctx| handler(ctx).into()));
+    }
+
+    /// Set the function called before the first statement, then at the next statement
+    /// as determined by the returned [`DebugAction`], and at every breakpoint.
+    /// This replaces the handler set with [`set_breakpoint_handler`](Evaluator::set_breakpoint_handler).
+    ///
+    /// The evaluation is paused while the callback runs. It can inspect the variables
+    /// and the call stack, and evaluate code in the paused frame.
+    /// Evaluators without a callback or breakpoints are not instrumented, so have no overhead.
+    ///
+    /// ```
+    /// use starlark::debug::DebugAction;
+    /// use starlark::environment::Globals;
+    /// use starlark::environment::Module;
+    /// use starlark::eval::Evaluator;
+    /// use starlark::syntax::AstModule;
+    /// use starlark::syntax::Dialect;
+    ///
+    /// let program = "def f(x):\n    return x + 1\ny = f(1)\nz = y * 2\n";
+    /// let ast = AstModule::parse("x.star", program.to_owned(), &Dialect::Standard).unwrap();
+    /// let module = Module::new();
+    /// let mut lines = Vec::new();
+    /// {
+    ///     let mut eval = Evaluator::new(&module);
+    ///     eval.set_debug_callback(|ctx| {
+    ///         lines.push(ctx.location().resolve_span().begin.line + 1);
+    ///         DebugAction::StepOver
+    ///     });
+    ///     eval.eval_module(ast, &Globals::standard()).unwrap();
+    /// }
+    /// // The body of `f` on line 2 is stepped over.
+    /// assert_eq!(lines, [1, 3, 4]);
+    /// ```
+    pub fn set_debug_callback(
+        &mut self,
+        callback: impl for<'v1> FnMut(&mut BreakpointContext<'_, 'v1, 'a, 'e>) -> DebugAction + 'a,
+    ) {
+        let breakpoints = self.stmt_breakpoints();
+        breakpoints.handler = Some(Box::new(callback));
+        breakpoints.step = Step::In;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::environment::Globals;
+    use crate::environment::Module;
+
+    const PROGRAM: &str = "
+def f():
+    total = 0
+    for i in range(5):
+        total += i
+    return total
+x = f()
+";
+
+    fn eval_program(eval: &mut Evaluator) -> crate::Result<()> {
+        let ast = AstModule::parse("test.star", PROGRAM.to_owned(), &Dialect::Standard)?;
+        eval.eval_module(ast, &Globals::standard())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_conditional_breakpoint() {
+        let hits = RefCell::new(Vec::new());
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_breakpoint_if("test.star", 5, "i == 3").unwrap();
+        eval.set_breakpoint_handler(|ctx| {
+            let i = ctx.local("i").unwrap().unpack_i32().unwrap();
+            let total = ctx.evaluate("total * 10").unwrap().unpack_i32().unwrap();
+            hits.borrow_mut()
+                .push((ctx.location().to_string(), i, total));
+            BreakpointAction::Continue
+        });
+        eval_program(&mut eval).unwrap();
+        assert_eq!(vec![("test.star:5:9-19".to_owned(), 3, 30)], *hits.borrow());
+    }
+
+    #[test]
+    fn test_conditional_breakpoint_no_side_effects() {
+        let hits = RefCell::new(Vec::new());
+        let mut trace = Vec::new();
+        let module = Module::new();
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.enable_coverage();
+            eval.set_trace_writer(&mut trace);
+            eval.set_breakpoint_if("test.star", 5, "i % 2 == 1 and total >= len([])")
+                .unwrap();
+            eval.set_breakpoint_handler(|ctx| {
+                hits.borrow_mut()
+                    .push(ctx.local("i").unwrap().unpack_i32().unwrap());
+                BreakpointAction::Continue
+            });
+            eval_program(&mut eval).unwrap();
+            assert!(!eval.disable_gc);
+            let coverage = eval.coverage_data().unwrap();
+            assert_eq!(vec!["test.star"], coverage.files().collect::<Vec<_>>());
+        }
+        assert_eq!(vec![1, 3], *hits.borrow());
+        let trace = String::from_utf8(trace).unwrap();
+        assert!(trace.contains("test.star"));
+        assert!(!trace.contains("breakpoint condition"), "{trace}");
+        assert!(!trace.contains(r#""function":"len""#), "{trace}");
+    }
+
+    #[test]
+    fn test_breakpoint_condition_any_expression() {
+        let hits = RefCell::new(Vec::new());
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_breakpoint_if(
+            "test.star",
+            5,
+            "i in [j for j in range(5)][1::2] and (lambda t: t)(t = f'{total}') != ''",
+        )
+        .unwrap();
+        eval.set_breakpoint_handler(|ctx| {
+            hits.borrow_mut()
+                .push(ctx.local("i").unwrap().unpack_i32().unwrap());
+            BreakpointAction::Continue
+        });
+        eval_program(&mut eval).unwrap();
+        assert_eq!(vec![1, 3], *hits.borrow());
+    }
+
+    #[test]
+    fn test_breakpoint_condition_not_expression() {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        assert!(eval.set_breakpoint_if("test.star", 5, "x = 1").is_err());
+        assert!(eval.set_breakpoint_if("test.star", 5, "1 +").is_err());
+    }
+
+    #[test]
+    fn test_breakpoint_every_iteration() {
+        let hits = RefCell::new(0);
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_breakpoint("test.star", 5);
+        eval.set_breakpoint_handler(|_| {
+            *hits.borrow_mut() += 1;
+            BreakpointAction::Continue
+        });
+        eval_program(&mut eval).unwrap();
+        assert_eq!(5, *hits.borrow());
+    }
+
+    #[test]
+    fn test_breakpoint_abort() {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_breakpoint("test.star", 6);
+        eval.set_breakpoint_handler(|_| BreakpointAction::Abort);
+        let err = eval_program(&mut eval).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Evaluation aborted at breakpoint at test.star:6:5-17"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_condition_error_reported_once() {
+        let errors = RefCell::new(Vec::new());
+        let hits = RefCell::new(0);
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_breakpoint_if("test.star", 5, "undefined == 1")
+            .unwrap();
+        eval.set_breakpoint_condition_error_handler(|location, error| {
+            errors.borrow_mut().push(format!("{location}: {error}"));
+        });
+        eval.set_breakpoint_handler(|_| {
+            *hits.borrow_mut() += 1;
+            BreakpointAction::Continue
+        });
+        eval_program(&mut eval).unwrap();
+        assert_eq!(0, *hits.borrow());
+        let errors = errors.borrow();
+        assert_eq!(1, errors.len());
+        assert!(errors[0].starts_with("test.star:5:9-19: "), "{errors:?}");
+        assert!(errors[0].contains("undefined"), "{errors:?}");
+    }
+
+    #[test]
+    fn test_breakpoint_condition_error_does_not_stop_evaluation() {
+        // The default soft error handler fails on any error, it is not used.
+        let hits = RefCell::new(0);
+        let module = Module::new();
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.set_breakpoint_if("test.star", 5, "undefined == 1")
+                .unwrap();
+            eval.set_breakpoint_handler(|_| {
+                *hits.borrow_mut() += 1;
+                BreakpointAction::Continue
+            });
+            eval_program(&mut eval).unwrap();
+            let errors = eval.take_breakpoint_condition_errors();
+            assert_eq!(1, errors.len());
+            assert_eq!("test.star:5:9-19", errors[0].0.to_string());
+            assert!(errors[0].1.to_string().contains("undefined"));
+            assert!(eval.take_breakpoint_condition_errors().is_empty());
+        }
+        assert_eq!(0, *hits.borrow());
+        assert_eq!(Some(10), module.get("x").and_then(|x| x.unpack_i32()));
+    }
+
+    #[test]
+    fn test_debug_callback_stepping() {
+        let program = "
+def g():
+    return 1
+def f():
+    a = g()
+    return a + 1
+x = f()
+y = x
+";
+        let run = |actions: &[DebugAction]| {
+            let ast =
+                AstModule::parse("test.star", program.to_owned(), &Dialect::Standard).unwrap();
+            let module = Module::new();
+            let mut stops = Vec::new();
+            let mut actions = actions.iter().copied();
+            {
+                let mut eval = Evaluator::new(&module);
+                eval.set_debug_callback(|ctx| {
+                    let line = ctx.location().resolve_span().begin.line + 1;
+                    stops.push((line, ctx.call_stack().frames.len()));
+                    actions.next().unwrap_or(DebugAction::Continue)
+                });
+                eval.eval_module(ast, &Globals::standard()).unwrap();
+            }
+            stops
+        };
+        use DebugAction::*;
+        assert_eq!(
+            vec![(2, 0), (4, 0), (7, 0), (5, 1), (3, 2), (6, 1), (8, 0)],
+            run(&[StepIn; 7])
+        );
+        assert_eq!(
+            vec![(2, 0), (4, 0), (7, 0), (5, 1), (6, 1), (8, 0)],
+            run(&[StepIn, StepIn, StepIn, StepOver, StepOver, StepOver])
+        );
+        assert_eq!(
+            vec![(2, 0), (4, 0), (7, 0), (5, 1), (3, 2), (6, 1), (8, 0)],
+            run(&[StepIn, StepIn, StepIn, StepIn, StepOut, StepOut])
+        );
+        assert_eq!(vec![(2, 0)], run(&[Continue]));
+    }
+}
@@ -15,25 +15,42 @@
  * limitations under the License.
  */
 
+use std::mem;
+
 use crate::collections::SmallMap;
 use crate::debug::inspect::to_scope_names_by_local_slot_id;
+use crate::eval::runtime::coverage::StmtCoverage;
 use crate::eval::runtime::slots::LocalSlotIdCapturedOrNot;
 use crate::eval::Evaluator;
 use crate::syntax::AstModule;
 use crate::values::FrozenStringValue;
 use crate::values::Value;
 
-impl<'v> Evaluator<'v, '_, '_> {
+impl<'v, 'a, 'e> Evaluator<'v, 'a, 'e> {
     /// Evaluate statements in the existing context. This function is designed for debugging,
     /// not production use.
     ///
     /// There are lots of health warnings on this code. Might not work with frozen modules, unassigned variables,
     /// nested definitions etc. It would be a bad idea to rely on the results of continued execution
     /// after evaluating stuff randomly.
+    ///
+    /// The evaluation is not visible to the code being debugged: garbage collection,
+    /// coverage and tracing are the same afterwards as before.
     pub fn eval_statements(&mut self, statements: AstModule) -> crate::Result<Value<'v>> {
         // We are doing a lot of funky stuff here. It's amazing anything works, so let's not push our luck with GC.
-        self.disable_gc();
+        let disable_gc = mem::replace(&mut self.disable_gc, true);
+        let coverage = mem::replace(&mut self.stmt_coverage, StmtCoverage::new());
+        let trace_writer = self.replace_trace_writer(None);
+
+        let res = self.eval_statements_in_frame(statements);
+
+        self.replace_trace_writer(trace_writer);
+        self.stmt_coverage = coverage;
+        self.disable_gc = disable_gc;
+        res
+    }
 
+    fn eval_statements_in_frame(&mut self, statements: AstModule) -> crate::Result<Value<'v>> {
         // Everything must be evaluated with the current heap (or we'll lose memory), which means
         // the current module (eval.module_env).
         // We also want access to the module variables (fine), the locals (need to move them over),
@@ -37,12 +37,14 @@ use thiserror::Error;
 #[derive(Debug, Error)]
 enum EnvironmentError {
     /// Cannot import private symbol, i.e. underscore prefixed
-    #[error("Cannot import private symbol `{0}`")]
-    CannotImportPrivateSymbol(String),
+    #[error("Symbol `{0}` is private to `{1}`")]
+    CannotImportPrivateSymbol(String, String),
     #[error("Module has no symbol `{0}`")]
     ModuleHasNoSymbol(String),
-    #[error("Module has no symbol `{0}`, did you mean `{1}`?")]
+    #[error("Module has no symbol `{0}`, did you mean {1}?")]
     ModuleHasNoSymbolDidYouMean(String, String),
     #[error("Module symbol `{0}` is not exported")]
     ModuleSymbolIsNotExported(String),
+    #[error("Global `{0}` is defined with different values in the globals being combined")]
+    GlobalsConflict(String),
 }
@@ -31,19 +31,27 @@ use crate::docs::DocModule;
 use crate::docs::DocString;
 use crate::docs::DocStringKind;
 use crate::docs::DocType;
+use crate::environment::EnvironmentError;
 use crate::stdlib;
 pub use crate::stdlib::LibraryExtension;
 use crate::typing::Ty;
+use crate::typing::TyBasic;
+use crate::typing::TyStarlarkValue;
+use crate::values::dict::value::MutableDict;
 use crate::values::function::NativeFunc;
 use crate::values::function::SpecialBuiltinFunction;
+use crate::values::list::value::List;
 use crate::values::namespace::value::MaybeDocHiddenValue;
 use crate::values::namespace::FrozenNamespace;
+use crate::values::set::value::MutableSet;
 use crate::values::types::function::NativeFunction;
+use crate::values::typing::type_compiled::compiled::TypeCompiled;
 use crate::values::AllocFrozenValue;
 use crate::values::FrozenHeap;
 use crate::values::FrozenHeapRef;
 use crate::values::FrozenStringValue;
 use crate::values::FrozenValue;
+use crate::values::Heap;
 use crate::values::Value;
 
 /// The global values available during execution.
@@ -122,21 +130,63 @@ impl Globals {
         self.0.variables.get_str(name).map(|x| x.value)
     }
 
-    /// Get all the names defined in this environment.
+    /// Get all the names defined in this environment, sorted.
     pub fn names(&self) -> impl Iterator<Item = FrozenStringValue> + '_ {
         self.0.variable_names.iter().copied()
     }
 
-    /// Iterate over all the items in this environment.
+    /// Iterate over all the items in this environment, sorted by name,
+    /// like [`names`](Globals::names).
     /// Note returned values are owned by this globals.
     pub fn iter(&self) -> impl Iterator<Item = (&str, FrozenValue)> {
-        self.0.variables.iter().map(|(n, v)| (n.as_str(), v.value))
+        self.0.variable_names.iter().map(|n| {
+            let n = n.as_str();
+            (n, self.get_frozen(n).unwrap())
+        })
     }
 
     pub(crate) fn heap(&self) -> &FrozenHeapRef {
         &self.0.heap
     }
 
+    /// Combine several [`Globals`] into one, for example contributed by different plugins.
+    ///
+    /// A name may be defined in several globals only if it refers to the same value,
+    /// otherwise an error is returned. The result has no docstring.
+    pub fn union(others: &[Globals]) -> anyhow::Result<Globals> {
+        let heap = FrozenHeap::new();
+        let mut variables = SymbolMap::new();
+        for other in others {
+            heap.add_reference(other.heap());
+            for (name, value) in other.0.variables.iter() {
+                match variables.get(name) {
+                    None => {
+                        variables.insert(name.as_str(), value.clone());
+                    }
+                    Some(prev) => {
+                        if !prev.value.to_value().ptr_eq(value.value.to_value()) {
+                            return Err(EnvironmentError::GlobalsConflict(
+                                name.as_str().to_owned(),
+                            )
+                            .into());
+                        }
+                    }
+                }
+            }
+        }
+        let mut variable_names: Vec<_> = variables
+            .keys()
+            .map(|x| heap.alloc_str_intern(x.as_str()))
+            .collect();
+        variable_names.sort();
+        Ok(Globals(Arc::new(GlobalsData {
+            heap: heap.into_ref(),
+            variables,
+            variable_names,
+            docstring: None,
+        })))
+    }
+
     /// Print information about the values in this object.
     pub fn describe(&self) -> String {
         self.0
@@ -151,6 +201,41 @@ impl Globals {
         self.0.docstring.as_deref()
     }
 
+    /// Documentation of the methods available on values of the type named `type_name`,
+    /// e.g. `str`, `list` or `dict`, with each method's signature.
+    /// Returns [`None`] if `type_name` is not a global which can be used as a type.
+    ///
+    /// ```
+    /// # use starlark::environment::Globals;
+    /// let methods = Globals::standard().methods_of("str").unwrap();
+    /// assert!(methods.members.contains_key("startswith"));
+    /// assert!(Globals::standard().methods_of("len").is_none());
+    /// ```
+    pub fn methods_of(&self, type_name: &str) -> Option<DocType> {
+        let value = self.get_frozen(type_name)?;
+        let heap = Heap::new();
+        let ty = TypeCompiled::new(value.to_value(), &heap)
+            .ok()?
+            .as_ty()
+            .clone();
+        let methods = match ty.iter_union() {
+            [TyBasic::StarlarkValue(x)] => x.methods(),
+            [TyBasic::List(_)] => TyStarlarkValue::new::<List>().methods(),
+            [TyBasic::Dict(..)] => TyStarlarkValue::new::<MutableDict>().methods(),
+            [TyBasic::Set(_)] => TyStarlarkValue::new::<MutableSet>().methods(),
+            _ => None,
+        };
+        Some(match methods {
+            Some(methods) => methods.documentation(ty),
+            None => DocType {
+                docs: None,
+                members: SmallMap::new(),
+                ty,
+                constructor: None,
+            },
+        })
+    }
+
     /// Get the documentation for both the object itself, and its members.
     pub fn documentation(&self) -> DocModule {
         let (docs, members) = common_documentation(
@@ -257,6 +342,30 @@ impl GlobalsBuilder {
     }
 
     /// Set a value in the [`GlobalsBuilder`].
+    ///
+    /// This is how to expose constants, which are frozen and visible to all scripts.
+    /// In a [`#[starlark_module]`](crate::starlark_module) a `const` declaration does the same.
+    ///
+    /// ```
+    /// # use starlark::assert::Assert;
+    /// use starlark::environment::GlobalsBuilder;
+    /// use starlark::starlark_module;
+    /// use starlark::values::structs::AllocStruct;
+    ///
+    /// #[starlark_module]
+    /// fn constants(builder: &mut GlobalsBuilder) {
+    ///     const MAX_INT: i32 = 2147483647;
+    /// }
+    ///
+    /// let mut globals = GlobalsBuilder::standard().with(constants);
+    /// globals.set("VERSION", "1.2.3");
+    /// globals.set("config", AllocStruct([("debug", false), ("verbose", true)]));
+    ///
+    /// let a = Assert::new().with_globals(globals.build());
+    /// a.pass("assert_eq(MAX_INT, 2147483647)");
+    /// a.pass("assert_eq(VERSION, '1.2.3')");
+    /// a.pass("assert_eq(config.verbose, True)");
+    /// ```
     pub fn set<'v, V: AllocFrozenValue>(&'v mut self, name: &str, value: V) {
         let value = value.alloc_frozen_value(&self.heap);
         self.set_inner(name, value, false)
@@ -395,6 +504,7 @@ mod tests {
 
     use super::*;
     use crate as starlark;
+    use crate::docs::DocMember;
 
     #[test]
     fn test_send_sync()
@@ -427,4 +537,68 @@ mod tests {
         };
         assert_eq!(&docs.members.into_keys().exactly_one().ok().unwrap(), "x");
     }
+
+    #[test]
+    fn test_union() {
+        let foo = GlobalsBuilder::new().with(register_foo).build();
+        let bar = GlobalsBuilder::new()
+            .with(|g| g.set("bar", 2))
+            .with_namespace("ns", |g| g.set("x", 3))
+            .build();
+        let globals = Globals::union(&[foo.dupe(), bar, foo.dupe()]).unwrap();
+        assert_eq!(
+            vec!["bar", "foo", "ns"],
+            globals.names().map(|n| n.as_str()).collect::<Vec<_>>()
+        );
+        let mut a = crate::assert::Assert::new();
+        a.globals(globals);
+        a.eq("3", "foo() + bar");
+        a.eq("3", "ns.x");
+
+        // Functions from the same module are the same values.
+        let same_foo = GlobalsBuilder::new().with(register_foo).build();
+        Globals::union(&[foo.dupe(), same_foo]).unwrap();
+        let other_foo = GlobalsBuilder::new().with(|g| g.set("foo", 1)).build();
+        let err = Globals::union(&[foo, other_foo]).unwrap_err();
+        assert!(err.to_string().contains("`foo`"), "{err}");
+    }
+
+    #[test]
+    fn test_iter_sorted() {
+        let globals = GlobalsBuilder::new()
+            .with(|g| {
+                for name in ["zeta", "alpha", "mu", "beta"] {
+                    g.set(name, 1);
+                }
+            })
+            .build();
+        assert_eq!(
+            vec!["alpha", "beta", "mu", "zeta"],
+            globals.iter().map(|(n, _)| n).collect::<Vec<_>>()
+        );
+        assert!(Globals::standard()
+            .iter()
+            .map(|(n, _)| n)
+            .eq(Globals::standard().names().map(|n| n.as_str())));
+    }
+
+    #[test]
+    fn test_methods_of() {
+        let globals = Globals::extended_internal();
+        let method_params =
+            |ty: &str, name: &str| match globals.methods_of(ty).unwrap().members.get(name) {
+                Some(DocMember::Function(f)) => f
+                    .params
+                    .regular_params()
+                    .map(|p| p.name.clone())
+                    .collect::<Vec<_>>(),
+                x => panic!("`{ty}.{name}` is not a method: {x:?}"),
+            };
+        assert_eq!(vec!["prefix"], method_params("str", "startswith"));
+        assert_eq!(vec!["el"], method_params("list", "append"));
+        assert_eq!(vec!["key", "default"], method_params("dict", "get"));
+        assert!(globals.methods_of("tuple").unwrap().members.is_empty());
+        assert!(globals.methods_of("len").is_none());
+        assert!(globals.methods_of("no_such_type").is_none());
+    }
 }
@@ -244,24 +244,85 @@ impl MethodsBuilder {
 
 /// Used to create methods for a [`StarlarkValue`](crate::values::StarlarkValue).
 ///
-/// To define a method `foo()` on your type, define
-///  usually written as:
+/// Methods are written as a [`#[starlark_module]`](macro@crate::starlark_module) over a
+/// [`MethodsBuilder`], whose functions take the value as their first parameter `this`,
+/// with the same argument parsing as global functions. `#[starlark(attribute)]` turns
+/// a function of `this` into an attribute. The methods are returned from
+/// [`get_methods`](crate::values::StarlarkValue::get_methods), which also makes them
+/// visible to `dir()`, `getattr()`, `hasattr()` and [`DocType::from_starlark_value`](crate::docs::DocType::from_starlark_value):
 ///
-/// ```ignore
-/// fn my_methods(builder: &mut GlobalsBuilder) {
-///     fn foo(me: ARef<Foo>) -> anyhow::Result<NoneType> {
-///         ...
+/// ```
+/// #[macro_use]
+/// extern crate starlark;
+/// # fn main() {
+/// use allocative::Allocative;
+/// use starlark::assert::Assert;
+/// use starlark::docs::DocType;
+/// use starlark::environment::Methods;
+/// use starlark::environment::MethodsBuilder;
+/// use starlark::environment::MethodsStatic;
+/// use starlark::values::NoSerialize;
+/// use starlark::values::ProvidesStaticType;
+/// use starlark::values::StarlarkValue;
+/// use starlark::values::starlark_value;
+///
+/// #[derive(Debug, derive_more::Display, ProvidesStaticType, NoSerialize, Allocative)]
+/// #[display("target({})", name)]
+/// struct Target {
+///     name: String,
+///     deps: Vec<String>,
+/// }
+/// starlark_simple_value!(Target);
+///
+/// #[starlark_module]
+/// fn target_methods(builder: &mut MethodsBuilder) {
+///     /// The name of the target.
+///     #[starlark(attribute)]
+///     fn name(this: &Target) -> anyhow::Result<String> {
+///         Ok(this.name.clone())
+///     }
+///
+///     /// Number of dependencies.
+///     fn dep_count(this: &Target) -> anyhow::Result<i32> {
+///         Ok(this.deps.len() as i32)
+///     }
+///
+///     /// Does the target depend on `dep`?
+///     fn depends_on(
+///         this: &Target,
+///         #[starlark(require = pos)] dep: &str,
+///         #[starlark(require = named, default = false)] prefix: bool,
+///     ) -> anyhow::Result<bool> {
+///         Ok(this.deps.iter().any(|d| if prefix { d.starts_with(dep) } else { d == dep }))
 ///     }
 /// }
 ///
-/// impl StarlarkValue<'_> for Foo {
-///     ...
-///     fn get_methods(&self) -> Option<&'static Globals> {
-///         static RES: GlobalsStatic = GlobalsStatic::new();
-///         RES.methods(module_creator)
+/// #[starlark_value(type = "target")]
+/// impl<'v> StarlarkValue<'v> for Target {
+///     fn get_methods() -> Option<&'static Methods> {
+///         static RES: MethodsStatic = MethodsStatic::new();
+///         RES.methods(target_methods)
 ///     }
-///     ...
 /// }
+///
+/// let mut a = Assert::new();
+/// a.globals_add(|g| {
+///     let t = g.alloc(Target { name: "app".to_owned(), deps: vec!["//lib:a".to_owned()] });
+///     g.set("t", t);
+/// });
+/// a.eq("'app'", "t.name");
+/// a.eq("1", "t.dep_count()");
+/// a.is_true("t.depends_on('//lib', prefix = True)");
+/// a.is_true("not t.depends_on('//lib')");
+/// a.eq("['dep_count', 'depends_on', 'name']", "dir(t)");
+/// a.fail("t.depends_on(dep = '//lib:a')", "Missing positional-only parameter `dep`");
+///
+/// let docs = DocType::from_starlark_value::<Target>();
+/// assert_eq!(
+///     vec!["dep_count", "depends_on", "name"],
+///     docs.members.keys().map(|k| k.as_str()).collect::<Vec<_>>()
+/// );
+/// # }
 /// ```
 pub struct MethodsStatic(OnceCell<Methods>);
 
@@ -27,9 +27,13 @@ use std::time::Duration;
 use std::time::Instant;
 
 use allocative::Allocative;
+use anyhow::Context;
 use dupe::Dupe;
 use itertools::Itertools;
+use starlark_syntax::syntax::ast::StmtP;
 use starlark_syntax::syntax::ast::Visibility;
+use starlark_syntax::syntax::module::AstModuleFields;
+use starlark_syntax::syntax::type_expr::TypeExprUnpackP;
 
 use crate::cast::transmute;
 use crate::collections::Hashed;
@@ -46,11 +50,16 @@ use crate::environment::slots::MutableSlots;
 use crate::environment::EnvironmentError;
 use crate::environment::Globals;
 use crate::errors::did_you_mean::did_you_mean;
+use crate::errors::did_you_mean::format_did_you_mean;
 use crate::eval::runtime::profile::heap::RetainedHeapProfileMode;
+use crate::eval::Evaluator;
 use crate::eval::ProfileData;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
 use crate::values::layout::heap::heap_type::HeapKind;
 use crate::values::layout::heap::profile::aggregated::AggregateHeapProfileInfo;
 use crate::values::layout::heap::profile::aggregated::RetainedHeapProfile;
+use crate::values::typing::TypeCompiled;
 use crate::values::Freeze;
 use crate::values::Freezer;
 use crate::values::FrozenHeap;
@@ -59,6 +68,8 @@ use crate::values::FrozenRef;
 use crate::values::FrozenStringValue;
 use crate::values::FrozenValue;
 use crate::values::Heap;
+use crate::values::HeapSummary;
+use crate::values::HeapValueInfo;
 use crate::values::OwnedFrozenValue;
 use crate::values::Trace;
 use crate::values::Tracer;
@@ -70,6 +81,10 @@ enum ModuleError {
     RetainedMemoryProfileNotEnabled,
     #[error("Extra value already set to a value of type `{}`", .0)]
     ExtraValueAlreadySet(&'static str),
+    #[error("Value for `{0}` is `{1}` of type `{2}`, which does not match the type `{3}`")]
+    SetTypedMismatch(String, String, &'static str, String),
+    #[error("Not a type expression: `{0}`")]
+    NotTypeExpr(String),
 }
 
 /// The result of freezing a [`Module`], making it and its contained values immutable.
@@ -78,6 +93,12 @@ enum ModuleError {
 /// can be obtained using [`frozen_heap`](FrozenModule::frozen_heap). Be careful not to use
 /// these values after the [`FrozenModule`] has been released unless you obtain a reference
 /// to the frozen heap.
+///
+/// When a module `load`s values from a [`FrozenModule`], they are not copied: the loading
+/// module refers to the same frozen values, and keeps their frozen heap alive. Frozen values
+/// can be read, indexed and iterated like any other, but attempts to mutate them, e.g.
+/// `x.append(1)` on a loaded list, fail with a "cannot mutate frozen value" error.
+/// To obtain a mutable version, copy the value explicitly, e.g. with `list(x)` or `dict(x)`.
 #[derive(Debug, Clone, Dupe, Allocative)]
 // We store the two elements separately since the FrozenHeapRef contains
 // a copy of the FrozenModuleData inside it.
@@ -109,6 +130,58 @@ pub(crate) struct FrozenModuleData {
 /// You can get references to these heaps with [`frozen_heap`](Module::frozen_heap) and
 /// [`heap`](Module::heap). Be careful not to use these values after the [`Module`] has been
 /// released unless you obtain a reference to the frozen heap.
+///
+/// # Keeping values after evaluation
+///
+/// A [`Value<'v>`](Value) borrows the module, so the borrow checker prevents using it
+/// once the module is frozen or dropped. Values which should outlive the evaluation are
+/// kept alive by freezing:
+///
+/// * values on the [`heap`](Module::heap) reachable from the module variables
+///   or from the [`extra_value`](Module::set_extra_value) are frozen by
+///   [`freeze`](Module::freeze), everything else is dropped;
+/// * values allocated on the [`frozen_heap`](Module::frozen_heap) are never moved,
+///   the resulting [`FrozenModule`] owns that heap;
+/// * a [`FrozenValue`] is valid as long as its heap is alive, and an [`OwnedFrozenValue`]
+///   pairs it with a [`FrozenHeapRef`] to keep the heap alive, without copying.
+///
+/// For example, to cache values computed during evaluation without converting
+/// them to Rust types:
+///
+/// ```
+/// # fn main() -> starlark::Result<()> {
+/// use starlark::environment::Globals;
+/// use starlark::environment::Module;
+/// use starlark::eval::Evaluator;
+/// use starlark::syntax::AstModule;
+/// use starlark::syntax::Dialect;
+/// use starlark::values::list::AllocList;
+/// use starlark::values::OwnedFrozenValue;
+///
+/// let cached: OwnedFrozenValue = {
+///     let module = Module::new();
+///     // Valid for as long as the module, and then the frozen module, is alive.
+///     let label = module.frozen_heap().alloc("config");
+///     let mut eval = Evaluator::new(&module);
+///     let ast = AstModule::parse(
+///         "config.star",
+///         "def f(): return {'deps': [1, 2]}\nf()".to_owned(),
+///         &Dialect::Standard,
+///     )?;
+///     // Not a module variable: would be lost on freeze unless retained.
+///     let result = eval.eval_module(ast, &Globals::standard())?;
+///     drop(eval);
+///     let retained = module.heap().alloc(AllocList([label.to_value(), result]));
+///     module.set_extra_value(retained);
+///     module.freeze()?.owned_extra_value().unwrap()
+/// };
+/// // The module is gone, `cached` keeps its frozen heap alive.
+/// let items: Vec<OwnedFrozenValue> = cached.downcast_frozen_list().unwrap().collect();
+/// assert_eq!(items[0].unpack_str(), Some("config"));
+/// assert_eq!(items[1].value().to_repr(), r#"{"deps": [1, 2]}"#);
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Debug)]
 pub struct Module {
     heap: Heap,
@@ -170,12 +243,10 @@ impl FrozenModule {
     #[doc(hidden)]
     pub fn get_any_visibility(&self, name: &str) -> anyhow::Result<(OwnedFrozenValue, Visibility)> {
         self.get_any_visibility_option(name).ok_or_else(|| {
-            match did_you_mean(name, self.names().map(|s| s.as_str())) {
-                Some(better) => EnvironmentError::ModuleHasNoSymbolDidYouMean(
-                    name.to_owned(),
-                    better.to_owned(),
-                )
-                .into(),
+            match format_did_you_mean(&did_you_mean(name, self.names().map(|s| s.as_str()))) {
+                Some(better) => {
+                    EnvironmentError::ModuleHasNoSymbolDidYouMean(name.to_owned(), better).into()
+                }
                 None => EnvironmentError::ModuleHasNoSymbol(name.to_owned()).into(),
             }
         })
@@ -196,6 +267,13 @@ impl FrozenModule {
         }
     }
 
+    /// Get the value of the variable `name`, even if it is private,
+    /// like `_helper`, which Starlark code cannot `load` from this module.
+    /// Returns an error if the variable isn't defined in the module.
+    pub fn get_any(&self, name: &str) -> anyhow::Result<OwnedFrozenValue> {
+        self.get_any_visibility(name).map(|(value, _)| value)
+    }
+
     /// Get the value of the exported variable `name`.
     /// Returns an error if the variable isn't defined in the module or it is private.
     pub fn get(&self, name: &str) -> anyhow::Result<OwnedFrozenValue> {
@@ -219,6 +297,21 @@ impl FrozenModule {
         &self.heap
     }
 
+    /// Count and size of the values owned by this module, by type.
+    /// Doesn't include the modules it keeps alive by reference, such as those it loaded.
+    ///
+    /// To attribute memory to the functions which allocated it, enable
+    /// [`ProfileMode::HeapSummaryRetained`](crate::eval::ProfileMode::HeapSummaryRetained)
+    /// during evaluation and use [`heap_profile`](FrozenModule::heap_profile).
+    pub fn heap_summary(&self) -> HeapSummary {
+        self.heap.allocated_summary()
+    }
+
+    /// The `n` values owned by this module using the most memory, largest first.
+    pub fn largest_values(&self, n: usize) -> Vec<HeapValueInfo> {
+        self.heap.largest_values(n)
+    }
+
     /// Print out some approximation of the module definitions.
     pub fn describe(&self) -> String {
         self.module.describe()
@@ -228,6 +321,13 @@ impl FrozenModule {
         self.module.all_items()
     }
 
+    /// The docstring of the module: the string literal at the start of its source, if any.
+    ///
+    /// The string is returned as written, without removing indentation.
+    pub fn docstring(&self) -> Option<&str> {
+        self.module.docstring.as_deref()
+    }
+
     /// The documentation for the module, and all of its top level values
     ///
     /// Returns `(<module documentation>, { <symbol> : <that symbol's documentation> })`
@@ -345,11 +445,34 @@ impl Module {
         &self.heap
     }
 
+    /// Garbage collect the [heap](Module::heap) now, keeping the variables of
+    /// this module and its [extra value](Module::set_extra_value).
+    ///
+    /// # Safety
+    ///
+    /// Must not be called while an [`Evaluator`] using this module is running,
+    /// only between evaluations. Any other `Value` of the heap becomes invalid,
+    /// for example a value returned by [`eval_module`](Evaluator::eval_module).
+    pub unsafe fn gc_now(&self) {
+        self.heap().gc_now(|tracer| self.trace(tracer))
+    }
+
     /// Get the frozen heap on which frozen values are allocated by this module.
+    ///
+    /// Values allocated there remain valid after [`freeze`](Module::freeze),
+    /// as the heap is owned by the resulting [`FrozenModule`].
     pub fn frozen_heap(&self) -> &FrozenHeap {
         &self.frozen_heap
     }
 
+    /// Count and size of the values allocated by this module on both its heaps, by type.
+    pub fn heap_summary(&self) -> HeapSummary {
+        HeapSummary::merge([
+            &self.heap.allocated_summary(),
+            &self.frozen_heap.allocated_summary(),
+        ])
+    }
+
     /// Iterate through all the names defined in this module.
     /// Only includes symbols that are publicly exposed.
     pub fn names(&self) -> impl Iterator<Item = FrozenStringValue> + '_ {
@@ -406,6 +529,10 @@ impl Module {
     }
 
     /// Freeze the environment, all its value will become immutable afterwards.
+    ///
+    /// Fails if a value cannot be frozen, for example one allocated with
+    /// [`Heap::alloc_complex_no_freeze`], or whose [`Freeze`] implementation fails.
+    /// The error names the module variable holding the value.
     pub fn freeze(self) -> anyhow::Result<FrozenModule> {
         let Module {
             names,
@@ -422,9 +549,13 @@ impl Module {
         // Note that we even freeze anonymous slots, since they are accessed by
         // slot-index in the code, and we don't walk into them, so don't know if
         // they are used.
-        let freezer = Freezer::new(frozen_heap);
-        let slots = slots.freeze(&freezer)?;
-        let extra_value = extra_value.into_inner().freeze(&freezer)?;
+        let mut freezer = Freezer::new(frozen_heap);
+        freezer.share_short_strings = heap.share_short_strings();
+        let slots = slots.freeze(&freezer, &names)?;
+        let extra_value = extra_value
+            .into_inner()
+            .freeze(&freezer)
+            .context("Failed to freeze the module `extra_value`")?;
         let stacks = if let Some(mode) = heap_profile_on_freeze.get() {
             // TODO(nga): retained heap profile does not store information about data
             //   allocated in frozen heap before freeze starts.
@@ -468,6 +599,77 @@ impl Module {
         slots.set_slot(slot, value);
     }
 
+    /// Set the value of a variable which evaluated code may read but not assign.
+    /// Evaluating a module which assigns to `name` at the top level (including
+    /// via `def`, `load` or a `for` loop) fails before any code runs, with the
+    /// error pointing at the assignment. The host can still replace the value
+    /// with [`set`](Module::set).
+    ///
+    /// ```
+    /// # use starlark::environment::{Globals, Module};
+    /// # use starlark::eval::Evaluator;
+    /// # use starlark::syntax::{AstModule, Dialect};
+    /// let module = Module::new();
+    /// module.set_constant("version", module.heap().alloc(3));
+    /// let ast = AstModule::parse("x.star", "version = 4".to_owned(), &Dialect::Standard).unwrap();
+    /// let err = Evaluator::new(&module)
+    ///     .eval_module(ast, &Globals::standard())
+    ///     .unwrap_err();
+    /// assert!(err.to_string().contains("Cannot assign to `version`"));
+    /// ```
+    pub fn set_constant<'v>(&'v self, name: &str, value: Value<'v>) {
+        self.set(name, value);
+        self.names
+            .add_constant(self.frozen_heap.alloc_str_intern(name));
+    }
+
+    /// Set the value of a variable after checking it against a type, written as
+    /// it would be in a Starlark annotation, e.g. `"list[str] | None"`.
+    /// The type may refer to the names in `globals`, including the types the host
+    /// registered there. Only type expressions are accepted, so no code is run.
+    /// Fails without setting anything if the type is invalid or the value
+    /// does not match it.
+    pub fn set_typed<'v>(
+        &'v self,
+        name: &str,
+        value: Value<'v>,
+        ty: &str,
+        globals: &Globals,
+    ) -> anyhow::Result<()> {
+        let env = Module::new();
+        let ast = AstModule::parse("type", ty.to_owned(), &Dialect::Extended)
+            .map_err(crate::Error::into_anyhow)?;
+        let expr = match &ast.statement().node {
+            StmtP::Expression(expr) => expr,
+            _ => return Err(ModuleError::NotTypeExpr(ty.to_owned()).into()),
+        };
+        TypeExprUnpackP::unpack(expr, ast.codemap())
+            .map_err(|e| crate::Error::from(e).into_anyhow())?;
+        let ty_value = Evaluator::new(&env)
+            .eval_module(ast, globals)
+            .map_err(crate::Error::into_anyhow)?;
+        let parsed_ty = TypeCompiled::new(ty_value, env.heap())?.as_ty().clone();
+        if !TypeCompiled::from_ty(&parsed_ty, self.heap()).matches(value) {
+            return Err(ModuleError::SetTypedMismatch(
+                name.to_owned(),
+                value.to_repr(),
+                value.get_type(),
+                ty.to_owned(),
+            )
+            .into());
+        }
+        self.set(name, value);
+        Ok(())
+    }
+
+    /// Names which already existed in this module when code was evaluated in it
+    /// (for example values injected with [`set`](Module::set)) and which that
+    /// code referenced. Names are listed in the order they were first referenced.
+    /// Use it to find injected values that no script needs.
+    pub fn referenced_names(&self) -> Vec<FrozenStringValue> {
+        self.names.all_referenced()
+    }
+
     /// Symbols starting with underscore are considered private.
     pub(crate) fn default_visibility(symbol: &str) -> Visibility {
         match symbol.starts_with('_') {
@@ -497,13 +699,19 @@ impl Module {
         }
     }
 
+    /// Get `symbol` from `module`, which was loaded as `module_name`.
     pub(crate) fn load_symbol<'v>(
         &'v self,
         module: &FrozenModule,
+        module_name: &str,
         symbol: &str,
     ) -> anyhow::Result<Value<'v>> {
         if Self::default_visibility(symbol) != Visibility::Public {
-            return Err(EnvironmentError::CannotImportPrivateSymbol(symbol.to_owned()).into());
+            return Err(EnvironmentError::CannotImportPrivateSymbol(
+                symbol.to_owned(),
+                module_name.to_owned(),
+            )
+            .into());
         }
         match module.get_any_visibility(symbol)? {
             (v, Visibility::Public) => Ok(v.owned_value(self.frozen_heap())),
@@ -535,6 +743,9 @@ impl Module {
     }
 
     /// Field that can be used for any purpose you want.
+    ///
+    /// The value is kept alive by garbage collection and frozen with the module,
+    /// see [`FrozenModule::owned_extra_value`].
     pub fn set_extra_value<'v>(&'v self, v: Value<'v>) {
         // Cast lifetime.
         let v = unsafe { transmute!(Value, Value, v) };
@@ -566,9 +777,13 @@ where
 
 #[cfg(test)]
 mod tests {
+    use allocative::Allocative;
+    use derive_more::Display;
     use starlark_derive::starlark_module;
+    use starlark_derive::starlark_value;
 
     use crate as starlark;
+    use crate::any::ProvidesStaticType;
     use crate::environment::FrozenModule;
     use crate::environment::Globals;
     use crate::environment::GlobalsBuilder;
@@ -577,7 +792,14 @@ mod tests {
     use crate::eval::Evaluator;
     use crate::syntax::AstModule;
     use crate::syntax::Dialect;
+    use crate::values::bytes::StarlarkBytes;
+    use crate::values::list::AllocList;
     use crate::values::list::ListRef;
+    use crate::values::HeapValueInfo;
+    use crate::values::NoSerialize;
+    use crate::values::StarlarkValue;
+    use crate::values::Trace;
+    use crate::values::Value;
 
     #[test]
     fn test_gen_heap_summary_profile() {
@@ -609,6 +831,94 @@ x = f(1)
         assert!(heap_summary.contains("\"x.star.f\""), "{:?}", heap_summary);
     }
 
+    #[test]
+    fn test_heap_summary() {
+        let module = Module::new();
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.eval_module(
+                AstModule::parse(
+                    "x.star",
+                    "strings = [('x' * 100) + str(i) for i in range(1000)]".to_owned(),
+                    &Dialect::Standard,
+                )
+                .unwrap(),
+                &Globals::standard(),
+            )
+            .unwrap();
+        }
+        let module = module.freeze().unwrap();
+
+        let summary = module.heap_summary();
+        let (largest_type, (count, bytes)) = summary
+            .summary()
+            .into_iter()
+            .max_by_key(|(_, (_, bytes))| *bytes)
+            .unwrap();
+        assert_eq!("string", largest_type);
+        assert!(count >= 1000);
+        assert!(bytes * 2 > summary.total_allocated_bytes(), "{summary}");
+        assert!(summary.to_string().starts_with("Type "), "{summary}");
+
+        let largest = module.largest_values(1);
+        assert_eq!(1, largest.len());
+        assert_eq!("list", largest[0].type_name);
+        assert!(largest[0].repr.starts_with("[\"xxx"), "{:?}", largest[0]);
+        assert!(largest[0].repr.ends_with("..."), "{:?}", largest[0]);
+        assert_eq!(HeapValueInfo::MAX_REPR_LEN + 3, largest[0].repr.len());
+    }
+
+    #[test]
+    fn test_freeze_dedupes_strings() {
+        let module = Module::new();
+        module.heap().set_share_short_strings(true);
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.eval_module(
+                AstModule::parse(
+                    "x.star",
+                    "strings = ['dep_' + str(i % 10) for i in range(10000)]".to_owned(),
+                    &Dialect::Standard,
+                )
+                .unwrap(),
+                &Globals::standard(),
+            )
+            .unwrap();
+        }
+        let module = module.freeze().unwrap();
+
+        let summary = module.heap_summary().summary();
+        let (count, bytes) = summary["string"];
+        assert!(count < 100, "{count} strings after freeze");
+        assert!(bytes < 10000, "{bytes} bytes of strings after freeze");
+        let strings = module.get("strings").unwrap();
+        let strings = ListRef::from_value(strings.value()).unwrap();
+        assert!(strings[0].ptr_eq(strings[10]));
+        assert!(!strings[0].ptr_eq(strings[1]));
+    }
+
+    #[test]
+    fn test_freeze_error_names_variable() {
+        #[derive(Debug, Display, Trace, ProvidesStaticType, NoSerialize, Allocative)]
+        #[display("unfreezable")]
+        struct Unfreezable;
+
+        #[starlark_value(type = "unfreezable")]
+        impl<'v> StarlarkValue<'v> for Unfreezable {}
+
+        let module = Module::new();
+        module.set("fine", module.heap().alloc("fine"));
+        let unfreezable = module.heap().alloc_complex_no_freeze(Unfreezable);
+        module.set("bad", module.heap().alloc(AllocList([unfreezable])));
+        let err = format!("{:#}", module.freeze().unwrap_err());
+        assert!(
+            err.starts_with("Failed to freeze module variable `bad`: "),
+            "{err}"
+        );
+        assert!(err.contains("Unfreezable"), "{err}");
+        assert!(err.contains("cannot be frozen"), "{err}");
+    }
+
     #[test]
     fn test_frozen_module_from_globals() {
         #[starlark_module]
@@ -633,4 +943,202 @@ x = f(1)
                 .len()
         );
     }
+
+    #[test]
+    fn test_docstring() {
+        let module = Module::new();
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.eval_module(
+                AstModule::parse(
+                    "x.star",
+                    r#"
+"""Helpers for widgets.
+
+More details.
+"""
+
+def make(x):
+    """Make a widget."""
+    return x
+
+def undocumented():
+    pass
+
+lambda_fn = lambda: None
+"#
+                    .to_owned(),
+                    &Dialect::Standard,
+                )
+                .unwrap(),
+                &Globals::standard(),
+            )
+            .unwrap();
+            assert_eq!(
+                Some("Make a widget."),
+                module.get("make").unwrap().docstring()
+            );
+        }
+        let module = module.freeze().unwrap();
+        assert_eq!(
+            Some("Helpers for widgets.\n\nMore details.\n"),
+            module.docstring()
+        );
+        let docstring = |name| {
+            module
+                .get(name)
+                .unwrap()
+                .value()
+                .docstring()
+                .map(str::to_owned)
+        };
+        assert_eq!(Some("Make a widget.".to_owned()), docstring("make"));
+        assert_eq!(None, docstring("undocumented"));
+        assert_eq!(None, docstring("lambda_fn"));
+
+        let module = Module::new().freeze().unwrap();
+        assert_eq!(None, module.docstring());
+    }
+
+    fn eval_in(module: &Module, program: &str) -> crate::Result<()> {
+        let ast = AstModule::parse("x.star", program.to_owned(), &Dialect::Extended)?;
+        Evaluator::new(module).eval_module(ast, &Globals::standard())?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_constant() {
+        for program in [
+            "version = 2",
+            "version += 1",
+            "a, (b, version) = 1, (2, 3)",
+            "def version(): pass",
+            "for version in []: pass",
+            "if True:\n    version = 2",
+        ] {
+            let module = Module::new();
+            module.set_constant("version", module.heap().alloc(1));
+            let err = eval_in(&module, program).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("Cannot assign to `version`, it is a constant defined by the host"),
+                "{program}: {err}"
+            );
+            let span = err.span().unwrap().resolve_span();
+            assert_eq!(span.begin.line, span.end.line, "{program}");
+            // Nothing ran.
+            assert_eq!(Some(1), module.get("version").unwrap().unpack_i32());
+        }
+
+        // `del` only takes an index or a slice, so it cannot unbind the constant.
+        let module = Module::new();
+        module.set_constant("version", module.heap().alloc(1));
+        let ast = AstModule::parse(
+            "x.star",
+            "del version".to_owned(),
+            &Dialect::AllOptionsInternal,
+        );
+        assert!(ast.is_err());
+        assert_eq!(Some(1), module.get("version").unwrap().unpack_i32());
+
+        let module = Module::new();
+        module.set_constant("version", module.heap().alloc(1));
+        // Reading, and shadowing in a local scope, are fine.
+        eval_in(
+            &module,
+            "x = version + 1\ndef f(version): return version\ny = [version for version in [5]]",
+        )
+        .unwrap();
+        assert_eq!(Some(2), module.get("x").unwrap().unpack_i32());
+    }
+
+    #[test]
+    fn test_set_typed() {
+        let module = Module::new();
+        let heap = module.heap();
+        let globals = Globals::standard();
+        module
+            .set_typed("xs", heap.alloc(vec!["a", "b"]), "list[str]", &globals)
+            .unwrap();
+        module
+            .set_typed("n", Value::new_none(), "int | None", &globals)
+            .unwrap();
+        let err = module
+            .set_typed("bad", heap.alloc(vec![1]), "list[str]", &globals)
+            .unwrap_err();
+        assert_eq!(
+            "Value for `bad` is `[1]` of type `list`, which does not match the type `list[str]`",
+            err.to_string()
+        );
+        assert!(module.get("bad").is_none());
+        assert!(module
+            .set_typed("bad", heap.alloc(1), "no_such_type", &globals)
+            .is_err());
+        eval_in(&module, "ys = xs + ['c']").unwrap();
+    }
+
+    #[test]
+    fn test_set_typed_host_type() {
+        let module = Module::new();
+        let b = module.heap().alloc(StarlarkBytes::new(b"x".as_slice()));
+        // `bytes` is not in scope without the standard globals.
+        assert!(module
+            .set_typed("b", b, "bytes | None", &Globals::new())
+            .is_err());
+        let globals = Globals::standard();
+        module.set_typed("b", b, "bytes | None", &globals).unwrap();
+        let err = module
+            .set_typed("s", module.heap().alloc("x"), "list[bytes]", &globals)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("does not match the type `list[bytes]`"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_set_typed_not_type_expr() {
+        let module = Module::new();
+        let globals = Globals::standard();
+        for (ty, msg) in [
+            // Rejected before evaluation, so `fail` does not run.
+            (
+                "fail('x')",
+                "call expression is not allowed in type expression",
+            ),
+            (
+                "int if True else str",
+                "if expression is not allowed in type expression",
+            ),
+            ("x = int", "Not a type expression: `x = int`"),
+        ] {
+            let err = module
+                .set_typed("x", Value::new_none(), ty, &globals)
+                .unwrap_err();
+            assert!(err.to_string().contains(msg), "{ty}: {err}");
+        }
+        assert!(module.get("x").is_none());
+    }
+
+    #[test]
+    fn test_referenced_names() {
+        let module = Module::new();
+        for name in ["used_top", "used_in_def", "unused", "reassigned"] {
+            module.set(name, module.heap().alloc(1));
+        }
+        eval_in(
+            &module,
+            "x = used_top\ndef f():\n    return used_in_def\nreassigned = 2\nz = x",
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["used_top", "used_in_def"],
+            module
+                .referenced_names()
+                .iter()
+                .map(|n| n.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
 }
@@ -22,6 +22,7 @@ use starlark_syntax::syntax::ast::Visibility;
 
 use crate::collections::Hashed;
 use crate::collections::SmallMap;
+use crate::collections::SmallSet;
 use crate::environment::slots::ModuleSlotId;
 use crate::values::FrozenStringValue;
 
@@ -43,24 +44,34 @@ use crate::values::FrozenStringValue;
 /// On an unscope, we do the reverse, putting things back to how they were
 /// before (apart from the total) number of slots required.
 #[derive(Debug)]
-pub(crate) struct MutableNames(RefCell<SmallMap<FrozenStringValue, (ModuleSlotId, Visibility)>>);
+pub(crate) struct MutableNames {
+    names: RefCell<SmallMap<FrozenStringValue, (ModuleSlotId, Visibility)>>,
+    /// Names which evaluated code may not assign to.
+    constants: RefCell<SmallSet<FrozenStringValue>>,
+    /// Names which were defined before evaluation and then referenced by evaluated code.
+    referenced: RefCell<SmallSet<FrozenStringValue>>,
+}
 
 #[derive(Debug, Allocative)]
 pub(crate) struct FrozenNames(SmallMap<FrozenStringValue, (ModuleSlotId, Visibility)>);
 
 impl MutableNames {
     pub(crate) fn new() -> Self {
-        Self(RefCell::new(SmallMap::new()))
+        Self {
+            names: RefCell::new(SmallMap::new()),
+            constants: RefCell::new(SmallSet::new()),
+            referenced: RefCell::new(SmallSet::new()),
+        }
     }
 
     pub(crate) fn slot_count(&self) -> u32 {
-        self.0.borrow().len().try_into().unwrap()
+        self.names.borrow().len().try_into().unwrap()
     }
 
     /// Try and go back from a slot to a name.
     /// Inefficient - only use in error paths.
     pub(crate) fn get_slot(&self, slot: ModuleSlotId) -> Option<FrozenStringValue> {
-        for (s, (i, _vis)) in &*self.0.borrow() {
+        for (s, (i, _vis)) in &*self.names.borrow() {
             if *i == slot {
                 return Some(*s);
             }
@@ -69,7 +80,7 @@ impl MutableNames {
     }
 
     pub(crate) fn get_name(&self, name: Hashed<&str>) -> Option<(ModuleSlotId, Visibility)> {
-        self.0.borrow().get_hashed(name).copied()
+        self.names.borrow().get_hashed(name).copied()
     }
 
     /// Add a name with explicit visibility to the module.
@@ -78,7 +89,7 @@ impl MutableNames {
         name: FrozenStringValue,
         vis: Visibility,
     ) -> ModuleSlotId {
-        let mut x = self.0.borrow_mut();
+        let mut x = self.names.borrow_mut();
         match x.get_mut_hashed(name.get_hashed().as_ref()) {
             Some((slot, stored_vis)) => {
                 // Public visibility wins.
@@ -101,11 +112,11 @@ impl MutableNames {
     }
 
     pub(crate) fn hide_name(&self, name: &str) {
-        self.0.borrow_mut().shift_remove(name);
+        self.names.borrow_mut().shift_remove(name);
     }
 
     pub(crate) fn all_names_and_slots(&self) -> Vec<(FrozenStringValue, ModuleSlotId)> {
-        self.0
+        self.names
             .borrow()
             .iter()
             .map(|(name, (slot, _vis))| (*name, *slot))
@@ -113,7 +124,7 @@ impl MutableNames {
     }
 
     pub(crate) fn all_names_and_visibilities(&self) -> Vec<(FrozenStringValue, Visibility)> {
-        self.0
+        self.names
             .borrow()
             .iter()
             .map(|(name, (_slot, vis))| (*name, *vis))
@@ -123,15 +134,31 @@ impl MutableNames {
     pub(crate) fn all_names_slots_and_visibilities(
         &self,
     ) -> Vec<(FrozenStringValue, ModuleSlotId, Visibility)> {
-        self.0
+        self.names
             .borrow()
             .iter()
             .map(|(name, (slot, vis))| (*name, *slot, *vis))
             .collect()
     }
 
+    pub(crate) fn add_constant(&self, name: FrozenStringValue) {
+        self.constants.borrow_mut().insert(name);
+    }
+
+    pub(crate) fn is_constant(&self, name: &str) -> bool {
+        self.constants.borrow().contains(name)
+    }
+
+    pub(crate) fn add_referenced(&self, name: FrozenStringValue) {
+        self.referenced.borrow_mut().insert(name);
+    }
+
+    pub(crate) fn all_referenced(&self) -> Vec<FrozenStringValue> {
+        self.referenced.borrow().iter().copied().collect()
+    }
+
     pub(crate) fn freeze(self) -> FrozenNames {
-        FrozenNames(self.0.into_inner())
+        FrozenNames(self.names.into_inner())
     }
 }
 
@@ -19,9 +19,10 @@ use std::cell::RefCell;
 use std::cell::RefMut;
 
 use allocative::Allocative;
+use anyhow::Context;
 use dupe::Dupe;
 
-use crate::values::Freeze;
+use crate::environment::names::MutableNames;
 use crate::values::Freezer;
 use crate::values::FrozenValue;
 use crate::values::Value;
@@ -86,8 +87,33 @@ impl<'v> MutableSlots<'v> {
             .collect()
     }
 
-    pub(crate) fn freeze(self, freezer: &Freezer) -> anyhow::Result<FrozenSlots> {
-        let slots = self.0.into_inner().freeze(freezer)?;
+    /// Freeze the slots. `names` are only used to report which variable failed to freeze.
+    pub(crate) fn freeze(
+        self,
+        freezer: &Freezer,
+        names: &MutableNames,
+    ) -> anyhow::Result<FrozenSlots> {
+        let slots = self
+            .0
+            .into_inner()
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let Some(v) = v else {
+                    return Ok(None);
+                };
+                // The type of `v` is not reported: after a failed freeze it may be a forward.
+                v.freeze(freezer).map(Some).with_context(|| {
+                    let slot = ModuleSlotId::new(u32::try_from(i).unwrap());
+                    match names.get_slot(slot) {
+                        Some(name) => {
+                            format!("Failed to freeze module variable `{}`", name.as_str())
+                        }
+                        None => format!("Failed to freeze module slot {i}"),
+                    }
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
         Ok(FrozenSlots(slots))
     }
 }
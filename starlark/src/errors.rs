@@ -17,10 +17,15 @@
 
 //! Error types used by Starlark.
 
+pub use starlark_syntax::diagnostic::DiagnosticOptions;
 pub use starlark_syntax::frame::Frame;
 
 pub use crate::analysis::EvalMessage;
 pub use crate::analysis::EvalSeverity;
 pub use crate::analysis::Lint;
+pub use crate::errors::code::ErrorCode;
+pub use crate::errors::fail::FailError;
 
+mod code;
 pub(crate) mod did_you_mean;
+mod fail;
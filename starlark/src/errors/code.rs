@@ -0,0 +1,243 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Stable classification of Starlark errors.
+
+use dupe::Dupe;
+use starlark_syntax::error::without_message_prefix;
+
+use crate::eval::compiler::scope::ScopeError;
+use crate::eval::runtime::arguments::FunctionError;
+use crate::eval::runtime::arguments::ParamError;
+use crate::eval::runtime::evaluator::EvaluatorError;
+use crate::values::error::ValueError;
+use crate::values::types::int::int_or_big::StarlarkIntError;
+use crate::values::types::num::value::NumError;
+use crate::values::typing::type_compiled::compiled::TypingError;
+use crate::ErrorKind;
+
+/// A stable code identifying what went wrong, finer-grained than [`ErrorKind`].
+///
+/// Prefer matching on the code to matching on the error message, which may change
+/// between releases:
+///
+/// ```
+/// use starlark::errors::ErrorCode;
+///
+/// let err = starlark::assert::fail("1 // 0", "division by zero");
+/// assert_eq!(ErrorCode::of(&err), ErrorCode::DivisionByZero);
+/// ```
+///
+/// Errors that have not been assigned a specific code get the code matching their
+/// [`ErrorKind`], e.g. [`ErrorCode::Value`] for an unclassified [`ErrorKind::Value`].
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// An explicit `fail` invocation.
+    Fail,
+    /// Starlark call stack overflow.
+    StackOverflow,
+    /// Syntax error.
+    Parse,
+    /// Reference to a variable which is not defined.
+    UndefinedVariable,
+    /// Reference to a local variable before it was assigned.
+    UnboundVariable,
+    /// Other scope errors.
+    Scope,
+    /// A value does not match its type annotation.
+    TypeAnnotationMismatch,
+    /// Division or modulo by zero.
+    DivisionByZero,
+    /// Integer overflow.
+    IntegerOverflow,
+    /// Index out of bounds.
+    IndexOutOfBounds,
+    /// Key not found in a mapping.
+    KeyNotFound,
+    /// Attribute not found.
+    NoAttribute,
+    /// Operation not supported for the types of its operands.
+    UnsupportedOperation,
+    /// Mutation of an immutable or frozen value, or of a collection being iterated.
+    CannotMutate,
+    /// Other errors associated with a value.
+    Value,
+    /// Wrong arguments in a function call.
+    Function,
+    /// Logic bug in starlark.
+    Internal,
+    /// Error from a user provided native function.
+    Native,
+    /// Errors which have no more specific code.
+    Other,
+}
+
+impl ErrorCode {
+    /// Classify an error.
+    pub fn of(error: &crate::Error) -> ErrorCode {
+        let kind = error.kind();
+        let e = match kind {
+            ErrorKind::Fail(_) => return ErrorCode::Fail,
+            ErrorKind::StackOverflow(_) => return ErrorCode::StackOverflow,
+            ErrorKind::Parser(_) => return ErrorCode::Parse,
+            ErrorKind::Internal(_) => return ErrorCode::Internal,
+            ErrorKind::Native(_) => return ErrorCode::Native,
+            ErrorKind::Value(e)
+            | ErrorKind::Function(e)
+            | ErrorKind::Scope(e)
+            | ErrorKind::Other(e) => without_message_prefix(e),
+            _ => return ErrorCode::Other,
+        };
+        let e = match e.downcast_ref::<ParamError>() {
+            Some(e) => without_message_prefix(&e.error),
+            None => e,
+        };
+        if let Some(e) = e.downcast_ref::<ValueError>() {
+            return match e {
+                ValueError::DivisionByZero => ErrorCode::DivisionByZero,
+                ValueError::IntegerOverflow => ErrorCode::IntegerOverflow,
+                ValueError::IndexOutOfBound(_) | ValueError::IndexOutOfBoundForLength { .. } => {
+                    ErrorCode::IndexOutOfBounds
+                }
+                ValueError::KeyNotFound(_) => ErrorCode::KeyNotFound,
+                ValueError::NoAttr(..) | ValueError::NoAttrDidYouMean(..) => ErrorCode::NoAttribute,
+                ValueError::OperationNotSupported { .. }
+                | ValueError::OperationNotSupportedBinary { .. } => ErrorCode::UnsupportedOperation,
+                ValueError::CannotMutateImmutableValue
+                | ValueError::CannotMutateFrozenValue(_)
+                | ValueError::MutationDuringIteration => ErrorCode::CannotMutate,
+                _ => ErrorCode::Value,
+            };
+        }
+        if let Some(e) = e.downcast_ref::<StarlarkIntError>() {
+            return match e {
+                StarlarkIntError::FloorDivisionByZero(..) | StarlarkIntError::ModuloByZero(..) => {
+                    ErrorCode::DivisionByZero
+                }
+                StarlarkIntError::LeftShiftOverflow => ErrorCode::IntegerOverflow,
+                _ => ErrorCode::Value,
+            };
+        }
+        if let Some(NumError::DivisionByZero(..)) = e.downcast_ref::<NumError>() {
+            return ErrorCode::DivisionByZero;
+        }
+        if let Some(e) = e.downcast_ref::<ScopeError>() {
+            return match e {
+                ScopeError::VariableNotFound(_) | ScopeError::VariableNotFoundDidYouMean(..) => {
+                    ErrorCode::UndefinedVariable
+                }
+                _ => ErrorCode::Scope,
+            };
+        }
+        if let Some(EvaluatorError::LocalVariableReferencedBeforeAssignment(_)) =
+            e.downcast_ref::<EvaluatorError>()
+        {
+            return ErrorCode::UnboundVariable;
+        }
+        if let Some(
+            TypingError::TypeAnnotationMismatch(..) | TypingError::ValueDoesNotMatchType(..),
+        ) = e.downcast_ref::<TypingError>()
+        {
+            return ErrorCode::TypeAnnotationMismatch;
+        }
+        if e.downcast_ref::<FunctionError>().is_some() {
+            return ErrorCode::Function;
+        }
+        match kind {
+            ErrorKind::Value(_) => ErrorCode::Value,
+            ErrorKind::Function(_) => ErrorCode::Function,
+            ErrorKind::Scope(_) => ErrorCode::Scope,
+            _ => ErrorCode::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+    use crate::assert::Assert;
+    use crate::errors::ErrorCode;
+    use crate::syntax::Dialect;
+    use crate::syntax::DialectTypes;
+
+    fn code(program: &str, msg: &str) -> ErrorCode {
+        ErrorCode::of(&assert::fail(program, msg))
+    }
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(ErrorCode::Fail, code("fail('x')", "x"));
+        assert_eq!(ErrorCode::Parse, code("1 +", "Parse error"));
+        assert_eq!(
+            ErrorCode::UndefinedVariable,
+            code("undefined", "Variable `undefined` not found")
+        );
+        assert_eq!(
+            ErrorCode::UnboundVariable,
+            code(
+                "def f():\n  x\n  x = 1\nf()",
+                "referenced before assignment"
+            )
+        );
+        assert_eq!(
+            ErrorCode::DivisionByZero,
+            code("1 // 0", "division by zero")
+        );
+        assert_eq!(ErrorCode::IndexOutOfBounds, code("[1][1]", "out of bound"));
+        assert_eq!(ErrorCode::DivisionByZero, code("1 % 0", "Modulo by zero"));
+        assert_eq!(
+            ErrorCode::DivisionByZero,
+            code("1 / 0", "float division by zero")
+        );
+        assert_eq!(ErrorCode::KeyNotFound, code("{}[1]", "not found"));
+        assert_eq!(ErrorCode::NoAttribute, code("[].foo", "has no attribute"));
+        assert_eq!(
+            ErrorCode::UnsupportedOperation,
+            code("1 + 'a'", "not supported")
+        );
+        assert_eq!(
+            ErrorCode::Function,
+            code("def f(): pass\nf(1)", "extra positional")
+        );
+        assert_eq!(
+            ErrorCode::StackOverflow,
+            code("def f(): f()\nf()", "Starlark call stack overflow")
+        );
+        assert_eq!(
+            ErrorCode::DivisionByZero,
+            code(
+                "sorted([1, 0], key=lambda x: 1 // x)",
+                "Calling `key` on element 1: Floor division by zero: 1 // 0"
+            )
+        );
+    }
+
+    #[test]
+    fn test_error_code_type_annotation() {
+        let mut a = Assert::new();
+        a.dialect(&Dialect {
+            enable_types: DialectTypes::Enable,
+            ..Dialect::Standard
+        });
+        let err = a.fail(
+            "def f(x: int): pass\nf('test')",
+            "does not match the type annotation",
+        );
+        assert_eq!(ErrorCode::TypeAnnotationMismatch, ErrorCode::of(&err));
+    }
+}
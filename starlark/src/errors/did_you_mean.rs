@@ -19,13 +19,20 @@
 
 use strsim::levenshtein;
 
-/// Find a suggestion for a typo.
+/// Maximum number of suggestions in an error message.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Find suggestions for a typo, closest first.
+///
+/// Returns at most three distinct suggestions, preferring earlier variants
+/// when the distance is the same. To avoid noise, suggestions after the first
+/// are only returned if they are a single edit away from `value`.
 pub(crate) fn did_you_mean<'a>(
     value: &str,
     variants: impl IntoIterator<Item = &'a str>,
-) -> Option<&'a str> {
+) -> Vec<&'a str> {
     if value.is_empty() {
-        return None;
+        return Vec::new();
     }
 
     let max_dist = if value.len() <= 2 {
@@ -35,61 +42,138 @@ pub(crate) fn did_you_mean<'a>(
         2
     };
 
-    variants
+    let mut candidates: Vec<(&str, usize)> = Vec::new();
+    for v in variants {
+        let dist = levenshtein(value, v);
+        if dist <= max_dist && !candidates.iter().any(|(c, _)| *c == v) {
+            candidates.push((v, dist));
+        }
+    }
+    // Stable sort, so earlier variants win ties.
+    candidates.sort_by_key(|(_, dist)| *dist);
+    candidates
         .into_iter()
-        .map(|v| (v, levenshtein(value, v)))
-        .filter(|(_, dist)| *dist <= max_dist)
-        .min_by_key(|(_v, sim)| *sim)
-        .map(|(v, _)| v)
+        .enumerate()
+        .filter(|(i, (_, dist))| *i == 0 || *dist <= 1)
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, (v, _))| v)
+        .collect()
+}
+
+/// Format suggestions found by [`did_you_mean`] for an error message,
+/// e.g. `` `a` `` or `` `a`, `b` or `c` ``. Returns `None` if there are no suggestions.
+pub(crate) fn format_did_you_mean(suggestions: &[&str]) -> Option<String> {
+    match suggestions {
+        [] => None,
+        [x] => Some(format!("`{x}`")),
+        [xs @ .., last] => Some(format!(
+            "{} or `{last}`",
+            xs.iter()
+                .map(|x| format!("`{x}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::errors::did_you_mean::did_you_mean;
+    use crate::errors::did_you_mean::format_did_you_mean;
 
     #[test]
     fn prefixes() {
         assert_eq!(
-            Some("cxx_library"),
+            vec!["cxx_library"],
             did_you_mean("cxx_librar", vec!["cxx_library"])
         );
         assert_eq!(
-            Some("cxx_library"),
+            vec!["cxx_library"],
             did_you_mean("cxx_libra", vec!["cxx_library"])
         );
-        assert_eq!(None, did_you_mean("cxx_libr", vec!["cxx_library"]));
+        assert_eq!(
+            Vec::<&str>::new(),
+            did_you_mean("cxx_libr", vec!["cxx_library"])
+        );
     }
 
     #[test]
     fn typos() {
         assert_eq!(
-            Some("cxx_library"),
+            vec!["cxx_library"],
             did_you_mean("cxx_librarx", vec!["cxx_library"])
         );
         assert_eq!(
-            Some("cxx_library"),
+            vec!["cxx_library"],
             did_you_mean("cxx_libraxx", vec!["cxx_library"])
         );
-        assert_eq!(None, did_you_mean("cxx_librxxx", vec!["cxx_library"]));
+        assert_eq!(
+            Vec::<&str>::new(),
+            did_you_mean("cxx_librxxx", vec!["cxx_library"])
+        );
     }
 
     #[test]
     fn best() {
-        assert_eq!(Some("abc"), did_you_mean("abx", vec!["abc", "abcd"]));
+        assert_eq!(vec!["abc"], did_you_mean("abx", vec!["abcd", "abc"]));
+        assert_eq!(vec!["abcd"], did_you_mean("abxd", vec!["abc", "abcd"]));
     }
 
     #[test]
     fn very_short() {
-        assert_eq!(Some("a"), did_you_mean("b", vec!["a"]));
-        assert_eq!(Some("ab"), did_you_mean("b", vec!["ab"]));
-        assert_eq!(None, did_you_mean("b", vec!["cd"]));
+        assert_eq!(vec!["a"], did_you_mean("b", vec!["a"]));
+        assert_eq!(vec!["ab"], did_you_mean("b", vec!["ab"]));
+        assert_eq!(Vec::<&str>::new(), did_you_mean("b", vec!["cd"]));
 
-        assert_eq!(None, did_you_mean("bc", vec!["de"]));
+        assert_eq!(Vec::<&str>::new(), did_you_mean("bc", vec!["de"]));
     }
 
     #[test]
     fn earlier_variants_are_more_important() {
-        assert_eq!(Some("aaaay"), did_you_mean("aaaax", vec!["aaaay", "aaaaz"]));
-        assert_eq!(Some("aaaaz"), did_you_mean("aaaax", vec!["aaaaz", "aaaay"]));
+        assert_eq!(
+            vec!["aaaay", "aaaaz"],
+            did_you_mean("aaaax", vec!["aaaay", "aaaaz"])
+        );
+        assert_eq!(
+            vec!["aaaaz", "aaaay"],
+            did_you_mean("aaaax", vec!["aaaaz", "aaaay"])
+        );
+    }
+
+    #[test]
+    fn at_most_three_distinct() {
+        assert_eq!(
+            vec!["aaaa1", "aaaa2", "aaaa3"],
+            did_you_mean(
+                "aaaax",
+                vec!["aaaa1", "aaaa1", "aaaa2", "aaa12", "aaaa3", "aaaa4"]
+            )
+        );
+    }
+
+    #[test]
+    fn only_close_alternatives() {
+        assert_eq!(
+            vec!["abcd", "abxe"],
+            did_you_mean("abxd", vec!["abc", "abcd", "abxe"])
+        );
+        assert_eq!(
+            vec!["value"],
+            did_you_mean("val", vec!["value", "all", "fail"])
+        );
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(None, format_did_you_mean(&[]));
+        assert_eq!(Some("`a`".to_owned()), format_did_you_mean(&["a"]));
+        assert_eq!(
+            Some("`a` or `b`".to_owned()),
+            format_did_you_mean(&["a", "b"])
+        );
+        assert_eq!(
+            Some("`a`, `b` or `c`".to_owned()),
+            format_did_you_mean(&["a", "b", "c"])
+        );
     }
 }
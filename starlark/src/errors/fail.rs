@@ -0,0 +1,69 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Error produced by the `fail` function.
+
+/// The error raised by a call to `fail` in Starlark.
+///
+/// It is stored in [`ErrorKind::Fail`](crate::ErrorKind::Fail), so native code can
+/// recover the arguments of `fail` by downcasting:
+///
+/// ```
+/// use starlark::errors::FailError;
+/// use starlark::ErrorKind;
+///
+/// let err = starlark::assert::fail("fail('expected', 1, 'got', 2)", "expected 1 got 2");
+/// let ErrorKind::Fail(e) = err.kind() else {
+///     panic!("not a `fail` error");
+/// };
+/// let fail = e.downcast_ref::<FailError>().unwrap();
+/// assert_eq!(fail.message(), "expected 1 got 2");
+/// assert_eq!(fail.args(), ["expected", "1", "got", "2"]);
+/// ```
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+pub struct FailError {
+    message: String,
+    args: Vec<String>,
+    arg_repr: Option<String>,
+}
+
+impl FailError {
+    pub(crate) fn new(message: String, args: Vec<String>, arg_repr: Option<String>) -> FailError {
+        FailError {
+            message,
+            args,
+            arg_repr,
+        }
+    }
+
+    /// The arguments converted with `str` and joined with the separator.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Each argument converted with `str`.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// The `repr` of the argument, if `fail` was called with a single non-string argument,
+    /// e.g. `"abc"` for `fail(["abc"])` rather than `abc`.
+    pub fn arg_repr(&self) -> Option<&str> {
+        self.arg_repr.as_deref()
+    }
+}
@@ -19,6 +19,7 @@
 //! [`eval_module`](Evaluator::eval_module).
 
 pub(crate) mod bc;
+mod compiled_module;
 pub(crate) mod compiler;
 mod params;
 pub(crate) mod runtime;
@@ -29,28 +30,43 @@ use std::mem;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
 
+pub use compiled_module::AstModuleCompile;
+pub use compiled_module::CompiledModule;
 use dupe::Dupe;
 pub use runtime::arguments::Arguments;
 pub use runtime::before_stmt::BeforeStmtFuncDyn;
+pub use runtime::coverage::CoverageData;
+pub use runtime::evaluator::AttrAccess;
+pub use runtime::evaluator::AttrHookDecision;
+pub use runtime::evaluator::CallHookEvent;
 pub use runtime::evaluator::Evaluator;
 pub use runtime::file_loader::FileLoader;
+pub use runtime::file_loader::LoadContext;
 pub use runtime::file_loader::ReturnFileLoader;
+pub use runtime::module_cache::ModuleCache;
 pub use runtime::params::parser::ParametersParser;
 pub use runtime::params::spec::ParametersSpec;
 pub use runtime::params::spec::ParametersSpecParam;
 pub use runtime::profile::data::ProfileData;
 pub use runtime::profile::mode::ProfileMode;
+pub use soft_error::SoftError;
 pub use soft_error::SoftErrorHandler;
 pub use starlark_syntax::call_stack::CallStack;
+use starlark_syntax::eval_exception::EvalException;
 use starlark_syntax::slice_vec_ext::SliceExt;
 use starlark_syntax::syntax::module::AstModule;
 use starlark_syntax::syntax::module::AstModuleFields;
 
+use crate::codemap::CodeMap;
 use crate::collections::symbol::symbol::Symbol;
+use crate::collections::Hashed;
+use crate::collections::SmallMap;
 use crate::docs::DocString;
 use crate::environment::Globals;
+use crate::environment::Module;
 use crate::eval::compiler::def::DefInfo;
 use crate::eval::compiler::scope::scope_resolver_globals::ScopeResolverGlobals;
+use crate::eval::compiler::scope::visit_top_level_assigns;
 use crate::eval::compiler::scope::ModuleScopes;
 use crate::eval::compiler::scope::ScopeId;
 use crate::eval::compiler::Compiler;
@@ -59,8 +75,22 @@ use crate::eval::runtime::arguments::ArgNames;
 use crate::eval::runtime::arguments::ArgumentsFull;
 use crate::eval::runtime::evaluator;
 use crate::syntax::DialectTypes;
+use crate::values::recursive_repr_or_json_guard::ReprLimitsGuard;
+use crate::values::FrozenRef;
 use crate::values::Value;
 
+/// The result of [`Evaluator::eval_module_ret`].
+#[derive(Debug)]
+pub struct EvalModuleResult<'v> {
+    /// The value of the last statement if it is an expression, otherwise Starlark `None`.
+    pub value: Value<'v>,
+    /// The module the code was evaluated in.
+    pub module: &'v Module,
+    /// Soft errors reported with [`Evaluator::soft_error`] which the
+    /// [`SoftErrorHandler`] accepted, in the order they occurred.
+    pub soft_errors: Vec<SoftError>,
+}
+
 impl<'v, 'a, 'e> Evaluator<'v, 'a, 'e> {
     /// Evaluate an [`AstModule`] with this [`Evaluator`], modifying the in-scope
     /// [`Module`](crate::environment::Module) as appropriate.
@@ -70,6 +100,8 @@ impl<'v, 'a, 'e> Evaluator<'v, 'a, 'e> {
 
         let (codemap, statement, dialect, typecheck) = ast.into_parts();
 
+        self.stmt_coverage.add_module(&codemap, &statement);
+
         let codemap = self.module_env.frozen_heap().alloc_any(codemap.dupe());
 
         let globals = self.module_env.frozen_heap().alloc_any(globals.dupe());
@@ -78,12 +110,7 @@ impl<'v, 'a, 'e> Evaluator<'v, 'a, 'e> {
             self.module_env.set_docstring(docstring)
         }
 
-        let ModuleScopes {
-            cst,
-            module_slot_count,
-            scope_data,
-            top_level_stmt_count,
-        } = ModuleScopes::check_module_err(
+        let scopes = ModuleScopes::check_module_err(
             self.module_env.mutable_names(),
             self.module_env.frozen_heap(),
             &HashMap::new(),
@@ -93,60 +120,194 @@ impl<'v, 'a, 'e> Evaluator<'v, 'a, 'e> {
             },
             codemap,
             &dialect,
+            self.strict_names,
         )?;
 
+        self.trace_event(
+            || serde_json::json!({"event": "module_start", "path": codemap.filename()}),
+        );
+
+        let res = self.eval_module_scopes(
+            scopes,
+            codemap,
+            globals,
+            dialect.enable_types == DialectTypes::Enable,
+            typecheck,
+        );
+
+        self.trace_event(|| serde_json::json!({"event": "module_end", "path": codemap.filename()}));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.module_env.add_eval_duration(start.elapsed());
+
+        res
+    }
+
+    /// Like [`eval_module`](Evaluator::eval_module), but return the module
+    /// and the soft errors accepted during evaluation along with the value.
+    pub fn eval_module_ret(
+        &mut self,
+        ast: AstModule,
+        globals: &Globals,
+    ) -> crate::Result<EvalModuleResult<'v>> {
+        let old_soft_errors = self.soft_errors.replace(Vec::new());
+        let res = self.eval_module(ast, globals);
+        let soft_errors = mem::replace(&mut self.soft_errors, old_soft_errors).unwrap_or_default();
+        Ok(EvalModuleResult {
+            value: res?,
+            module: self.module_env,
+            soft_errors,
+        })
+    }
+
+    /// Like [`eval_module`](Evaluator::eval_module), but return the variables
+    /// the module defines at the top level, in the order they are first defined.
+    ///
+    /// Names starting with `_` are skipped, as are names bound by `load`
+    /// unless `include_loads` is set. A name whose assignment never ran
+    /// (e.g. in an `if` branch not taken) is skipped too.
+    ///
+    /// The values are the live values stored in the module, not copies:
+    /// mutating one, e.g. appending to a list, is visible through the module,
+    /// and later evaluation in the same module may change them.
+    pub fn eval_module_exports(
+        &mut self,
+        ast: AstModule,
+        globals: &Globals,
+        include_loads: bool,
+    ) -> crate::Result<SmallMap<String, Value<'v>>> {
+        let mut names = Vec::new();
+        visit_top_level_assigns(ast.statement(), &mut |x, load| {
+            names.push((x.ident.clone(), load))
+        });
+        self.eval_module(ast, globals)?;
+        let mut res = SmallMap::new();
+        for (name, load) in names {
+            if name.starts_with('_') || (load && !include_loads) || res.contains_key(&name) {
+                continue;
+            }
+            if let Some((value, _)) = self
+                .module_env
+                .get_any_visibility(Hashed::new(name.as_str()))
+            {
+                res.insert(name, value);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Generate and run the code of a module after name resolution.
+    fn eval_module_scopes(
+        &mut self,
+        scopes: ModuleScopes<'v>,
+        codemap: FrozenRef<'static, CodeMap>,
+        globals: FrozenRef<'static, Globals>,
+        check_types: bool,
+        typecheck: bool,
+    ) -> crate::Result<Value<'v>> {
+        let ModuleScopes {
+            cst,
+            module_slot_count,
+            scope_data,
+            top_level_stmt_count,
+        } = scopes;
+
         let scope_names = scope_data.get_scope(ScopeId::module());
         let local_names = self.frozen_heap().alloc_any_slice(&scope_names.used);
+        let module_def_info = self.module_env.frozen_heap().alloc_any(DefInfo::for_module(
+            codemap,
+            local_names,
+            self.module_env
+                .frozen_heap()
+                .alloc_any_slice(&scope_names.parent),
+            globals,
+        ));
 
-        self.module_env.slots().ensure_slots(module_slot_count);
-        let old_def_info = mem::replace(
-            &mut self.module_def_info,
-            self.module_env.frozen_heap().alloc_any(DefInfo::for_module(
-                codemap,
-                local_names,
-                self.module_env
-                    .frozen_heap()
-                    .alloc_any_slice(&scope_names.parent),
+        self.with_module_frame(module_slot_count, module_def_info, |eval| {
+            let mut compiler = Compiler {
+                scope_data,
+                locals: Vec::new(),
                 globals,
-            )),
-        );
+                codemap,
+                eval,
+                check_types,
+                top_level_stmt_count,
+                typecheck,
+            };
+            compiler.eval_module(cst, local_names)
+        })
+    }
+
+    /// Set up the module frame for `eval`, which compiles or runs the top-level statements.
+    pub(crate) fn with_module_frame<R>(
+        &mut self,
+        module_slot_count: u32,
+        module_def_info: FrozenRef<'static, DefInfo>,
+        eval: impl FnOnce(&mut Self) -> Result<R, EvalException>,
+    ) -> crate::Result<R> {
+        self.module_env.slots().ensure_slots(module_slot_count);
 
         self.call_stack.alloc_if_needed(
             self.max_callstack_size
                 .unwrap_or(evaluator::DEFAULT_STACK_SIZE),
         )?;
 
+        let old_def_info = mem::replace(&mut self.module_def_info, module_def_info);
+
         // Set up the world to allow evaluation (do NOT use ? from now on)
 
+        let _repr_limits = ReprLimitsGuard::set(self.repr_limits);
+
         self.call_stack.push(Value::new_none(), None).unwrap();
 
         // Evaluation
-        let mut compiler = Compiler {
-            scope_data,
-            locals: Vec::new(),
-            globals,
-            codemap,
-            eval: self,
-            check_types: dialect.enable_types == DialectTypes::Enable,
-            top_level_stmt_count,
-            typecheck,
-        };
-
-        let res = compiler.eval_module(cst, local_names);
+        let res = eval(self);
 
         // Clean up the world, putting everything back
         self.call_stack.pop();
 
         self.module_def_info = old_def_info;
 
-        #[cfg(not(target_arch = "wasm32"))]
-        self.module_env.add_eval_duration(start.elapsed());
-
         // Return the result of evaluation
         res.map_err(|e| e.into_error())
     }
 
     /// Evaluate a function stored in a [`Value`], passing in `positional` and `named` arguments.
+    ///
+    /// This may be called from a native function which was itself called from Starlark,
+    /// for example to implement a higher-order builtin. The callback runs on the same heap,
+    /// shares the call stack (so the stack size limit applies and errors include the frames
+    /// of the caller), and can return values allocated on the heap.
+    /// Take the evaluator as `eval: &mut Evaluator<'v, '_, '_>` and keep the values
+    /// as `Value<'v>`, so they can be passed to the callback and returned from the function:
+    ///
+    /// ```
+    /// use starlark::environment::GlobalsBuilder;
+    /// use starlark::eval::Evaluator;
+    /// use starlark::starlark_module;
+    /// use starlark::values::list::AllocList;
+    /// use starlark::values::list::UnpackList;
+    /// use starlark::values::Value;
+    ///
+    /// #[starlark_module]
+    /// fn apply_each_module(builder: &mut GlobalsBuilder) {
+    ///     fn apply_each<'v>(
+    ///         f: Value<'v>,
+    ///         xs: UnpackList<Value<'v>>,
+    ///         eval: &mut Evaluator<'v, '_, '_>,
+    ///     ) -> starlark::Result<AllocList<Vec<Value<'v>>>> {
+    ///         let mut res = Vec::new();
+    ///         for x in xs.items {
+    ///             res.push(eval.eval_function(f, &[x], &[])?);
+    ///         }
+    ///         Ok(AllocList(res))
+    ///     }
+    /// }
+    ///
+    /// # let mut a = starlark::assert::Assert::new();
+    /// # a.globals_add(apply_each_module);
+    /// # a.pass("assert_eq(apply_each(lambda x: x * 2, [1, 2]), [2, 4])");
+    /// ```
     pub fn eval_function(
         &mut self,
         function: Value<'v>,
@@ -166,9 +327,15 @@ impl<'v, 'a, 'e> Evaluator<'v, 'a, 'e> {
             self.max_callstack_size
                 .unwrap_or(evaluator::DEFAULT_STACK_SIZE),
         )?;
+        let _repr_limits = ReprLimitsGuard::set(self.repr_limits);
+        if self.call_stack.count() != 0 {
+            // Called from a native function during evaluation,
+            // so the frames of the caller are already on the stack.
+            return function.invoke(&params, self);
+        }
         // eval_module pushes an "empty" call stack frame. other places expect that first frame to be ignorable, and
         // so we push an empty frame too (otherwise things would ignore this function's own frame).
-        self.with_call_stack(Value::new_none(), None, |this| {
+        self.with_call_stack(Value::new_none(), None, &params.0, |this| {
             function.invoke(&params, this)
         })
         .map_err(Into::into)
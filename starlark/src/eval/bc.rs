@@ -29,6 +29,7 @@ pub(crate) mod instr;
 pub(crate) mod instr_arg;
 pub(crate) mod instr_impl;
 pub(crate) mod instrs;
+pub(crate) mod member_cache;
 pub(crate) mod native_function;
 pub(crate) mod opcode;
 pub(crate) mod repr;
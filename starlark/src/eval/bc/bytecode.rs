@@ -34,6 +34,7 @@ use crate::eval::bc::slow_arg::BcInstrEndArg;
 use crate::eval::bc::slow_arg::BcInstrSlowArg;
 use crate::eval::compiler::add_span_to_expr_error;
 use crate::eval::runtime::evaluator::EvaluationCallbacks;
+use crate::eval::runtime::frame_span::FrameSpan;
 use crate::eval::Evaluator;
 use crate::values::Value;
 
@@ -84,10 +85,39 @@ impl Bc {
         e: crate::Error,
         eval: &Evaluator,
     ) -> EvalException {
-        let span = Self::slow_arg_at_ptr(ptr).span;
+        let slow_arg = Self::slow_arg_at_ptr(ptr);
+        let e = match slow_arg.access {
+            Some(access) if eval.verbose_errors => e.with_message_prefix(format_args!(
+                "At `{}`",
+                access.span.file_span_ref().source_span()
+            )),
+            _ => e,
+        };
+        let span = match slow_arg {
+            BcInstrSlowArg {
+                args: Some(args), ..
+            } if Self::is_call_error(&e, slow_arg.span) => args.span_for_error(&e),
+            BcInstrSlowArg {
+                bin_op: Some(bin_op),
+                ..
+            } => bin_op.span_for_error(&e, eval.current_frame),
+            _ => None,
+        }
+        .unwrap_or(slow_arg.span);
         add_span_to_expr_error(e, span, eval)
     }
 
+    /// Error was raised by the call at `span` itself (e.g. binding the arguments),
+    /// not by the code it called.
+    fn is_call_error(e: &crate::Error, span: FrameSpan) -> bool {
+        e.span().is_none()
+            && e.call_stack()
+                .frames
+                .last()
+                .and_then(|f| f.location.as_ref())
+                == Some(&span.span.to_file_span())
+    }
+
     /// Run the bytecode in the current frame allocated in the evaluator.
     ///
     /// Frame must be allocated properly, otherwise it will likely result in memory corruption.
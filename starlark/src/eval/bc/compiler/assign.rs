@@ -20,9 +20,11 @@
 use starlark_syntax::slice_vec_ext::SliceExt;
 
 use crate::collections::symbol::symbol::Symbol;
+use crate::eval::bc::compiler::expr::write_expr_opt;
 use crate::eval::bc::compiler::expr::write_n_exprs;
 use crate::eval::bc::instr_impl::InstrSetArrayIndex;
 use crate::eval::bc::instr_impl::InstrSetObjectField;
+use crate::eval::bc::instr_impl::InstrSetSlice;
 use crate::eval::bc::instr_impl::InstrStoreModuleAndExport;
 use crate::eval::bc::instr_impl::InstrUnpack;
 use crate::eval::bc::stack_ptr::BcSlotIn;
@@ -44,6 +46,13 @@ impl AssignCompiledValue {
                 array.mark_definitely_assigned_after(bc);
                 index.mark_definitely_assigned_after(bc);
             }
+            AssignCompiledValue::Slice(array_start_stop_step) => {
+                let (array, start, stop, step) = &**array_start_stop_step;
+                array.mark_definitely_assigned_after(bc);
+                for x in [start, stop, step].into_iter().flatten() {
+                    x.mark_definitely_assigned_after(bc);
+                }
+            }
             AssignCompiledValue::LocalCaptured(_slot) => {}
             AssignCompiledValue::Local(slot) => {
                 bc.mark_definitely_assigned(*slot);
@@ -72,6 +81,21 @@ impl IrSpanned<AssignCompiledValue> {
                     bc.write_instr::<InstrSetArrayIndex>(span, (value, array, index));
                 });
             }
+            AssignCompiledValue::Slice(ref array_start_stop_step) => {
+                let (array, start, stop, step) = &**array_start_stop_step;
+                array.write_bc_cb(bc, |array, bc| {
+                    write_expr_opt(start, bc, |start, bc| {
+                        write_expr_opt(stop, bc, |stop, bc| {
+                            write_expr_opt(step, bc, |step, bc| {
+                                bc.write_instr::<InstrSetSlice>(
+                                    span,
+                                    (value, array, start, stop, step),
+                                )
+                            })
+                        })
+                    })
+                });
+            }
             AssignCompiledValue::Tuple(ref xs) => {
                 // All assignments are to local variables, e. g.
                 // ```
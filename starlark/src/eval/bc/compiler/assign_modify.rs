@@ -38,6 +38,7 @@ use crate::eval::bc::instr_impl::InstrRightShift;
 use crate::eval::bc::instr_impl::InstrSetObjectField;
 use crate::eval::bc::instr_impl::InstrStoreModule;
 use crate::eval::bc::instr_impl::InstrSub;
+use crate::eval::bc::member_cache::MemberCache;
 use crate::eval::bc::stack_ptr::BcSlotIn;
 use crate::eval::bc::stack_ptr::BcSlotOut;
 use crate::eval::bc::stack_ptr::BcSlotsN;
@@ -113,7 +114,12 @@ impl AssignModifyLhs {
                         let field = Symbol::new(field.as_str());
                         bc.write_instr::<InstrObjectField>(
                             span,
-                            (object, field.clone(), lhs_rhs.get::<0>().to_out()),
+                            (
+                                object,
+                                field.clone(),
+                                MemberCache::default(),
+                                lhs_rhs.get::<0>().to_out(),
+                            ),
                         );
                         rhs.write_bc(lhs_rhs.get::<1>().to_out(), bc);
                         op.write_bc(
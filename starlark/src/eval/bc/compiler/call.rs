@@ -39,7 +39,10 @@ use crate::eval::bc::instr_impl::InstrCallPos;
 use crate::eval::bc::instr_impl::InstrIsInstance;
 use crate::eval::bc::instr_impl::InstrLen;
 use crate::eval::bc::instr_impl::InstrType;
+use crate::eval::bc::member_cache::MemberCache;
 use crate::eval::bc::native_function::BcNativeFunction;
+use crate::eval::bc::slow_arg::BcCallArgSpans;
+use crate::eval::bc::slow_arg::BcInstrSlowArg;
 use crate::eval::bc::stack_ptr::BcSlotOut;
 use crate::eval::bc::writer::BcWriter;
 use crate::eval::compiler::args::ArgsCompiledValue;
@@ -55,6 +58,24 @@ use crate::values::FrozenValue;
 use crate::values::FrozenValueTyped;
 
 impl ArgsCompiledValue {
+    /// Spans of the arguments, used to point errors at the argument which caused them.
+    fn spans(&self) -> BcCallArgSpans {
+        let (pos, named) = self
+            .pos_named
+            .split_at(self.pos_named.len() - self.names.len());
+        BcCallArgSpans {
+            pos: pos.iter().map(|a| a.span).collect(),
+            named: self
+                .names
+                .iter()
+                .zip(named)
+                .map(|((_, name), a)| (name.as_str().to_owned(), a.span))
+                .collect(),
+            args: self.args.as_ref().map(|a| a.span),
+            kwargs: self.kwargs.as_ref().map(|a| a.span),
+        }
+    }
+
     /// After evaluation of function arguments like `foo(a, b=c[d], **e)`,
     /// variables `a`, `b`, `c`, `d`, and `e` are definitely assigned.
     fn mark_definitely_assigned_after(&self, bc: &mut BcWriter) {
@@ -104,6 +125,14 @@ impl CallCompiled {
 }
 
 impl IrSpanned<CallCompiled> {
+    fn slow_arg(span: FrameSpan, args: &ArgsCompiledValue) -> BcInstrSlowArg {
+        BcInstrSlowArg {
+            span,
+            args: Some(Box::new(args.spans())),
+            ..Default::default()
+        }
+    }
+
     fn write_args(
         args: &ArgsCompiledValue,
         bc: &mut BcWriter,
@@ -130,13 +159,15 @@ impl IrSpanned<CallCompiled> {
         bc: &mut BcWriter,
     ) {
         let file_span = bc.alloc_file_span(span);
+        let slow_arg = Self::slow_arg(span, args);
         if let Some(fun) = FrozenValueTyped::<FrozenDef>::new(fun) {
             Self::write_args(args, bc, |args, bc| match args {
-                Either::Left(npops) => {
-                    bc.write_instr::<InstrCallFrozenDefPos>(span, (fun, npops, file_span, target))
-                }
-                Either::Right(args) => bc.write_instr::<InstrCallFrozenDef>(
-                    span,
+                Either::Left(npops) => bc.write_instr_explicit::<InstrCallFrozenDefPos>(
+                    slow_arg,
+                    (fun, npops, file_span, target),
+                ),
+                Either::Right(args) => bc.write_instr_explicit::<InstrCallFrozenDef>(
+                    slow_arg,
                     (fun, args.resolve(fun.as_ref()), file_span, target),
                 ),
             })
@@ -144,22 +175,31 @@ impl IrSpanned<CallCompiled> {
             let fun = BcNativeFunction::new(fun);
             Self::write_args(args, bc, |args, bc| match args {
                 Either::Left(npops) => {
-                    bc.write_instr::<InstrCallFrozenNativePos>(
-                        span,
+                    bc.write_instr_explicit::<InstrCallFrozenNativePos>(
+                        slow_arg,
                         (fun, npops, file_span, target),
                     );
                 }
                 Either::Right(args) => {
-                    bc.write_instr::<InstrCallFrozenNative>(span, (fun, args, file_span, target));
+                    bc.write_instr_explicit::<InstrCallFrozenNative>(
+                        slow_arg,
+                        (fun, args, file_span, target),
+                    );
                 }
             })
         } else {
             Self::write_args(args, bc, |args, bc| match args {
                 Either::Left(npops) => {
-                    bc.write_instr::<InstrCallFrozenPos>(span, (fun, npops, file_span, target));
+                    bc.write_instr_explicit::<InstrCallFrozenPos>(
+                        slow_arg,
+                        (fun, npops, file_span, target),
+                    );
                 }
                 Either::Right(args) => {
-                    bc.write_instr::<InstrCallFrozen>(span, (fun, args, file_span, target));
+                    bc.write_instr_explicit::<InstrCallFrozen>(
+                        slow_arg,
+                        (fun, args, file_span, target),
+                    );
                 }
             })
         }
@@ -173,29 +213,32 @@ impl IrSpanned<CallCompiled> {
         args: &ArgsCompiledValue,
         bc: &mut BcWriter,
     ) {
+        let slow_arg = Self::slow_arg(span, args);
         this.write_bc_cb(bc, |this, bc| {
             let file_span = bc.alloc_file_span(span);
             let known_method = get_known_method(symbol.as_str());
             if let Some(pos) = args.pos_only() {
                 write_exprs(pos, bc, |pos, bc| {
                     if let Some(known_method) = known_method {
-                        bc.write_instr::<InstrCallMaybeKnownMethodPos>(
-                            span,
+                        bc.write_instr_explicit::<InstrCallMaybeKnownMethodPos>(
+                            slow_arg,
                             (
                                 this,
                                 symbol.clone(),
                                 known_method,
+                                MemberCache::default(),
                                 BcCallArgsPos { pos },
                                 file_span,
                                 target,
                             ),
                         );
                     } else {
-                        bc.write_instr::<InstrCallMethodPos>(
-                            span,
+                        bc.write_instr_explicit::<InstrCallMethodPos>(
+                            slow_arg,
                             (
                                 this,
                                 symbol.clone(),
+                                MemberCache::default(),
                                 BcCallArgsPos { pos },
                                 file_span,
                                 target,
@@ -206,14 +249,29 @@ impl IrSpanned<CallCompiled> {
             } else {
                 args.write_bc(bc, |args, bc| {
                     if let Some(known_method) = known_method {
-                        bc.write_instr::<InstrCallMaybeKnownMethod>(
-                            span,
-                            (this, symbol.clone(), known_method, args, file_span, target),
+                        bc.write_instr_explicit::<InstrCallMaybeKnownMethod>(
+                            slow_arg,
+                            (
+                                this,
+                                symbol.clone(),
+                                known_method,
+                                MemberCache::default(),
+                                args,
+                                file_span,
+                                target,
+                            ),
                         );
                     } else {
-                        bc.write_instr::<InstrCallMethod>(
-                            span,
-                            (this, symbol.clone(), args, file_span, target),
+                        bc.write_instr_explicit::<InstrCallMethod>(
+                            slow_arg,
+                            (
+                                this,
+                                symbol.clone(),
+                                MemberCache::default(),
+                                args,
+                                file_span,
+                                target,
+                            ),
                         );
                     }
                 })
@@ -244,12 +302,18 @@ impl IrSpanned<CallCompiled> {
             None => match self.fun.as_value() {
                 Some(f) => Self::write_call_frozen(span, f, &self.args, target, bc),
                 None => {
+                    let slow_arg = Self::slow_arg(span, &self.args);
                     self.fun.write_bc_cb(bc, |fun, bc| {
                         Self::write_args(&self.args, bc, |args, bc| match args {
-                            Either::Left(npops) => bc
-                                .write_instr::<InstrCallPos>(span, (fun, npops, file_span, target)),
+                            Either::Left(npops) => bc.write_instr_explicit::<InstrCallPos>(
+                                slow_arg,
+                                (fun, npops, file_span, target),
+                            ),
                             Either::Right(args) => {
-                                bc.write_instr::<InstrCall>(span, (fun, args, file_span, target));
+                                bc.write_instr_explicit::<InstrCall>(
+                                    slow_arg,
+                                    (fun, args, file_span, target),
+                                );
                             }
                         })
                     });
@@ -21,9 +21,12 @@ use crate::eval::bc::compiler::expr::write_n_exprs;
 use crate::eval::bc::compiler::if_compiler::write_if_then;
 use crate::eval::bc::compiler::stmt::write_for;
 use crate::eval::bc::instr_impl::InstrComprDictInsert;
+use crate::eval::bc::instr_impl::InstrComprDictNew;
 use crate::eval::bc::instr_impl::InstrComprListAppend;
+use crate::eval::bc::instr_impl::InstrComprListNew;
 use crate::eval::bc::instr_impl::InstrDictNew;
 use crate::eval::bc::instr_impl::InstrListNew;
+use crate::eval::bc::stack_ptr::BcSlotIn;
 use crate::eval::bc::stack_ptr::BcSlotOut;
 use crate::eval::bc::writer::BcWriter;
 use crate::eval::compiler::compr::ClauseCompiled;
@@ -31,27 +34,46 @@ use crate::eval::compiler::compr::ComprCompiled;
 use crate::eval::compiler::expr::MaybeNot;
 use crate::eval::runtime::frame_span::FrameSpan;
 
+/// Nested clauses do nothing before their loop. A function rather than a closure
+/// so recursive `write_bc` instantiation terminates.
+fn no_before_loop(_over: BcSlotIn, _bc: &mut BcWriter) {}
+
 impl ClauseCompiled {
+    /// Comprehension with this single clause and no `if`s evaluates its
+    /// expression once per item of the iterable, so the result collection
+    /// can be allocated with the iterable length as capacity.
+    fn produces_one_per_item(&self, rem: &[ClauseCompiled]) -> bool {
+        rem.is_empty() && self.ifs.is_empty()
+    }
+
     fn write_bc(
         &self,
         bc: &mut BcWriter,
         rem: &[ClauseCompiled],
+        before_loop: impl FnOnce(BcSlotIn, &mut BcWriter),
         term: impl FnOnce(&mut BcWriter),
     ) {
-        write_for(&self.over, &self.var, self.over.span, bc, |bc| {
-            for c in &self.ifs {
-                write_if_then(c, MaybeNot::Not, |bc| bc.write_continue(c.span), bc);
-            }
-
-            match rem.split_last() {
-                Some((first, rem)) => {
-                    first.write_bc(bc, rem, term);
+        write_for(
+            &self.over,
+            &self.var,
+            self.over.span,
+            bc,
+            before_loop,
+            |bc| {
+                for c in &self.ifs {
+                    write_if_then(c, MaybeNot::Not, |bc| bc.write_continue(c.span), bc);
                 }
-                None => {
-                    term(bc);
+
+                match rem.split_last() {
+                    Some((first, rem)) => {
+                        first.write_bc(bc, rem, no_before_loop, term);
+                    }
+                    None => {
+                        term(bc);
+                    }
                 }
-            }
-        })
+            },
+        )
     }
 }
 
@@ -72,9 +94,17 @@ impl ComprCompiled {
         bc.alloc_slot(|temp, bc| {
             match self {
                 ComprCompiled::List(ref expr, ref clauses) => {
-                    bc.write_instr::<InstrListNew>(span, temp.to_out());
                     let (first, rem) = clauses.split_last();
-                    first.write_bc(bc, rem, |bc| {
+                    let presize = first.produces_one_per_item(rem);
+                    if !presize {
+                        bc.write_instr::<InstrListNew>(span, temp.to_out());
+                    }
+                    let before_loop = |over: BcSlotIn, bc: &mut BcWriter| {
+                        if presize {
+                            bc.write_instr::<InstrComprListNew>(span, (over, temp.to_out()));
+                        }
+                    };
+                    first.write_bc(bc, rem, before_loop, |bc| {
                         expr.write_bc_cb(bc, |expr_slot, bc| {
                             bc.write_instr::<InstrComprListAppend>(
                                 expr.span,
@@ -85,9 +115,17 @@ impl ComprCompiled {
                 }
                 ComprCompiled::Dict(k_v, clauses) => {
                     let (k, v) = &**k_v;
-                    bc.write_instr::<InstrDictNew>(span, temp.to_out());
                     let (first, rem) = clauses.split_last();
-                    first.write_bc(bc, rem, |bc| {
+                    let presize = first.produces_one_per_item(rem);
+                    if !presize {
+                        bc.write_instr::<InstrDictNew>(span, temp.to_out());
+                    }
+                    let before_loop = |over: BcSlotIn, bc: &mut BcWriter| {
+                        if presize {
+                            bc.write_instr::<InstrComprDictNew>(span, (over, temp.to_out()));
+                        }
+                    };
+                    first.write_bc(bc, rem, before_loop, |bc| {
                         write_n_exprs([k, v], bc, |[k_slot, v_slot], bc| {
                             bc.write_instr::<InstrComprDictInsert>(
                                 k.span,
@@ -25,6 +25,8 @@ use crate::collections::Hashed;
 use crate::collections::SmallMap;
 use crate::eval::bc::compiler::if_compiler::write_if_else;
 use crate::eval::bc::instr_impl::*;
+use crate::eval::bc::member_cache::MemberCache;
+use crate::eval::bc::slow_arg::BcBinOpOperands;
 use crate::eval::bc::slow_arg::BcInstrSlowArg;
 use crate::eval::bc::stack_ptr::BcSlot;
 use crate::eval::bc::stack_ptr::BcSlotIn;
@@ -75,6 +77,28 @@ pub(crate) fn write_exprs<'a>(
     }
 }
 
+/// Write a binary operator instruction, recording the operands for errors.
+fn write_bin_op<I: InstrBinOpImpl>(
+    span: FrameSpan,
+    (l, l_span): (BcSlotIn, FrameSpan),
+    (r, r_span): (BcSlotIn, FrameSpan),
+    target: BcSlotOut,
+    bc: &mut BcWriter,
+) {
+    bc.write_instr_explicit::<InstrBinOp<I>>(
+        BcInstrSlowArg {
+            span,
+            bin_op: Some(BcBinOpOperands {
+                lhs: (l, l_span),
+                rhs: (r, r_span),
+                type_error_operand: I::type_error_operand,
+            }),
+            ..Default::default()
+        },
+        (l, r, target),
+    )
+}
+
 pub(crate) fn write_expr_opt(
     expr: &Option<IrSpanned<ExprCompiled>>,
     bc: &mut BcWriter,
@@ -249,6 +273,7 @@ impl IrSpanned<ExprCompiled> {
                     BcInstrSlowArg {
                         span,
                         spans: key_spans,
+                        ..Default::default()
                     },
                     (kvs, target),
                 );
@@ -350,6 +375,7 @@ impl IrSpanned<ExprCompiled> {
             }
             ExprCompiled::Builtin1(Builtin1::Not, expr) => Self::write_not(expr, target, bc),
             ExprCompiled::Builtin1(op, expr) => {
+                let expr_span = expr.span;
                 expr.write_bc_cb(bc, |expr, bc| {
                     let arg = (expr, target);
                     match op {
@@ -365,9 +391,10 @@ impl IrSpanned<ExprCompiled> {
                         Builtin1::FormatOne(before, after) => {
                             bc.write_instr::<InstrFormatOne>(span, (*before, expr, *after, target))
                         }
-                        Builtin1::Dot(field) => {
-                            bc.write_instr::<InstrObjectField>(span, (expr, field.clone(), target))
-                        }
+                        Builtin1::Dot(field) => bc.write_instr_explicit::<InstrObjectField>(
+                            BcInstrSlowArg::access(span.after(&expr_span), span),
+                            (expr, field.clone(), MemberCache::default(), target),
+                        ),
                     }
                 });
             }
@@ -407,42 +434,66 @@ impl IrSpanned<ExprCompiled> {
             }
             ExprCompiled::Builtin2(op, l_r) => {
                 let (l, r) = &**l_r;
+                let l_span = l.span;
+                let r_span = r.span;
                 write_n_exprs([l, r], bc, |[l, r], bc| {
                     let arg = (l, r, target);
+                    let (l, r) = ((l, l_span), (r, r_span));
                     match op {
                         Builtin2::Equals => unreachable!("handled above"),
                         Builtin2::Compare(CompareOp::Less) => {
-                            bc.write_instr::<InstrLess>(span, arg)
+                            write_bin_op::<InstrCompare<InstrLessImpl>>(span, l, r, target, bc)
                         }
                         Builtin2::Compare(CompareOp::Greater) => {
-                            bc.write_instr::<InstrGreater>(span, arg)
+                            write_bin_op::<InstrCompare<InstrGreaterImpl>>(span, l, r, target, bc)
                         }
                         Builtin2::Compare(CompareOp::LessOrEqual) => {
-                            bc.write_instr::<InstrLessOrEqual>(span, arg)
+                            write_bin_op::<InstrCompare<InstrLessOrEqualImpl>>(
+                                span, l, r, target, bc,
+                            )
                         }
                         Builtin2::Compare(CompareOp::GreaterOrEqual) => {
-                            bc.write_instr::<InstrGreaterOrEqual>(span, arg)
+                            write_bin_op::<InstrCompare<InstrGreaterOrEqualImpl>>(
+                                span, l, r, target, bc,
+                            )
+                        }
+                        Builtin2::In => write_bin_op::<InstrInImpl>(span, l, r, target, bc),
+                        Builtin2::Sub => write_bin_op::<InstrSubImpl>(span, l, r, target, bc),
+                        Builtin2::Add => write_bin_op::<InstrAddImpl>(span, l, r, target, bc),
+                        Builtin2::Multiply => {
+                            write_bin_op::<InstrMultiplyImpl>(span, l, r, target, bc)
+                        }
+                        Builtin2::Divide => write_bin_op::<InstrDivideImpl>(span, l, r, target, bc),
+                        Builtin2::FloorDivide => {
+                            write_bin_op::<InstrFloorDivideImpl>(span, l, r, target, bc)
+                        }
+                        Builtin2::Percent => {
+                            write_bin_op::<InstrPercentImpl>(span, l, r, target, bc)
+                        }
+                        Builtin2::BitAnd => write_bin_op::<InstrBitAndImpl>(span, l, r, target, bc),
+                        Builtin2::BitOr => write_bin_op::<InstrBitOrImpl>(span, l, r, target, bc),
+                        Builtin2::BitXor => write_bin_op::<InstrBitXorImpl>(span, l, r, target, bc),
+                        Builtin2::LeftShift => {
+                            write_bin_op::<InstrLeftShiftImpl>(span, l, r, target, bc)
+                        }
+                        Builtin2::RightShift => {
+                            write_bin_op::<InstrRightShiftImpl>(span, l, r, target, bc)
                         }
-                        Builtin2::In => bc.write_instr::<InstrIn>(span, arg),
-                        Builtin2::Sub => bc.write_instr::<InstrSub>(span, arg),
-                        Builtin2::Add => bc.write_instr::<InstrAdd>(span, arg),
-                        Builtin2::Multiply => bc.write_instr::<InstrMultiply>(span, arg),
-                        Builtin2::Divide => bc.write_instr::<InstrDivide>(span, arg),
-                        Builtin2::FloorDivide => bc.write_instr::<InstrFloorDivide>(span, arg),
-                        Builtin2::Percent => bc.write_instr::<InstrPercent>(span, arg),
-                        Builtin2::BitAnd => bc.write_instr::<InstrBitAnd>(span, arg),
-                        Builtin2::BitOr => bc.write_instr::<InstrBitOr>(span, arg),
-                        Builtin2::BitXor => bc.write_instr::<InstrBitXor>(span, arg),
-                        Builtin2::LeftShift => bc.write_instr::<InstrLeftShift>(span, arg),
-                        Builtin2::RightShift => bc.write_instr::<InstrRightShift>(span, arg),
-                        Builtin2::ArrayIndex => bc.write_instr::<InstrArrayIndex>(span, arg),
+                        Builtin2::ArrayIndex => bc.write_instr_explicit::<InstrArrayIndex>(
+                            BcInstrSlowArg::access(span.after(&l_span), span),
+                            arg,
+                        ),
                     }
                 });
             }
             ExprCompiled::Index2(a_i0_i1) => {
                 let (a, i0, i1) = &**a_i0_i1;
+                let a_span = a.span;
                 write_n_exprs([a, i0, i1], bc, |[a, i0, i1], bc| {
-                    bc.write_instr::<InstrArrayIndex2>(span, (a, i0, i1, target))
+                    bc.write_instr_explicit::<InstrArrayIndex2>(
+                        BcInstrSlowArg::access(span.after(&a_span), span),
+                        (a, i0, i1, target),
+                    )
                 });
             }
             ExprCompiled::Call(ref call) => call.write_bc(target, bc),
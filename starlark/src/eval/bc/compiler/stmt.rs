@@ -16,9 +16,13 @@
  */
 
 use crate::eval::bc::bytecode::Bc;
+use crate::eval::bc::compiler::expr::write_expr_opt;
+use crate::eval::bc::compiler::expr::write_n_exprs;
 use crate::eval::bc::compiler::if_compiler::write_if_else;
 use crate::eval::bc::compiler::if_compiler::write_if_then;
 use crate::eval::bc::instr_impl::InstrCheckType;
+use crate::eval::bc::instr_impl::InstrDelIndex;
+use crate::eval::bc::instr_impl::InstrDelSlice;
 use crate::eval::bc::instr_impl::InstrPossibleGc;
 use crate::eval::bc::instr_impl::InstrReturn;
 use crate::eval::bc::instr_impl::InstrReturnCheckType;
@@ -39,16 +43,20 @@ use crate::values::FrozenRef;
 use crate::values::FrozenStringValue;
 use crate::values::FrozenValue;
 
+/// Write a `for` loop. `before_loop` is called after `over` is evaluated
+/// but before the iteration starts.
 pub(crate) fn write_for(
     over: &IrSpanned<ExprCompiled>,
     var: &IrSpanned<AssignCompiledValue>,
     span: FrameSpan,
     bc: &mut BcWriter,
+    before_loop: impl FnOnce(BcSlotIn, &mut BcWriter),
     body: impl FnOnce(&mut BcWriter),
 ) {
     let definitely_assigned = bc.save_definitely_assigned();
 
     over.write_bc_cb(bc, |over, bc| {
+        before_loop(over, bc);
         if let Some(var) = var.as_local_non_captured() {
             // Typical case: `for x in ...: ...`,
             // compile loop assignment directly to a local variable.
@@ -102,6 +110,18 @@ impl StmtCompiled {
                 rhs.mark_definitely_assigned_after(bc);
                 lhs.mark_definitely_assigned_after(bc);
             }
+            StmtCompiled::DelIndex(array_index) => {
+                let (array, index) = &**array_index;
+                array.mark_definitely_assigned_after(bc);
+                index.mark_definitely_assigned_after(bc);
+            }
+            StmtCompiled::DelSlice(array_start_stop_step) => {
+                let (array, start, stop, step) = &**array_start_stop_step;
+                array.mark_definitely_assigned_after(bc);
+                for x in [start, stop, step].into_iter().flatten() {
+                    x.mark_definitely_assigned_after(bc);
+                }
+            }
             StmtCompiled::If(cond_t_f) => {
                 let (cond, t, f) = &**cond_t_f;
                 cond.mark_definitely_assigned_after(bc);
@@ -135,7 +155,7 @@ impl StmtCompiled {
 
 impl IrSpanned<StmtCompiled> {
     fn write_bc(&self, compiler: &StmtCompileContext, bc: &mut BcWriter) {
-        bc.mark_before_stmt(self.span);
+        bc.mark_before_stmt(self.span, matches!(self.node, StmtCompiled::PossibleGc));
         self.write_bc_inner(compiler, bc);
         self.mark_definitely_assigned_after(bc);
     }
@@ -236,13 +256,38 @@ impl IrSpanned<StmtCompiled> {
             StmtCompiled::AssignModify(lhs, op, rhs) => {
                 lhs.write_bc(span, *op, rhs, bc);
             }
+            StmtCompiled::DelIndex(array_index) => {
+                let (array, index) = &**array_index;
+                write_n_exprs([array, index], bc, |[array, index], bc| {
+                    bc.write_instr::<InstrDelIndex>(span, (array, index));
+                });
+            }
+            StmtCompiled::DelSlice(array_start_stop_step) => {
+                let (array, start, stop, step) = &**array_start_stop_step;
+                array.write_bc_cb(bc, |array, bc| {
+                    write_expr_opt(start, bc, |start, bc| {
+                        write_expr_opt(stop, bc, |stop, bc| {
+                            write_expr_opt(step, bc, |step, bc| {
+                                bc.write_instr::<InstrDelSlice>(span, (array, start, stop, step))
+                            })
+                        })
+                    })
+                });
+            }
             StmtCompiled::If(c_t_f) => {
                 let (c, t, f) = &**c_t_f;
                 Self::write_if_else(c, t, f, compiler, bc);
             }
             StmtCompiled::For(assign_over_body) => {
                 let (assign, over, body) = &**assign_over_body;
-                write_for(over, assign, span, bc, |bc| body.write_bc(compiler, bc));
+                write_for(
+                    over,
+                    assign,
+                    span,
+                    bc,
+                    |_, _| {},
+                    |bc| body.write_bc(compiler, bc),
+                );
             }
             StmtCompiled::Break => {
                 bc.write_break(span);
@@ -37,6 +37,7 @@ use crate::eval::bc::call::BcCallArgsPos;
 use crate::eval::bc::for_loop::LoopDepth;
 use crate::eval::bc::instr::BcInstr;
 use crate::eval::bc::instr_impl::InstrDefData;
+use crate::eval::bc::member_cache::MemberCache;
 use crate::eval::bc::native_function::BcNativeFunction;
 use crate::eval::bc::opcode::BcOpcode;
 use crate::eval::bc::opcode::BcOpcodeHandler;
@@ -242,6 +243,48 @@ impl<A: BcInstrArg, B: BcInstrArg, C: BcInstrArg, D: BcInstrArg, E: BcInstrArg,
     }
 }
 
+#[allow(clippy::many_single_char_names)]
+impl<
+    A: BcInstrArg,
+    B: BcInstrArg,
+    C: BcInstrArg,
+    D: BcInstrArg,
+    E: BcInstrArg,
+    F: BcInstrArg,
+    G: BcInstrArg,
+> BcInstrArg for (A, B, C, D, E, F, G)
+{
+    fn fmt_append(
+        (a, b, c, d, e, f, g): &Self,
+        ip: BcAddr,
+        end_arg: Option<&BcInstrEndArg>,
+        w: &mut dyn Write,
+    ) -> fmt::Result {
+        A::fmt_append(a, ip, end_arg, w)?;
+        B::fmt_append(b, ip, end_arg, w)?;
+        C::fmt_append(c, ip, end_arg, w)?;
+        D::fmt_append(d, ip, end_arg, w)?;
+        E::fmt_append(e, ip, end_arg, w)?;
+        F::fmt_append(f, ip, end_arg, w)?;
+        G::fmt_append(g, ip, end_arg, w)?;
+        Ok(())
+    }
+
+    fn visit_jump_addr(
+        (a, b, c, d, e, f, g): &Self,
+        ip: BcAddr,
+        consumer: &mut dyn FnMut(BcAddr),
+    ) {
+        BcInstrArg::visit_jump_addr(a, ip, consumer);
+        BcInstrArg::visit_jump_addr(b, ip, consumer);
+        BcInstrArg::visit_jump_addr(c, ip, consumer);
+        BcInstrArg::visit_jump_addr(d, ip, consumer);
+        BcInstrArg::visit_jump_addr(e, ip, consumer);
+        BcInstrArg::visit_jump_addr(f, ip, consumer);
+        BcInstrArg::visit_jump_addr(g, ip, consumer);
+    }
+}
+
 impl<A: BcInstrArg, const N: usize> BcInstrArg for [A; N] {
     fn fmt_append(
         param: &Self,
@@ -591,6 +634,20 @@ impl BcInstrArg for KnownMethod {
     fn visit_jump_addr(_param: &Self, _ip: BcAddr, _consumer: &mut dyn FnMut(BcAddr)) {}
 }
 
+impl BcInstrArg for MemberCache {
+    fn fmt_append(
+        _param: &Self,
+        _ip: BcAddr,
+        _end_arg: Option<&BcInstrEndArg>,
+        _f: &mut dyn Write,
+    ) -> fmt::Result {
+        // Runtime state, not part of the instruction.
+        Ok(())
+    }
+
+    fn visit_jump_addr(_param: &Self, _ip: BcAddr, _consumer: &mut dyn FnMut(BcAddr)) {}
+}
+
 impl BcInstrArg for Symbol {
     fn fmt_append(
         param: &Self,
@@ -17,10 +17,12 @@
 
 //! Instruction implementations.
 
+use std::cmp;
 use std::cmp::Ordering;
 use std::marker;
 use std::ptr;
 
+use dupe::Dupe;
 use starlark_syntax::eval_exception::EvalException;
 
 use crate::coerce::coerce;
@@ -42,6 +44,7 @@ use crate::eval::bc::frame::BcFramePtr;
 use crate::eval::bc::instr::BcInstr;
 use crate::eval::bc::instr::InstrControl;
 use crate::eval::bc::instr_arg::BcInstrArg;
+use crate::eval::bc::member_cache::MemberCache;
 use crate::eval::bc::native_function::BcNativeFunction;
 use crate::eval::bc::slow_arg::BcInstrEndArg;
 use crate::eval::bc::stack_ptr::BcSlotIn;
@@ -53,8 +56,9 @@ use crate::eval::compiler::def::Def;
 use crate::eval::compiler::def::FrozenDef;
 use crate::eval::compiler::def::ParameterCompiled;
 use crate::eval::compiler::def::ParametersCompiled;
-use crate::eval::compiler::expr::get_attr_hashed_bind;
+use crate::eval::compiler::expr::get_attr_hashed_bind_cached;
 use crate::eval::compiler::expr::get_attr_hashed_raw;
+use crate::eval::compiler::expr::get_attr_hashed_raw_cached;
 use crate::eval::compiler::expr::EvalError;
 use crate::eval::compiler::expr_throw_starlark_result;
 use crate::eval::compiler::stmt::add_assign;
@@ -152,6 +156,9 @@ pub(crate) struct InstrArrayIndexSetImpl;
 pub(crate) struct InstrObjectFieldImpl;
 pub(crate) struct InstrSetObjectFieldImpl;
 pub(crate) struct InstrSliceImpl;
+pub(crate) struct InstrSetSliceImpl;
+pub(crate) struct InstrDelIndexImpl;
+pub(crate) struct InstrDelSliceImpl;
 pub(crate) struct InstrArrayIndex2Impl;
 
 pub(crate) type InstrLoadLocal = InstrNoFlow<InstrLoadLocalImpl>;
@@ -168,6 +175,9 @@ pub(crate) type InstrArrayIndexSet = InstrNoFlow<InstrArrayIndexSetImpl>;
 pub(crate) type InstrObjectField = InstrNoFlow<InstrObjectFieldImpl>;
 pub(crate) type InstrSetObjectField = InstrNoFlow<InstrSetObjectFieldImpl>;
 pub(crate) type InstrSlice = InstrNoFlow<InstrSliceImpl>;
+pub(crate) type InstrSetSlice = InstrNoFlow<InstrSetSliceImpl>;
+pub(crate) type InstrDelIndex = InstrNoFlow<InstrDelIndexImpl>;
+pub(crate) type InstrDelSlice = InstrNoFlow<InstrDelSliceImpl>;
 pub(crate) type InstrArrayIndex2 = InstrNoFlow<InstrArrayIndex2Impl>;
 
 impl InstrNoFlowImpl for InstrLoadLocalImpl {
@@ -363,17 +373,21 @@ impl InstrNoFlowImpl for InstrArrayIndexSetImpl {
 }
 
 impl InstrNoFlowImpl for InstrObjectFieldImpl {
-    type Arg = (BcSlotIn, Symbol, BcSlotOut);
+    type Arg = (BcSlotIn, Symbol, MemberCache, BcSlotOut);
 
     #[inline(always)]
     fn run_with_args<'v>(
         eval: &mut Evaluator<'v, '_, '_>,
         frame: BcFramePtr<'v>,
-        _ip: BcPtrAddr,
-        (object, field, target): &(BcSlotIn, Symbol, BcSlotOut),
+        ip: BcPtrAddr,
+        (object, field, cache, target): &(BcSlotIn, Symbol, MemberCache, BcSlotOut),
     ) -> crate::Result<()> {
         let object = frame.get_bc_slot(*object);
-        let value = get_attr_hashed_bind(object, field, eval.heap())?;
+        if eval.has_attr_hooks() {
+            let span = Bc::slow_arg_at_ptr(ip).span.span.file_span_ref();
+            eval.run_attr_hooks(object, field.as_str(), Some(span))?;
+        }
+        let value = get_attr_hashed_bind_cached(object, field, cache, eval.heap())?;
         frame.set_bc_slot(*target, value);
         Ok(())
     }
@@ -426,6 +440,78 @@ impl InstrNoFlowImpl for InstrSliceImpl {
     }
 }
 
+impl InstrNoFlowImpl for InstrSetSliceImpl {
+    type Arg = (
+        BcSlotIn,
+        BcSlotIn,
+        Option<BcSlotIn>,
+        Option<BcSlotIn>,
+        Option<BcSlotIn>,
+    );
+
+    fn run_with_args<'v>(
+        eval: &mut Evaluator<'v, '_, '_>,
+        frame: BcFramePtr<'v>,
+        _ip: BcPtrAddr,
+        (source, list, start, stop, step): &(
+            BcSlotIn,
+            BcSlotIn,
+            Option<BcSlotIn>,
+            Option<BcSlotIn>,
+            Option<BcSlotIn>,
+        ),
+    ) -> crate::Result<()> {
+        let value = frame.get_bc_slot(*source);
+        let list = frame.get_bc_slot(*list);
+        let start = start.map(|s| frame.get_bc_slot(s));
+        let stop = stop.map(|s| frame.get_bc_slot(s));
+        let step = step.map(|s| frame.get_bc_slot(s));
+        list.set_slice(start, stop, step, value, eval.heap())
+    }
+}
+
+impl InstrNoFlowImpl for InstrDelIndexImpl {
+    type Arg = (BcSlotIn, BcSlotIn);
+
+    fn run_with_args<'v>(
+        _eval: &mut Evaluator<'v, '_, '_>,
+        frame: BcFramePtr<'v>,
+        _ip: BcPtrAddr,
+        (array, index): &(BcSlotIn, BcSlotIn),
+    ) -> crate::Result<()> {
+        let array = frame.get_bc_slot(*array);
+        let index = frame.get_bc_slot(*index);
+        array.del_at(index)
+    }
+}
+
+impl InstrNoFlowImpl for InstrDelSliceImpl {
+    type Arg = (
+        BcSlotIn,
+        Option<BcSlotIn>,
+        Option<BcSlotIn>,
+        Option<BcSlotIn>,
+    );
+
+    fn run_with_args<'v>(
+        eval: &mut Evaluator<'v, '_, '_>,
+        frame: BcFramePtr<'v>,
+        _ip: BcPtrAddr,
+        (list, start, stop, step): &(
+            BcSlotIn,
+            Option<BcSlotIn>,
+            Option<BcSlotIn>,
+            Option<BcSlotIn>,
+        ),
+    ) -> crate::Result<()> {
+        let list = frame.get_bc_slot(*list);
+        let start = start.map(|s| frame.get_bc_slot(s));
+        let stop = stop.map(|s| frame.get_bc_slot(s));
+        let step = step.map(|s| frame.get_bc_slot(s));
+        list.del_slice(start, stop, step, eval.heap())
+    }
+}
+
 impl InstrNoFlowImpl for InstrArrayIndex2Impl {
     type Arg = (BcSlotIn, BcSlotIn, BcSlotIn, BcSlotOut);
 
@@ -580,6 +666,30 @@ impl InstrUnOpImpl for InstrBitNotImpl {
 
 pub(crate) trait InstrBinOpImpl: 'static {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>>;
+
+    /// Operand to point at when the operation is not supported for the operand types.
+    fn type_error_operand(_v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Rhs
+    }
+}
+
+/// Operand of a binary operator.
+#[derive(Clone, Copy, Dupe, Debug)]
+pub(crate) enum BinOpOperand {
+    Lhs,
+    Rhs,
+}
+
+impl BinOpOperand {
+    /// Operand to point at when the operation dispatched on this operand
+    /// is not supported: this operand if its type does not implement the operation at all,
+    /// otherwise the other operand, which has a type the operation does not accept.
+    fn blame(self, implemented: bool) -> BinOpOperand {
+        match (self, implemented) {
+            (BinOpOperand::Lhs, false) | (BinOpOperand::Rhs, true) => BinOpOperand::Lhs,
+            (BinOpOperand::Lhs, true) | (BinOpOperand::Rhs, false) => BinOpOperand::Rhs,
+        }
+    }
 }
 
 pub(crate) trait InstrUnOpImpl: 'static {
@@ -661,6 +771,10 @@ impl InstrBinOpImpl for InstrAddImpl {
     fn eval<'v>(l: Value<'v>, r: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         l.add(r, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_add)
+    }
 }
 
 impl InstrBinOpImpl for InstrAddAssignImpl {
@@ -675,6 +789,10 @@ impl InstrBinOpImpl for InstrSubImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         v0.sub(v1, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_sub)
+    }
 }
 
 impl InstrBinOpImpl for InstrMultiplyImpl {
@@ -682,6 +800,10 @@ impl InstrBinOpImpl for InstrMultiplyImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         v0.mul(v1, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_mul)
+    }
 }
 
 impl InstrBinOpImpl for InstrPercentImpl {
@@ -689,6 +811,10 @@ impl InstrBinOpImpl for InstrPercentImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         v0.percent(v1, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_percent)
+    }
 }
 
 impl InstrBinOpImpl for InstrFloorDivideImpl {
@@ -696,6 +822,10 @@ impl InstrBinOpImpl for InstrFloorDivideImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         v0.floor_div(v1, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_floor_div)
+    }
 }
 
 impl InstrBinOpImpl for InstrDivideImpl {
@@ -703,6 +833,10 @@ impl InstrBinOpImpl for InstrDivideImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         v0.div(v1, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_div)
+    }
 }
 
 impl InstrBinOpImpl for InstrBitAndImpl {
@@ -710,6 +844,10 @@ impl InstrBinOpImpl for InstrBitAndImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         v0.bit_and(v1, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_bit_and)
+    }
 }
 
 impl InstrBinOpImpl for InstrBitOrImpl {
@@ -717,6 +855,10 @@ impl InstrBinOpImpl for InstrBitOrImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         v0.bit_or(v1, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_bit_or)
+    }
 }
 
 impl InstrBinOpImpl for InstrBitOrAssignImpl {
@@ -731,6 +873,10 @@ impl InstrBinOpImpl for InstrBitXorImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         v0.bit_xor(v1, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_bit_xor)
+    }
 }
 
 impl InstrBinOpImpl for InstrLeftShiftImpl {
@@ -738,6 +884,10 @@ impl InstrBinOpImpl for InstrLeftShiftImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         v0.left_shift(v1, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_left_shift)
+    }
 }
 
 impl InstrBinOpImpl for InstrRightShiftImpl {
@@ -745,6 +895,10 @@ impl InstrBinOpImpl for InstrRightShiftImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
         v0.right_shift(v1, heap)
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_right_shift)
+    }
 }
 
 impl InstrBinOpImpl for InstrInImpl {
@@ -752,6 +906,10 @@ impl InstrBinOpImpl for InstrInImpl {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, _heap: &'v Heap) -> crate::Result<Value<'v>> {
         Ok(Value::new_bool(v1.is_in(v0)?))
     }
+
+    fn type_error_operand(_v0: Value, v1: Value) -> BinOpOperand {
+        BinOpOperand::Rhs.blame(v1.vtable().starlark_value.HAS_is_in)
+    }
 }
 
 pub(crate) struct InstrPercentSOneImpl;
@@ -787,7 +945,7 @@ impl InstrNoFlowImpl for InstrFormatOneImpl {
         (before, arg, after, target): &(FrozenStringValue, BcSlotIn, FrozenStringValue, BcSlotOut),
     ) -> crate::Result<()> {
         let arg = frame.get_bc_slot(*arg);
-        let r = format_one(before.as_str(), arg, after.as_str(), eval.heap());
+        let r = format_one(before.as_str(), arg, after.as_str(), eval.heap())?;
         frame.set_bc_slot(*target, r.to_value());
         Ok(())
     }
@@ -804,6 +962,10 @@ impl<I: InstrCompareImpl> InstrBinOpImpl for InstrCompare<I> {
     fn eval<'v>(v0: Value<'v>, v1: Value<'v>, _heap: &'v Heap) -> crate::Result<Value<'v>> {
         Ok(Value::new_bool(I::eval_compare(v0.compare(v1)?)))
     }
+
+    fn type_error_operand(v0: Value, _v1: Value) -> BinOpOperand {
+        BinOpOperand::Lhs.blame(v0.vtable().starlark_value.HAS_compare)
+    }
 }
 
 pub(crate) struct InstrLessImpl;
@@ -1077,6 +1239,64 @@ impl InstrNoFlowImpl for InstrDictNewImpl {
     }
 }
 
+/// Upper bound on the capacity preallocated for a comprehension result,
+/// so a huge iterable does not reserve memory before anything is produced.
+const COMPR_CAPACITY_HINT_MAX: usize = 1 << 20;
+
+/// Capacity to allocate for a comprehension over `over`, if its length is known.
+#[inline(always)]
+fn compr_capacity_hint(over: Value) -> usize {
+    match over.length() {
+        Ok(len) => cmp::min(len as usize, COMPR_CAPACITY_HINT_MAX),
+        // Not all iterables have a length, and iteration will report errors.
+        Err(_) => 0,
+    }
+}
+
+pub(crate) struct InstrComprListNewImpl;
+pub(crate) struct InstrComprDictNewImpl;
+
+/// Allocate a list for a comprehension, sized for the iterable in the first slot.
+pub(crate) type InstrComprListNew = InstrNoFlow<InstrComprListNewImpl>;
+/// Allocate a dict for a comprehension, sized for the iterable in the first slot.
+pub(crate) type InstrComprDictNew = InstrNoFlow<InstrComprDictNewImpl>;
+
+impl InstrNoFlowImpl for InstrComprListNewImpl {
+    type Arg = (BcSlotIn, BcSlotOut);
+
+    #[inline(always)]
+    fn run_with_args<'v>(
+        eval: &mut Evaluator<'v, '_, '_>,
+        frame: BcFramePtr<'v>,
+        _: BcPtrAddr,
+        (over, target): &(BcSlotIn, BcSlotOut),
+    ) -> crate::Result<()> {
+        let capacity = compr_capacity_hint(frame.get_bc_slot(*over));
+        let list = eval.heap().alloc_list_with_capacity(capacity);
+        frame.set_bc_slot(*target, list);
+        Ok(())
+    }
+}
+
+impl InstrNoFlowImpl for InstrComprDictNewImpl {
+    type Arg = (BcSlotIn, BcSlotOut);
+
+    #[inline(always)]
+    fn run_with_args<'v>(
+        eval: &mut Evaluator<'v, '_, '_>,
+        frame: BcFramePtr<'v>,
+        _: BcPtrAddr,
+        (over, target): &(BcSlotIn, BcSlotOut),
+    ) -> crate::Result<()> {
+        let capacity = compr_capacity_hint(frame.get_bc_slot(*over));
+        let dict = eval
+            .heap()
+            .alloc(Dict::new(SmallMap::with_capacity(capacity)));
+        frame.set_bc_slot(*target, dict);
+        Ok(())
+    }
+}
+
 pub(crate) struct InstrComprListAppend;
 pub(crate) struct InstrComprDictInsert;
 
@@ -1144,7 +1364,7 @@ impl InstrNoFlowImpl for InstrCheckTypeImpl {
         } else {
             None
         };
-        let res = ty.check_type(expr, None);
+        let res = ty.check_type_in_eval(expr, None, eval);
         if let Some(start) = start {
             let name = const_frozen_string!("assignment");
             eval.typecheck_profile.add(name, start.elapsed());
@@ -1277,6 +1497,10 @@ impl BcInstr for InstrContinue {
         ),
     ) -> InstrControl<'v, 'b> {
         let iter = frame.get_bc_slot(*iter);
+        if let Err(e) = eval.check_max_heap_bytes() {
+            iter.get_ref().iter_stop();
+            return InstrControl::Err(e);
+        }
         let loop_depth = *loop_depth;
         let i = frame.get_iter_index(loop_depth);
         match iter.get_ref().iter_next(i, eval.heap()) {
@@ -1431,7 +1655,7 @@ impl InstrNoFlowImpl for InstrDefImpl {
                         // Check the type of the default
                         let (_, _, ty_compiled) = parameter_types.last().unwrap();
                         expr_throw_starlark_result(
-                            ty_compiled.check_type(value, Some(&n.name)),
+                            ty_compiled.check_type_in_eval(value, Some(&n.name), eval),
                             x.span,
                             eval,
                         )
@@ -1487,7 +1711,7 @@ impl BcFrozenCallable for FrozenValueTyped<'static, FrozenDef> {
         args: &Arguments<'v, '_>,
         eval: &mut Evaluator<'v, '_, '_>,
     ) -> crate::Result<Value<'v>> {
-        eval.with_call_stack(self.to_value(), Some(location), |eval| {
+        eval.with_call_stack(self.to_value(), Some(location), &args.0, |eval| {
             self.as_ref().invoke(self.to_value(), args, eval)
         })
     }
@@ -1501,7 +1725,7 @@ impl BcFrozenCallable for BcNativeFunction {
         args: &Arguments<'v, '_>,
         eval: &mut Evaluator<'v, '_, '_>,
     ) -> crate::Result<Value<'v>> {
-        eval.with_call_stack(self.to_value(), Some(location), |eval| {
+        eval.with_call_stack(self.to_value(), Some(location), &args.0, |eval| {
             self.invoke(args, eval)
         })
     }
@@ -1593,7 +1817,7 @@ impl<A: BcCallArgsForDef> InstrNoFlowImpl for InstrCallFrozenDefImpl<A> {
         ),
     ) -> crate::Result<()> {
         let arguments = args.pop_from_stack(frame);
-        let r = eval.with_call_stack(fun.to_value(), Some(*span), |eval| {
+        let r = eval.with_call_stack(fun.to_value(), Some(*span), &arguments, |eval| {
             fun.as_ref()
                 .invoke_with_args(fun.to_value(), &arguments, eval)
         })?;
@@ -1609,12 +1833,19 @@ fn call_method_common<'v>(
     frame: BcFramePtr<'v>,
     this: Value<'v>,
     symbol: &Symbol,
+    cache: Option<&MemberCache>,
     arguments: &Arguments<'v, '_>,
     span: FrozenRef<'static, FrameSpan>,
     target: BcSlotOut,
 ) -> crate::Result<()> {
     // TODO: wrong span: should be span of `object.method`, not of the whole expression
-    let method = get_attr_hashed_raw(this, symbol, eval.heap())?;
+    if eval.has_attr_hooks() {
+        eval.run_attr_hooks(this, symbol.as_str(), Some(span.span.file_span_ref()))?;
+    }
+    let method = match cache {
+        Some(cache) => get_attr_hashed_raw_cached(this, symbol, cache, eval.heap())?,
+        None => get_attr_hashed_raw(this, symbol, eval.heap())?,
+    };
     let r = method.invoke(this, span, arguments, eval)?;
     frame.set_bc_slot(target, r);
     Ok(())
@@ -1628,6 +1859,7 @@ fn call_maybe_known_method_common<'v>(
     this: Value<'v>,
     symbol: &Symbol,
     known_method: &KnownMethod,
+    cache: &MemberCache,
     arguments: &Arguments<'v, '_>,
     span: FrozenRef<'static, FrameSpan>,
     target: BcSlotOut,
@@ -1637,21 +1869,37 @@ fn call_maybe_known_method_common<'v>(
         // If pointers are equal, getattr would return the same method
         // we already have.
         if ptr::eq(methods, known_method.type_methods) {
-            let r = eval.with_call_stack(known_method.to_value(), Some(span), |eval| {
-                known_method.invoke_method(this, arguments, eval)
-            })?;
+            if eval.has_attr_hooks() {
+                eval.run_attr_hooks(this, symbol.as_str(), Some(span.span.file_span_ref()))?;
+            }
+            let r =
+                eval.with_call_stack(known_method.to_value(), Some(span), &arguments.0, |eval| {
+                    known_method.invoke_method(this, arguments, eval)
+                })?;
             frame.set_bc_slot(target, r);
             return Ok(());
         }
     }
 
-    call_method_common(eval, frame, this, symbol, arguments, span, target)
+    // Receiver of another type, like a string at a call site compiled
+    // for `list.index`: look the method up through the cache.
+    call_method_common(
+        eval,
+        frame,
+        this,
+        symbol,
+        Some(cache),
+        arguments,
+        span,
+        target,
+    )
 }
 
 impl<A: BcCallArgs<Symbol>> InstrNoFlowImpl for InstrCallMethodImpl<A> {
     type Arg = (
         BcSlotIn,
         Symbol,
+        MemberCache,
         A,
         FrozenRef<'static, FrameSpan>,
         BcSlotOut,
@@ -1662,9 +1910,10 @@ impl<A: BcCallArgs<Symbol>> InstrNoFlowImpl for InstrCallMethodImpl<A> {
         eval: &mut Evaluator<'v, '_, '_>,
         frame: BcFramePtr<'v>,
         _ip: BcPtrAddr,
-        (this, symbol, args, span, target): &(
+        (this, symbol, cache, args, span, target): &(
             BcSlotIn,
             Symbol,
+            MemberCache,
             A,
             FrozenRef<'static, FrameSpan>,
             BcSlotOut,
@@ -1672,7 +1921,16 @@ impl<A: BcCallArgs<Symbol>> InstrNoFlowImpl for InstrCallMethodImpl<A> {
     ) -> crate::Result<()> {
         let this = frame.get_bc_slot(*this);
         let arguments = Arguments(args.pop_from_stack(frame));
-        call_method_common(eval, frame, this, symbol, &arguments, *span, *target)
+        call_method_common(
+            eval,
+            frame,
+            this,
+            symbol,
+            Some(cache),
+            &arguments,
+            *span,
+            *target,
+        )
     }
 }
 
@@ -1681,6 +1939,7 @@ impl<A: BcCallArgs<Symbol>> InstrNoFlowImpl for InstrCallMaybeKnownMethodImpl<A>
         BcSlotIn,
         Symbol,
         KnownMethod,
+        MemberCache,
         A,
         FrozenRef<'static, FrameSpan>,
         BcSlotOut,
@@ -1691,10 +1950,11 @@ impl<A: BcCallArgs<Symbol>> InstrNoFlowImpl for InstrCallMaybeKnownMethodImpl<A>
         eval: &mut Evaluator<'v, '_, '_>,
         frame: BcFramePtr<'v>,
         _ip: BcPtrAddr,
-        (this, symbol, known_method, args, span, target): &(
+        (this, symbol, known_method, cache, args, span, target): &(
             BcSlotIn,
             Symbol,
             KnownMethod,
+            MemberCache,
             A,
             FrozenRef<'static, FrameSpan>,
             BcSlotOut,
@@ -1708,6 +1968,7 @@ impl<A: BcCallArgs<Symbol>> InstrNoFlowImpl for InstrCallMaybeKnownMethodImpl<A>
             this,
             symbol,
             known_method,
+            cache,
             &arguments,
             *span,
             *target,
@@ -1728,8 +1989,7 @@ impl InstrNoFlowImpl for InstrPossibleGcImpl {
         _ip: BcPtrAddr,
         (): &(),
     ) -> crate::Result<()> {
-        possible_gc(eval);
-        Ok(())
+        possible_gc(eval)
     }
 }
 
@@ -0,0 +1,212 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Inline cache of attribute and method lookup.
+
+use std::fmt;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::ptr;
+use std::sync::atomic;
+use std::sync::atomic::AtomicPtr;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+
+use crate::values::layout::vtable::AValueVTable;
+use crate::values::types::unbound::UnboundValue;
+
+/// Member resolved by the last execution of an instruction like `x.index`
+/// or `x.method(...)`. Calls of common stdlib methods like `x.append(...)`
+/// first compare the receiver with the
+/// [`KnownMethod`](crate::values::types::known_methods::KnownMethod) resolved at compile time,
+/// which needs no lookup, and only use the cache for receivers of other types,
+/// like a string at a call site of `x.index(...)`, which is compiled for `list.index`.
+///
+/// Members from [`get_methods`](crate::values::StarlarkValue::get_methods)
+/// depend only on the type of the receiver, identified by its vtable,
+/// so the cached member is valid for any receiver with the same vtable, and
+/// never needs invalidation. Attributes from `get_attr` are not cached.
+///
+/// Bytecode is shared between threads, so the pair of pointers is guarded by
+/// a sequence lock: readers retry on the slow path rather than wait,
+/// and writers give up if another writer is active.
+pub(crate) struct MemberCache {
+    /// Odd while a writer updates the pointers.
+    seq: AtomicU32,
+    vtable: AtomicPtr<AValueVTable>,
+    member: AtomicPtr<UnboundValue>,
+}
+
+impl Debug for MemberCache {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemberCache").finish_non_exhaustive()
+    }
+}
+
+impl Default for MemberCache {
+    fn default() -> Self {
+        MemberCache {
+            seq: AtomicU32::new(0),
+            vtable: AtomicPtr::new(ptr::null_mut()),
+            member: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+}
+
+impl MemberCache {
+    /// Member cached for the receiver type, if any.
+    #[inline(always)]
+    pub(crate) fn get(&self, vtable: &'static AValueVTable) -> Option<&'static UnboundValue> {
+        let seq = self.seq.load(Ordering::Acquire);
+        let cached_vtable = self.vtable.load(Ordering::Relaxed);
+        let member = self.member.load(Ordering::Relaxed);
+        atomic::fence(Ordering::Acquire);
+        if seq & 1 != 0
+            || self.seq.load(Ordering::Relaxed) != seq
+            || !ptr::eq(cached_vtable, vtable)
+            || member.is_null()
+        {
+            return None;
+        }
+        // SAFETY: the pointers were stored together by `set` from `'static` references.
+        Some(unsafe { &*member })
+    }
+
+    /// Remember the member resolved for the receiver type.
+    #[cold]
+    pub(crate) fn set(&self, vtable: &'static AValueVTable, member: &'static UnboundValue) {
+        let seq = self.seq.load(Ordering::Relaxed);
+        if seq & 1 != 0
+            || self
+                .seq
+                .compare_exchange(
+                    seq,
+                    seq.wrapping_add(1),
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                )
+                .is_err()
+        {
+            // Another thread is updating the cache, this lookup is not cached.
+            return;
+        }
+        atomic::fence(Ordering::Release);
+        self.vtable
+            .store(vtable as *const AValueVTable as *mut _, Ordering::Relaxed);
+        self.member
+            .store(member as *const UnboundValue as *mut _, Ordering::Relaxed);
+        self.seq.store(seq.wrapping_add(2), Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::assert;
+    use crate::environment::Module;
+    use crate::eval::Evaluator;
+    use crate::values::list::AllocList;
+
+    #[test]
+    fn test_member_cache_mixed_receivers() {
+        assert::pass(
+            r#"
+def index(x, y):
+    return x.index(y)
+
+def field(x):
+    return x.index
+
+values = []
+for _ in range(10):
+    values.append(index([1, 2, 3], 3))
+    values.append(index("abc", "b"))
+    values.append(field(struct(index = 7)))
+    values.append(field("ab")("b"))
+assert_eq(values, [2, 1, 7, 1] * 10)
+"#,
+        );
+        assert::fail(
+            r#"
+def index(x, y):
+    return x.index(y)
+
+index([1, 2], 2)
+index(1, 2)
+"#,
+            "has no attribute `index`",
+        );
+    }
+
+    #[test]
+    fn test_member_cache_known_method_other_receivers() {
+        // `clear` and `pop` are compiled for lists, dicts and sets use the cache.
+        assert::pass(
+            r#"
+def clear(x):
+    x.clear()
+    return x
+
+def pop(x, k):
+    return x.pop(k)
+
+for _ in range(10):
+    assert_eq(clear([1, 2]), [])
+    assert_eq(clear({1: 2}), {})
+    assert_eq(clear(set([1])), set())
+    assert_eq(pop([1, 2], 0), 1)
+    assert_eq(pop({"a": 2}, "a"), 2)
+"#,
+        );
+        assert::fail(
+            r#"
+def clear(x):
+    x.clear()
+
+clear({})
+clear(1)
+"#,
+            "has no attribute `clear`",
+        );
+    }
+
+    #[test]
+    fn test_member_cache_threads() {
+        let module = assert::pass_module("def index(x, y):\n    return x.index(y)\n");
+        let index = module.get("index").unwrap();
+        thread::scope(|s| {
+            for t in 0..4 {
+                let index = &index;
+                s.spawn(move || {
+                    let module = Module::new();
+                    let mut eval = Evaluator::new(&module);
+                    let heap = module.heap();
+                    for i in 0..1000 {
+                        let (x, y, expected) = if (i + t) % 2 == 0 {
+                            (heap.alloc(AllocList([1, 2, 3])), heap.alloc(3), 2)
+                        } else {
+                            (heap.alloc("abc"), heap.alloc("b"), 1)
+                        };
+                        let r = eval.eval_function(index.value(), &[x, y], &[]).unwrap();
+                        assert_eq!(Some(expected), r.unpack_i32());
+                    }
+                });
+            }
+        });
+    }
+}
@@ -45,6 +45,9 @@ pub(crate) enum BcOpcode {
     SetArrayIndex,
     ArrayIndexSet,
     Slice,
+    SetSlice,
+    DelIndex,
+    DelSlice,
     ObjectField,
     SetObjectField,
     Eq,
@@ -88,6 +91,8 @@ pub(crate) enum BcOpcode {
     DictNPop,
     DictOfConsts,
     DictConstKeys,
+    ComprListNew,
+    ComprDictNew,
     ComprListAppend,
     ComprDictInsert,
     CheckType,
@@ -15,10 +15,18 @@
  * limitations under the License.
  */
 
+use crate::errors::ErrorCode;
 use crate::eval::bc::addr::BcAddr;
+use crate::eval::bc::frame::BcFramePtr;
+use crate::eval::bc::instr_impl::BinOpOperand;
+use crate::eval::bc::stack_ptr::BcSlotIn;
+use crate::eval::runtime::arguments::FunctionError;
+use crate::eval::runtime::arguments::ParamError;
+use crate::eval::runtime::arguments::ParamRef;
 use crate::eval::runtime::frame_span::FrameSpan;
 use crate::values::FrozenRef;
 use crate::values::FrozenStringValue;
+use crate::values::Value;
 
 /// Slow instruction arg: stored in the end of bytecode,
 /// expensive to access. Used to implement errors.
@@ -28,6 +36,118 @@ pub(crate) struct BcInstrSlowArg {
     pub(crate) span: FrameSpan,
     /// Spans when an instruction needs multiple spans.
     pub(crate) spans: Vec<FrameSpan>,
+    /// For attribute and index instructions, the whole access expression,
+    /// e.g. `a.b[c]` when the instruction is `[c]`. Used by verbose errors.
+    pub(crate) access: Option<FrameSpan>,
+    /// For call instructions, spans of the arguments,
+    /// used to point errors caused by one argument at that argument.
+    pub(crate) args: Option<Box<BcCallArgSpans>>,
+    /// For binary operator instructions, the operands,
+    /// used to point errors caused by one operand at that operand.
+    pub(crate) bin_op: Option<BcBinOpOperands>,
+}
+
+impl BcInstrSlowArg {
+    /// Slow arg of an instruction accessing the part of an object,
+    /// `span` is the operation, `access` is the whole access expression.
+    pub(crate) fn access(span: FrameSpan, access: FrameSpan) -> BcInstrSlowArg {
+        BcInstrSlowArg {
+            span,
+            access: Some(access),
+            ..Default::default()
+        }
+    }
+}
+
+/// Operands of a binary operator instruction.
+#[derive(Debug)]
+pub(crate) struct BcBinOpOperands {
+    pub(crate) lhs: (BcSlotIn, FrameSpan),
+    pub(crate) rhs: (BcSlotIn, FrameSpan),
+    /// Operand to point at when the operation is not supported for the operand types.
+    pub(crate) type_error_operand: for<'v> fn(Value<'v>, Value<'v>) -> BinOpOperand,
+}
+
+impl BcBinOpOperands {
+    /// Span of the operand which caused the operation to fail,
+    /// if the error is caused by one operand.
+    ///
+    /// Operand values are read from the frame, which must be the frame of the instruction.
+    pub(crate) fn span_for_error(&self, e: &crate::Error, frame: BcFramePtr) -> Option<FrameSpan> {
+        let operand = match ErrorCode::of(e) {
+            ErrorCode::DivisionByZero => BinOpOperand::Rhs,
+            ErrorCode::UnsupportedOperation => (self.type_error_operand)(
+                frame.get_bc_slot(self.lhs.0),
+                frame.get_bc_slot(self.rhs.0),
+            ),
+            _ => return None,
+        };
+        match operand {
+            BinOpOperand::Lhs => Some(self.lhs.1),
+            BinOpOperand::Rhs => Some(self.rhs.1),
+        }
+    }
+}
+
+/// Spans of the arguments of a call.
+#[derive(Default, Debug)]
+pub(crate) struct BcCallArgSpans {
+    /// Positional arguments.
+    pub(crate) pos: Vec<FrameSpan>,
+    /// Named arguments with their names.
+    pub(crate) named: Vec<(String, FrameSpan)>,
+    /// `*args` argument.
+    pub(crate) args: Option<FrameSpan>,
+    /// `**kwargs` argument.
+    pub(crate) kwargs: Option<FrameSpan>,
+}
+
+impl BcCallArgSpans {
+    /// Span of the argument which caused the call to fail,
+    /// if the error is about a single argument.
+    pub(crate) fn span_for_error(&self, e: &crate::Error) -> Option<FrameSpan> {
+        match e.kind() {
+            crate::ErrorKind::Function(e) => match e.downcast_ref::<FunctionError>()? {
+                FunctionError::ExtraNamedArg { names, .. } => match names.as_slice() {
+                    [name] => self.named(name),
+                    _ => None,
+                },
+                FunctionError::ExtraNamedArgDidYouMean { name, .. }
+                | FunctionError::RepeatedArg { name } => self.named(name),
+                FunctionError::ArgsArrayIsNotIterable => self.args,
+                FunctionError::KwArgsIsNotDict | FunctionError::KwArgsKeyIsNotString => self.kwargs,
+                _ => None,
+            },
+            crate::ErrorKind::Other(e) => self.param(&e.downcast_ref::<ParamError>()?.param),
+            _ => None,
+        }
+    }
+
+    /// Argument which supplied the value of the parameter.
+    fn param(&self, param: &ParamRef) -> Option<FrameSpan> {
+        if param.positional && param.index < self.pos.len() {
+            return Some(self.pos[param.index]);
+        }
+        if param.named {
+            if let Some((_, span)) = self.named.iter().find(|(n, _)| *n == param.name) {
+                return Some(*span);
+            }
+        }
+        // The value could only come from `*args` or `**kwargs`.
+        match (self.args, self.kwargs) {
+            (Some(args), None) if param.positional => Some(args),
+            (None, Some(kwargs)) if param.named => Some(kwargs),
+            _ => None,
+        }
+    }
+
+    /// Named argument, or `**kwargs` if there is no argument with that name.
+    fn named(&self, name: &str) -> Option<FrameSpan> {
+        match self.named.iter().find(|(n, _)| n == name) {
+            Some((_, span)) => Some(*span),
+            None => self.kwargs,
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -60,6 +60,9 @@ use crate::values::FrozenValue;
 #[derive(Debug)]
 pub(crate) struct BcStmtLoc {
     pub(crate) span: FrameSpan,
+    /// This is the garbage collection point preceding a top-level statement,
+    /// which has the span of that statement.
+    pub(crate) possible_gc: bool,
 }
 
 /// This records the locations of the first instruction for each starlark statement. It's effectively
@@ -223,8 +226,9 @@ impl<'f> BcWriter<'f> {
         self.instrs.write::<I>(arg)
     }
 
-    pub(crate) fn mark_before_stmt(&mut self, span: FrameSpan) {
-        self.stmt_locs.push(self.ip(), BcStmtLoc { span })
+    pub(crate) fn mark_before_stmt(&mut self, span: FrameSpan, possible_gc: bool) {
+        self.stmt_locs
+            .push(self.ip(), BcStmtLoc { span, possible_gc })
     }
 
     /// Write an instruction, return address and argument.
@@ -0,0 +1,404 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Module analyzed once and evaluated many times.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use dupe::Dupe;
+use starlark_syntax::syntax::ast::AstStmt;
+use starlark_syntax::syntax::ast::Visibility;
+use starlark_syntax::syntax::module::AstModule;
+use starlark_syntax::syntax::module::AstModuleFields;
+
+use crate::codemap::CodeMap;
+use crate::docs::DocString;
+use crate::environment::names::MutableNames;
+use crate::environment::Globals;
+use crate::environment::Module;
+use crate::eval::compiler::def::DefInfo;
+use crate::eval::compiler::module::eval_compiled_module;
+use crate::eval::compiler::module::TopLevelStmtCompiled;
+use crate::eval::compiler::scope::scope_resolver_globals::ScopeResolverGlobals;
+use crate::eval::compiler::scope::ModuleScopes;
+use crate::eval::compiler::scope::ScopeId;
+use crate::eval::compiler::Compiler;
+use crate::eval::Evaluator;
+use crate::syntax::DialectTypes;
+use crate::values::FrozenHeapRef;
+use crate::values::FrozenRef;
+use crate::values::FrozenStringValue;
+use crate::values::Value;
+
+#[derive(Debug, thiserror::Error)]
+enum CompiledModuleError {
+    #[error("Compiled module can only be evaluated in an empty module, but `{0}` is defined")]
+    ModuleNotEmpty(String),
+}
+
+/// Module after parsing, name resolution and code generation,
+/// created with [`AstModuleCompile::compile`].
+///
+/// The module is immutable and can be evaluated many times, possibly concurrently,
+/// with [`Evaluator::eval_compiled`], skipping the parsing, name resolution and
+/// bytecode generation of the module and its functions done for
+/// [`eval_module`](Evaluator::eval_module).
+///
+/// The bytecode is generated before any value is loaded, so unlike
+/// [`eval_module`](Evaluator::eval_module) it does not inline loaded values,
+/// until the functions are optimized when the module is frozen.
+/// The code is generated on each evaluation, as by [`eval_module`](Evaluator::eval_module),
+/// when a type refers to a module variable, when the module is typechecked,
+/// or when the [`Evaluator`] has attribute hooks.
+pub struct CompiledModule {
+    codemap: FrozenRef<'static, CodeMap>,
+    globals: FrozenRef<'static, Globals>,
+    /// Source statement, used for coverage.
+    statement: AstStmt,
+    /// Module names in slot order.
+    names: Vec<(FrozenStringValue, Visibility)>,
+    scopes: ModuleScopes<'static>,
+    /// Bytecode of the module, `None` if it must be generated on each evaluation.
+    code: Option<ModuleCode>,
+    docstring: Option<String>,
+    check_types: bool,
+    typecheck: bool,
+    /// Names, codemap, globals and bytecode are allocated here.
+    heap: FrozenHeapRef,
+}
+
+struct ModuleCode {
+    stmts: Vec<TopLevelStmtCompiled>,
+    module_slot_count: u32,
+    local_count: usize,
+    def_info: FrozenRef<'static, DefInfo>,
+}
+
+impl Debug for CompiledModule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompiledModule")
+            .field("filename", &self.codemap.filename())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Resolve names of an [`AstModule`] once to evaluate it many times.
+pub trait AstModuleCompile {
+    /// Check the module against the globals, resolve its names and generate its bytecode.
+    ///
+    /// Reports the same errors as [`eval_module`](Evaluator::eval_module)
+    /// before it starts executing the module. There is no evaluator yet, so names are
+    /// resolved as without [`enable_strict_names`](Evaluator::enable_strict_names):
+    /// only the first undefined variable is reported.
+    fn compile(&self, globals: &Globals) -> crate::Result<CompiledModule>;
+}
+
+impl AstModuleCompile for AstModule {
+    fn compile(&self, globals: &Globals) -> crate::Result<CompiledModule> {
+        let (codemap, statement, dialect, typecheck) = self.clone().into_parts();
+        // The code is generated in an empty module, so no module variable is inlined.
+        // The frozen heap of the module keeps everything alive.
+        let module = Module::new();
+        let heap = module.frozen_heap();
+        let names = MutableNames::new();
+        let codemap = heap.alloc_any(codemap);
+        let globals = heap.alloc_any(globals.dupe());
+        let docstring = DocString::extract_raw_starlark_docstring(&statement);
+        let scopes = ModuleScopes::check_module_err(
+            &names,
+            heap,
+            &HashMap::new(),
+            statement.clone(),
+            ScopeResolverGlobals {
+                globals: Some(globals),
+            },
+            codemap,
+            &dialect,
+            false,
+        )?;
+        // Names are allocated in `heap`, which is kept alive by the compiled module
+        // and the modules it is evaluated in.
+        let scopes = scopes.into_static();
+        let check_types = dialect.enable_types == DialectTypes::Enable;
+
+        let code = {
+            let ModuleScopes {
+                cst,
+                module_slot_count,
+                scope_data,
+                top_level_stmt_count,
+            } = scopes.clone();
+            let scope_names = scope_data.get_scope(ScopeId::module());
+            let local_names = heap.alloc_any_slice(&scope_names.used);
+            let def_info = heap.alloc_any(DefInfo::for_module(
+                codemap,
+                local_names,
+                heap.alloc_any_slice(&scope_names.parent),
+                globals,
+            ));
+            let mut eval = Evaluator::new(&module);
+            eval.with_module_frame(module_slot_count, def_info, |eval| {
+                let mut compiler = Compiler {
+                    scope_data,
+                    locals: Vec::new(),
+                    globals,
+                    codemap,
+                    eval,
+                    check_types,
+                    top_level_stmt_count,
+                    typecheck,
+                };
+                Ok(compiler
+                    .compile_module(cst, local_names)
+                    .map(|stmts| ModuleCode {
+                        stmts,
+                        module_slot_count,
+                        local_count: local_names.len(),
+                        def_info,
+                    }))
+            })?
+        };
+
+        Ok(CompiledModule {
+            codemap,
+            globals,
+            statement,
+            names: names.all_names_and_visibilities(),
+            scopes,
+            code,
+            docstring,
+            check_types,
+            typecheck,
+            heap: module.freeze()?.frozen_heap().dupe(),
+        })
+    }
+}
+
+impl<'v, 'a, 'e> Evaluator<'v, 'a, 'e> {
+    /// Evaluate a [`CompiledModule`] with this [`Evaluator`].
+    ///
+    /// The in-scope [`Module`](crate::environment::Module) must be empty, typically
+    /// created with [`Module::new`](crate::environment::Module::new) for each evaluation.
+    /// The result and errors are the same as for [`eval_module`](Evaluator::eval_module)
+    /// of the original [`AstModule`].
+    pub fn eval_compiled(&mut self, module: &CompiledModule) -> crate::Result<Value<'v>> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = Instant::now();
+
+        let names = self.module_env.mutable_names();
+        if let Some((name, _)) = names.all_names_and_slots().first() {
+            return Err(crate::Error::new_other(
+                CompiledModuleError::ModuleNotEmpty(name.as_str().to_owned()),
+            ));
+        }
+        for (name, vis) in &module.names {
+            names.add_name_visibility(*name, *vis);
+        }
+        self.module_env.frozen_heap().add_reference(&module.heap);
+
+        self.stmt_coverage
+            .add_module(&module.codemap, &module.statement);
+
+        if let Some(docstring) = &module.docstring {
+            self.module_env.set_docstring(docstring.clone())
+        }
+
+        let res = match &module.code {
+            Some(code)
+                if !module.typecheck && !self.static_typechecking && self.fold_constants() =>
+            {
+                self.with_module_frame(code.module_slot_count, code.def_info, |eval| {
+                    eval_compiled_module(eval, &code.stmts, code.local_count)
+                })
+            }
+            // Generate the code as `eval_module` does.
+            _ => self.eval_module_scopes(
+                module.scopes.clone(),
+                module.codemap,
+                module.globals,
+                module.check_types,
+                module.typecheck,
+            ),
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.module_env.add_eval_duration(start.elapsed());
+
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::thread;
+
+    use crate::environment::Globals;
+    use crate::environment::Module;
+    use crate::eval::compiler::def::Def;
+    use crate::eval::compiler::def::DefInfo;
+    use crate::eval::AstModuleCompile;
+    use crate::eval::CompiledModule;
+    use crate::eval::Evaluator;
+    use crate::eval::ReturnFileLoader;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+    use crate::values::ValueLike;
+
+    const PROGRAM: &str = r#"
+"""Template."""
+load("lib", "scale")
+def area(w, h):
+    return scale * w * h
+def check(x):
+    if x < 0:
+        fail("negative: " + str(x))
+    return x
+total = area(2, 3)
+check(total)
+"#;
+
+    fn parse(program: &str) -> AstModule {
+        AstModule::parse("t.star", program.to_owned(), &Dialect::AllOptionsInternal).unwrap()
+    }
+
+    fn eval_compiled(compiled: &CompiledModule, scale: i32) -> crate::Result<i32> {
+        let lib = Module::new();
+        lib.set("scale", lib.heap().alloc(scale));
+        let lib = lib.freeze().unwrap();
+        let modules = HashMap::from([("lib", &lib)]);
+        let loader = ReturnFileLoader { modules: &modules };
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_loader(&loader);
+        let res = eval.eval_compiled(compiled)?;
+        Ok(res.unpack_i32().unwrap())
+    }
+
+    #[test]
+    fn test_eval_compiled() {
+        let compiled = parse(PROGRAM).compile(&Globals::standard()).unwrap();
+        assert_eq!(6, eval_compiled(&compiled, 1).unwrap());
+        assert_eq!(60, eval_compiled(&compiled, 10).unwrap());
+
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        let compiled = parse("x = [1]\ndef f():\n    return x[0]\ny = f()\n")
+            .compile(&Globals::standard())
+            .unwrap();
+        eval.eval_compiled(&compiled).unwrap();
+        drop(eval);
+        let module = module.freeze().unwrap();
+        assert_eq!(Some(1), module.get("y").unwrap().value().unpack_i32());
+        assert!(module.get("f").is_ok());
+    }
+
+    #[test]
+    fn test_eval_compiled_shares_code() {
+        fn area_def_info(compiled: &CompiledModule) -> *const DefInfo {
+            let module = Module::new();
+            let mut eval = Evaluator::new(&module);
+            eval.eval_compiled(compiled).unwrap();
+            let area = module.get("area").unwrap();
+            area.downcast_ref::<Def>().unwrap().def_info.as_ref()
+        }
+
+        let program = "def area(w, h):\n    return w * h\nx = area(2, 3)\n";
+        let compiled = parse(program).compile(&Globals::standard()).unwrap();
+        assert!(compiled.code.is_some());
+        assert_eq!(area_def_info(&compiled), area_def_info(&compiled));
+    }
+
+    #[test]
+    fn test_eval_compiled_type_of_module_variable() {
+        // The type of `x` is only known after `Size = int` runs,
+        // so the code is generated on each evaluation.
+        let program = "Size = int\ndef area(x: Size) -> Size:\n    return x * x\narea(3)\n";
+        let compiled = parse(program).compile(&Globals::standard()).unwrap();
+        assert!(compiled.code.is_none());
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        let res = eval.eval_compiled(&compiled).unwrap();
+        assert_eq!(Some(9), res.unpack_i32());
+    }
+
+    #[test]
+    fn test_eval_compiled_same_errors() {
+        let err =
+            eval_compiled(&parse(PROGRAM).compile(&Globals::standard()).unwrap(), -1).unwrap_err();
+        let module = Module::new();
+        let lib = Module::new();
+        lib.set("scale", lib.heap().alloc(-1));
+        let lib = lib.freeze().unwrap();
+        let modules = HashMap::from([("lib", &lib)]);
+        let loader = ReturnFileLoader { modules: &modules };
+        let mut eval = Evaluator::new(&module);
+        eval.set_loader(&loader);
+        let expected = eval
+            .eval_module(parse(PROGRAM), &Globals::standard())
+            .unwrap_err();
+        assert!(err.to_string().contains("negative: -6"), "{err}");
+        assert_eq!(format!("{expected:#}"), format!("{err:#}"));
+
+        let program = "def f():\n    return undefined\n";
+        let err = parse(program).compile(&Globals::standard()).unwrap_err();
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        let expected = eval
+            .eval_module(parse(program), &Globals::standard())
+            .unwrap_err();
+        assert_eq!(format!("{expected:#}"), format!("{err:#}"));
+    }
+
+    #[test]
+    fn test_eval_compiled_non_empty_module() {
+        let compiled = parse("x = 1").compile(&Globals::standard()).unwrap();
+        let module = Module::new();
+        module.set("y", module.heap().alloc(2));
+        let mut eval = Evaluator::new(&module);
+        let err = eval.eval_compiled(&compiled).unwrap_err();
+        assert!(err.to_string().contains("empty module"), "{err}");
+    }
+
+    #[test]
+    fn test_eval_compiled_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<CompiledModule>();
+
+        let compiled = parse(PROGRAM).compile(&Globals::standard()).unwrap();
+        thread::scope(|s| {
+            let threads: Vec<_> = (1..=2)
+                .map(|t| {
+                    let compiled = &compiled;
+                    s.spawn(move || {
+                        for i in 0..50 {
+                            assert_eq!(6 * (t + i), eval_compiled(compiled, t + i).unwrap());
+                        }
+                    })
+                })
+                .collect();
+            for t in threads {
+                t.join().unwrap();
+            }
+        });
+    }
+}
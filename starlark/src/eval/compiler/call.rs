@@ -53,7 +53,7 @@ impl CallCompiled {
         args: ArgsCompiledValue,
         ctx: &mut OptCtx,
     ) -> ExprCompiled {
-        if let Some(this) = this.as_value() {
+        if let (Some(this), true) = (this.as_value(), ctx.constant_folding()) {
             if let Some(v) = ExprCompiled::compile_time_getattr(this, field, ctx) {
                 let v = ExprCompiled::Value(v);
                 let v = IrSpanned {
@@ -213,7 +213,7 @@ impl CallCompiled {
     ) -> Option<ExprCompiled> {
         let fun = fun.as_value()?;
 
-        if !fun.speculative_exec_safe() {
+        if !fun.speculative_exec_safe() || !ctx.constant_folding() {
             return None;
         }
 
@@ -72,6 +72,7 @@ use crate::eval::compiler::stmt::StmtCompileContext;
 use crate::eval::compiler::stmt::StmtsCompiled;
 use crate::eval::compiler::Compiler;
 use crate::eval::runtime::arguments::ArgumentsImpl;
+use crate::eval::runtime::arguments::ParamError;
 use crate::eval::runtime::arguments::ResolvedArgName;
 use crate::eval::runtime::evaluator::Evaluator;
 use crate::eval::runtime::frame_span::FrameSpan;
@@ -702,7 +703,13 @@ where
                 None => {
                     panic!("Not allowed optional unassigned with type annotations on them")
                 }
-                Some(v) => ty.check_type(v, Some(arg_name))?,
+                Some(v) => {
+                    ty.check_type_in_eval(v, Some(arg_name), eval)
+                        .map_err(|e| match self.parameters.param_ref(i.0 as usize) {
+                            Some(param) => ParamError::wrap(e, param),
+                            None => e,
+                        })?
+                }
             }
         }
         if let Some(start) = start {
@@ -725,7 +732,7 @@ where
         } else {
             None
         };
-        return_type_ty.check_type(ret, None)?;
+        return_type_ty.check_type_in_eval(ret, None, eval)?;
         if let Some(start) = start {
             eval.typecheck_profile
                 .add(self.def_info.name, start.elapsed());
@@ -858,6 +865,7 @@ impl FrozenDef {
                     module: def_module.as_ref(),
                     heap,
                     frozen_heap,
+                    constant_folding: self.def_info.stmt_compile_context.constant_folding,
                 },
                 self.parameters.len().try_into().unwrap(),
             ))
@@ -35,8 +35,11 @@ use thiserror::Error;
 
 use crate::codemap::Spanned;
 use crate::collections::symbol::symbol::Symbol;
+use crate::collections::SmallMap;
 use crate::environment::slots::ModuleSlotId;
 use crate::errors::did_you_mean::did_you_mean;
+use crate::errors::did_you_mean::format_did_you_mean;
+use crate::eval::bc::member_cache::MemberCache;
 use crate::eval::compiler::args::ArgsCompiledValue;
 use crate::eval::compiler::call::CallCompiled;
 use crate::eval::compiler::compr::ComprCompiled;
@@ -67,6 +70,7 @@ use crate::values::function::FrozenBoundMethod;
 use crate::values::list::ListRef;
 use crate::values::range::Range;
 use crate::values::string::interpolation::parse_percent_s_one;
+use crate::values::types::dict::value::FrozenDictData;
 use crate::values::types::dict::Dict;
 use crate::values::types::ellipsis::Ellipsis;
 use crate::values::types::float::StarlarkFloat;
@@ -154,7 +158,9 @@ impl Builtin1 {
             Builtin1::Not => Some(Value::new_bool(!v.to_value().to_bool())),
             Builtin1::TypeIs(t) => Some(Value::new_bool(v.to_value().get_type_value() == *t)),
             Builtin1::FormatOne(before, after) => {
-                Some(format_one(before, v.to_value(), after, ctx.heap()).to_value())
+                format_one(before, v.to_value(), after, ctx.heap())
+                    .map(|s| s.to_value())
+                    .ok()
             }
             Builtin1::PercentSOne(before, after) => {
                 percent_s_one(before, v.to_value(), after, ctx.heap())
@@ -565,9 +571,13 @@ impl IrSpanned<ExprCompiled> {
 }
 
 impl ExprCompiled {
-    fn equals(l: IrSpanned<ExprCompiled>, r: IrSpanned<ExprCompiled>) -> IrSpanned<ExprCompiled> {
+    fn equals(
+        l: IrSpanned<ExprCompiled>,
+        r: IrSpanned<ExprCompiled>,
+        ctx: &mut OptCtx,
+    ) -> IrSpanned<ExprCompiled> {
         let span = l.span.merge(&r.span);
-        if let (Some(l), Some(r)) = (l.as_value(), r.as_value()) {
+        if let (Some(l), Some(r), true) = (l.as_value(), r.as_value(), ctx.constant_folding()) {
             // If comparison fails, let it fail in runtime.
             if let Ok(r) = l.equals(r.to_value()) {
                 return IrSpanned {
@@ -672,7 +682,7 @@ impl ExprCompiled {
         after: FrozenStringValue,
         ctx: &mut OptCtx,
     ) -> ExprCompiled {
-        if let Some(arg) = arg.as_value() {
+        if let (Some(arg), true) = (arg.as_value(), ctx.constant_folding()) {
             if let Ok(value) =
                 percent_s_one(before.as_str(), arg.to_value(), after.as_str(), ctx.heap())
             {
@@ -690,10 +700,11 @@ impl ExprCompiled {
         after: FrozenStringValue,
         ctx: &mut OptCtx,
     ) -> ExprCompiled {
-        if let Some(arg) = arg.as_value() {
-            let value = format_one(&before, arg.to_value(), &after, ctx.heap());
-            let value = ctx.frozen_heap().alloc_str_intern(value.as_str());
-            return ExprCompiled::Value(value.to_frozen_value());
+        if let (Some(arg), true) = (arg.as_value(), ctx.constant_folding()) {
+            if let Ok(value) = format_one(&before, arg.to_value(), &after, ctx.heap()) {
+                let value = ctx.frozen_heap().alloc_str_intern(value.as_str());
+                return ExprCompiled::Value(value.to_frozen_value());
+            }
         }
 
         ExprCompiled::Builtin1(Builtin1::FormatOne(before, after), Box::new(arg))
@@ -715,7 +726,12 @@ impl ExprCompiled {
         let span = l.span.merge(&r.span);
         // Binary operators should have no side effects,
         // but to avoid possible problems, we only fold binary operators on builtin types.
-        if let (Some(l), Some(r)) = (l.as_builtin_value(), r.as_builtin_value()) {
+        // Operators which fail are not folded, so they fail at runtime, when executed.
+        if let (Some(l), Some(r), true) = (
+            l.as_builtin_value(),
+            r.as_builtin_value(),
+            ctx.constant_folding(),
+        ) {
             if let Ok(v) = bin_op.eval(l.to_value(), r.to_value(), ctx.heap()) {
                 if let Some(v) = ExprCompiled::try_value(span, v, ctx.frozen_heap()) {
                     return v;
@@ -726,8 +742,9 @@ impl ExprCompiled {
         match bin_op {
             Builtin2::Percent => ExprCompiled::percent(l, r, ctx),
             Builtin2::Add => ExprCompiled::add(l, r),
-            Builtin2::Equals => ExprCompiled::equals(l, r).node,
+            Builtin2::Equals => ExprCompiled::equals(l, r, ctx).node,
             Builtin2::ArrayIndex => ExprCompiled::index(l, r, ctx),
+            Builtin2::In => ExprCompiled::is_in(l, r, ctx),
             bin_op => ExprCompiled::Builtin2(bin_op, Box::new((l, r))),
         }
     }
@@ -769,7 +786,7 @@ impl ExprCompiled {
         expr: IrSpanned<ExprCompiled>,
         ctx: &mut OptCtx,
     ) -> ExprCompiled {
-        if let Some(v) = expr.as_builtin_value() {
+        if let (Some(v), true) = (expr.as_builtin_value(), ctx.constant_folding()) {
             if let Some(v) = op.eval(v, ctx) {
                 if let Some(v) = ExprCompiled::try_value(expr.span, v, ctx.frozen_heap()) {
                     return v;
@@ -890,7 +907,7 @@ impl ExprCompiled {
         field: &Symbol,
         ctx: &mut OptCtx,
     ) -> ExprCompiled {
-        if let Some(left) = object.as_value() {
+        if let (Some(left), true) = (object.as_value(), ctx.constant_folding()) {
             if let Some(v) = Self::compile_time_getattr(left, field, ctx) {
                 return ExprCompiled::Value(v);
             }
@@ -907,11 +924,12 @@ impl ExprCompiled {
         step: Option<IrSpanned<ExprCompiled>>,
         ctx: &mut OptCtx,
     ) -> ExprCompiled {
-        if let (Some(array), Some(start), Some(stop), Some(step)) = (
+        if let (Some(array), Some(start), Some(stop), Some(step), true) = (
             array.as_builtin_value(),
             start.as_ref().map(|e| e.as_value()),
             stop.as_ref().map(|e| e.as_value()),
             step.as_ref().map(|e| e.as_value()),
+            ctx.constant_folding(),
         ) {
             if let Ok(v) = array.to_value().slice(
                 start.map(|v| v.to_value()),
@@ -933,7 +951,11 @@ impl ExprCompiled {
         ctx: &mut OptCtx,
     ) -> ExprCompiled {
         let span = array.span.merge(&index.span);
-        if let (Some(array), Some(index)) = (array.as_builtin_value(), index.as_value()) {
+        if let (Some(array), Some(index), true) = (
+            array.as_builtin_value(),
+            index.as_value(),
+            ctx.constant_folding(),
+        ) {
             if let Ok(v) = array.to_value().at(index.to_value(), ctx.heap()) {
                 if let Some(expr) = ExprCompiled::try_value(span, v, ctx.frozen_heap()) {
                     return expr;
@@ -943,6 +965,46 @@ impl ExprCompiled {
         ExprCompiled::Builtin2(Builtin2::ArrayIndex, Box::new((array, index)))
     }
 
+    /// Optimize `x in {...}` with constant keys and values
+    /// to use a dict allocated once on the frozen heap.
+    /// (List literals are already replaced with tuples by `list_to_tuple`.)
+    fn is_in(
+        needle: IrSpanned<ExprCompiled>,
+        collection: IrSpanned<ExprCompiled>,
+        ctx: &mut OptCtx,
+    ) -> ExprCompiled {
+        let hoisted = match &collection.node {
+            ExprCompiled::Dict(kvs) if ctx.constant_folding() => {
+                Self::try_frozen_dict(kvs, ctx.frozen_heap())
+            }
+            _ => None,
+        };
+        let collection = match hoisted {
+            Some(v) => IrSpanned {
+                span: collection.span,
+                node: ExprCompiled::Value(v),
+            },
+            None => collection,
+        };
+        ExprCompiled::Builtin2(Builtin2::In, Box::new((needle, collection)))
+    }
+
+    /// Dict literal with constant keys and values, unless constructing it would fail.
+    fn try_frozen_dict(
+        kvs: &[(IrSpanned<ExprCompiled>, IrSpanned<ExprCompiled>)],
+        heap: &FrozenHeap,
+    ) -> Option<FrozenValue> {
+        let mut content = SmallMap::with_capacity(kvs.len());
+        for (k, v) in kvs {
+            let k = k.as_value()?.get_hashed().ok()?;
+            if content.insert_hashed(k, v.as_value()?).is_some() {
+                // Duplicate keys are an error at runtime.
+                return None;
+            }
+        }
+        Some(heap.alloc(FrozenDictData { content }))
+    }
+
     pub(crate) fn index2(
         array: IrSpanned<ExprCompiled>,
         index0: IrSpanned<ExprCompiled>,
@@ -1109,12 +1171,16 @@ impl<P: AstPayload> CompilerExprUtil<P> for ExprP<P> {
 #[cold]
 #[inline(never)]
 fn get_attr_no_attr_error<'v>(x: Value<'v>, attribute: &Symbol) -> crate::Error {
-    match did_you_mean(attribute.as_str(), x.dir_attr().iter().map(|s| s.as_str())) {
+    let dir_attr = x.dir_attr();
+    match format_did_you_mean(&did_you_mean(
+        attribute.as_str(),
+        dir_attr.iter().map(|s| s.as_str()),
+    )) {
         None => ValueError::NoAttr(x.get_type().to_owned(), attribute.as_str().to_owned()).into(),
         Some(better) => ValueError::NoAttrDidYouMean(
             x.get_type().to_owned(),
             attribute.as_str().to_owned(),
-            better.to_owned(),
+            better,
         )
         .into(),
     }
@@ -1159,24 +1225,39 @@ pub(crate) fn get_attr_hashed_raw<'v>(
     }
 }
 
-pub(crate) fn get_attr_hashed_bind<'v>(
+/// [`get_attr_hashed_raw`] remembering members found in `get_methods` in the cache.
+#[inline(always)]
+pub(crate) fn get_attr_hashed_raw_cached<'v>(
     x: Value<'v>,
     attribute: &Symbol,
+    cache: &MemberCache,
     heap: &'v Heap,
-) -> crate::Result<Value<'v>> {
-    let aref = x.get_ref();
-    if let Some(methods) = aref.vtable().methods() {
-        if let Some(v) = methods.get_frozen_symbol(attribute) {
-            return v.bind(x, heap);
-        }
+) -> crate::Result<MemberOrValue<'v, 'static>> {
+    let vtable = x.vtable();
+    if let Some(member) = cache.get(vtable) {
+        return Ok(MemberOrValue::Member(member));
     }
-    match aref.get_attr_hashed(attribute.as_str_hashed(), heap) {
-        None => Err(get_attr_no_attr_error(x, attribute)),
-        Some(x) => {
-            // Only `get_methods` is allowed to return unbound methods or attributes.
-            // Both types are crate private, so we assume `get_attr` never returns them.
-            Ok(x)
-        }
+    let member = get_attr_hashed_raw(x, attribute, heap)?;
+    if let MemberOrValue::Member(m) = member {
+        cache.set(vtable, m);
+    }
+    Ok(member)
+}
+
+/// Get an attribute, binding methods to the object,
+/// remembering members found in `get_methods` in the cache.
+#[inline(always)]
+pub(crate) fn get_attr_hashed_bind_cached<'v>(
+    x: Value<'v>,
+    attribute: &Symbol,
+    cache: &MemberCache,
+    heap: &'v Heap,
+) -> crate::Result<Value<'v>> {
+    match get_attr_hashed_raw_cached(x, attribute, cache, heap)? {
+        MemberOrValue::Member(member) => member.bind(x, heap),
+        // Only `get_methods` is allowed to return unbound methods or attributes.
+        // Both types are crate private, so we assume `get_attr` never returns them.
+        MemberOrValue::Value(v) => Ok(v),
     }
 }
 
@@ -1317,7 +1398,12 @@ impl<'v, 'a, 'e> Compiler<'v, 'a, 'e, '_> {
                 ExprCompiled::un_op(span, &Builtin1::BitNot, expr, &mut self.opt_ctx())
             }
             ExprP::Op(left, op, right) => {
-                if let Some(x) = ExprP::reduces_to_string(*op, left, right) {
+                let string = if self.eval.fold_constants() {
+                    ExprP::reduces_to_string(*op, left, right)
+                } else {
+                    None
+                };
+                if let Some(x) = string {
                     // Note there's const propagation for `+` on compiled expressions,
                     // but special handling of `+` on AST might be slightly more efficient
                     // (no unnecessary allocations on the heap). So keep it.
@@ -1335,9 +1421,14 @@ impl<'v, 'a, 'e> Compiler<'v, 'a, 'e, '_> {
                     match op {
                         BinOp::Or => return Ok(ExprCompiled::or(l, r)),
                         BinOp::And => return Ok(ExprCompiled::and(l, r)),
-                        BinOp::Equal => return Ok(ExprCompiled::equals(l, r)),
+                        BinOp::Equal => {
+                            return Ok(ExprCompiled::equals(l, r, &mut self.opt_ctx()));
+                        }
                         BinOp::NotEqual => {
-                            return Ok(ExprCompiled::not(span, ExprCompiled::equals(l, r)));
+                            return Ok(ExprCompiled::not(
+                                span,
+                                ExprCompiled::equals(l, r, &mut self.opt_ctx()),
+                            ));
                         }
                         BinOp::Less => ExprCompiled::bin_op(
                             Builtin2::Compare(CompareOp::Less),
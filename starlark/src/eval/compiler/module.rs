@@ -17,6 +17,7 @@
 
 //! Compile and evaluate module top-level statements.
 
+use itertools::Itertools;
 use starlark_syntax::eval_exception::EvalException;
 use starlark_syntax::syntax::ast::LoadP;
 use starlark_syntax::syntax::ast::StmtP;
@@ -24,6 +25,8 @@ use starlark_syntax::syntax::top_level_stmts::top_level_stmts_mut;
 
 use crate::codemap::Spanned;
 use crate::const_frozen_string;
+use crate::environment::slots::ModuleSlotId;
+use crate::eval::bc::bytecode::Bc;
 use crate::eval::bc::frame::alloca_frame;
 use crate::eval::compiler::add_span_to_expr_error;
 use crate::eval::compiler::expr_throw;
@@ -34,12 +37,15 @@ use crate::eval::compiler::scope::Slot;
 use crate::eval::compiler::Compiler;
 use crate::eval::runtime::frame_span::FrameSpan;
 use crate::eval::runtime::frozen_file_span::FrozenFileSpan;
+use crate::eval::runtime::load_stack::LoadCycle;
+use crate::eval::Evaluator;
 use crate::typing::bindings::BindingsCollect;
 use crate::typing::error::InternalError;
 use crate::typing::fill_types_for_lint::ModuleVarTypes;
 use crate::typing::mode::TypecheckMode;
 use crate::typing::typecheck::solve_bindings;
 use crate::typing::Ty;
+use crate::typing::TypecheckErrors;
 use crate::typing::TypingOracleCtx;
 use crate::values::FrozenRef;
 use crate::values::FrozenStringValue;
@@ -49,57 +55,109 @@ use crate::values::Value;
 enum ModuleError {
     #[error("No imports are available, you tried `{0}` (no call to `Evaluator.set_loader`)")]
     NoImportsAvailable(String),
+    #[error("Cyclic load: {0}")]
+    CyclicLoad(String),
     #[error("Unexpected statement (internal error)")]
     UnexpectedStatement,
     #[error("Top level stmt count mismatch (internal error)")]
     TopLevelStmtCountMismatch,
 }
 
-impl<'v> Compiler<'v, '_, '_, '_> {
-    fn eval_load(&mut self, load: Spanned<&LoadP<CstPayload>>) -> Result<(), EvalException> {
-        let name = &load.node.module.node;
+/// `load` statement with the slots of the loaded symbols resolved.
+pub(crate) struct ModuleLoad {
+    module: String,
+    span: FrameSpan,
+    /// Slot of the local name, name in the loaded module and span of each symbol.
+    symbols: Vec<(ModuleSlotId, String, FrameSpan)>,
+}
 
-        let span = FrameSpan::new(FrozenFileSpan::new(self.codemap, load.span));
+impl ModuleLoad {
+    pub(crate) fn eval(&self, eval: &mut Evaluator) -> Result<(), EvalException> {
+        let name = &self.module;
+        let span = self.span;
 
-        let loadenv = match self.eval.loader.as_ref() {
+        eval.trace_event(|| serde_json::json!({"event": "load", "module": name}));
+
+        let loadenv = match eval.loader.as_ref() {
             None => {
                 return Err(add_span_to_expr_error(
                     crate::Error::new_other(ModuleError::NoImportsAvailable(name.to_owned())),
                     span,
-                    self.eval,
+                    eval,
                 ));
             }
-            Some(loader) => expr_throw(loader.load(name), span, self.eval)?,
+            Some(loader) => {
+                let id = expr_throw(loader.resolve(name), span, eval)?;
+                let context = match eval.load_context(&id) {
+                    Ok(context) => context,
+                    Err(LoadCycle(cycle)) => {
+                        return Err(add_span_to_expr_error(
+                            crate::Error::new_other(ModuleError::CyclicLoad(
+                                cycle.iter().map(|p| format!("`{p}`")).join(" -> "),
+                            )),
+                            span,
+                            eval,
+                        ));
+                    }
+                };
+                expr_throw(loader.load_with_context(name, &context), span, eval)?
+            }
         };
 
-        for load_arg in &load.node.args {
-            let (slot, _captured) = self
-                .scope_data
-                .get_assign_ident_slot(&load_arg.local, &self.codemap);
-            let slot = match slot {
-                Slot::Local(..) => unreachable!("symbol need to be resolved to module"),
-                Slot::Module(slot) => slot,
-            };
+        for (slot, their, span) in &self.symbols {
             let value = expr_throw(
-                self.eval
-                    .module_env
-                    .load_symbol(&loadenv, &load_arg.their.node),
-                FrameSpan::new(FrozenFileSpan::new(self.codemap, load_arg.span())),
-                self.eval,
+                eval.module_env.load_symbol(&loadenv, name, their),
+                *span,
+                eval,
             )?;
-            self.eval.set_slot_module(slot, value)
+            eval.set_slot_module(*slot, value)
         }
 
         Ok(())
     }
+}
 
-    /// Compile and evaluate regular statement.
+/// Top-level statement with its bytecode, see [`Compiler::compile_module`].
+pub(crate) enum TopLevelStmtCompiled {
+    Load(ModuleLoad),
+    Stmt(Bc),
+}
+
+impl<'v> Compiler<'v, '_, '_, '_> {
+    fn module_load(&self, load: Spanned<&LoadP<CstPayload>>) -> ModuleLoad {
+        let symbols = load
+            .node
+            .args
+            .iter()
+            .map(|load_arg| {
+                let (slot, _captured) = self
+                    .scope_data
+                    .get_assign_ident_slot(&load_arg.local, &self.codemap);
+                let slot = match slot {
+                    Slot::Local(..) => unreachable!("symbol need to be resolved to module"),
+                    Slot::Module(slot) => slot,
+                };
+                (
+                    slot,
+                    load_arg.their.node.clone(),
+                    FrameSpan::new(FrozenFileSpan::new(self.codemap, load_arg.span())),
+                )
+            })
+            .collect();
+        ModuleLoad {
+            module: load.node.module.node.clone(),
+            span: FrameSpan::new(FrozenFileSpan::new(self.codemap, load.span)),
+            symbols,
+        }
+    }
+
+    /// Compile regular statement.
     /// Regular statement is a statement which is not `load` or a sequence of statements.
-    fn eval_regular_top_level_stmt(
+    fn compile_regular_top_level_stmt(
         &mut self,
         stmt: &mut CstStmt,
         local_names: FrozenRef<'static, [FrozenStringValue]>,
-    ) -> Result<Value<'v>, EvalException> {
+    ) -> Result<Bc, EvalException> {
         if matches!(stmt.node, StmtP::Statements(_) | StmtP::Load(_)) {
             return Err(EvalException::new_anyhow(
                 ModuleError::UnexpectedStatement.into(),
@@ -111,23 +169,22 @@ impl<'v> Compiler<'v, '_, '_, '_> {
         let stmt = self
             .module_top_level_stmt(stmt)
             .map_err(|e| e.into_eval_exception())?;
-        let bc = stmt.as_bc(
+        Ok(stmt.as_bc(
             &self.compile_context(false),
             local_names,
             0,
             self.eval.module_env.frozen_heap(),
-        );
-        // We don't preserve locals between top level statements.
-        // That is OK for now: the only locals used in module evaluation
-        // are comprehension bindings.
-        let local_count = local_names.len().try_into().unwrap();
-        alloca_frame(
-            self.eval,
-            local_count,
-            bc.max_stack_size,
-            bc.max_loop_depth,
-            |eval| eval.eval_bc(const_frozen_string!("module").to_value(), &bc),
-        )
+        ))
+    }
+
+    /// Compile and evaluate regular statement.
+    fn eval_regular_top_level_stmt(
+        &mut self,
+        stmt: &mut CstStmt,
+        local_names: FrozenRef<'static, [FrozenStringValue]>,
+    ) -> Result<Value<'v>, EvalException> {
+        let bc = self.compile_regular_top_level_stmt(stmt, local_names)?;
+        eval_top_level_bc(self.eval, &bc, local_names.len())
     }
 
     #[allow(clippy::mut_mut)] // Another false positive.
@@ -152,10 +209,11 @@ impl<'v> Compiler<'v, '_, '_, '_> {
 
             match &mut stmt.node {
                 StmtP::Load(load) => {
-                    self.eval_load(Spanned {
+                    self.module_load(Spanned {
                         node: load,
                         span: stmt.span,
-                    })?;
+                    })
+                    .eval(self.eval)?;
                     last = Value::new_none();
                 }
                 _ => last = self.eval_regular_top_level_stmt(stmt, local_names)?,
@@ -177,6 +235,7 @@ impl<'v> Compiler<'v, '_, '_, '_> {
             codemap: &self.codemap,
         };
         let module_var_types = self.mk_module_var_types();
+        let mut all_errors = Vec::new();
         for top in stmts.iter_mut() {
             if let StmtP::Def(_) = &mut top.node {
                 let BindingsCollect { bindings, .. } = BindingsCollect::collect_one(
@@ -191,13 +250,24 @@ impl<'v> Compiler<'v, '_, '_, '_> {
                     Err(e) => return Err(e.into_eval_exception()),
                 };
 
-                if let Some(error) = errors.into_iter().next() {
-                    return Err(error.into_eval_exception());
-                }
+                all_errors.extend(errors);
             }
         }
 
-        Ok(())
+        // Report every violation at once, located at the first one.
+        if all_errors.len() <= 1 {
+            return match all_errors.pop() {
+                Some(error) => Err(error.into_eval_exception()),
+                None => Ok(()),
+            };
+        }
+        let all_errors = all_errors.into_iter().map(|e| e.into_error()).collect_vec();
+        let span = all_errors[0].span().cloned();
+        let error = crate::Error::new_other(TypecheckErrors::new(all_errors));
+        Err(match span {
+            Some(span) => EvalException::new(error, span.span, &span.file),
+            None => EvalException::new_unknown_span(error),
+        })
     }
 
     fn mk_module_var_types(&self) -> ModuleVarTypes {
@@ -222,4 +292,77 @@ impl<'v> Compiler<'v, '_, '_, '_> {
         assert!(self.locals.is_empty());
         Ok(value)
     }
+
+    /// Generate the bytecode of all the top-level statements without running them,
+    /// so unlike [`eval_module`](Self::eval_module) it does not inline the values
+    /// of module variables, and the code can run in any module with the same names.
+    ///
+    /// Returns `None` if a statement cannot be compiled before the statements
+    /// before it run, i.e. a type refers to a module variable.
+    #[allow(clippy::mut_mut)] // Another false positive.
+    pub(crate) fn compile_module(
+        &mut self,
+        mut stmt: CstStmt,
+        local_names: FrozenRef<'static, [FrozenStringValue]>,
+    ) -> Option<Vec<TopLevelStmtCompiled>> {
+        let mut stmts = top_level_stmts_mut(&mut stmt);
+        if stmts.len() != self.top_level_stmt_count {
+            return None;
+        }
+        self.enter_scope(ScopeId::module());
+        let mut compiled = Vec::with_capacity(stmts.len());
+        for stmt in stmts.iter_mut() {
+            self.populate_types_in_stmt(stmt).ok()?;
+            compiled.push(match &mut stmt.node {
+                StmtP::Load(load) => TopLevelStmtCompiled::Load(self.module_load(Spanned {
+                    node: load,
+                    span: stmt.span,
+                })),
+                _ => TopLevelStmtCompiled::Stmt(
+                    self.compile_regular_top_level_stmt(stmt, local_names)
+                        .ok()?,
+                ),
+            });
+        }
+        self.exit_scope();
+        Some(compiled)
+    }
+}
+
+/// Run the bytecode of a top-level statement.
+fn eval_top_level_bc<'v>(
+    eval: &mut Evaluator<'v, '_, '_>,
+    bc: &Bc,
+    local_count: usize,
+) -> Result<Value<'v>, EvalException> {
+    // We don't preserve locals between top level statements.
+    // That is OK for now: the only locals used in module evaluation
+    // are comprehension bindings.
+    let local_count = local_count.try_into().unwrap();
+    alloca_frame(
+        eval,
+        local_count,
+        bc.max_stack_size,
+        bc.max_loop_depth,
+        |eval| eval.eval_bc(const_frozen_string!("module").to_value(), bc),
+    )
+}
+
+/// Run the statements generated by [`Compiler::compile_module`].
+pub(crate) fn eval_compiled_module<'v>(
+    eval: &mut Evaluator<'v, '_, '_>,
+    stmts: &[TopLevelStmtCompiled],
+    local_count: usize,
+) -> Result<Value<'v>, EvalException> {
+    let mut last = Value::new_none();
+    for stmt in stmts {
+        match stmt {
+            TopLevelStmtCompiled::Load(load) => {
+                load.eval(eval)?;
+                last = Value::new_none();
+            }
+            TopLevelStmtCompiled::Stmt(bc) => last = eval_top_level_bc(eval, bc, local_count)?,
+        }
+    }
+    Ok(last)
 }
@@ -26,6 +26,7 @@ pub(crate) trait OptCtxEval<'v, 'a, 'e> {
     fn frozen_heap(&self) -> &FrozenHeap;
     fn eval(&mut self) -> Option<&mut Evaluator<'v, 'a, 'e>>;
     fn frozen_module(&self) -> Option<&FrozenModuleData>;
+    fn constant_folding(&self) -> bool;
 }
 
 impl<'v, 'a, 'e> OptCtxEval<'v, 'a, 'e> for OptimizeOnFreezeContext<'v, 'a> {
@@ -44,6 +45,10 @@ impl<'v, 'a, 'e> OptCtxEval<'v, 'a, 'e> for OptimizeOnFreezeContext<'v, 'a> {
     fn frozen_module(&self) -> Option<&FrozenModuleData> {
         Some(self.module)
     }
+
+    fn constant_folding(&self) -> bool {
+        self.constant_folding
+    }
 }
 
 impl<'v, 'a, 'e> OptCtxEval<'v, 'a, 'e> for Evaluator<'v, 'a, 'e> {
@@ -62,6 +67,10 @@ impl<'v, 'a, 'e> OptCtxEval<'v, 'a, 'e> for Evaluator<'v, 'a, 'e> {
     fn frozen_module(&self) -> Option<&FrozenModuleData> {
         None
     }
+
+    fn constant_folding(&self) -> bool {
+        self.fold_constants()
+    }
 }
 
 /// Optimization context.
@@ -98,4 +107,9 @@ impl<'v, 'a, 'e: 'a, 'x> OptCtx<'v, 'a, 'e, 'x> {
     pub(crate) fn frozen_module(&self) -> Option<&FrozenModuleData> {
         self.eval.frozen_module()
     }
+
+    /// Constant expressions may be evaluated at compile time.
+    pub(crate) fn constant_folding(&self) -> bool {
+        self.eval.constant_folding()
+    }
 }
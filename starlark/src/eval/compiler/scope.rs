@@ -20,6 +20,7 @@ pub(crate) mod scope_resolver_globals;
 mod tests;
 
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::iter;
 use std::marker::PhantomData;
 use std::mem;
@@ -33,7 +34,9 @@ use starlark_syntax::syntax::ast::AssignIdent;
 use starlark_syntax::syntax::ast::AssignP;
 use starlark_syntax::syntax::ast::AssignTarget;
 use starlark_syntax::syntax::ast::AstAssignIdentP;
+use starlark_syntax::syntax::ast::AstPayload;
 use starlark_syntax::syntax::ast::AstStmt;
+use starlark_syntax::syntax::ast::AstStmtP;
 use starlark_syntax::syntax::ast::ClauseP;
 use starlark_syntax::syntax::ast::DefP;
 use starlark_syntax::syntax::ast::ExprP;
@@ -53,6 +56,7 @@ use crate::environment::names::MutableNames;
 use crate::environment::slots::ModuleSlotId;
 use crate::environment::Module;
 use crate::errors::did_you_mean::did_you_mean;
+use crate::errors::did_you_mean::format_did_you_mean;
 use crate::eval::compiler::def::CopySlotFromParent;
 use crate::eval::compiler::scope::payload::CstAssignIdent;
 use crate::eval::compiler::scope::payload::CstAssignTarget;
@@ -74,13 +78,17 @@ use crate::values::FrozenStringValue;
 use crate::values::FrozenValue;
 
 #[derive(Debug, thiserror::Error)]
-enum ScopeError {
+pub(crate) enum ScopeError {
     #[error("Variable `{0}` not found")]
     VariableNotFound(String),
-    #[error("Variable `{0}` not found, did you mean `{1}`?")]
+    #[error("Variable `{0}` not found, did you mean {1}?")]
     VariableNotFoundDidYouMean(String, String),
+    #[error("Variables not found:{}", display_errors(.0))]
+    VariablesNotFound(Vec<crate::Error>),
     #[error("Identifiers in type expressions can only refer globals or builtins: `{0}`")]
     TypeExpressionGlobalOrBuiltin(String),
+    #[error("Cannot assign to `{0}`, it is a constant defined by the host")]
+    AssignToConstant(String),
 }
 
 impl From<ScopeError> for crate::Error {
@@ -89,6 +97,18 @@ impl From<ScopeError> for crate::Error {
     }
 }
 
+/// Each error on its own line, with its location.
+fn display_errors(errors: &[crate::Error]) -> String {
+    let mut s = String::new();
+    for e in errors {
+        match e.span() {
+            Some(span) => write!(s, "\n  {}: {}", span, e.without_diagnostic()).unwrap(),
+            None => write!(s, "\n  {}", e.without_diagnostic()).unwrap(),
+        }
+    }
+    s
+}
+
 /// All scopes and bindings in a module.
 struct ModuleScopeBuilder<'a> {
     scope_data: ModuleScopeData<'a>,
@@ -105,6 +125,7 @@ struct ModuleScopeBuilder<'a> {
     top_level_stmt_count: usize,
 }
 
+#[derive(Clone)]
 pub(crate) struct ModuleScopes<'f> {
     pub(crate) scope_data: ModuleScopeData<'f>,
     pub(crate) module_slot_count: u32,
@@ -127,7 +148,7 @@ struct UnscopeBinding {
 #[derive(Default)]
 struct Unscope(SmallMap<FrozenStringValue, UnscopeBinding>);
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub(crate) struct ScopeNames<'f> {
     /// `Some` when scope is initialized.
     /// For module scope, the value is zero.
@@ -238,6 +259,26 @@ pub(crate) enum Slot {
     Local(LocalSlotIdCapturedOrNot),
 }
 
+/// Visit the names bound at the top level of a module, with whether they are bound by `load`.
+pub(crate) fn visit_top_level_assigns<'a, P: AstPayload>(
+    stmt: &'a AstStmtP<P>,
+    f: &mut impl FnMut(&'a AstAssignIdentP<P>, bool),
+) {
+    match &stmt.node {
+        StmtP::Assign(AssignP { lhs: dest, .. }) | StmtP::AssignModify(dest, _, _) => {
+            dest.visit_lvalue(|x| f(x, false))
+        }
+        StmtP::For(ForP { var, over: _, body }) => {
+            var.visit_lvalue(|x| f(x, false));
+            visit_top_level_assigns(body, f);
+        }
+        StmtP::Def(DefP { name, .. }) => f(name, false),
+        StmtP::Del(target) => target.visit_lvalue(|x| f(x, false)),
+        StmtP::Load(load) => load.args.iter().for_each(|x| f(&x.local, true)),
+        _ => stmt.visit_stmt(|x| visit_top_level_assigns(x, f)),
+    }
+}
+
 #[derive(Clone, Copy, Dupe)]
 enum ResolveIdentScope {
     /// Resolving normal identifier.
@@ -342,6 +383,9 @@ impl<'f> ModuleScopeBuilder<'f> {
             errors: Vec::new(),
             top_level_stmt_count: top_level_stmts.len(),
         };
+        for stmt in top_level_stmts.iter() {
+            scope.check_constant_assigns(stmt);
+        }
         for stmt in top_level_stmts.iter_mut() {
             scope.resolve_idents(stmt);
         }
@@ -373,6 +417,23 @@ impl<'f> ModuleScopeBuilder<'f> {
 }
 
 impl<'f> ModuleScopes<'f> {
+    /// Release the borrow of the heap the names are allocated in,
+    /// to keep the scopes together with a reference to the heap.
+    pub(crate) fn into_static(self) -> ModuleScopes<'static> {
+        let ModuleScopes {
+            scope_data,
+            module_slot_count,
+            cst,
+            top_level_stmt_count,
+        } = self;
+        ModuleScopes {
+            scope_data: scope_data.into_static(),
+            module_slot_count,
+            cst,
+            top_level_stmt_count,
+        }
+    }
+
     pub(crate) fn check_module_err(
         module: &'f MutableNames,
         frozen_heap: &'f FrozenHeap,
@@ -381,13 +442,43 @@ impl<'f> ModuleScopes<'f> {
         globals: ScopeResolverGlobals,
         codemap: FrozenRef<'static, CodeMap>,
         dialect: &Dialect,
+        strict_names: bool,
     ) -> crate::Result<ModuleScopes<'f>> {
         let (errors, scopes) =
             ModuleScopes::check_module(module, frozen_heap, loads, stmt, globals, codemap, dialect);
-        if let Some(error) = errors.into_iter().next() {
-            return Err(error.into_error());
+        let mut errors = errors.into_iter().map(EvalException::into_error);
+        if !strict_names {
+            return match errors.next() {
+                Some(first) => Err(first),
+                None => Ok(scopes),
+            };
         }
-        Ok(scopes)
+        // Report every undefined variable at once, so all typos can be fixed in one go,
+        // even if another error comes first.
+        let (mut not_found, mut other): (Vec<_>, Vec<_>) =
+            errors.partition(Self::is_variable_not_found);
+        if not_found.len() <= 1 {
+            return match not_found.pop().or_else(|| other.drain(..).next()) {
+                Some(error) => Err(error),
+                None => Ok(scopes),
+            };
+        }
+        let span = not_found[0].span().cloned();
+        let mut error: crate::Error = ScopeError::VariablesNotFound(not_found).into();
+        if let Some(span) = span {
+            error.set_span(span.span, &span.file);
+        }
+        Err(error)
+    }
+
+    fn is_variable_not_found(error: &crate::Error) -> bool {
+        let crate::ErrorKind::Scope(e) = error.kind() else {
+            return false;
+        };
+        matches!(
+            e.downcast_ref::<ScopeError>(),
+            Some(ScopeError::VariableNotFound(_) | ScopeError::VariableNotFoundDidYouMean(..))
+        )
     }
 
     pub(crate) fn check_module(
@@ -548,6 +639,21 @@ impl<'f> ModuleScopeBuilder<'f> {
         });
     }
 
+    /// Report assignments at module level to names the host marked constant.
+    fn check_constant_assigns(&mut self, code: &CstStmt) {
+        let mut assigned: Vec<&CstAssignIdent> = Vec::new();
+        visit_top_level_assigns(code, &mut |x, _| assigned.push(x));
+        for ident in assigned {
+            if self.module.is_constant(&ident.ident) {
+                self.errors.push(EvalException::new(
+                    ScopeError::AssignToConstant(ident.ident.clone()).into(),
+                    ident.span,
+                    &self.codemap,
+                ));
+            }
+        }
+    }
+
     fn resolve_idents(&mut self, code: &mut CstStmt) {
         match &mut code.node {
             StmtP::Def(DefP {
@@ -659,16 +765,15 @@ impl<'f> ModuleScopeBuilder<'f> {
         let variants = self
             .current_scope_all_visible_names_for_did_you_mean()
             .unwrap_or_default();
-        let better = did_you_mean(
+        let better = format_did_you_mean(&did_you_mean(
             ident.node.ident.as_str(),
             variants.iter().map(|s| s.as_str()),
-        );
+        ));
         EvalException::new(
             match better {
-                Some(better) => ScopeError::VariableNotFoundDidYouMean(
-                    ident.node.ident.clone(),
-                    better.to_owned(),
-                ),
+                Some(better) => {
+                    ScopeError::VariableNotFoundDidYouMean(ident.node.ident.clone(), better)
+                }
                 None => ScopeError::VariableNotFound(ident.node.ident.clone()),
             }
             .into(),
@@ -690,7 +795,13 @@ impl<'f> ModuleScopeBuilder<'f> {
                     Some(v) => ResolvedIdent::Global(v),
                 }
             }
-            Some((slot, binding_id)) => ResolvedIdent::Slot(slot, binding_id),
+            Some((slot, binding_id)) => {
+                let binding = self.scope_data.get_binding(binding_id);
+                if let BindingSource::FromModule = binding.source {
+                    self.module.add_referenced(binding.name);
+                }
+                ResolvedIdent::Slot(slot, binding_id)
+            }
         };
         match scope {
             ResolveIdentScope::Any => {}
@@ -1034,7 +1145,7 @@ impl AssignTargetCollectDefinesLvalue for AssignTarget {
 }
 
 /// Storage of objects referenced by AST.
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub(crate) struct ModuleScopeData<'f> {
     /// Bindings by id.
     bindings: Vec<Binding<'f>>,
@@ -1042,7 +1153,7 @@ pub(crate) struct ModuleScopeData<'f> {
     scopes: Vec<ScopeNames<'f>>,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Dupe, Eq, PartialEq)]
 pub(crate) enum AssignCount {
     /// Variable is assigned at most once during the execution of the scope.
     AtMostOnce,
@@ -1057,7 +1168,7 @@ pub(crate) enum Captured {
     No,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Dupe)]
 pub(crate) enum BindingSource {
     /// Variable is defined in the source of the module.
     Source(Span),
@@ -1070,7 +1181,7 @@ pub(crate) enum BindingSource {
 /// For example, in code `x = 1; x = 2`, there's one binding for name `x`.
 ///
 /// In code `x = 1; def f(): x = 2`, there are two bindings for name `x`.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct Binding<'f> {
     pub(crate) name: FrozenStringValue,
     pub(crate) source: BindingSource,
@@ -1159,6 +1270,56 @@ impl<'f> ModuleScopeData<'f> {
         &self.bindings[id]
     }
 
+    /// Release the borrow of the heap the names are allocated in.
+    ///
+    /// Scopes only store frozen strings, which are valid as long as the heap is,
+    /// so whoever keeps the scopes must keep a reference to the heap too.
+    fn into_static(self) -> ModuleScopeData<'static> {
+        let ModuleScopeData { bindings, scopes } = self;
+        ModuleScopeData {
+            bindings: bindings
+                .into_iter()
+                .map(
+                    |Binding {
+                         name,
+                         source,
+                         vis,
+                         slot,
+                         assign_count,
+                         captured,
+                         _marker,
+                     }| Binding {
+                        name,
+                        source,
+                        vis,
+                        slot,
+                        assign_count,
+                        captured,
+                        _marker: PhantomData,
+                    },
+                )
+                .collect(),
+            scopes: scopes
+                .into_iter()
+                .map(
+                    |ScopeNames {
+                         param_count,
+                         used,
+                         mp,
+                         parent,
+                         _heap,
+                     }| ScopeNames {
+                        param_count,
+                        used,
+                        mp,
+                        parent,
+                        _heap: PhantomData,
+                    },
+                )
+                .collect(),
+        }
+    }
+
     fn mut_binding(&mut self, BindingId(id): BindingId) -> &mut Binding<'f> {
         &mut self.bindings[id]
     }
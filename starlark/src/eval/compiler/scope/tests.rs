@@ -61,6 +61,7 @@ fn test_with_module(program: &str, expected: &str, module: &MutableNames) {
         },
         codemap,
         &Dialect::AllOptionsInternal,
+        false,
     )
     .unwrap();
     let mut r = String::new();
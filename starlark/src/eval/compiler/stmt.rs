@@ -55,7 +55,6 @@ use crate::eval::compiler::small_vec_1::SmallVec1;
 use crate::eval::compiler::span::IrSpanned;
 use crate::eval::compiler::Compiler;
 use crate::eval::runtime::evaluator::Evaluator;
-use crate::eval::runtime::evaluator::GC_THRESHOLD;
 use crate::eval::runtime::frame_span::FrameSpan;
 use crate::eval::runtime::frozen_file_span::FrozenFileSpan;
 use crate::eval::runtime::slots::LocalCapturedSlotId;
@@ -91,6 +90,17 @@ pub(crate) enum StmtCompiled {
         IrSpanned<ExprCompiled>,
     ),
     AssignModify(AssignModifyLhs, AssignOp, IrSpanned<ExprCompiled>),
+    /// `del x[i]`.
+    DelIndex(Box<(IrSpanned<ExprCompiled>, IrSpanned<ExprCompiled>)>),
+    /// `del x[start:stop:step]`.
+    DelSlice(
+        Box<(
+            IrSpanned<ExprCompiled>,
+            Option<IrSpanned<ExprCompiled>>,
+            Option<IrSpanned<ExprCompiled>>,
+            Option<IrSpanned<ExprCompiled>>,
+        )>,
+    ),
     If(Box<(IrSpanned<ExprCompiled>, StmtsCompiled, StmtsCompiled)>),
     For(
         Box<(
@@ -107,6 +117,8 @@ pub(crate) enum StmtCompiled {
 pub(crate) struct StmtCompileContext {
     /// Current function has return type.
     pub(crate) has_return_type: bool,
+    /// Constant folding was enabled when the function was compiled.
+    pub(crate) constant_folding: bool,
 }
 
 pub(crate) struct OptimizeOnFreezeContext<'v, 'a> {
@@ -116,6 +128,7 @@ pub(crate) struct OptimizeOnFreezeContext<'v, 'a> {
     /// (when invoking operations which require heap).
     pub(crate) heap: &'v Heap,
     pub(crate) frozen_heap: &'a FrozenHeap,
+    pub(crate) constant_folding: bool,
 }
 
 impl AssignModifyLhs {
@@ -178,6 +191,28 @@ impl IrSpanned<StmtCompiled> {
                 span,
                 node: StmtCompiled::AssignModify(lhs.optimize(ctx), *op, rhs.optimize(ctx)),
             }),
+            StmtCompiled::DelIndex(array_index) => {
+                let (array, index) = &**array_index;
+                StmtsCompiled::one(IrSpanned {
+                    span,
+                    node: StmtCompiled::DelIndex(Box::new((
+                        array.optimize(ctx),
+                        index.optimize(ctx),
+                    ))),
+                })
+            }
+            StmtCompiled::DelSlice(array_start_stop_step) => {
+                let (array, start, stop, step) = &**array_start_stop_step;
+                StmtsCompiled::one(IrSpanned {
+                    span,
+                    node: StmtCompiled::DelSlice(Box::new((
+                        array.optimize(ctx),
+                        start.as_ref().map(|x| x.optimize(ctx)),
+                        stop.as_ref().map(|x| x.optimize(ctx)),
+                        step.as_ref().map(|x| x.optimize(ctx)),
+                    ))),
+                })
+            }
         }
     }
 }
@@ -351,6 +386,14 @@ pub(crate) enum AssignError {
 pub(crate) enum AssignCompiledValue {
     Dot(IrSpanned<ExprCompiled>, String),
     Index(IrSpanned<ExprCompiled>, IrSpanned<ExprCompiled>),
+    Slice(
+        Box<(
+            IrSpanned<ExprCompiled>,
+            Option<IrSpanned<ExprCompiled>>,
+            Option<IrSpanned<ExprCompiled>>,
+            Option<IrSpanned<ExprCompiled>>,
+        )>,
+    ),
     Tuple(Vec<IrSpanned<AssignCompiledValue>>),
     Local(LocalSlotId),
     LocalCaptured(LocalCapturedSlotId),
@@ -381,6 +424,15 @@ impl IrSpanned<AssignCompiledValue> {
                 let index = index.optimize(ctx);
                 AssignCompiledValue::Index(array, index)
             }
+            AssignCompiledValue::Slice(ref array_start_stop_step) => {
+                let (array, start, stop, step) = &**array_start_stop_step;
+                AssignCompiledValue::Slice(Box::new((
+                    array.optimize(ctx),
+                    start.as_ref().map(|x| x.optimize(ctx)),
+                    stop.as_ref().map(|x| x.optimize(ctx)),
+                    step.as_ref().map(|x| x.optimize(ctx)),
+                )))
+            }
             AssignCompiledValue::Tuple(ref xs) => {
                 let xs = xs.map(|x| x.optimize(ctx));
                 AssignCompiledValue::Tuple(xs)
@@ -411,6 +463,13 @@ impl Compiler<'_, '_, '_, '_> {
                 let idx = self.expr(idx)?;
                 AssignCompiledValue::Index(e, idx)
             }
+            AssignTargetP::Slice(e, start, stop, stride) => {
+                let e = self.expr(e)?;
+                let start = start.as_ref().map(|x| self.expr(x)).transpose()?;
+                let stop = stop.as_ref().map(|x| self.expr(x)).transpose()?;
+                let stride = stride.as_ref().map(|x| self.expr(x)).transpose()?;
+                AssignCompiledValue::Slice(Box::new((e, start, stop, stride)))
+            }
             AssignTargetP::Tuple(v) => {
                 let v = v
                     .iter()
@@ -512,6 +571,9 @@ impl Compiler<'_, '_, '_, '_> {
             AssignTargetP::Tuple(_) => {
                 unreachable!("Assign modify validates that the LHS is never a tuple")
             }
+            AssignTargetP::Slice(..) => {
+                unreachable!("Assign modify validates that the LHS is never a slice")
+            }
         }
     }
 }
@@ -545,14 +607,17 @@ impl Compiler<'_, '_, '_, '_> {
 //
 // We also require that `extra_v` is None, since otherwise the user might have
 // additional values stashed somewhere.
-pub(crate) fn possible_gc(eval: &mut Evaluator) {
+//
+// The heap limit is checked after the collection, so only live values count.
+pub(crate) fn possible_gc(eval: &mut Evaluator) -> crate::Result<()> {
     if !eval.disable_gc && eval.heap().allocated_bytes() >= eval.next_gc_level {
         // When we are at a module scope (as checked above) the eval contains
         // references to all values, so walking covers everything and the unsafe
         // is satisfied.
         unsafe { eval.garbage_collect() }
-        eval.next_gc_level = cmp::max(eval.heap().allocated_bytes() * 2, GC_THRESHOLD);
+        eval.next_gc_level = cmp::max(eval.heap().allocated_bytes() * 2, eval.gc_threshold);
     }
+    eval.check_max_heap_bytes()
 }
 
 /// Implement lhs |= rhs, which is special in Starlark, because dicts are mutated,
@@ -638,7 +703,10 @@ pub(crate) fn add_assign<'v>(
 
 impl Compiler<'_, '_, '_, '_> {
     pub(crate) fn compile_context(&self, has_return_type: bool) -> StmtCompileContext {
-        StmtCompileContext { has_return_type }
+        StmtCompileContext {
+            has_return_type,
+            constant_folding: self.eval.fold_constants(),
+        }
     }
 
     pub(crate) fn stmt(
@@ -723,6 +791,32 @@ impl Compiler<'_, '_, '_, '_> {
         Ok(StmtsCompiled::expr(expr))
     }
 
+    fn stmt_del(
+        &mut self,
+        span: FrameSpan,
+        target: &CstAssignTarget,
+    ) -> Result<StmtsCompiled, CompilerInternalError> {
+        let node = match &target.node {
+            AssignTargetP::Index(array_index) => {
+                let (array, index) = &**array_index;
+                let array = self.expr(array)?;
+                let index = self.expr(index)?;
+                StmtCompiled::DelIndex(Box::new((array, index)))
+            }
+            AssignTargetP::Slice(array, start, stop, stride) => {
+                let array = self.expr(array)?;
+                let start = start.as_ref().map(|x| self.expr(x)).transpose()?;
+                let stop = stop.as_ref().map(|x| self.expr(x)).transpose()?;
+                let stride = stride.as_ref().map(|x| self.expr(x)).transpose()?;
+                StmtCompiled::DelSlice(Box::new((array, start, stop, stride)))
+            }
+            AssignTargetP::Identifier(..) | AssignTargetP::Tuple(..) | AssignTargetP::Dot(..) => {
+                unreachable!("Parser validates that `del` target is an index or a slice")
+            }
+        };
+        Ok(StmtsCompiled::one(IrSpanned { span, node }))
+    }
+
     fn stmt_direct(
         &mut self,
         stmt: &CstStmt,
@@ -807,6 +901,7 @@ impl Compiler<'_, '_, '_, '_> {
                 let rhs = self.expr(rhs)?;
                 self.assign_modify(span.span.span(), lhs, rhs, *op)
             }
+            StmtP::Del(target) => self.stmt_del(span, target),
             StmtP::Load(..) => unreachable!(),
             StmtP::Pass => Ok(StmtsCompiled::empty()),
             StmtP::Break => Ok(StmtsCompiled::one(IrSpanned {
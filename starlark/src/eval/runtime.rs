@@ -18,11 +18,14 @@
 pub(crate) mod arguments;
 pub(crate) mod before_stmt;
 pub(crate) mod cheap_call_stack;
+pub(crate) mod coverage;
 pub(crate) mod evaluator;
 pub(crate) mod file_loader;
 pub(crate) mod frame_span;
 pub(crate) mod frozen_file_span;
 pub(crate) mod inlined_frame;
+pub(crate) mod load_stack;
+pub(crate) mod module_cache;
 pub(crate) mod params;
 pub(crate) mod profile;
 pub(crate) mod rust_loc;
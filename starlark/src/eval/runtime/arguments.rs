@@ -52,10 +52,16 @@ pub(crate) enum FunctionError {
         names: Vec<String>,
         function: String,
     },
+    #[error("Found `{name}` extra named parameter for call to {function}, did you mean {better}?")]
+    ExtraNamedArgDidYouMean {
+        name: String,
+        function: String,
+        better: String,
+    },
     #[error("Argument `{name}` occurs more than once")]
     RepeatedArg { name: String },
-    #[error("The argument provided for *args is not an identifier")]
-    ArgsValueIsNotString,
+    #[error("The keys of the dictionary provided for **kwargs must be strings")]
+    KwArgsKeyIsNotString,
     #[error("The argument provided for *args is not iterable")]
     ArgsArrayIsNotIterable,
     #[error("The argument provided for **kwargs is not a dictionary")]
@@ -71,6 +77,44 @@ impl From<FunctionError> for crate::Error {
     }
 }
 
+/// A function parameter, as needed to tell which argument of a call supplied its value.
+#[derive(Debug, Clone)]
+pub(crate) struct ParamRef {
+    /// Index of the parameter.
+    pub(crate) index: usize,
+    /// Name of the parameter.
+    pub(crate) name: String,
+    /// The parameter can be filled positionally.
+    pub(crate) positional: bool,
+    /// The parameter can be filled by name.
+    pub(crate) named: bool,
+}
+
+/// Error caused by the value of one parameter,
+/// so the call can point at the argument which supplied the value.
+///
+/// Displayed as the underlying error.
+#[derive(Debug, Error)]
+#[error("{error}")]
+pub(crate) struct ParamError {
+    pub(crate) param: ParamRef,
+    pub(crate) error: anyhow::Error,
+}
+
+impl ParamError {
+    /// Attach the parameter to an error, keeping the error kind.
+    #[cold]
+    pub(crate) fn wrap(e: crate::Error, param: ParamRef) -> crate::Error {
+        if e.has_diagnostic() {
+            return e;
+        }
+        match e.into_kind() {
+            crate::ErrorKind::Other(error) => crate::Error::new_other(ParamError { param, error }),
+            kind => crate::Error::new_kind(kind),
+        }
+    }
+}
+
 /// An object accompanying argument name for faster argument resolution.
 pub(crate) trait ArgSymbol: Debug + Coerce<Self> + 'static {
     fn get_index_from_param_spec<'v, V: ValueLike<'v>>(
@@ -294,7 +338,7 @@ impl<'v, 'a> Arguments<'v, 'a> {
                 if self.0.names().names().is_empty() {
                     match kwargs.downcast_ref_key_string() {
                         Some(kwargs) => Ok(kwargs.clone()),
-                        None => Err(FunctionError::ArgsValueIsNotString.into()),
+                        None => Err(FunctionError::KwArgsKeyIsNotString.into()),
                     }
                 } else {
                     // We have to insert the names before the kwargs since the iteration order is observable
@@ -376,7 +420,7 @@ impl<'v, 'a> Arguments<'v, 'a> {
     #[inline(always)]
     pub(crate) fn unpack_kwargs_key_as_value(k: Value<'v>) -> crate::Result<StringValue<'v>> {
         match StringValue::new(k) {
-            None => Err(FunctionError::ArgsValueIsNotString.into()),
+            None => Err(FunctionError::KwArgsKeyIsNotString.into()),
             Some(k) => Ok(k),
         }
     }
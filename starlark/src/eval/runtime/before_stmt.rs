@@ -36,7 +36,7 @@ pub(crate) struct BeforeStmt<'a, 'e: 'a> {
 #[doc(hidden)]
 pub enum BeforeStmtFunc<'a, 'e: 'a> {
     Fn(&'a dyn for<'v1> Fn(FileSpanRef, &mut Evaluator<'v1, 'a, 'e>)),
-    Dyn(Box<dyn BeforeStmtFuncDyn<'a, 'e>>),
+    Dyn(Box<dyn BeforeStmtFuncDyn<'a, 'e> + 'a>),
 }
 
 impl<'a, 'e: 'a> BeforeStmtFunc<'a, 'e> {
@@ -53,6 +53,13 @@ impl<'a, 'e: 'a> BeforeStmtFunc<'a, 'e> {
             BeforeStmtFunc::Dyn(d) => d.call(span, eval),
         }
     }
+
+    pub(crate) fn statements_only(&self) -> bool {
+        match self {
+            BeforeStmtFunc::Fn(_) => false,
+            BeforeStmtFunc::Dyn(d) => d.statements_only(),
+        }
+    }
 }
 
 /// This is used by DAP, and it is not public API.
@@ -67,6 +74,33 @@ pub trait BeforeStmtFuncDyn<'a, 'e: 'a> {
         span: FileSpanRef,
         eval: &mut Evaluator<'v, 'a, 'e>,
     ) -> crate::Result<()>;
+
+    /// Only call this function before statements, not also before the garbage
+    /// collection points preceding top-level statements, which share their spans.
+    #[doc(hidden)]
+    fn statements_only(&self) -> bool {
+        false
+    }
+}
+
+/// Closure passed to [`Evaluator::add_before_stmt_hook`].
+pub(crate) struct BeforeStmtClosure<F>(pub(crate) F);
+
+impl<'a, 'e: 'a, F> BeforeStmtFuncDyn<'a, 'e> for BeforeStmtClosure<F>
+where
+    F: for<'v> FnMut(FileSpanRef, &mut Evaluator<'v, 'a, 'e>) -> crate::Result<()>,
+{
+    fn call<'v>(
+        &mut self,
+        span: FileSpanRef,
+        eval: &mut Evaluator<'v, 'a, 'e>,
+    ) -> crate::Result<()> {
+        (self.0)(span, eval)
+    }
+
+    fn statements_only(&self) -> bool {
+        true
+    }
 }
 
 impl<'a, 'e: 'a> BeforeStmt<'a, 'e> {
@@ -0,0 +1,320 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Statement coverage, collected with [`Evaluator::enable_coverage`](crate::eval::Evaluator::enable_coverage).
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::rc::Rc;
+
+use starlark_map::StarlarkHasherBuilder;
+use starlark_syntax::codemap::CodeMaps;
+use starlark_syntax::syntax::ast::AstStmt;
+use starlark_syntax::syntax::ast::ExprP;
+use starlark_syntax::syntax::ast::StmtP;
+
+use crate::codemap::CodeMap;
+use crate::codemap::CodeMapId;
+use crate::codemap::FileSpanRef;
+use crate::codemap::Span;
+
+/// Which lines of which files were executed, obtained from
+/// [`Evaluator::coverage_data`](crate::eval::Evaluator::coverage_data).
+///
+/// A line is covered if any statement starting on it was executed, and uncovered
+/// if it has statements but none of them were executed. Lines are 1-based.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageData {
+    /// File name to line to the number of times the line was executed.
+    files: BTreeMap<String, BTreeMap<usize, usize>>,
+}
+
+impl CoverageData {
+    /// Names of the files with statements, in sorted order.
+    pub fn files(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(|f| f.as_str())
+    }
+
+    /// Lines of the file which were executed at least once.
+    pub fn covered_lines(&self, filename: &str) -> BTreeSet<usize> {
+        self.lines(filename, |count| count != 0)
+    }
+
+    /// Lines of the file which have statements, none of which were executed.
+    pub fn uncovered_lines(&self, filename: &str) -> BTreeSet<usize> {
+        self.lines(filename, |count| count == 0)
+    }
+
+    fn lines(&self, filename: &str, pred: impl Fn(usize) -> bool) -> BTreeSet<usize> {
+        self.files
+            .get(filename)
+            .into_iter()
+            .flatten()
+            .filter(|(_, count)| pred(**count))
+            .map(|(line, _)| *line)
+            .collect()
+    }
+
+    /// Add the coverage of another run, for example of another [`Evaluator`](crate::eval::Evaluator)
+    /// used to evaluate a loaded module, or of the same files evaluated in parallel.
+    pub fn merge(&mut self, other: &CoverageData) {
+        for (filename, lines) in &other.files {
+            let ours = self.files.entry(filename.clone()).or_default();
+            for (line, count) in lines {
+                *ours.entry(*line).or_default() += count;
+            }
+        }
+    }
+
+    /// Render in the [LCOV](https://github.com/linux-test-project/lcov) tracefile format.
+    pub fn to_lcov(&self) -> String {
+        let mut lcov = String::new();
+        for (filename, lines) in &self.files {
+            writeln!(lcov, "SF:{filename}").unwrap();
+            for (line, count) in lines {
+                writeln!(lcov, "DA:{line},{count}").unwrap();
+            }
+            writeln!(lcov, "LH:{}", lines.values().filter(|c| **c != 0).count()).unwrap();
+            writeln!(lcov, "LF:{}", lines.len()).unwrap();
+            writeln!(lcov, "end_of_record").unwrap();
+        }
+        lcov
+    }
+}
+
+/// Cloning shares the state, so the evaluators of loaded modules
+/// record into the coverage of the evaluator which loads them.
+#[derive(Clone, Default)]
+pub(crate) struct StmtCoverage(
+    // Pointer because when coverage is not enabled, we want this to be small and cheap
+    Option<Rc<RefCell<StmtCoverageState>>>,
+);
+
+struct StmtCoverageState {
+    files: CodeMaps,
+    /// Statements of evaluated modules, with the number of times they were executed.
+    stmts: HashMap<(CodeMapId, Span), usize, StarlarkHasherBuilder>,
+}
+
+impl StmtCoverage {
+    pub(crate) fn new() -> Self {
+        StmtCoverage(None)
+    }
+
+    pub(crate) fn enable(&mut self) {
+        self.0 = Some(Rc::new(RefCell::new(StmtCoverageState {
+            files: CodeMaps::default(),
+            stmts: HashMap::default(),
+        })));
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Record the statements of a module about to be evaluated, so the ones
+    /// which are never executed are reported as uncovered.
+    pub(crate) fn add_module(&mut self, codemap: &CodeMap, stmt: &AstStmt) {
+        fn walk(
+            id: CodeMapId,
+            stmt: &AstStmt,
+            stmts: &mut HashMap<(CodeMapId, Span), usize, StarlarkHasherBuilder>,
+        ) {
+            match &stmt.node {
+                StmtP::Statements(_) => {}
+                // These produce no code, so never count as executed.
+                StmtP::Pass | StmtP::Load(_) => {}
+                StmtP::Expression(e) if matches!(e.node, ExprP::Literal(_)) => {}
+                _ => {
+                    stmts.entry((id, stmt.span)).or_default();
+                }
+            }
+            stmt.visit_stmt(|x| walk(id, x, stmts));
+        }
+
+        if let Some(state) = &self.0 {
+            let mut state = state.borrow_mut();
+            let state = &mut *state;
+            state.files.add(codemap);
+            walk(codemap.id(), stmt, &mut state.stmts);
+        }
+    }
+
+    pub(crate) fn before_stmt(&mut self, span: FileSpanRef) {
+        if let Some(state) = &self.0 {
+            let mut state = state.borrow_mut();
+            state.files.add(span.file);
+            *state.stmts.entry((span.file.id(), span.span)).or_default() += 1;
+        }
+    }
+
+    pub(crate) fn data(&self) -> Option<CoverageData> {
+        let state = self.0.as_ref()?.borrow();
+        let mut data = CoverageData::default();
+        for ((id, span), count) in &state.stmts {
+            let Some(codemap) = state.files.get(*id) else {
+                continue;
+            };
+            let line = codemap.find_line(span.begin()) + 1;
+            let lines = data.files.entry(codemap.filename().to_owned()).or_default();
+            // Several statements on one line: the line ran as often as the most run of them.
+            let entry = lines.entry(line).or_default();
+            *entry = (*entry).max(*count);
+        }
+        Some(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::environment::Globals;
+    use crate::environment::Module;
+    use crate::eval::Evaluator;
+    use crate::eval::ModuleCache;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+
+    const PROGRAM: &str = r#"
+def f(x):
+    """Docstring."""
+    if x:
+        return 1
+    else:
+        y = 2
+        return y
+f(True)
+if f(True) == 2:
+    pass
+    f(False)
+"#;
+
+    fn eval(eval: &mut Evaluator, name: &str, program: &str) {
+        let ast = AstModule::parse(name, program.to_owned(), &Dialect::Extended).unwrap();
+        eval.eval_module(ast, &Globals::standard()).unwrap();
+    }
+
+    #[test]
+    fn test_coverage_dead_branches() {
+        let module = Module::new();
+        let mut e = Evaluator::new(&module);
+        e.enable_coverage();
+        eval(&mut e, "a.star", PROGRAM);
+        let coverage = e.coverage_data().unwrap();
+        assert_eq!(vec!["a.star"], coverage.files().collect::<Vec<_>>());
+        assert_eq!(
+            vec![2, 4, 5, 9, 10],
+            coverage
+                .covered_lines("a.star")
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![7, 8, 12],
+            coverage
+                .uncovered_lines("a.star")
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_coverage_merge_and_lcov() {
+        let module = Module::new();
+        let mut e = Evaluator::new(&module);
+        e.enable_coverage();
+        eval(&mut e, "a.star", "x = 1\nif x == 2:\n    x = 3\n");
+        eval(&mut e, "b.star", "y = 1\n");
+        let mut coverage = e.coverage_data().unwrap();
+
+        let module = Module::new();
+        let mut e = Evaluator::new(&module);
+        e.enable_coverage();
+        eval(&mut e, "a.star", "x = 2\nif x == 2:\n    x = 3\n");
+        coverage.merge(&e.coverage_data().unwrap());
+
+        assert_eq!(
+            "\
+SF:a.star
+DA:1,2
+DA:2,2
+DA:3,1
+LH:3
+LF:3
+end_of_record
+SF:b.star
+DA:1,1
+LH:1
+LF:1
+end_of_record
+",
+            coverage.to_lcov()
+        );
+    }
+
+    #[test]
+    fn test_coverage_of_loaded_modules() {
+        let sources = HashMap::from([(
+            "lib.star",
+            "def f(x):\n    if x:\n        return 1\n    return 2\ny = f(True)\n",
+        )]);
+        let cache = ModuleCache::new(&Dialect::Extended, &Globals::standard(), |path| {
+            Ok(sources[path].to_owned())
+        });
+        let module = Module::new();
+        let mut e = Evaluator::new(&module);
+        e.enable_coverage();
+        e.set_loader(&cache);
+        eval(
+            &mut e,
+            "a.star",
+            "load('lib.star', 'y')
+x = y
+",
+        );
+        let coverage = e.coverage_data().unwrap();
+        assert_eq!(
+            vec!["a.star", "lib.star"],
+            coverage.files().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![1, 2, 3, 5],
+            coverage
+                .covered_lines("lib.star")
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![4],
+            coverage
+                .uncovered_lines("lib.star")
+                .into_iter()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_coverage_not_enabled() {
+        let module = Module::new();
+        let e = Evaluator::new(&module);
+        assert!(e.coverage_data().is_err());
+    }
+}
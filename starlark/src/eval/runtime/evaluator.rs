@@ -16,6 +16,7 @@
  */
 
 use std::collections::HashSet;
+use std::io::Write;
 use std::mem;
 use std::mem::MaybeUninit;
 use std::path::Path;
@@ -34,6 +35,7 @@ use crate::codemap::ResolvedFileSpan;
 use crate::collections::alloca::Alloca;
 use crate::collections::string_pool::StringPool;
 use crate::const_frozen_string;
+use crate::debug::StmtBreakpoints;
 use crate::environment::slots::ModuleSlotId;
 use crate::environment::FrozenModuleData;
 use crate::environment::Module;
@@ -46,9 +48,15 @@ use crate::eval::compiler::def::CopySlotFromParent;
 use crate::eval::compiler::def::Def;
 use crate::eval::compiler::def::DefInfo;
 use crate::eval::compiler::def::FrozenDef;
+use crate::eval::runtime::arguments::ArgumentsImpl;
 use crate::eval::runtime::before_stmt::BeforeStmt;
+use crate::eval::runtime::before_stmt::BeforeStmtClosure;
 use crate::eval::runtime::before_stmt::BeforeStmtFunc;
 use crate::eval::runtime::cheap_call_stack::CheapCallStack;
+use crate::eval::runtime::coverage::CoverageData;
+use crate::eval::runtime::coverage::StmtCoverage;
+use crate::eval::runtime::load_stack::LoadCycle;
+use crate::eval::runtime::load_stack::LoadStack;
 use crate::eval::runtime::frame_span::FrameSpan;
 use crate::eval::runtime::inlined_frame::InlinedFrames;
 use crate::eval::runtime::profile::bc::BcProfile;
@@ -68,6 +76,8 @@ use crate::eval::runtime::slots::LocalSlotId;
 use crate::eval::soft_error::HardErrorSoftErrorHandler;
 use crate::eval::CallStack;
 use crate::eval::FileLoader;
+use crate::eval::LoadContext;
+use crate::eval::SoftError;
 use crate::eval::SoftErrorHandler;
 use crate::stdlib::breakpoint::BreakpointConsole;
 use crate::stdlib::breakpoint::RealBreakpointConsole;
@@ -77,6 +87,7 @@ use crate::values::function::NativeFunction;
 use crate::values::layout::value_captured::value_captured_get;
 use crate::values::layout::value_captured::FrozenValueCaptured;
 use crate::values::layout::value_captured::ValueCaptured;
+use crate::values::recursive_repr_or_json_guard::ReprLimits;
 use crate::values::FrozenHeap;
 use crate::values::FrozenRef;
 use crate::values::Heap;
@@ -86,7 +97,7 @@ use crate::values::Value;
 use crate::values::ValueLike;
 
 #[derive(Error, Debug)]
-enum EvaluatorError {
+pub(crate) enum EvaluatorError {
     #[error("Profiling was not enabled")]
     ProfilingNotEnabled,
     #[error("Profile data already collected")]
@@ -105,6 +116,8 @@ enum EvaluatorError {
     CallstackSizeAlreadySet,
     #[error("Max callstack size cannot be zero")]
     ZeroCallstackSize,
+    #[error("Heap limit of {0} bytes exceeded, {1} bytes allocated")]
+    MaxHeapBytesExceeded(usize, usize),
 }
 
 /// Number of bytes to allocate between GC's.
@@ -133,14 +146,27 @@ pub struct Evaluator<'v, 'a, 'e> {
     // If true, the interpreter prints to stderr on GC.
     // This is used for debugging.
     pub(crate) verbose_gc: bool,
+    // If true, type annotation errors describe where in a container the mismatch is,
+    // and attribute and index errors name the access expression.
+    pub(crate) verbose_errors: bool,
     // Size of the heap when we should next perform a GC.
     pub(crate) next_gc_level: usize,
+    // Minimum heap size to trigger a GC, set by `set_gc_threshold`.
+    pub(crate) gc_threshold: usize,
     /// Run static typechecking of the module being evaluated.
     pub(crate) static_typechecking: bool,
+    /// Report all undefined variables of a module, not just the first.
+    pub(crate) strict_names: bool,
+    /// Evaluate constant expressions at compile time.
+    pub(crate) constant_folding: bool,
     // Profiling or instrumentation enabled.
     pub(crate) profile_or_instrumentation_mode: ProfileOrInstrumentationMode,
     // Used for line profiling
     stmt_profile: StmtProfile,
+    /// Statement coverage, if enabled.
+    pub(crate) stmt_coverage: StmtCoverage,
+    /// The modules being loaded, by this evaluator and the ones which loaded its module.
+    load_stack: LoadStack,
     // Holds things that require hooking into evaluation.
     eval_instrumentation: EvaluationInstrumentation<'a, 'e>,
     // Total time spent in runtime typechecking.
@@ -156,26 +182,73 @@ pub struct Evaluator<'v, 'a, 'e> {
     /// Called to perform console IO each time `breakpoint` function is called.
     pub(crate) breakpoint_handler:
         Option<Box<dyn Fn() -> anyhow::Result<Box<dyn BreakpointConsole>>>>,
+    /// Breakpoints set with [`set_breakpoint`](Evaluator::set_breakpoint).
+    pub(crate) stmt_breakpoints: Option<Box<StmtBreakpoints<'a, 'e>>>,
     /// Use in implementation of `print` function.
     pub(crate) print_handler: &'a (dyn PrintHandler + 'a),
     /// Deprecation handler.
     pub(crate) soft_error_handler: &'a (dyn SoftErrorHandler + 'a),
+    /// Soft errors accepted by the handler, collected during `eval_module_ret`.
+    pub(crate) soft_errors: Option<Vec<SoftError>>,
     /// Max size of starlark stack
     pub(crate) max_callstack_size: Option<usize>,
+    /// Limits set by `set_max_repr_depth` and `set_max_repr_len`.
+    pub(crate) repr_limits: ReprLimits,
+    // Limit set by `set_max_heap_bytes`.
+    max_heap_bytes: Option<usize>,
+    // Hooks added with `add_attr_hook`, usually empty.
+    attr_hooks: Vec<Box<dyn FnMut(&AttrAccess) -> crate::Result<AttrHookDecision> + 'a>>,
     // The Starlark-level call-stack of functions.
     // Must go last because it's quite a big structure
     pub(crate) call_stack: CheapCallStack<'v>,
 }
 
+/// Whether a function is being entered or left, passed to hooks
+/// installed with [`Evaluator::add_call_hook`].
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum CallHookEvent {
+    /// The function is about to run.
+    Call,
+    /// The function has returned or failed.
+    Return,
+}
+
+/// Attribute or method lookup, passed to hooks installed with [`Evaluator::add_attr_hook`].
+#[derive(Debug, Clone, Copy)]
+pub struct AttrAccess<'a> {
+    /// Type of the value the attribute is looked up on, as returned by `type()`.
+    pub type_name: &'a str,
+    /// Name of the attribute or method.
+    pub attr: &'a str,
+    /// Location of the lookup, [`None`] if unknown.
+    pub span: Option<FileSpanRef<'a>>,
+}
+
+/// What to do with an attribute lookup, returned by hooks installed with
+/// [`Evaluator::add_attr_hook`]. To deny the lookup, a hook returns an error instead.
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq)]
+pub enum AttrHookDecision {
+    /// Allow the lookup without consulting the hooks added later.
+    Allow,
+    /// Leave the decision to the hooks added later, the lookup is allowed if none decides.
+    PassThrough,
+}
+
 /// Just holds things that require using EvaluationCallbacksEnabled so that we can cache whether that needs to be enabled or not.
 struct EvaluationInstrumentation<'a, 'e: 'a> {
     // Bytecode profile.
     bc_profile: BcProfile,
     // Extra functions to run on each statement, usually empty
     before_stmt: BeforeStmt<'a, 'e>,
+    // Functions to run when entering or leaving a `def` or `lambda`, usually empty
+    call_hooks: Vec<Box<dyn FnMut(CallHookEvent, &str) + 'a>>,
+    // Destination of trace events, set by `set_trace_writer`.
+    trace_writer: Option<Box<dyn Write + 'a>>,
     heap_or_flame_profile: bool,
     // Whether we need to instrument evaluation or not, should be set if before_stmt or bc_profile are enabled.
     enabled: bool,
+    // Whether we need to instrument every call, should be set if heap_or_flame_profile or trace_writer are enabled.
+    calls_enabled: bool,
 }
 
 impl<'a, 'e: 'a> EvaluationInstrumentation<'a, 'e> {
@@ -183,8 +256,11 @@ impl<'a, 'e: 'a> EvaluationInstrumentation<'a, 'e> {
         Self {
             bc_profile: BcProfile::new(),
             before_stmt: BeforeStmt::default(),
+            call_hooks: Vec::new(),
+            trace_writer: None,
             heap_or_flame_profile: false,
             enabled: false,
+            calls_enabled: false,
         }
     }
 
@@ -194,8 +270,11 @@ impl<'a, 'e: 'a> EvaluationInstrumentation<'a, 'e> {
 
     fn change<F: FnOnce(&mut EvaluationInstrumentation<'a, 'e>) -> R, R>(&mut self, f: F) -> R {
         let r = f(self);
-        self.enabled =
-            self.bc_profile.enabled() || self.before_stmt.enabled() || self.heap_or_flame_profile;
+        self.enabled = self.bc_profile.enabled()
+            || self.before_stmt.enabled()
+            || !self.call_hooks.is_empty()
+            || self.heap_or_flame_profile;
+        self.calls_enabled = self.heap_or_flame_profile || self.trace_writer.is_some();
         r
     }
 }
@@ -219,22 +298,33 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
             loader: None,
             extra: None,
             next_gc_level: GC_THRESHOLD,
+            gc_threshold: GC_THRESHOLD,
             disable_gc: false,
             alloca: Alloca::new(),
             profile_or_instrumentation_mode: ProfileOrInstrumentationMode::None,
             heap_profile: HeapProfile::new(),
             stmt_profile: StmtProfile::new(),
+            stmt_coverage: StmtCoverage::new(),
+            load_stack: LoadStack::default(),
             typecheck_profile: TypecheckProfile::default(),
             time_flame_profile: TimeFlameProfile::new(),
             eval_instrumentation: EvaluationInstrumentation::new(),
             module_def_info: DefInfo::empty(), // Will be replaced before it is used
             string_pool: StringPool::default(),
             breakpoint_handler: None,
+            stmt_breakpoints: None,
             print_handler: &StderrPrintHandler,
             soft_error_handler: &HardErrorSoftErrorHandler,
+            soft_errors: None,
             verbose_gc: false,
+            verbose_errors: false,
             static_typechecking: false,
+            strict_names: false,
+            constant_folding: true,
             max_callstack_size: None,
+            repr_limits: ReprLimits::default(),
+            max_heap_bytes: None,
+            attr_hooks: Vec::new(),
         }
     }
 
@@ -245,11 +335,53 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
         self.disable_gc = true;
     }
 
+    /// Garbage collect when the heap reaches `bytes`, and after that when it doubles
+    /// since the previous collection, but not before it reaches `bytes` again.
+    /// The default is 100000.
+    ///
+    /// Collections only happen between top-level statements of a module,
+    /// see [`Heap::gc_stats`] for how many took place.
+    pub fn set_gc_threshold(&mut self, bytes: usize) {
+        self.gc_threshold = bytes;
+        self.next_gc_level = bytes;
+    }
+
     /// Enable GC logging.
     pub fn verbose_gc(&mut self) {
         self.verbose_gc = true;
     }
 
+    /// Include more context in errors. Off by default.
+    ///
+    /// Currently, when a list, tuple or dict does not match a type annotation
+    /// of a parameter, a return type or an assignment, the error names the
+    /// innermost element which does not match:
+    ///
+    /// ```
+    /// # use starlark::environment::{Globals, Module};
+    /// # use starlark::eval::Evaluator;
+    /// # use starlark::syntax::{AstModule, Dialect};
+    /// let program = r#"
+    /// def serve(config: dict[str, list[dict[str, int]]]):
+    ///     pass
+    /// serve({"servers": [{"port": 80}, {"port": "8080"}]})
+    /// "#;
+    /// let ast = AstModule::parse("config.star", program.to_owned(), &Dialect::Extended).unwrap();
+    /// let module = Module::new();
+    /// let mut eval = Evaluator::new(&module);
+    /// eval.verbose_errors();
+    /// let err = eval.eval_module(ast, &Globals::standard()).unwrap_err();
+    /// assert!(err.to_string().contains(
+    ///     r#"at `config["servers"][1]["port"]`: value `"8080"` of type `string` does not match `int`"#
+    /// ));
+    /// ```
+    ///
+    /// And when an attribute or index operation fails, the error is prefixed
+    /// with the whole access expression, e.g. ``At `config["servers"][0].port`: ``.
+    pub fn verbose_errors(&mut self) {
+        self.verbose_errors = true;
+    }
+
     /// Enable static typechecking. For example:
     ///
     /// ```python
@@ -257,13 +389,46 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
     /// ```
     ///
     /// would fail when static typechecking is enabled even if `foo` is never called.
+    ///
+    /// Function bodies are checked after the module top-level statements are evaluated,
+    /// and all type errors found are reported in a single error,
+    /// located at the first of them.
+    /// When there are several, the individual errors are available
+    /// as [`TypecheckErrors`](crate::typing::TypecheckErrors).
     pub fn enable_static_typechecking(&mut self, enable: bool) {
         self.static_typechecking = enable;
     }
 
+    /// Enable strict name resolution. Off by default.
+    ///
+    /// Names are always resolved before a module is executed, so a variable
+    /// which is not defined in any enclosing scope, the module, or the globals
+    /// is an error even in code which never runs. By default only the first
+    /// such variable is reported. In strict mode all of them are reported
+    /// in a single error, each with its location and "did you mean" hint.
+    pub fn enable_strict_names(&mut self, enable: bool) {
+        self.strict_names = enable;
+    }
+
+    /// Enable compile time evaluation of constant expressions, on by default.
+    /// For example, `3600 * 24` is compiled to `86400`, `"-".join(["a", "b"])` to `"a-b"`,
+    /// and the list in `x in ["a", "b"]` is allocated once instead of on every evaluation.
+    ///
+    /// Expressions which fail are not folded, so they fail when executed, as without folding.
+    /// Disabling is mostly useful to compare the performance or behavior of the optimizer.
+    /// Affects code compiled after the call, including functions optimized when frozen.
+    pub fn enable_constant_folding(&mut self, enable: bool) {
+        self.constant_folding = enable;
+    }
+
     /// Set the [`FileLoader`] used to resolve `load()` statements.
     /// A list of all load statements can be obtained through
     /// [`AstModule::loads`](crate::syntax::AstModule::loads).
+    ///
+    /// The loader may evaluate the loaded module with another [`Evaluator`] and the same loader.
+    /// If it passes on the [`LoadContext`] given to [`FileLoader::load_with_context`],
+    /// a `load()` of a module which is still being loaded, as identified by
+    /// [`FileLoader::resolve`], fails with an error listing the cycle, instead of recursing forever.
     pub fn set_loader(&mut self, loader: &'a dyn FileLoader) {
         self.loader = Some(loader);
     }
@@ -305,6 +470,9 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
             ProfileMode::Statement | ProfileMode::Coverage => {
                 self.stmt_profile.enable();
                 self.before_stmt_fn(&|span, eval| eval.stmt_profile.before_stmt(span));
+                if *mode == ProfileMode::Coverage {
+                    self.enable_coverage();
+                }
             }
             ProfileMode::TimeFlame => {
                 self.time_flame_profile.enable();
@@ -372,6 +540,52 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
         }
     }
 
+    /// Record which statements are executed, to be obtained with
+    /// [`coverage_data`](Evaluator::coverage_data).
+    ///
+    /// Only statements of modules evaluated with [`eval_module`](Evaluator::eval_module)
+    /// after this call are reported as uncovered if they never run. Modules loaded
+    /// with `load()` are included if the [`FileLoader`] passes its [`LoadContext`] to the
+    /// evaluator of the loaded module, as [`ModuleCache`](crate::eval::ModuleCache) does.
+    /// Otherwise enable coverage there too and combine the results with [`CoverageData::merge`].
+    pub fn enable_coverage(&mut self) {
+        if !self.stmt_coverage.is_enabled() {
+            self.stmt_coverage.enable();
+            self.add_coverage_hook();
+        }
+    }
+
+    fn add_coverage_hook(&mut self) {
+        self.add_before_stmt_hook(|span, eval| {
+            eval.stmt_coverage.before_stmt(span);
+            Ok(())
+        });
+    }
+
+    /// Continue the evaluation of the `load` statement which gave `context`
+    /// to [`FileLoader::load_with_context`]: detect the `load()` of a module
+    /// which is still being loaded, and record coverage into the coverage
+    /// of the loading evaluator, if it is enabled.
+    pub fn set_load_context(&mut self, context: &LoadContext) {
+        self.load_stack = context.load_stack.clone();
+        if context.coverage.is_enabled() {
+            let enabled = self.stmt_coverage.is_enabled();
+            self.stmt_coverage = context.coverage.clone();
+            if !enabled {
+                self.add_coverage_hook();
+            }
+        }
+    }
+
+    /// The context for the loader called by a `load` statement of the module `id`,
+    /// or error if `id` is already being loaded.
+    pub(crate) fn load_context(&self, id: &str) -> Result<LoadContext, LoadCycle> {
+        Ok(LoadContext {
+            load_stack: self.load_stack.push(id)?,
+            coverage: self.stmt_coverage.clone(),
+        })
+    }
+
     /// Get code coverage.
     ///
     /// Works if statement profile is enabled.
@@ -388,6 +602,19 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
         }
     }
 
+    /// Get per-line code coverage of all the modules evaluated so far,
+    /// including the lines which never ran.
+    ///
+    /// Works if coverage was enabled with [`enable_coverage`](Evaluator::enable_coverage)
+    /// or with [`ProfileMode::Coverage`].
+    ///
+    /// Same imprecision as [`coverage`](Evaluator::coverage) applies.
+    pub fn coverage_data(&self) -> crate::Result<CoverageData> {
+        self.stmt_coverage
+            .data()
+            .ok_or_else(|| crate::Error::new_other(EvaluatorError::CoverageNotEnabled))
+    }
+
     /// Enable interactive `breakpoint()`. When enabled, `breakpoint()`
     /// reads commands from stdin and write to stdout.
     /// When disabled (default), `breakpoint()` function results in error.
@@ -414,6 +641,37 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
 
     /// Obtain the top location on the call-stack. May be [`None`] if the
     /// call happened via native functions.
+    ///
+    /// Called from a native function, this is the span of the Starlark call
+    /// expression which invoked it, in the innermost Starlark function.
+    /// It only reads the top of the stack, so it is cheap enough to call on
+    /// every invocation, e.g. to tag host logs with the config line.
+    /// Use [`FileSpan::resolve`] to get the file name and line numbers.
+    ///
+    /// ```
+    /// # use starlark::environment::{Globals, GlobalsBuilder, Module};
+    /// # use starlark::eval::Evaluator;
+    /// # use starlark::starlark_module;
+    /// # use starlark::syntax::{AstModule, Dialect};
+    /// #[starlark_module]
+    /// fn log_functions(builder: &mut GlobalsBuilder) {
+    ///     fn where_am_i(eval: &mut Evaluator) -> anyhow::Result<String> {
+    ///         let location = eval.call_stack_top_location().unwrap().resolve();
+    ///         Ok(format!("{}:{}", location.file, location.span.begin.line + 1))
+    ///     }
+    /// }
+    ///
+    /// let globals = GlobalsBuilder::standard().with(log_functions).build();
+    /// let module = Module::new();
+    /// let ast = AstModule::parse(
+    ///     "config.star",
+    ///     "def f():\n    return where_am_i()\nf()".to_owned(),
+    ///     &Dialect::Standard,
+    /// )
+    /// .unwrap();
+    /// let res = Evaluator::new(&module).eval_module(ast, &globals).unwrap();
+    /// assert_eq!(res.unpack_str(), Some("config.star:2"));
+    /// ```
     pub fn call_stack_top_location(&self) -> Option<FileSpan> {
         self.call_stack.top_location()
     }
@@ -437,6 +695,136 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
         self.before_stmt(f)
     }
 
+    /// Call `f` before each statement is executed, with the span of the statement.
+    /// Each execution of a statement is reported once.
+    /// If `f` returns an error, the evaluation fails with that error.
+    ///
+    /// Hooks must be added before the code is compiled, that is, before calling
+    /// [`eval_module`](Evaluator::eval_module). Multiple hooks run in the order they were added.
+    ///
+    /// A hook may inspect the evaluator, but must not start another evaluation
+    /// (like [`eval_module`](Evaluator::eval_module) or [`eval_function`](Evaluator::eval_function))
+    /// with it, and must not add more hooks.
+    pub fn add_before_stmt_hook(
+        &mut self,
+        f: impl for<'v1> FnMut(FileSpanRef, &mut Evaluator<'v1, 'a, 'e>) -> crate::Result<()> + 'a,
+    ) {
+        self.before_stmt(BeforeStmtFunc::Dyn(Box::new(BeforeStmtClosure(f))))
+    }
+
+    /// Call `f` when a `def` or `lambda` is entered and left, with the name of the function.
+    ///
+    /// Functions implemented in Rust, and functions inlined by the optimizer, are not reported.
+    /// Like [`add_before_stmt_hook`](Evaluator::add_before_stmt_hook), hooks must be added
+    /// before the code is compiled.
+    pub fn add_call_hook(&mut self, f: impl FnMut(CallHookEvent, &str) + 'a) {
+        self.eval_instrumentation
+            .change(|v| v.call_hooks.push(Box::new(f)))
+    }
+
+    /// Call `f` on each attribute or method lookup, `x.attr`, `x.method(...)` or `getattr(x, "attr")`.
+    /// If `f` returns an error, the lookup fails with that error.
+    /// Hooks run in the order they were added, until one returns [`AttrHookDecision::Allow`].
+    ///
+    /// While hooks are installed, constant folding is disabled
+    /// (see [`enable_constant_folding`](Evaluator::enable_constant_folding)),
+    /// so lookups on constants are not resolved at compile time, and are reported too.
+    /// Like [`add_before_stmt_hook`](Evaluator::add_before_stmt_hook), hooks must be added
+    /// before the code is compiled.
+    pub fn add_attr_hook(
+        &mut self,
+        f: impl FnMut(&AttrAccess) -> crate::Result<AttrHookDecision> + 'a,
+    ) {
+        self.attr_hooks.push(Box::new(f));
+    }
+
+    #[inline(always)]
+    pub(crate) fn has_attr_hooks(&self) -> bool {
+        !self.attr_hooks.is_empty()
+    }
+
+    /// Run the hooks added with [`add_attr_hook`](Evaluator::add_attr_hook).
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn run_attr_hooks(
+        &mut self,
+        value: Value<'v>,
+        attr: &str,
+        span: Option<FileSpanRef>,
+    ) -> crate::Result<()> {
+        let access = AttrAccess {
+            type_name: value.get_type(),
+            attr,
+            span,
+        };
+        for hook in &mut self.attr_hooks {
+            match hook(&access)? {
+                AttrHookDecision::Allow => break,
+                AttrHookDecision::PassThrough => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Constant expressions may be evaluated at compile time.
+    pub(crate) fn fold_constants(&self) -> bool {
+        self.constant_folding && self.attr_hooks.is_empty()
+    }
+
+    /// Write a line of JSON to `writer` for each evaluation event, to see what
+    /// the code does without installing hooks. Each line is an object whose `event` field is:
+    ///
+    /// * `module_start` and `module_end`, with the module `path`,
+    ///   around [`eval_module`](Evaluator::eval_module);
+    /// * `load`, with the loaded `module`, before it is requested from the [`FileLoader`];
+    /// * `call`, with the `function` name and the number of `args`, when a function is entered,
+    ///   and `return`, with the `function` name, when it is left. Functions implemented in Rust
+    ///   are reported too, unlike with [`add_call_hook`](Evaluator::add_call_hook).
+    ///   Arguments in `*args` and `**kwargs` are counted one by one, `args` is `null` if they cannot be;
+    /// * `gc`, with the bytes `allocated_before` and `allocated_after` the collection;
+    /// * `print` and `fail`, with the `message`.
+    ///
+    /// Errors writing to `writer` are ignored. Without a trace writer no events are created.
+    /// Calls inlined by the optimizer are not reported, and calls evaluated by the optimizer
+    /// are reported when the code is compiled.
+    ///
+    /// ```
+    /// # use starlark::environment::{Globals, Module};
+    /// # use starlark::eval::Evaluator;
+    /// # use starlark::syntax::{AstModule, Dialect};
+    /// let mut trace = Vec::new();
+    /// let module = Module::new();
+    /// let mut eval = Evaluator::new(&module);
+    /// eval.set_trace_writer(&mut trace);
+    /// let ast = AstModule::parse("a.star", "def f(): pass\nf()".to_owned(), &Dialect::Standard)
+    ///     .unwrap();
+    /// eval.eval_module(ast, &Globals::standard()).unwrap();
+    /// drop(eval);
+    /// assert!(String::from_utf8(trace).unwrap().contains(r#"{"args":0,"event":"call","function":"f"}"#));
+    /// ```
+    pub fn set_trace_writer(&mut self, writer: impl Write + 'a) {
+        self.eval_instrumentation
+            .change(|v| v.trace_writer = Some(Box::new(writer)))
+    }
+
+    /// Set the trace writer, returning the previous one.
+    pub(crate) fn replace_trace_writer(
+        &mut self,
+        writer: Option<Box<dyn Write + 'a>>,
+    ) -> Option<Box<dyn Write + 'a>> {
+        self.eval_instrumentation
+            .change(|v| mem::replace(&mut v.trace_writer, writer))
+    }
+
+    /// Write the event built by `event` with the trace writer, if one is set.
+    #[inline]
+    pub(crate) fn trace_event(&mut self, event: impl FnOnce() -> serde_json::Value) {
+        if let Some(writer) = &mut self.eval_instrumentation.trace_writer {
+            // Tracing must not change the result of evaluation.
+            let _ = writeln!(writer, "{}", event());
+        }
+    }
+
     /// Set the handler invoked when `print` function is used.
     pub fn set_print_handler(&mut self, handler: &'a (dyn PrintHandler + 'a)) {
         self.print_handler = handler;
@@ -447,13 +835,40 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
         self.soft_error_handler = handler;
     }
 
+    /// Report a soft error, e.g. the use of a deprecated function, to the handler set with
+    /// [`set_soft_error_handler`](Evaluator::set_soft_error_handler). Native functions
+    /// call it and propagate the error if the handler rejects it.
+    ///
+    /// Errors the handler accepts during [`eval_module_ret`](Evaluator::eval_module_ret)
+    /// are returned in [`EvalModuleResult::soft_errors`](crate::eval::EvalModuleResult::soft_errors).
+    pub fn soft_error(&mut self, category: &str, error: crate::Error) -> crate::Result<()> {
+        match &mut self.soft_errors {
+            None => self.soft_error_handler.soft_error(category, error),
+            Some(soft_errors) => {
+                // The handler consumes the error, so record it first.
+                let soft_error = SoftError {
+                    category: category.to_owned(),
+                    message: error.without_diagnostic().to_string(),
+                    span: error
+                        .span()
+                        .cloned()
+                        .or_else(|| self.call_stack.top_location()),
+                };
+                self.soft_error_handler.soft_error(category, error)?;
+                soft_errors.push(soft_error);
+                Ok(())
+            }
+        }
+    }
+
     /// Called to add an entry to the call stack, by the function being invoked.
     /// Called for all types of function, including those written in Rust.
     #[inline(always)]
-    pub(crate) fn with_call_stack<R>(
+    pub(crate) fn with_call_stack<'a2, R>(
         &mut self,
         function: Value<'v>,
         span: Option<FrozenRef<'static, FrameSpan>>,
+        args: &impl ArgumentsImpl<'v, 'a2>,
         within: impl FnOnce(&mut Self) -> crate::Result<R>,
     ) -> crate::Result<R> {
         #[cold]
@@ -464,13 +879,73 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
             e
         }
 
+        self.check_max_heap_bytes()?;
         self.call_stack.push(function, span)?;
         // Must always call .pop regardless
-        let res = within(self).map_err(|e| add_diagnostics(e, self));
+        let res = if self.eval_instrumentation.calls_enabled {
+            self.with_call_instrumentation(function, args, within)
+        } else {
+            within(self)
+        }
+        .map_err(|e| add_diagnostics(e, self));
         self.call_stack.pop();
         res
     }
 
+    /// Write the trace events of a call, and record it in the profiles.
+    #[cold]
+    #[inline(never)]
+    fn with_call_instrumentation<'a2, R>(
+        &mut self,
+        function: Value<'v>,
+        args: &impl ArgumentsImpl<'v, 'a2>,
+        within: impl FnOnce(&mut Self) -> crate::Result<R>,
+    ) -> crate::Result<R> {
+        // The function of the outermost frame is `None`, it is not a call.
+        if function.is_none() {
+            return within(self);
+        }
+        if self.eval_instrumentation.trace_writer.is_none() {
+            return self.with_native_call_profile(function, within);
+        }
+        let name = function.name_for_call_stack();
+        self.trace_event(|| {
+            let star_len = |v: Option<Value>| v.map_or(Ok(0), |v| v.length()).ok();
+            let args = star_len(args.args())
+                .zip(star_len(args.kwargs()))
+                .map(|(a, k)| args.pos().len() + args.named().len() + a as usize + k as usize);
+            serde_json::json!({"event": "call", "function": name, "args": args})
+        });
+        let res = if self.eval_instrumentation.heap_or_flame_profile {
+            self.with_native_call_profile(function, within)
+        } else {
+            within(self)
+        };
+        self.trace_event(|| serde_json::json!({"event": "return", "function": name}));
+        res
+    }
+
+    /// Record a call of a function implemented in Rust in the heap and time flame profiles.
+    /// Calls of `def` and `lambda` are recorded when their bytecode is run.
+    fn with_native_call_profile<R>(
+        &mut self,
+        function: Value<'v>,
+        within: impl FnOnce(&mut Self) -> crate::Result<R>,
+    ) -> crate::Result<R> {
+        if function.is_none()
+            || function.downcast_ref::<Def>().is_some()
+            || function.downcast_ref::<FrozenDef>().is_some()
+        {
+            return within(self);
+        }
+        self.heap_profile.record_call_enter(function, self.heap());
+        self.time_flame_profile.record_call_enter(function);
+        let res = within(self);
+        self.heap_profile.record_call_exit(self.heap());
+        self.time_flame_profile.record_call_exit();
+        res
+    }
+
     /// The active heap where [`Value`]s are allocated.
     pub fn heap(&self) -> &'v Heap {
         self.module_env.heap()
@@ -733,10 +1208,20 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
         self.time_flame_profile
             .record_call_enter(const_frozen_string!("GC").to_value());
 
+        let allocated_before = self.heap().allocated_bytes();
         self.heap().garbage_collect(|tracer| self.trace(tracer));
 
         self.time_flame_profile.record_call_exit();
 
+        let allocated_after = self.heap().allocated_bytes();
+        self.trace_event(|| {
+            serde_json::json!({
+                "event": "gc",
+                "allocated_before": allocated_before,
+                "allocated_after": allocated_after,
+            })
+        });
+
         if self.verbose_gc {
             eprintln!(
                 "Starlark: GC complete. Allocated bytes: {}.",
@@ -794,6 +1279,29 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
         bc: &Bc,
     ) -> Result<Value<'v>, EvalException> {
         debug_assert!(self.eval_instrumentation.enabled);
+        // Top-level statements are evaluated with a string in place of the function,
+        // they are not calls.
+        if self.eval_instrumentation.call_hooks.is_empty() || def.unpack_str().is_some() {
+            return self.eval_bc_with_stmt_callbacks(def, bc);
+        }
+        let name = def.name_for_call_stack();
+        self.run_call_hooks(CallHookEvent::Call, &name);
+        let res = self.eval_bc_with_stmt_callbacks(def, bc);
+        self.run_call_hooks(CallHookEvent::Return, &name);
+        res
+    }
+
+    fn run_call_hooks(&mut self, event: CallHookEvent, name: &str) {
+        for hook in &mut self.eval_instrumentation.call_hooks {
+            hook(event, name);
+        }
+    }
+
+    fn eval_bc_with_stmt_callbacks(
+        &mut self,
+        def: Value<'v>,
+        bc: &Bc,
+    ) -> Result<Value<'v>, EvalException> {
         if self.eval_instrumentation.heap_or_flame_profile {
             self.heap_profile.record_call_enter(def, self.heap());
             self.time_flame_profile.record_call_enter(def);
@@ -816,6 +1324,9 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
                                 "both before_stmt and bc_profile are enabled"
                             )));
                         }
+                        (false, false) if !self.eval_instrumentation.call_hooks.is_empty() => {
+                            return bc.run(self, &mut EvalCallbacksDisabled);
+                        }
                         (false, false) => {
                             return Err(EvalException::new_unknown_span(internal_error!(
                                 "neither before_stmt nor bc_profile are enabled"
@@ -850,6 +1361,56 @@ impl<'v, 'a, 'e: 'a> Evaluator<'v, 'a, 'e> {
         self.max_callstack_size = Some(stack_size);
         Ok(())
     }
+
+    /// Limit how deeply nested values are rendered by `repr`, `str`, `print`
+    /// and string formatting while this evaluator runs.
+    ///
+    /// Values nested more than `max_depth` levels deep are rendered like cycles,
+    /// e.g. `[...]` or `{...}`. Cycles are always detected, whether or not a
+    /// limit is set.
+    pub fn set_max_repr_depth(&mut self, max_depth: usize) {
+        self.repr_limits.max_depth = Some(max_depth);
+    }
+
+    /// Limit the length in bytes of the output of `repr`, `str`, `print`
+    /// and string formatting of values while this evaluator runs.
+    ///
+    /// Longer output is truncated and ends with `...`. Elements of lists and
+    /// dicts past the limit are not rendered at all, so rendering large values
+    /// is cheap. Strings are not truncated by `str`, which returns them as is.
+    pub fn set_max_repr_len(&mut self, max_len: usize) {
+        self.repr_limits.max_len = Some(max_len);
+    }
+
+    /// Fail the evaluation when more than `max_bytes` are allocated on the
+    /// [heap](Evaluator::heap), as reported by [`Heap::allocated_bytes`].
+    ///
+    /// The limit is checked at garbage collection points, on each function call
+    /// and on each loop or comprehension iteration rather than on each allocation,
+    /// so a single allocation (e.g. a large string multiplication) may go past it.
+    pub fn set_max_heap_bytes(&mut self, max_bytes: usize) {
+        self.max_heap_bytes = Some(max_bytes);
+    }
+
+    #[inline(always)]
+    pub(crate) fn check_max_heap_bytes(&self) -> crate::Result<()> {
+        match self.max_heap_bytes {
+            None => Ok(()),
+            Some(max_bytes) => self.check_max_heap_bytes_slow(max_bytes),
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn check_max_heap_bytes_slow(&self, max_bytes: usize) -> crate::Result<()> {
+        let allocated = self.heap().allocated_bytes();
+        if allocated > max_bytes {
+            return Err(crate::Error::new_other(
+                EvaluatorError::MaxHeapBytesExceeded(max_bytes, allocated),
+            ));
+        }
+        Ok(())
+    }
 }
 
 pub(crate) trait EvaluationCallbacks {
@@ -890,7 +1451,7 @@ impl<'a> EvalCallbacksEnabled<'a> {
     fn before_stmt(&mut self, eval: &mut Evaluator, ip: BcPtrAddr) -> crate::Result<()> {
         let offset = ip.offset_from(self.bc_start_ptr);
         if let Some(loc) = self.stmt_locs.stmt_at(offset) {
-            before_stmt(loc.span, eval)?;
+            before_stmt(loc.span, loc.possible_gc, eval)?;
         }
         Ok(())
     }
@@ -918,7 +1479,11 @@ impl<'a> EvaluationCallbacks for EvalCallbacksEnabled<'a> {
 // The purposes are GC, profiling and debugging.
 //
 // This function is called only if `before_stmt` is set before compilation start.
-pub(crate) fn before_stmt(span: FrameSpan, eval: &mut Evaluator) -> crate::Result<()> {
+pub(crate) fn before_stmt(
+    span: FrameSpan,
+    possible_gc: bool,
+    eval: &mut Evaluator,
+) -> crate::Result<()> {
     assert!(
         eval.eval_instrumentation.before_stmt.enabled(),
         "this code should only be called if `before_stmt` is set"
@@ -928,7 +1493,7 @@ pub(crate) fn before_stmt(span: FrameSpan, eval: &mut Evaluator) -> crate::Resul
     });
     let mut result = Ok(());
     for f in &mut fs {
-        if result.is_ok() {
+        if result.is_ok() && !(possible_gc && f.statements_only()) {
             result = f.call(span.span.file_span_ref(), eval);
         }
     }
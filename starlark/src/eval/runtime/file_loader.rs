@@ -23,11 +23,47 @@ use std::collections::HashMap;
 use dupe::Dupe;
 
 use crate::environment::FrozenModule;
+use crate::eval::runtime::coverage::StmtCoverage;
+use crate::eval::runtime::load_stack::LoadStack;
+
+/// What the [`Evaluator`](crate::eval::Evaluator) running a `load` statement passes on to
+/// the evaluator of the loaded module, see [`FileLoader::load_with_context`].
+///
+/// With it a `load()` of a module which is still being loaded fails with an error
+/// listing the cycle, instead of recursing forever, and the coverage enabled with
+/// [`enable_coverage`](crate::eval::Evaluator::enable_coverage) includes the loaded modules.
+#[derive(Clone, Default)]
+pub struct LoadContext {
+    /// The modules being loaded, including the one this context is for.
+    pub(crate) load_stack: LoadStack,
+    pub(crate) coverage: StmtCoverage,
+}
 
 /// A trait for turning a `path` given by a `load()` statement into a [`FrozenModule`].
 pub trait FileLoader {
     /// Open the file given by the load statement `path`.
     fn load(&self, path: &str) -> anyhow::Result<FrozenModule>;
+
+    /// The identity of the module the load statement `path` refers to,
+    /// used to detect cyclic loads. By default it is `path` itself.
+    ///
+    /// A loader which resolves `path` relative to the loading file should return the resolved
+    /// path, so different files loading the same relative path are not reported as a cycle.
+    fn resolve(&self, path: &str) -> anyhow::Result<String> {
+        Ok(path.to_owned())
+    }
+
+    /// Called by `load()` statements instead of [`load`](FileLoader::load).
+    ///
+    /// A loader which evaluates the loaded module should pass `context` to the evaluator with
+    /// [`set_load_context`](crate::eval::Evaluator::set_load_context).
+    /// Otherwise cyclic loads are not detected, and a loader which evaluates the loaded
+    /// modules with itself as the loader recurses forever on them.
+    /// By default `context` is ignored.
+    fn load_with_context(&self, path: &str, context: &LoadContext) -> anyhow::Result<FrozenModule> {
+        let _ = context;
+        self.load(path)
+    }
 }
 
 /// [`FileLoader`] that looks up modules by name from a [`HashMap`].
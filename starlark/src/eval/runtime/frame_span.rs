@@ -47,6 +47,22 @@ impl FrameSpan {
     }
 }
 
+impl FrameSpan {
+    /// The part of this span after `prefix`, e.g. `[i]` in `a[i]` when `prefix` is `a`.
+    ///
+    /// Used to point runtime errors at the failing operation rather than the whole expression.
+    pub(crate) fn after(&self, prefix: &FrameSpan) -> FrameSpan {
+        if self.inlined_frames == prefix.inlined_frames {
+            FrameSpan {
+                span: self.span.after(&prefix.span),
+                inlined_frames: self.inlined_frames,
+            }
+        } else {
+            *self
+        }
+    }
+}
+
 impl Display for FrameSpan {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // TODO(nga): either display inlined frames or remove this.
@@ -74,6 +74,22 @@ impl FrozenFileSpan {
         }
     }
 
+    /// The part of this span after `prefix`, if `prefix` is a prefix of this span
+    /// in the same file, otherwise this span.
+    pub(crate) fn after(&self, prefix: &FrozenFileSpan) -> FrozenFileSpan {
+        if self.file == prefix.file
+            && self.span.begin() <= prefix.span.begin()
+            && prefix.span.end() <= self.span.end()
+        {
+            FrozenFileSpan {
+                file: self.file,
+                span: Span::new(prefix.span.end(), self.span.end()),
+            }
+        } else {
+            *self
+        }
+    }
+
     pub(crate) fn file_span_ref(&self) -> FileSpanRef<'static> {
         FileSpanRef {
             file: self.file.as_ref(),
@@ -0,0 +1,48 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Detect cycles of `load` statements.
+//!
+//! A [`FileLoader`](crate::eval::FileLoader) usually evaluates the loaded module
+//! with a fresh [`Evaluator`](crate::eval::Evaluator), so the modules being loaded
+//! are passed on to it in the [`LoadContext`](crate::eval::LoadContext).
+
+/// Identities of the modules being loaded, outermost first.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct LoadStack(Vec<String>);
+
+/// Returned when a module is loaded while it is already being loaded.
+/// Contains the load paths forming the cycle, starting and ending with the repeated path.
+pub(crate) struct LoadCycle(pub(crate) Vec<String>);
+
+impl LoadStack {
+    /// The stack with `path` pushed, or error if it is already on the stack.
+    pub(crate) fn push(&self, path: &str) -> Result<LoadStack, LoadCycle> {
+        match self.0.iter().position(|p| p == path) {
+            Some(i) => {
+                let mut cycle = self.0[i..].to_vec();
+                cycle.push(path.to_owned());
+                Err(LoadCycle(cycle))
+            }
+            None => {
+                let mut stack = self.0.clone();
+                stack.push(path.to_owned());
+                Ok(LoadStack(stack))
+            }
+        }
+    }
+}
@@ -0,0 +1,413 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Reuse frozen modules across evaluations while their sources and loads are unchanged.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+use dupe::Dupe;
+
+use crate::environment::FrozenModule;
+use crate::environment::Globals;
+use crate::environment::Module;
+use crate::eval::runtime::load_stack::LoadCycle;
+use crate::eval::Evaluator;
+use crate::eval::FileLoader;
+use crate::eval::LoadContext;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+
+#[derive(Debug, thiserror::Error)]
+enum ModuleCacheError {
+    #[error("Cyclic load: {}", .0.iter().map(|p| format!("`{p}`")).collect::<Vec<_>>().join(" -> "))]
+    CyclicLoad(Vec<String>),
+}
+
+struct CacheEntry {
+    /// Hash of the source the module was evaluated from.
+    source_hash: u64,
+    /// The modules loaded during evaluation, in order.
+    loads: Vec<(String, FrozenModule)>,
+    module: FrozenModule,
+}
+
+/// A [`FileLoader`] which evaluates modules from source and caches the frozen results.
+///
+/// On each [`load`](FileLoader::load) the source of the module is read again,
+/// as are the sources of the modules it loads, each once per `load`. The cached
+/// module is reused if its source is unchanged and every module it loaded is itself reused,
+/// so a change to a file invalidates all the modules which load it, directly or transitively.
+/// Otherwise the module is evaluated again, loading its dependencies through the cache.
+///
+/// This makes repeated evaluation of a large project, e.g. in an editor on every change,
+/// only re-evaluate the modules affected by the change.
+///
+/// ```
+/// use std::cell::RefCell;
+/// use std::collections::HashMap;
+///
+/// use starlark::environment::Globals;
+/// use starlark::eval::FileLoader;
+/// use starlark::eval::ModuleCache;
+/// use starlark::syntax::Dialect;
+///
+/// let files = RefCell::new(HashMap::from([
+///     ("a.star", "x = 1".to_owned()),
+///     ("b.star", "load('a.star', 'x')\ny = x + 1".to_owned()),
+/// ]));
+/// let cache = ModuleCache::new(&Dialect::Standard, &Globals::standard(), |path| {
+///     files.borrow().get(path).cloned().ok_or_else(|| anyhow::anyhow!("no file `{path}`"))
+/// });
+/// let b = cache.load("b.star").unwrap();
+/// assert_eq!(b.get("y").unwrap().value().unpack_i32(), Some(2));
+///
+/// files.borrow_mut().insert("a.star", "x = 10".to_owned());
+/// let b = cache.load("b.star").unwrap();
+/// assert_eq!(b.get("y").unwrap().value().unpack_i32(), Some(11));
+/// ```
+pub struct ModuleCache<'a> {
+    dialect: Dialect,
+    globals: Globals,
+    read: Box<dyn Fn(&str) -> anyhow::Result<String> + 'a>,
+    entries: RefCell<HashMap<String, CacheEntry>>,
+    /// Modules loaded during the outermost `load` in progress, so each module is
+    /// read and validated at most once, however many modules load it.
+    pass: RefCell<Option<HashMap<String, FrozenModule>>>,
+}
+
+/// Ends the pass started by the outermost `load` on drop.
+struct PassGuard<'c>(&'c RefCell<Option<HashMap<String, FrozenModule>>>);
+
+impl Drop for PassGuard<'_> {
+    fn drop(&mut self) {
+        *self.0.borrow_mut() = None;
+    }
+}
+
+impl<'a> ModuleCache<'a> {
+    /// Create an empty cache. Modules are parsed with `dialect`, evaluated with `globals`,
+    /// and their source is obtained by calling `read` with the path given to `load`.
+    pub fn new(
+        dialect: &Dialect,
+        globals: &Globals,
+        read: impl Fn(&str) -> anyhow::Result<String> + 'a,
+    ) -> Self {
+        Self {
+            dialect: dialect.clone(),
+            globals: globals.dupe(),
+            read: Box::new(read),
+            entries: RefCell::new(HashMap::new()),
+            pass: RefCell::new(None),
+        }
+    }
+
+    /// Forget the cached module for `path`, so the next load evaluates it again.
+    /// Modules loading it are evaluated again too.
+    pub fn invalidate(&self, path: &str) {
+        self.entries.borrow_mut().remove(path);
+    }
+
+    /// Forget all cached modules.
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    /// Is there a cached module for `path`. It may be out of date.
+    pub fn contains(&self, path: &str) -> bool {
+        self.entries.borrow().contains_key(path)
+    }
+
+    /// Load a dependency, reporting a cycle as an error.
+    fn load_dependency(&self, path: &str, context: &LoadContext) -> anyhow::Result<FrozenModule> {
+        let context = match context.load_stack.push(path) {
+            Ok(load_stack) => LoadContext {
+                load_stack,
+                coverage: context.coverage.clone(),
+            },
+            Err(LoadCycle(cycle)) => return Err(ModuleCacheError::CyclicLoad(cycle).into()),
+        };
+        self.load_with_context(path, &context)
+    }
+
+    /// The cached module for `path`, if it was evaluated from `source_hash`
+    /// and none of its loads changed.
+    fn cached(
+        &self,
+        path: &str,
+        source_hash: u64,
+        context: &LoadContext,
+    ) -> anyhow::Result<Option<FrozenModule>> {
+        let (loads, module) = match self.entries.borrow().get(path) {
+            Some(entry) if entry.source_hash == source_hash => {
+                (entry.loads.clone(), entry.module.dupe())
+            }
+            _ => return Ok(None),
+        };
+        for (load, used) in loads {
+            if self.load_dependency(&load, context)?.frozen_heap() != used.frozen_heap() {
+                return Ok(None);
+            }
+        }
+        Ok(Some(module))
+    }
+
+    fn eval(
+        &self,
+        path: &str,
+        source: String,
+        context: &LoadContext,
+    ) -> crate::Result<(FrozenModule, Vec<(String, FrozenModule)>)> {
+        let ast = AstModule::parse(path, source, &self.dialect)?;
+        let loader = RecordingLoader {
+            cache: self,
+            loads: RefCell::new(Vec::new()),
+        };
+        let module = Module::new();
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.set_loader(&loader);
+            eval.set_load_context(context);
+            eval.eval_module(ast, &self.globals)?;
+        }
+        Ok((module.freeze()?, loader.loads.into_inner()))
+    }
+
+    /// Read `path`, and reuse the cached module or evaluate it again.
+    fn load_in_pass(&self, path: &str, context: &LoadContext) -> anyhow::Result<FrozenModule> {
+        let source = (self.read)(path)?;
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        let source_hash = hasher.finish();
+
+        if let Some(module) = self.cached(path, source_hash, context)? {
+            return Ok(module);
+        }
+
+        let (module, loads) = self
+            .eval(path, source, context)
+            .map_err(|e| e.into_anyhow())?;
+        self.entries.borrow_mut().insert(
+            path.to_owned(),
+            CacheEntry {
+                source_hash,
+                loads,
+                module: module.dupe(),
+            },
+        );
+        Ok(module)
+    }
+}
+
+impl<'a> FileLoader for ModuleCache<'a> {
+    fn load(&self, path: &str) -> anyhow::Result<FrozenModule> {
+        self.load_dependency(path, &LoadContext::default())
+    }
+
+    /// Modules evaluated again record their coverage into the coverage of the `context`.
+    fn load_with_context(&self, path: &str, context: &LoadContext) -> anyhow::Result<FrozenModule> {
+        let loaded = match &*self.pass.borrow() {
+            Some(pass) => pass.get(path).map(|m| m.dupe()),
+            None => None,
+        };
+        if let Some(module) = loaded {
+            return Ok(module);
+        }
+        let outermost = self.pass.borrow().is_none();
+        let _pass = if outermost {
+            *self.pass.borrow_mut() = Some(HashMap::new());
+            Some(PassGuard(&self.pass))
+        } else {
+            None
+        };
+
+        let module = self.load_in_pass(path, context)?;
+        if let Some(pass) = &mut *self.pass.borrow_mut() {
+            pass.insert(path.to_owned(), module.dupe());
+        }
+        Ok(module)
+    }
+}
+
+/// Load through the cache, recording the modules loaded.
+struct RecordingLoader<'c, 'a> {
+    cache: &'c ModuleCache<'a>,
+    loads: RefCell<Vec<(String, FrozenModule)>>,
+}
+
+impl<'c, 'a> FileLoader for RecordingLoader<'c, 'a> {
+    fn load(&self, path: &str) -> anyhow::Result<FrozenModule> {
+        self.load_with_context(path, &LoadContext::default())
+    }
+
+    fn load_with_context(&self, path: &str, context: &LoadContext) -> anyhow::Result<FrozenModule> {
+        let module = self.cache.load_with_context(path, context)?;
+        self.loads
+            .borrow_mut()
+            .push((path.to_owned(), module.dupe()));
+        Ok(module)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use crate::environment::Globals;
+    use crate::eval::FileLoader;
+    use crate::eval::ModuleCache;
+    use crate::syntax::Dialect;
+
+    struct Files {
+        sources: RefCell<HashMap<String, String>>,
+        reads: RefCell<usize>,
+    }
+
+    impl Files {
+        fn new(sources: &[(&str, &str)]) -> Self {
+            Files {
+                sources: RefCell::new(
+                    sources
+                        .iter()
+                        .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+                        .collect(),
+                ),
+                reads: RefCell::new(0),
+            }
+        }
+
+        fn cache(&self) -> ModuleCache<'_> {
+            ModuleCache::new(&Dialect::Standard, &Globals::standard(), |path| {
+                *self.reads.borrow_mut() += 1;
+                self.sources
+                    .borrow()
+                    .get(path)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("no file `{path}`"))
+            })
+        }
+    }
+
+    #[test]
+    fn test_module_cache_reuse_and_cascade() {
+        let files = Files::new(&[
+            ("a.star", "a = []"),
+            ("b.star", "b = []"),
+            (
+                "c.star",
+                "load('a.star', 'a')\nload('b.star', 'b')\nc = [a, b]",
+            ),
+        ]);
+        let cache = files.cache();
+        let c1 = cache.load("c.star").unwrap();
+        let c2 = cache.load("c.star").unwrap();
+        assert!(c1.frozen_heap() == c2.frozen_heap());
+
+        let b1 = cache.load("b.star").unwrap();
+        files
+            .sources
+            .borrow_mut()
+            .insert("a.star".to_owned(), "a = [1]".to_owned());
+        let c3 = cache.load("c.star").unwrap();
+        assert!(c1.frozen_heap() != c3.frozen_heap());
+        assert_eq!(c3.get("c").unwrap().value().to_string(), "[[1], []]");
+        // `b.star` did not change, so it is not evaluated again.
+        assert!(cache.load("b.star").unwrap().frozen_heap() == b1.frozen_heap());
+    }
+
+    #[test]
+    fn test_module_cache_diamonds() {
+        // Each `m{i}` loads `l{i}` and `r{i}`, which both load `m{i+1}`.
+        const DEPTH: usize = 20;
+        let mut sources = Vec::new();
+        for i in 0..DEPTH {
+            sources.push((
+                format!("m{i}.star"),
+                format!("load('l{i}.star', 'l')\nload('r{i}.star', 'r')\nm = l + r"),
+            ));
+            sources.push((
+                format!("l{i}.star"),
+                format!("load('m{}.star', 'm')\nl = m", i + 1),
+            ));
+            sources.push((
+                format!("r{i}.star"),
+                format!("load('m{}.star', 'm')\nr = m", i + 1),
+            ));
+        }
+        sources.push((format!("m{DEPTH}.star"), "m = 1".to_owned()));
+        let sources: Vec<(&str, &str)> = sources
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        let files = Files::new(&sources);
+        let cache = files.cache();
+
+        let m1 = cache.load("m0.star").unwrap();
+        assert_eq!(m1.get("m").unwrap().value().unpack_i32(), Some(1 << DEPTH));
+        assert_eq!(*files.reads.borrow(), sources.len());
+
+        // Validating the cached module reads every file once more.
+        let m2 = cache.load("m0.star").unwrap();
+        assert!(m1.frozen_heap() == m2.frozen_heap());
+        assert_eq!(*files.reads.borrow(), 2 * sources.len());
+    }
+
+    #[test]
+    fn test_module_cache_invalidate() {
+        let files = Files::new(&[("a.star", "a = []")]);
+        let cache = files.cache();
+        let a1 = cache.load("a.star").unwrap();
+        assert!(cache.contains("a.star"));
+        cache.invalidate("a.star");
+        assert!(!cache.contains("a.star"));
+        let a2 = cache.load("a.star").unwrap();
+        assert!(a1.frozen_heap() != a2.frozen_heap());
+    }
+
+    #[test]
+    fn test_module_cache_errors() {
+        let files = Files::new(&[("a.star", "fail('bad')")]);
+        let cache = files.cache();
+        assert!(cache
+            .load("a.star")
+            .unwrap_err()
+            .to_string()
+            .contains("bad"));
+        assert!(!cache.contains("a.star"));
+        assert!(cache.load("missing.star").is_err());
+    }
+
+    #[test]
+    fn test_module_cache_cycle_after_edit() {
+        let files = Files::new(&[
+            ("a.star", "a = 1"),
+            ("b.star", "load('a.star', 'a')\nb = a"),
+        ]);
+        let cache = files.cache();
+        cache.load("b.star").unwrap();
+        files
+            .sources
+            .borrow_mut()
+            .insert("a.star".to_owned(), "load('b.star', 'b')\na = 1".to_owned());
+        let err = cache.load("b.star").unwrap_err().to_string();
+        assert!(err.contains("Cyclic load"), "{}", err);
+    }
+}
@@ -37,9 +37,12 @@ use crate::collections::symbol::map::SymbolMap;
 use crate::docs::DocParam;
 use crate::docs::DocParams;
 use crate::docs::DocString;
+use crate::errors::did_you_mean::did_you_mean;
+use crate::errors::did_you_mean::format_did_you_mean;
 use crate::eval::runtime::arguments::ArgSymbol;
 use crate::eval::runtime::arguments::ArgumentsImpl;
 use crate::eval::runtime::arguments::FunctionError;
+use crate::eval::runtime::arguments::ParamRef;
 use crate::eval::runtime::arguments::ResolvedArgName;
 use crate::eval::runtime::params::display::fmt_param_spec;
 use crate::eval::runtime::params::display::ParamFmt;
@@ -492,6 +495,19 @@ impl<V> ParametersSpec<V> {
     pub(crate) fn has_args_or_kwargs(&self) -> bool {
         self.indices.args.is_some() || self.indices.kwargs.is_some()
     }
+
+    /// Parameter `index`, `None` for `*args` and `**kwargs`.
+    pub(crate) fn param_ref(&self, index: usize) -> Option<ParamRef> {
+        if Some(index as u32) == self.indices.args || Some(index as u32) == self.indices.kwargs {
+            return None;
+        }
+        Some(ParamRef {
+            index,
+            name: self.param_names[index].clone(),
+            positional: index < self.indices.num_positional as usize,
+            named: index >= self.indices.num_positional_only as usize,
+        })
+    }
 }
 
 impl<'v, V: ValueLike<'v>> ParametersSpec<V> {
@@ -696,7 +712,7 @@ impl<'v> ParametersSpec<Value<'v>> {
                 Some(y) => {
                     for (k, v) in y.iter_hashed() {
                         match StringValue::new(*k.key()) {
-                            None => return Err(FunctionError::ArgsValueIsNotString.into()),
+                            None => return Err(FunctionError::KwArgsKeyIsNotString.into()),
                             Some(s) => {
                                 let repeat = match self
                                     .names
@@ -779,15 +795,45 @@ impl<'v> ParametersSpec<Value<'v>> {
         if let Some(kwargs_pos) = self.indices.kwargs {
             slots[kwargs_pos as usize] = Some(kwargs.alloc(heap));
         } else if let Some(kwargs) = kwargs.kwargs {
-            return Err(FunctionError::ExtraNamedArg {
-                names: kwargs.keys().map(|x| x.as_str().to_owned()).collect(),
-                function: self.signature(),
-            }
-            .into());
+            return Err(self.extra_named_arg_error(
+                kwargs.keys().map(|x| x.as_str().to_owned()).collect(),
+                next_position,
+            ));
         }
         Ok(())
     }
 
+    #[cold]
+    #[inline(never)]
+    fn extra_named_arg_error(
+        &self,
+        names: Vec<String>,
+        filled_positionally: usize,
+    ) -> crate::Error {
+        if let [name] = names.as_slice() {
+            // Don't suggest parameters already filled by positional arguments.
+            let candidates = self
+                .names
+                .iter()
+                .filter(|(_, index)| *index as usize >= filled_positionally)
+                .map(|(s, _)| s.as_str());
+            let better = format_did_you_mean(&did_you_mean(name, candidates));
+            if let Some(better) = better {
+                return FunctionError::ExtraNamedArgDidYouMean {
+                    name: name.clone(),
+                    function: self.signature(),
+                    better,
+                }
+                .into();
+            }
+        }
+        FunctionError::ExtraNamedArg {
+            names,
+            function: self.signature(),
+        }
+        .into()
+    }
+
     /// Check if current parameters can be filled with given arguments signature.
     #[allow(clippy::needless_range_loop)]
     fn can_fill_with_args_impl(&self, pos: usize, names: &[&str]) -> bool {
@@ -143,3 +143,56 @@ fn test_profile_golden_time_flame() {
 fn test_profile_golden_typecheck() {
     test_profile_golden_for_mode(ProfileMode::Typecheck);
 }
+
+/// Sum the values of the folded stacks with a frame named `frame`.
+fn folded_total(folded: &str, frame: &str) -> u64 {
+    folded
+        .lines()
+        .filter_map(|line| line.rsplit_once(' '))
+        .filter(|(stack, _)| stack.split(';').any(|f| f == frame))
+        .map(|(_, value)| value.parse::<u64>().unwrap())
+        .sum()
+}
+
+#[test]
+fn test_profile_hot_function_dominates() {
+    for mode in [ProfileMode::TimeFlame, ProfileMode::HeapFlameAllocated] {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.enable_profile(&mode).unwrap();
+        eval.eval_module(
+            AstModule::parse(
+                "hot.star",
+                r#"
+def hot():
+    r = []
+    for i in range(20000):
+        r.append(str(i))
+    return r
+
+def cold():
+    return [1]
+
+cold()
+hot()
+cold()
+"#
+                .to_owned(),
+                &Dialect::Standard,
+            )
+            .unwrap(),
+            &GlobalsBuilder::standard().build(),
+        )
+        .unwrap();
+        let folded = eval.gen_profile().unwrap().gen().unwrap();
+
+        let hot = folded_total(&folded, "hot.star.hot");
+        let cold = folded_total(&folded, "hot.star.cold");
+        assert!(hot > 10 * cold, "{mode}: hot={hot} cold={cold}\n{folded}");
+        // Functions implemented in Rust get their own frames.
+        assert!(
+            folded_total(&folded, "str") > 0,
+            "{mode}: no `str` frame\n{folded}"
+        );
+    }
+}
@@ -15,6 +15,8 @@
  * limitations under the License.
  */
 
+use crate::codemap::FileSpan;
+
 /// Deprecation handler provided by a user.
 pub trait SoftErrorHandler {
     /// Handle deprecation error. If this function returns `Ok`, error will be ignored,
@@ -30,3 +32,15 @@ impl SoftErrorHandler for HardErrorSoftErrorHandler {
         Err(error)
     }
 }
+
+/// A soft error accepted by the [`SoftErrorHandler`],
+/// reported by [`eval_module_ret`](crate::eval::Evaluator::eval_module_ret).
+#[derive(Debug)]
+pub struct SoftError {
+    /// Category passed to [`SoftErrorHandler::soft_error`].
+    pub category: String,
+    /// The error message, without the diagnostic.
+    pub message: String,
+    /// The location of the error, or of the innermost call if the error has none.
+    pub span: Option<FileSpan>,
+}
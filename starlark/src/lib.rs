@@ -102,6 +102,73 @@
 //! # fn main(){ run().unwrap(); }
 //! ```
 //!
+//! ## Test Rust functions
+//!
+//! The [`assert`](mod@assert) module runs Starlark snippets and checks their outcome.
+//! To unit test the functions of a `#[starlark_module]`, create an
+//! [`Assert`](assert::Assert) with the globals they are part of:
+//!
+//! ```
+//! #[macro_use]
+//! extern crate starlark;
+//! # fn main() {
+//! use starlark::assert::Assert;
+//! use starlark::environment::GlobalsBuilder;
+//! use starlark::syntax::Dialect;
+//!
+//! #[starlark_module]
+//! fn starlark_quadratic(builder: &mut GlobalsBuilder) {
+//!     fn quadratic(a: i32, b: i32, c: i32, x: i32) -> anyhow::Result<i32> {
+//!         Ok(a * x * x + b * x + c)
+//!     }
+//! }
+//!
+//! let mut a = Assert::new()
+//!     .with_dialect(&Dialect::Standard)
+//!     .with_globals(GlobalsBuilder::standard().with(starlark_quadratic).build());
+//! // Variables the tests can use.
+//! a.setup_module(|module| module.set("x", module.heap().alloc(8)));
+//! a.eq("quadratic(4, 2, 1, x)", "273");
+//! a.pass("assert_eq(quadratic(0, 0, 1, x), 1)");
+//! a.fail("quadratic(4, 2, 1)", "Missing parameter `x`");
+//! # }
+//! ```
+//!
+//! ## Accept any iterable
+//!
+//! A Rust function which accepts any iterable (lists, tuples, dicts, ranges, user types...)
+//! takes a [`Value`](values::Value) and calls [`iterate`](values::Value::iterate) on it:
+//!
+//! ```
+//! #[macro_use]
+//! extern crate starlark;
+//! # fn main() {
+//! use starlark::assert::Assert;
+//! use starlark::environment::GlobalsBuilder;
+//! use starlark::values::Heap;
+//! use starlark::values::UnpackValue;
+//! use starlark::values::Value;
+//!
+//! #[starlark_module]
+//! fn starlark_total(builder: &mut GlobalsBuilder) {
+//!     fn total<'v>(xs: Value<'v>, heap: &'v Heap) -> starlark::Result<i32> {
+//!         let mut total = 0;
+//!         for x in xs.iterate(heap)? {
+//!             total += i32::unpack_value_err(x)?;
+//!         }
+//!         Ok(total)
+//!     }
+//! }
+//!
+//! let mut a = Assert::new();
+//! a.globals_add(starlark_total);
+//! a.eq("total([1, 2, 3])", "6");
+//! a.eq("total(range(4))", "6");
+//! a.eq("total({1: 'x', 2: 'y'})", "3");
+//! a.fail("total(1)", "not supported");
+//! # }
+//! ```
+//!
 //! ## Collect Starlark values
 //!
 //! If we want to use Starlark as an enhanced JSON, we can define an `emit` function
@@ -179,6 +246,7 @@
 //! # fn run() -> starlark::Result<()> {
 //! use starlark::environment::Globals;
 //! use starlark::environment::Module;
+//! use starlark::errors::ErrorCode;
 //! use starlark::eval::Evaluator;
 //! use starlark::syntax::AstModule;
 //! use starlark::syntax::Dialect;
@@ -202,9 +270,10 @@
 //! let mut eval = Evaluator::new(&module);
 //! let res = eval.eval_module(ast, &globals);
 //! // We expect this to fail, since it is a type violation
+//! let err = res.unwrap_err();
+//! assert_eq!(ErrorCode::of(&err), ErrorCode::TypeAnnotationMismatch);
 //! assert!(
-//!     res.unwrap_err()
-//!         .to_string()
+//!     err.to_string()
 //!         .contains("Value `test` of type `string` does not match the type annotation `int`")
 //! );
 //! # Ok(())
@@ -218,13 +287,18 @@
 //! That requires that the loaded modules are first frozen with [`Module.freeze`](environment::Module::freeze).
 //! There is no requirement that the files are on disk, but that would be a common pattern.
 //!
+//! A loader which evaluates the loaded modules itself should pass on the
+//! [`LoadContext`](eval::LoadContext) it is given to the evaluator of the loaded module,
+//! so a module which ends up loading itself is an error rather than an infinite recursion.
+//!
 //! ```
 //! # fn run() -> starlark::Result<()> {
 //! use starlark::environment::FrozenModule;
 //! use starlark::environment::Globals;
 //! use starlark::environment::Module;
 //! use starlark::eval::Evaluator;
-//! use starlark::eval::ReturnFileLoader;
+//! use starlark::eval::FileLoader;
+//! use starlark::eval::LoadContext;
 //! use starlark::syntax::AstModule;
 //! use starlark::syntax::Dialect;
 //!
@@ -233,6 +307,8 @@
 //!     match file {
 //!         "a.star" => "a = 7",
 //!         "b.star" => "b = 6",
+//!         "cycle1.star" => "load('cycle2.star', 'y')\nx = y",
+//!         "cycle2.star" => "load('cycle1.star', 'x')\ny = x",
 //!         _ => {
 //!             r#"
 //! load('a.star', 'a')
@@ -243,32 +319,46 @@
 //!     }
 //! }
 //!
-//! fn get_module(file: &str) -> starlark::Result<FrozenModule> {
-//!     let ast = AstModule::parse(file, get_source(file).to_owned(), &Dialect::Standard)?;
+//! // A loader which evaluates the modules it is asked for, loading their own
+//! // dependencies with itself.
+//! struct Loader;
+//!
+//! impl Loader {
+//!     fn get_module(&self, file: &str, context: &LoadContext) -> starlark::Result<FrozenModule> {
+//!         let ast = AstModule::parse(file, get_source(file).to_owned(), &Dialect::Standard)?;
+//!         let globals = Globals::standard();
+//!         let module = Module::new();
+//!         {
+//!             let mut eval = Evaluator::new(&module);
+//!             eval.set_loader(self);
+//!             // Tell the evaluator which modules are being loaded.
+//!             eval.set_load_context(context);
+//!             eval.eval_module(ast, &globals)?;
+//!         }
+//!         // After creating a module we freeze it, preventing further mutation.
+//!         // It can now be used as the input for other Starlark modules.
+//!         Ok(module.freeze()?)
+//!     }
+//! }
 //!
-//!     // We can get the loaded modules from `ast.loads`.
-//!     // And ultimately produce a `loader` capable of giving those modules to Starlark.
-//!     let mut loads = Vec::new();
-//!     for load in ast.loads() {
-//!         loads.push((load.module_id.to_owned(), get_module(load.module_id)?));
+//! impl FileLoader for Loader {
+//!     fn load(&self, path: &str) -> anyhow::Result<FrozenModule> {
+//!         self.load_with_context(path, &LoadContext::default())
 //!     }
-//!     let modules = loads.iter().map(|(a, b)| (a.as_str(), b)).collect();
-//!     let mut loader = ReturnFileLoader { modules: &modules };
-//!
-//!     let globals = Globals::standard();
-//!     let module = Module::new();
-//!     {
-//!         let mut eval = Evaluator::new(&module);
-//!         eval.set_loader(&mut loader);
-//!         eval.eval_module(ast, &globals)?;
+//!
+//!     fn load_with_context(&self, path: &str, context: &LoadContext) -> anyhow::Result<FrozenModule> {
+//!         self.get_module(path, context).map_err(|e| e.into_anyhow())
 //!     }
-//!     // After creating a module we freeze it, preventing further mutation.
-//!     // It can now be used as the input for other Starlark modules.
-//!     Ok(module.freeze()?)
 //! }
 //!
-//! let ab = get_module("ab.star")?;
+//! let ab = Loader.get_module("ab.star", &LoadContext::default())?;
 //! assert_eq!(ab.get("ab").unwrap().unpack_i32(), Some(42));
+//!
+//! // `cycle1.star` loads `cycle2.star`, which loads `cycle1.star` again.
+//! let err = Loader
+//!     .get_module("cycle1.star", &LoadContext::default())
+//!     .unwrap_err();
+//! assert!(err.to_string().contains("Cyclic load"), "{err}");
 //! # Ok(())
 //! # }
 //! # fn main(){ run().unwrap(); }
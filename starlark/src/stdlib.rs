@@ -25,6 +25,7 @@ use crate::values::namespace::globals::register_namespace;
 
 pub(crate) mod breakpoint;
 pub(crate) mod call_stack;
+pub(crate) mod catch;
 pub(crate) mod extra;
 mod funcs;
 pub(crate) mod internal;
@@ -35,9 +36,11 @@ pub use extra::PrintHandler;
 
 use crate::stdlib::funcs::globals::register_globals;
 use crate::stdlib::internal::register_internal;
+use crate::values::bytes::globals::register_bytes;
 use crate::values::enumeration::globals::register_enum;
 use crate::values::record::globals::register_record;
 use crate::values::structs::structs::register_struct;
+use crate::values::types::num::globals::register_math;
 use crate::values::types::set::set::register_set;
 use crate::values::typing;
 
@@ -47,7 +50,9 @@ use crate::values::typing;
 /// For example `stdlib::standard_environment().freeze().child("test")` create a
 /// child environment of this global environment that have been frozen.
 pub(crate) fn standard_environment() -> GlobalsBuilder {
-    GlobalsBuilder::new().with(register_globals)
+    GlobalsBuilder::new()
+        .with(register_globals)
+        .with(register_bytes)
 }
 
 /// The extra library definitions available in this Starlark implementation, but not in the standard.
@@ -98,6 +103,12 @@ pub enum LibraryExtension {
     CallStack,
     /// Definitions to support the `set` type, the `set()` constructor.
     SetType,
+    /// Add a function `catch(f)` which calls `f` and recovers from `fail` and value errors.
+    /// Not part of the Starlark spec, which has no error handling.
+    Catch,
+    /// Add functions `round(x, ndigits=None)`, `divmod(a, b)` and `pow(base, exp, modulus=None)`,
+    /// which behave like their Python counterparts.
+    Math,
     // Make sure if you add anything new, you add it to `all` below.
 }
 
@@ -124,6 +135,8 @@ impl LibraryExtension {
             Internal,
             CallStack,
             SetType,
+            Catch,
+            Math,
         ]
     }
 
@@ -149,6 +162,8 @@ impl LibraryExtension {
             Typing => typing::globals::register_typing(builder),
             Internal => register_internal(builder),
             CallStack => call_stack::global(builder),
+            Catch => catch::catch(builder),
+            Math => register_math(builder),
         }
     }
 }
@@ -0,0 +1,145 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Implementation of the `catch` function.
+
+use starlark_derive::starlark_module;
+
+use crate as starlark;
+use crate::environment::GlobalsBuilder;
+use crate::errors::ErrorCode;
+use crate::eval::Evaluator;
+use crate::values::function::StarlarkFunction;
+use crate::values::structs::AllocStruct;
+use crate::values::Value;
+use crate::values::ValueOfUnchecked;
+use crate::ErrorKind;
+
+/// Errors raised by the evaluated code itself, as opposed to the limits of the evaluator,
+/// errors from native code and bugs.
+fn is_catchable(e: &crate::Error) -> bool {
+    match ErrorCode::of(e) {
+        ErrorCode::Fail
+        | ErrorCode::UnboundVariable
+        | ErrorCode::TypeAnnotationMismatch
+        | ErrorCode::DivisionByZero
+        | ErrorCode::IntegerOverflow
+        | ErrorCode::IndexOutOfBounds
+        | ErrorCode::KeyNotFound
+        | ErrorCode::NoAttribute
+        | ErrorCode::UnsupportedOperation
+        | ErrorCode::CannotMutate
+        | ErrorCode::Value
+        | ErrorCode::Function => true,
+        _ => false,
+    }
+}
+
+#[starlark_module]
+pub fn catch(builder: &mut GlobalsBuilder) {
+    /// Call `f` with no arguments, recovering from the errors it raises.
+    ///
+    /// Returns a struct with fields `ok`, `value` and `error`.
+    /// If `f` returns normally, `ok` is `True`, `value` is the result and `error` is `None`.
+    /// If `f` calls `fail`, or raises an error from an operation on values
+    /// (e.g. an index out of bounds) or from a function call (e.g. a missing argument),
+    /// `ok` is `False`, `value` is `None` and `error` is the error message.
+    ///
+    /// Other errors, such as exceeding the call stack or heap limits, are not caught
+    /// and abort the evaluation as usual.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// catch(lambda: 1 + 2).value == 3
+    /// catch(lambda: fail("oops")).ok == False
+    /// catch(lambda: fail("oops")).error == "oops"
+    /// catch(lambda: [][0]).error == "Index `0` is out of bound for length 0"
+    /// # "#);
+    /// ```
+    fn catch<'v>(
+        #[starlark(require = pos)] f: ValueOfUnchecked<'v, StarlarkFunction>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<Value<'v>> {
+        let heap = eval.heap();
+        let (ok, value, error) = match f.get().invoke_pos(&[], eval) {
+            Ok(value) => (true, value, Value::new_none()),
+            Err(e) if is_catchable(&e) => {
+                let message = match e.kind() {
+                    ErrorKind::Fail(e) => e.to_string(),
+                    kind => kind.to_string(),
+                };
+                (false, Value::new_none(), heap.alloc(message))
+            }
+            Err(e) => return Err(e),
+        };
+        Ok(heap.alloc(AllocStruct([
+            ("ok", Value::new_bool(ok)),
+            ("value", value),
+            ("error", error),
+        ])))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+    use crate::assert::Assert;
+    use crate::environment::GlobalsBuilder;
+    use crate::stdlib::LibraryExtension;
+
+    #[test]
+    fn test_catch() {
+        assert::pass(
+            r#"
+def ok():
+    return [1, 2]
+
+r = catch(ok)
+assert_eq(r, struct(ok = True, value = [1, 2], error = None))
+
+r = catch(lambda: fail("bad", 1))
+assert_eq(r, struct(ok = False, value = None, error = "bad 1"))
+
+assert_eq(catch(lambda: {}["x"]).error, "Key `\"x\"` was not found")
+assert_eq(catch(lambda: 1 + "a").ok, False)
+assert_eq(catch(lambda: ok(1)).ok, False)
+
+# Evaluation continues normally after a caught error.
+def nested():
+    return catch(lambda: fail("inner")).error + " caught"
+assert_eq(nested(), "inner caught")
+"#,
+        );
+    }
+
+    #[test]
+    fn test_catch_does_not_catch_stack_overflow() {
+        assert::fail(
+            "def f(): return f()\ncatch(f)",
+            "Starlark call stack overflow",
+        );
+    }
+
+    #[test]
+    fn test_catch_is_opt_in() {
+        let mut a = Assert::new();
+        a.globals(GlobalsBuilder::standard().build());
+        a.fail("catch(lambda: 1)", "Variable `catch` not found");
+        a.globals(GlobalsBuilder::extended_by(&[LibraryExtension::Catch]).build());
+        a.eq("3", "catch(lambda: 3).value");
+    }
+}
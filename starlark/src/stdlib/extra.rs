@@ -21,11 +21,13 @@ use itertools::Itertools;
 use starlark_derive::starlark_module;
 
 use crate as starlark;
+use crate::codemap::FileSpan;
 use crate::environment::GlobalsBuilder;
 use crate::eval::Evaluator;
 use crate::values::function::StarlarkFunction;
 use crate::values::none::NoneOr;
 use crate::values::none::NoneType;
+use crate::values::promise::StarlarkPromise;
 use crate::values::tuple::UnpackTuple;
 use crate::values::typing::iter::StarlarkIter;
 use crate::values::StringValue;
@@ -116,9 +118,20 @@ impl fmt::Display for PrintWrapper<'_, '_> {
 }
 
 /// Invoked from `print` or `pprint` to print a value.
+///
+/// Install with [`Evaluator::set_print_handler`]; the default handler writes to stderr.
 pub trait PrintHandler {
     /// If this function returns error, evaluation fails with this error.
     fn println(&self, text: &str) -> anyhow::Result<()>;
+
+    /// Like [`println`](PrintHandler::println), but also receives the location
+    /// of the `print` call, if known.
+    ///
+    /// The default implementation ignores the location.
+    fn println_at(&self, text: &str, location: Option<&FileSpan>) -> anyhow::Result<()> {
+        let _ignore = location;
+        self.println(text)
+    }
 }
 
 pub(crate) struct StderrPrintHandler;
@@ -133,27 +146,49 @@ impl PrintHandler for StderrPrintHandler {
 #[starlark_module]
 pub fn print(builder: &mut GlobalsBuilder) {
     /// Print some values to the output.
+    ///
+    /// Each argument is converted with `str()`, and the results are joined by
+    /// `sep`, which defaults to a single space. The output goes to the
+    /// [`PrintHandler`] installed on the evaluator, stderr by default.
+    ///
+    /// ```
+    /// # starlark::assert::pass(r#"
+    /// print("hello", 1, [2])        # hello 1 [2]
+    /// print("a", "b", sep = ", ")  # a, b
+    /// # "#);
+    /// ```
     fn print(
         #[starlark(args)] args: UnpackTuple<Value>,
+        #[starlark(require = named, default = " ")] sep: &str,
         eval: &mut Evaluator,
     ) -> anyhow::Result<NoneType> {
-        // In practice most users should want to put the print somewhere else, but this does for now
+        for x in args.items.iter() {
+            StarlarkPromise::check_str(*x).map_err(|e| e.into_anyhow())?;
+        }
         // Unfortunately, we can't use PrintWrapper because strings to_str() and Display are different.
-        eval.print_handler
-            .println(&args.items.iter().map(|x| x.to_str()).join(" "))?;
+        let text = args.items.iter().map(|x| x.to_str()).join(sep);
+        eval.trace_event(|| serde_json::json!({"event": "print", "message": text}));
+        let location = eval.call_stack_top_location();
+        eval.print_handler.println_at(&text, location.as_ref())?;
         Ok(NoneType)
     }
 }
 
 #[starlark_module]
 pub fn pprint(builder: &mut GlobalsBuilder) {
+    /// Like `print`, but renders values over multiple lines with indentation.
+    ///
+    /// Dictionaries are rendered in iteration order, and cycles are rendered
+    /// as `[...]` or `{...}`.
     fn pprint(
         #[starlark(args)] args: UnpackTuple<Value>,
         eval: &mut Evaluator,
     ) -> anyhow::Result<NoneType> {
-        // In practice most users may want to put the print somewhere else, but this does for now
-        eval.print_handler
-            .println(&format!("{:#}", PrintWrapper(&args.items)))?;
+        let location = eval.call_stack_top_location();
+        eval.print_handler.println_at(
+            &format!("{:#}", PrintWrapper(&args.items)),
+            location.as_ref(),
+        )?;
         Ok(NoneType)
     }
 }
@@ -206,7 +241,14 @@ mod tests {
 
     use crate::assert;
     use crate::assert::Assert;
+    use crate::codemap::FileSpan;
+    use crate::environment::Globals;
+    use crate::environment::LibraryExtension;
+    use crate::environment::Module;
+    use crate::eval::Evaluator;
     use crate::stdlib::PrintHandler;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
 
     #[test]
     fn test_filter() {
@@ -280,6 +322,62 @@ assert_eq(["11",8], map(double, ["1",4]))
         assert_eq!("hw", s_copy.borrow().as_str());
     }
 
+    #[test]
+    fn test_print_collect_lines() {
+        #[derive(Default)]
+        struct CollectingPrintHandler {
+            lines: RefCell<Vec<(String, String)>>,
+        }
+        impl PrintHandler for CollectingPrintHandler {
+            fn println(&self, _text: &str) -> anyhow::Result<()> {
+                unreachable!("`print` calls `println_at`")
+            }
+
+            fn println_at(&self, text: &str, location: Option<&FileSpan>) -> anyhow::Result<()> {
+                let location = location.map_or_else(String::new, |l| l.to_string());
+                self.lines.borrow_mut().push((text.to_owned(), location));
+                Ok(())
+            }
+        }
+
+        let program = r#"
+print("hello", 1, [2, "x"])
+print("a", "b", "c", sep = ", ")
+print()
+def f(x):
+    print(x, sep = "-")
+f("in def")
+"#;
+        let print_handler = CollectingPrintHandler::default();
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_print_handler(&print_handler);
+        let ast = AstModule::parse("print.star", program.to_owned(), &Dialect::Standard).unwrap();
+        let globals = Globals::extended_by(&[LibraryExtension::Print]);
+        eval.eval_module(ast, &globals).unwrap();
+        drop(eval);
+        assert_eq!(
+            vec![
+                (
+                    "hello 1 [2, \"x\"]".to_owned(),
+                    "print.star:2:1-28".to_owned()
+                ),
+                ("a, b, c".to_owned(), "print.star:3:1-33".to_owned()),
+                ("".to_owned(), "print.star:4:1-8".to_owned()),
+                ("in def".to_owned(), "print.star:6:5-24".to_owned()),
+            ],
+            print_handler.lines.into_inner()
+        );
+    }
+
+    #[test]
+    fn test_print_sep_type() {
+        assert::fail(
+            "print('a', sep = 1)",
+            "Type of parameter `sep` doesn't match",
+        );
+    }
+
     #[test]
     fn test_pstr() {
         assert::pass(
@@ -309,4 +407,73 @@ assert_eq(prepr("abcd"), "\"abcd\"")
 "#,
         );
     }
+
+    #[test]
+    fn test_pprint() {
+        #[derive(Default)]
+        struct LastLine(RefCell<String>);
+        impl PrintHandler for LastLine {
+            fn println(&self, text: &str) -> anyhow::Result<()> {
+                *self.0.borrow_mut() = text.to_owned();
+                Ok(())
+            }
+        }
+
+        let last_line = LastLine::default();
+        let mut a = Assert::new();
+        a.set_print_handler(&last_line);
+        a.pass(r#"pprint({"b": [1, {"c": ()}], "a": {"x": None, "y": "z"}})"#);
+        assert_eq!(
+            r#"{
+  "b": [
+    1,
+    { "c": () }
+  ],
+  "a": {
+    "x": None,
+    "y": "z"
+  }
+}"#,
+            last_line.0.borrow().as_str()
+        );
+
+        a.pass("l = [1]; l.append(l); pprint(l)");
+        assert_eq!("[\n  1,\n  [...]\n]", last_line.0.borrow().as_str());
+        a.pass("d = {}; d['d'] = d; pprint(d)");
+        assert_eq!("{ \"d\": {...} }", last_line.0.borrow().as_str());
+    }
+
+    #[test]
+    fn test_max_repr_depth() {
+        let mut a = Assert::new();
+        a.setup_eval(|eval| eval.set_max_repr_depth(2));
+        a.all_true(
+            r#"
+repr([[1, "a"], {"k": None}]) == '[[1, "a"], {"k": None}]'
+repr([[[1]]]) == "[[[...]]]"
+str({1: {2: {3: 4}}}) == "{1: {2: {...}}}"
+"%s" % [[[1]]] == "[[[...]]]"
+repr([(1, (2, 3))]) == "[(1, (...))]"
+"#,
+        );
+        assert::eq("repr([[[1]]])", "'[[[1]]]'");
+    }
+
+    #[test]
+    fn test_max_repr_len() {
+        let mut a = Assert::new();
+        a.setup_eval(|eval| eval.set_max_repr_len(10));
+        a.all_true(
+            r#"
+repr([1, 2]) == "[1, 2]"
+repr(list(range(100000))) == "[0, 1, 2, ..."
+str({"a": "b" * 100}) == '{"a": "bbb...'
+"%s" % [[1, 2, 3], [4, 5, 6]] == "[[1, 2, 3]..."
+str("x" * 100) == "x" * 100
+"#,
+        );
+        let mut a = Assert::new();
+        a.setup_eval(|eval| eval.set_max_repr_len(3));
+        a.eq("'[\"a...'", r#"repr(["abc"])"#);
+    }
 }
@@ -22,24 +22,30 @@ use starlark_derive::starlark_module;
 use crate as starlark;
 use crate::environment::GlobalsBuilder;
 use crate::eval::Evaluator;
+use crate::values::list::sort::invoke_key;
 use crate::values::tuple::UnpackTuple;
 use crate::values::Value;
 
+#[derive(Debug, thiserror::Error)]
+enum MinMaxError {
+    #[error("Argument is an empty iterable, {0}() expect a non empty iterable")]
+    Empty(&'static str),
+    #[error("Cannot specify a default for {0}() with multiple positional arguments")]
+    DefaultWithMultipleArgs(&'static str),
+    #[error("{0}() expects at least one argument")]
+    NoArguments(&'static str),
+}
+
 fn min_max_iter<'v>(
     mut it: impl Iterator<Item = Value<'v>>,
     key: Option<Value<'v>>,
     eval: &mut Evaluator<'v, '_, '_>,
     // Select min on true, max on false.
     min: bool,
-) -> crate::Result<Value<'v>> {
+) -> crate::Result<Option<Value<'v>>> {
     let mut max = match it.next() {
         Some(x) => x,
-        None => {
-            return Err(anyhow::anyhow!(
-                "Argument is an empty iterable, max() expect a non empty iterable"
-            )
-            .into());
-        }
+        None => return Ok(None),
     };
     let update_max_ordering = if min {
         Ordering::Greater
@@ -55,9 +61,9 @@ fn min_max_iter<'v>(
             }
         }
         Some(key) => {
-            let mut cached = key.invoke_pos(&[max], eval)?;
-            for i in it {
-                let keyi = key.invoke_pos(&[i], eval)?;
+            let mut cached = invoke_key(key, max, 0, eval)?;
+            for (index, i) in it.enumerate() {
+                let keyi = invoke_key(key, i, index + 1, eval)?;
                 if cached.compare(keyi)? == update_max_ordering {
                     max = i;
                     cached = keyi;
@@ -65,22 +71,34 @@ fn min_max_iter<'v>(
             }
         }
     };
-    Ok(max)
+    Ok(Some(max))
 }
 
 /// Common implementation of `min` and `max`.
 fn min_max<'v>(
     mut args: UnpackTuple<Value<'v>>,
     key: Option<Value<'v>>,
+    default: Option<Value<'v>>,
     eval: &mut Evaluator<'v, '_, '_>,
     // Select min on true, max on false.
     min: bool,
 ) -> crate::Result<Value<'v>> {
-    if args.items.len() == 1 {
+    let name = if min { "min" } else { "max" };
+    let res = if args.items.is_empty() {
+        return Err(crate::Error::new_other(MinMaxError::NoArguments(name)));
+    } else if args.items.len() == 1 {
         let it = args.items.swap_remove(0).iterate(eval.heap())?;
-        min_max_iter(it, key, eval, min)
+        min_max_iter(it, key, eval, min)?
+    } else if default.is_some() {
+        return Err(crate::Error::new_other(
+            MinMaxError::DefaultWithMultipleArgs(name),
+        ));
     } else {
-        min_max_iter(args.items.into_iter(), key, eval, min)
+        min_max_iter(args.items.into_iter(), key, eval, min)?
+    };
+    match res.or(default) {
+        Some(res) => Ok(res),
+        None => Err(crate::Error::new_other(MinMaxError::Empty(name))),
     }
 }
 
@@ -96,11 +114,16 @@ pub(crate) fn register_min_max(globals: &mut GlobalsBuilder) {
     /// or if the sequence is empty.
     ///
     /// The optional named parameter `key` specifies a function to be applied
-    /// to each element prior to comparison.
+    /// to each element prior to comparison. Of several greatest elements,
+    /// the first one is returned.
+    ///
+    /// With a single iterable argument, the optional named parameter `default`
+    /// is returned if the iterable is empty.
     ///
     /// ```
     /// # starlark::assert::all_true(r#"
     /// max([3, 1, 4, 1, 5, 9])               == 9
+    /// max([], default=0)                    == 0
     /// max("two", "three", "four")           == "two"    # the lexicographically greatest
     /// max("two", "three", "four", key=len)  == "three"  # the longest
     /// # "#);
@@ -109,9 +132,10 @@ pub(crate) fn register_min_max(globals: &mut GlobalsBuilder) {
     fn max<'v>(
         #[starlark(args)] args: UnpackTuple<Value<'v>>,
         key: Option<Value<'v>>,
+        #[starlark(require = named)] default: Option<Value<'v>>,
         eval: &mut Evaluator<'v, '_, '_>,
     ) -> starlark::Result<Value<'v>> {
-        min_max(args, key, eval, false)
+        min_max(args, key, default, eval, false)
     }
 
     /// [min](
@@ -123,9 +147,12 @@ pub(crate) fn register_min_max(globals: &mut GlobalsBuilder) {
     /// It is an error if any element does not support ordered comparison,
     /// or if the sequence is empty.
     ///
+    /// The optional named parameters `key` and `default` behave as for `max`.
+    ///
     /// ```
     /// # starlark::assert::all_true(r#"
     /// min([3, 1, 4, 1, 5, 9])                 == 1
+    /// min([], default=0)                      == 0
     /// min("two", "three", "four")             == "four"  # the lexicographically least
     /// min("two", "three", "four", key=len)    == "two"   # the shortest
     /// # "#);
@@ -134,8 +161,86 @@ pub(crate) fn register_min_max(globals: &mut GlobalsBuilder) {
     fn min<'v>(
         #[starlark(args)] args: UnpackTuple<Value<'v>>,
         key: Option<Value<'v>>,
+        #[starlark(require = named)] default: Option<Value<'v>>,
         eval: &mut Evaluator<'v, '_, '_>,
     ) -> starlark::Result<Value<'v>> {
-        min_max(args, key, eval, true)
+        min_max(args, key, default, eval, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_min_max_key() {
+        assert::all_true(
+            r#"
+max([(1, "a"), (2, "b"), (2, "c")], key=lambda p: p[0]) == (2, "b")
+min([(1, "a"), (2, "b"), (1, "c")], key=lambda p: p[0]) == (1, "a")
+max("ab", "c", key=len) == "ab"
+max([], default=None) == None
+min([], default=-1) == -1
+min([3], default=-1) == 3
+"#,
+        );
+        assert::fail("min([])", "min() expect a non empty iterable");
+        assert::fail("max(1, 2, default=3)", "Cannot specify a default for max()");
+        assert::fail(
+            "max([1, 2, 3], key=lambda x: 1 // (x - 3))",
+            "Calling `key` on element 2",
+        );
+        assert::fail("max([1, 'a'], key=lambda x: x)", "not supported for types");
+        assert::fail("max()", "max() expects at least one argument");
+        assert::fail("min(key=len)", "min() expects at least one argument");
+    }
+
+    #[test]
+    fn test_min_max_values() {
+        assert::all_true(
+            r#"
+max(["apple", "pear", "fig"]) == "pear"
+min(["apple", "pear", "fig"]) == "apple"
+max("apple", "pear", "fig", key=len) == "apple"
+min("apple", "pear", "fig", key=len) == "fig"
+max([-3, 7, 2]) == 7
+min(-3, 7, 2) == -3
+max([1, 2.5, 2]) == 2.5
+min(range(5, 10)) == 5
+max("hello".elems()) == "o"
+"#,
+        );
+        assert::fail("max(1, 'a')", "not supported for types");
+    }
+
+    #[test]
+    fn test_min_max_ties_are_stable() {
+        assert::all_true(
+            r#"
+max(["bb", "aa", "cc"], key=len) == "bb"
+min(["bb", "aa", "cc"], key=len) == "bb"
+max((1, "x"), (1, "y"), key=lambda p: p[0]) == (1, "x")
+min([[1], [1]], key=len) == [1]
+"#,
+        );
+    }
+
+    #[test]
+    fn test_min_max_key_struct() {
+        assert::pass(
+            r#"
+people = [
+    struct(name = "dan", age = 30),
+    struct(name = "ann", age = 25),
+    struct(name = "bob", age = 30),
+    struct(name = "cat", age = 25),
+]
+assert_eq(max(people, key=lambda p: p.age).name, "dan")
+assert_eq(min(people, key=lambda p: p.age).name, "ann")
+assert_eq(max(people, key=lambda p: p.name).name, "dan")
+assert_eq(min(people, key=lambda p: (p.age, p.name)).name, "ann")
+assert_eq(max([], key=lambda p: p.age, default=None), None)
+"#,
+        );
     }
 }
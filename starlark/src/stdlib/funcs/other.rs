@@ -18,15 +18,15 @@
 //! A module with the standard function and constants that are by default in all
 //! dialect of Starlark
 
-use std::cmp::Ordering;
-
 use starlark_derive::starlark_module;
 
 use crate as starlark;
 use crate::environment::GlobalsBuilder;
+use crate::errors::FailError;
 use crate::eval::Evaluator;
 use crate::values::list::AllocList;
 use crate::values::tuple::UnpackTuple;
+use crate::values::types::list::sort::sort_values;
 use crate::values::typing::never::StarlarkNever;
 use crate::values::typing::ty::AbstractType;
 use crate::values::typing::StarlarkIter;
@@ -36,12 +36,21 @@ use crate::values::FrozenStringValue;
 use crate::values::Heap;
 use crate::values::Value;
 use crate::values::ValueError;
-use crate::values::ValueLike;
+
+#[derive(Debug, thiserror::Error)]
+enum ReversedError {
+    #[error("reversed() requires an iterable, got `{0}`")]
+    NotIterable(&'static str),
+}
 
 #[starlark_module]
 pub(crate) fn register_other(builder: &mut GlobalsBuilder) {
     /// fail: fail the execution
     ///
+    /// The arguments are converted with `str()` and joined by `sep`, which
+    /// defaults to a single space, as for `print`. Native code can inspect the
+    /// arguments through [`FailError`](crate::errors::FailError).
+    ///
     /// ```
     /// # starlark::assert::fail(r#"
     /// fail("this is an error")  # fail: this is an error
@@ -49,18 +58,24 @@ pub(crate) fn register_other(builder: &mut GlobalsBuilder) {
     /// # starlark::assert::fail(r#"
     /// fail("oops", 1, False)  # fail: oops 1 False
     /// # "#, "oops 1 False");
+    /// # starlark::assert::fail(r#"
+    /// fail("expected", 1, "got", 2, sep = ", ")  # fail: expected, 1, got, 2
+    /// # "#, "expected, 1, got, 2");
     /// ```
-    fn fail(#[starlark(args)] args: UnpackTuple<Value>) -> starlark::Result<StarlarkNever> {
-        let mut s = String::new();
-        for x in args.items {
-            s.push(' ');
-            match x.unpack_str() {
-                Some(x) => s.push_str(x),
-                None => x.collect_repr(&mut s),
-            }
-        }
+    fn fail(
+        #[starlark(args)] args: UnpackTuple<Value>,
+        #[starlark(require = named, default = " ")] sep: &str,
+        eval: &mut Evaluator,
+    ) -> starlark::Result<StarlarkNever> {
+        let arg_repr = match args.items.as_slice() {
+            [x] if x.unpack_str().is_none() => Some(x.to_repr()),
+            _ => None,
+        };
+        let strs: Vec<String> = args.items.iter().map(|x| x.to_str()).collect();
+        let message = strs.join(sep);
+        eval.trace_event(|| serde_json::json!({"event": "fail", "message": message}));
         Err(starlark::Error::new_kind(starlark::ErrorKind::Fail(
-            anyhow::Error::msg(s),
+            anyhow::Error::new(FailError::new(message, strs, arg_repr)),
         )))
     }
 
@@ -192,15 +207,21 @@ pub(crate) fn register_other(builder: &mut GlobalsBuilder) {
         #[starlark(require = pos)] a: Value<'v>,
         #[starlark(require = pos)] attr: &str,
         #[starlark(require = pos)] default: Option<Value<'v>>,
-        heap: &'v Heap,
+        eval: &mut Evaluator<'v, '_, '_>,
     ) -> starlark::Result<Value<'v>> {
+        if eval.has_attr_hooks() {
+            let location = eval.call_stack_top_location();
+            eval.run_attr_hooks(a, attr, location.as_ref().map(|l| l.as_ref()))?;
+        }
         // TODO(nga): this doesn't cache string hash, so it is suboptimal.
-        match a.get_attr(attr, heap)? {
-            Some(v) => Ok(v),
-            None => match default {
-                Some(x) => Ok(x),
-                None => ValueError::unsupported_owned(a.get_type(), &format!(".{}", attr), None),
-            },
+        match (a.get_attr(attr, eval.heap()), default) {
+            (Ok(Some(v)), _) => Ok(v),
+            // With a default, `getattr` never fails, even if the attribute cannot be bound.
+            (Ok(None) | Err(_), Some(x)) => Ok(x),
+            (Ok(None), None) => {
+                ValueError::unsupported_owned(a.get_type(), &format!(".{}", attr), None)
+            }
+            (Err(e), None) => Err(e),
         }
     }
 
@@ -229,8 +250,12 @@ pub(crate) fn register_other(builder: &mut GlobalsBuilder) {
     /// `hash` fails if x, or any value upon which its hash depends, is
     /// unhashable.
     ///
+    /// The result is not randomized: the same string has the same hash
+    /// in every run and every process.
+    ///
     /// ```
     /// # starlark::assert::all_true(r#"
+    /// hash("hello") == 99162322
     /// hash("hello") != hash("world")
     /// # "#);
     /// ```
@@ -292,7 +317,8 @@ pub(crate) fn register_other(builder: &mut GlobalsBuilder) {
     /// ): reverse a sequence
     ///
     /// `reversed(x)` returns a new list containing the elements of the iterable
-    /// sequence x in reverse order.
+    /// sequence x in reverse order. Every iterable has a defined iteration order,
+    /// so dictionaries are reversed in key insertion order.
     ///
     /// ```
     /// # starlark::assert::all_true(r#"
@@ -301,13 +327,27 @@ pub(crate) fn register_other(builder: &mut GlobalsBuilder) {
     /// reversed("stressed".elems())           == ["d", "e", "s", "s", "e", "r", "t", "s"]
     /// reversed({"one": 1, "two": 2}.keys())  == ["two", "one"]
     /// # "#);
+    /// # starlark::assert::fail(r#"
+    /// reversed(1)    # error: reversed() requires an iterable, got `int`
+    /// # "#, "reversed() requires an iterable, got `int`");
     /// ```
     #[starlark(speculative_exec_safe)]
     fn reversed<'v>(
         #[starlark(require = pos)] a: ValueOfUnchecked<'v, StarlarkIter<Value<'v>>>,
         heap: &'v Heap,
     ) -> starlark::Result<Vec<Value<'v>>> {
-        let mut v: Vec<Value> = a.get().iterate(heap)?.collect();
+        let it = a.get().iterate(heap).map_err(|e| match e.kind() {
+            starlark::ErrorKind::Value(v)
+                if matches!(
+                    v.downcast_ref::<ValueError>(),
+                    Some(ValueError::OperationNotSupported { op, .. }) if op == "(iter)"
+                ) =>
+            {
+                starlark::Error::new_value(ReversedError::NotIterable(a.get().get_type()))
+            }
+            _ => e,
+        })?;
+        let mut v: Vec<Value> = it.collect();
         v.reverse();
         Ok(v)
     }
@@ -343,37 +383,7 @@ pub(crate) fn register_other(builder: &mut GlobalsBuilder) {
         eval: &mut Evaluator<'v, '_, '_>,
     ) -> starlark::Result<AllocList<impl IntoIterator<Item = Value<'v>>>> {
         let it = x.get().iterate(eval.heap())?;
-        let mut it = match key {
-            None => it.map(|x| (x, x)).collect(),
-            Some(key) => {
-                let mut v = Vec::new();
-                for el in it {
-                    v.push((el, key.invoke_pos(&[el], eval)?));
-                }
-                v
-            }
-        };
-
-        let mut compare_ok = Ok(());
-
-        it.sort_by(|x: &(Value, Value), y: &(Value, Value)| {
-            let ord_or_err = if reverse {
-                x.1.compare(y.1).map(Ordering::reverse)
-            } else {
-                x.1.compare(y.1)
-            };
-            match ord_or_err {
-                Ok(r) => r,
-                Err(e) => {
-                    compare_ok = Err(e);
-                    Ordering::Equal // does not matter
-                }
-            }
-        });
-
-        compare_ok?;
-
-        Ok(AllocList(it.into_iter().map(|x| x.0)))
+        Ok(AllocList(sort_values(it, key, reverse, eval)?))
     }
 
     /// [type](
@@ -399,6 +409,59 @@ pub(crate) fn register_other(builder: &mut GlobalsBuilder) {
 mod tests {
     use crate::assert;
     use crate::assert::Assert;
+    use crate::errors::FailError;
+    use crate::tests::util::test_ints_globals;
+
+    #[test]
+    fn test_all_any_short_circuit() {
+        let mut a = Assert::new();
+        a.globals_add(test_ints_globals);
+        a.pass(
+            r#"
+t = ints(1, 0, 1, 1)
+assert_eq(all(t), False)
+assert_eq(pulled(t), 2)
+t = ints(0, 0, 1, 0, 0)
+assert_eq(any(t), True)
+assert_eq(pulled(t), 3)
+t = ints(1, 1)
+assert_eq(all(t), True)
+assert_eq(any(ints()), False)
+assert_eq(all(ints()), True)
+"#,
+        );
+    }
+
+    #[test]
+    fn test_reversed() {
+        assert::all_true(
+            r#"
+reversed([]) == []
+reversed((1, 2, 3)) == [3, 2, 1]
+reversed("abc".elems()) == ["c", "b", "a"]
+reversed({"a": 1, "b": 2}) == ["b", "a"]
+reversed({"a": 1, "b": 2}.items()) == [("b", 2), ("a", 1)]
+"#,
+        );
+        assert::fail("reversed(1)", "reversed() requires an iterable, got `int`");
+        assert::fail(
+            "reversed(None)",
+            "reversed() requires an iterable, got `NoneType`",
+        );
+    }
+
+    #[test]
+    fn test_enumerate() {
+        assert::all_true(
+            r#"
+enumerate([]) == []
+enumerate("ab".elems(), 5) == [(5, "a"), (6, "b")]
+enumerate(["x"], start = -1) == [(-1, "x")]
+enumerate({"k": "v"}) == [(0, "k")]
+"#,
+        );
+        assert::fail("enumerate(1)", "not supported");
+    }
 
     #[test]
     fn test_abs() {
@@ -473,6 +536,46 @@ noop(hash)(foo)
         assert::eq("-2147483649", "int('-2147483649')");
     }
 
+    #[test]
+    fn test_sorted_key() {
+        assert::pass(
+            r#"
+people = [
+    {"name": "dan", "age": 30},
+    {"name": "ann", "age": 25},
+    {"name": "bob", "age": 30},
+    {"name": "cat", "age": 25},
+]
+def by_age(p):
+    return p["age"]
+def names(ps):
+    return [p["name"] for p in ps]
+# Stable: equal keys keep their original order, also when reversed.
+assert_eq(names(sorted(people, key=by_age)), ["ann", "cat", "dan", "bob"])
+assert_eq(names(sorted(people, key=by_age, reverse=True)), ["dan", "bob", "ann", "cat"])
+assert_eq(names(people), ["dan", "ann", "bob", "cat"])
+assert_eq(sorted("bca".elems()), ["a", "b", "c"])
+"#,
+        );
+        let e = assert::fail(
+            r#"
+def key(x):
+    return 1 // x
+sorted([1, 2, 0, 3], key=key)
+"#,
+            "Calling `key` on element 2: Floor division by zero",
+        );
+        // The original message is not repeated in the context trace.
+        for e in [format!("{e:#}"), format!("{e:?}")] {
+            assert_eq!(1, e.matches("Floor division by zero").count(), "{e}");
+        }
+        assert::eq("[1, 'a']", "sorted([1, 'a'], key=str)");
+        assert::fail(
+            "sorted([1, 'a'], key=lambda x: x)",
+            "not supported for types",
+        );
+    }
+
     #[test]
     fn test_tuple() {
         let mut a = Assert::new();
@@ -481,4 +584,102 @@ noop(hash)(foo)
         a.eq("(1, 2)", "tuple((1, 2))");
         a.eq("(1, 2)", "tuple([1, 2])");
     }
+
+    fn fail_error(err: &crate::Error) -> &FailError {
+        match err.kind() {
+            crate::ErrorKind::Fail(e) => e.downcast_ref::<FailError>().unwrap(),
+            _ => panic!("not a `fail` error: {err}"),
+        }
+    }
+
+    #[test]
+    fn test_fail() {
+        let err = assert::fail(
+            "fail('expected', 1, 'got', [2])",
+            "fail: expected 1 got [2]",
+        );
+        let fail = fail_error(&err);
+        assert_eq!("expected 1 got [2]", fail.message());
+        assert_eq!(["expected", "1", "got", "[2]"], fail.args());
+        assert_eq!(None, fail.arg_repr());
+        assert!(err.to_string().contains("--> assert.bzl:1:1"), "{err}");
+
+        let err = assert::fail("fail('a', 'b', sep = '')", "fail: ab");
+        assert_eq!(["a", "b"], fail_error(&err).args());
+
+        let err = assert::fail("fail(['x'])", "fail: [\"x\"]");
+        assert_eq!(Some("[\"x\"]"), fail_error(&err).arg_repr());
+        let err = assert::fail("fail('x')", "fail: x");
+        assert_eq!(None, fail_error(&err).arg_repr());
+
+        assert::fail("fail(1, sep = 2)", "Type of parameter `sep` doesn't match");
+    }
+
+    #[test]
+    fn test_fail_in_loaded_module() {
+        let mut a = Assert::new();
+        a.module(
+            "lib.star",
+            "def check(x):\n    if x < 0:\n        fail('negative:', x)",
+        );
+        let err = a.fail(
+            "load('lib.star', 'check')\ndef go():\n    check(-1)\ngo()",
+            "fail: negative: -1",
+        );
+        let err = format!("{err}");
+        assert!(err.contains("assert.bzl:4, in <module>"), "{err}");
+        assert!(err.contains("assert.bzl:3, in go"), "{err}");
+        assert!(err.contains("lib.star.bzl:3, in check"), "{err}");
+    }
+
+    #[test]
+    fn test_attrs_consistent() {
+        let mut a = Assert::new();
+        a.globals_add(test_ints_globals);
+        for program in [
+            "'abc'",
+            "[1]",
+            "{1: 2}",
+            "set([1])",
+            "(1,)",
+            "1",
+            "1.5",
+            "True",
+            "None",
+            "range(3)",
+            "struct(b = 1, a = 2)",
+            "namespace(a = 1)",
+            "r = record(x = int)\nr(x = 1)",
+            "enum('a', 'b')('a')",
+            "json",
+            "lambda x: x",
+            "len",
+            "'abc'.split",
+            "ints(1, 2)",
+            "ints()",
+        ] {
+            a.attrs_consistent(program);
+        }
+    }
+
+    #[test]
+    fn test_dir_hasattr_getattr() {
+        let mut a = Assert::new();
+        a.globals_add(test_ints_globals);
+        a.all_true(
+            r#"
+"split" in dir("abc") and hasattr("abc", "split")
+"append" in dir([]) and hasattr([], "append")
+"setdefault" in dir({}) and hasattr({}, "setdefault")
+dir(ints(1, 2)) == ["first", "last"]
+hasattr(ints(1, 2), "first") and not hasattr(ints(1, 2), "z")
+getattr(ints(1, 2), "last") == 2
+getattr(ints(1, 2), "z", "d") == "d"
+getattr(1, "missing", None) == None
+getattr(None, "missing", 7) == 7
+getattr("abc", "upper")() == "ABC"
+"#,
+        );
+        a.fail("getattr(ints(1, 2), 'z')", "Operation `.z` not supported");
+    }
 }
@@ -74,7 +74,7 @@ pub(crate) fn register_zip(globals: &mut GlobalsBuilder) {
     /// `zip`.  That is, the first tuple contains the first element of each of
     /// the sequences, the second element contains the second element of each
     /// of the sequences, and so on.  The result list is only as long as the
-    /// shortest of the input sequences.
+    /// shortest of the input sequences, and the others are not iterated further.
     ///
     /// ```
     /// # starlark::assert::all_true(r#"
@@ -88,22 +88,69 @@ pub(crate) fn register_zip(globals: &mut GlobalsBuilder) {
         #[starlark(args)] args: UnpackTuple<ValueOfUnchecked<'v, StarlarkIter<FrozenValue>>>,
         heap: &'v Heap,
     ) -> starlark::Result<Vec<Value<'v>>> {
+        let mut iters = args
+            .items
+            .into_iter()
+            .map(|arg| arg.get().iterate(heap))
+            .collect::<starlark::Result<Vec<_>>>()?;
         let mut v = Vec::new();
-        let mut first = true;
-        for arg in args.items {
-            let mut idx = 0;
-            for e in arg.get().iterate(heap)? {
-                if first {
-                    v.push(heap.alloc((e,)));
-                    idx += 1;
-                } else if idx < v.len() {
-                    v[idx] = v[idx].add(heap.alloc((e,)), heap)?;
-                    idx += 1;
+        if iters.is_empty() {
+            return Ok(v);
+        }
+        // Advance all the iterators together, so the longer ones are not
+        // consumed past the end of the shortest.
+        let mut items = Vec::with_capacity(iters.len());
+        'zip: loop {
+            items.clear();
+            for iter in &mut iters {
+                match iter.next() {
+                    Some(e) => items.push(e),
+                    None => break 'zip,
                 }
             }
-            v.truncate(idx);
-            first = false;
+            v.push(heap.alloc_tuple(&items));
         }
         Ok(v)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+    use crate::assert::Assert;
+    use crate::tests::util::test_ints_globals;
+
+    #[test]
+    fn test_zip_enumerate_custom_iterable() {
+        let mut a = Assert::new();
+        a.globals_add(test_ints_globals);
+        a.eq("[(3, 'a'), (2, 'b')]", "zip(countdown(3), ['a', 'b'])");
+        a.eq("[(3,), (2,), (1,)]", "zip(countdown(3))");
+        a.eq("[]", "zip(countdown(0), [1])");
+        a.eq("[(0, 2), (1, 1)]", "enumerate(countdown(2))");
+        a.eq("[(10, 2), (11, 1)]", "enumerate(countdown(2), start = 10)");
+    }
+
+    #[test]
+    fn test_zip_stops_at_shortest() {
+        // Iterating the whole range would take far too long.
+        assert::eq(
+            "[(1, 0, 'a'), (2, 1, 'b')]",
+            "zip([1, 2, 3], range(2000000000), 'ab'.elems())",
+        );
+        assert::eq("[]", "zip()");
+    }
+
+    #[test]
+    fn test_zip() {
+        assert::all_true(
+            r#"
+zip([1, 2]) == [(1,), (2,)]
+zip([], [1]) == []
+zip((1, 2), {"a": 1, "b": 2}) == [(1, "a"), (2, "b")]
+zip("ab".elems(), [1, 2, 3], range(10)) == [("a", 1, 0), ("b", 2, 1)]
+"#,
+        );
+        assert::fail("zip([1], 2)", "not supported");
+    }
+}
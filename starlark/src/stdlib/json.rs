@@ -208,6 +208,23 @@ impl AllocFrozenValue for serde_json::Value {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid JSON at byte offset {offset}: {error}")]
+struct JsonDecodeError {
+    offset: usize,
+    error: serde_json::Error,
+}
+
+/// Convert a 1-based line and column, as reported by `serde_json`, to a byte offset.
+fn byte_offset(s: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = s
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(|l| l.len())
+        .sum();
+    (line_start + column.saturating_sub(1)).min(s.len())
+}
+
 pub(crate) fn json(globals: &mut GlobalsBuilder) {
     #[starlark_module]
     fn json_members(globals: &mut GlobalsBuilder) {
@@ -215,11 +232,23 @@ pub(crate) fn json(globals: &mut GlobalsBuilder) {
             x.to_json()
         }
 
+        /// Parse a JSON string. Objects become dicts, arrays become lists,
+        /// numbers with a fraction or an exponent become floats and other numbers become ints.
         fn decode<'v>(
             #[starlark(require = pos)] x: &str,
             heap: &'v Heap,
-        ) -> anyhow::Result<Value<'v>> {
-            Ok(heap.alloc(serde_json::from_str::<serde_json::Value>(x)?))
+        ) -> starlark::Result<Value<'v>> {
+            match serde_json::from_str::<serde_json::Value>(x) {
+                Ok(v) => Ok(heap.alloc(v)),
+                Err(e) => Err(starlark::Error::new_value(JsonDecodeError {
+                    offset: if e.is_eof() {
+                        x.len()
+                    } else {
+                        byte_offset(x, e.line(), e.column())
+                    },
+                    error: e,
+                })),
+            }
         }
     }
 
@@ -248,9 +277,40 @@ mod tests {
         );
 
         a.eq("3.142", "json.decode('3.142')");
+        // Integers which don't fit in 64 bits are decoded as floats,
+        // which are not equal to the exact int.
         a.eq(
-            "123456789123456789123456789",
+            "float(123456789123456789123456789)",
             "json.decode('123456789123456789123456789')",
         );
+        a.is_true("type(json.decode('3')) == 'int'");
+        a.is_true("type(json.decode('3.0')) == 'float'");
+        a.is_true("type(json.decode('3e2')) == 'float'");
+        a.eq("-7", "json.decode('-7')");
+    }
+
+    #[test]
+    fn test_json_decode_error() {
+        let a = Assert::new();
+        a.fail(
+            "json.decode('[1, 2,]')",
+            "Invalid JSON at byte offset 6: trailing comma",
+        );
+        a.fail(
+            "json.decode('{\"a\": 1,\\n\"b\": }')",
+            "Invalid JSON at byte offset 14:",
+        );
+        a.fail("json.decode('[1')", "Invalid JSON at byte offset 2:");
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let a = Assert::new();
+        a.is_true(
+            r#"
+x = {"a": [1, 2.5, None, True], "b": {"c": "d"}}
+json.decode(json.encode(x)) == x
+"#,
+        );
     }
 }
@@ -21,3 +21,4 @@ pub use starlark_syntax::dialect::Dialect;
 pub use starlark_syntax::dialect::DialectTypes;
 pub use starlark_syntax::syntax::AstLoad;
 pub use starlark_syntax::syntax::AstModule;
+pub use starlark_syntax::syntax::TextEdit;
@@ -15,21 +15,26 @@
  * limitations under the License.
  */
 
+mod annotated;
+mod attr_hook;
 mod basic;
 mod bc;
 mod before_stmt;
 mod call;
 mod comprehension;
 mod def;
+mod del;
 mod derive;
 mod for_loop;
 mod freeze_access_value;
 mod fstring;
 mod go;
 mod interop;
+mod load;
 mod opt;
 mod replace_binary;
 mod runtime;
+mod trace;
 mod type_annot;
 mod uncategorized;
 pub(crate) mod util;
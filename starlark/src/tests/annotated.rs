@@ -0,0 +1,70 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Run the `###`-annotated test files in `testcases/eval/annotated`.
+
+use crate::assert;
+
+#[test]
+fn test_annotated() {
+    macro_rules! test_case {
+        ($name:expr) => {
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testcases/eval/annotated/",
+                $name,
+            ))
+        };
+    }
+
+    assert::conformance_strict(test_case!("dict.star"));
+    assert::conformance_strict(test_case!("int.star"));
+    assert::conformance_strict(test_case!("list.star"));
+    assert::conformance_strict(test_case!("string.star"));
+}
+
+#[test]
+#[should_panic(expected = "failed at wrong line")]
+fn test_conformance_wrong_line() {
+    assert::conformance_strict("x = 1 ### \"error\"\nfail(\"error\")");
+}
+
+#[test]
+#[should_panic(expected = "failed with the wrong message")]
+fn test_conformance_wrong_message() {
+    assert::conformance_strict("fail(\"bad\") ### \"other\"");
+}
+
+#[test]
+#[should_panic]
+fn test_conformance_unexpected_pass() {
+    assert::conformance_strict("x = 1 ### \"error\"");
+}
+
+#[test]
+fn test_conformance_expected_failure() {
+    assert::Assert::new().conformance_expect_failures(
+        "assert_eq(1, 1)\n---\nassert_eq(1, 2)\n---\nx = 1 ### \"error\"",
+        &["assert_eq(1, 2)", "x = 1"],
+    );
+}
+
+#[test]
+#[should_panic(expected = "expected failure passed")]
+fn test_conformance_expected_failure_passes() {
+    assert::Assert::new().conformance_expect_failures("assert_eq(1, 1)", &["assert_eq"]);
+}
@@ -0,0 +1,118 @@
+/*
+ * Copyright 2018 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::cell::Cell;
+use std::cell::RefCell;
+
+use crate::environment::Globals;
+use crate::environment::Module;
+use crate::eval::AttrHookDecision;
+use crate::eval::Evaluator;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+
+#[test]
+fn attr_hook_sees_all_lookups() {
+    let module = Module::new();
+    let globals = Globals::extended_internal();
+    let accesses = RefCell::new(Vec::new());
+
+    let mut evaluator = Evaluator::new(&module);
+    evaluator.add_attr_hook(|access| {
+        let line = access.span.map_or(0, |s| s.resolve_span().begin.line + 1);
+        accesses
+            .borrow_mut()
+            .push(format!("{}.{} {}", access.type_name, access.attr, line));
+        Ok(AttrHookDecision::PassThrough)
+    });
+
+    let program = "\
+x = []
+x.append(1)
+y = \"a\".upper()
+z = getattr(x, \"pop\")
+s = struct(a = 1).a
+";
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+    evaluator.eval_module(ast, &globals).unwrap();
+    drop(evaluator);
+    assert_eq!(
+        vec![
+            "list.append 2",
+            "string.upper 3",
+            "list.pop 4",
+            "struct.a 5"
+        ],
+        accesses.into_inner()
+    );
+}
+
+#[test]
+fn attr_hook_deny() {
+    let module = Module::new();
+    let globals = Globals::standard();
+
+    let mut evaluator = Evaluator::new(&module);
+    evaluator.add_attr_hook(|access| {
+        if access.attr == "upper" {
+            Err(crate::Error::new_other(anyhow::anyhow!(
+                "`upper` is forbidden"
+            )))
+        } else {
+            Ok(AttrHookDecision::PassThrough)
+        }
+    });
+
+    let program = "\
+def f(s):
+  return s.lower() + s.upper()
+f(\"a\")
+";
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+    let err = evaluator.eval_module(ast, &globals).unwrap_err();
+    assert!(err.to_string().contains("`upper` is forbidden"), "{err}");
+}
+
+#[test]
+fn attr_hook_allow_stops_later_hooks() {
+    let module = Module::new();
+    let globals = Globals::standard();
+    let later = Cell::new(0);
+
+    let mut evaluator = Evaluator::new(&module);
+    evaluator.add_attr_hook(|access| {
+        if access.attr == "append" {
+            Ok(AttrHookDecision::Allow)
+        } else {
+            Ok(AttrHookDecision::PassThrough)
+        }
+    });
+    evaluator.add_attr_hook(|_| {
+        later.set(later.get() + 1);
+        Ok(AttrHookDecision::PassThrough)
+    });
+
+    let program = "\
+x = []
+x.append(1)
+x.extend([2])
+";
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+    evaluator.eval_module(ast, &globals).unwrap();
+    drop(evaluator);
+    assert_eq!(1, later.get());
+}
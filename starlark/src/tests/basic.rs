@@ -20,6 +20,8 @@
 use crate::assert;
 use crate::assert::Assert;
 use crate::environment::GlobalsBuilder;
+use crate::values::FrozenHeap;
+use crate::values::Heap;
 use crate::values::OwnedFrozenValue;
 
 #[test]
@@ -168,6 +170,54 @@ assert_eq(all([frozen_dict[x] != None for x in values]), True)
     ));
 }
 
+#[test]
+fn test_hash_deterministic() {
+    // `hash` is specified as `java.lang.String.hashCode`, so these never change.
+    assert::eq("hash('')", "0");
+    assert::eq("hash('hello')", "99162322");
+    assert::eq("hash('été')", "227742");
+
+    // Value hashes do not depend on the heap or on whether the value is frozen.
+    let heap1 = Heap::new();
+    let heap2 = Heap::new();
+    let frozen = FrozenHeap::new();
+    for x in ["", "hello"] {
+        let h = heap1.alloc(x).get_hash().unwrap();
+        assert_eq!(h, heap2.alloc(x).get_hash().unwrap());
+        assert_eq!(h, frozen.alloc(x).to_value().get_hash().unwrap());
+    }
+    for x in [0, 1, -17, i32::MAX] {
+        let h = heap1.alloc(x).get_hash().unwrap();
+        assert_eq!(h, heap2.alloc(x).get_hash().unwrap());
+        assert_eq!(h, frozen.alloc(x).to_value().get_hash().unwrap());
+    }
+    let big = heap1.alloc(1i64 << 40).get_hash().unwrap();
+    assert_eq!(big, heap2.alloc(1i64 << 40).get_hash().unwrap());
+}
+
+#[test]
+fn test_small_int_arithmetic() {
+    assert::eq("2147483647 + 1", "2147483648");
+    assert::eq("-2147483648 - 1", "-2147483649");
+    assert::eq("65536 * 65536", "4294967296");
+    assert::eq("-2147483648 * -1", "2147483648");
+    assert::is_true("noop(1) < 2 and noop(2) > 1 and noop(1) <= 1 and noop(-1) >= -1");
+    assert::is_true("noop(1) < 1.5 and noop(1) == 1.0 and noop(1) != 2");
+    assert::eq(
+        "s = 0\nfor i in range(-2, 1000, 3):\n    s += i * i - i\ns",
+        "110445450",
+    );
+    assert::fail(
+        "noop(1) + 'a'",
+        "Operation `+` not supported for types `int` and `string`",
+    );
+    assert::fail(
+        "noop(1) - 'a'",
+        "Operation `-` not supported for types `int` and `string`",
+    );
+    assert::fail("noop(1) < 'a'", "Operation `compare` not supported");
+}
+
 #[test]
 fn test_compare() {
     assert::fail("noop(1) > False", "Operation `compare` not supported");
@@ -53,3 +53,19 @@ fn test_if_true_clause_on_freeze() {
         "def test(y): return [x for x in y if C]\nC = False\nC = True",
     );
 }
+
+#[test]
+fn test_dict_presized() {
+    bc_golden_test(
+        "compr_dict_presized",
+        "def test(y): return {x: 1 for x in y}",
+    );
+}
+
+#[test]
+fn test_filtered_not_presized() {
+    bc_golden_test(
+        "compr_filtered_not_presized",
+        "def test(y): return [x for x in y if x]",
+    );
+}
@@ -16,6 +16,7 @@
  */
 
 use std::cell::Cell;
+use std::cell::RefCell;
 
 use crate::codemap::FileSpanRef;
 use crate::environment::Globals;
@@ -47,3 +48,65 @@ f()            # 2 + 1
     evaluator.eval_module(ast, &globals).unwrap();
     assert_eq!(7, counter.get());
 }
+
+#[test]
+fn before_stmt_hook_lines() {
+    let module = Module::new();
+    let globals = Globals::standard();
+    let lines = RefCell::new(Vec::new());
+    let calls = RefCell::new(Vec::new());
+
+    let mut evaluator = Evaluator::new(&module);
+    evaluator.add_before_stmt_hook(|span, _eval| {
+        lines.borrow_mut().push(span.resolve_span().begin.line + 1);
+        Ok(())
+    });
+    evaluator.add_call_hook(|event, name| calls.borrow_mut().push(format!("{event:?} {name}")));
+
+    let program = "\
+def f(x):
+  if x:
+    return 1
+  return 2
+f(True)
+for i in [1, 2]:
+  f(False)
+";
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+    evaluator.eval_module(ast, &globals).unwrap();
+    drop(evaluator);
+    assert_eq!(vec![1, 5, 2, 3, 6, 7, 2, 4, 7, 2, 4], lines.into_inner());
+    assert_eq!(
+        vec!["Call f", "Return f", "Call f", "Return f", "Call f", "Return f"],
+        calls.into_inner()
+    );
+}
+
+#[test]
+fn before_stmt_hook_error() {
+    let module = Module::new();
+    let globals = Globals::standard();
+    let count = Cell::new(0);
+
+    let mut evaluator = Evaluator::new(&module);
+    evaluator.add_before_stmt_hook(|_, _| {
+        count.set(count.get() + 1);
+        Ok(())
+    });
+    evaluator.add_before_stmt_hook(|span, _| {
+        if span.resolve_span().begin.line == 1 {
+            Err(crate::Error::new_other(anyhow::anyhow!(
+                "line 2 is forbidden"
+            )))
+        } else {
+            Ok(())
+        }
+    });
+
+    let program = "x = 1\ny = 2\nz = 3\n";
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+    let err = evaluator.eval_module(ast, &globals).unwrap_err();
+    assert!(err.to_string().contains("line 2 is forbidden"), "{err}");
+    drop(evaluator);
+    assert_eq!(2, count.get());
+}
@@ -167,7 +167,10 @@ fn test_parameter_defaults_frozen() {
     // It works if we call it with an explicit parameter
     a.is_true("load('f.bzl', 'f')\nf(1, [2]) == [2, 1]");
     // But fails if we don't, with a frozen error
-    a.fail("load('f.bzl', 'f')\nf(1) == [1]", "Immutable");
+    a.fail(
+        "load('f.bzl', 'f')\nf(1) == [1]",
+        "Cannot mutate frozen value",
+    );
 }
 
 #[test]
@@ -223,6 +226,45 @@ assert_eq(r, [1,2,3,4,5])
     );
 }
 
+#[test]
+fn test_kwargs_spread() {
+    assert::pass(
+        r#"
+def f(a, b = 2, **kwargs):
+    return (a, b, kwargs)
+config = {"b": 3, "a": 1, "c": 4}
+assert_eq(f(**config), (1, 3, {"c": 4}))
+assert_eq(f(0, **{"c": 4}), (0, 2, {"c": 4}))
+assert_eq(dict(x = 1, **{"y": 2}), {"x": 1, "y": 2})
+"#,
+    );
+    let mut a = Assert::new();
+    a.disable_static_typechecking();
+    a.fail(
+        "def f(a, b = 2): pass\nf(1, **{'a': 2})",
+        "Argument `a` occurs more than once",
+    );
+    a.fail(
+        "def f(**kwargs): pass\nf(x = 1, **{'x': 2})",
+        "Argument `x` occurs more than once",
+    );
+    a.fail(
+        "noop(dict)(a = 1, **{'a': 2})",
+        "Argument `a` occurs more than once",
+    );
+    a.fail("def f(a): pass\nf(**{'a': 1, 'b': 2})", "extra named");
+    for call in ["f(**{1: 2})", "f(a = 1, **{1: 2})", "dict(**{1: 2})"] {
+        a.fail(
+            &format!("def f(a = 1, **kwargs): pass\n{call}"),
+            "The keys of the dictionary provided for **kwargs must be strings",
+        );
+    }
+    a.fail(
+        "def f(**kwargs): pass\nf(**[1])",
+        "The argument provided for **kwargs is not a dictionary",
+    );
+}
+
 #[test]
 fn test_empty_args_kwargs() {
     // This was a bug that was introduced in the past, so make sure you don't forget
@@ -114,3 +114,20 @@ assert_eq(res, [1,2])
         "variable `x` referenced before assignment",
     );
 }
+
+#[test]
+fn test_presized() {
+    // Single clause comprehensions allocate the result using the iterable length.
+    check_comp(&["[x + 1 for x in range(5)] == [1, 2, 3, 4, 5]"]);
+    check_comp(&["[x for x in {1: 2, 3: 4}] == [1, 3]"]);
+    check_comp(&["{x % 2: x for x in range(5)} == {0: 4, 1: 3}"]);
+    check_comp(&["{k: v for k, v in [(1, 2)]} == {1: 2}"]);
+    check_comp(&["[x for x in []] == []"]);
+    check_comp(&[
+        "seen = []",
+        "res = [seen.append(x) or len(seen) for x in (5, 6, 7)]",
+        "res == [1, 2, 3] and seen == [5, 6, 7]",
+    ]);
+    check_comp(&["[[y * x for y in range(x)] for x in range(3)] == [[], [0], [0, 2]]"]);
+    assert::fail("[x for x in 1]", "not supported");
+}
@@ -0,0 +1,32 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Tests of `del` statement.
+
+use crate::assert::Assert;
+use crate::syntax::Dialect;
+
+#[test]
+fn test_del_dialect() {
+    let mut a = Assert::new();
+    a.dialect(&Dialect::Standard);
+    a.fail("x = [1]\ndel x[0]", "`del` is not allowed in this dialect");
+    a.fail(
+        "x = [1]\nx[0:1] = []",
+        "Slice assignment is not allowed in this dialect",
+    );
+}
@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 
+use crate::assert;
 use crate::values::list::ListRef;
 use crate::values::Freeze;
 use crate::values::Freezer;
@@ -54,3 +55,28 @@ fn test() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_read_frozen_containers() {
+    let module = assert::pass_module(r#"cfg = {"deps": ["a", "b"]}"#);
+    let cfg = module.get("cfg").unwrap();
+
+    let deps: Vec<String> = cfg
+        .downcast_frozen_dict()
+        .unwrap()
+        .find(|(k, _)| k.unpack_str() == Some("deps"))
+        .unwrap()
+        .1
+        .downcast_frozen_list()
+        .unwrap()
+        .map(|dep| dep.unpack_str().unwrap().to_owned())
+        .collect();
+    assert_eq!(deps, vec!["a".to_owned(), "b".to_owned()]);
+
+    // SAFE: `module` outlives every use of the raw frozen values below.
+    let cfg = unsafe { cfg.unchecked_frozen_value() };
+    let (key, deps) = cfg.downcast_frozen_dict().unwrap().next().unwrap();
+    assert_eq!(key.downcast_frozen_str().as_deref(), Some("deps"));
+    assert_eq!(deps.downcast_frozen_list().map(|xs| xs.len()), Some(2));
+    assert!(deps.downcast_frozen_dict().is_none());
+}
@@ -46,7 +46,7 @@ fn test_go() {
     let mut assert = Assert::new();
     // TODO(nga): fix and enable.
     assert.disable_static_typechecking();
-    assert.conformance_except(
+    assert.conformance_expect_failures(
         test_case!("assign.star"),
         &[
             "hasfields()", // Not sure what this is, but we don't support
@@ -80,24 +80,17 @@ fn test_go() {
         ],
     ));
     assert.conformance(test_case!("control.star"));
-    assert.conformance_except(
-        &ignore_bad_lines(
-            test_case!("dict.star"),
-            &[
-                "unknown binary op: dict \\\\+ dict",   // We support {} + {}
-                "cannot insert into frozen hash table", // We don't actually have freeze
-                "cannot clear frozen hash table",
-                "asserts.eq(a, 1)", // End of the test above
-                "asserts.eq(x, {1: 2, 2: 4, 0: 2})",
-                "x9a", // Starlark spec does not allow test list in index expression
-            ],
-        ),
+    assert.conformance(&ignore_bad_lines(
+        test_case!("dict.star"),
         &[
-            "Verify position of an \"unhashable key\"", // FIXME: We should give a better error message
-            "Verify position of a \"duplicate key\"",   // FIXME: Give a better line number
-            "Verify position of an \"unhashable key\"", // FIXME: we should do better
+            "unknown binary op: dict \\\\+ dict",   // We support {} + {}
+            "cannot insert into frozen hash table", // We don't actually have freeze
+            "cannot clear frozen hash table",
+            "asserts.eq(a, 1)", // End of the test above
+            "asserts.eq(x, {1: 2, 2: 4, 0: 2})",
+            "x9a", // Starlark spec does not allow test list in index expression
         ],
-    );
+    ));
     assert.conformance_except(
         &ignore_bad_lines(
             test_case!("set.star"),
@@ -138,7 +131,7 @@ fn test_go() {
     ));
     // Skip int.star, a lot of bit mask stuff, floats and int's outside our range
     // Skip list.star, our strings disagree about whether they are lists of codepoints or lists of 1-char strings
-    assert.conformance_except(
+    assert.conformance_expect_failures(
         &ignore_bad_lines(
             test_case!("misc.star"),
             &[
@@ -17,6 +17,8 @@
 
 //! Test starlark-rust embedding.
 
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -31,17 +33,22 @@ use crate::any::ProvidesStaticType;
 use crate::assert;
 use crate::assert::Assert;
 use crate::collections::SmallMap;
+use crate::environment::Globals;
 use crate::environment::GlobalsBuilder;
 use crate::environment::Module;
 use crate::eval::Evaluator;
 use crate::syntax::AstModule;
 use crate::syntax::Dialect;
+use crate::tests::util::test_ints_globals;
 use crate::values::any::StarlarkAny;
 use crate::values::exported_name::FrozenExportedName;
+use crate::values::list::AllocList;
+use crate::values::list::UnpackList;
 use crate::values::none::NoneType;
 use crate::values::types::exported_name::ExportedName;
 use crate::values::types::exported_name::MutableExportedName;
 use crate::values::Freeze;
+use crate::values::Heap;
 use crate::values::NoSerialize;
 use crate::values::StarlarkValue;
 use crate::values::Value;
@@ -124,7 +131,15 @@ load('a', 'x', 'y', 'longer_name', 'arrayed')
 v = str((x, y, longer_name, arrayed[0]))
 v == '{}' or v == '{}'"#,
         opt1, opt2
-    ))
+    ));
+
+    // When bindings are separate statements, the first one wins.
+    a.module("b", "first = exporter(4)\nsecond = first\nthird = second");
+    a.is_true(
+        r#"
+load('b', 'first', 'second', 'third')
+str((first, second, third)) == '(first=4, first=4, first=4)'"#,
+    );
 }
 
 #[test]
@@ -260,3 +275,226 @@ fun
         .unwrap();
     assert_eq!(v.unpack_str(), Some("(8, \"hello\", 1)"))
 }
+
+#[test]
+fn test_native_higher_order_function() {
+    #[starlark_module]
+    fn module(builder: &mut GlobalsBuilder) {
+        fn my_map<'v>(
+            f: Value<'v>,
+            xs: UnpackList<Value<'v>>,
+            eval: &mut Evaluator<'v, '_, '_>,
+        ) -> starlark::Result<AllocList<Vec<Value<'v>>>> {
+            let mut res = Vec::with_capacity(xs.items.len());
+            for x in xs.items {
+                res.push(eval.eval_function(f, &[x], &[])?);
+            }
+            Ok(AllocList(res))
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(module);
+    a.pass(
+        r#"
+def add(y):
+    return lambda x: x + y
+assert_eq(my_map(add(10), [1, 2]), [11, 12])
+assert_eq(my_map(lambda xs: my_map(str, xs), [[1], [2, 3]]), [["1"], ["2", "3"]])
+"#,
+    );
+    let e = a.fail(
+        r#"
+def inner(x):
+    fail("bad " + str(x))
+def outer():
+    return my_map(inner, [1, 2])
+outer()
+"#,
+        "bad 1",
+    );
+    // The callback is called in the frame of `my_map`.
+    assert_eq!(
+        vec!["outer", "my_map", "inner", "fail"],
+        e.call_stack()
+            .frames
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>()
+    );
+    // Recursion through the native function counts towards the stack size limit.
+    a.fail(
+        r#"
+def f(x):
+    return my_map(f, [x])
+f(1)
+"#,
+        "Starlark call stack overflow",
+    );
+}
+
+#[test]
+fn test_native_iterate() {
+    #[starlark_module]
+    fn module(builder: &mut GlobalsBuilder) {
+        fn collect<'v>(
+            xs: Value<'v>,
+            heap: &'v Heap,
+        ) -> starlark::Result<AllocList<Vec<Value<'v>>>> {
+            Ok(AllocList(xs.iterate(heap)?.collect()))
+        }
+
+        fn for_each<'v>(
+            xs: Value<'v>,
+            f: Value<'v>,
+            eval: &mut Evaluator<'v, '_, '_>,
+        ) -> starlark::Result<NoneType> {
+            for x in xs.iterate(eval.heap())? {
+                eval.eval_function(f, &[x], &[])?;
+            }
+            Ok(NoneType)
+        }
+    }
+
+    let mut a = Assert::new();
+    a.globals_add(|g| {
+        module(g);
+        test_ints_globals(g);
+    });
+    a.eq("[1, 2]", "collect([1, 2])");
+    a.eq("[1, 2]", "collect((1, 2))");
+    a.eq("['x', 'y']", "collect({'x': 1, 'y': 2})");
+    a.eq("[0, 1, 2]", "collect(range(3))");
+    a.eq("['a', 'b']", "collect('ab'.elems())");
+    a.eq("[3, 2, 1]", "collect(countdown(3))");
+    a.eq("[]", "collect([])");
+    a.pass(
+        r#"
+res = []
+for_each(countdown(2), res.append)
+assert_eq(res, [2, 1])
+"#,
+    );
+
+    // Same errors as a `for` loop.
+    a.fail(
+        "collect(1)",
+        "Operation `(iter)` not supported on type `int`",
+    );
+    a.fail(
+        "for x in 1: pass",
+        "Operation `(iter)` not supported on type `int`",
+    );
+    a.fail(
+        r#"
+xs = [1]
+for_each(xs, xs.append)
+"#,
+        "mutate an iterable for an iterator while iterating",
+    );
+    a.fail(
+        r#"
+def test():
+    xs = [1]
+    for x in xs:
+        xs.append(x)
+test()
+"#,
+        "mutate an iterable for an iterator while iterating",
+    );
+    a.fail(
+        r#"
+d = {1: 2}
+for_each(d, lambda k: d.pop(k))
+"#,
+        "mutate an iterable for an iterator while iterating",
+    );
+}
+
+#[test]
+fn test_alloc_callable() {
+    // State shared with the closure does not need to be `Send` or `Sync`.
+    let calls = Rc::new(RefCell::new(Vec::new()));
+
+    let module = Module::new();
+    let log = calls.clone();
+    let key = module
+        .heap()
+        .alloc_callable_no_freeze("key", move |eval, args| {
+            args.no_named_args()?;
+            let x = args.positional1(eval.heap())?;
+            log.borrow_mut().push(x.to_repr());
+            Ok(eval.heap().alloc(-x.unpack_i32().unwrap_or_default()))
+        });
+    module.set("key", key);
+    let fails = module.heap().alloc_callable("fails", |_eval, _args| {
+        Err(crate::Error::new_other(anyhow::anyhow!("bad callback")))
+    });
+    module.set("fails", fails);
+
+    let mut eval = Evaluator::new(&module);
+    let ast = AstModule::parse(
+        "callable.star",
+        r#"
+def apply(f, xs):
+    return [f(x) for x in xs]
+res = (apply(key, [1, 2]), sorted([2, 3, 1], key = key), repr(key), type(key))
+"#
+        .to_owned(),
+        &Dialect::Standard,
+    )
+    .unwrap();
+    eval.eval_module(ast, &Globals::standard()).unwrap();
+    assert_eq!(
+        r#"([-1, -2], [3, 2, 1], "key", "function")"#,
+        module.get("res").unwrap().to_repr()
+    );
+    assert_eq!(vec!["1", "2", "2", "3", "1"], *calls.borrow());
+
+    let ast = AstModule::parse(
+        "fails.star",
+        "def run():\n    fails()\nrun()".to_owned(),
+        &Dialect::Standard,
+    )
+    .unwrap();
+    let e = eval.eval_module(ast, &Globals::standard()).unwrap_err();
+    assert!(e.to_string().contains("bad callback"), "{e}");
+    assert_eq!(
+        vec!["run", "fails"],
+        e.call_stack()
+            .frames
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>()
+    );
+    drop(eval);
+
+    // A closure which is not `Send` or `Sync` cannot be frozen.
+    let e = module.freeze().err().unwrap();
+    assert!(
+        format!("{e:#}").contains("Failed to freeze module variable `key`"),
+        "{e:#}"
+    );
+}
+
+#[test]
+fn test_alloc_callable_freeze() {
+    let module = Module::new();
+    let twice = module.heap().alloc_callable("twice", |eval, args| {
+        let x = args.positional1(eval.heap())?;
+        x.add(x, eval.heap())
+    });
+    module.set("twice", twice);
+    let frozen = module.freeze().unwrap();
+
+    let module = Module::new();
+    let mut eval = Evaluator::new(&module);
+    let twice = frozen
+        .get("twice")
+        .unwrap()
+        .owned_value(module.frozen_heap());
+    let res = eval
+        .eval_function(twice, &[module.heap().alloc("ab")], &[])
+        .unwrap();
+    assert_eq!(res.unpack_str(), Some("abab"));
+}
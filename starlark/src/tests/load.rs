@@ -0,0 +1,305 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Tests of `load` statements.
+
+use std::collections::HashMap;
+
+use crate::assert::Assert;
+use crate::environment::FrozenModule;
+use crate::environment::Globals;
+use crate::environment::Module;
+use crate::eval::Evaluator;
+use crate::eval::FileLoader;
+use crate::eval::LoadContext;
+use crate::eval::ReturnFileLoader;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+
+/// Loader which evaluates each loaded module when it is loaded, with itself as the loader.
+struct RecursiveLoader {
+    sources: HashMap<&'static str, &'static str>,
+}
+
+impl RecursiveLoader {
+    fn eval(&self, path: &str) -> crate::Result<FrozenModule> {
+        self.eval_with_context(path, &LoadContext::default())
+    }
+
+    fn eval_with_context(&self, path: &str, context: &LoadContext) -> crate::Result<FrozenModule> {
+        let source = self.sources[path];
+        let ast = AstModule::parse(path, source.to_owned(), &Dialect::Standard)?;
+        let module = Module::new();
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.set_loader(self);
+            eval.set_load_context(context);
+            eval.eval_module(ast, &Globals::standard())?;
+        }
+        Ok(module.freeze()?)
+    }
+}
+
+impl FileLoader for RecursiveLoader {
+    fn load(&self, path: &str) -> anyhow::Result<FrozenModule> {
+        self.load_with_context(path, &LoadContext::default())
+    }
+
+    fn load_with_context(&self, path: &str, context: &LoadContext) -> anyhow::Result<FrozenModule> {
+        self.eval_with_context(path, context)
+            .map_err(|e| e.into_anyhow())
+    }
+}
+
+#[test]
+fn test_load_diamond() {
+    let loader = RecursiveLoader {
+        sources: HashMap::from([
+            (
+                "root.star",
+                "load('a.star', 'a')\nload('b.star', 'b')\nx = a + b",
+            ),
+            ("a.star", "load('c.star', 'c')\na = c + 1"),
+            ("b.star", "load('c.star', 'c')\nb = c + 2"),
+            ("c.star", "c = 10"),
+        ]),
+    };
+    let root = loader.eval("root.star").unwrap();
+    assert_eq!(Some(23), root.get("x").unwrap().unpack_i32());
+}
+
+#[test]
+fn test_load_cycle() {
+    let loader = RecursiveLoader {
+        sources: HashMap::from([
+            ("root.star", "load('a.star', 'a')"),
+            ("a.star", "load('b.star', 'b')\na = 1"),
+            ("b.star", "load('c.star', 'c')\nb = 1"),
+            ("c.star", "load('a.star', 'a')\nc = 1"),
+        ]),
+    };
+    let err = loader.eval("root.star").unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Cyclic load: `a.star` -> `b.star` -> `c.star` -> `a.star`"),
+        "{err:#}"
+    );
+
+    // The load stack is cleared after the error, so other loads still work.
+    let loader = RecursiveLoader {
+        sources: HashMap::from([("root.star", "load('a.star', 'a')"), ("a.star", "a = 1")]),
+    };
+    loader.eval("root.star").unwrap();
+}
+
+#[test]
+fn test_load_self() {
+    let loader = RecursiveLoader {
+        sources: HashMap::from([
+            ("root.star", "load('a.star', 'a')"),
+            ("a.star", "load('a.star', 'a')"),
+        ]),
+    };
+    let err = loader.eval("root.star").unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Cyclic load: `a.star` -> `a.star`"),
+        "{err:#}"
+    );
+}
+
+/// Loader which resolves load paths relative to the directory of the loading file.
+struct RelativeLoader<'a> {
+    sources: &'a HashMap<&'static str, &'static str>,
+    dir: &'a str,
+}
+
+impl RelativeLoader<'_> {
+    fn eval(&self, path: &str) -> crate::Result<FrozenModule> {
+        self.eval_with_context(path, &LoadContext::default())
+    }
+
+    fn eval_with_context(&self, path: &str, context: &LoadContext) -> crate::Result<FrozenModule> {
+        let source = self.sources[path];
+        let ast = AstModule::parse(path, source.to_owned(), &Dialect::Standard)?;
+        let loader = RelativeLoader {
+            sources: self.sources,
+            dir: path.rsplit_once('/').map_or("", |(dir, _)| dir),
+        };
+        let module = Module::new();
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.set_loader(&loader);
+            eval.set_load_context(context);
+            eval.eval_module(ast, &Globals::standard())?;
+        }
+        Ok(module.freeze()?)
+    }
+}
+
+impl FileLoader for RelativeLoader<'_> {
+    fn load(&self, path: &str) -> anyhow::Result<FrozenModule> {
+        self.load_with_context(path, &LoadContext::default())
+    }
+
+    fn load_with_context(&self, path: &str, context: &LoadContext) -> anyhow::Result<FrozenModule> {
+        self.eval_with_context(&self.resolve(path)?, context)
+            .map_err(|e| e.into_anyhow())
+    }
+
+    fn resolve(&self, path: &str) -> anyhow::Result<String> {
+        if self.dir.is_empty() {
+            Ok(path.to_owned())
+        } else {
+            Ok(format!("{}/{}", self.dir, path))
+        }
+    }
+}
+
+#[test]
+fn test_load_same_relative_path() {
+    // Both files load `lib/x.star`, which are different files.
+    let sources = HashMap::from([
+        ("root.star", "load('lib/x.star', 'x')"),
+        ("lib/x.star", "load('lib/x.star', 'y')\nx = y + 1"),
+        ("lib/lib/x.star", "y = 1"),
+    ]);
+    let loader = RelativeLoader {
+        sources: &sources,
+        dir: "",
+    };
+    let root = loader.eval("root.star").unwrap();
+    assert_eq!(Some(2), root.get("x").unwrap().unpack_i32());
+
+    // A real cycle is reported with the resolved paths.
+    let sources = HashMap::from([
+        ("root.star", "load('lib/x.star', 'x')"),
+        ("lib/x.star", "load('y.star', 'y')\nx = 1"),
+        ("lib/y.star", "load('x.star', 'x')\ny = 1"),
+    ]);
+    let loader = RelativeLoader {
+        sources: &sources,
+        dir: "",
+    };
+    let err = loader.eval("root.star").unwrap_err();
+    assert!(
+        format!("{err:#}").contains("Cyclic load: `lib/x.star` -> `lib/y.star` -> `lib/x.star`"),
+        "{err:#}"
+    );
+}
+
+#[test]
+fn test_load_frozen_values_are_not_copied() {
+    let dep = Module::new();
+    {
+        let mut eval = Evaluator::new(&dep);
+        let ast = AstModule::parse(
+            "dep.star",
+            "d = {'a': [1, 2], 'b': [3]}".to_owned(),
+            &Dialect::Standard,
+        )
+        .unwrap();
+        eval.eval_module(ast, &Globals::standard()).unwrap();
+    }
+    let dep = dep.freeze().unwrap();
+    let modules = HashMap::from([("dep.star", &dep)]);
+    let loader = ReturnFileLoader { modules: &modules };
+
+    let module = Module::new();
+    let mut eval = Evaluator::new(&module);
+    eval.set_loader(&loader);
+    let ast = AstModule::parse(
+        "root.star",
+        "load('dep.star', 'd')\nn = len(d['a']) + len(d['b']) + d['a'][1]\nd".to_owned(),
+        &Dialect::Standard,
+    )
+    .unwrap();
+    let res = eval.eval_module(ast, &Globals::standard()).unwrap();
+    // The mutable module sees the frozen dict itself, not a copy.
+    assert!(res.ptr_eq(dep.get("d").unwrap().value()));
+    assert_eq!(Some(5), module.get("n").unwrap().unpack_i32());
+
+    let ast = AstModule::parse(
+        "mutate.star",
+        "load('dep.star', 'd')\nd['a'].append(3)".to_owned(),
+        &Dialect::Standard,
+    )
+    .unwrap();
+    let err = eval.eval_module(ast, &Globals::standard()).unwrap_err();
+    let err = format!("{err}");
+    assert!(
+        err.contains("Cannot mutate frozen value of type `list`"),
+        "{err}"
+    );
+    assert!(err.contains("mutate.star:2:1"), "{err}");
+}
+
+#[test]
+fn test_load_fail_reports_load_chain() {
+    let loader = RecursiveLoader {
+        sources: HashMap::from([
+            ("root.star", "load('a.star', 'a')"),
+            ("a.star", "load('b.star', 'b')\na = b"),
+            ("b.star", "fail('broken', 'b')"),
+        ]),
+    };
+    let err = loader.eval("root.star").unwrap_err();
+    let err = format!("{err}");
+    // The failure is shown with each `load` leading to it.
+    assert!(err.contains("fail: broken b\n --> b.star:1:1"), "{err}");
+    assert!(err.contains(" --> a.star:1:1"), "{err}");
+    assert!(err.contains(" --> root.star:1:1"), "{err}");
+}
+
+#[test]
+fn test_assert_with_loader() {
+    let loader = RecursiveLoader {
+        sources: HashMap::from([("c.star", "c = 10")]),
+    };
+    let mut a = Assert::new().with_loader(&loader);
+    a.module("local.star", "d = 1");
+    a.eq("load('c.star', 'c'); load('local.star', 'd'); c + d", "11");
+}
+
+#[test]
+fn test_load_private_symbol() {
+    let loader = RecursiveLoader {
+        sources: HashMap::from([
+            ("root.star", "load('lib.star', 'helper')\nx = helper()"),
+            ("aliased.star", "load('lib.star', h = '_helper')"),
+            (
+                "lib.star",
+                "def _helper(): return 1\ndef helper(): return _helper()",
+            ),
+        ]),
+    };
+    let root = loader.eval("root.star").unwrap();
+    assert_eq!(Some(1), root.get("x").unwrap().unpack_i32());
+
+    let err = format!("{}", loader.eval("aliased.star").unwrap_err());
+    assert!(
+        err.contains("Symbol `_helper` is private to `lib.star`"),
+        "{err}"
+    );
+    assert!(err.contains("aliased.star:1:18"), "{err}");
+
+    // From Rust, private symbols are still available with `get_any`.
+    let lib = loader.eval("lib.star").unwrap();
+    assert!(lib.get("_helper").is_err());
+    assert_eq!(
+        lib.get_any("_helper").unwrap().value().get_type(),
+        "function"
+    );
+}
@@ -16,6 +16,12 @@
  */
 
 use crate::assert::Assert;
+use crate::environment::Globals;
+use crate::environment::Module;
+use crate::eval::compiler::def::FrozenDef;
+use crate::eval::Evaluator;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
 use crate::tests::bc::golden::bc_golden_test;
 
 #[test]
@@ -41,3 +47,69 @@ fn test_fold_list_add_too_large() {
         );
     }
 }
+
+#[test]
+fn test_fold_in_dict() {
+    bc_golden_test(
+        "constant_folding_in_dict",
+        "def test(x): return x in {'a': 1, 'b': 2}",
+    );
+}
+
+#[test]
+fn test_fold_errors_only_when_executed() {
+    let mut a = Assert::new();
+    a.module(
+        "m.star",
+        r#"
+def day(fail_now):
+    if fail_now:
+        return 1 // 0
+    return 3600 * 24
+
+def unhashable(x):
+    return x in {[]: 1}
+
+def duplicate(x):
+    return x in {"a": 1, "a": 2}
+"#,
+    );
+    a.eq("86400", "load('m.star', 'day'); day(False)");
+    a.fail("load('m.star', 'day'); day(True)", "division by zero");
+    a.fail(
+        "load('m.star', 'unhashable'); unhashable(1)",
+        "not hashable",
+    );
+    a.fail("load('m.star', 'duplicate'); duplicate(1)", "key repeated");
+}
+
+#[test]
+fn test_fold_disabled() {
+    fn day_bytecode(constant_folding: bool) -> String {
+        let module = Module::new();
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.enable_constant_folding(constant_folding);
+            let ast = AstModule::parse(
+                "m.star",
+                "def test(): return 3600 * 24 if 'x' in ('x', 'y') else '-'.join(['a', 'b'])"
+                    .to_owned(),
+                &Dialect::Standard,
+            )
+            .unwrap();
+            eval.eval_module(ast, &Globals::standard()).unwrap();
+        }
+        let module = module.freeze().unwrap();
+        let test = module.get("test").unwrap();
+        let def = test.downcast::<FrozenDef>().unwrap();
+        def.bc().dump_debug()
+    }
+
+    let folded = day_bytecode(true);
+    assert!(folded.contains("86400"), "{folded}");
+    assert!(!folded.contains("Mul"), "{folded}");
+    let not_folded = day_bytecode(false);
+    assert!(!not_folded.contains("86400"), "{not_folded}");
+    assert!(not_folded.contains("Mul"), "{not_folded}");
+    assert!(not_folded.contains("join"), "{not_folded}");
+}
@@ -17,6 +17,7 @@
 
 //! Test of runtime.
 
+use std::collections::HashMap;
 use std::fmt::Write;
 use std::mem;
 use std::sync::atomic::AtomicUsize;
@@ -29,10 +30,17 @@ use starlark_derive::starlark_module;
 use crate as starlark;
 use crate::assert;
 use crate::assert::Assert;
+use crate::environment::Globals;
 use crate::environment::GlobalsBuilder;
+use crate::environment::Module;
 use crate::eval::Evaluator;
+use crate::eval::ReturnFileLoader;
+use crate::eval::SoftErrorHandler;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
 use crate::values::any::StarlarkAny;
 use crate::values::FrozenHeap;
+use crate::values::GcStats;
 use crate::values::Heap;
 
 #[test]
@@ -233,3 +241,349 @@ fn test_display_debug() {
     assert_eq!(format!("{:?}", v), "FrozenValue(\"test\")");
     assert_eq!(format!("{:#?}", v), "FrozenValue(\n    \"test\",\n)");
 }
+
+#[test]
+fn test_error_call_stack_frames() {
+    let err = assert::fail(
+        r#"
+def inner(x):
+    return x + 1
+
+def outer():
+    return inner("a")
+
+outer()
+"#,
+        "not supported",
+    );
+    let frames: Vec<(&str, String)> = err
+        .call_stack()
+        .frames
+        .iter()
+        .map(|f| {
+            (
+                f.name.as_str(),
+                f.location.as_ref().unwrap().resolve().to_string(),
+            )
+        })
+        .collect();
+    // Each frame records where the function was called from,
+    // and the innermost position is the span of the error itself.
+    assert_eq!(
+        vec![
+            ("outer", "assert.bzl:8:1-8".to_owned()),
+            ("inner", "assert.bzl:6:12-22".to_owned()),
+        ],
+        frames
+    );
+    assert_eq!(
+        Some("assert.bzl:3:16-17".to_owned()),
+        err.span().map(|s| s.resolve().to_string())
+    );
+}
+
+#[test]
+fn test_error_span_is_failing_operation() {
+    fn span(program: &str, msg: &str) -> String {
+        let err = assert::fail(program, msg);
+        err.span().unwrap().resolve().to_string()
+    }
+
+    let s = "s = struct(b = [struct(d = 1)])\n";
+    // The index operation, not the whole expression.
+    assert_eq!(
+        "assert.bzl:2:12-15",
+        span(&format!("{s}x = 1 + s.b[5].d"), "out of bound")
+    );
+    // The attribute name, not the object it is looked up on.
+    assert_eq!(
+        "assert.bzl:2:15-17",
+        span(&format!("{s}x = 1 + s.b[0].e"), "has no attribute")
+    );
+    assert_eq!(
+        "assert.bzl:2:13-19",
+        span(&format!("{s}x = s.b[0].d[1, 2]"), "not supported")
+    );
+    // The operand of the innermost failing binary operation which has the wrong type.
+    assert_eq!(
+        "assert.bzl:1:14-17",
+        span("y = 2 + (1 + 'a')", "not supported")
+    );
+    // The left operand when its type does not implement the operator at all.
+    assert_eq!("assert.bzl:1:5-9", span("y = None + 1", "not supported"));
+    assert_eq!("assert.bzl:1:10-11", span("y = 1 in 2", "not supported"));
+    assert_eq!("assert.bzl:1:10-11", span("y = 1 // 0", "by zero"));
+
+    let f = "def f(a, b: int):\n    pass\n";
+    // The argument which does not match the parameter type.
+    assert_eq!(
+        "assert.bzl:3:6-9",
+        span(
+            &format!("{f}f(1, 'x')"),
+            "does not match the type annotation"
+        )
+    );
+    assert_eq!(
+        "assert.bzl:3:10-13",
+        span(
+            &format!("{f}f(1, b = 'x')"),
+            "does not match the type annotation"
+        )
+    );
+    // The argument which the function does not accept.
+    assert_eq!(
+        "assert.bzl:3:13-14",
+        span(&format!("{f}f(1, 2, c = 3)"), "extra named")
+    );
+    assert_eq!(
+        "assert.bzl:3:17-18",
+        span(&format!("{f}f(1, b = 2, a = 3)"), "occurs more than once")
+    );
+    assert_eq!(
+        "assert.bzl:3:5-6",
+        span(&format!("{f}f(**1)"), "not a dictionary")
+    );
+    // A function called by a native function is not called with these arguments.
+    assert_eq!(
+        "assert.bzl:3:1-23",
+        span(
+            "def k(x: int):\n    pass\nsorted(['a'], key = k)",
+            "does not match the type annotation"
+        )
+    );
+}
+
+#[test]
+fn test_max_heap_bytes() {
+    fn run(max_bytes: usize, n: usize) -> crate::Result<()> {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_max_heap_bytes(max_bytes);
+        let program = format!(
+            r#"
+def grow(n):
+    xs = []
+    for i in range(n):
+        xs.append(str(i) * 10)
+    return len(xs)
+grow({n})
+"#
+        );
+        let ast = AstModule::parse("heap.star", program, &Dialect::Standard)?;
+        eval.eval_module(ast, &Globals::standard())?;
+        Ok(())
+    }
+
+    run(1_000_000, 10).unwrap();
+    let err = run(1_000_000, 1_000_000).unwrap_err().to_string();
+    assert!(
+        err.contains("Heap limit of 1000000 bytes exceeded"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_max_heap_bytes_within_statement() {
+    // A single comprehension without function calls is stopped part way.
+    let module = Module::new();
+    let mut eval = Evaluator::new(&module);
+    eval.set_max_heap_bytes(1_000_000);
+    let ast = AstModule::parse(
+        "heap.star",
+        "x = [(i, i) for i in range(10000000)]\n".to_owned(),
+        &Dialect::Standard,
+    )
+    .unwrap();
+    let err = eval
+        .eval_module(ast, &Globals::standard())
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Heap limit of 1000000 bytes exceeded"), "{err}");
+    assert!(module.heap().allocated_bytes() < 10_000_000);
+}
+
+#[test]
+fn test_gc_stats() {
+    fn run(configure: impl FnOnce(&mut Evaluator)) -> GcStats {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        configure(&mut eval);
+        let ast = AstModule::parse(
+            "gc.star",
+            r#"
+def garbage():
+    return [str(i) for i in range(10000)]
+x = garbage()
+x = garbage()
+x = garbage()
+x = garbage()
+"#
+            .to_owned(),
+            &Dialect::Standard,
+        )
+        .unwrap();
+        eval.eval_module(ast, &Globals::standard()).unwrap();
+        module.heap().gc_stats()
+    }
+
+    let stats = run(|_| {});
+    assert!(stats.collections > 0, "{stats:?}");
+    assert!(stats.bytes_after < stats.bytes_before, "{stats:?}");
+
+    assert_eq!(GcStats::default(), run(|eval| eval.disable_gc()));
+
+    // A threshold larger than everything allocated means no collection.
+    let stats = run(|eval| eval.set_gc_threshold(1 << 30));
+    assert_eq!(0, stats.collections);
+
+    // Disabled GC still lets the heap limit stop the evaluation.
+    let module = Module::new();
+    let mut eval = Evaluator::new(&module);
+    eval.disable_gc();
+    eval.set_max_heap_bytes(100_000);
+    let ast = AstModule::parse(
+        "gc.star",
+        "x = [str(i) for i in range(10000)]\ny = 1\n".to_owned(),
+        &Dialect::Standard,
+    )
+    .unwrap();
+    let err = eval
+        .eval_module(ast, &Globals::standard())
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("Heap limit"), "{err}");
+}
+
+#[test]
+fn test_eval_module_ret() {
+    let module = Module::new();
+    let mut eval = Evaluator::new(&module);
+    let ast = AstModule::parse("x.star", "x = 1\nx + 1".to_owned(), &Dialect::Standard).unwrap();
+    let res = eval.eval_module_ret(ast, &Globals::standard()).unwrap();
+    assert_eq!(Some(2), res.value.unpack_i32());
+    assert_eq!(Some(1), res.module.get("x").and_then(|x| x.unpack_i32()));
+
+    assert!(res.soft_errors.is_empty());
+
+    // Errors are reported as usual.
+    let ast = AstModule::parse("y.star", "fail('oops')".to_owned(), &Dialect::Standard).unwrap();
+    let err = eval.eval_module_ret(ast, &Globals::standard()).unwrap_err();
+    assert!(err.to_string().contains("oops"), "{err}");
+}
+
+#[test]
+fn test_eval_module_ret_soft_errors() {
+    #[starlark_module]
+    fn deprecated(builder: &mut GlobalsBuilder) {
+        fn old_api(eval: &mut Evaluator) -> starlark::Result<i32> {
+            eval.soft_error(
+                "deprecated",
+                crate::Error::new_other(anyhow::anyhow!("`old_api` is deprecated")),
+            )?;
+            Ok(1)
+        }
+    }
+
+    struct Accept;
+
+    impl SoftErrorHandler for Accept {
+        fn soft_error(&self, _category: &str, _error: crate::Error) -> crate::Result<()> {
+            Ok(())
+        }
+    }
+
+    let globals = GlobalsBuilder::standard().with(deprecated).build();
+    let program = "x = 1\ny = old_api()\n";
+    let module = Module::new();
+    let mut eval = Evaluator::new(&module);
+    eval.set_soft_error_handler(&Accept);
+    let ast = AstModule::parse("x.star", program.to_owned(), &Dialect::Standard).unwrap();
+    let res = eval.eval_module_ret(ast, &globals).unwrap();
+    assert_eq!(Some(1), res.module.get("y").and_then(|y| y.unpack_i32()));
+    assert_eq!(1, res.soft_errors.len());
+    let soft_error = &res.soft_errors[0];
+    assert_eq!("deprecated", soft_error.category);
+    assert_eq!("`old_api` is deprecated", soft_error.message);
+    assert_eq!(
+        "x.star:2:5-14",
+        soft_error.span.as_ref().unwrap().to_string()
+    );
+
+    // The default handler turns soft errors into errors.
+    let module = Module::new();
+    let mut eval = Evaluator::new(&module);
+    let ast = AstModule::parse("x.star", program.to_owned(), &Dialect::Standard).unwrap();
+    let err = eval.eval_module_ret(ast, &globals).unwrap_err();
+    assert!(err.to_string().contains("`old_api` is deprecated"), "{err}");
+}
+
+#[test]
+fn test_eval_module_exports() {
+    let dep = Module::new();
+    dep.set("shared", dep.heap().alloc(7));
+    let dep = dep.freeze().unwrap();
+    let modules = HashMap::from([("dep.star", &dep)]);
+    let loader = ReturnFileLoader { modules: &modules };
+
+    let program = r#"
+load("dep.star", "shared")
+def _helper(x):
+    return x * 2
+name = "app"
+ports = [_helper(40)]
+name = name.upper()
+timeout = shared
+"#;
+    let exports = |include_loads| {
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        eval.set_loader(&loader);
+        let ast = AstModule::parse("config.star", program.to_owned(), &Dialect::Standard).unwrap();
+        let res = eval
+            .eval_module_exports(ast, &Globals::standard(), include_loads)
+            .unwrap()
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<_>>();
+        res
+    };
+    assert_eq!(
+        vec!["name=\"APP\"", "ports=[80]", "timeout=7"],
+        exports(false)
+    );
+    assert_eq!(
+        vec!["shared=7", "name=\"APP\"", "ports=[80]", "timeout=7"],
+        exports(true)
+    );
+}
+
+#[test]
+fn test_gc_now() {
+    let module = Module::new();
+    {
+        let mut eval = Evaluator::new(&module);
+        eval.disable_gc();
+        let ast = AstModule::parse(
+            "gc.star",
+            r#"
+def garbage():
+    return [str(i) for i in range(10000)]
+garbage()
+kept = ["a", "b"]
+"#
+            .to_owned(),
+            &Dialect::Standard,
+        )
+        .unwrap();
+        eval.eval_module(ast, &Globals::standard()).unwrap();
+    }
+    assert_eq!(0, module.heap().gc_stats().collections);
+    let before = module.heap().allocated_bytes();
+    // SAFETY: nothing is evaluating, and no values are held outside the module.
+    unsafe { module.gc_now() };
+    let stats = module.heap().gc_stats();
+    assert_eq!(1, stats.collections);
+    assert_eq!(before, stats.bytes_before);
+    assert!(module.heap().allocated_bytes() < before / 2, "{stats:?}");
+    assert_eq!("[\"a\", \"b\"]", module.get("kept").unwrap().to_repr());
+}
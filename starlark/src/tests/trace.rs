@@ -0,0 +1,105 @@
+/*
+ * Copyright 2018 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+
+use crate::environment::Globals;
+use crate::environment::Module;
+use crate::eval::Evaluator;
+use crate::eval::ReturnFileLoader;
+use crate::stdlib::PrintHandler;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
+use crate::values::Value;
+
+#[test]
+fn trace_writer_events() {
+    struct IgnorePrint;
+    impl PrintHandler for IgnorePrint {
+        fn println(&self, _text: &str) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    let lib = Module::new();
+    lib.set("one", Value::testing_new_int(1));
+    let lib = lib.freeze().unwrap();
+    let modules = HashMap::from([("lib.star", &lib)]);
+    let loader = ReturnFileLoader { modules: &modules };
+
+    let module = Module::new();
+    let globals = Globals::extended_internal();
+    let mut trace = Vec::new();
+    let mut evaluator = Evaluator::new(&module);
+    evaluator.set_loader(&loader);
+    evaluator.set_print_handler(&IgnorePrint);
+    evaluator.set_trace_writer(&mut trace);
+
+    let program = "\
+load('lib.star', 'one')
+def inner(x):
+  print('inner', x)
+  return x
+def outer():
+  return inner(one) + inner(*[2])
+outer()
+fail('done')
+";
+    let ast = AstModule::parse("a.star", program.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+    let err = evaluator.eval_module(ast, &globals).unwrap_err();
+    assert!(err.to_string().contains("fail: done"), "{err}");
+    drop(evaluator);
+
+    let events: Vec<String> = String::from_utf8(trace)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let event: serde_json::Value = serde_json::from_str(line).unwrap();
+            let detail = ["path", "module", "function", "message"]
+                .iter()
+                .find_map(|k| event[k].as_str())
+                .unwrap();
+            match event["args"].as_u64() {
+                Some(args) => format!("{} {} {}", event["event"].as_str().unwrap(), detail, args),
+                None => format!("{} {}", event["event"].as_str().unwrap(), detail),
+            }
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            "module_start a.star",
+            "load lib.star",
+            "call outer 0",
+            "call inner 1",
+            "call print 2",
+            "print inner 1",
+            "return print",
+            "return inner",
+            "call inner 1",
+            "call print 2",
+            "print inner 2",
+            "return print",
+            "return inner",
+            "return outer",
+            "call fail 1",
+            "fail done",
+            "return fail",
+            "module_end a.star",
+        ],
+        events
+    );
+}
@@ -19,6 +19,7 @@ use crate::assert;
 use crate::assert::Assert;
 use crate::syntax::Dialect;
 use crate::syntax::DialectTypes;
+use crate::typing::TypecheckErrors;
 
 const PROGRAM: &str = "\
 def f(x: int): pass
@@ -112,6 +113,53 @@ def g(): f("")
     );
 }
 
+#[test]
+fn test_typecheck_reports_all_errors() {
+    let err = assert::fail(
+        r#"
+def f() -> int:
+    return "x"
+def g():
+    y: str = 1
+def h():
+    # Never called, still checked.
+    f(True, 2)
+"#,
+        "Static typechecking found 3 errors",
+    );
+    assert_eq!("assert.bzl:3:5-15", err.span().unwrap().to_string());
+    let crate::ErrorKind::Other(e) = err.kind() else {
+        panic!("expected other error, got {err:?}");
+    };
+    let errors = e.downcast_ref::<TypecheckErrors>().unwrap().errors();
+    let errors = errors
+        .iter()
+        .map(|e| {
+            (
+                e.span().unwrap().to_string(),
+                e.without_diagnostic().to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (
+                "assert.bzl:3:5-15".to_owned(),
+                "Expected type `int` but got `str`".to_owned()
+            ),
+            (
+                "assert.bzl:5:8-11".to_owned(),
+                "Expected type `str` but got `int`".to_owned()
+            ),
+            (
+                "assert.bzl:8:7-11".to_owned(),
+                "Too many positional arguments".to_owned()
+            ),
+        ],
+        errors
+    );
+}
+
 #[test]
 fn test_string_lit_as_type() {
     assert::fail(
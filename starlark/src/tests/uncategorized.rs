@@ -33,10 +33,12 @@ use crate::collections::SmallMap;
 use crate::environment::Globals;
 use crate::environment::GlobalsBuilder;
 use crate::environment::Module;
+use crate::errors::DiagnosticOptions;
 use crate::eval::Evaluator;
 use crate::starlark_simple_value;
 use crate::syntax::AstModule;
 use crate::syntax::Dialect;
+use crate::tests::util::test_ints_globals;
 use crate::tests::util::trim_rust_backtrace;
 use crate::values::list_or_tuple::UnpackListOrTuple;
 use crate::values::none::NoneType;
@@ -91,6 +93,28 @@ fn test_tabs_fail() {
     a.fail("def f():\n x\t=3", "Parse error");
 }
 
+#[test]
+fn test_parse_fail() {
+    assert::parse_fail("def f():\n\tpass", &Dialect::Extended, "Parse error");
+    assert::parse_fail(
+        "for x in []:\n  pass",
+        &Dialect::Standard,
+        "`for` cannot be used outside `def`",
+    );
+}
+
+#[test]
+#[should_panic(expected = "failed with the wrong message")]
+fn test_parse_fail_wrong_message() {
+    assert::parse_fail("def f(:\n  pass", &Dialect::Extended, "Unexpected `for`");
+}
+
+#[test]
+#[should_panic(expected = "didn't fail")]
+fn test_parse_fail_parses() {
+    assert::parse_fail("for x in []:\n  pass", &Dialect::Extended, "");
+}
+
 #[test]
 fn test_top_level_statements() {
     assert::pass(
@@ -282,6 +306,61 @@ xs[1] += 1
     );
 }
 
+#[test]
+fn test_add_assign_subscript() {
+    assert::pass(
+        r#"
+counts = {"a": 1}
+counts["a"] += 1
+acc = {"list": [1]}
+acc["list"] += [2]
+l = [1, 2, 3]
+l[-1] *= 2
+assert_eq(counts, {"a": 2})
+assert_eq(acc, {"list": [1, 2]})
+assert_eq(l, [1, 2, 6])
+"#,
+    );
+    // Missing key fails the same way as `d[k] = d[k] + 1`.
+    assert::fail(
+        r#"
+d = {}
+d["k"] = d["k"] + 1
+"#,
+        "Key `\"k\"` was not found",
+    );
+    assert::fail(
+        r#"
+d = {}
+d["k"] += 1
+"#,
+        "Key `\"k\"` was not found",
+    );
+    assert::fail(
+        r#"
+l = [1]
+l[1] *= 2
+"#,
+        "out of bound",
+    );
+    let mut a = Assert::new();
+    a.module("frozen.star", "d = {'k': 1}\nl = [1]");
+    a.fail(
+        r#"
+load("frozen.star", "d")
+d["k"] += 1
+"#,
+        "Cannot mutate frozen value",
+    );
+    a.fail(
+        r#"
+load("frozen.star", "l")
+l[0] *= 2
+"#,
+        "Cannot mutate frozen value",
+    );
+}
+
 #[test]
 fn test_radd() {
     // We want select append to always produce a select, much like the
@@ -358,6 +437,73 @@ assert_eq(repr(s2), "$[2, 1, 3]")
     );
 }
 
+#[test]
+fn test_operator_hooks() {
+    // `ints` implements every operator hook, with the reflected `radd` and
+    // `rmul` used when the left operand declines.
+    let mut a = Assert::new();
+    a.globals_add(test_ints_globals);
+    a.pass(
+        r#"
+v = ints(1, 2)
+assert_eq(v, ints(1, 2))
+assert_true(v != ints(1, 3))
+assert_true(v < ints(1, 10))
+assert_true(ints(2, 0) > v)
+assert_eq(max([ints(1, 5), v, ints(0, 9)]), ints(1, 5))
+assert_eq(sorted([ints(1, 5), v]), [v, ints(1, 5)])
+assert_true(2 in v)
+assert_true(3 not in v)
+assert_true('x' not in v)
+assert_eq(+v, v)
+assert_eq(-v, ints(-1, -2))
+assert_eq(~v, ints(-2, -3))
+assert_eq(v + 3, ints(1, 2, 3))
+assert_eq(v + ints(3), ints(1, 2, 3))
+assert_eq(3 + v, ints(3, 1, 2))
+assert_eq(v - 1, ints(0, 1))
+assert_eq(v * 2, ints(2, 4))
+assert_eq(2 * v, ints(2, 4))
+assert_eq(ints(5, 7) % 3, ints(2, 1))
+assert_eq(ints(5, 7) // 2, ints(2, 3))
+assert_eq(ints(6, 3) & ints(3, 1), ints(2, 1))
+assert_eq(ints(4, 1) | ints(1, 2), ints(5, 3))
+assert_eq(ints(6, 3) ^ ints(3, 1), ints(5, 2))
+assert_eq(v << 2, ints(4, 8))
+assert_eq(ints(4, 8) >> 2, v)
+"#,
+    );
+    a.fail(
+        "ints(1, 2) < 3",
+        "Operation `compare` not supported for types `ints` and `int`",
+    );
+    a.fail(
+        "3 < ints(1, 2)",
+        "Operation `compare` not supported for types `int` and `ints`",
+    );
+    a.fail(
+        "ints(1, 2) + []",
+        "Operation `+` not supported for types `ints` and `list`",
+    );
+    a.fail(
+        "[] + ints(1, 2)",
+        "Operation `+` not supported for types `list` and `ints`",
+    );
+    a.fail(
+        "1 - ints(1, 2)",
+        "Operation `-` not supported for types `int` and `ints`",
+    );
+    a.fail(
+        "ints(1, 2) / 2",
+        "Operation `/` not supported for types `ints` and `int`",
+    );
+    a.fail(
+        "ints(1, 2) & ints(1)",
+        "Operation `&` not supported for types `ints` and `ints`",
+    );
+    a.fail("-[]", "Operation `-` not supported on type `list`");
+}
+
 #[test]
 fn test_compound_assignment() {
     assert::pass(
@@ -472,6 +618,37 @@ should_fail()"#,
     );
 }
 
+#[test]
+fn test_diagnostics_display_options() {
+    let err = assert::fail(
+        r#"
+def f(x):
+    a = 1
+    b = 2
+    return x // 0
+c = 3
+d = 4
+e = 5
+f(1)"#,
+        "division by zero",
+    );
+
+    golden_test_template(
+        "src/tests/uncategorized_diagnostics_display_options.golden",
+        &err.display_with(DiagnosticOptions {
+            context_lines: 2,
+            call_stack: false,
+            ..DiagnosticOptions::default()
+        })
+        .to_string(),
+    );
+
+    assert_eq!(
+        err.to_string(),
+        err.display_with(DiagnosticOptions::default()).to_string()
+    );
+}
+
 #[test]
 // Check that errors print out "nicely"
 fn test_error_display() {
@@ -497,7 +674,7 @@ load('imported', 'add2')
 def add3(z):
     add2(z)
 add3(8)"#,
-        "Immutable",
+        "Cannot mutate frozen value",
     );
 
     golden_test_template(
@@ -648,6 +825,112 @@ fn test_compr_did_you_mean() {
     );
 }
 
+#[test]
+fn test_builtin_did_you_mean() {
+    assert::fail(
+        "sroted([])",
+        "Variable `sroted` not found, did you mean `sorted`?",
+    );
+}
+
+#[test]
+fn test_undefined_in_dead_code() {
+    // Names are resolved before execution, so a never-taken branch still fails,
+    // while forward references to later top-level bindings are fine.
+    assert::is_true(
+        r#"
+def f():
+    return later
+later = True
+f()
+"#,
+    );
+    assert::fail(
+        r#"
+fail("must not run")
+def f():
+    if False:
+        undefined_name
+"#,
+        "Variable `undefined_name` not found",
+    );
+}
+
+#[test]
+fn test_all_undefined_reported() {
+    let program = r#"
+def f(x):
+    if False:
+        return foo(x)
+    return [y for y in bar]
+def g(nmae):
+    return name
+"#;
+    // Only the first undefined variable by default.
+    let e = assert::fail(program, "Variable `foo` not found");
+    assert!(!e.to_string().contains("bar"), "{e}");
+
+    let mut a = Assert::new();
+    a.setup_eval(|eval| eval.enable_strict_names(true));
+    let e = a.fail(program, "Variables not found:");
+    let e = e.to_string();
+    assert!(
+        e.contains(
+            "Variables not found:\n  \
+            assert.bzl:4:16-19: Variable `foo` not found, did you mean `f`?\n  \
+            assert.bzl:5:24-27: Variable `bar` not found, did you mean `chr`?\n  \
+            assert.bzl:7:12-16: Variable `name` not found, did you mean `nmae`?\n"
+        ),
+        "{e}"
+    );
+
+    // Undefined variables are reported even after another error.
+    let program = r#"
+def g(y):
+    def h(x: y):
+        pass
+def f():
+    return foo + bar
+"#;
+    assert::fail(
+        program,
+        "Identifiers in type expressions can only refer globals or builtins",
+    );
+    let e = a.fail(program, "Variables not found:");
+    let e = e.to_string();
+    assert!(e.contains("Variable `foo` not found"), "{e}");
+    assert!(e.contains("Variable `bar` not found"), "{e}");
+}
+
+#[test]
+fn test_several_did_you_mean() {
+    assert::fail(
+        "attr1 = 1; attr2 = 2; attr3 = 3; attr4 = 4; print(attr)",
+        "Variable `attr` not found, did you mean `attr1`, `attr2` or `attr3`?",
+    );
+    assert::fail(
+        "struct(attr1 = 1, attr2 = 2).attr",
+        "Object of type `struct` has no attribute `attr`, did you mean `attr1` or `attr2`?",
+    );
+}
+
+#[test]
+fn test_named_arg_did_you_mean() {
+    assert::fail(
+        "def f(x, color = 1): pass\nf(1, colour = 2)",
+        "Found `colour` extra named parameter for call to assert.bzl.f, did you mean `color`?",
+    );
+    assert::fail(
+        "sorted([], reversed = True)",
+        "Found `reversed` extra named parameter for call to sorted, did you mean `reverse`?",
+    );
+    // No suggestion if nothing is close.
+    assert::fail(
+        "def f(x): pass\nf(1, y = 2)",
+        "Found `y` extra named parameter(s) for call to",
+    );
+}
+
 #[test]
 fn test_unassigned() {
     assert::fails("y = x; x = 1", &["referenced before assignment", "`x`"]);
@@ -979,3 +1262,27 @@ fn test_fuzzer_59839() {
     let mut eval: Evaluator = Evaluator::new(&module);
     assert!(eval.eval_module(ast, &globals).is_err());
 }
+
+#[test]
+fn test_deterministic_iteration() {
+    assert::deterministic(
+        r#"
+names = ["k{}".format(i) for i in range(50)]
+d = {n: i for i, n in enumerate(reversed(names))}
+keys = list(d)
+items = d.items()
+text = str(d)
+attrs = dir(d)
+sattrs = dir(struct(z = 1, a = 2, m = 3))
+lattrs = dir([])
+(keys, text, attrs)
+"#,
+    );
+    assert::eq("dir(struct(z = 1, a = 2, m = 3))", "['a', 'm', 'z']");
+    assert::eq(
+        "str({'b': 1, 'a': 2, 'c': 3})",
+        "'{\"b\": 1, \"a\": 2, \"c\": 3}'",
+    );
+    assert::eq("list({'b': 1, 'a': 2, 'c': 3})", "['b', 'a', 'c']");
+    assert::eq("dir('')", "sorted(dir(''))");
+}
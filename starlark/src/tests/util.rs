@@ -17,7 +17,16 @@
 
 #![cfg(test)]
 
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Display;
+use std::iter;
+use std::sync::atomic;
+use std::sync::atomic::AtomicUsize;
+
 use allocative::Allocative;
+use itertools::Itertools;
+use starlark_derive::starlark_module;
 use starlark_derive::starlark_value;
 use starlark_derive::Freeze;
 use starlark_derive::NoSerialize;
@@ -25,6 +34,11 @@ use starlark_derive::Trace;
 
 use crate as starlark;
 use crate::any::ProvidesStaticType;
+use crate::environment::GlobalsBuilder;
+use crate::starlark_simple_value;
+use crate::values::apply_slice;
+use crate::values::convert_index;
+use crate::values::tuple::UnpackTuple;
 use crate::values::AllocFrozenValue;
 use crate::values::AllocValue;
 use crate::values::FrozenHeap;
@@ -32,6 +46,7 @@ use crate::values::FrozenValue;
 use crate::values::Heap;
 use crate::values::StarlarkValue;
 use crate::values::Value;
+use crate::values::ValueError;
 use crate::values::ValueLifetimeless;
 use crate::values::ValueLike;
 
@@ -65,6 +80,221 @@ impl AllocFrozenValue for TestComplexValue<FrozenValue> {
     }
 }
 
+/// A sequence of ints, shared by tests of how builtins and operators treat custom values.
+///
+/// Iterates lazily, counting the items pulled, supports indexing, slicing and `in`,
+/// has attributes `first` and `last`, and implements the operators:
+/// `+` appends or prepends an int and concatenates, the other arithmetic
+/// and shift operators apply elementwise with an int, and the bitwise
+/// operators apply elementwise with another sequence of the same length.
+#[derive(Debug, ProvidesStaticType, NoSerialize, Allocative)]
+pub(crate) struct TestInts {
+    pub(crate) items: Vec<i32>,
+    #[allocative(skip)]
+    pulled: AtomicUsize,
+}
+
+starlark_simple_value!(TestInts);
+
+impl TestInts {
+    pub(crate) fn new(items: Vec<i32>) -> TestInts {
+        TestInts {
+            items,
+            pulled: AtomicUsize::new(0),
+        }
+    }
+
+    fn map(&self, f: impl Fn(i32) -> i32) -> TestInts {
+        TestInts::new(self.items.iter().map(|x| f(*x)).collect())
+    }
+
+    fn map_int<'v>(
+        &self,
+        op: &str,
+        other: Value<'v>,
+        heap: &'v Heap,
+        f: impl Fn(i32, i32) -> Option<i32>,
+    ) -> crate::Result<Value<'v>> {
+        let mapped = other.unpack_i32().and_then(|n| {
+            let items = self.items.iter().map(|x| f(*x, n)).collect::<Option<_>>()?;
+            Some(TestInts::new(items))
+        });
+        match mapped {
+            Some(x) => Ok(heap.alloc(x)),
+            None => ValueError::unsupported_with(self, op, other),
+        }
+    }
+
+    fn zip<'v>(
+        &self,
+        op: &str,
+        other: Value<'v>,
+        heap: &'v Heap,
+        f: impl Fn(i32, i32) -> i32,
+    ) -> crate::Result<Value<'v>> {
+        match TestInts::from_value(other) {
+            Some(o) if o.items.len() == self.items.len() => {
+                let items = self.items.iter().zip(&o.items).map(|(x, y)| f(*x, *y));
+                Ok(heap.alloc(TestInts::new(items.collect())))
+            }
+            _ => ValueError::unsupported_with(self, op, other),
+        }
+    }
+}
+
+impl Display for TestInts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ints({})", self.items.iter().join(", "))
+    }
+}
+
+#[starlark_value(type = "ints")]
+impl<'v> StarlarkValue<'v> for TestInts {
+    fn equals(&self, other: Value<'v>) -> crate::Result<bool> {
+        Ok(TestInts::from_value(other).is_some_and(|o| o.items == self.items))
+    }
+
+    fn compare(&self, other: Value<'v>) -> crate::Result<Ordering> {
+        match TestInts::from_value(other) {
+            Some(o) => Ok(self.items.cmp(&o.items)),
+            None => ValueError::unsupported_owned(Self::TYPE, "compare", Some(other.get_type())),
+        }
+    }
+
+    fn length(&self) -> crate::Result<i32> {
+        Ok(self.items.len() as i32)
+    }
+
+    fn at(&self, index: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        let i = convert_index(index, self.items.len() as i32)?;
+        Ok(heap.alloc(self.items[i as usize]))
+    }
+
+    fn slice(
+        &self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        heap: &'v Heap,
+    ) -> crate::Result<Value<'v>> {
+        Ok(heap.alloc(apply_slice(&self.items, start, stop, stride)?))
+    }
+
+    fn is_in(&self, other: Value<'v>) -> crate::Result<bool> {
+        Ok(other.unpack_i32().is_some_and(|i| self.items.contains(&i)))
+    }
+
+    unsafe fn iterate(&self, me: Value<'v>, _heap: &'v Heap) -> crate::Result<Value<'v>> {
+        Ok(me)
+    }
+
+    unsafe fn iter_next(&self, index: usize, heap: &'v Heap) -> Option<Value<'v>> {
+        let item = self.items.get(index)?;
+        self.pulled.fetch_add(1, atomic::Ordering::SeqCst);
+        Some(heap.alloc(*item))
+    }
+
+    unsafe fn iter_stop(&self) {}
+
+    fn get_attr(&self, attribute: &str, heap: &'v Heap) -> Option<Value<'v>> {
+        let item = match attribute {
+            "first" => self.items.first(),
+            "last" => self.items.last(),
+            _ => return None,
+        };
+        Some(item.map_or(Value::new_none(), |x| heap.alloc(*x)))
+    }
+
+    fn dir_attr(&self) -> Vec<String> {
+        vec!["first".to_owned(), "last".to_owned()]
+    }
+
+    fn plus(&self, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        Ok(heap.alloc(self.map(|x| x)))
+    }
+
+    fn minus(&self, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        Ok(heap.alloc(self.map(|x| -x)))
+    }
+
+    fn bit_not(&self, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        Ok(heap.alloc(self.map(|x| !x)))
+    }
+
+    fn add(&self, rhs: Value<'v>, heap: &'v Heap) -> Option<crate::Result<Value<'v>>> {
+        let rhs = match TestInts::from_value(rhs) {
+            Some(rhs) => rhs.items.clone(),
+            None => vec![rhs.unpack_i32()?],
+        };
+        let items = self.items.iter().copied().chain(rhs).collect();
+        Some(Ok(heap.alloc(TestInts::new(items))))
+    }
+
+    fn radd(&self, lhs: Value<'v>, heap: &'v Heap) -> Option<crate::Result<Value<'v>>> {
+        let lhs = lhs.unpack_i32()?;
+        let items = iter::once(lhs).chain(self.items.iter().copied()).collect();
+        Some(Ok(heap.alloc(TestInts::new(items))))
+    }
+
+    fn sub(&self, other: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.map_int("-", other, heap, |x, n| Some(x - n))
+    }
+
+    fn mul(&self, rhs: Value<'v>, heap: &'v Heap) -> Option<crate::Result<Value<'v>>> {
+        let n = rhs.unpack_i32()?;
+        Some(Ok(heap.alloc(self.map(|x| x * n))))
+    }
+
+    fn rmul(&self, lhs: Value<'v>, heap: &'v Heap) -> Option<crate::Result<Value<'v>>> {
+        self.mul(lhs, heap)
+    }
+
+    fn percent(&self, other: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.map_int("%", other, heap, |x, n| x.checked_rem_euclid(n))
+    }
+
+    fn floor_div(&self, other: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.map_int("//", other, heap, |x, n| x.checked_div_euclid(n))
+    }
+
+    fn bit_and(&self, other: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.zip("&", other, heap, |x, y| x & y)
+    }
+
+    fn bit_or(&self, other: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.zip("|", other, heap, |x, y| x | y)
+    }
+
+    fn bit_xor(&self, other: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.zip("^", other, heap, |x, y| x ^ y)
+    }
+
+    fn left_shift(&self, other: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.map_int("<<", other, heap, |x, n| Some(x << n))
+    }
+
+    fn right_shift(&self, other: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.map_int(">>", other, heap, |x, n| Some(x >> n))
+    }
+}
+
+/// Globals to create [`TestInts`]: `ints(*items)`, `countdown(n)` for `n`, ..., `1`,
+/// and `pulled(ints)` for the number of items pulled by iterating.
+#[starlark_module]
+pub(crate) fn test_ints_globals(globals: &mut GlobalsBuilder) {
+    fn ints(#[starlark(args)] items: UnpackTuple<i32>) -> anyhow::Result<TestInts> {
+        Ok(TestInts::new(items.items))
+    }
+
+    fn countdown(n: i32) -> anyhow::Result<TestInts> {
+        Ok(TestInts::new((1..=n).rev().collect()))
+    }
+
+    fn pulled(ints: &TestInts) -> anyhow::Result<i32> {
+        Ok(ints.pulled.load(atomic::Ordering::SeqCst) as i32)
+    }
+}
+
 /// There's no anyhow API to print error without rust backtrace
 /// ([issue](https://github.com/dtolnay/anyhow/issues/300)).
 pub(crate) fn trim_rust_backtrace(error: &str) -> &str {
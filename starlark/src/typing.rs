@@ -55,6 +55,7 @@ pub use basic::TyBasic;
 pub use callable::TyCallable;
 pub use callable_param::ParamIsRequired;
 pub use callable_param::ParamSpec;
+pub use error::TypecheckErrors;
 pub use function::TyFunction;
 pub use interface::Interface;
 pub use oracle::ctx::TypingOracleCtx;
@@ -165,6 +165,12 @@ impl<'a, 'b> BindingsCollect<'a, 'b> {
                     ));
                 }
             },
+            AssignTargetP::Slice(..) => {
+                self.approximations.push(Approximation::new(
+                    "Underapproximation",
+                    "a[b:c] = .. not handled",
+                ));
+            }
             AssignTargetP::Dot(_, _) => {
                 self.approximations.push(Approximation::new(
                     "Underapproximation",
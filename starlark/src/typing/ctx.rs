@@ -236,6 +236,7 @@ impl TypingContext<'_> {
         match &**x {
             AssignTargetP::Tuple(_) => Ok(self.approximation("expression_assignment", x)),
             AssignTargetP::Index(a_b) => self.expr_index(x.span, &a_b.0, &a_b.1),
+            AssignTargetP::Slice(..) => Ok(self.approximation("expression_assignment", x)),
             AssignTargetP::Dot(_, _) => Ok(self.approximation("expression_assignment", x)),
             AssignTargetP::Identifier(x) => {
                 if let Some(i) = x.payload {
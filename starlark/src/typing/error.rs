@@ -113,6 +113,42 @@ impl TypingError {
     }
 }
 
+/// All the errors found by static typechecking of a module,
+/// when there is more than one.
+///
+/// Module evaluation with [`Evaluator::enable_static_typechecking`](crate::eval::Evaluator::enable_static_typechecking)
+/// returns a single error of kind [`ErrorKind::Other`](crate::ErrorKind::Other) wrapping this type,
+/// which can be obtained with `downcast_ref`.
+#[derive(Debug)]
+pub struct TypecheckErrors(Vec<crate::Error>);
+
+impl TypecheckErrors {
+    pub(crate) fn new(errors: Vec<crate::Error>) -> TypecheckErrors {
+        TypecheckErrors(errors)
+    }
+
+    /// The errors, in the order of the functions they were found in.
+    /// Each error keeps its own kind and span.
+    pub fn errors(&self) -> &[crate::Error] {
+        &self.0
+    }
+}
+
+impl Display for TypecheckErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Static typechecking found {} errors:", self.0.len())?;
+        for e in &self.0 {
+            match e.span() {
+                Some(span) => write!(f, "\n  {}: {}", span, e.without_diagnostic())?,
+                None => write!(f, "\n  {}", e.without_diagnostic())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TypecheckErrors {}
+
 /// Like [`TypingError`], but without a message or span.
 pub struct TypingNoContextError;
 
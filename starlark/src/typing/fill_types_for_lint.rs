@@ -353,6 +353,7 @@ impl<'a, 'v> GlobalTypesBuilder<'a, 'v> {
                 Ok(())
             }
             AssignTargetP::Index(_) => Ok(()),
+            AssignTargetP::Slice(..) => Ok(()),
             AssignTargetP::Dot(_, _) => Ok(()),
             AssignTargetP::Identifier(ident) => self.assign_ident_value(ident, rhs),
         }
@@ -388,6 +389,7 @@ impl<'a, 'v> GlobalTypesBuilder<'a, 'v> {
                 Ok(())
             }
             AssignTargetP::Index(..) => Ok(()),
+            AssignTargetP::Slice(..) => Ok(()),
             AssignTargetP::Dot(..) => Ok(()),
             AssignTargetP::Identifier(ident) => self.assign_unset_ident(ident),
         }
@@ -421,6 +423,7 @@ impl<'a, 'v> GlobalTypesBuilder<'a, 'v> {
             StmtP::Expression(_) => Ok(()),
             StmtP::Assign(AssignP { lhs, .. }) => self.assign_unset(lhs),
             StmtP::AssignModify(lhs, _, _) => self.assign_unset(lhs),
+            StmtP::Del(_) => Ok(()),
             StmtP::Statements(xs) => {
                 for x in xs {
                     self.eval_stmt_unset(x)?;
@@ -495,6 +498,7 @@ impl<'a, 'v> GlobalTypesBuilder<'a, 'v> {
             StmtP::Expression(_) => Ok(()),
             StmtP::Assign(assign) => self.assign_stmt(assign),
             StmtP::AssignModify(..) => Ok(()),
+            StmtP::Del(_) => Ok(()),
             StmtP::Statements(_) => {
                 Err(self.internal_error(span, "statements in top-level statement"))
             }
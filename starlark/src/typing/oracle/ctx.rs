@@ -78,6 +78,17 @@ enum TypingOracleCtxError {
     },
 }
 
+/// Required and actual types if the error is a type mismatch found by the typechecker.
+pub(crate) fn incompatible_types(error: &crate::Error) -> Option<(&str, &str)> {
+    match error.kind() {
+        crate::ErrorKind::Other(e) => match e.downcast_ref::<TypingOracleCtxError>()? {
+            TypingOracleCtxError::IncompatibleType { got, require } => Some((require, got)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
 /// Oracle reference with utility methods.
 ///
 /// This type is stateless.
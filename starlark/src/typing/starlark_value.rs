@@ -28,6 +28,7 @@ use allocative::Allocative;
 use dupe::Dupe;
 use starlark_syntax::codemap::Span;
 
+use crate::environment::Methods;
 use crate::typing::error::TypingError;
 use crate::typing::error::TypingNoContextError;
 use crate::typing::ty::TypeRenderConfig;
@@ -256,8 +257,13 @@ impl TyStarlarkValue {
         self.vtable.vtable.HAS_at
     }
 
+    /// Methods of values of this type, if it has any.
+    pub(crate) fn methods(self) -> Option<&'static Methods> {
+        (self.vtable.vtable.get_methods)()
+    }
+
     pub(crate) fn attr_from_methods(self, name: &str) -> Result<Ty, TypingNoContextError> {
-        if let Some(methods) = (self.vtable.vtable.get_methods)() {
+        if let Some(methods) = self.methods() {
             if let Some(ty) = methods.get_ty(name) {
                 return Ok(ty);
             }
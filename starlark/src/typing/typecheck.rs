@@ -192,40 +192,48 @@ impl AstModuleTypecheck for AstModule {
         globals: &Globals,
         loads: &HashMap<String, Interface>,
     ) -> (Vec<crate::Error>, TypeMap, Interface, Vec<Approximation>) {
-        let (codemap, statement, _dialect, _) = self.into_parts();
-        let names = MutableNames::new();
-        let frozen_heap = FrozenHeap::new();
-        let (
-            scope_errors,
-            ModuleScopes {
-                mut cst,
-                scope_data,
-                ..
-            },
-        ) = ModuleScopes::check_module(
-            &names,
-            &frozen_heap,
-            loads,
-            statement,
-            ScopeResolverGlobals {
-                globals: Some(frozen_heap.alloc_any(globals.dupe())),
-            },
-            frozen_heap.alloc_any(codemap.dupe()),
-            &Dialect::AllOptionsInternal,
-        );
-        let scope_errors = scope_errors.into_map(TypingError::from_eval_exception);
-        // We don't really need to properly unpack top-level statements,
-        // but make it safe against future changes.
-        let mut cst: Vec<&mut CstStmt> = top_level_stmts_mut(&mut cst);
-        let oracle = TypingOracleCtx { codemap: &codemap };
+        typecheck_module(self, globals, loads, false)
+    }
+}
 
-        let mut approximations = Vec::new();
-        let (fill_types_errors, module_var_types) = match fill_types_for_lint_typechecker(
-            &mut cst,
-            oracle,
-            &scope_data,
-            &mut approximations,
-        ) {
+/// Typecheck a module, checking only function bodies,
+/// or also top-level statements if `check_top_level` is set.
+pub(crate) fn typecheck_module(
+    module: AstModule,
+    globals: &Globals,
+    loads: &HashMap<String, Interface>,
+    check_top_level: bool,
+) -> (Vec<crate::Error>, TypeMap, Interface, Vec<Approximation>) {
+    let (codemap, statement, _dialect, _) = module.into_parts();
+    let names = MutableNames::new();
+    let frozen_heap = FrozenHeap::new();
+    let (
+        scope_errors,
+        ModuleScopes {
+            mut cst,
+            scope_data,
+            ..
+        },
+    ) = ModuleScopes::check_module(
+        &names,
+        &frozen_heap,
+        loads,
+        statement,
+        ScopeResolverGlobals {
+            globals: Some(frozen_heap.alloc_any(globals.dupe())),
+        },
+        frozen_heap.alloc_any(codemap.dupe()),
+        &Dialect::AllOptionsInternal,
+    );
+    let scope_errors = scope_errors.into_map(TypingError::from_eval_exception);
+    // We don't really need to properly unpack top-level statements,
+    // but make it safe against future changes.
+    let mut cst: Vec<&mut CstStmt> = top_level_stmts_mut(&mut cst);
+    let oracle = TypingOracleCtx { codemap: &codemap };
+
+    let mut approximations = Vec::new();
+    let (fill_types_errors, module_var_types) =
+        match fill_types_for_lint_typechecker(&mut cst, oracle, &scope_data, &mut approximations) {
             Ok(fill_types_errors) => fill_types_errors,
             Err(e) => {
                 return (
@@ -240,21 +248,36 @@ impl AstModuleTypecheck for AstModule {
             }
         };
 
-        let mut typemap = UnorderedMap::new();
-        let mut all_solve_errors = Vec::new();
+    let mut typemap = UnorderedMap::new();
+    let mut all_solve_errors = Vec::new();
 
-        for top in cst.iter_mut() {
-            if let StmtP::Def(_) = &mut top.node {
-                let bindings = match BindingsCollect::collect_one(
-                    top,
-                    TypecheckMode::Lint,
-                    &codemap,
-                    &mut approximations,
-                ) {
-                    Ok(bindings) => bindings,
+    for top in cst.iter_mut() {
+        if check_top_level || matches!(top.node, StmtP::Def(_)) {
+            let bindings = match BindingsCollect::collect_one(
+                top,
+                TypecheckMode::Lint,
+                &codemap,
+                &mut approximations,
+            ) {
+                Ok(bindings) => bindings,
+                Err(e) => {
+                    return (
+                        vec![InternalError::into_error(e)],
+                        TypeMap {
+                            codemap,
+                            bindings: UnorderedMap::new(),
+                        },
+                        Interface::default(),
+                        Vec::new(),
+                    );
+                }
+            };
+            let (solve_errors, types, solve_approximations) =
+                match solve_bindings(bindings.bindings, oracle, &module_var_types) {
+                    Ok(x) => x,
                     Err(e) => {
                         return (
-                            vec![InternalError::into_error(e)],
+                            vec![e.into_error()],
                             TypeMap {
                                 codemap,
                                 bindings: UnorderedMap::new(),
@@ -264,61 +287,45 @@ impl AstModuleTypecheck for AstModule {
                         );
                     }
                 };
-                let (solve_errors, types, solve_approximations) =
-                    match solve_bindings(bindings.bindings, oracle, &module_var_types) {
-                        Ok(x) => x,
-                        Err(e) => {
-                            return (
-                                vec![e.into_error()],
-                                TypeMap {
-                                    codemap,
-                                    bindings: UnorderedMap::new(),
-                                },
-                                Interface::default(),
-                                Vec::new(),
-                            );
-                        }
-                    };
 
-                all_solve_errors.extend(solve_errors);
-                approximations.extend(solve_approximations);
+            all_solve_errors.extend(solve_errors);
+            approximations.extend(solve_approximations);
 
-                for (id, ty) in &types {
-                    let binding = scope_data.get_binding(*id);
-                    let name = binding.name.as_str().to_owned();
-                    let span = match binding.source {
-                        BindingSource::Source(span) => span,
-                        BindingSource::FromModule => Span::default(),
-                    };
-                    typemap.insert(*id, (name, span, ty.clone()));
-                }
+            for (id, ty) in &types {
+                let binding = scope_data.get_binding(*id);
+                let name = binding.name.as_str().to_owned();
+                let span = match binding.source {
+                    BindingSource::Source(span) => span,
+                    BindingSource::FromModule => Span::default(),
+                };
+                typemap.insert(*id, (name, span, ty.clone()));
             }
         }
+    }
 
-        let typemap = TypeMap {
-            bindings: typemap,
-            codemap: codemap.dupe(),
-        };
+    let typemap = TypeMap {
+        bindings: typemap,
+        codemap: codemap.dupe(),
+    };
 
-        let errors = [scope_errors, fill_types_errors, all_solve_errors]
-            .into_iter()
-            .flatten()
-            .map(TypingError::into_error)
-            .collect();
+    let errors = [scope_errors, fill_types_errors, all_solve_errors]
+        .into_iter()
+        .flatten()
+        .map(TypingError::into_error)
+        .collect();
 
-        let mut res = HashMap::new();
-        for (name, module_slot_id, vis) in names.all_names_slots_and_visibilities() {
-            if vis == Visibility::Public {
-                let ty = module_var_types
-                    .types
-                    .get(&module_slot_id)
-                    .cloned()
-                    .unwrap_or_else(Ty::any);
-                res.insert(name.as_str().to_owned(), ty);
-            }
+    let mut res = HashMap::new();
+    for (name, module_slot_id, vis) in names.all_names_slots_and_visibilities() {
+        if vis == Visibility::Public {
+            let ty = module_var_types
+                .types
+                .get(&module_slot_id)
+                .cloned()
+                .unwrap_or_else(Ty::any);
+            res.insert(name.as_str().to_owned(), ty);
         }
-        let interface = Interface::new(res);
-
-        (errors, typemap, interface, approximations)
     }
+    let interface = Interface::new(res);
+
+    (errors, typemap, interface, approximations)
 }
@@ -27,6 +27,12 @@
 //!   (or specialised methods like [`unpack_str`](Value::unpack_str)).
 //! * To define your own Rust data type that can live in a [`Value`] it must implement the [`StarlarkValue`]
 //!   trait.
+//! * A sequence-like [`StarlarkValue`] should implement
+//!   [`length`](StarlarkValue::length), [`at`](StarlarkValue::at) (with [`convert_index`]),
+//!   [`slice`](StarlarkValue::slice) (with [`apply_slice`] or [`convert_slice_indices`]),
+//!   [`is_in`](StarlarkValue::is_in) and [`iterate_collect`](StarlarkValue::iterate_collect).
+//!   The index helpers are the ones used by `list`, `tuple` and `str`, so indexing and slicing
+//!   behave, and fail, the same way as for the built-in sequences.
 //! * All the nested modules represent the built-in Starlark values. These are all defined using [`StarlarkValue`],
 //!   so may serve as interesting inspiration for writing your own values, in addition to occurring in Starlark programs.
 
@@ -52,13 +58,19 @@ pub use crate::values::demand::Demand;
 pub use crate::values::error::ValueError;
 pub use crate::values::freeze::Freeze;
 pub use crate::values::frozen_ref::FrozenRef;
+pub use crate::values::index::apply_slice;
+pub use crate::values::index::convert_index;
+pub use crate::values::index::convert_slice_indices;
 pub use crate::values::iter::StarlarkIterator;
 pub use crate::values::layout::complex::ValueTypedComplex;
 pub use crate::values::layout::heap::heap_type::Freezer;
 pub use crate::values::layout::heap::heap_type::FrozenHeap;
 pub use crate::values::layout::heap::heap_type::FrozenHeapRef;
+pub use crate::values::layout::heap::heap_type::GcStats;
 pub use crate::values::layout::heap::heap_type::Heap;
 pub use crate::values::layout::heap::heap_type::Tracer;
+pub use crate::values::layout::heap::profile::by_type::HeapSummary;
+pub use crate::values::layout::heap::profile::by_type::HeapValueInfo;
 pub use crate::values::layout::identity::ValueIdentity;
 pub use crate::values::layout::static_string::constant_string;
 pub use crate::values::layout::static_string::StarlarkStrNRepr;
@@ -80,6 +92,7 @@ pub use crate::values::types::any;
 pub use crate::values::types::any_complex;
 pub use crate::values::types::array;
 pub use crate::values::types::bool;
+pub use crate::values::types::bytes;
 pub use crate::values::types::dict;
 pub use crate::values::types::enumeration;
 pub use crate::values::types::exported_name;
@@ -90,6 +103,7 @@ pub use crate::values::types::list;
 pub use crate::values::types::list_or_tuple;
 pub use crate::values::types::namespace;
 pub use crate::values::types::none;
+pub use crate::values::types::promise;
 pub use crate::values::types::range;
 pub use crate::values::types::record;
 pub use crate::values::types::set;
@@ -50,15 +50,21 @@ pub enum ValueError {
     MissingRequired(String),
     #[error("Index `{0}` is out of bound")]
     IndexOutOfBound(i32),
+    #[error("Index `{index}` is out of bound for length {len}")]
+    IndexOutOfBoundForLength { index: i32, len: i32 },
+    #[error("Slice step cannot be zero")]
+    SliceStepZero,
     #[error("Key `{0}` was not found")]
     KeyNotFound(String),
     #[error("Immutable")]
     CannotMutateImmutableValue,
+    #[error("Cannot mutate frozen value of type `{0}`")]
+    CannotMutateFrozenValue(&'static str),
     #[error("This operation mutate an iterable for an iterator while iterating.")]
     MutationDuringIteration,
     #[error("Object of type `{0}` has no attribute `{1}`")]
     NoAttr(String, String),
-    #[error("Object of type `{0}` has no attribute `{1}`, did you mean `{2}`?")]
+    #[error("Object of type `{0}` has no attribute `{1}`, did you mean {2}?")]
     NoAttrDidYouMean(String, String, String),
 }
 
@@ -77,12 +83,14 @@ pub(crate) enum ControlError {
 }
 
 impl ValueError {
+    /// Helper to create an [`OperationNotSupported`](ValueError::OperationNotSupported) error,
+    /// or an [`OperationNotSupportedBinary`](ValueError::OperationNotSupportedBinary) error
+    /// if `right` is given, from type names.
+    ///
+    /// Useful when `self` is not the left operand, for example in
+    /// [`StarlarkValue::is_in`](crate::values::StarlarkValue::is_in).
     #[cold]
-    pub(crate) fn unsupported_owned<T>(
-        left: &str,
-        op: &str,
-        right: Option<&str>,
-    ) -> crate::Result<T> {
+    pub fn unsupported_owned<T>(left: &str, op: &str, right: Option<&str>) -> crate::Result<T> {
         match right {
             None => Err(ValueError::OperationNotSupported {
                 op: op.to_owned(),
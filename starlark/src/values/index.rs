@@ -46,32 +46,50 @@ fn convert_index_aux(
     }
 }
 
-/// Function to parse the index for at/set_at methods.
+/// Convert a possibly negative index into an index into a sequence of length `len`.
 ///
-/// Return an `i32` from self corresponding to the index recenterd between 0
-/// and len. Raise the correct errors if the value is not numeric or the
-/// index is out of bound.
-pub(crate) fn convert_index(v: Value, len: i32) -> anyhow::Result<i32> {
-    let x = i32::unpack_value_err(v)?;
-    let i = if x < 0 {
-        len.checked_add(x).ok_or(ValueError::IntegerOverflow)?
+/// Negative indices count from the end, so `-1` is the last element. Out-of-range indices
+/// are reported with the index as written and the length of the sequence.
+pub(crate) fn normalize_index(index: i32, len: usize) -> Result<usize, ValueError> {
+    let out_of_bound = || ValueError::IndexOutOfBoundForLength {
+        index,
+        len: len as i32,
+    };
+    let i = if index < 0 {
+        (len as i64) + (index as i64)
     } else {
-        x
+        index as i64
     };
-    if i < 0 || i >= len {
-        Err(ValueError::IndexOutOfBound(i).into())
+    if i < 0 || i >= len as i64 {
+        Err(out_of_bound())
     } else {
-        Ok(i)
+        Ok(i as usize)
     }
 }
 
-/// Parse indices for slicing.
+/// Parse the index for [`at`](crate::values::StarlarkValue::at)
+/// and [`set_at`](crate::values::StarlarkValue::set_at) of a sequence of length `len`.
+///
+/// Returns the index recentred between `0` and `len`, or an error if the value is not
+/// an integer or the index is out of bound. Negative indices count from the end,
+/// so custom sequence types using this function index like `list` and `str`.
+pub fn convert_index(v: Value, len: i32) -> anyhow::Result<i32> {
+    let x = i32::unpack_value_err(v)?;
+    Ok(normalize_index(x, len.max(0) as usize)? as i32)
+}
+
+/// Parse indices for [`slice`](crate::values::StarlarkValue::slice)
+/// of a sequence of length `len`.
 ///
 /// Takes the object length and 3 optional values and returns `(i32, i32,
 /// i32)` with those index correctly converted in range of length.
 /// Return the correct errors if the values are not numeric or the stride is
 /// 0.
-pub(crate) fn convert_slice_indices(
+///
+/// The result is `(start, stop, stride)` such that the slice contains
+/// `start`, `start + stride`, ... up to but excluding `stop`. When the stride is negative,
+/// `stop` may be `-1`. Use [`apply_slice`] if the sequence is available as a slice.
+pub fn convert_slice_indices(
     len: i32,
     start: Option<Value>,
     stop: Option<Value>,
@@ -83,7 +101,7 @@ pub(crate) fn convert_slice_indices(
         Some(v) => i32::unpack_value_err(v)?,
     };
     match stride {
-        0 => Err(ValueError::IndexOutOfBound(0).into()),
+        0 => Err(ValueError::SliceStepZero.into()),
         stride => {
             let def_start = if stride < 0 { len - 1 } else { 0 };
             let def_end = if stride < 0 { -1 } else { len };
@@ -99,7 +117,9 @@ pub(crate) fn convert_slice_indices(
     }
 }
 
-pub(crate) fn apply_slice<T: Copy>(
+/// Apply a slice `[start:stop:stride]` to a sequence, with the same semantics
+/// for negative and out of range bounds and strides as `list` and `str` slicing.
+pub fn apply_slice<T: Copy>(
     xs: &[T],
     start: Option<Value>,
     stop: Option<Value>,
@@ -148,6 +168,9 @@ pub(crate) fn apply_slice<T: Copy>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::assert;
+    use crate::assert::Assert;
+    use crate::tests::util::test_ints_globals;
     use crate::values::types::int::inline_int::InlineInt;
     use crate::values::Heap;
 
@@ -253,4 +276,81 @@ mod tests {
         .unwrap();
         assert_eq!(x, &[] as &[i32]);
     }
+
+    #[test]
+    fn test_negative_index_sequences() {
+        for (seq, last) in [
+            ("[1, 2, 3]", "3"),
+            ("(1, 2, 3)", "3"),
+            ("'abc'", "'c'"),
+            ("'\u{e9}t\u{e9}'", "'\u{e9}'"),
+            ("bytes([97, 98, 99])", "99"),
+            ("range(1, 4)", "3"),
+        ] {
+            assert::eq(&format!("{seq}[-1]"), last);
+            assert::eq(&format!("{seq}[-3]"), &format!("{seq}[0]"));
+            assert::eq(&format!("{seq}[::-1][0]"), last);
+            assert::eq(&format!("{seq}[-2:]"), &format!("{seq}[1:]"));
+            assert::eq(&format!("{seq}[-100:100]"), seq);
+            assert::fail(
+                &format!("{seq}[-4]"),
+                "Index `-4` is out of bound for length 3",
+            );
+            assert::fail(
+                &format!("{seq}[3]"),
+                "Index `3` is out of bound for length 3",
+            );
+            assert::fail(&format!("{seq}[::0]"), "Slice step cannot be zero");
+        }
+    }
+
+    #[test]
+    fn test_list_pop_negative_index() {
+        assert::eq("x = [1, 2, 3]\nx.pop(-3)", "1");
+        assert::fail(
+            "x = [1, 2, 3]\nx.pop(-4)",
+            "Index `-4` is out of bound for length 3",
+        );
+        assert::fail("[].pop()", "Index `-1` is out of bound for length 0");
+    }
+
+    #[test]
+    fn test_custom_sequence_matches_list() {
+        let mut a = Assert::new();
+        a.globals_add(test_ints_globals);
+        a.pass(
+            r#"
+def test():
+    t = ints(0, 1, 2, 3, 4)
+    l = list(t)
+    assert_eq(l, [0, 1, 2, 3, 4])
+    bounds = [None, -100, -6, -5, -2, -1, 0, 1, 2, 4, 5, 6, 100]
+    for start in bounds:
+        for stop in bounds:
+            for step in [None, 1, 2, 3, -1, -2, -3, 100, -100]:
+                assert_eq(t[start:stop:step], l[start:stop:step])
+    for i in range(-5, 5):
+        assert_eq(t[i], l[i])
+    assert_eq(len(t), len(l))
+    assert_eq([x in t for x in [-1, 0, 4, 5, "0"]], [x in l for x in [-1, 0, 4, 5, "0"]])
+test()
+"#,
+        );
+        for (expr, msg) in [
+            ("[{}][5]", "Index `5` is out of bound for length 5"),
+            ("[{}][-6]", "Index `-6` is out of bound for length 5"),
+            ("[{}][::0]", "Slice step cannot be zero"),
+            (
+                "[{}]['x']",
+                "Expected `int`, but got `string (repr: \"x\")`",
+            ),
+            (
+                "[{}][:'x']",
+                "Expected `int`, but got `string (repr: \"x\")`",
+            ),
+        ] {
+            a.fail(&expr.replace("[{}]", "[0, 1, 2, 3, 4]"), msg);
+            a.fail(&expr.replace("[{}]", "ints(0, 1, 2, 3, 4)"), msg);
+        }
+    }
 }
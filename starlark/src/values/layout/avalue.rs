@@ -35,6 +35,7 @@ use crate::private::Private;
 use crate::values::array::VALUE_EMPTY_ARRAY;
 use crate::values::layout::aligned_size::AlignedSize;
 use crate::values::layout::heap::arena::MIN_ALLOC;
+use crate::values::layout::heap::heap_type::STR_CACHE_MAX_LEN;
 use crate::values::layout::heap::repr::AValueForward;
 use crate::values::layout::heap::repr::AValueHeader;
 use crate::values::layout::heap::repr::AValueRepr;
@@ -258,8 +259,12 @@ impl<'v> AValue<'v> for StarlarkStrAValue {
             "short strings are allocated statically"
         );
 
-        let s = (*me).payload.as_str();
-        let fv = freezer.alloc(s);
+        let s = &(*me).payload;
+        let fv = if freezer.share_short_strings && s.len() <= STR_CACHE_MAX_LEN {
+            freezer.alloc_str_cached(s.as_str_hashed()).to_frozen_value()
+        } else {
+            freezer.alloc(s.as_str())
+        };
         debug_assert!(fv.is_str());
         AValueHeader::overwrite_with_forward::<Self::StarlarkValue>(me, ForwardPtr::new_frozen(fv));
         Ok(fv)
@@ -26,6 +26,7 @@
 //! to tag it as being a usize, and the word after is the size of the
 //! item it replaced.
 
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::mem;
 use std::mem::MaybeUninit;
@@ -469,6 +470,15 @@ impl<A: ArenaAllocator> Arena<A> {
         }
         HeapSummary { summary }
     }
+
+    /// The `n` values using the most memory, largest first, with their size.
+    pub(crate) fn largest_values(&self, n: usize) -> Vec<(&AValueHeader, usize)> {
+        let mut values = Vec::new();
+        self.for_each_unordered(|x| values.push((x, x.unpack().total_memory())));
+        values.sort_by_key(|(_, bytes)| Reverse(*bytes));
+        values.truncate(n);
+        values
+    }
 }
 
 impl<A: ArenaAllocator> Drop for Arena<A> {
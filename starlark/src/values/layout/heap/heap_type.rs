@@ -34,6 +34,7 @@ use std::ops::Deref;
 use std::ptr;
 use std::slice;
 use std::sync::Arc;
+use std::time::Duration;
 
 use allocative::Allocative;
 use bumpalo::Bump;
@@ -48,9 +49,13 @@ use crate::collections::Hashed;
 use crate::collections::StarlarkHashValue;
 use crate::eval::compiler::def::FrozenDef;
 use crate::eval::runtime::profile::instant::ProfilerInstant;
+use crate::eval::Arguments;
+use crate::eval::Evaluator;
 use crate::values::any::StarlarkAny;
 use crate::values::array::Array;
 use crate::values::array::VALUE_EMPTY_ARRAY;
+use crate::values::function::NativeClosureNoFreeze;
+use crate::values::function::NativeFunction;
 use crate::values::layout::avalue::any_array_avalue;
 use crate::values::layout::avalue::array_avalue;
 use crate::values::layout::avalue::complex;
@@ -73,6 +78,7 @@ use crate::values::layout::heap::call_enter_exit::NoDrop;
 use crate::values::layout::heap::fast_cell::FastCell;
 use crate::values::layout::heap::maybe_uninit_slice_util::maybe_uninit_write_from_exact_size_iter;
 use crate::values::layout::heap::profile::by_type::HeapSummary;
+use crate::values::layout::heap::profile::by_type::HeapValueInfo;
 use crate::values::layout::heap::repr::AValueOrForwardUnpack;
 use crate::values::layout::heap::repr::AValueRepr;
 use crate::values::layout::static_string::constant_string;
@@ -80,6 +86,8 @@ use crate::values::layout::typed::string::StringValueLike;
 use crate::values::layout::value::FrozenValue;
 use crate::values::layout::value::Value;
 use crate::values::list::value::VALUE_EMPTY_FROZEN_LIST;
+use crate::values::promise::StarlarkPromise;
+use crate::values::string::intern::cache::StringCache;
 use crate::values::string::intern::interner::FrozenStringValueInterner;
 use crate::values::string::intern::interner::StringValueInterner;
 use crate::values::string::str_type::StarlarkStr;
@@ -104,13 +112,35 @@ pub(crate) enum HeapKind {
     Frozen,
 }
 
+/// Garbage collection statistics of a [`Heap`], see [`Heap::gc_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcStats {
+    /// Number of garbage collections.
+    pub collections: usize,
+    /// Sum of the bytes allocated before each collection.
+    pub bytes_before: usize,
+    /// Sum of the bytes allocated after each collection.
+    pub bytes_after: usize,
+    /// Total time spent collecting.
+    pub time: Duration,
+}
+
+/// Strings up to this length are shared by [`Heap::alloc_str`] and when freezing,
+/// if enabled with [`Heap::set_share_short_strings`], see [`StringCache`].
+pub(crate) const STR_CACHE_MAX_LEN: usize = 32;
+
 /// A heap on which [`Value`]s can be allocated. The values will be annotated with the heap lifetime.
 #[derive(Default)]
 pub struct Heap {
     /// Peak memory seen when a garbage collection takes place (may be lower than currently allocated)
     peak_allocated: Cell<usize>,
+    gc_stats: Cell<GcStats>,
     arena: FastCell<Arena<Bump>>,
     str_interner: RefCell<StringValueInterner<'static>>,
+    /// Share equal short strings, see [`set_share_short_strings`](Heap::set_share_short_strings).
+    share_short_strings: Cell<bool>,
+    /// Recently allocated short strings.
+    str_cache: RefCell<StringCache<StringValue<'static>>>,
 }
 
 impl Debug for Heap {
@@ -236,6 +266,23 @@ impl FrozenHeapRef {
             .as_ref()
             .map_or_else(HeapSummary::default, |a| a.arena.allocated_summary())
     }
+
+    /// The `n` values using the most memory on this heap, largest first.
+    /// Doesn't include the heaps it keeps alive by reference.
+    pub fn largest_values(&self, n: usize) -> Vec<HeapValueInfo> {
+        let Some(heap) = &self.0 else {
+            return Vec::new();
+        };
+        heap.arena
+            .largest_values(n)
+            .into_iter()
+            .map(|(header, bytes)| {
+                // SAFETY: the header is a live value of this frozen heap.
+                let value = unsafe { header.unpack_value(HeapKind::Frozen) };
+                HeapValueInfo::new(value, bytes)
+            })
+            .collect()
+    }
 }
 
 impl FrozenHeap {
@@ -520,6 +567,10 @@ pub struct Freezer {
     pub(crate) heap: FrozenHeap,
     /// Defs frozen by this freezer.
     pub(crate) frozen_defs: RefCell<Vec<FrozenRef<'static, FrozenDef>>>,
+    /// Share equal short strings frozen by this freezer.
+    pub(crate) share_short_strings: bool,
+    /// Recently frozen short strings.
+    str_cache: RefCell<StringCache<FrozenStringValue>>,
 }
 
 impl Freezer {
@@ -527,6 +578,8 @@ impl Freezer {
         Freezer {
             heap,
             frozen_defs: RefCell::new(Vec::new()),
+            share_short_strings: false,
+            str_cache: RefCell::default(),
         }
     }
 
@@ -539,6 +592,13 @@ impl Freezer {
         val.alloc_frozen_value(&self.heap)
     }
 
+    /// Allocate a string while freezing, sharing short strings frozen before.
+    pub(crate) fn alloc_str_cached(&self, s: Hashed<&str>) -> FrozenStringValue {
+        self.str_cache
+            .borrow_mut()
+            .get_or_alloc(s, || self.heap.alloc_str_hashed(s))
+    }
+
     pub(crate) fn reserve<'v, 'v2, T: AValue<'v2, ExtraElem = ()>>(
         &'v self,
     ) -> (FrozenValue, Reservation<'v2, T>) {
@@ -598,6 +658,21 @@ impl Heap {
         self.arena.borrow().allocated_bytes()
     }
 
+    /// Share equal short strings, such as attribute names and labels, when they are
+    /// allocated with [`alloc_str`](Heap::alloc_str) and when the module owning this heap
+    /// is frozen. Off by default.
+    ///
+    /// This saves memory when many copies of the same strings are live, but each short
+    /// string allocation pays for a hash and a cache lookup, so it is slower when
+    /// most strings are distinct or short-lived.
+    pub fn set_share_short_strings(&self, share: bool) {
+        self.share_short_strings.set(share);
+    }
+
+    pub(crate) fn share_short_strings(&self) -> bool {
+        self.share_short_strings.get()
+    }
+
     /// Peak memory allocated to this heap, even if the value is now lower
     /// as a result of a subsequent garbage collection.
     pub fn peak_allocated_bytes(&self) -> usize {
@@ -661,9 +736,20 @@ impl Heap {
     }
 
     /// Allocate a string on the heap.
+    ///
+    /// Allocating the same short string twice may return the same value,
+    /// see [`set_share_short_strings`](Heap::set_share_short_strings).
     pub fn alloc_str<'v>(&'v self, x: &str) -> StringValue<'v> {
         if let Some(x) = constant_string(x) {
             x.to_string_value()
+        } else if x.len() <= STR_CACHE_MAX_LEN && self.share_short_strings.get() {
+            let x = Hashed::new(x);
+            let mut cache = self.str_cache.borrow_mut();
+            unsafe {
+                cache
+                    .get_or_alloc(x, || self.alloc_str_impl(x.key(), x.hash()).cast_lifetime())
+                    .cast_lifetime()
+            }
         } else {
             self.alloc_str_init(x.len(), StarlarkStr::UNINIT_HASH, |dest| unsafe {
                 copy_nonoverlapping(x.as_ptr(), dest, x.len())
@@ -781,6 +867,12 @@ impl Heap {
         self.alloc_raw(list_avalue(array))
     }
 
+    /// Allocate an empty list which can hold `capacity` elements without reallocating.
+    pub(crate) fn alloc_list_with_capacity<'v>(&'v self, capacity: usize) -> Value<'v> {
+        let array = self.alloc_array(capacity);
+        self.alloc_raw(list_avalue(array))
+    }
+
     /// Allocate a list with the given elements.
     pub(crate) fn alloc_list_iter<'v>(
         &'v self,
@@ -851,6 +943,69 @@ impl Heap {
         self.alloc_raw(complex_no_freeze(x))
     }
 
+    /// Allocate a function implemented by a Rust closure, e.g. to pass a callback
+    /// bound to some host state to Starlark code.
+    ///
+    /// The function prints as `name`, and appears under that name in the call stack.
+    /// The closure receives the evaluator and the arguments of the call, and does its own
+    /// argument checking. Like other functions, the value can be frozen,
+    /// so mutable state should be shared with the closure through e.g. an `Arc<Mutex<_>>`.
+    /// Use [`alloc_callable_no_freeze`](Heap::alloc_callable_no_freeze)
+    /// for closures which are not [`Send`] and [`Sync`].
+    ///
+    /// ```
+    /// use std::sync::atomic::AtomicI32;
+    /// use std::sync::atomic::Ordering;
+    /// use std::sync::Arc;
+    ///
+    /// use starlark::environment::Globals;
+    /// use starlark::environment::Module;
+    /// use starlark::eval::Evaluator;
+    /// use starlark::syntax::AstModule;
+    /// use starlark::syntax::Dialect;
+    ///
+    /// let total = Arc::new(AtomicI32::new(0));
+    /// let module = Module::new();
+    /// let state = total.clone();
+    /// let add = module.heap().alloc_callable("add", move |eval, args| {
+    ///     args.no_named_args()?;
+    ///     let x = args.positional1(eval.heap())?.unpack_i32().unwrap_or(0);
+    ///     Ok(eval.heap().alloc(state.fetch_add(x, Ordering::SeqCst) + x))
+    /// });
+    /// module.set("add", add);
+    ///
+    /// let ast = AstModule::parse("add.star", "add(3) + add(4)".to_owned(), &Dialect::Standard).unwrap();
+    /// let res = Evaluator::new(&module).eval_module(ast, &Globals::standard()).unwrap();
+    /// assert_eq!(res.unpack_i32(), Some(10));
+    /// assert_eq!(total.load(Ordering::SeqCst), 7);
+    /// ```
+    pub fn alloc_callable<'v, F>(&'v self, name: &str, f: F) -> Value<'v>
+    where
+        F: for<'a> Fn(&mut Evaluator<'a, '_, '_>, &Arguments<'a, '_>) -> crate::Result<Value<'a>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.alloc_simple(NativeFunction::from_closure(name, f))
+    }
+
+    /// Like [`alloc_callable`](Heap::alloc_callable), but the closure does not need
+    /// to be [`Send`] or [`Sync`], so it can share state through e.g. an `Rc<RefCell<_>>`.
+    /// Freezing a module which holds the value fails.
+    pub fn alloc_callable_no_freeze<'v, F>(&'v self, name: &str, f: F) -> Value<'v>
+    where
+        F: for<'a> Fn(&mut Evaluator<'a, '_, '_>, &Arguments<'a, '_>) -> crate::Result<Value<'a>>
+            + 'static,
+    {
+        self.alloc_complex_no_freeze(NativeClosureNoFreeze::new(name, f))
+    }
+
+    /// Allocate a promise: a placeholder for a value the host provides after evaluation,
+    /// see [`promise`](crate::values::promise).
+    pub fn alloc_promise<'v>(&'v self, tag: &str) -> Value<'v> {
+        self.alloc_simple(StarlarkPromise::new(tag))
+    }
+
     /// Allocate a new value on a [`Heap`].
     pub fn alloc<'v, T: AllocValue<'v>>(&'v self, x: T) -> Value<'v> {
         x.alloc_value(self)
@@ -897,7 +1052,35 @@ impl Heap {
     pub(crate) unsafe fn garbage_collect<'v>(&'v self, f: impl FnOnce(&Tracer<'v>)) {
         // Record the highest peak, so it never decreases
         self.peak_allocated.set(self.peak_allocated_bytes());
-        self.garbage_collect_internal(f)
+        let start = ProfilerInstant::now();
+        let bytes_before = self.allocated_bytes();
+        self.garbage_collect_internal(f);
+        let mut stats = self.gc_stats.get();
+        stats.collections += 1;
+        stats.bytes_before += bytes_before;
+        stats.bytes_after += self.allocated_bytes();
+        stats.time += start.elapsed();
+        self.gc_stats.set(stats);
+    }
+
+    /// Statistics of the garbage collections of this heap so far.
+    /// Collections are done by the [`Evaluator`](crate::eval::Evaluator) which uses this heap,
+    /// or requested with [`gc_now`](Heap::gc_now).
+    pub fn gc_stats(&self) -> GcStats {
+        self.gc_stats.get()
+    }
+
+    /// Garbage collect now, keeping the values traced by `roots`, and the values
+    /// reachable from them. Use [`Module::gc_now`](crate::environment::Module::gc_now)
+    /// to keep the variables of a module.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called while an [`Evaluator`](crate::eval::Evaluator) using this
+    /// heap is running, only between evaluations. Any `Value<'v>` not traced by
+    /// `roots` becomes invalid, as do references into values, e.g. `&'v str`.
+    pub unsafe fn gc_now<'v>(&'v self, roots: impl FnOnce(&Tracer<'v>)) {
+        self.garbage_collect(roots)
     }
 
     unsafe fn garbage_collect_internal<'v>(&'v self, f: impl FnOnce(&Tracer<'v>)) {
@@ -911,6 +1094,20 @@ impl Heap {
             phantom: PhantomData,
         };
         f(&tracer);
+        // Cached strings are only kept if something else kept them.
+        self.str_cache.borrow_mut().retain(|s| {
+            let value = s.to_value();
+            if !value.0.is_unfrozen() {
+                return Some(s);
+            }
+            match value.0.unpack_ptr().unwrap().unpack() {
+                AValueOrForwardUnpack::Forward(x) => Some(
+                    StringValue::new_unchecked(x.forward_ptr().unpack_unfrozen_value())
+                        .cast_lifetime(),
+                ),
+                AValueOrForwardUnpack::Header(_) => None,
+            }
+        });
         self.arena.set(tracer.arena);
     }
 
@@ -1042,6 +1239,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_short_string_shared_on_heap() {
+        let heap = Heap::new();
+        heap.set_share_short_strings(true);
+        let first = heap.alloc_str("xx");
+        let second = heap.alloc_str("xx");
+        assert!(first.to_value().ptr_eq(second.to_value()));
+    }
+
+    #[test]
+    fn test_shared_string_survives_gc() {
+        let heap = Heap::new();
+        heap.set_share_short_strings(true);
+        let mut kept = heap.alloc_str("kept").to_value();
+        heap.alloc_str("dropped");
+        unsafe { heap.garbage_collect(|tracer| tracer.trace(&mut kept)) };
+        assert!(kept.ptr_eq(heap.alloc_str("kept").to_value()));
+        assert_eq!("dropped", heap.alloc_str("dropped").as_str());
+    }
+
     #[test]
     fn test_interned_string_equal() {
         let heap = Heap::new();
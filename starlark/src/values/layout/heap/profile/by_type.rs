@@ -16,16 +16,24 @@
  */
 
 use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Display;
 
 use allocative::Allocative;
 use starlark_map::small_map::SmallMap;
 
 use crate::values::layout::heap::profile::alloc_counts::AllocCounts;
+use crate::values::recursive_repr_or_json_guard::ReprLimits;
+use crate::values::recursive_repr_or_json_guard::ReprLimitsGuard;
+use crate::values::Value;
 
 #[derive(Debug, Default, Clone, Allocative)]
 /// Information about the data stored on a heap. Accessible through
 /// the function `allocated_summary` available on [`Heap`](crate::values::Heap)
-/// and [`FrozenHeap`](crate::values::FrozenHeap)
+/// and [`FrozenHeap`](crate::values::FrozenHeap), or through
+/// [`FrozenModule::heap_summary`](crate::environment::FrozenModule::heap_summary).
+///
+/// Displayed as a table of types, largest first.
 pub struct HeapSummary {
     /// For each type, give the (number of entries, size of all entries).
     /// The size may be approximate as it includes information from
@@ -72,3 +80,49 @@ impl HeapSummary {
         }
     }
 }
+
+impl Display for HeapSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut types: Vec<_> = self.summary.iter().collect();
+        types.sort_by(|(a_name, a), (b_name, b)| b.bytes.cmp(&a.bytes).then(a_name.cmp(b_name)));
+        writeln!(f, "{:<24} {:>10} {:>12}", "Type", "Count", "Bytes")?;
+        for (name, counts) in types {
+            writeln!(f, "{:<24} {:>10} {:>12}", name, counts.count, counts.bytes)?;
+        }
+        let total = self.total();
+        writeln!(f, "{:<24} {:>10} {:>12}", "TOTAL", total.count, total.bytes)
+    }
+}
+
+/// A value on a heap, as returned by
+/// [`FrozenHeapRef::largest_values`](crate::values::FrozenHeapRef::largest_values).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeapValueInfo {
+    /// Type of the value, as returned by `type()`.
+    pub type_name: String,
+    /// Memory used by the value, like in [`HeapSummary`].
+    pub bytes: usize,
+    /// The value `repr`, truncated to [`HeapValueInfo::MAX_REPR_LEN`] bytes.
+    pub repr: String,
+}
+
+impl HeapValueInfo {
+    /// Longer `repr`s are truncated and end with `...`.
+    pub const MAX_REPR_LEN: usize = 100;
+
+    pub(crate) fn new(value: Value, bytes: usize) -> HeapValueInfo {
+        // Only render what fits, the value may be huge.
+        let repr = {
+            let _limits = ReprLimitsGuard::set(ReprLimits {
+                max_depth: None,
+                max_len: Some(Self::MAX_REPR_LEN),
+            });
+            value.to_repr()
+        };
+        HeapValueInfo {
+            type_name: value.get_type().to_owned(),
+            bytes,
+            repr,
+        }
+    }
+}
@@ -98,11 +98,13 @@ use crate::values::layout::vtable::AValueDyn;
 use crate::values::layout::vtable::AValueDynFull;
 use crate::values::layout::vtable::AValueVTable;
 use crate::values::list::value::VALUE_EMPTY_FROZEN_LIST;
+use crate::values::list::FrozenListRef;
 use crate::values::none::none_type::VALUE_NONE;
 use crate::values::range::Range;
 use crate::values::record::instance::FrozenRecord;
 use crate::values::record::record_type::RecordType;
 use crate::values::recursive_repr_or_json_guard::json_stack_push;
+use crate::values::recursive_repr_or_json_guard::repr_len_limited;
 use crate::values::recursive_repr_or_json_guard::repr_stack_push;
 use crate::values::stack_guard;
 use crate::values::starlark_type_id::StarlarkTypeId;
@@ -598,11 +600,23 @@ impl<'v> Value<'v> {
 
     /// `x - other`.
     pub fn sub(self, other: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        // Fast special case for ints, on overflow take the slow path.
+        if let (Some(ls), Some(rs)) = (self.unpack_inline_int(), other.unpack_inline_int()) {
+            if let Some(diff) = ls.checked_sub(rs) {
+                return Ok(heap.alloc(diff));
+            }
+        }
         self.get_ref().sub(other, heap)
     }
 
     /// `x * other`.
     pub fn mul(self, other: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        // Fast special case for ints, on overflow take the slow path.
+        if let (Some(ls), Some(rs)) = (self.unpack_inline_int(), other.unpack_inline_int()) {
+            if let Some(product) = ls.checked_mul_i32(rs.to_i32()) {
+                return Ok(heap.alloc(product));
+            }
+        }
         if let Some(r) = self.get_ref().mul(other, heap) {
             r
         } else if let Some(r) = other.get_ref().rmul(self, heap) {
@@ -663,7 +677,7 @@ impl<'v> Value<'v> {
         args: &Arguments<'v, '_>,
         eval: &mut Evaluator<'v, '_, '_>,
     ) -> crate::Result<Value<'v>> {
-        eval.with_call_stack(self, location, |eval| {
+        eval.with_call_stack(self, location, &args.0, |eval| {
             self.get_ref_full().invoke(args, eval)
         })
     }
@@ -681,6 +695,19 @@ impl<'v> Value<'v> {
         }
     }
 
+    /// The docstring of a function, as written in the source.
+    ///
+    /// For now it only returns the docstring of `def` functions.
+    pub fn docstring(self) -> Option<&'v str> {
+        if let Some(def) = self.downcast_ref::<Def>() {
+            def.def_info.docstring.as_deref()
+        } else if let Some(def) = self.downcast_ref::<FrozenDef>() {
+            def.def_info.docstring.as_deref()
+        } else {
+            None
+        }
+    }
+
     /// Invoke self with given arguments.
     pub(crate) fn invoke(
         self,
@@ -831,16 +858,27 @@ impl<'v> Value<'v> {
         freezer.freeze(self)
     }
 
-    /// Implement the `str()` function - converts a string value to itself,
-    /// otherwise uses `repr()`.
+    /// Implement the `str()` function: the user-facing form of the value.
+    ///
+    /// Strings are returned as their raw contents, without quotes. Types which
+    /// don't override [`StarlarkValue::collect_str`] produce the same result
+    /// as [`to_repr`](Value::to_repr).
     pub fn to_str(self) -> String {
         match self.unpack_str() {
-            None => self.to_repr(),
+            None => {
+                let mut s = String::new();
+                self.collect_str(&mut s);
+                s
+            }
             Some(s) => s.to_owned(),
         }
     }
 
-    /// Implement the `repr()` function.
+    /// Implement the `repr()` function: the quoted form of the value.
+    ///
+    /// For strings this is a valid Starlark string literal which evaluates back
+    /// to the original string. This is also what [`Display`] and
+    /// [`to_string`](ToString::to_string) produce for a [`Value`].
     pub fn to_repr(self) -> String {
         let mut s = String::new();
         self.collect_repr(&mut s);
@@ -873,26 +911,92 @@ impl<'v> Value<'v> {
         self.get_ref().set_at(index, alloc_value)
     }
 
+    /// Forwards to [`StarlarkValue::set_slice`].
+    pub fn set_slice(
+        self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        new_value: Value<'v>,
+        heap: &'v Heap,
+    ) -> crate::Result<()> {
+        self.get_ref()
+            .set_slice(start, stop, stride, new_value, heap)
+    }
+
+    /// Forwards to [`StarlarkValue::del_at`].
+    pub fn del_at(self, index: Value<'v>) -> crate::Result<()> {
+        self.get_ref().del_at(index)
+    }
+
+    /// Forwards to [`StarlarkValue::del_slice`].
+    pub fn del_slice(
+        self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        heap: &'v Heap,
+    ) -> crate::Result<()> {
+        self.get_ref().del_slice(start, stop, stride, heap)
+    }
+
     /// Forwards to [`StarlarkValue::documentation`].
     pub fn documentation(self) -> DocItem {
         self.get_ref().documentation()
     }
 
     /// Produce an iterable from a value.
+    ///
+    /// This is how native functions should consume an arbitrary iterable argument:
+    /// it supports the same values as a `for` loop in Starlark, including
+    /// user types implementing [`StarlarkValue::iterate`](crate::values::StarlarkValue::iterate),
+    /// and fails with the same error for values which are not iterable.
+    ///
+    /// While the iterator is alive, the iterated container is locked,
+    /// so mutating it (e.g. from a callback) fails like it does in a `for` loop.
     #[inline]
     pub fn iterate(self, heap: &'v Heap) -> crate::Result<StarlarkIterator<'v>> {
         let iter = self.get_ref().iterate(self, heap)?;
         Ok(StarlarkIterator::new(iter, heap))
     }
 
+    /// Iterate over a value and collect the items into a [`Vec`].
+    /// Fails if the value is not iterable.
+    ///
+    /// ```
+    /// # use starlark::values::Heap;
+    /// let heap = Heap::new();
+    /// let items = heap.alloc(("a", 1)).iterate_collect(&heap).unwrap();
+    /// assert_eq!(items.len(), 2);
+    /// assert!(heap.alloc(1).iterate_collect(&heap).is_err());
+    /// ```
+    pub fn iterate_collect(self, heap: &'v Heap) -> crate::Result<Vec<Value<'v>>> {
+        Ok(self.iterate(heap)?.collect())
+    }
+
     /// Get the [`Hashed`] version of this [`Value`].
     #[inline]
     pub fn get_hashed(self) -> crate::Result<Hashed<Self>> {
         ValueLike::get_hashed(self)
     }
 
-    /// Are two values equal. If the values are of different types it will
+    /// Are two values equal, using the same semantics as `==` in Starlark:
+    /// collections are compared structurally. If the values are of different types it will
     /// return [`false`]. It will only error if there is excessive recursion.
+    ///
+    /// The values may come from different heaps, e.g. from two evaluated modules:
+    ///
+    /// ```
+    /// # use starlark::assert;
+    /// let old = assert::pass_module("config = {'srcs': ['a.c'], 'opt': 1}");
+    /// let new = assert::pass_module("config = {'opt': 1, 'srcs': ['a' + '.c']}");
+    /// let old = old.get("config").unwrap();
+    /// let new = new.get("config").unwrap();
+    /// assert!(old.value().equals(new.value()).unwrap());
+    /// // Values of different types are not equal.
+    /// let one = assert::pass("1");
+    /// assert!(!old.value().equals(one.value()).unwrap());
+    /// ```
     #[inline]
     pub fn equals(self, other: Value<'v>) -> crate::Result<bool> {
         if self.ptr_eq(other) {
@@ -907,6 +1011,10 @@ impl<'v> Value<'v> {
 
     #[inline]
     fn equals_not_ptr_eq(self, other: Value<'v>) -> crate::Result<bool> {
+        // Equal inline ints are pointer-equal.
+        if self.unpack_inline_int().is_some() && other.unpack_inline_int().is_some() {
+            return Ok(false);
+        }
         let _guard = stack_guard::stack_guard()?;
         self.get_ref().equals(other)
     }
@@ -914,6 +1022,10 @@ impl<'v> Value<'v> {
     /// How are two values comparable. For values of different types will return [`Err`].
     #[inline]
     pub fn compare(self, other: Value<'v>) -> crate::Result<Ordering> {
+        // Fast special case for ints, which are usually loop counters.
+        if let (Some(ls), Some(rs)) = (self.unpack_inline_int(), other.unpack_inline_int()) {
+            return Ok(ls.cmp(&rs));
+        }
         ValueLike::compare(self, other)
     }
 
@@ -976,7 +1088,7 @@ impl<'v> Value<'v> {
         aref.has_attr(attribute, heap)
     }
 
-    /// Get a list of all the attributes this function supports, used to implement the
+    /// Get a sorted list of all the attributes this value supports, used to implement the
     /// `dir()` function.
     pub fn dir_attr(self) -> Vec<String> {
         let aref = self.get_ref();
@@ -988,6 +1100,7 @@ impl<'v> Value<'v> {
             aref.dir_attr()
         };
         result.sort();
+        result.dedup();
         result
     }
 
@@ -1228,6 +1341,20 @@ impl FrozenValue {
             .map(|value| FrozenRef { value })
     }
 
+    /// Downcast to the items of a list.
+    #[inline]
+    pub fn downcast_frozen_list(self) -> Option<&'static [FrozenValue]> {
+        FrozenListRef::from_frozen_value(self).map(|list| &**list)
+    }
+
+    /// Downcast to the entries of a dict, in insertion order.
+    #[inline]
+    pub fn downcast_frozen_dict(
+        self,
+    ) -> Option<impl ExactSizeIterator<Item = (FrozenValue, FrozenValue)>> {
+        FrozenDictRef::from_frozen_value(self).map(|dict| dict.iter())
+    }
+
     /// Note: see docs about ['Value::unpack_box_str'] about instability
     #[inline]
     pub fn downcast_frozen_starlark_str(self) -> Option<FrozenRef<'static, StarlarkStr>> {
@@ -1319,13 +1446,7 @@ pub trait ValueLike<'v>:
     fn collect_repr(self, collector: &mut String);
 
     /// `str(x)`.
-    fn collect_str(self, collector: &mut String) {
-        if let Some(s) = self.to_value().unpack_str() {
-            collector.push_str(s);
-        } else {
-            self.collect_repr(collector);
-        }
-    }
+    fn collect_str(self, collector: &mut String);
 
     /// `x == other`.
     ///
@@ -1395,14 +1516,29 @@ impl<'v> ValueLike<'v> for Value<'v> {
     }
 
     fn collect_repr(self, collector: &mut String) {
-        match repr_stack_push(self) {
+        repr_len_limited(collector, |collector| match repr_stack_push(self) {
             Ok(_guard) => {
                 self.get_ref().collect_repr(collector);
             }
             Err(..) => {
                 self.get_ref().collect_repr_cycle(collector);
             }
+        })
+    }
+
+    fn collect_str(self, collector: &mut String) {
+        if let Some(s) = self.unpack_str() {
+            collector.push_str(s);
+            return;
         }
+        repr_len_limited(collector, |collector| match repr_stack_push(self) {
+            Ok(_guard) => {
+                self.get_ref().collect_str(collector);
+            }
+            Err(..) => {
+                self.get_ref().collect_repr_cycle(collector);
+            }
+        })
     }
 
     fn write_hash(self, hasher: &mut StarlarkHasher) -> crate::Result<()> {
@@ -1447,6 +1583,10 @@ impl<'v> ValueLike<'v> for FrozenValue {
         self.to_value().collect_repr(collector)
     }
 
+    fn collect_str(self, collector: &mut String) {
+        self.to_value().collect_str(collector)
+    }
+
     #[inline]
     fn write_hash(self, hasher: &mut StarlarkHasher) -> crate::Result<()> {
         self.to_value().write_hash(hasher)
@@ -1596,4 +1736,21 @@ mod tests {
             "{e}"
         );
     }
+
+    #[test]
+    fn test_iterate_collect() {
+        let heap = Heap::new();
+        let dict = assert::pass("{'x': 1, 'y': 2}");
+        let keys: Vec<String> = dict
+            .value()
+            .iterate_collect(&heap)
+            .unwrap()
+            .into_iter()
+            .map(|k| k.unpack_str().unwrap().to_owned())
+            .collect();
+        assert_eq!(keys, vec!["x".to_owned(), "y".to_owned()]);
+
+        let e = heap.alloc("abc").iterate_collect(&heap).unwrap_err();
+        assert!(e.to_string().contains("(iter)"), "{e}");
+    }
 }
@@ -492,6 +492,11 @@ impl<'v> AValueDyn<'v> {
         (self.vtable.starlark_value.collect_repr)(self.value, collector)
     }
 
+    #[inline]
+    pub(crate) fn collect_str(self, collector: &mut String) {
+        (self.vtable.starlark_value.collect_str)(self.value, collector)
+    }
+
     #[inline]
     pub(crate) fn collect_repr_cycle(self, collector: &mut String) {
         (self.vtable.starlark_value.collect_repr_cycle)(self.value, collector)
@@ -536,6 +541,34 @@ impl<'v> AValueDyn<'v> {
         (self.vtable.starlark_value.set_at)(self.value, index, new_value)
     }
 
+    #[inline]
+    pub(crate) fn set_slice(
+        self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        new_value: Value<'v>,
+        heap: &'v Heap,
+    ) -> crate::Result<()> {
+        (self.vtable.starlark_value.set_slice)(self.value, start, stop, stride, new_value, heap)
+    }
+
+    #[inline]
+    pub(crate) fn del_at(self, index: Value<'v>) -> crate::Result<()> {
+        (self.vtable.starlark_value.del_at)(self.value, index)
+    }
+
+    #[inline]
+    pub(crate) fn del_slice(
+        self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        heap: &'v Heap,
+    ) -> crate::Result<()> {
+        (self.vtable.starlark_value.del_slice)(self.value, start, stop, stride, heap)
+    }
+
     #[inline]
     pub(crate) fn set_attr(self, attribute: &str, new_value: Value<'v>) -> crate::Result<()> {
         (self.vtable.starlark_value.set_attr)(self.value, attribute, new_value)
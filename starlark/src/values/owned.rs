@@ -128,6 +128,35 @@ impl OwnedFrozenValue {
         self.value.unpack_str()
     }
 
+    /// Iterate over the items of the underlying value if it is a list,
+    /// or [`None`] if it is not a list. Each item keeps the owning heap alive.
+    pub fn downcast_frozen_list(&self) -> Option<impl ExactSizeIterator<Item = OwnedFrozenValue>> {
+        let items = self.value.downcast_frozen_list()?;
+        let owner = self.owner.dupe();
+        // SAFE: the items are kept alive by `owner`, which every result holds on to.
+        Some(
+            items
+                .iter()
+                .map(move |v| unsafe { OwnedFrozenValue::new(owner.dupe(), *v) }),
+        )
+    }
+
+    /// Iterate over the entries of the underlying value if it is a dict,
+    /// or [`None`] if it is not a dict. Each entry keeps the owning heap alive.
+    pub fn downcast_frozen_dict(
+        &self,
+    ) -> Option<impl ExactSizeIterator<Item = (OwnedFrozenValue, OwnedFrozenValue)>> {
+        let entries = self.value.downcast_frozen_dict()?;
+        let owner = self.owner.dupe();
+        // SAFE: the entries are kept alive by `owner`, which every result holds on to.
+        Some(entries.map(move |(k, v)| unsafe {
+            (
+                OwnedFrozenValue::new(owner.dupe(), k),
+                OwnedFrozenValue::new(owner.dupe(), v),
+            )
+        }))
+    }
+
     /// Check if `self` references `<T>`.
     pub fn downcast<T: StarlarkValue<'static>>(self) -> Result<OwnedFrozenValueTyped<T>, Self> {
         match FrozenValueTyped::new(self.value) {
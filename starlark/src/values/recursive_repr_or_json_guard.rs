@@ -21,8 +21,11 @@ use std::cell::Cell;
 
 use crate::collections::SmallSet;
 use crate::hint::unlikely;
+use crate::values::float::StarlarkFloat;
 use crate::values::layout::pointer::RawPointer;
+use crate::values::types::bigint::StarlarkBigInt;
 use crate::values::Value;
+use crate::values::ValueLike;
 
 /// Pop the stack on drop.
 pub(crate) struct ReprStackGuard;
@@ -60,17 +63,116 @@ pub(crate) struct JsonCycle;
 
 thread_local! {
     static REPR_STACK: Cell<SmallSet<RawPointer>> = const { Cell::new(SmallSet::new()) };
+    /// Limits of values rendered by `repr`, set while an evaluator is running.
+    static REPR_LIMITS: Cell<ReprLimits> = const { Cell::new(ReprLimits { max_depth: None, max_len: None }) };
+    /// The string the outermost `repr` is written to, and its length when it started,
+    /// while a length limit is set.
+    static REPR_LEN_START: Cell<Option<(*const String, usize)>> = const { Cell::new(None) };
+}
+
+/// Limits of `repr`, `str` and string formatting.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct ReprLimits {
+    /// Values nested deeper are rendered like cycles, e.g. `[...]`.
+    pub(crate) max_depth: Option<usize>,
+    /// Longer output is truncated and ends with `...`.
+    pub(crate) max_len: Option<usize>,
+}
+
+/// Restore the previous `repr` limits on drop.
+pub(crate) struct ReprLimitsGuard(ReprLimits);
+
+impl ReprLimitsGuard {
+    /// Limit the values rendered by `repr` until the guard is dropped.
+    pub(crate) fn set(limits: ReprLimits) -> ReprLimitsGuard {
+        ReprLimitsGuard(REPR_LIMITS.replace(limits))
+    }
+}
+
+impl Drop for ReprLimitsGuard {
+    fn drop(&mut self) {
+        REPR_LIMITS.set(self.0);
+    }
+}
+
+/// Values which never contain other values, so they are rendered at any depth.
+fn is_repr_leaf(value: Value) -> bool {
+    value.is_none()
+        || value.unpack_bool().is_some()
+        || value.unpack_inline_int().is_some()
+        || value.is_str()
+        || value.downcast_ref::<StarlarkBigInt>().is_some()
+        || value.downcast_ref::<StarlarkFloat>().is_some()
+}
+
+/// Clear the start of the outermost `repr` on drop.
+struct ReprLenStartGuard;
+
+impl Drop for ReprLenStartGuard {
+    fn drop(&mut self) {
+        REPR_LEN_START.set(None);
+    }
+}
+
+/// Append the `repr` of a value to `collector` with `render`, unless the output
+/// already reached the length limit, in which case `...` is appended instead.
+/// The outermost call truncates the output to the limit.
+pub(crate) fn repr_len_limited(collector: &mut String, render: impl FnOnce(&mut String)) {
+    let Some(max_len) = REPR_LIMITS.get().max_len else {
+        return render(collector);
+    };
+    match REPR_LEN_START.get() {
+        None => {
+            let start = collector.len();
+            REPR_LEN_START.set(Some((collector as *const String, start)));
+            let _guard = ReprLenStartGuard;
+            render(collector);
+            if collector.len() - start > max_len {
+                let mut end = start + max_len;
+                while !collector.is_char_boundary(end) {
+                    end -= 1;
+                }
+                collector.truncate(end);
+                collector.push_str("...");
+            }
+        }
+        Some((outer, start)) if std::ptr::eq(outer, collector) => {
+            if collector.len() - start < max_len {
+                render(collector);
+            } else if !collector.ends_with("...") {
+                collector.push_str("...");
+            }
+        }
+        // Rendered to a temporary string, which is appended to the outermost one later.
+        Some(_) => render(collector),
+    }
+}
+
+/// Whether `collector`, being written by [`repr_len_limited`], reached the length limit,
+/// so containers can stop rendering their elements.
+pub(crate) fn repr_len_exceeded(collector: &String) -> bool {
+    match (REPR_LIMITS.get().max_len, REPR_LEN_START.get()) {
+        (Some(max_len), Some((outer, start))) if std::ptr::eq(outer, collector) => {
+            collector.len() - start >= max_len
+        }
+        _ => false,
+    }
 }
 
 thread_local! {
     static JSON_STACK: Cell<SmallSet<RawPointer>> = const { Cell::new(SmallSet::new()) };
 }
 
-/// Push a value to the stack, return error if it is already on the stack.
+/// Push a value to the stack, return error if it is already on the stack,
+/// or if the stack is deeper than the limit set with [`ReprLimitsGuard`].
 pub(crate) fn repr_stack_push(value: Value) -> Result<ReprStackGuard, ReprCycle> {
     REPR_STACK.with(|repr_stack| {
         let mut stack = Cell::take(repr_stack);
-        if unlikely(!stack.insert(value.ptr_value())) {
+        let too_deep = match REPR_LIMITS.get().max_depth {
+            Some(max_depth) => stack.len() >= max_depth && !is_repr_leaf(value),
+            None => false,
+        };
+        if unlikely(too_deep || !stack.insert(value.ptr_value())) {
             repr_stack.set(stack);
             Err(ReprCycle)
         } else {
@@ -93,3 +195,35 @@ pub(crate) fn json_stack_push(value: Value) -> Result<JsonStackGuard, JsonCycle>
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_repr_cycles() {
+        assert::pass(
+            r#"
+x = []
+x.append(x)
+d = {}
+d["self"] = d
+d["list"] = [d, x]
+R = record(items = list)
+r = R(items = [])
+r.items.append(r)
+s = struct(items = [])
+s.items.append(s)
+
+assert_eq(str(x), "[[...]]")
+assert_eq(repr(x), "[[...]]")
+assert_eq("%s" % x, "[[...]]")
+assert_eq("{}".format(x), "[[...]]")
+assert_eq(str(d), '{"self": {...}, "list": [{...}, [[...]]]}')
+assert_eq(str([x, x]), "[[[...]], [[...]]]")
+assert_eq(str(r), "record[R](items=[record[R](...)])")
+assert_eq(str(s), "struct(items=[struct(...)])")
+"#,
+        );
+    }
+}
@@ -362,10 +362,31 @@ pub trait StarlarkValue<'v>:
     }
 
     /// Invoked to print `repr` when a cycle is the object stack is detected.
+    ///
+    /// Containers render their elements with
+    /// [`ValueLike::collect_repr`](crate::values::ValueLike::collect_repr), which detects
+    /// cycles and calls this method instead of recursing, like `[...]` for lists.
     fn collect_repr_cycle(&self, collector: &mut String) {
         write!(collector, "<{}...>", Self::TYPE).unwrap()
     }
 
+    /// Return a string representation of self, as returned by the `str()` function.
+    ///
+    /// Unlike [`collect_repr`](StarlarkValue::collect_repr), the result is meant
+    /// for humans rather than for parsing back, so it need not be valid Starlark.
+    /// The default implementation delegates to `collect_repr`, which is what most
+    /// types want; `str` overrides it to produce its contents without quotes.
+    ///
+    /// ```rust
+    /// # starlark::assert::all_true(r#"
+    /// str("test") == 'test'
+    /// str([1, "x"]) == '[1, "x"]'
+    /// # "#);
+    /// ```
+    fn collect_str(&self, collector: &mut String) {
+        self.collect_repr(collector)
+    }
+
     /// String used when printing call stack. `repr(self)` by default.
     fn name_for_call_stack(&self, me: Value<'v>) -> String {
         me.to_repr()
@@ -621,6 +642,10 @@ pub trait StarlarkValue<'v>:
     /// The three methods [`get_attr`](StarlarkValue::get_attr),
     /// [`has_attr`](StarlarkValue::has_attr) and [`dir_attr`](StarlarkValue::dir_attr)
     /// must be consistent - if you implement one, you should probably implement all three.
+    /// [`assert::attrs_consistent`](crate::assert::attrs_consistent) checks this.
+    ///
+    /// Methods from [`get_methods`](StarlarkValue::get_methods) are added by `dir()`,
+    /// so they need not be listed here.
     fn dir_attr(&self) -> Vec<String> {
         Vec::new()
     }
@@ -668,6 +693,11 @@ pub trait StarlarkValue<'v>:
 
     /// Add with the arguments the other way around.
     /// Normal `add` should return `None` in order for it to be evaluated.
+    ///
+    /// This is how `1 + x` can be handled by a custom type of `x`: builtin types return
+    /// `None` from `add` for operands they don't know. Only `+` and `*` have reflected
+    /// variants ([`radd`](StarlarkValue::radd) and [`rmul`](StarlarkValue::rmul)),
+    /// other binary operators and comparisons are dispatched to the left operand only.
     fn radd(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<crate::Result<Value<'v>>> {
         None
     }
@@ -729,12 +759,18 @@ pub trait StarlarkValue<'v>:
 
     /// Divide the current value by `other`. Always results in a float value.
     ///
+    /// For numbers this is true division, as in Python 3 and the Starlark spec,
+    /// in every [`Dialect`](crate::syntax::Dialect): integer operands are converted to float,
+    /// and [`floor_div`](StarlarkValue::floor_div) (`//`) is the integer division.
+    ///
     /// # Examples
     ///
     /// ```rust
     /// # starlark::assert::all_true(r#"
     /// 4 / 2.0 == 2.0
     /// 7 / 2 == 3.5
+    /// 7.0 / 2 == 3.5
+    /// -7 / 2 == -3.5
     /// # "#);
     /// ```
     fn div(&self, other: Value<'v>, _heap: &'v Heap) -> crate::Result<Value<'v>> {
@@ -844,7 +880,15 @@ pub trait StarlarkValue<'v>:
         None
     }
 
-    /// Called when exporting a value under a specific name,
+    /// Called when a value is assigned to a top-level (module) variable.
+    ///
+    /// The evaluator calls this for every top-level assignment, so a value
+    /// bound first as `x` and later as `y = x` receives both calls, in program
+    /// order. Types which record their name (rules, providers, enum and record
+    /// types) should keep only the first name they are given;
+    /// [`ExportedName`](crate::values::exported_name::ExportedName) implements
+    /// that pattern. Values nested in containers, and values bound to local
+    /// variables, are not exported.
     fn export_as(
         &self,
         _variable_name: &str,
@@ -871,6 +915,44 @@ pub trait StarlarkValue<'v>:
         ))
     }
 
+    /// Replace the elements selected by `x[start:stop:stride]` with the
+    /// elements of the iterable `new_value` (e.g. `x[1:3] = value`).
+    ///
+    /// ```rust
+    /// # starlark::assert::is_true(r#"
+    /// v = [1, 2, 3, 4]
+    /// v[1:3] = ["a", "b", "c"]
+    /// v == [1, "a", "b", "c", 4]
+    /// # "#);
+    /// ```
+    fn set_slice(
+        &self,
+        _start: Option<Value<'v>>,
+        _stop: Option<Value<'v>>,
+        _stride: Option<Value<'v>>,
+        _new_value: Value<'v>,
+        _heap: &'v Heap,
+    ) -> crate::Result<()> {
+        ValueError::unsupported(self, "[::]=")
+    }
+
+    /// Remove the element at `index` (e.g. `del x[index]`).
+    fn del_at(&self, _index: Value<'v>) -> crate::Result<()> {
+        ValueError::unsupported(self, "del []")
+    }
+
+    /// Remove the elements selected by `x[start:stop:stride]`
+    /// (e.g. `del x[1:3]`).
+    fn del_slice(
+        &self,
+        _start: Option<Value<'v>>,
+        _stop: Option<Value<'v>>,
+        _stride: Option<Value<'v>>,
+        _heap: &'v Heap,
+    ) -> crate::Result<()> {
+        ValueError::unsupported(self, "del [::]")
+    }
+
     /// Set the attribute named `attribute` of the current value to
     /// `value` (e.g. `a.attribute = value`).
     fn set_attr(&self, attribute: &str, _new_value: Value<'v>) -> crate::Result<()> {
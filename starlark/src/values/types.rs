@@ -21,6 +21,7 @@ pub mod any_complex;
 pub mod array;
 pub mod bigint;
 pub mod bool;
+pub mod bytes;
 pub mod dict;
 pub(crate) mod ellipsis;
 pub mod enumeration;
@@ -34,6 +35,7 @@ pub mod list_or_tuple;
 pub mod namespace;
 pub mod none;
 pub(crate) mod num;
+pub mod promise;
 pub mod range;
 pub mod record;
 pub mod set;
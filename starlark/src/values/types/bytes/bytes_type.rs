@@ -0,0 +1,300 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Write;
+use std::hash::Hash;
+
+use allocative::Allocative;
+use serde::Serialize;
+use serde::Serializer;
+use starlark_derive::starlark_value;
+
+use crate as starlark;
+use crate::any::ProvidesStaticType;
+use crate::collections::StarlarkHasher;
+use crate::environment::Methods;
+use crate::environment::MethodsStatic;
+use crate::starlark_simple_value;
+use crate::typing::Ty;
+use crate::values::index::apply_slice;
+use crate::values::index::convert_index;
+use crate::values::Heap;
+use crate::values::StarlarkValue;
+use crate::values::UnpackValue;
+use crate::values::Value;
+use crate::values::ValueError;
+use crate::values::ValueLike;
+
+#[derive(Debug, thiserror::Error)]
+enum BytesError {
+    #[error("Unsupported encoding `{0}`, only `utf-8` is supported")]
+    UnsupportedEncoding(String),
+    #[error("Byte value {0} is out of range, expected an int in 0..256")]
+    ByteOutOfRange(i32),
+    #[error("Invalid UTF-8 in bytes at index {0}")]
+    InvalidUtf8(usize),
+}
+
+/// An immutable sequence of bytes, the result of `bytes()` or `str.encode()`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, ProvidesStaticType, Allocative)]
+pub struct StarlarkBytes {
+    bytes: Box<[u8]>,
+}
+
+starlark_simple_value!(StarlarkBytes);
+
+impl StarlarkBytes {
+    /// The result of calling `type()` on bytes.
+    pub const TYPE: &'static str = "bytes";
+
+    /// Create a new [`StarlarkBytes`].
+    pub fn new(bytes: impl Into<Box<[u8]>>) -> StarlarkBytes {
+        StarlarkBytes {
+            bytes: bytes.into(),
+        }
+    }
+
+    /// The underlying bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Decode the bytes as UTF-8, failing on invalid sequences.
+    pub(crate) fn decode_utf8(&self) -> anyhow::Result<&str> {
+        std::str::from_utf8(&self.bytes)
+            .map_err(|e| BytesError::InvalidUtf8(e.valid_up_to()).into())
+    }
+
+    pub(crate) fn check_encoding(encoding: &str) -> anyhow::Result<()> {
+        match encoding.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Ok(()),
+            _ => Err(BytesError::UnsupportedEncoding(encoding.to_owned()).into()),
+        }
+    }
+
+    pub(crate) fn byte_from_int(x: i32) -> anyhow::Result<u8> {
+        u8::try_from(x).map_err(|_| BytesError::ByteOutOfRange(x).into())
+    }
+}
+
+impl Display for StarlarkBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("b\"")?;
+        for &b in self.bytes.iter() {
+            match b {
+                b'"' => f.write_str("\\\"")?,
+                b'\\' => f.write_str("\\\\")?,
+                b'\n' => f.write_str("\\n")?,
+                b'\r' => f.write_str("\\r")?,
+                b'\t' => f.write_str("\\t")?,
+                0x20..=0x7e => f.write_char(b as char)?,
+                _ => write!(f, "\\x{:02x}", b)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+/// Bytes have no JSON counterpart, so they are serialized as a base64 string.
+impl Serialize for StarlarkBytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64_encode(&self.bytes))
+    }
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut res = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                res.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
+
+pub(crate) fn bytes_methods() -> Option<&'static Methods> {
+    static RES: MethodsStatic = MethodsStatic::new();
+    RES.methods(crate::values::types::bytes::methods::bytes_methods)
+}
+
+#[starlark_value(type = StarlarkBytes::TYPE)]
+impl<'v> StarlarkValue<'v> for StarlarkBytes {
+    fn to_bool(&self) -> bool {
+        !self.bytes.is_empty()
+    }
+
+    fn length(&self) -> crate::Result<i32> {
+        Ok(self.bytes.len() as i32)
+    }
+
+    fn write_hash(&self, hasher: &mut StarlarkHasher) -> crate::Result<()> {
+        self.bytes.hash(hasher);
+        Ok(())
+    }
+
+    fn equals(&self, other: Value<'v>) -> crate::Result<bool> {
+        match other.downcast_ref::<Self>() {
+            Some(other) => Ok(self.bytes == other.bytes),
+            None => Ok(false),
+        }
+    }
+
+    fn compare(&self, other: Value<'v>) -> crate::Result<Ordering> {
+        match other.downcast_ref::<Self>() {
+            Some(other) => Ok(self.bytes.cmp(&other.bytes)),
+            None => ValueError::unsupported_with(self, "cmp()", other),
+        }
+    }
+
+    fn at(&self, index: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        let i = convert_index(index, self.bytes.len() as i32)?;
+        Ok(heap.alloc(self.bytes[i as usize] as i32))
+    }
+
+    fn slice(
+        &self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        heap: &'v Heap,
+    ) -> crate::Result<Value<'v>> {
+        let bytes = apply_slice(&self.bytes, start, stop, stride)?;
+        Ok(heap.alloc(StarlarkBytes::new(bytes)))
+    }
+
+    unsafe fn iterate(&self, me: Value<'v>, _heap: &'v Heap) -> crate::Result<Value<'v>> {
+        Ok(me)
+    }
+
+    unsafe fn iter_size_hint(&self, index: usize) -> (usize, Option<usize>) {
+        let rem = self.bytes.len().saturating_sub(index);
+        (rem, Some(rem))
+    }
+
+    unsafe fn iter_next(&self, index: usize, heap: &'v Heap) -> Option<Value<'v>> {
+        self.bytes.get(index).map(|b| heap.alloc(*b as i32))
+    }
+
+    unsafe fn iter_stop(&self) {}
+
+    fn is_in(&self, other: Value<'v>) -> crate::Result<bool> {
+        if let Some(needle) = other.downcast_ref::<Self>() {
+            Ok(needle.bytes.is_empty()
+                || self
+                    .bytes
+                    .windows(needle.bytes.len())
+                    .any(|w| *w == *needle.bytes))
+        } else if let Some(b) = i32::unpack_value(other)? {
+            Ok(self.bytes.contains(&Self::byte_from_int(b)?))
+        } else {
+            ValueError::unsupported_owned(other.get_type(), "in", Some(Self::TYPE))
+        }
+    }
+
+    fn add(&self, rhs: Value<'v>, heap: &'v Heap) -> Option<crate::Result<Value<'v>>> {
+        let rhs = rhs.downcast_ref::<Self>()?;
+        let mut bytes = Vec::with_capacity(self.bytes.len() + rhs.bytes.len());
+        bytes.extend_from_slice(&self.bytes);
+        bytes.extend_from_slice(&rhs.bytes);
+        Some(Ok(heap.alloc(StarlarkBytes::new(bytes))))
+    }
+
+    fn get_methods() -> Option<&'static Methods> {
+        bytes_methods()
+    }
+
+    fn get_type_starlark_repr() -> Ty {
+        Ty::starlark_value::<StarlarkBytes>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+    use crate::values::bytes::bytes_type::base64_encode;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(&[0xff, 0xfe]), "//4=");
+    }
+
+    #[test]
+    fn test_bytes_sequence() {
+        assert::all_true(
+            r#"
+len(bytes([104, 105])) == 2
+bytes([104, 105])[0] == 104
+bytes([104, 105])[-1] == 105
+list(bytes([1, 2, 3])) == [1, 2, 3]
+bytes([1, 2, 3])[1:] == bytes([2, 3])
+bytes([1, 2]) + bytes([3]) == bytes([1, 2, 3])
+2 in bytes([1, 2, 3])
+4 not in bytes([1, 2, 3])
+bytes([2, 3]) in bytes([1, 2, 3])
+bytes([3, 2]) not in bytes([1, 2, 3])
+bytes([1]) < bytes([1, 0])
+not bytes([])
+type(bytes([])) == "bytes"
+bytes([]) != ""
+"#,
+        );
+        assert::fail("bytes([1])[1]", "out of bound");
+        assert::fail("bytes([1]) + 'x'", "not supported");
+        assert::fail("256 in bytes([1])", "out of range");
+    }
+
+    #[test]
+    fn test_bytes_repr() {
+        assert::eq(
+            r#"repr("a\"b\\c\n\t".encode())"#,
+            r#"'b"a\\"b\\\\c\\n\\t"'"#,
+        );
+        assert::eq("repr(bytes([0, 127, 255]))", r#"'b"\\x00\\x7f\\xff"'"#);
+        assert::eq("str('hi'.encode())", r#"'b"hi"'"#);
+    }
+
+    #[test]
+    fn test_bytes_json() {
+        assert::eq("json.encode('foo'.encode())", r#"'"Zm9v"'"#);
+        assert::eq(
+            "json.encode({'k': bytes([0xff, 0xfe])})",
+            r#"'{"k":"//4="}'"#,
+        );
+    }
+
+    #[test]
+    fn test_bytes_hash() {
+        assert::eq("{'a'.encode(): 1}[bytes([97])]", "1");
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use starlark_derive::starlark_module;
+
+use crate as starlark;
+use crate::environment::GlobalsBuilder;
+use crate::values::bytes::StarlarkBytes;
+use crate::values::typing::StarlarkIter;
+use crate::values::Heap;
+use crate::values::UnpackValue;
+use crate::values::Value;
+use crate::values::ValueLike;
+use crate::values::ValueOfUnchecked;
+
+#[starlark_module]
+pub(crate) fn register_bytes(globals: &mut GlobalsBuilder) {
+    /// `bytes(x)`: construct an immutable sequence of bytes.
+    ///
+    /// `x` may be a string, which is encoded as UTF-8, another `bytes` value,
+    /// or an iterable of ints, each of which must be in the range `0..256`.
+    /// Indexing and iterating over bytes produce ints.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// list(bytes([104, 105])) == [104, 105]
+    /// bytes("hi") == "hi".encode()
+    /// bytes("hi")[0] == 104
+    /// bytes([104, 105]).decode() == "hi"
+    /// # "#);
+    /// ```
+    #[starlark(as_type = StarlarkBytes, speculative_exec_safe)]
+    fn bytes<'v>(
+        #[starlark(require = pos)] x: ValueOfUnchecked<'v, StarlarkIter<Value<'v>>>,
+        heap: &'v Heap,
+    ) -> starlark::Result<StarlarkBytes> {
+        if let Some(s) = x.get().unpack_str() {
+            return Ok(StarlarkBytes::new(s.as_bytes()));
+        }
+        if let Some(b) = x.get().downcast_ref::<StarlarkBytes>() {
+            return Ok(b.clone());
+        }
+        let mut bytes = Vec::new();
+        for v in x.get().iterate(heap)? {
+            bytes.push(StarlarkBytes::byte_from_int(i32::unpack_value_err(v)?)?);
+        }
+        Ok(StarlarkBytes::new(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_bytes_constructor() {
+        assert::all_true(
+            r#"
+bytes("") == bytes([])
+bytes("世") == bytes([0xe4, 0xb8, 0x96])
+bytes(bytes([1])) == bytes([1])
+bytes(range(3)) == bytes([0, 1, 2])
+isinstance(bytes([]), bytes)
+"#,
+        );
+        assert::fail("bytes([256])", "out of range");
+        assert::fail("bytes([-1])", "out of range");
+        assert::fail("bytes(['a'])", "Expected `int`");
+    }
+}
@@ -0,0 +1,65 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Methods for the `bytes` type.
+
+use starlark_derive::starlark_module;
+
+use crate as starlark;
+use crate::environment::MethodsBuilder;
+use crate::values::bytes::StarlarkBytes;
+
+#[starlark_module]
+pub(crate) fn bytes_methods(builder: &mut MethodsBuilder) {
+    /// `b.decode(encoding="utf-8")`: decode bytes into a string.
+    ///
+    /// Only UTF-8 is supported, and invalid UTF-8 sequences are an error.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// bytes([104, 105]).decode() == "hi"
+    /// "世界".encode().decode("utf-8") == "世界"
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn decode<'v>(
+        this: &'v StarlarkBytes,
+        #[starlark(require = pos, default = "utf-8")] encoding: &str,
+    ) -> anyhow::Result<&'v str> {
+        StarlarkBytes::check_encoding(encoding)?;
+        this.decode_utf8()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_decode() {
+        assert::eq("bytes([0xc3, 0xa9]).decode()", "'é'");
+        assert::eq("'abc'.encode('UTF8').decode('utf-8')", "'abc'");
+        assert::fail(
+            "bytes([0x61, 0xff]).decode()",
+            "Invalid UTF-8 in bytes at index 1",
+        );
+        assert::fail(
+            "bytes([]).decode('latin-1')",
+            "Unsupported encoding `latin-1`",
+        );
+    }
+}
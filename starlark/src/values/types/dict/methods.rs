@@ -194,7 +194,9 @@ pub(crate) fn dict_methods(registry: &mut MethodsBuilder) {
     /// ): returns and removes the first key/value pair of a dictionary.
     ///
     /// `D.popitem()` returns the first key/value pair, removing it from the
-    /// dictionary.
+    /// dictionary. Dictionaries preserve insertion order, so repeated calls
+    /// return the pairs in the order they were inserted, which is what the
+    /// Starlark spec requires (unlike Python, which pops the last pair).
     ///
     /// `popitem` fails if the dictionary is empty, frozen, or has active
     /// iterators.
@@ -283,7 +285,7 @@ pub(crate) fn dict_methods(registry: &mut MethodsBuilder) {
     /// `D.update([pairs][, name=value[, ...])` makes a sequence of key/value
     /// insertions into dictionary D, then returns `None.`
     ///
-    /// If the positional argument `pairs` is present, it must be `None`,
+    /// If the positional argument `pairs` is present, it must be
     /// another `dict`, or some other iterable.
     /// If it is another `dict`, then its key/value pairs are inserted into D.
     /// If it is an iterable, it must provide a sequence of pairs (or other
@@ -292,7 +294,8 @@ pub(crate) fn dict_methods(registry: &mut MethodsBuilder) {
     ///
     /// For each `name=value` argument present, the name is converted to a
     /// string and used as the key for an insertion into D, with its
-    /// corresponding value being `value`.
+    /// corresponding value being `value`. These insertions happen after those of
+    /// `pairs`, so a key given both ways gets the keyword argument value.
     ///
     /// `update` fails if the dictionary is frozen.
     ///
@@ -330,9 +333,13 @@ pub(crate) fn dict_methods(registry: &mut MethodsBuilder) {
                 }
             } else {
                 for v in pairs.iterate(heap)? {
-                    let mut it = v.iterate(heap)?;
                     // `StarlarkIterator` is fused.
-                    let (Some(k), Some(v), None) = (it.next(), it.next(), it.next()) else {
+                    let Some((k, v)) = v.iterate(heap).ok().and_then(|mut it| {
+                        match (it.next(), it.next(), it.next()) {
+                            (Some(k), Some(v), None) => Some((k, v)),
+                            _ => None,
+                        }
+                    }) else {
                         return Err(anyhow::anyhow!(
                             "dict.update expect a list of pairs or a dictionary as first argument, got a list of non-pairs.",
                         ).into());
@@ -416,7 +423,7 @@ load('d.star', 'D')
 
 D.update(D)
 "#,
-            "Immutable",
+            "Cannot mutate frozen value",
         );
     }
 
@@ -429,7 +436,86 @@ D.update(D)
 load('d.star', 'D')
 D.update(**D)
 "#,
-            "Immutable",
+            "Cannot mutate frozen value",
+        );
+    }
+
+    #[test]
+    fn test_dict_update_all_forms() {
+        assert::pass(
+            r#"
+d = {"a": 0}
+d.update({"b": 1, "c": 1}, c=2, d=2)
+assert_eq(d, {"a": 0, "b": 1, "c": 2, "d": 2})
+d.update([("d", 3), ["e", 3]], e=4, f=4)
+assert_eq(d, {"a": 0, "b": 1, "c": 2, "d": 3, "e": 4, "f": 4})
+d.update((("g", "h"),), a=5)
+assert_eq(d, {"a": 5, "b": 1, "c": 2, "d": 3, "e": 4, "f": 4, "g": "h"})
+"#,
+        );
+        assert::fail("{}.update([1])", "pairs");
+        assert::fail("{}.update([(1, 2, 3)])", "pairs");
+        assert::fail("{}.update(None)", "not supported");
+    }
+
+    #[test]
+    fn test_dict_popitem_insertion_order() {
+        assert::pass(
+            r#"
+d = {"c": 1, "a": 2}
+d["b"] = 3
+d["c"] = 4  # Updating a key keeps its position.
+d.pop("a")
+d["a"] = 5  # Re-inserting a key moves it to the end.
+popped = []
+for _ in range(len(d)):
+    popped.append(d.popitem())
+assert_eq(popped, [("c", 4), ("b", 3), ("a", 5)])
+assert_eq(d, {})
+"#,
         );
+        assert::fail("{}.popitem()", "empty dictionary");
+    }
+
+    #[test]
+    fn test_dict_setdefault_pop() {
+        assert::pass(
+            r#"
+d = {}
+assert_eq(d.setdefault("a"), None)
+assert_eq(d.setdefault("b", []), [])
+d.setdefault("b", []).append(1)
+assert_eq(d, {"a": None, "b": [1]})
+assert_eq(d.pop("b"), [1])
+assert_eq(d.pop("b", "x"), "x")
+"#,
+        );
+        assert::fail("{}.setdefault([])", "not hashable");
+        assert::fail(
+            "{1: 2}.pop('key')",
+            "Key `\"key\"` not found in dictionary `{1: 2}`",
+        );
+    }
+
+    #[test]
+    fn test_frozen_dict_methods() {
+        let mut a = Assert::new();
+        a.module("d.star", "D = {'x': 1}");
+        for code in [
+            "D.setdefault('x')",
+            "D.setdefault('y', 2)",
+            "D.update({'y': 2})",
+            "D.update([('y', 2)])",
+            "D.update(y = 2)",
+            "D.popitem()",
+            "D.pop('x')",
+            "D.pop('y', None)",
+            "D.clear()",
+        ] {
+            a.fail(
+                &format!("load('d.star', 'D')\n{code}"),
+                "Cannot mutate frozen value",
+            );
+        }
     }
 }
@@ -96,7 +96,7 @@ impl<'v> DictMut<'v> {
         #[inline(never)]
         fn error<'v>(x: Value<'v>) -> anyhow::Error {
             if x.downcast_ref::<DictGen<FrozenDictData>>().is_some() {
-                ValueError::CannotMutateImmutableValue.into()
+                ValueError::CannotMutateFrozenValue("dict").into()
             } else {
                 NotDictError(x.get_type()).into()
             }
@@ -52,6 +52,7 @@ use crate::values::layout::avalue::alloc_static;
 use crate::values::layout::avalue::AValueImpl;
 use crate::values::layout::avalue::AValueSimple;
 use crate::values::layout::heap::repr::AValueRepr;
+use crate::values::recursive_repr_or_json_guard::repr_len_exceeded;
 use crate::values::string::str_type::hash_string_value;
 use crate::values::type_repr::StarlarkTypeRepr;
 use crate::values::types::dict::dict_type::DictType;
@@ -380,7 +381,7 @@ impl<'v> DictLike<'v> for FrozenDictData {
 
     fn set_at(&self, _index: Hashed<Value<'v>>, _value: Value<'v>) -> crate::Result<()> {
         Err(crate::Error::new_other(
-            ValueError::CannotMutateImmutableValue,
+            ValueError::CannotMutateFrozenValue("dict"),
         ))
     }
 }
@@ -406,6 +407,10 @@ where
         r.push('{');
         for (i, (name, value)) in self.0.content().iter().enumerate() {
             if i != 0 {
+                if repr_len_exceeded(r) {
+                    r.push_str(", ...");
+                    break;
+                }
                 r.push_str(", ");
             }
             name.collect_repr(r);
@@ -27,6 +27,7 @@ use starlark_derive::NoSerialize;
 use crate as starlark;
 use crate::any::ProvidesStaticType;
 use crate::coerce::Coerce;
+use crate::docs::DocFunction;
 use crate::docs::DocItem;
 use crate::docs::DocMember;
 use crate::docs::DocProperty;
@@ -100,6 +101,25 @@ pub trait NativeFunc: Send + Sync + 'static {
     ) -> crate::Result<Value<'v>>;
 }
 
+/// [`NativeFunc`] implemented by a Rust closure.
+struct NativeClosure<F>(F);
+
+impl<F> NativeFunc for NativeClosure<F>
+where
+    F: for<'v> Fn(&mut Evaluator<'v, '_, '_>, &Arguments<'v, '_>) -> crate::Result<Value<'v>>
+        + Send
+        + Sync
+        + 'static,
+{
+    fn invoke<'v>(
+        &self,
+        eval: &mut Evaluator<'v, '_, '_>,
+        args: &Arguments<'v, '_>,
+    ) -> crate::Result<Value<'v>> {
+        (self.0)(eval, args)
+    }
+}
+
 /// Native method implementation.
 ///
 /// This trait is implemented by generated code and rarely needed to be implemented manually.
@@ -145,6 +165,80 @@ pub(crate) struct NativeFunction {
     pub(crate) special_builtin_function: Option<SpecialBuiltinFunction>,
 }
 
+impl NativeFunction {
+    /// Function with the given name implemented by a closure,
+    /// accepting any arguments as far as the typechecker is concerned.
+    pub(crate) fn from_closure<F>(name: &str, f: F) -> NativeFunction
+    where
+        F: for<'v> Fn(&mut Evaluator<'v, '_, '_>, &Arguments<'v, '_>) -> crate::Result<Value<'v>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        NativeFunction {
+            function: Box::new(NativeClosure(f)),
+            name: name.to_owned(),
+            as_type: None,
+            ty: Ty::any_callable(),
+            speculative_exec_safe: false,
+            docs: DocItem::Member(DocMember::Function(DocFunction::default())),
+            special_builtin_function: None,
+        }
+    }
+}
+
+/// Function implemented by a Rust closure which is not [`Send`] or [`Sync`],
+/// allocated with [`Heap::alloc_callable_no_freeze`]. It cannot be frozen.
+#[derive(
+    Derivative,
+    ProvidesStaticType,
+    Display,
+    NoSerialize,
+    Allocative,
+    Trace
+)]
+#[derivative(Debug)]
+#[display("{}", name)]
+pub(crate) struct NativeClosureNoFreeze {
+    #[derivative(Debug = "ignore")]
+    #[allocative(skip)]
+    #[trace(unsafe_ignore)]
+    #[allow(clippy::type_complexity)]
+    function: Box<
+        dyn for<'v> Fn(&mut Evaluator<'v, '_, '_>, &Arguments<'v, '_>) -> crate::Result<Value<'v>>,
+    >,
+    name: String,
+}
+
+impl NativeClosureNoFreeze {
+    pub(crate) fn new<F>(name: &str, f: F) -> NativeClosureNoFreeze
+    where
+        F: for<'v> Fn(&mut Evaluator<'v, '_, '_>, &Arguments<'v, '_>) -> crate::Result<Value<'v>>
+            + 'static,
+    {
+        NativeClosureNoFreeze {
+            function: Box::new(f),
+            name: name.to_owned(),
+        }
+    }
+}
+
+#[starlark_value(type = FUNCTION_TYPE)]
+impl<'v> StarlarkValue<'v> for NativeClosureNoFreeze {
+    fn invoke(
+        &self,
+        _me: Value<'v>,
+        args: &Arguments<'v, '_>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> crate::Result<Value<'v>> {
+        (self.function)(eval, args)
+    }
+
+    fn typechecker_ty(&self) -> Option<Ty> {
+        Some(Ty::any_callable())
+    }
+}
+
 impl AllocFrozenValue for NativeFunction {
     fn alloc_frozen_value(self, heap: &FrozenHeap) -> FrozenValue {
         heap.alloc_simple(self)
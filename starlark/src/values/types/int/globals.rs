@@ -23,10 +23,80 @@ use crate::environment::GlobalsBuilder;
 use crate::values::int::int_or_big::StarlarkInt;
 use crate::values::int::pointer_i32::PointerI32;
 use crate::values::types::num::value::NumRef;
+use crate::values::types::string::repr::string_repr;
 use crate::values::Heap;
 use crate::values::ValueOf;
 use crate::values::ValueOfUnchecked;
 
+#[derive(Debug, thiserror::Error)]
+enum IntError {
+    #[error("{0} is not a valid base, int() base must be >= 2 and <= 36")]
+    InvalidBase(i32),
+    #[error("Cannot parse {0} as an integer in base {1}")]
+    CannotParse(String, i32),
+    #[error("int() cannot convert non-string with explicit base '{0}'")]
+    NonStringWithBase(i32),
+}
+
+/// Parse the string argument of `int()`.
+///
+/// Leading and trailing whitespace is ignored, and single underscores may
+/// separate digits. When `base` is omitted or zero, the base is inferred from a
+/// `0b`, `0o` or `0x` prefix; an explicit zero base additionally rejects
+/// redundant leading zeros, as a literal would. An explicit non-zero base only
+/// accepts the prefix matching that base.
+fn parse_int_str(input: &str, base: Option<i32>) -> crate::Result<StarlarkInt> {
+    let base_or_zero = base.unwrap_or(0);
+    if base_or_zero == 1 || !(0..=36).contains(&base_or_zero) {
+        return Err(crate::Error::new_other(IntError::InvalidBase(base_or_zero)));
+    }
+    let cannot_parse = || {
+        let mut repr = String::new();
+        string_repr(input, &mut repr);
+        crate::Error::new_other(IntError::CannotParse(repr, base_or_zero))
+    };
+
+    let s = input.trim();
+    let (negate, s) = match s.as_bytes().first() {
+        Some(b'+') => (false, &s[1..]),
+        Some(b'-') => (true, &s[1..]),
+        _ => (false, s),
+    };
+    let prefix_base = match s.get(0..2) {
+        Some("0b" | "0B") => Some(2),
+        Some("0o" | "0O") => Some(8),
+        Some("0x" | "0X") => Some(16),
+        _ => None,
+    };
+    let (radix, digits) = match (base_or_zero as u32, prefix_base) {
+        (0, Some(p)) => (p, s[2..].strip_prefix('_').unwrap_or(&s[2..])),
+        (b, Some(p)) if b == p => (p, s[2..].strip_prefix('_').unwrap_or(&s[2..])),
+        (0, None) => (10, s),
+        (b, _) => (b, s),
+    };
+
+    // Digits must be non-empty, and underscores may only appear singly between digits.
+    if digits.is_empty()
+        || digits.starts_with('_')
+        || digits.ends_with('_')
+        || digits.contains("__")
+        || !digits.chars().all(|c| c == '_' || c.is_digit(radix))
+    {
+        return Err(cannot_parse());
+    }
+    let digits = digits.replace('_', "");
+    if base == Some(0)
+        && prefix_base.is_none()
+        && digits.starts_with('0')
+        && digits.bytes().any(|b| b != b'0')
+    {
+        return Err(cannot_parse());
+    }
+
+    let x = StarlarkInt::from_str_radix(&digits, radix).map_err(|_| cannot_parse())?;
+    Ok(if negate { -x } else { x })
+}
+
 #[starlark_module]
 pub(crate) fn register_int(globals: &mut GlobalsBuilder) {
     /// [int](
@@ -46,8 +116,11 @@ pub(crate) fn register_int(globals: &mut GlobalsBuilder) {
     /// base to use. The string may specify an arbitrarily large integer,
     /// whereas true integer literals are restricted to 64 bits.
     /// If a non-zero `base` argument is provided, the string is interpreted
-    /// in that base and no base prefix is permitted; the base argument may
-    /// specified by name.
+    /// in that base, and only the prefix matching that base is permitted;
+    /// the base argument may specified by name. With `base=0` the base is
+    /// inferred from the prefix, and a decimal number may not have leading
+    /// zeros. Surrounding whitespace is ignored, and single underscores may
+    /// separate digits.
     ///
     /// `int()` with no arguments returns 0.
     ///
@@ -62,6 +135,10 @@ pub(crate) fn register_int(globals: &mut GlobalsBuilder) {
     /// int('16', 10) == 16
     /// int('16', 8) == 14
     /// int('16', 16) == 22
+    /// int('ff', 16) == 255
+    /// int('0x1f', 0) == 31
+    /// int('1_000') == 1000
+    /// int(' -42 ') == -42
     /// int(0.0) == 0
     /// int(3.14) == 3
     /// int(-12345.6789) == -12345
@@ -91,72 +168,12 @@ pub(crate) fn register_int(globals: &mut GlobalsBuilder) {
         let num_or_bool = match a.typed {
             Either::Left(num_or_bool) => num_or_bool,
             Either::Right(s) => {
-                let base = base.unwrap_or(0);
-                if base == 1 || base < 0 || base > 36 {
-                    return Err(anyhow::anyhow!(
-                        "{} is not a valid base, int() base must be >= 2 and <= 36",
-                        base
-                    )
-                    .into());
-                }
-                let (negate, s) = {
-                    match s.chars().next() {
-                        Some('+') => (false, s.get(1..).unwrap()),
-                        Some('-') => (true, s.get(1..).unwrap()),
-                        _ => (false, s),
-                    }
-                };
-                let base = if base == 0 {
-                    match s.get(0..2) {
-                        Some("0b") | Some("0B") => 2,
-                        Some("0o") | Some("0O") => 8,
-                        Some("0x") | Some("0X") => 16,
-                        _ => 10,
-                    }
-                } else {
-                    base as u32
-                };
-                let s = match base {
-                    16 => {
-                        if s.starts_with("0x") || s.starts_with("0X") {
-                            s.get(2..).unwrap()
-                        } else {
-                            s
-                        }
-                    }
-                    8 => {
-                        if s.starts_with("0o") || s.starts_with("0O") {
-                            s.get(2..).unwrap()
-                        } else {
-                            s
-                        }
-                    }
-                    2 => {
-                        if s.starts_with("0b") || s.starts_with("0B") {
-                            s.get(2..).unwrap()
-                        } else {
-                            s
-                        }
-                    }
-                    _ => s,
-                };
-                // We already handled the sign above, so we are not trying to parse another sign.
-                if s.starts_with('-') || s.starts_with('+') {
-                    return Err(anyhow::anyhow!("Cannot parse `{}` as an integer", s,).into());
-                }
-
-                let x = StarlarkInt::from_str_radix(s, base)?;
-                let x = if negate { -x } else { x };
-                return Ok(ValueOfUnchecked::new(heap.alloc(x)));
+                return Ok(ValueOfUnchecked::new(heap.alloc(parse_int_str(s, base)?)));
             }
         };
 
         if let Some(base) = base {
-            return Err(anyhow::anyhow!(
-                "int() cannot convert non-string with explicit base '{}'",
-                base
-            )
-            .into());
+            return Err(crate::Error::new_other(IntError::NonStringWithBase(base)));
         }
 
         match num_or_bool {
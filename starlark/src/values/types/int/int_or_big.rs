@@ -48,7 +48,7 @@ use crate::values::Value;
 use crate::values::ValueLike;
 
 #[derive(Debug, thiserror::Error)]
-enum StarlarkIntError {
+pub(crate) enum StarlarkIntError {
     #[error("Float `{0}` cannot be represented as exact integer")]
     CannotRepresentAsExact(f64),
     #[error("Floor division by zero: {0} // {1}")]
@@ -61,6 +61,12 @@ enum StarlarkIntError {
     LeftShiftNegative,
     #[error("Negative right shift")]
     RightShiftNegative,
+    #[error("Integer overflow computing power")]
+    PowOverflow,
+    #[error("Modulo by zero: pow({0}, {1}, 0)")]
+    PowModuloByZero(StarlarkInt, StarlarkInt),
+    #[error("Negative exponent with modulus: pow({0}, {1}, {2})")]
+    PowModuloNegativeExponent(StarlarkInt, StarlarkInt, StarlarkInt),
 }
 
 #[derive(
@@ -404,6 +410,69 @@ impl<'v> StarlarkIntRef<'v> {
             StarlarkIntRef::Big(i) => StarlarkInt::from(i.get().abs()),
         }
     }
+
+    /// `pow(self, exp)` for a non-negative `exp`.
+    pub(crate) fn pow(self, exp: StarlarkIntRef) -> anyhow::Result<StarlarkInt> {
+        let base = self.to_big();
+        if base.magnitude().bits() <= 1 {
+            // 0, 1 and -1 stay small whatever the exponent.
+            let even = (exp.to_big() % 2u32).is_zero();
+            return Ok(if exp.is_zero() || (base.is_negative() && even) {
+                StarlarkInt::from(1)
+            } else {
+                self.to_owned()
+            });
+        }
+        // Limit the size of the result like `<<` does.
+        match exp.to_u64() {
+            Some(exp) if base.bits().saturating_mul(exp) <= 100_000 => {
+                Ok(StarlarkInt::from(base.pow(exp as u32)))
+            }
+            _ => Err(StarlarkIntError::PowOverflow.into()),
+        }
+    }
+
+    /// `pow(self, exp, modulus)`. The result has the sign of `modulus`.
+    pub(crate) fn pow_mod(
+        self,
+        exp: StarlarkIntRef,
+        modulus: StarlarkIntRef,
+    ) -> anyhow::Result<StarlarkInt> {
+        if modulus.is_zero() {
+            return Err(StarlarkIntError::PowModuloByZero(self.to_owned(), exp.to_owned()).into());
+        }
+        if exp.is_negative() {
+            return Err(StarlarkIntError::PowModuloNegativeExponent(
+                self.to_owned(),
+                exp.to_owned(),
+                modulus.to_owned(),
+            )
+            .into());
+        }
+        Ok(StarlarkInt::from(
+            self.to_big().modpow(&exp.to_big(), &modulus.to_big()),
+        ))
+    }
+
+    /// Round to a multiple of `10**digits`, halfway cases to even.
+    pub(crate) fn round_to_power_of_ten(self, digits: u32) -> StarlarkInt {
+        let a = self.to_big();
+        // `|a| < 2**bits <= 2**(3 * digits - 1) < 10**digits / 2`, so `a` rounds to zero:
+        // don't compute a power of ten larger than needed.
+        if u64::from(digits) * 3 > a.bits() {
+            return StarlarkInt::from(0);
+        }
+        let unit = BigInt::from(10).pow(digits);
+        // Floor modulo, so `rem` is in `[0, unit)`.
+        let rem = ((&a % &unit) + &unit) % &unit;
+        let down = &a - &rem;
+        let round_up = match (&rem * 2u32).cmp(&unit) {
+            Ordering::Less => false,
+            Ordering::Greater => true,
+            Ordering::Equal => (&down / &unit % 2u32) != BigInt::zero(),
+        };
+        StarlarkInt::from(if round_up { down + unit } else { down })
+    }
 }
 
 impl<'v> StarlarkTypeRepr for StarlarkIntRef<'v> {
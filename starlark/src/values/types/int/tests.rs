@@ -18,6 +18,8 @@
 #![cfg(test)]
 
 use crate::assert;
+use crate::assert::Assert;
+use crate::syntax::Dialect;
 use crate::values::int::pointer_i32::PointerI32;
 use crate::values::types::int::inline_int::InlineInt;
 use crate::values::FrozenValue;
@@ -42,6 +44,30 @@ fn test_arithmetic_operators() {
     );
 }
 
+#[test]
+fn test_true_division() {
+    for dialect in [Dialect::Standard, Dialect::Extended] {
+        let mut a = Assert::new();
+        a.dialect(&dialect);
+        a.all_true(
+            r#"
+5 / 2 == 2.5
+type(4 / 2) == "float"
+7.0 / 2 == 3.5
+7 / 2.0 == 3.5
+-7 / 2 == -3.5
+10000000000000000000000 / 10000000000000000000000 == 1.0
+-7 // 2 == -4
+type(-7 // 2) == "int"
+-7.0 // 2 == -4.0
+type(7 // 2.0) == "float"
+"#,
+        );
+        a.fail("1 / 0", "float division by zero");
+        a.fail("1.0 // 0", "Cannot divide by zero");
+    }
+}
+
 #[test]
 fn test_minus() {
     // `-i32::MIN` should overflow to `StarlarkBigInt`.
@@ -71,3 +97,88 @@ fn test_as_avalue_dyn() {
     // `get_type` calls `as_avalue_dyn` internally.
     assert_eq!("int", Value::new_int(InlineInt::MINUS_ONE).get_type());
 }
+
+#[test]
+fn test_int_literal_underscores() {
+    assert::all_true(
+        r#"
+1_000_000 == 1000000
+0x_ff == 255
+0b1010_1010 == 170
+0o7_7 == 63
+10_000_000_000 == 10000000000
+"#,
+    );
+}
+
+#[test]
+fn test_int_from_string() {
+    assert::all_true(
+        r#"
+int("ff", 16) == 255
+int("FF", 16) == 255
+int("0xff", 16) == 255
+int("0b1", 16) == 177
+int("z", 36) == 35
+int("1_000") == 1000
+int("  -42  ") == -42
+int("\t+7\n") == 7
+int("-0x10") == -16
+int("0x_1f", 0) == 31
+int("123456789012345678901234567890", 10) == 123456789012345678901234567890
+int("0123") == 123
+"#,
+    );
+    assert::fail(
+        r#"int("0x1f", 10)"#,
+        "Cannot parse \"0x1f\" as an integer in base 10",
+    );
+    assert::fail(r#"int("0b2", 2)"#, "Cannot parse");
+    assert::fail(r#"int("1__000")"#, "Cannot parse");
+    assert::fail(r#"int("_1")"#, "Cannot parse");
+    assert::fail(r#"int("1_")"#, "Cannot parse");
+    assert::fail(r#"int("- 1")"#, "Cannot parse");
+    assert::fail(r#"int("")"#, "Cannot parse");
+    assert::fail(r#"int("--1")"#, "Cannot parse");
+    assert::fail(r#"int("1", 1)"#, "not a valid base");
+    assert::fail(r#"int("1", 37)"#, "not a valid base");
+    assert::fail("int(1, 10)", "cannot convert non-string with explicit base");
+}
+
+#[test]
+fn test_int_from_string_base_zero() {
+    assert::all_true(
+        r#"
+int("0x1f", 0) == 31
+int("0X1F", 0) == 31
+int("0o17", 0) == 15
+int("0b101", 0) == 5
+int("-0b101", 0) == -5
+int("42", 0) == 42
+int("0", 0) == 0
+int("000", 0) == 0
+int("-0", 0) == 0
+"#,
+    );
+    // Leading zeros are ambiguous with legacy octal, as for literals.
+    assert::fail(
+        r#"int("0123", 0)"#,
+        "Cannot parse \"0123\" as an integer in base 0",
+    );
+    assert::fail(r#"int("0x", 0)"#, "Cannot parse");
+    assert::fail(r#"int("0x_", 0)"#, "Cannot parse");
+    assert::fail(r#"int("0o8", 0)"#, "Cannot parse");
+    assert::fail(r#"int("1f", 0)"#, "Cannot parse");
+}
+
+#[test]
+fn test_int_from_bool_and_float() {
+    assert::all_true(
+        r#"
+int(True) == 1
+int(False) == 0
+int(2.9) == 2
+int(-2.9) == -2
+"#,
+    );
+}
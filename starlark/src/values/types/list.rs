@@ -22,10 +22,12 @@ pub(crate) mod globals;
 mod list_type;
 pub(crate) mod methods;
 mod refs;
+pub(crate) mod sort;
 pub(crate) mod unpack;
 pub(crate) mod value;
 
 pub use crate::values::types::list::alloc::AllocList;
 pub use crate::values::types::list::list_type::ListType;
+pub use crate::values::types::list::refs::FrozenListRef;
 pub use crate::values::types::list::refs::ListRef;
 pub use crate::values::types::list::unpack::UnpackList;
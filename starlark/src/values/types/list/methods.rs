@@ -23,16 +23,24 @@ use starlark_syntax::convert_indices::convert_indices;
 
 use crate as starlark;
 use crate::environment::MethodsBuilder;
+use crate::eval::Evaluator;
+use crate::values::index::normalize_index;
 use crate::values::list::ListRef;
 use crate::values::none::NoneOr;
 use crate::values::none::NoneType;
+use crate::values::types::list::sort::sort_values;
 use crate::values::types::list::value::ListData;
 use crate::values::typing::StarlarkIter;
 use crate::values::Heap;
 use crate::values::Value;
-use crate::values::ValueError;
 use crate::values::ValueOfUnchecked;
 
+#[derive(Debug, thiserror::Error)]
+enum ListMethodsError {
+    #[error("List was modified by the `key` function during `sort`")]
+    ModifiedDuringSort,
+}
+
 #[starlark_module]
 pub(crate) fn list_methods(builder: &mut MethodsBuilder) {
     /// [list.append](
@@ -203,18 +211,20 @@ pub(crate) fn list_methods(builder: &mut MethodsBuilder) {
     /// `L.pop([index])` removes and returns the last element of the list L, or,
     /// if the optional index is provided, at that index.
     ///
-    /// `pop` fails if the index is negative or not less than the length of
-    /// the list, of if the list is frozen or has active iterators.
+    /// A negative index counts from the end of the list. `pop` fails if the
+    /// index is out of range, or if the list is frozen or has active iterators.
     ///
     /// ```
     /// # starlark::assert::is_true(r#"
-    /// x = [1, 2, 3]
+    /// x = [1, 2, 3, 4]
     /// # (
-    /// x.pop() == 3
+    /// x.pop() == 4
     /// # and
-    /// x.pop() == 2
+    /// x.pop(-3) == 1
     /// # and
-    /// x == [1]
+    /// x.pop() == 3
+    /// # and
+    /// x == [2]
     /// # )"#);
     /// ```
     fn pop<'v>(
@@ -222,11 +232,8 @@ pub(crate) fn list_methods(builder: &mut MethodsBuilder) {
         #[starlark(require = pos)] index: Option<i32>,
     ) -> anyhow::Result<Value<'v>> {
         let this = ListData::from_value_mut(this)?;
-        let index = index.unwrap_or_else(|| (this.len() as i32) - 1);
-        if index < 0 || index >= this.len() as i32 {
-            return Err(ValueError::IndexOutOfBound(index).into());
-        }
-        Ok(this.remove(index as usize))
+        let index = normalize_index(index.unwrap_or(-1), this.len())?;
+        Ok(this.remove(index))
     }
 
     /// [list.remove](
@@ -290,6 +297,52 @@ pub(crate) fn list_methods(builder: &mut MethodsBuilder) {
             Ok(NoneType)
         }
     }
+
+    /// [list.sort](
+    /// https://docs.python.org/3/library/stdtypes.html#list.sort
+    /// ): sort a list in place.
+    ///
+    /// `L.sort()` sorts the elements of the list L in ascending order,
+    /// and returns `None`. The sort algorithm is stable.
+    ///
+    /// The optional named parameters `key` and `reverse` have the same
+    /// meaning as for [`sorted`](#sorted). `key` is called once per element.
+    ///
+    /// `sort` fails if the list is frozen or has active iterators,
+    /// or if the `key` function modifies the list.
+    ///
+    /// ```
+    /// # starlark::assert::is_true(r#"
+    /// x = ["two", "three", "four"]
+    /// x.sort()
+    /// y = ["two", "three", "four"]
+    /// y.sort(key=len, reverse=True)
+    /// x == ["four", "three", "two"] and y == ["three", "four", "two"]
+    /// # "#);
+    /// ```
+    fn sort<'v>(
+        this: Value<'v>,
+        #[starlark(require = named)] key: Option<Value<'v>>,
+        #[starlark(require = named, default = false)] reverse: bool,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> starlark::Result<NoneType> {
+        // Fail on frozen lists before calling `key`.
+        ListData::from_value_mut(this)?;
+        let content = ListRef::from_value(this).unwrap().content().to_vec();
+        let sorted = sort_values(content.iter().copied(), key, reverse, eval)?;
+        let current = ListRef::from_value(this).unwrap().content();
+        if current.len() != content.len()
+            || current.iter().zip(&content).any(|(x, y)| !x.ptr_eq(*y))
+        {
+            return Err(starlark::Error::new_value(
+                ListMethodsError::ModifiedDuringSort,
+            ));
+        }
+        let this = ListData::from_value_mut(this)?;
+        this.clear();
+        this.extend(sorted, eval.heap());
+        Ok(NoneType)
+    }
 }
 
 #[cfg(test)]
@@ -310,6 +363,89 @@ mod tests {
         assert::fail("[True].index(True, 1, 0)", "not found");
     }
 
+    #[test]
+    fn test_insert_remove_index_spec() {
+        assert::pass(
+            r#"
+x = [1, 2, 3]
+x.insert(10, 4) # Beyond the end appends.
+x.insert(-10, 0) # Negative indices clamp to the start.
+x.insert(-1, 3.5)
+assert_eq(x, [0, 1, 2, 3, 3.5, 4])
+
+x = [1, 2, 1]
+x.remove(1) # Removes the first occurrence.
+assert_eq(x, [2, 1])
+
+x = ["a", "b", "c", "b"]
+assert_eq(x.index("b"), 1)
+assert_eq(x.index("b", 2), 3)
+assert_eq(x.index("b", -2), 3)
+assert_eq(x.index("a", -10, 1), 0)
+"#,
+        );
+        assert::fail("[1, 2].remove('x')", "Element '\"x\"' not found");
+        assert::fail("['a', 'b'].index('a', 1)", "not found");
+        assert::fail("['a', 'b'].index('b', 0, 1)", "not found");
+    }
+
+    #[test]
+    fn test_sort() {
+        assert::eq("[1, 1, 3, 4, 5, 9]", "x = [3, 1, 4, 1, 5, 9]; x.sort(); x");
+        assert::eq(
+            "[9, 5, 4, 3, 1, 1]",
+            "x = [3, 1, 4, 1, 5, 9]; x.sort(reverse=True); x",
+        );
+        // Stable, also when reversed.
+        assert::eq(
+            "[(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]",
+            "x = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')]; x.sort(key=lambda p: p[0]); x",
+        );
+        assert::eq(
+            "[(2, 'a'), (2, 'c'), (1, 'b'), (1, 'd')]",
+            "x = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')]; x.sort(key=lambda p: p[0], reverse=True); x",
+        );
+        // The key function is called once per element and can call other functions.
+        assert::pass(
+            r#"
+calls = []
+def neg(x):
+    return -x
+def key(x):
+    calls.append(x)
+    return neg(x)
+x = [1, 3, 2]
+x.sort(key=key)
+assert_eq(x, [3, 2, 1])
+assert_eq(sorted(calls), [1, 2, 3])
+"#,
+        );
+        assert::fail("x = [1, 'a']; x.sort()", "not supported");
+        assert::fail(
+            "def f(x): fail('bad key')\nx = [1, 2]\nx.sort(key=f)",
+            "bad key",
+        );
+    }
+
+    #[test]
+    fn test_sort_key_modifies_list() {
+        assert::fail(
+            "x = [3, 1, 2]\ndef key(v):\n    x.append(v)\n    return v\nx.sort(key=key)",
+            "List was modified by the `key` function during `sort`",
+        );
+        assert::fail(
+            "x = [3, 1, 2]\ndef key(v):\n    x[0] = 0\n    return v\nx.sort(key=key)",
+            "List was modified by the `key` function during `sort`",
+        );
+    }
+
+    #[test]
+    fn test_sort_frozen() {
+        let mut a = assert::Assert::new();
+        a.module("m", "x = [2, 1]");
+        a.fail("load('m', 'x')\nx.sort()", "Cannot mutate frozen value");
+    }
+
     #[test]
     fn recursive_list() {
         assert::is_true(
@@ -0,0 +1,74 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Sorting shared by `sorted` and `list.sort`.
+
+use std::cmp::Ordering;
+
+use crate::eval::Evaluator;
+use crate::values::Value;
+
+/// Stable sort of `values`, calling `key` (if given) exactly once per element.
+pub(crate) fn sort_values<'v>(
+    values: impl IntoIterator<Item = Value<'v>>,
+    key: Option<Value<'v>>,
+    reverse: bool,
+    eval: &mut Evaluator<'v, '_, '_>,
+) -> crate::Result<Vec<Value<'v>>> {
+    let mut it: Vec<(Value, Value)> = match key {
+        None => values.into_iter().map(|x| (x, x)).collect(),
+        Some(key) => {
+            let mut v = Vec::new();
+            for (i, el) in values.into_iter().enumerate() {
+                v.push((el, invoke_key(key, el, i, eval)?));
+            }
+            v
+        }
+    };
+
+    let mut compare_ok = Ok(());
+
+    it.sort_by(|x: &(Value, Value), y: &(Value, Value)| {
+        let ord_or_err = if reverse {
+            x.1.compare(y.1).map(Ordering::reverse)
+        } else {
+            x.1.compare(y.1)
+        };
+        match ord_or_err {
+            Ok(r) => r,
+            Err(e) => {
+                compare_ok = Err(e);
+                Ordering::Equal // does not matter
+            }
+        }
+    });
+
+    compare_ok?;
+
+    Ok(it.into_iter().map(|x| x.0).collect())
+}
+
+/// Call the `key` function of `sorted`, `min` or `max` on the element at `index`.
+pub(crate) fn invoke_key<'v>(
+    key: Value<'v>,
+    value: Value<'v>,
+    index: usize,
+    eval: &mut Evaluator<'v, '_, '_>,
+) -> crate::Result<Value<'v>> {
+    key.invoke_pos(&[value], eval)
+        .map_err(|e| e.with_message_prefix(format_args!("Calling `key` on element {index}")))
+}
@@ -49,11 +49,13 @@ use crate::values::comparison::equals_slice;
 use crate::values::error::ValueError;
 use crate::values::index::apply_slice;
 use crate::values::index::convert_index;
+use crate::values::index::convert_slice_indices;
 use crate::values::layout::avalue::alloc_static;
 use crate::values::layout::avalue::AValueFrozenList;
 use crate::values::layout::avalue::AValueImpl;
 use crate::values::layout::heap::repr::AValueRepr;
 use crate::values::list::ListRef;
+use crate::values::recursive_repr_or_json_guard::repr_len_exceeded;
 use crate::values::type_repr::StarlarkTypeRepr;
 use crate::values::AllocFrozenValue;
 use crate::values::AllocValue;
@@ -121,7 +123,7 @@ impl<'v> ListData<'v> {
         #[inline(never)]
         fn error<'v>(x: Value<'v>) -> anyhow::Error {
             if x.downcast_ref::<ListGen<FrozenListData>>().is_some() {
-                ValueError::CannotMutateImmutableValue.into()
+                ValueError::CannotMutateFrozenValue("list").into()
             } else {
                 NotListError(x.get_type()).into()
             }
@@ -346,10 +348,18 @@ impl Display for FrozenListData {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+enum ListError {
+    #[error("Cannot assign a sequence of size {0} to an extended slice of size {1}")]
+    ExtendedSliceSize(usize, usize),
+}
+
 // This trait need to be `pub(crate)` because `ListGen<T>` is.
 pub(crate) trait ListLike<'v>: Debug + Allocative {
     fn content(&self) -> &[Value<'v>];
     fn set_at(&self, i: usize, v: Value<'v>) -> crate::Result<()>;
+    fn remove_at(&self, i: usize) -> crate::Result<()>;
+    fn set_content(&self, content: Vec<Value<'v>>, heap: &'v Heap) -> crate::Result<()>;
 
     // These functions are unsafe for the same reason
     // `StarlarkValue` iterator functions are unsafe.
@@ -370,6 +380,19 @@ impl<'v> ListLike<'v> for ListData<'v> {
         Ok(())
     }
 
+    fn remove_at(&self, i: usize) -> crate::Result<()> {
+        self.check_can_mutate()?;
+        self.remove(i);
+        Ok(())
+    }
+
+    fn set_content(&self, content: Vec<Value<'v>>, heap: &'v Heap) -> crate::Result<()> {
+        self.check_can_mutate()?;
+        self.clear();
+        self.extend(content, heap);
+        Ok(())
+    }
+
     unsafe fn new_iter(&self, _me: Value<'v>) -> Value<'v> {
         self.content.get().inc_iter_count();
         self.content.get().to_value()
@@ -395,7 +418,19 @@ impl<'v> ListLike<'v> for FrozenListData {
 
     fn set_at(&self, _i: usize, _v: Value<'v>) -> crate::Result<()> {
         Err(crate::Error::new_other(
-            ValueError::CannotMutateImmutableValue,
+            ValueError::CannotMutateFrozenValue("list"),
+        ))
+    }
+
+    fn remove_at(&self, _i: usize) -> crate::Result<()> {
+        Err(crate::Error::new_other(
+            ValueError::CannotMutateFrozenValue("list"),
+        ))
+    }
+
+    fn set_content(&self, _content: Vec<Value<'v>>, _heap: &'v Heap) -> crate::Result<()> {
+        Err(crate::Error::new_other(
+            ValueError::CannotMutateFrozenValue("list"),
         ))
     }
 
@@ -454,6 +489,10 @@ where
         s.push('[');
         for (i, v) in self.0.content().iter().enumerate() {
             if i != 0 {
+                if repr_len_exceeded(s) {
+                    s.push_str(", ...");
+                    break;
+                }
                 s.push_str(", ");
             }
             v.collect_repr(s);
@@ -556,6 +595,71 @@ where
         self.0.set_at(i, alloc_value)
     }
 
+    fn set_slice(
+        &self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        new_value: Value<'v>,
+        heap: &'v Heap,
+    ) -> crate::Result<()> {
+        // Collect before mutating, so `x[a:b] = x` sees the original elements.
+        let new = new_value.iterate_collect(heap)?;
+        let xs = self.0.content();
+        let (start_i, stop_i, stride_i) =
+            convert_slice_indices(xs.len() as i32, start, stop, stride)?;
+        let content = if stride_i == 1 {
+            // A simple slice may change the length of the list.
+            let start = start_i as usize;
+            let stop = cmp::max(start_i, stop_i) as usize;
+            let mut content = Vec::with_capacity(xs.len() - (stop - start) + new.len());
+            content.extend_from_slice(&xs[..start]);
+            content.extend(new);
+            content.extend_from_slice(&xs[stop..]);
+            content
+        } else {
+            // An extended slice replaces elements one for one.
+            let indices = apply_slice(&(0..xs.len()).collect::<Vec<_>>(), start, stop, stride)?;
+            if indices.len() != new.len() {
+                return Err(crate::Error::new_other(ListError::ExtendedSliceSize(
+                    new.len(),
+                    indices.len(),
+                )));
+            }
+            let mut content = xs.to_vec();
+            for (i, v) in indices.into_iter().zip(new) {
+                content[i] = v;
+            }
+            content
+        };
+        self.0.set_content(content, heap)
+    }
+
+    fn del_at(&self, index: Value<'v>) -> crate::Result<()> {
+        let i = convert_index(index, self.0.content().len() as i32)? as usize;
+        self.0.remove_at(i)
+    }
+
+    fn del_slice(
+        &self,
+        start: Option<Value<'v>>,
+        stop: Option<Value<'v>>,
+        stride: Option<Value<'v>>,
+        heap: &'v Heap,
+    ) -> crate::Result<()> {
+        let xs = self.0.content();
+        let mut removed = vec![false; xs.len()];
+        for i in apply_slice(&(0..xs.len()).collect::<Vec<_>>(), start, stop, stride)? {
+            removed[i] = true;
+        }
+        let content = xs
+            .iter()
+            .zip(removed)
+            .filter_map(|(x, removed)| if removed { None } else { Some(*x) })
+            .collect();
+        self.0.set_content(content, heap)
+    }
+
     fn typechecker_ty(&self) -> Option<Ty> {
         Some(Ty::any_list())
     }
@@ -608,6 +712,113 @@ v == [1, 1, [2, 3]]
         );
     }
 
+    #[test]
+    fn test_slice_assignment() {
+        assert::pass(
+            r#"
+x = [0, 1, 2, 3, 4]
+x[1:3] = ["a", "b", "c"]
+assert_eq(x, [0, "a", "b", "c", 3, 4])
+
+x = [0, 1, 2, 3, 4]
+x[1:4] = []
+assert_eq(x, [0, 4])
+
+x = [0, 1, 2]
+x[-1:] = (8, 9)
+assert_eq(x, [0, 1, 8, 9])
+
+x = [0, 1, 2]
+x[3:1] = ["a"] # Empty slice inserts at the start index.
+assert_eq(x, [0, 1, 2, "a"])
+
+x = [0, 1, 2]
+x[:] = "ab".elems()
+assert_eq(x, ["a", "b"])
+
+x = [0, 1, 2, 3, 4, 5]
+x[::2] = ["a", "b", "c"]
+assert_eq(x, ["a", 1, "b", 3, "c", 5])
+
+x = [0, 1, 2, 3]
+x[::-1] = [10, 11, 12, 13]
+assert_eq(x, [13, 12, 11, 10])
+"#,
+        );
+        assert::fail(
+            "x = [0, 1, 2, 3]\nx[::2] = [1]",
+            "Cannot assign a sequence of size 1 to an extended slice of size 2",
+        );
+        assert::fail("x = [0, 1]\nx[::0] = []", "Slice step cannot be zero");
+        assert::fail("x = [0, 1]\nx[0:1] = 1", "not supported");
+        assert::fail("x = (0, 1)\nx[0:1] = [1]", "not supported");
+    }
+
+    #[test]
+    fn test_slice_assignment_alias() {
+        assert::pass(
+            r#"
+x = [0, 1, 2, 3]
+x[1:3] = x
+assert_eq(x, [0, 0, 1, 2, 3, 3])
+
+x = [0, 1, 2, 3]
+x[::-1] = x
+assert_eq(x, [3, 2, 1, 0])
+"#,
+        );
+        assert::fail(
+            "x = [0, 1]\nfor y in x:\n  x[0:1] = []",
+            "mutate an iterable",
+        );
+    }
+
+    #[test]
+    fn test_del() {
+        assert::pass(
+            r#"
+x = [0, 1, 2, 3, 4, 5]
+del x[0]
+del x[-1]
+assert_eq(x, [1, 2, 3, 4])
+
+x = [0, 1, 2, 3, 4, 5]
+del x[1:3]
+assert_eq(x, [0, 3, 4, 5])
+
+x = [0, 1, 2, 3, 4, 5]
+del x[::2]
+assert_eq(x, [1, 3, 5])
+
+x = [0, 1, 2, 3, 4, 5]
+del x[::-2]
+assert_eq(x, [0, 2, 4])
+
+x = [0, 1, 2]
+del x[5:]
+assert_eq(x, [0, 1, 2])
+
+def f(x):
+    del x[:]
+    return x
+assert_eq(f([1, 2]), [])
+"#,
+        );
+        assert::fail("x = [0, 1]\ndel x[2]", "out of bound");
+        assert::fail("x = [0, 1]\ndel x[-3]", "out of bound");
+        assert::fail("x = (0, 1)\ndel x[0]", "not supported");
+        assert::fail("x = [0, 1]\nfor y in x:\n  del x[0]", "mutate an iterable");
+    }
+
+    #[test]
+    fn test_del_frozen() {
+        let mut a = Assert::new();
+        a.module("m", "x = [0, 1, 2]");
+        a.fail("load('m', 'x')\ndel x[0]", "Cannot mutate frozen value");
+        a.fail("load('m', 'x')\ndel x[0:1]", "Cannot mutate frozen value");
+        a.fail("load('m', 'x')\nx[0:1] = []", "Cannot mutate frozen value");
+    }
+
     #[test]
     fn test_arithmetic_on_list() {
         assert::all_true(
@@ -644,10 +855,13 @@ def list_result():
     return [1, 2, 4]
 "#,
         );
-        a.fail("load('x','frozen_list')\nfrozen_list += [1]", "Immutable");
+        a.fail(
+            "load('x','frozen_list')\nfrozen_list += [1]",
+            "Cannot mutate frozen value",
+        );
         a.fail(
             "load('x','frozen_list_result')\nx = frozen_list_result()\nx += [1]",
-            "Immutable",
+            "Cannot mutate frozen value",
         );
         a.is_true("load('x','list_result')\nx = list_result()\nx += [8]\nx == [1, 2, 4, 8]");
     }
@@ -19,9 +19,45 @@ use starlark_derive::starlark_module;
 
 use crate as starlark;
 use crate::environment::GlobalsBuilder;
+use crate::values::none::NoneOr;
+use crate::values::types::int::int_or_big::StarlarkInt;
 use crate::values::types::num::value::Num;
 use crate::values::types::num::value::NumRef;
 
+#[derive(Debug, thiserror::Error)]
+enum MathError {
+    #[error("pow() with a modulus requires int arguments")]
+    PowModulusNotInt,
+    #[error("round(): rounded value is too large for a float")]
+    RoundOverflow,
+}
+
+/// Round `x` to `ndigits` decimal digits, halfway cases to even.
+fn round_float(x: f64, ndigits: i32) -> anyhow::Result<f64> {
+    if !x.is_finite() {
+        Ok(x)
+    } else if ndigits >= 0 {
+        // Formatting rounds the exact binary value, like Python does.
+        // Past 1100 digits every float is already exact.
+        if ndigits > 1100 {
+            return Ok(x);
+        }
+        Ok(format!("{:.*}", ndigits as usize, x).parse().unwrap())
+    } else {
+        let digits = ndigits.unsigned_abs();
+        // `10**309` is more than twice the largest float, so `x` rounds to zero.
+        if digits > 308 {
+            return Ok(0.0f64.copysign(x));
+        }
+        let unit = 10f64.powi(digits as i32);
+        let rounded = (x / unit).round_ties_even() * unit;
+        if rounded.is_infinite() {
+            return Err(MathError::RoundOverflow.into());
+        }
+        Ok(rounded)
+    }
+}
+
 #[starlark_module]
 pub(crate) fn register_num(globals: &mut GlobalsBuilder) {
     /// Take the absolute value of an int.
@@ -42,3 +78,187 @@ pub(crate) fn register_num(globals: &mut GlobalsBuilder) {
         }
     }
 }
+
+#[starlark_module]
+pub(crate) fn register_math(globals: &mut GlobalsBuilder) {
+    /// Round a number to `ndigits` decimal digits, rounding halfway cases to
+    /// the even neighbour, as Python does.
+    ///
+    /// Without `ndigits` the result is an int. With `ndigits` the result has
+    /// the type of `x`; a negative `ndigits` rounds to tens, hundreds and so on.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// round(2.5) == 2
+    /// round(3.5) == 4
+    /// round(-0.5) == 0
+    /// round(7) == 7
+    /// round(2.675, 2) == 2.67
+    /// round(0.125, 2) == 0.12
+    /// round(1250, -2) == 1200
+    /// round(1350.0, -2) == 1400.0
+    /// # "#);
+    /// ```
+    fn round(
+        #[starlark(require = pos)] x: NumRef,
+        #[starlark(require = pos, default = NoneOr::None)] ndigits: NoneOr<i32>,
+    ) -> anyhow::Result<Num> {
+        match (x, ndigits) {
+            (NumRef::Int(x), NoneOr::None) => Ok(Num::Int(x.to_owned())),
+            (NumRef::Float(x), NoneOr::None) => Ok(Num::Int(StarlarkInt::from_f64_exact(
+                x.0.round_ties_even(),
+            )?)),
+            (NumRef::Int(x), NoneOr::Other(ndigits)) => Ok(Num::Int(if ndigits >= 0 {
+                x.to_owned()
+            } else {
+                x.round_to_power_of_ten(ndigits.unsigned_abs())
+            })),
+            (NumRef::Float(x), NoneOr::Other(ndigits)) => {
+                Ok(Num::Float(round_float(x.0, ndigits)?))
+            }
+        }
+    }
+
+    /// Return the tuple `(a // b, a % b)`.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// divmod(7, 2) == (3, 1)
+    /// divmod(-7, 2) == (-4, 1)
+    /// divmod(7.5, 2) == (3.0, 1.5)
+    /// # "#);
+    /// ```
+    fn divmod(
+        #[starlark(require = pos)] a: NumRef,
+        #[starlark(require = pos)] b: NumRef,
+    ) -> anyhow::Result<(Num, Num)> {
+        Ok((a.floor_div(b)?, a.percent(b)?))
+    }
+
+    /// Raise `base` to the power `exp`, optionally modulo `modulus`.
+    ///
+    /// The result is an int when `base` and `exp` are ints and `exp` is not
+    /// negative, and a float otherwise. With `modulus` all arguments must be
+    /// ints, `exp` must not be negative, and the result has the sign of `modulus`.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// pow(2, 10) == 1024
+    /// pow(2, -1) == 0.5
+    /// pow(4.0, 0.5) == 2.0
+    /// pow(3, 4, 5) == 1
+    /// pow(-2, 3, 5) == 2
+    /// # "#);
+    /// ```
+    fn pow(
+        #[starlark(require = pos)] base: NumRef,
+        #[starlark(require = pos)] exp: NumRef,
+        #[starlark(require = pos, default = NoneOr::None)] modulus: NoneOr<NumRef>,
+    ) -> anyhow::Result<Num> {
+        match modulus {
+            NoneOr::None => base.pow(exp),
+            NoneOr::Other(modulus) => match (base, exp, modulus) {
+                (NumRef::Int(base), NumRef::Int(exp), NumRef::Int(modulus)) => {
+                    Ok(Num::Int(base.pow_mod(exp, modulus)?))
+                }
+                _ => Err(MathError::PowModulusNotInt.into()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_round() {
+        assert::all_true(
+            r#"
+round(0.5) == 0
+round(1.5) == 2
+round(-1.5) == -2
+type(round(1.0)) == "int"
+type(round(1.0, 0)) == "float"
+round(1e20) == 100000000000000000000
+round(1.0e300, 2) == 1.0e300
+round(1.5, 2000) == 1.5
+round(123.456, -1) == 120.0
+round(5.0, -1) == 0.0
+round(15.0, -1) == 20.0
+round(1e300, -400) == 0.0
+round(25, -1) == 20
+round(35, -1) == 40
+round(-25, -1) == -20
+round(-26, -1) == -30
+round(123456789123456789123456789, -20) == 123456800000000000000000000
+round(7, 3) == 7
+round(1, -2147483648) == 0
+round(-pow(10, 100), -2147483648) == 0
+round(pow(10, 100), -100) == pow(10, 100)
+round(pow(10, 100), -101) == 0
+round(1.5, -2147483648) == 0.0
+repr(round(-1.5, -2147483648)) == "-0.0"
+round(1.7e308, -309) == 0.0
+"#,
+        );
+        assert::fail(
+            "round(float('inf'))",
+            "cannot be represented as exact integer",
+        );
+        assert::fail(
+            "round(float('nan'))",
+            "cannot be represented as exact integer",
+        );
+        assert::fail("round(1.7e308, -308)", "rounded value is too large");
+        assert::fail("round('1')", "Type of parameter `x` doesn't match");
+    }
+
+    #[test]
+    fn test_divmod() {
+        assert::all_true(
+            r#"
+divmod(7, -2) == (-4, -1)
+divmod(-7, -2) == (3, -1)
+divmod(123456789123456789123456789, 10) == (12345678912345678912345678, 9)
+divmod(-7.5, 2) == (-4.0, 0.5)
+"#,
+        );
+        assert::fail("divmod(1, 0)", "Floor division by zero");
+        assert::fail("divmod(1.0, 0)", "Cannot divide by zero");
+    }
+
+    #[test]
+    fn test_pow() {
+        assert::all_true(
+            r#"
+pow(2, 0) == 1
+pow(0, 0) == 1
+pow(-1, 1000000001) == -1
+pow(-1, 123456789123456789123456788) == 1
+pow(0, 123456789123456789123456789) == 0
+pow(2, 100) == 1267650600228229401496703205376
+pow(-3, 3) == -27
+pow(2.0, 3) == 8.0
+type(pow(2.0, 3)) == "float"
+pow(10, -2) == 0.01
+pow(-8.0, 1.0) == -8.0
+pow(2, 3, -5) == -2
+pow(123456789123456789, 123456789, 1000000007) == pow(123456789123456789 % 1000000007, 123456789, 1000000007)
+"#,
+        );
+        assert::fail("pow(0, -1)", "Zero cannot be raised to a negative power");
+        assert::fail(
+            "pow(0.0, -1.5)",
+            "Zero cannot be raised to a negative power",
+        );
+        assert::fail(
+            "pow(-8, 0.5)",
+            "Negative number cannot be raised to a fractional power",
+        );
+        assert::fail("pow(2, 1000000)", "Integer overflow computing power");
+        assert::fail("pow(2, 3, 0)", "Modulo by zero");
+        assert::fail("pow(2, -1, 5)", "Negative exponent with modulus");
+        assert::fail("pow(2.0, 3, 5)", "requires int arguments");
+    }
+}
@@ -33,9 +33,13 @@ use crate::values::AllocValue;
 use crate::values::UnpackValue;
 
 #[derive(Debug, thiserror::Error)]
-enum NumError {
+pub(crate) enum NumError {
     #[error("float division by zero: {0} / {1}")]
     DivisionByZero(Num, Num),
+    #[error("Zero cannot be raised to a negative power: pow({0}, {1})")]
+    ZeroToNegativePower(Num, Num),
+    #[error("Negative number cannot be raised to a fractional power: pow({0}, {1})")]
+    NegativeToFractionalPower(Num, Num),
 }
 
 /// [`NumRef`] represents a numerical value that can be unpacked from a [`Value`].
@@ -140,6 +144,35 @@ impl<'v> NumRef<'v> {
         }
     }
 
+    /// Compare an int with a float by mathematical value.
+    ///
+    /// Big ints are not converted to float, so `2**53 + 1 > float(2**53)`,
+    /// although the int rounds to the float.
+    fn cmp_int_float(i: StarlarkIntRef, f: f64) -> Ordering {
+        match i {
+            // Small ints are exactly representable as floats.
+            StarlarkIntRef::Small(i) => StarlarkFloat::compare_impl(i.to_f64(), f),
+            StarlarkIntRef::Big(_) => {
+                if f.is_nan() {
+                    // NaN is greater than any other number.
+                    Ordering::Less
+                } else if f.is_infinite() {
+                    0.0.partial_cmp(&f).unwrap()
+                } else {
+                    let floor = f.floor();
+                    let floor_int = match StarlarkInt::from_f64_exact(floor) {
+                        Ok(floor_int) => floor_int,
+                        Err(_) => return StarlarkFloat::compare_impl(i.to_f64(), f),
+                    };
+                    match i.cmp(&floor_int.as_ref()) {
+                        Ordering::Equal if floor != f => Ordering::Less,
+                        ord => ord,
+                    }
+                }
+            }
+        }
+    }
+
     pub(crate) fn floor_div(self, other: NumRef) -> anyhow::Result<Num> {
         if let (NumRef::Int(a), NumRef::Int(b)) = (self, other) {
             a.floor_div(b).map(Num::Int)
@@ -155,6 +188,24 @@ impl<'v> NumRef<'v> {
             StarlarkFloat::percent_impl(self.as_float(), other.as_float()).map(Num::Float)
         }
     }
+
+    /// `pow(self, exp)`: an int if both are ints and `exp` is not negative,
+    /// a float otherwise.
+    pub(crate) fn pow(self, exp: NumRef) -> anyhow::Result<Num> {
+        if let (NumRef::Int(a), NumRef::Int(b)) = (self, exp) {
+            if b >= 0 {
+                return a.pow(b).map(Num::Int);
+            }
+        }
+        let (a, b) = (self.as_float(), exp.as_float());
+        if a == 0.0 && b < 0.0 {
+            Err(NumError::ZeroToNegativePower(self.to_owned(), exp.to_owned()).into())
+        } else if a < 0.0 && b.is_finite() && b.fract() != 0.0 {
+            Err(NumError::NegativeToFractionalPower(self.to_owned(), exp.to_owned()).into())
+        } else {
+            Ok(Num::Float(a.powf(b)))
+        }
+    }
 }
 
 impl<'v> From<f64> for NumRef<'v> {
@@ -166,11 +217,7 @@ impl<'v> From<f64> for NumRef<'v> {
 /// This is total eq per starlark spec, not Rust's partial eq.
 impl<'v> PartialEq for NumRef<'v> {
     fn eq(&self, other: &Self) -> bool {
-        if let (NumRef::Int(a), NumRef::Int(b)) = (self, other) {
-            a == b
-        } else {
-            StarlarkFloat::compare_impl(self.as_float(), other.as_float()) == Ordering::Equal
-        }
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -184,10 +231,11 @@ impl<'v> PartialOrd for NumRef<'v> {
 
 impl<'v> Ord for NumRef<'v> {
     fn cmp(&self, other: &Self) -> Ordering {
-        if let (NumRef::Int(a), NumRef::Int(b)) = (self, other) {
-            a.cmp(b)
-        } else {
-            StarlarkFloat::compare_impl(self.as_float(), other.as_float())
+        match (self, other) {
+            (NumRef::Int(a), NumRef::Int(b)) => a.cmp(b),
+            (NumRef::Int(a), NumRef::Float(b)) => NumRef::cmp_int_float(*a, b.0),
+            (NumRef::Float(a), NumRef::Int(b)) => NumRef::cmp_int_float(*b, a.0).reverse(),
+            (NumRef::Float(a), NumRef::Float(b)) => StarlarkFloat::compare_impl(a.0, b.0),
         }
     }
 }
@@ -230,6 +278,7 @@ mod tests {
     use num_bigint::BigInt;
 
     use super::*;
+    use crate::assert;
     use crate::values::types::int::inline_int::InlineInt;
     use crate::values::Value;
 
@@ -375,4 +424,34 @@ mod tests {
             NumRef::Float(StarlarkFloat(10.0))
         );
     }
+
+    #[test]
+    fn test_int_float_compare() {
+        assert::all_true(
+            r#"
+1 == 1.0
+1.0 == 1
+2 < 2.5
+-3 > -3.5
+3 + 0.5 == 3.5
+type(3 + 0.0) == "float"
+type(2 * 1.0) == "float"
+100000000000000000000 == 1e20
+1e20 == 100000000000000000000
+100000000000000000000 + 1 != 1e20
+100000000000000000000 + 1 > 1e20
+100000000000000000000 - 1 < 1e20
+1e20 < 100000000000000000000 + 1
+(1 << 53) + 1 > float(1 << 53)
+(1 << 53) + 1 < float(1 << 53) + 2
+float(1 << 53) < (1 << 53) + 1
+-(1 << 53) - 1 < -float(1 << 53)
+(1 << 1000) < float("inf")
+-(1 << 1000) > float("-inf")
+(1 << 1000) < float("nan")
+1000000000000000000000000000000 < 1000000000000000000000000000000 + 0.5
+sorted([(1 << 53) + 1, float(1 << 53), (1 << 53) - 1]) == [(1 << 53) - 1, float(1 << 53), (1 << 53) + 1]
+"#,
+        );
+    }
 }
@@ -0,0 +1,498 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Promises: placeholders for values known only after evaluation.
+//!
+//! The host allocates a promise with [`Heap::alloc_promise`], identified by a tag.
+//! Scripts can store promises in containers, pass them around and compare them
+//! (promises are only equal to themselves), but any attempt to use their contents,
+//! such as arithmetic, comparison or indexing, fails with an "unresolved promise" error.
+//!
+//! Converting a promise to a string with `str()`, `"%s" %`, `"{}".format()`, f-strings
+//! or `print()` fails the same way. `repr()` renders a promise as `<promise tag>`,
+//! and so do all conversions of containers holding promises, so they can be inspected.
+//!
+//! After evaluation the host records the values of the promises with
+//! [`PromiseResolutions::resolve`], and replaces the promises nested in a value
+//! with [`PromiseResolutions::materialize`].
+//!
+//! Promises freeze like any other value, so frozen modules can contain unresolved promises,
+//! and can be materialized on another heap.
+
+use std::cmp::Ordering;
+
+use allocative::Allocative;
+use starlark_derive::starlark_value;
+use starlark_derive::NoSerialize;
+use starlark_map::small_map::SmallMap;
+
+use crate as starlark;
+use crate::any::ProvidesStaticType;
+use crate::starlark_simple_value;
+use crate::values::dict::Dict;
+use crate::values::dict::DictRef;
+use crate::values::list::AllocList;
+use crate::values::list::ListRef;
+use crate::values::structs::value::Struct;
+use crate::values::structs::StructRef;
+use crate::values::tuple::AllocTuple;
+use crate::values::tuple::TupleRef;
+use crate::values::Heap;
+use crate::values::StarlarkValue;
+use crate::values::Value;
+use crate::values::ValueIdentity;
+use crate::values::ValueLike;
+
+#[derive(Debug, thiserror::Error)]
+enum PromiseError {
+    #[error("unresolved promise `{0}`")]
+    Unresolved(String),
+    #[error("cannot materialize promise `{0}` inside a cyclic value")]
+    Cycle(String),
+    #[error("cannot materialize promise `{0}` inside a value of type `{1}`")]
+    UnsupportedContainer(String, String),
+}
+
+/// A value provided by the host after evaluation, see the [module docs](self).
+#[derive(
+    Debug,
+    derive_more::Display,
+    ProvidesStaticType,
+    NoSerialize,
+    Allocative
+)]
+#[display("<promise {}>", tag)]
+pub struct StarlarkPromise {
+    tag: String,
+}
+
+starlark_simple_value!(StarlarkPromise);
+
+impl StarlarkPromise {
+    pub(crate) fn new(tag: &str) -> StarlarkPromise {
+        StarlarkPromise {
+            tag: tag.to_owned(),
+        }
+    }
+
+    /// The tag the promise was allocated with.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub(crate) fn unresolved<T>(&self) -> crate::Result<T> {
+        Err(crate::Error::new_other(PromiseError::Unresolved(
+            self.tag.clone(),
+        )))
+    }
+
+    /// Fail if `value` is a promise, which cannot be converted to a string like `str()` does.
+    pub(crate) fn check_str(value: Value) -> crate::Result<()> {
+        match value.downcast_ref::<StarlarkPromise>() {
+            Some(p) => p.unresolved(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[starlark_value(type = "promise")]
+impl<'v> StarlarkValue<'v> for StarlarkPromise {
+    fn compare(&self, _other: Value<'v>) -> crate::Result<Ordering> {
+        self.unresolved()
+    }
+
+    fn at(&self, _index: Value<'v>, _heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.unresolved()
+    }
+
+    fn iterate_collect(&self, _heap: &'v Heap) -> crate::Result<Vec<Value<'v>>> {
+        self.unresolved()
+    }
+
+    fn length(&self) -> crate::Result<i32> {
+        self.unresolved()
+    }
+
+    fn is_in(&self, _other: Value<'v>) -> crate::Result<bool> {
+        self.unresolved()
+    }
+
+    fn plus(&self, _heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.unresolved()
+    }
+
+    fn minus(&self, _heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.unresolved()
+    }
+
+    fn radd(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<crate::Result<Value<'v>>> {
+        Some(self.unresolved())
+    }
+
+    fn add(&self, _rhs: Value<'v>, _heap: &'v Heap) -> Option<crate::Result<Value<'v>>> {
+        Some(self.unresolved())
+    }
+
+    fn sub(&self, _other: Value<'v>, _heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.unresolved()
+    }
+
+    fn rmul(&self, _lhs: Value<'v>, _heap: &'v Heap) -> Option<crate::Result<Value<'v>>> {
+        Some(self.unresolved())
+    }
+
+    fn mul(&self, _rhs: Value<'v>, _heap: &'v Heap) -> Option<crate::Result<Value<'v>>> {
+        Some(self.unresolved())
+    }
+
+    fn div(&self, _other: Value<'v>, _heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.unresolved()
+    }
+
+    fn percent(&self, _other: Value<'v>, _heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.unresolved()
+    }
+
+    fn floor_div(&self, _other: Value<'v>, _heap: &'v Heap) -> crate::Result<Value<'v>> {
+        self.unresolved()
+    }
+}
+
+/// Values of promises, by tag, used to replace the promises in evaluation results.
+#[derive(Debug, Default)]
+pub struct PromiseResolutions<'v> {
+    resolved: SmallMap<String, Value<'v>>,
+}
+
+impl<'v> PromiseResolutions<'v> {
+    /// No promises resolved.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of the promises with the given tag.
+    /// Resolving the same tag again replaces the value.
+    pub fn resolve(&mut self, tag: &str, value: Value<'v>) {
+        self.resolved.insert(tag.to_owned(), value);
+    }
+
+    /// Replace the promises in `value` with their resolutions.
+    ///
+    /// Lists, tuples, dicts (values only, as promises are not hashable) and structs
+    /// are copied if they contain promises, other values are returned unchanged.
+    /// Resolutions are used as is, promises nested inside them are not replaced.
+    ///
+    /// Fails if a promise is not resolved, if it is part of a cycle (a copy would
+    /// not refer to itself), or if it is reachable through the attributes of
+    /// a value of another type, such as a record, which cannot be copied.
+    pub fn materialize(&self, value: Value<'v>, heap: &'v Heap) -> crate::Result<Value<'v>> {
+        Ok(self
+            .materialize_impl(value, heap, &mut Vec::new())?
+            .map(|(v, _)| v)
+            .unwrap_or(value))
+    }
+
+    /// Return `None` if the value contains no promises,
+    /// otherwise the copy and the tag of one of the promises.
+    fn materialize_impl(
+        &self,
+        value: Value<'v>,
+        heap: &'v Heap,
+        stack: &mut Vec<(ValueIdentity<'v>, bool)>,
+    ) -> crate::Result<Option<(Value<'v>, &'v str)>> {
+        if let Some(promise) = value.downcast_ref::<StarlarkPromise>() {
+            return match self.resolved.get(promise.tag()) {
+                Some(v) => Ok(Some((*v, promise.tag()))),
+                None => promise.unresolved(),
+            };
+        }
+        // Mark the values on the cycle, they are only an error if they contain promises.
+        if let Some(i) = stack.iter().position(|(id, _)| *id == value.identity()) {
+            for (_, cyclic) in &mut stack[i..] {
+                *cyclic = true;
+            }
+            return Ok(None);
+        }
+        stack.push((value.identity(), false));
+        let res = self.materialize_container(value, heap, stack);
+        let (_, cyclic) = stack.pop().unwrap();
+        match res? {
+            Some((_, tag)) if cyclic => {
+                Err(crate::Error::new_other(PromiseError::Cycle(tag.to_owned())))
+            }
+            res => Ok(res),
+        }
+    }
+
+    fn materialize_container(
+        &self,
+        value: Value<'v>,
+        heap: &'v Heap,
+        stack: &mut Vec<(ValueIdentity<'v>, bool)>,
+    ) -> crate::Result<Option<(Value<'v>, &'v str)>> {
+        if let Some(list) = ListRef::from_value(value) {
+            let items = list.content().to_vec();
+            Ok(self
+                .materialize_all(&items, heap, stack)?
+                .map(|(items, tag)| (heap.alloc(AllocList(items)), tag)))
+        } else if let Some(tuple) = TupleRef::from_value(value) {
+            Ok(self
+                .materialize_all(tuple.content(), heap, stack)?
+                .map(|(items, tag)| (heap.alloc(AllocTuple(items)), tag)))
+        } else if let Some(dict) = DictRef::from_value(value) {
+            let entries: Vec<_> = dict.iter_hashed().collect();
+            drop(dict);
+            let values: Vec<Value> = entries.iter().map(|(_, v)| *v).collect();
+            Ok(self
+                .materialize_all(&values, heap, stack)?
+                .map(|(values, tag)| {
+                    let mut content = SmallMap::with_capacity(entries.len());
+                    for ((k, _), v) in entries.into_iter().zip(values) {
+                        content.insert_hashed(k, v);
+                    }
+                    (heap.alloc(Dict::new(content)), tag)
+                }))
+        } else if let Some(s) = StructRef::from_value(value) {
+            let (names, values): (Vec<_>, Vec<_>) = s.iter().unzip();
+            Ok(self
+                .materialize_all(&values, heap, stack)?
+                .map(|(values, tag)| {
+                    (
+                        heap.alloc(Struct::new(names.into_iter().zip(values).collect())),
+                        tag,
+                    )
+                }))
+        } else {
+            // Other values are not copied, but must not hide promises in their attributes.
+            for attr in value.get_ref().dir_attr() {
+                let Some(v) = value.get_ref().get_attr(&attr, heap) else {
+                    continue;
+                };
+                if let Some((_, tag)) = self.materialize_impl(v, heap, stack)? {
+                    return Err(crate::Error::new_other(PromiseError::UnsupportedContainer(
+                        tag.to_owned(),
+                        value.get_type().to_owned(),
+                    )));
+                }
+            }
+            Ok(None)
+        }
+    }
+
+    /// Return `None` if none of the values contain promises.
+    fn materialize_all(
+        &self,
+        values: &[Value<'v>],
+        heap: &'v Heap,
+        stack: &mut Vec<(ValueIdentity<'v>, bool)>,
+    ) -> crate::Result<Option<(Vec<Value<'v>>, &'v str)>> {
+        let mut res: Option<(Vec<Value<'v>>, &'v str)> = None;
+        for (i, v) in values.iter().enumerate() {
+            if let Some((new, tag)) = self.materialize_impl(*v, heap, stack)? {
+                res.get_or_insert_with(|| (values[..i].to_vec(), tag))
+                    .0
+                    .push(new);
+            } else if let Some((res, _)) = &mut res {
+                res.push(*v);
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use starlark_derive::starlark_module;
+
+    use crate as starlark;
+    use crate::assert::Assert;
+    use crate::environment::Globals;
+    use crate::environment::GlobalsBuilder;
+    use crate::environment::Module;
+    use crate::eval::Evaluator;
+    use crate::syntax::AstModule;
+    use crate::syntax::Dialect;
+    use crate::values::promise::PromiseResolutions;
+    use crate::values::Heap;
+    use crate::values::OwnedFrozenValue;
+    use crate::values::Value;
+
+    fn eval_with_promises(module: &Module, code: &str) -> crate::Result<()> {
+        module.set("out", module.heap().alloc_promise("out"));
+        module.set("src", module.heap().alloc_promise("src"));
+        let ast = AstModule::parse("promise.star", code.to_owned(), &Dialect::Standard)?;
+        let mut eval = Evaluator::new(module);
+        eval.eval_module(ast, &Globals::extended_internal())?;
+        Ok(())
+    }
+
+    #[starlark_module]
+    fn promise_globals(globals: &mut GlobalsBuilder) {
+        fn promise<'v>(tag: &str, heap: &'v Heap) -> anyhow::Result<Value<'v>> {
+            Ok(heap.alloc_promise(tag))
+        }
+    }
+
+    #[test]
+    fn test_promise_in_starlark() {
+        let mut a = Assert::new();
+        a.globals_add(promise_globals);
+        a.pass(
+            r#"
+out = promise("out")
+assert_eq(type(out), "promise")
+assert_eq(repr([out]), "[<promise out>]")
+assert_true(out == out)
+assert_true(out != promise("out"))
+assert_true(out in [1, out])
+assert_eq(repr(out), "<promise out>")
+assert_eq("%r" % out, "<promise out>")
+assert_eq("{!r}".format(out), "<promise out>")
+paths = {"out": out, "srcs": [promise("src"), "b.c"]}
+"#,
+        );
+        for (code, msg) in [
+            ("str(promise('out'))", "unresolved promise `out`"),
+            ("'%s' % promise('out')", "unresolved promise `out`"),
+            ("'x%sy' % promise('out')", "unresolved promise `out`"),
+            ("'{}'.format(promise('out'))", "unresolved promise `out`"),
+            ("'x{}y'.format(promise('out'))", "unresolved promise `out`"),
+            ("'{:>3}'.format(promise('out'))", "unresolved promise `out`"),
+            ("p = promise('out')\nf'x{p}y'", "unresolved promise `out`"),
+            ("print(promise('out'))", "unresolved promise `out`"),
+            ("promise('out') + 'x'", "unresolved promise `out`"),
+            ("'x' + promise('src')", "unresolved promise `src`"),
+            (
+                "1 - promise('out')",
+                "Operation `-` not supported for types `int` and `promise`",
+            ),
+            ("promise('out') < 1", "unresolved promise `out`"),
+            ("len(promise('out'))", "unresolved promise `out`"),
+            ("promise('out')[0]", "unresolved promise `out`"),
+            ("{promise('out'): 1}", "not hashable"),
+        ] {
+            a.fail(code, msg);
+        }
+    }
+
+    #[test]
+    fn test_materialize() {
+        let module = Module::new();
+        eval_with_promises(
+            &module,
+            r#"
+s = struct(out = out, n = 1)
+res = {"out": out, "srcs": (src, "b.c"), "s": s, "plain": [1, 2]}
+"#,
+        )
+        .unwrap();
+        let heap = module.heap();
+        let res = module.get("res").unwrap();
+
+        let mut resolutions = PromiseResolutions::new();
+        resolutions.resolve("out", heap.alloc("buck-out/a.o"));
+        assert_eq!(
+            resolutions.materialize(res, heap).unwrap_err().to_string(),
+            "unresolved promise `src`"
+        );
+
+        resolutions.resolve("src", heap.alloc("a.c"));
+        let materialized = resolutions.materialize(res, heap).unwrap();
+        assert_eq!(
+            materialized.to_repr(),
+            r#"{"out": "buck-out/a.o", "srcs": ("a.c", "b.c"), "s": struct(out="buck-out/a.o", n=1), "plain": [1, 2]}"#
+        );
+        // Values without promises are not copied.
+        let plain = module.get("res").unwrap().at(heap.alloc("plain"), heap);
+        let plain_materialized = materialized.at(heap.alloc("plain"), heap);
+        assert!(plain.unwrap().ptr_eq(plain_materialized.unwrap()));
+        // The original value is unchanged.
+        assert!(res.to_repr().contains("<promise out>"));
+    }
+
+    #[test]
+    fn test_materialize_frozen() {
+        let res: OwnedFrozenValue = {
+            let module = Module::new();
+            eval_with_promises(&module, "res = [out, [src]]").unwrap();
+            module.freeze().unwrap().get("res").unwrap()
+        };
+        let heap = Heap::new();
+        let mut resolutions = PromiseResolutions::new();
+        resolutions.resolve("out", heap.alloc(1));
+        resolutions.resolve("src", heap.alloc(2));
+        let materialized = resolutions.materialize(res.value(), &heap).unwrap();
+        assert_eq!(materialized.to_repr(), "[1, [2]]");
+    }
+
+    #[test]
+    fn test_materialize_cycle() {
+        let module = Module::new();
+        eval_with_promises(
+            &module,
+            r#"
+res = [out]
+res.append([res])
+cycle = [1]
+cycle.append(cycle)
+no_promise = [out, cycle]
+"#,
+        )
+        .unwrap();
+        let heap = module.heap();
+        let mut resolutions = PromiseResolutions::new();
+        resolutions.resolve("out", heap.alloc(1));
+        assert_eq!(
+            resolutions
+                .materialize(module.get("res").unwrap(), heap)
+                .unwrap_err()
+                .to_string(),
+            "cannot materialize promise `out` inside a cyclic value"
+        );
+        // Cycles without promises are kept.
+        let materialized = resolutions
+            .materialize(module.get("no_promise").unwrap(), heap)
+            .unwrap();
+        assert_eq!(materialized.to_repr(), "[1, [1, [...]]]");
+    }
+
+    #[test]
+    fn test_materialize_record() {
+        let module = Module::new();
+        eval_with_promises(
+            &module,
+            r#"
+R = record(a = typing.Any)
+with_promise = [R(a = [out])]
+plain = R(a = 1)
+"#,
+        )
+        .unwrap();
+        let heap = module.heap();
+        let mut resolutions = PromiseResolutions::new();
+        resolutions.resolve("out", heap.alloc(1));
+        assert_eq!(
+            resolutions
+                .materialize(module.get("with_promise").unwrap(), heap)
+                .unwrap_err()
+                .to_string(),
+            "cannot materialize promise `out` inside a value of type `record`"
+        );
+        let plain = module.get("plain").unwrap();
+        assert!(resolutions.materialize(plain, heap).unwrap().ptr_eq(plain));
+    }
+}
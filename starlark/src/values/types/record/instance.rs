@@ -18,6 +18,7 @@
 use std::fmt;
 use std::fmt::Debug;
 use std::fmt::Display;
+use std::fmt::Write;
 
 use allocative::Allocative;
 use display_container::fmt_keyed_container;
@@ -108,6 +109,11 @@ impl<'v, V: ValueLike<'v>> StarlarkValue<'v> for RecordGen<V>
 where
     Self: ProvidesStaticType<'v>,
 {
+    fn collect_repr_cycle(&self, collector: &mut String) {
+        let name = self.record_type_name().unwrap_or("anon");
+        write!(collector, "record[{}](...)", name).unwrap();
+    }
+
     fn equals(&self, other: Value<'v>) -> crate::Result<bool> {
         match Record::from_value(other) {
             Some(other) if self.typ.equals(other.typ)? => {
@@ -712,7 +712,7 @@ load('s.star', 'S')
 
 S.update(S)
 "#,
-            "Immutable",
+            "Cannot mutate frozen value",
         );
     }
 }
@@ -73,7 +73,7 @@ impl<'v> SetMut<'v> {
         #[inline(never)]
         fn error<'v>(x: Value<'v>) -> anyhow::Error {
             if x.downcast_ref::<SetGen<FrozenSetData>>().is_some() {
-                ValueError::CannotMutateImmutableValue.into()
+                ValueError::CannotMutateFrozenValue("set").into()
             } else {
                 NotSetError(x.get_type()).into()
             }
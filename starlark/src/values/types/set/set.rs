@@ -90,6 +90,37 @@ f_fail_ct('not_int')
         );
     }
 
+    #[test]
+    fn test_set_dedup_in_insertion_order() {
+        assert::pass(
+            r#"
+s = set(["b", "a", "b", "c", "a"])
+assert_eq(list(s), ["b", "a", "c"])
+assert_eq(len(s), 3)
+assert_true("a" in s)
+assert_true("d" not in s)
+s.add("d")
+s.add("a")
+s.remove("b")
+assert_eq(list(s), ["a", "c", "d"])
+assert_eq(list(s | set(["e", "a"])), ["a", "c", "d", "e"])
+assert_eq(list(s & set(["d", "a"])), ["d", "a"])
+assert_eq(list(s - set(["c"])), ["a", "d"])
+assert_eq(list(s ^ set(["e", "c"])), ["a", "d", "e"])
+assert_eq(list(s.union(["e"])), ["a", "c", "d", "e"])
+assert_eq(list(s.intersection(["d", "c"])), ["c", "d"])
+"#,
+        );
+    }
+
+    #[test]
+    fn test_set_unhashable() {
+        assert::fail("set([[1]])", "not hashable");
+        assert::fail("set().add({})", "not hashable");
+        assert::fail("[] in set([1])", "not hashable");
+        assert::fail("set([1]).union([[2]])", "not hashable");
+    }
+
     #[test]
     fn test_set_type_as_type_run_time() {
         assert::fail(
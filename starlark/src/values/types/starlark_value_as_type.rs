@@ -60,6 +60,20 @@ impl<'v> StarlarkValue<'v> for StarlarkValueAsTypeStarlarkValue {
         Some((self.0)())
     }
 
+    fn get_attr(&self, attribute: &str, heap: &'v Heap) -> Option<Value<'v>> {
+        match attribute {
+            "type" => Some(heap.alloc((self.0)().as_name()?)),
+            _ => None,
+        }
+    }
+
+    fn dir_attr(&self) -> Vec<String> {
+        match (self.0)().as_name() {
+            Some(_) => vec!["type".to_owned()],
+            None => Vec::new(),
+        }
+    }
+
     fn documentation(&self) -> DocItem {
         (self.1)()
     }
@@ -98,8 +112,9 @@ impl Display for StarlarkValueAsTypeStarlarkValue {
 /// fn my_type_globals(globals: &mut GlobalsBuilder) {
 ///     // This can now be used like:
 ///     // ```
-///     // def f(x: Temperature): pass
+///     // def f(x: Temperature, y: list[Temperature] | None): pass
 ///     // ```
+///     // and `Temperature.type == "temperature"`.
 ///     const Temperature: StarlarkValueAsType<Temperature> = StarlarkValueAsType::new();
 /// }
 /// ```
@@ -266,4 +281,45 @@ noop(h)(1)
             r#"Value `1` of type `int` does not match the type annotation"#,
         );
     }
+
+    #[test]
+    fn test_type_attr() {
+        let mut a = Assert::new();
+        a.globals_add(compiler_args_globals);
+        a.eq("'compiler_args'", "CompilerArgs.type");
+        a.eq("['type']", "dir(CompilerArgs)");
+        a.eq("'compiler_args'", "type(compiler_args('x'))");
+    }
+
+    #[test]
+    fn test_union_and_container() {
+        let mut a = Assert::new();
+        a.globals_add(compiler_args_globals);
+        a.pass(
+            r#"
+def f(x: CompilerArgs | None, xs: list[CompilerArgs], d: dict[str, CompilerArgs]) -> CompilerArgs:
+    return xs[0]
+
+f(None, [compiler_args("a")], {})
+f(compiler_args("b"), [compiler_args("a")], {"c": compiler_args("c")})
+isinstance(compiler_args("a"), CompilerArgs) or fail()
+"#,
+        );
+        a.fail(
+            r#"
+def f(x: CompilerArgs): pass
+
+f("hello")
+"#,
+            "Value `hello` of type `string` does not match the type annotation `compiler_args` for argument `x`",
+        );
+        a.fail(
+            r#"
+def f(xs: list[CompilerArgs]): pass
+
+f([compiler_args("a"), "hello"])
+"#,
+            "does not match the type annotation `list[compiler_args]` for argument `xs`",
+        );
+    }
 }
@@ -19,6 +19,7 @@
 
 mod alloc_unpack;
 pub(crate) mod dot_format;
+pub(crate) mod format_spec;
 pub(crate) mod globals;
 pub(crate) mod intern;
 pub(crate) mod interpolation;
@@ -23,6 +23,8 @@ use starlark_syntax::dot_format_parser::FormatToken;
 
 use crate::collections::string_pool::StringPool;
 use crate::values::dict::Dict;
+use crate::values::promise::StarlarkPromise;
+use crate::values::string::format_spec::FormatSpec;
 use crate::values::Heap;
 use crate::values::StringValue;
 use crate::values::Value;
@@ -42,7 +44,7 @@ pub(crate) fn parse_format_one(s: &str) -> Option<(String, String)> {
                 conv: FormatConv::Str,
                 pos: _,
             } => break,
-            FormatToken::Capture { .. } => return None,
+            FormatToken::Capture { .. } | FormatToken::CaptureWithSpec { .. } => return None,
         }
     }
 
@@ -51,7 +53,7 @@ pub(crate) fn parse_format_one(s: &str) -> Option<(String, String)> {
         match parser.next().ok()? {
             Some(FormatToken::Text(text)) => after.push_str(text),
             Some(FormatToken::Escape(e)) => after.push_str(e.as_str()),
-            Some(FormatToken::Capture { .. }) => return None,
+            Some(FormatToken::Capture { .. } | FormatToken::CaptureWithSpec { .. }) => return None,
             None => break,
         }
     }
@@ -65,15 +67,16 @@ pub(crate) fn format_one<'v>(
     arg: Value<'v>,
     after: &str,
     heap: &'v Heap,
-) -> StringValue<'v> {
+) -> crate::Result<StringValue<'v>> {
     match StringValue::new(arg) {
-        Some(arg) => heap.alloc_str_concat3(before, &arg, after),
+        Some(arg) => Ok(heap.alloc_str_concat3(before, &arg, after)),
         None => {
+            StarlarkPromise::check_str(arg)?;
             let mut result = String::with_capacity(before.len() + after.len() + 10);
             result.push_str(before);
             arg.collect_repr(&mut result);
             result.push_str(after);
-            heap.alloc_str(&result)
+            Ok(heap.alloc_str(&result))
         }
     }
 }
@@ -125,7 +128,11 @@ impl<'v, T: Iterator<Item = Value<'v>>> FormatArgs<'v, T> {
                 self.by_index = true;
             }
             match self.args.get(index) {
-                None => Err(ValueError::IndexOutOfBound(index as i32).into()),
+                None => Err(ValueError::IndexOutOfBoundForLength {
+                    index: index as i32,
+                    len: self.args.len() as i32,
+                }
+                .into()),
                 Some(v) => Ok(*v),
             }
         }
@@ -135,7 +142,7 @@ impl<'v, T: Iterator<Item = Value<'v>>> FormatArgs<'v, T> {
 pub(crate) fn format<'v>(
     this: &str,
     args: impl Iterator<Item = Value<'v>>,
-    kwargs: Dict<'v>,
+    kwargs: &Dict<'v>,
     string_pool: &mut StringPool,
     heap: &'v Heap,
 ) -> anyhow::Result<StringValue<'v>> {
@@ -146,11 +153,15 @@ pub(crate) fn format<'v>(
         match token {
             FormatToken::Text(text) => result.push_str(text),
             FormatToken::Escape(e) => result.push_str(e.as_str()),
-            FormatToken::Capture {
+            FormatToken::Capture { capture, conv, .. } => {
+                format_capture(capture, Some(conv), "", &mut args, kwargs, &mut result)?
+            }
+            FormatToken::CaptureWithSpec {
                 capture,
                 conv,
-                pos: _,
-            } => format_capture(capture, conv, &mut args, &kwargs, &mut result)?,
+                spec,
+                ..
+            } => format_capture(capture, conv, spec, &mut args, kwargs, &mut result)?,
         }
     }
     let r = heap.alloc_str(&result);
@@ -160,26 +171,68 @@ pub(crate) fn format<'v>(
 
 fn format_capture<'v, T: Iterator<Item = Value<'v>>>(
     field: &str,
-    conv: FormatConv,
+    conv: Option<FormatConv>,
+    spec: &str,
     args: &mut FormatArgs<'v, T>,
-    kwargs: &Dict,
+    kwargs: &Dict<'v>,
     result: &mut String,
 ) -> anyhow::Result<()> {
-    let conv_s = |x: Value, result: &mut String| x.collect_str(result);
-    let conv_r = |x: Value, result: &mut String| x.collect_repr(result);
-    let conv: &dyn Fn(Value, &mut String) = match conv {
-        FormatConv::Str => &conv_s,
-        FormatConv::Repr => &conv_r,
+    let value = format_field(field, args, kwargs)?;
+    if conv != Some(FormatConv::Repr) {
+        StarlarkPromise::check_str(value).map_err(|e| e.into_anyhow())?;
+    }
+    if spec.is_empty() {
+        match conv {
+            None | Some(FormatConv::Str) => value.collect_str(result),
+            Some(FormatConv::Repr) => value.collect_repr(result),
+        }
+        return Ok(());
+    }
+
+    // Replacement fields in the spec take arguments after the value.
+    let mut expanded = String::new();
+    let spec = if spec.contains('{') {
+        let mut parser = FormatParser::new(spec);
+        while let Some(token) = parser.next()? {
+            match token {
+                FormatToken::Text(text) => expanded.push_str(text),
+                FormatToken::Escape(e) => expanded.push_str(e.as_str()),
+                FormatToken::Capture { capture, conv, .. } => {
+                    format_capture(capture, Some(conv), "", args, kwargs, &mut expanded)?
+                }
+                FormatToken::CaptureWithSpec {
+                    capture,
+                    conv,
+                    spec,
+                    ..
+                } => format_capture(capture, conv, spec, args, kwargs, &mut expanded)?,
+            }
+        }
+        &expanded
+    } else {
+        spec
     };
+    let spec = FormatSpec::parse(spec)?;
+    match conv {
+        None => spec.format_value(value, result),
+        Some(FormatConv::Str) => spec.format_str(&value.to_str(), result),
+        Some(FormatConv::Repr) => spec.format_str(&value.to_repr(), result),
+    }
+}
+
+/// Value of the field name of a replacement field.
+fn format_field<'v, T: Iterator<Item = Value<'v>>>(
+    field: &str,
+    args: &mut FormatArgs<'v, T>,
+    kwargs: &Dict<'v>,
+) -> anyhow::Result<Value<'v>> {
     if field.is_empty() {
-        conv(args.next_ordered()?, result);
-        Ok(())
+        args.next_ordered()
     } else if field.bytes().all(|c| c.is_ascii_digit()) {
         let i = usize::from_str(field).map_err(|e| {
             anyhow::anyhow!("Error parsing `{field}` as a format string index: {e}")
         })?;
-        conv(args.by_index(i)?, result);
-        Ok(())
+        args.by_index(i)
     } else {
         if let Some(x) = field.bytes().find(|c| match c {
             b'.' | b',' | b'[' | b']' => true,
@@ -192,10 +245,7 @@ fn format_capture<'v, T: Iterator<Item = Value<'v>>>(
         }
         match kwargs.get_str(field) {
             None => Err(ValueError::KeyNotFound(field.to_owned()).into()),
-            Some(v) => {
-                conv(v, result);
-                Ok(())
-            }
+            Some(v) => Ok(v),
         }
     }
 }
@@ -218,10 +268,10 @@ mod tests {
         capture: &str,
         conv: FormatConv,
         args: &mut FormatArgs<'v, T>,
-        kwargs: &Dict,
+        kwargs: &Dict<'v>,
     ) -> anyhow::Result<String> {
         let mut result = String::new();
-        format_capture(capture, conv, args, kwargs, &mut result)?;
+        format_capture(capture, Some(conv), "", args, kwargs, &mut result)?;
         Ok(result)
     }
 
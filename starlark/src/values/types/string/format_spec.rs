@@ -0,0 +1,607 @@
+/*
+ * Copyright 2018 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Format specification mini-language, shared by `str.format` and the `%` operator.
+//! Based on <https://docs.python.org/3/library/string.html#format-specification-mini-language>
+
+use std::fmt::Binary;
+use std::fmt::Display;
+use std::fmt::LowerHex;
+use std::fmt::Octal;
+use std::fmt::UpperHex;
+use std::fmt::Write;
+
+use num_traits::Signed;
+use thiserror::Error;
+
+use crate::values::float::float;
+use crate::values::string::str_type::STRING_TYPE;
+use crate::values::types::int::int_or_big::StarlarkIntRef;
+use crate::values::types::num::value::NumRef;
+use crate::values::Value;
+
+#[derive(Debug, Error)]
+enum FormatSpecError {
+    #[error("Invalid format spec `{0}`")]
+    Invalid(String),
+    #[error("Unknown format code `{code}` in format spec `{spec}` for value of type `{ty}`")]
+    UnknownCode {
+        code: char,
+        spec: String,
+        ty: &'static str,
+    },
+    #[error("Sign not allowed in string format spec `{0}`")]
+    SignNotAllowed(String),
+    #[error("Alternate form (#) not allowed in string format spec `{0}`")]
+    AlternateNotAllowed(String),
+    #[error("`=` alignment not allowed in string format spec `{0}`")]
+    AlignAfterSignNotAllowed(String),
+    #[error("Precision not allowed in integer format spec `{0}`")]
+    PrecisionNotAllowed(String),
+    #[error("Width or precision too large in format spec `{0}`")]
+    TooLarge(String),
+}
+
+/// Largest width and precision, the same as in Go, so a spec cannot ask for gigabytes of padding.
+const MAX_WIDTH_OR_PRECISION: usize = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Align {
+    /// `<`.
+    Left,
+    /// `>`.
+    Right,
+    /// `^`.
+    Center,
+    /// `=`, padding goes between the sign and the digits.
+    AfterSign,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Sign {
+    /// `-`, only negative numbers have a sign.
+    Minus,
+    /// `+`.
+    Plus,
+    /// ` `, positive numbers have a leading space.
+    Space,
+}
+
+/// Parsed `[[fill]align][sign][#][0][width][.precision][type]`.
+#[derive(Debug, Clone)]
+pub(crate) struct FormatSpec<'s> {
+    /// Source of the spec, for error messages.
+    pub(crate) spec: &'s str,
+    pub(crate) fill: Option<char>,
+    pub(crate) align: Option<Align>,
+    pub(crate) sign: Sign,
+    /// `#`, adds `0x`, `0o` or `0b` prefix to integers.
+    pub(crate) alternate: bool,
+    /// `0`, pads numbers with zeros after the sign.
+    pub(crate) zero: bool,
+    pub(crate) width: usize,
+    pub(crate) precision: Option<usize>,
+    pub(crate) ty: Option<char>,
+}
+
+impl<'s> FormatSpec<'s> {
+    pub(crate) fn new(spec: &'s str) -> FormatSpec<'s> {
+        FormatSpec {
+            spec,
+            fill: None,
+            align: None,
+            sign: Sign::Minus,
+            alternate: false,
+            zero: false,
+            width: 0,
+            precision: None,
+            ty: None,
+        }
+    }
+
+    /// Parse the part of a replacement field after the colon.
+    pub(crate) fn parse(spec: &'s str) -> anyhow::Result<FormatSpec<'s>> {
+        let invalid = || FormatSpecError::Invalid(spec.to_owned());
+        let too_large = || FormatSpecError::TooLarge(spec.to_owned());
+        let mut res = FormatSpec::new(spec);
+        let mut rem = spec;
+
+        let align = |c| match c {
+            '<' => Some(Align::Left),
+            '>' => Some(Align::Right),
+            '^' => Some(Align::Center),
+            '=' => Some(Align::AfterSign),
+            _ => None,
+        };
+        let mut chars = rem.chars();
+        match (chars.next(), chars.next()) {
+            (Some(fill), Some(a)) if align(a).is_some() => {
+                res.fill = Some(fill);
+                res.align = align(a);
+                rem = chars.as_str();
+            }
+            (Some(a), _) if align(a).is_some() => {
+                res.align = align(a);
+                rem = &rem[1..];
+            }
+            _ => {}
+        }
+
+        if let Some(sign) = match rem.as_bytes().first() {
+            Some(b'-') => Some(Sign::Minus),
+            Some(b'+') => Some(Sign::Plus),
+            Some(b' ') => Some(Sign::Space),
+            _ => None,
+        } {
+            res.sign = sign;
+            rem = &rem[1..];
+        }
+        if let Some(r) = rem.strip_prefix('#') {
+            res.alternate = true;
+            rem = r;
+        }
+        if let Some(r) = rem.strip_prefix('0') {
+            res.zero = true;
+            rem = r;
+        }
+        let (width, r) = parse_usize(rem).ok_or_else(too_large)?;
+        res.width = width.unwrap_or(0);
+        rem = r;
+        if let Some(r) = rem.strip_prefix('.') {
+            let (precision, r) = parse_usize(r).ok_or_else(too_large)?;
+            res.precision = Some(precision.ok_or_else(invalid)?);
+            rem = r;
+        }
+        let mut chars = rem.chars();
+        res.ty = chars.next();
+        match res.ty {
+            Some(c) if c.is_ascii_alphabetic() || c == '%' => {}
+            None => {}
+            Some(_) => return Err(invalid().into()),
+        }
+        if !chars.as_str().is_empty() {
+            return Err(invalid().into());
+        }
+        res.check_size()?;
+        Ok(res)
+    }
+
+    /// Check width and precision are at most [`MAX_WIDTH_OR_PRECISION`].
+    pub(crate) fn check_size(&self) -> anyhow::Result<()> {
+        if self.width > MAX_WIDTH_OR_PRECISION
+            || self.precision.is_some_and(|p| p > MAX_WIDTH_OR_PRECISION)
+        {
+            return Err(FormatSpecError::TooLarge(self.spec.to_owned()).into());
+        }
+        Ok(())
+    }
+
+    /// Format a value for `str.format`.
+    pub(crate) fn format_value(&self, value: Value, result: &mut String) -> anyhow::Result<()> {
+        if let Some(s) = value.unpack_str() {
+            self.format_str(s, result)
+        } else if let Some(num) = value.unpack_num() {
+            match (num, self.ty) {
+                (NumRef::Int(i), None | Some('d' | 'x' | 'X' | 'o' | 'b')) => {
+                    if self.precision.is_some() {
+                        return Err(
+                            FormatSpecError::PrecisionNotAllowed(self.spec.to_owned()).into()
+                        );
+                    }
+                    self.write_int(i, result);
+                    Ok(())
+                }
+                (_, None | Some('e' | 'E' | 'f' | 'F' | 'g' | 'G' | '%')) => {
+                    self.write_float(num.as_float(), result);
+                    Ok(())
+                }
+                (_, Some(code)) => Err(self.unknown_code(code, value.get_type())),
+            }
+        } else {
+            match self.ty {
+                None | Some('s') => self.format_str(&value.to_str(), result),
+                Some(code) => Err(self.unknown_code(code, value.get_type())),
+            }
+        }
+    }
+
+    fn unknown_code(&self, code: char, ty: &'static str) -> anyhow::Error {
+        FormatSpecError::UnknownCode {
+            code,
+            spec: self.spec.to_owned(),
+            ty,
+        }
+        .into()
+    }
+
+    /// Format a string for `str.format`, after a `!s` or `!r` conversion.
+    pub(crate) fn format_str(&self, s: &str, result: &mut String) -> anyhow::Result<()> {
+        if let Some(code) = self.ty.filter(|c| *c != 's') {
+            return Err(self.unknown_code(code, STRING_TYPE));
+        }
+        if self.sign != Sign::Minus {
+            return Err(FormatSpecError::SignNotAllowed(self.spec.to_owned()).into());
+        }
+        if self.alternate {
+            return Err(FormatSpecError::AlternateNotAllowed(self.spec.to_owned()).into());
+        }
+        if self.align == Some(Align::AfterSign) {
+            return Err(FormatSpecError::AlignAfterSignNotAllowed(self.spec.to_owned()).into());
+        }
+        self.write_str(s, result);
+        Ok(())
+    }
+
+    /// Write a string, truncated to the precision and padded to the width.
+    pub(crate) fn write_str(&self, s: &str, result: &mut String) {
+        let s = match self.precision {
+            Some(p) => match s.char_indices().nth(p) {
+                Some((i, _)) => &s[..i],
+                None => s,
+            },
+            None => s,
+        };
+        let fill = self.fill.unwrap_or(if self.zero { '0' } else { ' ' });
+        self.pad(fill, self.align.unwrap_or(Align::Left), "", s, result);
+    }
+
+    /// Write an integer with type `d`, `x`, `X`, `o` or `b`.
+    /// Precision, which is only accepted by the `%` operator, is the minimum number of digits.
+    pub(crate) fn write_int(&self, i: StarlarkIntRef, result: &mut String) {
+        let ty = self.ty.unwrap_or('d');
+        let (negative, mut digits) = match i {
+            StarlarkIntRef::Small(i) => {
+                let i = i.to_i32() as i64;
+                (i < 0, radix_digits(i.unsigned_abs(), ty))
+            }
+            StarlarkIntRef::Big(i) => {
+                let i = i.get();
+                (i.is_negative(), radix_digits(i.magnitude(), ty))
+            }
+        };
+        if let Some(p) = self.precision {
+            if digits.len() < p {
+                digits.insert_str(0, &"0".repeat(p - digits.len()));
+            }
+        }
+        let prefix = match ty {
+            'x' if self.alternate => "0x",
+            'X' if self.alternate => "0X",
+            'o' if self.alternate => "0o",
+            'b' if self.alternate => "0b",
+            _ => "",
+        };
+        self.write_number(negative, prefix, &digits, result);
+    }
+
+    /// Write a float with type `e`, `E`, `f`, `F`, `g`, `G`, `%` or no type.
+    ///
+    /// Without precision `g` and no type format like `str`,
+    /// and non-finite values are `nan`, `+inf` and `-inf`, as elsewhere in Starlark.
+    pub(crate) fn write_float(&self, f: f64, result: &mut String) {
+        let ty = self.ty;
+        let upper = matches!(ty, Some('E' | 'F' | 'G'));
+        let exp = if upper { 'E' } else { 'e' };
+        if f.is_nan() {
+            let body = if upper { "NAN" } else { "nan" };
+            return self.write_number(false, "", body, result);
+        }
+        if f.is_infinite() {
+            let body = if upper { "INF" } else { "inf" };
+            let prefix = if f > 0.0 && self.sign == Sign::Minus {
+                "+"
+            } else {
+                ""
+            };
+            return self.write_number(f < 0.0, prefix, body, result);
+        }
+        let a = f.abs();
+        let precision = self.precision.unwrap_or(6);
+        let mut body = String::new();
+        match (ty, self.precision) {
+            (Some('f' | 'F'), None) => float::write_decimal(&mut body, a).unwrap(),
+            (Some('f' | 'F'), Some(_)) => write!(body, "{:.precision$}", a).unwrap(),
+            (Some('e' | 'E'), _) => write_exponent(&mut body, a, precision, exp),
+            (Some('%'), _) => write!(body, "{:.precision$}%", a * 100.0).unwrap(),
+            (None, None) => float::write_compact(&mut body, a, exp).unwrap(),
+            _ => {
+                write_general(&mut body, a, precision, exp, self.alternate);
+                if ty.is_none() && !body.contains(['.', exp]) {
+                    body.push_str(".0");
+                }
+            }
+        }
+        self.write_number(f.is_sign_negative(), "", &body, result);
+    }
+
+    fn write_number(&self, negative: bool, prefix: &str, digits: &str, result: &mut String) {
+        let sign = match (negative, self.sign) {
+            (true, _) => "-",
+            (false, Sign::Minus) => "",
+            (false, Sign::Plus) => "+",
+            (false, Sign::Space) => " ",
+        };
+        let prefix = format!("{sign}{prefix}");
+        let (fill, align) = match (self.fill, self.align) {
+            (fill, Some(align)) => (fill.unwrap_or(' '), align),
+            (Some(fill), None) => (fill, Align::Right),
+            (None, None) if self.zero => ('0', Align::AfterSign),
+            (None, None) => (' ', Align::Right),
+        };
+        self.pad(fill, align, &prefix, digits, result);
+    }
+
+    fn pad(&self, fill: char, align: Align, prefix: &str, body: &str, result: &mut String) {
+        let len = prefix.chars().count() + body.chars().count();
+        let padding = self.width.saturating_sub(len);
+        let (before, after) = match align {
+            Align::Left => (0, padding),
+            Align::Right | Align::AfterSign => (padding, 0),
+            Align::Center => (padding / 2, padding - padding / 2),
+        };
+        if align == Align::AfterSign {
+            result.push_str(prefix);
+            result.extend(std::iter::repeat_n(fill, before));
+        } else {
+            result.extend(std::iter::repeat_n(fill, before));
+            result.push_str(prefix);
+        }
+        result.push_str(body);
+        result.extend(std::iter::repeat_n(fill, after));
+    }
+}
+
+/// Parse leading decimal digits, returning `None` for an overflow.
+fn parse_usize(s: &str) -> Option<(Option<usize>, &str)> {
+    let end = s
+        .bytes()
+        .position(|c| !c.is_ascii_digit())
+        .unwrap_or(s.len());
+    if end == 0 {
+        Some((None, s))
+    } else {
+        Some((Some(s[..end].parse().ok()?), &s[end..]))
+    }
+}
+
+fn radix_digits<T: Display + LowerHex + UpperHex + Octal + Binary>(i: T, ty: char) -> String {
+    match ty {
+        'x' => format!("{i:x}"),
+        'X' => format!("{i:X}"),
+        'o' => format!("{i:o}"),
+        'b' => format!("{i:b}"),
+        _ => format!("{i}"),
+    }
+}
+
+/// Write a non-negative finite float as `1.500000e+02`.
+fn write_exponent(output: &mut String, a: f64, precision: usize, exp: char) {
+    let s = format!("{:.precision$e}", a);
+    let (mantissa, e) = s.split_once('e').unwrap();
+    let e: i32 = e.parse().unwrap();
+    write!(
+        output,
+        "{mantissa}{exp}{}{:02}",
+        if e < 0 { '-' } else { '+' },
+        e.abs()
+    )
+    .unwrap();
+}
+
+/// Write a non-negative finite float in fixed or exponent notation depending on its magnitude,
+/// with `precision` significant digits, without trailing zeros unless `alternate`.
+fn write_general(output: &mut String, a: f64, precision: usize, exp: char, alternate: bool) {
+    let precision = precision.max(1);
+    let e: i32 = if a == 0.0 {
+        0
+    } else {
+        let s = format!("{:.*e}", precision - 1, a);
+        s.split_once('e').unwrap().1.parse().unwrap()
+    };
+    let mut s = String::new();
+    if -4 <= e && e < precision as i32 {
+        write!(s, "{:.*}", (precision as i32 - 1 - e) as usize, a).unwrap();
+    } else {
+        write_exponent(&mut s, a, precision - 1, exp);
+    }
+    if !alternate {
+        let (mantissa, exponent) = match s.find(exp) {
+            Some(i) => s.split_at(i),
+            None => (s.as_str(), ""),
+        };
+        let mantissa = if mantissa.contains('.') {
+            mantissa.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            mantissa
+        };
+        output.push_str(mantissa);
+        output.push_str(exponent);
+    } else {
+        output.push_str(&s);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assert;
+
+    #[test]
+    fn test_format_spec_table() {
+        let cases = [
+            // Strings.
+            ("'{:>6}'.format('ab')", "    ab"),
+            ("'{:<6}|'.format('ab')", "ab    |"),
+            ("'{:^6}'.format('ab')", "  ab  "),
+            ("'{:^7}'.format('ab')", "  ab   "),
+            ("'{:*^7}'.format('ab')", "**ab***"),
+            ("'{:6}|'.format('ab')", "ab    |"),
+            ("'{:.2}'.format('abcdef')", "ab"),
+            ("'{:>5.3s}'.format('abcdef')", "  abc"),
+            ("'{:05}'.format('ab')", "ab000"),
+            ("'{:1}'.format('abc')", "abc"),
+            ("'{:>4}'.format('日本')", "  日本"),
+            ("'{!r:>6}'.format('ab')", "  \"ab\""),
+            ("'{!r:<6}|'.format(1)", "1     |"),
+            ("'{!s:4}|'.format(1)", "1   |"),
+            ("'{:>6}'.format(None)", "  None"),
+            ("'{:>8}'.format([1, 2])", "  [1, 2]"),
+            // Integers.
+            ("'{:5}'.format(42)", "   42"),
+            ("'{:<5}|'.format(42)", "42   |"),
+            ("'{:04d}'.format(42)", "0042"),
+            ("'{:04d}'.format(-42)", "-042"),
+            ("'{:+d}'.format(42)", "+42"),
+            ("'{: d}'.format(42)", " 42"),
+            ("'{:+d}'.format(-42)", "-42"),
+            ("'{:=+6}'.format(42)", "+   42"),
+            ("'{:x}'.format(255)", "ff"),
+            ("'{:X}'.format(255)", "FF"),
+            ("'{:#x}'.format(255)", "0xff"),
+            ("'{:#06x}'.format(255)", "0x00ff"),
+            ("'{:o}'.format(8)", "10"),
+            ("'{:#o}'.format(-8)", "-0o10"),
+            ("'{:b}'.format(5)", "101"),
+            ("'{:#b}'.format(5)", "0b101"),
+            ("'{:08b}'.format(5)", "00000101"),
+            ("'{:x}'.format(-2147483648)", "-80000000"),
+            (
+                "'{:d}'.format(12345678901234567890)",
+                "12345678901234567890",
+            ),
+            ("'{:x}'.format(-12345678901234567890)", "-ab54a98ceb1f0ad2"),
+            // Floats, and integers formatted as floats.
+            ("'{}'.format(1.5)", "1.5"),
+            ("'{:8}'.format(1.5)", "     1.5"),
+            ("'{:.2f}'.format(3.14159)", "3.14"),
+            ("'{:8.3f}'.format(-3.14159)", "  -3.142"),
+            ("'{:08.3f}'.format(-3.14159)", "-003.142"),
+            ("'{:f}'.format(1)", "1.000000"),
+            ("'{:.0f}'.format(2.5)", "2"),
+            ("'{:e}'.format(12345.678)", "1.234568e+04"),
+            ("'{:.2E}'.format(0.000123)", "1.23E-04"),
+            ("'{:.3g}'.format(3.14159)", "3.14"),
+            ("'{:.3g}'.format(1234567.0)", "1.23e+06"),
+            ("'{:.3g}'.format(0.0001234)", "0.000123"),
+            ("'{:.3g}'.format(0.00001234)", "1.23e-05"),
+            ("'{:#.3g}'.format(1.0)", "1.00"),
+            ("'{:g}'.format(1e6)", "1e+06"),
+            ("'{:g}'.format(123)", "123"),
+            ("'{:g}'.format(3.14159265)", "3.14159"),
+            ("'{:G}'.format(0.00001)", "1E-05"),
+            ("'{}'.format(123.0)", "123.0"),
+            ("'{:.3}'.format(1.0)", "1.0"),
+            ("'{:.3}'.format(3.14159)", "3.14"),
+            ("'{:.1%}'.format(0.256)", "25.6%"),
+            ("'{:+.1f}'.format(2.0)", "+2.0"),
+            ("'{:f}'.format(float('inf'))", "+inf"),
+            ("'{:>6f}'.format(float('-inf'))", "  -inf"),
+            ("'{:F}'.format(float('nan'))", "NAN"),
+            ("'{:.1f}'.format(-0.0)", "-0.0"),
+            // Nested fields in the spec.
+            ("'{:>{}}'.format('ab', 5)", "   ab"),
+            ("'{0:>{1}}'.format('ab', 4)", "  ab"),
+            ("'{x:{fill}^{w}}'.format(x='ab', fill='-', w=6)", "--ab--"),
+            ("'{:{}.{}f}'.format(3.14159, 7, 2)", "   3.14"),
+            ("'{{:>5}}'.format()", "{:>5}"),
+            // f-strings.
+            (
+                "[f'{x:>4}|{x!r:<5}|{y:.2f}' for x, y in [('a', 0.5)]][0]",
+                "   a|\"a\"  |0.50",
+            ),
+            // `%` operator.
+            ("'%5d|%-5d|' % (42, 42)", "   42|42   |"),
+            ("'%05d' % -42", "-0042"),
+            ("'%+d % d' % (1, 1)", "+1  1"),
+            ("'%.3d' % 7", "007"),
+            ("'%x %X %o' % (255, 255, 8)", "ff FF 10"),
+            ("'%#x %#o' % (255, 8)", "0xff 0o10"),
+            ("'%6s|%-6s|' % ('ab', 'ab')", "    ab|ab    |"),
+            ("'%.2s' % 'abc'", "ab"),
+            ("'%5r' % 'a'", "  \"a\""),
+            ("'%5.2f' % 3.14159", " 3.14"),
+            ("'%-8.2f|' % 3.14159", "3.14    |"),
+            ("'%08.2f' % -3.14159", "-0003.14"),
+            ("'%.2e' % 12345.678", "1.23e+04"),
+            ("'%.3g' % 1234567.0", "1.23e+06"),
+            ("'%10.3E' % 0.5", " 5.000E-01"),
+            ("'%d%%' % 50", "50%"),
+            ("'%5s' % 1", "    1"),
+        ];
+        for (program, expected) in cases {
+            assert::eq(program, &format!("{expected:?}"));
+        }
+    }
+
+    #[test]
+    fn test_format_spec_errors() {
+        assert::fail(
+            "'{:d}'.format('a')",
+            "Unknown format code `d` in format spec `d`",
+        );
+        assert::fail("'{:x}'.format(1.5)", "for value of type `float`");
+        assert::fail(
+            "'{:+}'.format('a')",
+            "Sign not allowed in string format spec `+`",
+        );
+        assert::fail("'{:#}'.format('a')", "Alternate form (#) not allowed");
+        assert::fail("'{:=5}'.format('a')", "`=` alignment not allowed");
+        assert::fail(
+            "'{:.2d}'.format(1)",
+            "Precision not allowed in integer format spec `.2d`",
+        );
+        assert::fail("'{:5x5}'.format(1)", "Invalid format spec `5x5`");
+        assert::fail("'{:.}'.format(1.0)", "Invalid format spec `.`");
+        assert::fail("'{:,}'.format(1)", "Invalid format spec `,`");
+        assert::fail(
+            "'{:{}}'.format('a', 'x')",
+            "Unknown format code `x` in format spec `x` for value of type `string`",
+        );
+        assert::fail(
+            "'{:{:{}}}'.format(1, 2, 3)",
+            "Max string recursion exceeded",
+        );
+        assert::fail("'{:>5'.format(1)", "Unmatched '{'");
+        assert::fail(
+            "x = 1\nf'{x:{x}}'",
+            "Nested replacement fields in format spec are not supported in f-strings",
+        );
+        assert::eq("len('{:1000000}'.format(1))", "1000000");
+        assert::eq("len('%1000000d' % 1)", "1000000");
+        assert::fail(
+            "'{:99999999999}'.format(1)",
+            "Width or precision too large in format spec `99999999999`",
+        );
+        assert::fail(
+            "'{:.1000001f}'.format(1.0)",
+            "Width or precision too large in format spec `.1000001f`",
+        );
+        assert::fail(
+            "'{:99999999999999999999999}'.format(1)",
+            "Width or precision too large in format spec `99999999999999999999999`",
+        );
+        assert::fail(
+            "'%99999999999d' % 1",
+            "Width or precision too large in format spec `%99999999999d`",
+        );
+        assert::fail(
+            "'%.99999999999999999999999s' % 'a'",
+            "Width or precision too large in format spec `%.99999999999999999999999s`",
+        );
+        assert::fail("'%5q' % 1", "Unsupported format character: 'q'");
+        assert::fail("'%5' % 1", "Incomplete format");
+    }
+}
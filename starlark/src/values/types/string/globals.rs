@@ -20,6 +20,7 @@ use starlark_derive::starlark_module;
 use crate as starlark;
 use crate::environment::GlobalsBuilder;
 use crate::eval::Evaluator;
+use crate::values::promise::StarlarkPromise;
 use crate::values::string::StarlarkStr;
 use crate::values::StringValue;
 use crate::values::Value;
@@ -135,13 +136,14 @@ pub(crate) fn register_str(globals: &mut GlobalsBuilder) {
     fn str<'v>(
         #[starlark(require = pos)] a: Value<'v>,
         eval: &mut Evaluator<'v, '_, '_>,
-    ) -> anyhow::Result<StringValue<'v>> {
+    ) -> starlark::Result<StringValue<'v>> {
         if let Some(a) = StringValue::new(a) {
             // Special case that can avoid reallocating, but is equivalent.
             Ok(a)
         } else {
+            StarlarkPromise::check_str(a)?;
             let mut s = eval.string_pool.alloc();
-            a.collect_repr(&mut s);
+            a.collect_str(&mut s);
             let r = eval.heap().alloc_str(&s);
             eval.string_pool.release(s);
             Ok(r)
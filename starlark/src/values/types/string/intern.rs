@@ -15,4 +15,5 @@
  * limitations under the License.
  */
 
+pub(crate) mod cache;
 pub(crate) mod interner;
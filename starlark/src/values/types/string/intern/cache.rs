@@ -0,0 +1,95 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Bounded cache of short strings.
+
+use std::ops::Deref;
+
+use crate::collections::Hashed;
+use crate::values::string::str_type::StarlarkStr;
+
+/// Number of slots in [`StringCache`].
+const SLOTS: usize = 4096;
+
+/// Cache of recently allocated strings, used to share equal short strings.
+///
+/// Unlike an interner, it does not guarantee that equal strings are shared:
+/// a string hash selects a pair of slots, which hold the two most recent strings
+/// with that hash, so lookups are cheap and memory stays constant
+/// even when most strings are distinct.
+pub(crate) struct StringCache<S> {
+    slots: Vec<Option<S>>,
+}
+
+impl<S> Default for StringCache<S> {
+    fn default() -> Self {
+        StringCache { slots: Vec::new() }
+    }
+}
+
+impl<S: Copy + Deref<Target = StarlarkStr>> StringCache<S> {
+    /// Get the cached string equal to `s`, or allocate and cache it.
+    pub(crate) fn get_or_alloc(&mut self, s: Hashed<&str>, alloc: impl FnOnce() -> S) -> S {
+        if self.slots.is_empty() {
+            self.slots = vec![None; SLOTS];
+        }
+        let i = (s.hash().promote() as usize % SLOTS) & !1;
+        let pair = &mut self.slots[i..i + 2];
+        for x in pair.iter().flatten() {
+            if s == x.as_str_hashed() {
+                return *x;
+            }
+        }
+        let x = alloc();
+        pair[1] = pair[0];
+        pair[0] = Some(x);
+        x
+    }
+
+    /// Replace each string with `f(string)`, or drop it if `f` returns `None`.
+    /// `f` must return a string with the same contents.
+    pub(crate) fn retain(&mut self, mut f: impl FnMut(S) -> Option<S>) {
+        for slot in &mut self.slots {
+            if let Some(x) = *slot {
+                *slot = f(x);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collections::Hashed;
+    use crate::values::string::intern::cache::StringCache;
+    use crate::values::FrozenHeap;
+
+    #[test]
+    fn test_cache() {
+        let heap = FrozenHeap::new();
+        let mut cache = StringCache::default();
+
+        let xx1 = cache.get_or_alloc(Hashed::new("xx"), || heap.alloc_str("xx"));
+        let xx2 = cache.get_or_alloc(Hashed::new("xx"), || {
+            panic!("alloc_str should be only called once")
+        });
+        assert!(xx1.to_value().ptr_eq(xx2.to_value()));
+
+        cache.retain(|_| None);
+        let xx3 = cache.get_or_alloc(Hashed::new("xx"), || heap.alloc_str("xx"));
+        assert!(!xx1.to_value().ptr_eq(xx3.to_value()));
+    }
+}
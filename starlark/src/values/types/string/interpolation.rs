@@ -18,16 +18,17 @@
 //! String interpolation-related code.
 //! Based on <https://docs.python.org/3/library/stdtypes.html#printf-style-string-formatting>
 
-use std::fmt::Write;
-
-use dupe::Dupe;
-use num_traits::Signed;
 use thiserror::Error;
 
-use crate::values::float::float;
+use crate::values::dict::DictRef;
 use crate::values::float::StarlarkFloat;
+use crate::values::promise::StarlarkPromise;
 use crate::values::string::dot_format::format_one;
-use crate::values::types::int::int_or_big::StarlarkIntRef;
+use crate::values::string::format_spec::Align;
+use crate::values::string::format_spec::FormatSpec;
+use crate::values::string::format_spec::Sign;
+use crate::values::string::repr::string_repr;
+use crate::values::types::int::int_or_big::StarlarkInt;
 use crate::values::types::num::value::NumRef;
 use crate::values::types::tuple::value::Tuple;
 use crate::values::Heap;
@@ -35,16 +36,9 @@ use crate::values::StringValue;
 use crate::values::UnpackValue;
 use crate::values::Value;
 use crate::values::ValueError;
-use crate::values::ValueLike;
-
-// `i32::abs(i32::MIN)` panics as `i32::MIN` has no corresponding
-// positive value that fits inside `i32`. For this edge case,
-// let's just hardcode the results.
-const I32_MIN_OCTAL: &str = "-20000000000";
-const I32_MIN_HEX: &str = "-80000000";
 
 /// Operator `%` format or evaluation errors
-#[derive(Clone, Dupe, Debug, Error)]
+#[derive(Clone, Debug, Error)]
 enum StringInterpolationError {
     #[error("Too many arguments for format string")]
     TooManyParameters,
@@ -56,6 +50,10 @@ enum StringInterpolationError {
     UnsupportedFormatCharacter(char),
     #[error("Expecting format character (internal error)")]
     ExpectingFormatCharacter,
+    #[error("Incomplete format key")]
+    IncompleteFormatKey,
+    #[error("Format with keys requires a dict, got `{0}`")]
+    FormatRequiresDict(&'static str),
 }
 
 enum PercentSFormat {
@@ -89,7 +87,70 @@ struct PercentFormatParser<'a> {
 
 struct Item<'a> {
     literal: &'a str,
-    format: Option<PercentSFormat>,
+    /// The key of `%(key)s`.
+    key: Option<&'a str>,
+    format: Option<(PercentSFormat, FormatSpec<'a>)>,
+}
+
+impl<'a> PercentFormatParser<'a> {
+    /// Parse `[flags][width][.precision]` after the percent sign,
+    /// returning the spec and the number of bytes parsed.
+    fn flags(directive: &'a str) -> (FormatSpec<'a>, usize) {
+        let mut spec = FormatSpec::new(directive);
+        let bytes = directive.as_bytes();
+        let mut left = false;
+        let mut i = 1;
+        while let Some(c) = bytes.get(i) {
+            match c {
+                b'-' => left = true,
+                b'+' => spec.sign = Sign::Plus,
+                b' ' if spec.sign != Sign::Plus => spec.sign = Sign::Space,
+                b' ' => {}
+                b'0' => spec.zero = true,
+                b'#' => spec.alternate = true,
+                _ => break,
+            }
+            i += 1;
+        }
+        let digits = |i: usize| -> (usize, usize) {
+            let len = bytes[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+            let n = match len {
+                0 => 0,
+                // Only fails on overflow, which `check_size` reports.
+                _ => directive[i..i + len].parse().unwrap_or(usize::MAX),
+            };
+            (n, i + len)
+        };
+        (spec.width, i) = digits(i);
+        if bytes.get(i) == Some(&b'.') {
+            let (precision, j) = digits(i + 1);
+            spec.precision = Some(precision);
+            i = j;
+        }
+        // `-` overrides `0`, and the flags don't set alignment, so the default is right.
+        spec.zero &= !left;
+        if left {
+            spec.align = Some(Align::Left);
+        }
+        (spec, i)
+    }
+}
+
+impl<'a> PercentFormatParser<'a> {
+    /// Parse `(key)` after the percent sign, returning the key and the index of `)`.
+    /// Parentheses in the key must be balanced, like in Python.
+    fn key(directive: &'a str) -> anyhow::Result<(&'a str, usize)> {
+        let mut depth = 0;
+        for (i, c) in directive.bytes().enumerate().skip(1) {
+            match c {
+                b'(' => depth += 1,
+                b')' if depth == 1 => return Ok((&directive[2..i], i)),
+                b')' => depth -= 1,
+                _ => {}
+            }
+        }
+        Err(StringInterpolationError::IncompleteFormatKey.into())
+    }
 }
 
 impl<'a> Iterator for PercentFormatParser<'a> {
@@ -101,80 +162,63 @@ impl<'a> Iterator for PercentFormatParser<'a> {
         if let Some(index_of_percent) = index_of_percent {
             let prev_rem = self.rem;
             let (literal, rem) = self.rem.split_at(index_of_percent);
-            match rem.as_bytes().get(1) {
+            if rem.as_bytes().get(1) == Some(&b'%') {
+                // Include the percent in the literal.
+                self.rem = &rem[2..];
+                return Some(Ok(Item {
+                    literal: &prev_rem[..index_of_percent + 1],
+                    key: None,
+                    format: None,
+                }));
+            }
+            // With a key, parse the rest of the directive as if the `%` were the `)`.
+            let (key, start) = if rem.as_bytes().get(1) == Some(&b'(') {
+                match Self::key(rem) {
+                    Ok((key, end)) => (Some(key), end),
+                    Err(e) => return Some(Err(e)),
+                }
+            } else {
+                (None, 0)
+            };
+            let (mut spec, i) = Self::flags(&rem[start..]);
+            let i = start + i;
+            let format = match rem.as_bytes().get(i) {
                 None => return Some(Err(StringInterpolationError::IncompleteFormat.into())),
-                Some(f) => {
-                    let res = match f {
-                        b'%' => {
-                            // Include the percent in the literal.
-                            let literal = &prev_rem[..index_of_percent + 1];
-                            Item {
-                                literal,
-                                format: None,
-                            }
-                        }
-                        b's' => Item {
-                            literal,
-                            format: Some(PercentSFormat::Str),
-                        },
-                        b'r' => Item {
-                            literal,
-                            format: Some(PercentSFormat::Repr),
-                        },
-                        b'd' => Item {
-                            literal,
-                            format: Some(PercentSFormat::Dec),
-                        },
-                        b'o' => Item {
-                            literal,
-                            format: Some(PercentSFormat::Oct),
-                        },
-                        b'x' => Item {
-                            literal,
-                            format: Some(PercentSFormat::Hex),
-                        },
-                        b'X' => Item {
-                            literal,
-                            format: Some(PercentSFormat::HexUpper),
-                        },
-                        b'e' => Item {
-                            literal,
-                            format: Some(PercentSFormat::Exp),
-                        },
-                        b'E' => Item {
-                            literal,
-                            format: Some(PercentSFormat::ExpUpper),
-                        },
-                        b'f' | b'F' => Item {
-                            literal,
-                            format: Some(PercentSFormat::Float),
-                        },
-                        b'g' => Item {
-                            literal,
-                            format: Some(PercentSFormat::FloatCompact),
-                        },
-                        b'G' => Item {
-                            literal,
-                            format: Some(PercentSFormat::FloatCompactUpper),
-                        },
-                        _ => {
-                            // Note we need to find the second character, not the second byte.
-                            let Some(c) = rem.chars().nth(1) else {
-                                return Some(Err(
-                                    StringInterpolationError::ExpectingFormatCharacter.into(),
-                                ));
-                            };
-                            return Some(Err(
-                                StringInterpolationError::UnsupportedFormatCharacter(c).into(),
-                            ));
-                        }
+                Some(b's') => PercentSFormat::Str,
+                Some(b'r') => PercentSFormat::Repr,
+                Some(b'd') => PercentSFormat::Dec,
+                Some(b'o') => PercentSFormat::Oct,
+                Some(b'x') => PercentSFormat::Hex,
+                Some(b'X') => PercentSFormat::HexUpper,
+                Some(b'e') => PercentSFormat::Exp,
+                Some(b'E') => PercentSFormat::ExpUpper,
+                Some(b'f' | b'F') => PercentSFormat::Float,
+                Some(b'g') => PercentSFormat::FloatCompact,
+                Some(b'G') => PercentSFormat::FloatCompactUpper,
+                Some(_) => {
+                    // Note we need to find the character, not the byte.
+                    let Some(c) = rem[i..].chars().next() else {
+                        return Some(Err(
+                            StringInterpolationError::ExpectingFormatCharacter.into()
+                        ));
                     };
-                    // We reach here only if format character is ASCII,
-                    // so we can safely skip 2 bytes.
-                    self.rem = &rem[2..];
-                    Some(Ok(res))
+                    return Some(Err(
+                        StringInterpolationError::UnsupportedFormatCharacter(c).into()
+                    ));
                 }
+            };
+            // We reach here only if format character is ASCII,
+            // so we can safely skip it.
+            spec.spec = &rem[..i + 1];
+            if let Err(e) = spec.check_size() {
+                return Some(Err(e));
             }
+            self.rem = &rem[i + 1..];
+            Some(Ok(Item {
+                literal,
+                key,
+                format: Some((format, spec)),
+            }))
         } else {
             if self.rem.is_empty() {
                 None
@@ -183,6 +227,7 @@ impl<'a> Iterator for PercentFormatParser<'a> {
                 self.rem = "";
                 Some(Ok(Item {
                     literal,
+                    key: None,
                     format: None,
                 }))
             }
@@ -205,148 +250,96 @@ pub(crate) fn percent(format: &str, value: Value) -> crate::Result<String> {
         None => one,
     };
     let mut values = values.iter().copied();
-    let mut next_value = || -> anyhow::Result<Value> {
-        values
-            .next()
-            .ok_or_else(|| StringInterpolationError::NotEnoughParameters.into())
+    // Whether `%(key)s` was used, in which case `value` is a dict which need not be consumed.
+    let mut keyed = false;
+    let mut next_value = |key: Option<&str>| -> crate::Result<Value> {
+        match key {
+            None => values.next().ok_or_else(|| {
+                crate::Error::new_other(StringInterpolationError::NotEnoughParameters)
+            }),
+            Some(key) => {
+                keyed = true;
+                let dict = DictRef::from_value(value).ok_or_else(|| {
+                    crate::Error::new_other(StringInterpolationError::FormatRequiresDict(
+                        value.get_type(),
+                    ))
+                })?;
+                dict.get_str(key).ok_or_else(|| {
+                    let mut repr = String::new();
+                    string_repr(key, &mut repr);
+                    crate::Error::new_other(ValueError::KeyNotFound(repr))
+                })
+            }
+        }
     };
 
     // because of the way format is defined, we can deal with it as bytes
     for item in (PercentFormatParser { rem: format }) {
         let item = item?;
         res.push_str(item.literal);
-        match item.format {
-            None => {}
-            Some(PercentSFormat::Str) => {
-                let arg = next_value()?;
-                match arg.unpack_str() {
-                    None => arg.collect_repr(&mut res),
-                    Some(s) => res.push_str(s),
+        let Some((format, mut spec)) = item.format else {
+            continue;
+        };
+        let key = item.key;
+        match format {
+            PercentSFormat::Str | PercentSFormat::Repr => {
+                // Only width, precision and `-` apply to strings.
+                spec.zero = false;
+                spec.align.get_or_insert(Align::Right);
+                let arg = next_value(key)?;
+                if let PercentSFormat::Str = format {
+                    StarlarkPromise::check_str(arg)?;
+                }
+                match (format, arg.unpack_str()) {
+                    (PercentSFormat::Str, Some(s)) => spec.write_str(s, &mut res),
+                    _ => spec.write_str(&arg.to_repr(), &mut res),
                 }
             }
-            Some(PercentSFormat::Repr) => next_value()?.collect_repr(&mut res),
-            Some(PercentSFormat::Dec) => {
-                let value = next_value()?;
+            PercentSFormat::Dec => {
+                let value = next_value(key)?;
+                spec.ty = Some('d');
                 match value.unpack_num() {
-                    Some(NumRef::Int(StarlarkIntRef::Small(v))) => {
-                        write!(res, "{}", v.to_i32()).unwrap()
-                    }
-                    Some(NumRef::Int(StarlarkIntRef::Big(v))) => {
-                        write!(res, "{}", v.get()).unwrap()
-                    }
+                    Some(NumRef::Int(v)) => spec.write_int(v, &mut res),
                     Some(NumRef::Float(v)) => {
                         match NumRef::Float(StarlarkFloat(v.0.trunc())).as_int() {
-                            Some(v) => write!(res, "{}", v).unwrap(),
+                            Some(v) => spec.write_int(StarlarkInt::from(v).as_ref(), &mut res),
                             None => ValueError::unsupported_type(value, "format(%d)")?,
                         }
                     }
                     None => ValueError::unsupported_type(value, "format(%d)")?,
                 }
             }
-            Some(PercentSFormat::Oct) => {
-                let value = next_value()?;
-                match value.unpack_num() {
-                    Some(NumRef::Int(StarlarkIntRef::Small(v))) => {
-                        let v = v.to_i32();
-                        match v.checked_abs() {
-                            Some(vp) => {
-                                write!(res, "{}{:o}", if v < 0 { "-" } else { "" }, vp).unwrap()
-                            }
-                            None => write!(res, "{}", I32_MIN_OCTAL).unwrap(),
-                        }
-                    }
-                    Some(NumRef::Int(StarlarkIntRef::Big(v))) => {
-                        let v = v.get();
-                        write!(
-                            res,
-                            "{}{:o}",
-                            if v.is_negative() { "-" } else { "" },
-                            v.abs()
-                        )
-                        .unwrap()
-                    }
-                    Some(NumRef::Float(_)) | None => {
-                        ValueError::unsupported_type(value, "format(%o)")?
-                    }
-                }
-            }
-            Some(PercentSFormat::Hex) => {
-                let value = next_value()?;
-                match value.unpack_num() {
-                    Some(NumRef::Int(StarlarkIntRef::Small(v))) => {
-                        let v = v.to_i32();
-                        match v.checked_abs() {
-                            Some(vp) => {
-                                write!(res, "{}{:x}", if v < 0 { "-" } else { "" }, vp).unwrap()
-                            }
-                            None => write!(res, "{}", I32_MIN_HEX).unwrap(),
-                        }
-                    }
-                    Some(NumRef::Int(StarlarkIntRef::Big(v))) => {
-                        let v = v.get();
-                        write!(
-                            res,
-                            "{}{:x}",
-                            if v.is_negative() { "-" } else { "" },
-                            v.abs()
-                        )
-                        .unwrap()
-                    }
-                    Some(NumRef::Float(_)) | None => {
-                        ValueError::unsupported_type(value, "format(%x)")?
-                    }
-                }
-            }
-            Some(PercentSFormat::HexUpper) => {
-                let value = next_value()?;
+            PercentSFormat::Oct | PercentSFormat::Hex | PercentSFormat::HexUpper => {
+                let (ty, op) = match format {
+                    PercentSFormat::Oct => ('o', "format(%o)"),
+                    PercentSFormat::Hex => ('x', "format(%x)"),
+                    _ => ('X', "format(%X)"),
+                };
+                let value = next_value(key)?;
+                spec.ty = Some(ty);
                 match value.unpack_num() {
-                    Some(NumRef::Int(StarlarkIntRef::Small(v))) => {
-                        let v = v.to_i32();
-                        match v.checked_abs() {
-                            Some(vp) => {
-                                write!(res, "{}{:X}", if v < 0 { "-" } else { "" }, vp).unwrap()
-                            }
-                            None => write!(res, "{}", I32_MIN_HEX).unwrap(),
-                        }
-                    }
-                    Some(NumRef::Int(StarlarkIntRef::Big(v))) => {
-                        let v = v.get();
-                        write!(
-                            res,
-                            "{}{:X}",
-                            if v.is_negative() { "-" } else { "" },
-                            v.abs()
-                        )
-                        .unwrap()
-                    }
-                    Some(NumRef::Float(_)) | None => {
-                        ValueError::unsupported_type(value, "format(%X)")?
-                    }
+                    Some(NumRef::Int(v)) => spec.write_int(v, &mut res),
+                    Some(NumRef::Float(_)) | None => ValueError::unsupported_type(value, op)?,
                 }
             }
-            Some(PercentSFormat::Exp) => {
-                let v = NumRef::unpack_param(next_value()?)?.as_float();
-                float::write_scientific(&mut res, v, 'e', false).unwrap()
-            }
-            Some(PercentSFormat::ExpUpper) => {
-                let v = NumRef::unpack_param(next_value()?)?.as_float();
-                float::write_scientific(&mut res, v, 'E', false).unwrap()
-            }
-            Some(PercentSFormat::Float) => {
-                let v = NumRef::unpack_param(next_value()?)?.as_float();
-                float::write_decimal(&mut res, v).unwrap()
-            }
-            Some(PercentSFormat::FloatCompact) => {
-                let v = NumRef::unpack_param(next_value()?)?.as_float();
-                float::write_compact(&mut res, v, 'e').unwrap()
-            }
-            Some(PercentSFormat::FloatCompactUpper) => {
-                let v = NumRef::unpack_param(next_value()?)?.as_float();
-                float::write_compact(&mut res, v, 'E').unwrap()
+            PercentSFormat::Exp
+            | PercentSFormat::ExpUpper
+            | PercentSFormat::Float
+            | PercentSFormat::FloatCompact
+            | PercentSFormat::FloatCompactUpper => {
+                spec.ty = Some(match format {
+                    PercentSFormat::Exp => 'e',
+                    PercentSFormat::ExpUpper => 'E',
+                    PercentSFormat::Float => 'f',
+                    PercentSFormat::FloatCompact => 'g',
+                    _ => 'G',
+                });
+                let v = NumRef::unpack_param(next_value(key)?)?.as_float();
+                spec.write_float(v, &mut res)
             }
         }
     }
-    if values.next().is_some() {
+    if !keyed && values.next().is_some() {
         Err(crate::Error::new_other(
             StringInterpolationError::TooManyParameters,
         ))
@@ -409,7 +402,7 @@ pub(crate) fn percent_s_one<'v>(
                 },
                 None => arg,
             };
-            format_one(before, one, after, heap)
+            format_one(before, one, after, heap)?
         }
     })
 }
@@ -445,6 +438,22 @@ mod tests {
         assert_eq!(None, parse_percent_s_one("%d"));
     }
 
+    #[test]
+    fn test_percent_keys() {
+        assert::eq("'%(a)s-%(b)d' % {'a': 'x', 'b': 2}", "'x-2'");
+        assert::eq("'%(a)5.2f|%(a)r' % {'a': 1.5}", "' 1.50|1.5'");
+        assert::eq("'%(a(b))s' % {'a(b)': 1}", "'1'");
+        assert::eq("'%(a)s' % {'a': 1, 'b': 2}", "'1'");
+        assert::eq("'%s' % {'a': 1}", "'{\"a\": 1}'");
+        assert::eq("'%s %d' % ('x', 1)", "'x 1'");
+        assert::fail("'%(a)s' % {'b': 1}", "Key `\"a\"` was not found");
+        assert::fail(
+            "'%(a)s' % (1,)",
+            "Format with keys requires a dict, got `tuple`",
+        );
+        assert::fail("'%(a' % {'a': 1}", "Incomplete format key");
+    }
+
     #[test]
     fn test_type_support_d() {
         assert::eq("'%d' % (-123,)", "'-123'");
@@ -28,6 +28,8 @@ use crate as starlark;
 use crate::environment::MethodsBuilder;
 use crate::eval::Arguments;
 use crate::eval::Evaluator;
+use crate::values::bytes::StarlarkBytes;
+use crate::values::dict::DictRef;
 use crate::values::list::AllocList;
 use crate::values::list::UnpackList;
 use crate::values::none::NoneOr;
@@ -98,6 +100,92 @@ fn rsplitn_whitespace(s: &str, maxsplit: usize) -> Vec<String> {
     v
 }
 
+/// Titlecase letters, which are neither uppercase nor lowercase.
+fn is_titlecase(c: char) -> bool {
+    matches!(
+        c,
+        '\u{01C5}'
+            | '\u{01C8}'
+            | '\u{01CB}'
+            | '\u{01F2}'
+            | '\u{1F88}'..='\u{1F8F}'
+            | '\u{1F98}'..='\u{1F9F}'
+            | '\u{1FA8}'..='\u{1FAF}'
+            | '\u{1FBC}'
+            | '\u{1FCC}'
+            | '\u{1FFC}'
+    )
+}
+
+/// Letters which have case, as in Python `str.title`.
+fn is_cased(c: char) -> bool {
+    c.is_lowercase() || c.is_uppercase() || is_titlecase(c)
+}
+
+/// Append the titlecase mapping of a character, which is the same as uppercase
+/// except for digraphs, ligatures and some Greek, Armenian and Georgian letters.
+fn push_titlecase(res: &mut String, c: char) {
+    let title: &str = match c {
+        '\u{00DF}' => "Ss",
+        '\u{01C4}'..='\u{01C6}' => "\u{01C5}",
+        '\u{01C7}'..='\u{01C9}' => "\u{01C8}",
+        '\u{01CA}'..='\u{01CC}' => "\u{01CB}",
+        '\u{01F1}'..='\u{01F3}' => "\u{01F2}",
+        '\u{0587}' => "\u{0535}\u{0582}",
+        // Georgian Mkhedruli letters are their own titlecase.
+        '\u{10D0}'..='\u{10FA}' | '\u{10FD}'..='\u{10FF}' => {
+            res.push(c);
+            return;
+        }
+        '\u{1F80}'..='\u{1F87}' | '\u{1F90}'..='\u{1F97}' | '\u{1FA0}'..='\u{1FA7}' => {
+            res.push(char::from_u32(c as u32 + 8).unwrap());
+            return;
+        }
+        '\u{1F88}'..='\u{1F8F}' | '\u{1F98}'..='\u{1F9F}' | '\u{1FA8}'..='\u{1FAF}' => {
+            res.push(c);
+            return;
+        }
+        '\u{1FB2}' => "\u{1FBA}\u{0345}",
+        '\u{1FB3}' | '\u{1FBC}' => "\u{1FBC}",
+        '\u{1FB4}' => "\u{0386}\u{0345}",
+        '\u{1FB7}' => "\u{0391}\u{0342}\u{0345}",
+        '\u{1FC2}' => "\u{1FCA}\u{0345}",
+        '\u{1FC3}' | '\u{1FCC}' => "\u{1FCC}",
+        '\u{1FC4}' => "\u{0389}\u{0345}",
+        '\u{1FC7}' => "\u{0397}\u{0342}\u{0345}",
+        '\u{1FF2}' => "\u{1FFA}\u{0345}",
+        '\u{1FF3}' | '\u{1FFC}' => "\u{1FFC}",
+        '\u{1FF4}' => "\u{038F}\u{0345}",
+        '\u{1FF7}' => "\u{03A9}\u{0342}\u{0345}",
+        '\u{FB00}' => "Ff",
+        '\u{FB01}' => "Fi",
+        '\u{FB02}' => "Fl",
+        '\u{FB03}' => "Ffi",
+        '\u{FB04}' => "Ffl",
+        '\u{FB05}' | '\u{FB06}' => "St",
+        '\u{FB13}' => "\u{0544}\u{0576}",
+        '\u{FB14}' => "\u{0544}\u{0565}",
+        '\u{FB15}' => "\u{0544}\u{056B}",
+        '\u{FB16}' => "\u{054E}\u{0576}",
+        '\u{FB17}' => "\u{0544}\u{056D}",
+        _ => {
+            res.extend(c.to_uppercase());
+            return;
+        }
+    };
+    res.push_str(title);
+}
+
+/// Append the lowercase mapping of a character, where capital sigma becomes final
+/// at the end of a word, that is when preceded by a cased letter and not followed by one.
+fn push_lowercase(res: &mut String, c: char, prev_cased: bool, next: Option<char>) {
+    if c == '\u{03A3}' && prev_cased && !next.is_some_and(is_cased) {
+        res.push('\u{03C2}');
+    } else {
+        res.extend(c.to_lowercase());
+    }
+}
+
 #[derive(StarlarkTypeRepr, UnpackValue)]
 enum StringOrTuple<'v> {
     String(&'v str),
@@ -130,7 +218,7 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
 
     /// [string.capitalize](
     /// https://github.com/bazelbuild/starlark/blob/master/spec.md#string%C2%B7capitalize
-    /// ): returns a copy of string S, where the first character (if any) is converted to uppercase;
+    /// ): returns a copy of string S, where the first character (if any) is converted to titlecase;
     /// all other characters are converted to lowercase.
     ///
     /// ```
@@ -138,17 +226,21 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// "hello, world!".capitalize() == "Hello, world!"
     /// "Hello, World!".capitalize() == "Hello, world!"
     /// "".capitalize() == ""
+    /// "ǆemal".capitalize() == "ǅemal"
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
     fn capitalize(this: &str) -> anyhow::Result<String> {
         let mut result = String::with_capacity(this.len());
-        for (i, c) in this.chars().enumerate() {
-            if i == 0 {
-                result.extend(c.to_uppercase())
-            } else {
-                result.extend(c.to_lowercase())
-            }
+        let mut prev_cased = false;
+        let mut chars = this.chars().peekable();
+        if let Some(c) = chars.next() {
+            push_titlecase(&mut result, c);
+            prev_cased = is_cased(c);
+        }
+        while let Some(c) = chars.next() {
+            push_lowercase(&mut result, c, prev_cased, chars.peek().copied());
+            prev_cased = is_cased(c);
         }
         Ok(result)
     }
@@ -217,6 +309,25 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
         }
     }
 
+    /// `S.encode(encoding="utf-8")`: returns the `bytes` of a string.
+    ///
+    /// Only UTF-8 is supported.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// list("hi".encode()) == [104, 105]
+    /// len("世界".encode("utf-8")) == 6
+    /// # "#);
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn encode(
+        this: &str,
+        #[starlark(require = pos, default = "utf-8")] encoding: &str,
+    ) -> anyhow::Result<StarlarkBytes> {
+        StarlarkBytes::check_encoding(encoding)?;
+        Ok(StarlarkBytes::new(this.as_bytes()))
+    }
+
     /// [string.endswith](
     /// https://github.com/bazelbuild/starlark/blob/master/spec.md#string·endswith
     /// ): determine if a string ends with a given suffix.
@@ -316,8 +427,11 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// the default.
     ///
     /// The *format specifier*, after a colon, specifies field width,
-    /// alignment, padding, and numeric precision.
-    /// Currently it must be empty, but it is reserved for future use.
+    /// alignment, padding, and numeric precision, as in Python:
+    /// `[[fill]align][sign][#][0][width][.precision][type]`.
+    /// Types are `s` for strings, `d`, `x`, `X`, `o`, `b` for integers,
+    /// and `e`, `E`, `f`, `F`, `g`, `G`, `%` for numbers.
+    /// The specifier may contain nested fields, like `{:>{}}`.
     ///
     /// ```rust
     /// # starlark::assert::all_true(r#"
@@ -326,6 +440,8 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// "a{}b{}c".format(1, 2) == "a1b2c"
     /// "({1}, {0})".format("zero", "one") == "(one, zero)"
     /// "Is {0!r} {0!s}?".format("heterological") == "Is \"heterological\" heterological?"
+    /// "[{:>4}|{:<4}|{:^4}]".format(1, 2, 3) == "[   1|2   | 3  ]"
+    /// "{:04d} {:#x} {:.2f}".format(7, 255, 3.14159) == "0007 0xff 3.14"
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
@@ -338,13 +454,46 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
         dot_format::format(
             this,
             iter,
-            args.names()?,
+            &args.names()?,
             &mut eval.string_pool,
             eval.module_env.heap(),
         )
         .map_err(Into::into)
     }
 
+    /// [string.format_map](
+    /// https://docs.python.org/3/library/stdtypes.html#str.format_map
+    /// ): format a string with named fields taken from a dictionary.
+    /// _Not part of standard Starlark._
+    ///
+    /// `S.format_map(mapping)` is like `S.format(**mapping)`, except the dictionary
+    /// is used directly. Only named fields are allowed, and it is an error
+    /// if a field name is not a key of `mapping`.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// "{greeting}, {name}!".format_map({"greeting": "Hello", "name": "World"}) == "Hello, World!"
+    /// "{x!r} {{x}}".format_map({"x": "y"}) == "\"y\" {x}"
+    /// # "#);
+    /// # starlark::assert::fail(r#"
+    /// "{missing}".format_map({}) # error: not found
+    /// # "#, "not found");
+    /// ```
+    #[starlark(speculative_exec_safe)]
+    fn format_map<'v>(
+        this: &str,
+        #[starlark(require = pos)] mapping: DictRef<'v>,
+        eval: &mut Evaluator<'v, '_, '_>,
+    ) -> anyhow::Result<StringValue<'v>> {
+        dot_format::format(
+            this,
+            std::iter::empty(),
+            &mapping,
+            &mut eval.string_pool,
+            eval.module_env.heap(),
+        )
+    }
+
     /// [string.index](
     /// https://github.com/bazelbuild/starlark/blob/master/spec.md#string·index
     /// ): search a substring inside a string, failing on not found.
@@ -1081,13 +1230,15 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// # starlark::assert::all_true(r#"
     /// "one\n\ntwo".splitlines() == ["one", "", "two"]
     /// "one\n\ntwo".splitlines(True) == ["one\n", "\n", "two"]
+    /// "one\r\ntwo".splitlines(keepends = True) == ["one\r\n", "two"]
     /// "a\nb".splitlines() == ["a", "b"]
+    /// "".splitlines() == []
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
     fn splitlines<'v>(
         this: &str,
-        #[starlark(require = pos, default = false)] keepends: bool,
+        #[starlark(default = false)] keepends: bool,
         heap: &'v Heap,
     ) -> anyhow::Result<Vec<StringValue<'v>>> {
         let mut s = this;
@@ -1181,30 +1332,29 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
     /// `S.title()` returns a copy of the string S with letters converted to
     /// titlecase.
     ///
-    /// Letters are converted to uppercase at the start of words, lowercase
-    /// elsewhere.
+    /// Letters are converted to titlecase at the start of words, lowercase
+    /// elsewhere. A word starts with a letter which has case and follows
+    /// a character which does not.
     ///
     /// ```
     /// # starlark::assert::all_true(r#"
     /// "hElLo, WoRlD!".title() == "Hello, World!"
+    /// "they're 2nd".title() == "They'Re 2Nd"
+    /// "ǆungla".title() == "ǅungla"
     /// # "#);
     /// ```
     #[starlark(speculative_exec_safe)]
     fn title(this: &str) -> anyhow::Result<String> {
-        let mut last_space = true;
+        let mut prev_cased = false;
         let mut result = String::with_capacity(this.len());
-        for c in this.chars() {
-            if !c.is_alphabetic() {
-                last_space = true;
-                result.extend(c.to_lowercase());
+        let mut chars = this.chars().peekable();
+        while let Some(c) = chars.next() {
+            if prev_cased {
+                push_lowercase(&mut result, c, prev_cased, chars.peek().copied());
             } else {
-                if last_space {
-                    result.extend(c.to_uppercase())
-                } else {
-                    result.extend(c.to_lowercase())
-                }
-                last_space = false;
+                push_titlecase(&mut result, c);
             }
+            prev_cased = is_cased(c);
         }
         Ok(result)
     }
@@ -1256,9 +1406,9 @@ pub(crate) fn string_methods(builder: &mut MethodsBuilder) {
 
     /// [string.removesuffix](
     /// https://docs.python.org/3.9/library/stdtypes.html#str.removesuffix
-    /// ): remove a prefix from a string. _Not part of standard Starlark._
+    /// ): remove a suffix from a string. _Not part of standard Starlark._
     ///
-    /// If the string starts with the prefix string, return `string[len(prefix):]`.
+    /// If the string ends with the suffix string, return `string[:-len(suffix)]`.
     /// Otherwise, return a copy of the original string:
     ///
     /// ```
@@ -1304,6 +1454,96 @@ mod tests {
         assert::eq("'Троянская война окончена'.find('война')", "10");
     }
 
+    #[test]
+    fn test_removeprefix_removesuffix() {
+        assert::all_true(
+            r#"
+"".removeprefix("") == ""
+"".removeprefix("a") == ""
+"abc".removeprefix("") == "abc"
+"abc".removesuffix("") == "abc"
+"".removesuffix("a") == ""
+"aaa".removeprefix("aa") == "a"
+"aaa".removesuffix("aa") == "a"
+"abab".removeprefix("ab") == "ab"
+"ab".removeprefix("abc") == "ab"
+"ab".removesuffix("zab") == "ab"
+"héllo".removeprefix("hé") == "llo"
+"日本語".removesuffix("語") == "日本"
+"日本語".removeprefix("本") == "日本語"
+"#,
+        );
+    }
+
+    #[test]
+    fn test_splitlines_keepends() {
+        assert::all_true(
+            r#"
+"".splitlines(True) == []
+"a\r\nb\rc\nd".splitlines(True) == ["a\r\n", "b\r", "c\n", "d"]
+"a\r\nb\rc\nd".splitlines() == ["a", "b", "c", "d"]
+"a\n\n".splitlines(True) == ["a\n", "\n"]
+"a\n".splitlines() == ["a"]
+"\r\r\n".splitlines(True) == ["\r", "\r\n"]
+"a\rb".splitlines(keepends = False) == ["a", "b"]
+"abc".partition("x") == ("abc", "", "")
+"abc".rpartition("x") == ("", "", "abc")
+"a.b.c".partition(".") == ("a", ".", "b.c")
+"a.b.c".rpartition(".") == ("a.b", ".", "c")
+"#,
+        );
+    }
+
+    #[test]
+    fn test_title_capitalize() {
+        assert::all_true(
+            r#"
+"".title() == ""
+"hello world".title() == "Hello World"
+"they're bill's".title() == "They'Re Bill'S"
+"a1b2 3c".title() == "A1B2 3C"
+"hello世界abc".title() == "Hello世界Abc"
+"éCOLE ÉTÉ".title() == "École Été"
+"ǆemal ǈubljana".title() == "ǅemal ǈubljana"
+"ß".title() == "Ss"
+"ﬁsh".title() == "Fish"
+"ΣΑΣ ΣΑΣ".title() == "Σας Σας"
+"".capitalize() == ""
+"hello World".capitalize() == "Hello world"
+"ǆemal".capitalize() == "ǅemal"
+"ßa".capitalize() == "Ssa"
+"1ABC".capitalize() == "1abc"
+"ΟΔΟΣ".capitalize() == "Οδος"
+"#,
+        );
+    }
+
+    #[test]
+    fn test_string_methods_listed() {
+        assert::all_true(
+            r#"
+"encode" in dir("")
+"removeprefix" in dir("")
+"removesuffix" in dir("")
+"splitlines" in dir("")
+"#,
+        );
+    }
+
+    #[test]
+    fn test_format_map() {
+        assert::all_true(
+            r#"
+"".format_map({}) == ""
+"plain".format_map({"unused": 1}) == "plain"
+"{a}{a}{b}".format_map({"a": 1, "b": [2]}) == "11[2]"
+"#,
+        );
+        assert::fail(r#""{a} {b}".format_map({"a": 1})"#, "Key `b` was not found");
+        assert::fail(r#""{}".format_map({})"#, "Not enough parameters");
+        assert::fail(r#""{a}".format_map([])"#, "Type of parameter `mapping`");
+    }
+
     #[test]
     fn test_opaque_iterator() {
         assert::is_true("type('foo'.elems()) != type([])");
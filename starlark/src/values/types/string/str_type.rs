@@ -24,6 +24,7 @@ use std::hash::Hash;
 use std::hash::Hasher;
 use std::mem;
 use std::ops::Deref;
+use std::ptr;
 use std::slice;
 use std::str;
 use std::sync::atomic;
@@ -89,7 +90,8 @@ impl Deref for StarlarkStr {
 
 impl PartialEq for StarlarkStr {
     fn eq(&self, other: &Self) -> bool {
-        self.as_aligned_padded_str() == other.as_aligned_padded_str()
+        // Short strings are shared, so equal strings are often the same string.
+        ptr::eq(self, other) || self.as_aligned_padded_str() == other.as_aligned_padded_str()
     }
 }
 
@@ -231,6 +233,10 @@ impl<'v> StarlarkValue<'v> for StarlarkStr {
         string_repr(self, buffer)
     }
 
+    fn collect_str(&self, buffer: &mut String) {
+        buffer.push_str(self.as_str())
+    }
+
     fn to_bool(&self) -> bool {
         !self.is_empty()
     }
@@ -265,16 +271,20 @@ impl<'v> StarlarkValue<'v> for StarlarkStr {
         // This method is disturbingly hot. Use the logic from `convert_index`,
         // but modified to be UTF8 string friendly.
         let i = i32::unpack_param(index)?;
+        let out_of_bound = || ValueError::IndexOutOfBoundForLength {
+            index: i,
+            len: fast_string::len(self).0 as i32,
+        };
         if i >= 0 {
             match fast_string::at(self, CharIndex(i as usize)) {
-                None => Err(ValueError::IndexOutOfBound(i).into()),
+                None => Err(out_of_bound().into()),
                 Some(c) => Ok(heap.alloc(c)),
             }
         } else {
             let len_chars = fast_string::len(self);
-            let ind = CharIndex((-i) as usize); // Index from the end, minimum of 1
+            let ind = CharIndex(i.unsigned_abs() as usize); // Index from the end, minimum of 1
             if ind > len_chars {
-                Err(ValueError::IndexOutOfBound(i).into())
+                Err(out_of_bound().into())
             } else if len_chars.0 == self.len() {
                 // We are a 7bit ASCII string, so take the fast-path
                 Ok(heap.alloc(self.as_bytes()[(len_chars - ind).0] as char))
@@ -434,6 +444,30 @@ mod tests {
         assert_ne!(0, heap.alloc("").get_hash().unwrap().get());
     }
 
+    #[test]
+    fn test_to_str_to_repr() {
+        let heap = Heap::new();
+        for x in EXAMPLES
+            .iter()
+            .chain(&["a\"b'c", "back\\slash", "new\nline\t\x00"])
+        {
+            let v = heap.alloc_str(x).to_value();
+            assert_eq!(*x, v.to_str());
+            assert_eq!(v.to_repr(), v.to_string());
+            // `repr` is a string literal which evaluates back to the original.
+            assert_eq!(*x, assert::pass(&v.to_repr()).unpack_str().unwrap());
+        }
+        let list = heap.alloc(vec!["x"]);
+        assert_eq!("[\"x\"]", list.to_str());
+        assert_eq!("[\"x\"]", list.to_repr());
+        assert::all_true(
+            r#"
+str("a\nb") == "a\nb"
+str(["a\nb"]) == '["a\\nb"]'
+"#,
+        );
+    }
+
     #[test]
     fn test_string_len() {
         assert::all_true(
@@ -75,6 +75,19 @@ impl TyCustomFunctionImpl for StructType {
 /// Register `struct` builtin.
 #[starlark_module]
 pub(crate) fn register_struct(builder: &mut GlobalsBuilder) {
+    /// Create an immutable record from named arguments, with the arguments as fields.
+    ///
+    /// Structs compare equal when they have the same fields with equal values,
+    /// `dir()` lists their fields, and `json.encode` renders them as objects.
+    ///
+    /// ```
+    /// # starlark::assert::all_true(r#"
+    /// struct(a = 1, b = 2).a == 1
+    /// struct(a = 1, b = 2) == struct(b = 2, a = 1)
+    /// dir(struct(b = 1, a = 2)) == ["a", "b"]
+    /// json.encode(struct(a = [1])) == '{"a":[1]}'
+    /// # "#);
+    /// ```
     #[starlark(
         ty_custom_function = StructType,
         as_type = FrozenStruct,
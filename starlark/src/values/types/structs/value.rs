@@ -262,6 +262,32 @@ json.encode(struct(foo = [struct(bar = "some")])) == '{"foo":[{"bar":"some"}]}'
         );
     }
 
+    #[test]
+    fn test_fields() {
+        assert::all_true(
+            r#"
+struct(a = 1, b = "x").b == "x"
+struct(a = 1, b = 2) == struct(b = 2, a = 1)
+struct(a = 1) != struct(a = 2)
+struct(a = 1) != struct(a = 1, b = 2)
+struct() == struct()
+dir(struct(b = 1, a = 2)) == ["a", "b"]
+hasattr(struct(a = None), "a")
+not hasattr(struct(a = 1), "b")
+getattr(struct(a = 1), "b", 3) == 3
+"#,
+        );
+        assert::fail(
+            "struct(a = 1).b",
+            "Object of type `struct` has no attribute `b`",
+        );
+        assert::fail(
+            "s = struct(a = 1)\ns.a = 2",
+            "Operation `.a=` not supported on type `struct`",
+        );
+        assert::fail("struct(1)", "positional");
+    }
+
     #[test]
     fn test_comparison_bug() {
         // TODO(nga): this should be false, because `a < b`,
@@ -88,6 +88,7 @@ impl UnboundValue {
         eval.with_call_stack(
             self.to_frozen_value().to_value(),
             Some(span),
+            &args.0,
             |eval| match self {
                 UnboundValue::Method(_, m) => m.invoke(eval, this, args),
                 UnboundValue::Attr(_, a) => {
@@ -37,8 +37,11 @@ use crate::coerce::Coerce;
 use crate::environment::Methods;
 use crate::environment::MethodsBuilder;
 use crate::environment::MethodsStatic;
+use crate::eval::Evaluator;
 use crate::private::Private;
+use crate::typing::tuple::TyTuple;
 use crate::typing::Ty;
+use crate::typing::TyBasic;
 use crate::values::dict::DictRef;
 use crate::values::layout::avalue::alloc_static;
 use crate::values::layout::avalue::AValueBasic;
@@ -65,11 +68,61 @@ use crate::values::Value;
 use crate::values::ValueLifetimeless;
 use crate::values::ValueLike;
 
+/// Find the innermost element of a list, tuple or dict `value` which does not match `ty`.
+///
+/// Returns the path to the element (e.g. `[0]["port"]`), the element and its expected type,
+/// or [`None`] if `value` is not a container or the mismatch is ambiguous.
+fn mismatch_path<'v>(ty: &Ty, value: Value<'v>, heap: &'v Heap) -> Option<(String, Value<'v>, Ty)> {
+    let matches = |ty: &Ty, x: Value<'v>| TypeCompiled::from_ty(ty, heap).matches(x);
+    let mut found = None;
+    for basic in ty.iter_union() {
+        let step = match basic {
+            TyBasic::List(elem) => ListRef::from_value(value).and_then(|xs| {
+                let (i, x) = xs.iter().enumerate().find(|(_, x)| !matches(elem, *x))?;
+                Some((format!("[{i}]"), x, (**elem).clone()))
+            }),
+            TyBasic::Tuple(tuple) => Tuple::from_value(value).and_then(|xs| {
+                let xs = xs.content();
+                if let TyTuple::Elems(elems) = tuple {
+                    if elems.len() != xs.len() {
+                        return None;
+                    }
+                }
+                xs.iter().enumerate().find_map(|(i, x)| {
+                    let elem = tuple.get(i)?;
+                    if matches(elem, *x) {
+                        None
+                    } else {
+                        Some((format!("[{i}]"), *x, elem.clone()))
+                    }
+                })
+            }),
+            TyBasic::Dict(_, elem) => DictRef::from_value(value).and_then(|xs| {
+                let (k, x) = xs.iter().find(|(_, x)| !matches(elem, *x))?;
+                Some((format!("[{}]", k.to_repr()), x, (**elem).clone()))
+            }),
+            _ => None,
+        };
+        if step.is_some() {
+            if found.is_some() {
+                // More than one alternative of a union could be meant.
+                return None;
+            }
+            found = step;
+        }
+    }
+    let (path, x, elem) = found?;
+    Some(match mismatch_path(&elem, x, heap) {
+        Some((rest, x, elem)) => (path + &rest, x, elem),
+        None => (path, x, elem),
+    })
+}
+
 #[derive(Debug, Error)]
-enum TypingError {
+pub(crate) enum TypingError {
     /// The value does not have the specified type
-    #[error("Value `{0}` of type `{1}` does not match the type annotation `{2}` for {3}")]
-    TypeAnnotationMismatch(String, String, String, String),
+    #[error("Value `{0}` of type `{1}` does not match the type annotation `{2}` for {3}{4}")]
+    TypeAnnotationMismatch(String, String, String, String, String),
     /// The given type annotation does not represent a type
     #[error("Type `{0}` is not a valid type annotation")]
     InvalidTypeAnnotation(String),
@@ -294,9 +347,26 @@ impl<'v, V: ValueLike<'v>> TypeCompiled<V> {
         self.downcast().unwrap().is_runtime_wildcard_dyn()
     }
 
+    /// `heap` is given to describe the path to the mismatching element.
     #[cold]
     #[inline(never)]
-    fn check_type_error(self, value: Value<'v>, arg_name: Option<&str>) -> crate::Result<()> {
+    fn check_type_error(
+        self,
+        value: Value<'v>,
+        arg_name: Option<&str>,
+        heap: Option<&'v Heap>,
+    ) -> crate::Result<()> {
+        let detail = match heap.and_then(|heap| mismatch_path(self.as_ty(), value, heap)) {
+            None => String::new(),
+            Some((path, value, ty)) => format!(
+                ", at `{}{}`: value `{}` of type `{}` does not match `{}`",
+                arg_name.unwrap_or(""),
+                path,
+                value.to_repr(),
+                value.get_type(),
+                ty,
+            ),
+        };
         Err(crate::Error::new_other(
             TypingError::TypeAnnotationMismatch(
                 value.to_str(),
@@ -306,6 +376,7 @@ impl<'v, V: ValueLike<'v>> TypeCompiled<V> {
                     None => "return type".to_owned(),
                     Some(x) => format!("argument `{}`", x),
                 },
+                detail,
             ),
         ))
     }
@@ -314,7 +385,23 @@ impl<'v, V: ValueLike<'v>> TypeCompiled<V> {
         if self.matches(value) {
             Ok(())
         } else {
-            self.check_type_error(value, arg_name)
+            self.check_type_error(value, arg_name, None)
+        }
+    }
+
+    /// Like [`check_type`](Self::check_type), but with
+    /// [verbose errors](Evaluator::verbose_errors) the error also points
+    /// at the element of a container which does not match.
+    pub(crate) fn check_type_in_eval(
+        self,
+        value: Value<'v>,
+        arg_name: Option<&str>,
+        eval: &Evaluator<'v, '_, '_>,
+    ) -> crate::Result<()> {
+        if self.matches(value) {
+            Ok(())
+        } else {
+            self.check_type_error(value, arg_name, eval.verbose_errors.then(|| eval.heap()))
         }
     }
 
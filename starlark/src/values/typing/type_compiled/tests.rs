@@ -18,6 +18,11 @@
 #![cfg(test)]
 
 use crate::assert;
+use crate::environment::Globals;
+use crate::environment::Module;
+use crate::eval::Evaluator;
+use crate::syntax::AstModule;
+use crate::syntax::Dialect;
 use crate::values::typing::type_compiled::compiled::TypeCompiled;
 use crate::values::Heap;
 
@@ -300,3 +305,87 @@ fn test_bit_or() {
         }
     }
 }
+
+#[test]
+fn test_verbose_errors() {
+    fn error(verbose: bool, program: &str) -> String {
+        let ast =
+            AstModule::parse("x.star", program.to_owned(), &Dialect::AllOptionsInternal).unwrap();
+        let module = Module::new();
+        let mut eval = Evaluator::new(&module);
+        if verbose {
+            eval.verbose_errors();
+        }
+        eval.eval_module(ast, &Globals::standard())
+            .unwrap_err()
+            .to_string()
+    }
+
+    let program = r#"
+def f(config: dict[str, list[tuple[int, ...]]]):
+    pass
+f({"a": [(1,)], "b": [(2,), (3, "4")]})
+"#;
+    assert!(
+        error(true, program).contains(
+            r#"for argument `config`, at `config["b"][1][1]`: value `"4"` of type `string` does not match `int`"#
+        ),
+        "{}",
+        error(true, program)
+    );
+    // Concise by default.
+    assert!(!error(false, program).contains(", at `"));
+
+    let program = r#"
+def f() -> list[int | None]:
+    return [1, None, "x"]
+f()
+"#;
+    let e = error(true, program);
+    assert!(
+        e.contains(
+            r#"for return type, at `[2]`: value `"x"` of type `string` does not match `None | int`"#
+        ),
+        "{e}"
+    );
+
+    // Not a container.
+    assert!(!error(true, "def f(x: list[int]):\n    pass\nf(1)").contains(", at `"));
+    // The container selects the alternative of the union.
+    let e = error(
+        true,
+        "def f(x: dict[str, int] | list[int]):\n    pass\nf([1, 'a'])",
+    );
+    assert!(e.contains("at `x[1]`"), "{e}");
+
+    // Attribute and index errors name the access expression up to the failing operation.
+    let config = r#"config = {"servers": [{"port": 80}]}"#;
+    for (access, path, msg) in [
+        (
+            r#"config["servers"][0].port"#,
+            r#"config["servers"][0].port"#,
+            "Object of type `dict` has no attribute `port`",
+        ),
+        (
+            r#"config["servers"][0]["host"]"#,
+            r#"config["servers"][0]["host"]"#,
+            "Key `\"host\"` was not found",
+        ),
+        (
+            r#"config["servers"][1]["port"]"#,
+            r#"config["servers"][1]"#,
+            "out of bound",
+        ),
+        (
+            r#"config["servers"][0]["port"][0]"#,
+            r#"config["servers"][0]["port"][0]"#,
+            "not supported",
+        ),
+    ] {
+        let program = format!("{config}\n{access} + 1");
+        let e = error(true, &program);
+        assert!(e.contains(&format!("At `{path}`: ")), "{e}");
+        assert!(e.contains(msg), "{e}");
+        assert!(!error(false, &program).contains("At `"));
+    }
+}
@@ -32,6 +32,7 @@ use starlark::debug::resolve_breakpoints;
 use starlark::debug::DapAdapter;
 use starlark::debug::DapAdapterClient;
 use starlark::debug::DapAdapterEvalHook;
+use starlark::debug::StepKind;
 use starlark::environment::Globals;
 use starlark::environment::Module;
 use starlark::eval::Evaluator;
@@ -219,6 +220,22 @@ impl DebugServer for Backend {
         self.adapter.continue_()?;
         Ok(ContinueResponseBody::default())
     }
+
+    fn next(&self, _: NextArguments) -> anyhow::Result<()> {
+        self.adapter.step(StepKind::Over)
+    }
+
+    fn step_in(&self, _: StepInArguments) -> anyhow::Result<()> {
+        self.adapter.step(StepKind::Into)
+    }
+
+    fn step_out(&self, _: StepOutArguments) -> anyhow::Result<()> {
+        self.adapter.step(StepKind::Out)
+    }
+
+    fn pause(&self, _: PauseArguments) -> anyhow::Result<()> {
+        self.adapter.pause()
+    }
 }
 
 pub(crate) fn server(dialect: Dialect, globals: Globals) {
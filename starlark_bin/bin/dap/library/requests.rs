@@ -35,6 +35,10 @@ pub(crate) trait DebugServer {
     fn scopes(&self, x: ScopesArguments) -> anyhow::Result<ScopesResponseBody>;
     fn variables(&self, x: VariablesArguments) -> anyhow::Result<VariablesResponseBody>;
     fn continue_(&self, x: ContinueArguments) -> anyhow::Result<ContinueResponseBody>;
+    fn next(&self, x: NextArguments) -> anyhow::Result<()>;
+    fn step_in(&self, x: StepInArguments) -> anyhow::Result<()>;
+    fn step_out(&self, x: StepOutArguments) -> anyhow::Result<()>;
+    fn pause(&self, x: PauseArguments) -> anyhow::Result<()>;
     fn evaluate(&self, x: EvaluateArguments) -> anyhow::Result<EvaluateResponseBody>;
     fn disconnect(&self, _x: DisconnectArguments) -> anyhow::Result<()> {
         Ok(())
@@ -84,6 +88,10 @@ pub(crate) fn dispatch(server: &impl DebugServer, r: &Request) -> Response {
         "scopes" => ret_some(r, server.scopes(arg(r))),
         "variables" => ret_some(r, server.variables(arg(r))),
         "continue" => ret_some(r, server.continue_(arg(r))),
+        "next" => ret_none(r, server.next(arg(r))),
+        "stepIn" => ret_none(r, server.step_in(arg(r))),
+        "stepOut" => ret_none(r, server.step_out(arg(r))),
+        "pause" => ret_none(r, server.pause(arg(r))),
         "evaluate" => ret_some(r, server.evaluate(arg(r))),
         "disconnect" => ret_none(r, server.disconnect(arg(r))),
         _ => ret_none(r, Err(anyhow::anyhow!("Unknown command: {}", r.command))),
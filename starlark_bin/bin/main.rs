@@ -49,6 +49,7 @@ mod bazel;
 mod dap;
 mod eval;
 mod suppression;
+mod testing;
 
 #[derive(Debug, Parser)]
 #[command(name = "starlark", about = "Evaluate Starlark code", version)]
@@ -91,6 +92,13 @@ struct Args {
     )]
     check: bool,
 
+    #[arg(
+        long = "test",
+        help = "Run the `test_*` functions of the given files.",
+        conflicts_with_all = &["lsp", "dap", "check", "docs", "evaluate"],
+    )]
+    test: bool,
+
     #[arg(
         long = "json",
         help = "Show output as JSON lines.",
@@ -287,7 +295,7 @@ fn main() -> anyhow::Result<()> {
     if args.dap {
         dap::server(dialect, globals);
     } else {
-        let is_interactive = args.evaluate.is_empty() && args.files.is_empty();
+        let is_interactive = args.evaluate.is_empty() && args.files.is_empty() && !args.test;
 
         let ext = args
             .extension
@@ -343,6 +351,8 @@ fn main() -> anyhow::Result<()> {
                 }
                 ArgsDoc::Code => println!("{}", global_module.render_as_code("globals")),
             };
+        } else if args.test {
+            testing::run_tests(&ctx, expand_dirs(ext, args.files.clone()), args.json)?;
         } else if is_interactive {
             interactive(&ctx)?;
         } else {
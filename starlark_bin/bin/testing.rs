@@ -0,0 +1,101 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::PathBuf;
+
+use dupe::Dupe;
+use starlark::assert::TestRunner;
+use starlark::syntax::AstModule;
+
+use crate::eval::Context;
+
+#[derive(Default)]
+struct TestStats {
+    passed: usize,
+    failed: usize,
+}
+
+fn print_json(path: &str, name: Option<&str>, error: Option<&starlark::Error>) {
+    let line = error
+        .and_then(|e| e.span())
+        .map(|span| span.resolve_span().begin.line + 1);
+    println!(
+        "{}",
+        serde_json::json!({
+            "path": path,
+            "name": name,
+            "passed": error.is_none(),
+            "error": error.map(|e| e.without_diagnostic().to_string()),
+            "line": line,
+        })
+    );
+}
+
+/// Run the `test_*` functions of each file, reporting one result per test.
+pub(crate) fn run_tests(
+    ctx: &Context,
+    files: impl Iterator<Item = PathBuf>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let mut runner = TestRunner::with_dialect_and_globals(&ctx.dialect, ctx.globals.dupe())?;
+    for p in &ctx.prelude {
+        runner.prelude(p.dupe());
+    }
+
+    let mut stats = TestStats::default();
+    for file in files {
+        let path = file.to_string_lossy();
+        let results =
+            AstModule::parse_file(&file, runner.dialect()).and_then(|ast| runner.run(ast));
+        let results = match results {
+            Ok(results) => results,
+            Err(e) => {
+                // The file itself failed to evaluate, so none of its tests ran.
+                stats.failed += 1;
+                if json {
+                    print_json(&path, None, Some(&e));
+                } else {
+                    println!("ERROR {path}\n{e}");
+                }
+                continue;
+            }
+        };
+        for result in results {
+            if result.passed() {
+                stats.passed += 1;
+            } else {
+                stats.failed += 1;
+            }
+            if json {
+                print_json(&path, Some(&result.name), result.error.as_ref());
+            } else {
+                match &result.error {
+                    None => println!("PASS {path}::{}", result.name),
+                    Some(e) => println!("FAIL {path}::{}\n{e}", result.name),
+                }
+            }
+        }
+    }
+
+    if !json {
+        println!("{} passed, {} failed", stats.passed, stats.failed);
+    }
+    if stats.failed > 0 {
+        return Err(anyhow::anyhow!("{} tests failed", stats.failed));
+    }
+    Ok(())
+}
@@ -232,6 +232,22 @@ fn expr_lvalue(x: &AstAssignTarget, res: &mut Vec<Bind>) {
     x.visit_lvalue(|x| res.push(Bind::Set(Assigner::Assign, x.clone())))
 }
 
+/// Evaluate all variables and expressions in an assignment target, reading its variables.
+fn expr_lvalue_get(x: &AstAssignTarget, res: &mut Vec<Bind>) {
+    x.visit_expr(|x| expr(x, res));
+    x.visit_lvalue(|x| {
+        res.push(Bind::Get(x.clone().map(
+            |AssignIdentP {
+                 ident: s,
+                 payload: (),
+             }| IdentP {
+                ident: s,
+                payload: (),
+            },
+        )))
+    });
+}
+
 fn parameters(args: &[AstParameter], res: &mut Vec<Bind>, inner: &mut Vec<Bind>) {
     for a in args {
         let (name, typ, default) = a.split();
@@ -261,6 +277,7 @@ fn stmt(x: &AstStmt, res: &mut Vec<Bind>) {
             flow(res)
         }
         Stmt::Expression(x) => expr(x, res),
+        Stmt::Del(x) => expr_lvalue_get(x, res),
         Stmt::If(a, b) => {
             expr(a, res);
             flow(res);
@@ -300,18 +317,7 @@ fn stmt(x: &AstStmt, res: &mut Vec<Bind>) {
             // 1. Evaluate all variables and expressions in a.
             // 2. Evaluate b.
             // 3. Assign to all variables in a.
-            lhs.visit_expr(|x| expr(x, res));
-            lhs.visit_lvalue(|x| {
-                res.push(Bind::Get(x.clone().map(
-                    |AssignIdentP {
-                         ident: s,
-                         payload: (),
-                     }| IdentP {
-                        ident: s,
-                        payload: (),
-                    },
-                )))
-            });
+            expr_lvalue_get(lhs, res);
             expr(rhs, res);
             expr_lvalue(lhs, res);
         }
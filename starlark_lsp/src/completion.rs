@@ -20,6 +20,7 @@
 use std::collections::HashMap;
 use std::path::Path;
 
+use dupe::Dupe;
 use lsp_types::CompletionItem;
 use lsp_types::CompletionItemKind;
 use lsp_types::CompletionTextEdit;
@@ -28,20 +29,33 @@ use lsp_types::MarkupContent;
 use lsp_types::MarkupKind;
 use lsp_types::Range;
 use lsp_types::TextEdit;
+use starlark::codemap::CodeMap;
+use starlark::codemap::Pos;
 use starlark::codemap::ResolvedSpan;
+use starlark::codemap::Span;
 use starlark::docs::markdown::render_doc_item_no_link;
 use starlark::docs::markdown::render_doc_param;
 use starlark::docs::DocItem;
 use starlark::docs::DocMember;
+use starlark::environment::FrozenModule;
+use starlark::environment::Globals;
+use starlark::syntax::AstModule;
 use starlark_syntax::codemap::ResolvedPos;
+use starlark_syntax::syntax::ast::AstExprP;
+use starlark_syntax::syntax::ast::AstLiteral;
+use starlark_syntax::syntax::ast::AstNoPayload;
+use starlark_syntax::syntax::ast::ExprP;
 use starlark_syntax::syntax::ast::StmtP;
 use starlark_syntax::syntax::module::AstModuleFields;
+use starlark_syntax::syntax::uniplate::Visit;
 
 use crate::definition::Definition;
 use crate::definition::DottedDefinition;
 use crate::definition::IdentifierDefinition;
 use crate::definition::LspModule;
 use crate::exported::SymbolKind as ExportedSymbolKind;
+use crate::inspect::AstModuleInspect;
+use crate::inspect::AutocompleteType;
 use crate::server::Backend;
 use crate::server::LspContext;
 use crate::server::LspUrl;
@@ -70,6 +84,220 @@ pub struct StringCompletionResult {
     pub kind: CompletionItemKind,
 }
 
+/// The kind of symbol offered by a [`Completion`].
+#[derive(Debug, Clone, Copy, Dupe, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CompletionKind {
+    /// A function, either defined in the module or a global.
+    Function,
+    /// A method or attribute, offered after a `.`.
+    Method,
+    /// A variable or parameter defined in the module.
+    Variable,
+    /// A global value that is not a function, e.g. a type or a constant.
+    Value,
+}
+
+/// A possible result of [`complete`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    /// The name to insert.
+    pub label: String,
+    /// What kind of symbol this is.
+    pub kind: CompletionKind,
+    /// The one-line documentation of the symbol, if available.
+    pub detail: Option<String>,
+    /// The range of the document that should be replaced with [`label`](Completion::label).
+    pub replace_span: ResolvedSpan,
+}
+
+/// Offer completions for the cursor at `pos` in `ast`, without requiring a running LSP server.
+///
+/// Offers, depending on where the cursor is:
+///
+/// * After a `.`, the methods of the receiver if its type is statically known,
+///   which is currently the case for string, list and dict expressions.
+///   The methods are taken from the type documentation in `globals`.
+/// * Inside the symbol list of a `load`, the public symbols of the module in `loaded`
+///   keyed by the load path.
+/// * Elsewhere in expressions, the locals, parameters and module level bindings in scope,
+///   followed by the members of `globals`. Names defined in the module shadow globals.
+///
+/// Results are sorted by label.
+pub fn complete(
+    ast: &AstModule,
+    pos: ResolvedPos,
+    globals: &Globals,
+    loaded: &HashMap<String, FrozenModule>,
+) -> Vec<Completion> {
+    let codemap = ast.codemap();
+    let Some(line_span) = codemap.line_span_opt(pos.line) else {
+        return Vec::new();
+    };
+    // The column counts characters, the codemap counts bytes.
+    let line = codemap.source_span(line_span);
+    let column = line
+        .char_indices()
+        .nth(pos.column)
+        .map_or(line.len(), |(i, _)| i);
+    let offset = line_span.begin() + column as u32;
+
+    let mut completions = match find_dot_at(Visit::Stmt(ast.statement()), offset) {
+        Some((receiver, attr_span)) => {
+            attribute_completions(receiver, codemap.resolve_span(attr_span), globals)
+        }
+        None => match ast.get_auto_complete_type(pos.line as u32, pos.column as u32) {
+            Some(AutocompleteType::LoadSymbol {
+                path,
+                current_span,
+                previously_loaded,
+            }) => loaded
+                .get(&path)
+                .map(|module| load_symbol_completions(module, current_span, &previously_loaded))
+                .unwrap_or_default(),
+            Some(AutocompleteType::Default) | Some(AutocompleteType::Parameter { .. }) => {
+                let replace_span = codemap.resolve_span(identifier_span_at(codemap, offset));
+                identifier_completions(ast, pos, replace_span, globals)
+            }
+            Some(
+                AutocompleteType::LoadPath { .. }
+                | AutocompleteType::String { .. }
+                | AutocompleteType::Type
+                | AutocompleteType::None,
+            )
+            | None => Vec::new(),
+        },
+    };
+    completions.sort_by(|a, b| (&a.label, a.kind).cmp(&(&b.label, b.kind)));
+    completions
+}
+
+/// Find the `receiver.attr` expression whose attribute name contains `offset`.
+fn find_dot_at(
+    node: Visit<'_, AstNoPayload>,
+    offset: Pos,
+) -> Option<(&AstExprP<AstNoPayload>, Span)> {
+    if let Visit::Expr(AstExprP {
+        node: ExprP::Dot(receiver, attr),
+        ..
+    }) = node
+    {
+        if attr.span.contains(offset) {
+            return Some((receiver, attr.span));
+        }
+    }
+    let mut result = None;
+    node.visit_children(|child| {
+        if result.is_none() {
+            result = find_dot_at(child, offset);
+        }
+    });
+    result
+}
+
+/// The span of the identifier around `offset`, or an empty span at `offset` if there is none.
+fn identifier_span_at(codemap: &CodeMap, offset: Pos) -> Span {
+    let source = codemap.source().as_bytes();
+    let is_ident = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+    let mut begin = offset.get() as usize;
+    while begin > 0 && is_ident(source[begin - 1]) {
+        begin -= 1;
+    }
+    let mut end = offset.get() as usize;
+    while end < source.len() && is_ident(source[end]) {
+        end += 1;
+    }
+    Span::new(Pos::new(begin as u32), Pos::new(end as u32))
+}
+
+fn attribute_completions(
+    receiver: &AstExprP<AstNoPayload>,
+    replace_span: ResolvedSpan,
+    globals: &Globals,
+) -> Vec<Completion> {
+    let type_name = match &receiver.node {
+        ExprP::Literal(AstLiteral::String(_)) | ExprP::FString(_) => "str",
+        ExprP::List(_) | ExprP::ListComprehension(..) => "list",
+        ExprP::Dict(_) | ExprP::DictComprehension(..) => "dict",
+        _ => return Vec::new(),
+    };
+    match globals.documentation().members.get(type_name) {
+        Some(DocItem::Type(ty)) => ty
+            .members
+            .iter()
+            .map(|(name, member)| Completion {
+                label: name.clone(),
+                kind: CompletionKind::Method,
+                detail: member.get_doc_summary().map(str::to_owned),
+                replace_span,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn load_symbol_completions(
+    module: &FrozenModule,
+    replace_span: ResolvedSpan,
+    previously_loaded: &[String],
+) -> Vec<Completion> {
+    module
+        .documentation()
+        .members
+        .into_iter()
+        .filter(|(name, _)| !previously_loaded.contains(name))
+        .map(|(name, doc)| Completion {
+            kind: match doc {
+                DocItem::Member(DocMember::Function(_)) => CompletionKind::Function,
+                _ => CompletionKind::Variable,
+            },
+            detail: doc.get_doc_summary().map(str::to_owned),
+            label: name,
+            replace_span,
+        })
+        .collect()
+}
+
+fn identifier_completions(
+    ast: &AstModule,
+    pos: ResolvedPos,
+    replace_span: ResolvedSpan,
+    globals: &Globals,
+) -> Vec<Completion> {
+    let symbols = find_symbols_at_location(ast.codemap(), ast.statement(), pos);
+    let globals = globals
+        .documentation()
+        .members
+        .into_iter()
+        .filter(|(name, _)| !symbols.contains_key(name))
+        .map(|(name, doc)| Completion {
+            kind: match doc {
+                DocItem::Member(DocMember::Function(_)) => CompletionKind::Function,
+                _ => CompletionKind::Value,
+            },
+            detail: doc.get_doc_summary().map(str::to_owned),
+            label: name,
+            replace_span,
+        })
+        .collect::<Vec<_>>();
+    symbols
+        .into_values()
+        .map(|symbol| Completion {
+            kind: match symbol.kind {
+                SymbolKind::Method => CompletionKind::Function,
+                SymbolKind::Variable => CompletionKind::Variable,
+            },
+            detail: symbol.detail.or_else(|| {
+                symbol
+                    .doc
+                    .and_then(|doc| doc.get_doc_summary().map(str::to_owned))
+            }),
+            label: symbol.name,
+            replace_span,
+        })
+        .chain(globals)
+        .collect()
+}
+
 impl<T: LspContext> Backend<T> {
     pub(crate) fn default_completion_options(
         &self,
@@ -367,3 +595,140 @@ impl<T: LspContext> Backend<T> {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use starlark::environment::FrozenModule;
+    use starlark::environment::Globals;
+    use starlark::environment::Module;
+    use starlark::eval::Evaluator;
+    use starlark::syntax::AstModule;
+    use starlark::syntax::Dialect;
+    use starlark_syntax::codemap::ResolvedPos;
+
+    use super::complete;
+    use super::Completion;
+    use super::CompletionKind;
+
+    fn parse(program: &str) -> AstModule {
+        AstModule::parse("t.star", program.to_owned(), &Dialect::AllOptionsInternal).unwrap()
+    }
+
+    fn frozen_module(program: &str) -> FrozenModule {
+        let module = Module::new();
+        {
+            let mut eval = Evaluator::new(&module);
+            eval.eval_module(parse(program), &Globals::standard())
+                .unwrap();
+        }
+        module.freeze().unwrap()
+    }
+
+    fn labels(completions: &[Completion]) -> Vec<(&str, CompletionKind)> {
+        completions
+            .iter()
+            .map(|c| (c.label.as_str(), c.kind))
+            .collect()
+    }
+
+    #[test]
+    fn completes_string_methods() {
+        let ast = parse("x = 'abc'.st");
+        let completions = complete(
+            &ast,
+            ResolvedPos {
+                line: 0,
+                column: 12,
+            },
+            &Globals::standard(),
+            &HashMap::new(),
+        );
+        let names = labels(&completions);
+        assert!(names.contains(&("startswith", CompletionKind::Method)));
+        assert!(names.contains(&("strip", CompletionKind::Method)));
+        assert!(!names.contains(&("append", CompletionKind::Method)));
+        let strip = completions.iter().find(|c| c.label == "strip").unwrap();
+        assert!(strip.detail.is_some());
+        assert_eq!("1:11-13", strip.replace_span.to_string(),);
+    }
+
+    #[test]
+    fn completes_after_multi_byte_characters() {
+        let ast = parse("x = 'ÿ€😀'.st");
+        let completions = complete(
+            &ast,
+            ResolvedPos {
+                line: 0,
+                column: 12,
+            },
+            &Globals::standard(),
+            &HashMap::new(),
+        );
+        let names = labels(&completions);
+        assert!(names.contains(&("startswith", CompletionKind::Method)));
+        assert!(names.contains(&("strip", CompletionKind::Method)));
+    }
+
+    #[test]
+    fn completes_load_symbols() {
+        let ast = parse(r#"load("lib.star", "b")"#);
+        let loaded = HashMap::from([(
+            "lib.star".to_owned(),
+            frozen_module(
+                r#"
+def a():
+    """Does a."""
+    pass
+b = 1
+c = 2
+_private = 3
+"#,
+            ),
+        )]);
+        let completions = complete(
+            &ast,
+            ResolvedPos {
+                line: 0,
+                column: 18,
+            },
+            &Globals::standard(),
+            &loaded,
+        );
+        // `b` is the symbol being completed, so it is offered too.
+        assert_eq!(
+            vec![
+                ("a", CompletionKind::Function),
+                ("b", CompletionKind::Variable),
+                ("c", CompletionKind::Variable),
+            ],
+            labels(&completions)
+        );
+        assert_eq!(Some("Does a."), completions[0].detail.as_deref());
+        assert_eq!("1:19-20", completions[0].replace_span.to_string());
+    }
+
+    #[test]
+    fn completes_identifiers() {
+        let ast = parse("def f(param):\n    return pa\nlen = 1\n");
+        let completions = complete(
+            &ast,
+            ResolvedPos {
+                line: 1,
+                column: 13,
+            },
+            &Globals::standard(),
+            &HashMap::new(),
+        );
+        let names = labels(&completions);
+        assert!(names.contains(&("param", CompletionKind::Variable)));
+        assert!(names.contains(&("f", CompletionKind::Function)));
+        assert!(names.contains(&("str", CompletionKind::Value)));
+        // The module binding shadows the global function.
+        assert!(names.contains(&("len", CompletionKind::Variable)));
+        assert!(!names.contains(&("len", CompletionKind::Function)));
+        assert!(names.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!("2:12-14", completions[0].replace_span.to_string());
+    }
+}
@@ -0,0 +1,147 @@
+/*
+ * Copyright 2019 The Starlark in Rust Authors.
+ * Copyright (c) Facebook, Inc. and its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     https://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Find the symbols defined at the top level of a module, used to answer
+//! `textDocument/documentSymbol` requests.
+
+use lsp_types::DocumentSymbol;
+use lsp_types::SymbolKind;
+use starlark::codemap::CodeMap;
+use starlark::codemap::Span;
+use starlark_syntax::syntax::ast::AssignP;
+use starlark_syntax::syntax::ast::AstAssignIdentP;
+use starlark_syntax::syntax::ast::AstPayload;
+use starlark_syntax::syntax::ast::AstStmtP;
+use starlark_syntax::syntax::ast::ExprP;
+use starlark_syntax::syntax::ast::StmtP;
+
+use crate::docs::get_doc_item_for_def;
+
+/// Collect `def`s and assignments at the top level of the module, in source order.
+///
+/// Function bodies are not entered, but the bodies of top-level `if` and `for`
+/// statements are, because bindings made there are module-level bindings too.
+pub(crate) fn get_document_symbols<P: AstPayload>(
+    codemap: &CodeMap,
+    ast: &AstStmtP<P>,
+) -> Vec<DocumentSymbol> {
+    fn symbol<P: AstPayload>(
+        codemap: &CodeMap,
+        name: &AstAssignIdentP<P>,
+        span: Span,
+        kind: SymbolKind,
+        detail: Option<String>,
+    ) -> DocumentSymbol {
+        #[allow(deprecated)] // `deprecated` is deprecated in favour of `tags`, but still required.
+        DocumentSymbol {
+            name: name.ident.clone(),
+            detail,
+            kind,
+            tags: None,
+            deprecated: None,
+            range: codemap.resolve_span(span).into(),
+            selection_range: codemap.resolve_span(name.span).into(),
+            children: None,
+        }
+    }
+
+    fn walk<P: AstPayload>(
+        codemap: &CodeMap,
+        ast: &AstStmtP<P>,
+        symbols: &mut Vec<DocumentSymbol>,
+    ) {
+        match &ast.node {
+            StmtP::Def(def) => {
+                let detail = get_doc_item_for_def(def, codemap)
+                    .and_then(|doc| doc.docs)
+                    .map(|docs| docs.summary);
+                symbols.push(symbol(
+                    codemap,
+                    &def.name,
+                    ast.span,
+                    SymbolKind::FUNCTION,
+                    detail,
+                ));
+            }
+            StmtP::Assign(AssignP { lhs, ty: _, rhs }) => {
+                let kind = match rhs.node {
+                    ExprP::Lambda(_) => SymbolKind::FUNCTION,
+                    _ => SymbolKind::VARIABLE,
+                };
+                lhs.visit_lvalue(|x| symbols.push(symbol(codemap, x, ast.span, kind, None)));
+            }
+            stmt => stmt.visit_stmt(|x| walk(codemap, x, symbols)),
+        }
+    }
+
+    let mut symbols = Vec::new();
+    walk(codemap, ast, &mut symbols);
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::SymbolKind;
+    use starlark::syntax::AstModule;
+    use starlark::syntax::Dialect;
+    use starlark_syntax::syntax::module::AstModuleFields;
+
+    use super::get_document_symbols;
+
+    #[test]
+    fn top_level_symbols() {
+        let ast_module = AstModule::parse(
+            "t.star",
+            r#"load("foo.star", "loaded")
+
+def method(param):
+    """Does something."""
+    inner = 1
+
+my_var = True
+(a, b) = 1, 2
+f = lambda x: x
+if my_var:
+    in_if = 3
+"#
+            .to_owned(),
+            &Dialect::AllOptionsInternal,
+        )
+        .unwrap();
+
+        let symbols = get_document_symbols(ast_module.codemap(), ast_module.statement());
+        assert_eq!(
+            vec![
+                ("method", SymbolKind::FUNCTION, Some("Does something.")),
+                ("my_var", SymbolKind::VARIABLE, None),
+                ("a", SymbolKind::VARIABLE, None),
+                ("b", SymbolKind::VARIABLE, None),
+                ("f", SymbolKind::FUNCTION, None),
+                ("in_if", SymbolKind::VARIABLE, None),
+            ],
+            symbols
+                .iter()
+                .map(|s| (s.name.as_str(), s.kind, s.detail.as_deref()))
+                .collect::<Vec<_>>()
+        );
+
+        let method = &symbols[0];
+        assert_eq!(2, method.range.start.line);
+        assert_eq!(2, method.selection_range.start.line);
+        assert_eq!(4, method.selection_range.start.character);
+    }
+}
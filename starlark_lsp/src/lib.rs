@@ -25,6 +25,7 @@ mod bind;
 pub mod completion;
 mod definition;
 pub(crate) mod docs;
+mod document_symbols;
 pub mod error;
 mod exported;
 pub(crate) mod inspect;
@@ -43,6 +43,7 @@ use lsp_types::notification::DidOpenTextDocument;
 use lsp_types::notification::LogMessage;
 use lsp_types::notification::PublishDiagnostics;
 use lsp_types::request::Completion;
+use lsp_types::request::DocumentSymbolRequest;
 use lsp_types::request::GotoDefinition;
 use lsp_types::request::HoverRequest;
 use lsp_types::CompletionItem;
@@ -55,6 +56,8 @@ use lsp_types::Diagnostic;
 use lsp_types::DidChangeTextDocumentParams;
 use lsp_types::DidCloseTextDocumentParams;
 use lsp_types::DidOpenTextDocumentParams;
+use lsp_types::DocumentSymbolParams;
+use lsp_types::DocumentSymbolResponse;
 use lsp_types::Documentation;
 use lsp_types::GotoDefinitionParams;
 use lsp_types::GotoDefinitionResponse;
@@ -105,6 +108,7 @@ use crate::definition::Definition;
 use crate::definition::DottedDefinition;
 use crate::definition::IdentifierDefinition;
 use crate::definition::LspModule;
+use crate::document_symbols::get_document_symbols;
 use crate::inspect::AstModuleInspect;
 use crate::inspect::AutocompleteType;
 use crate::symbols::find_symbols_at_location;
@@ -408,6 +412,7 @@ impl<T: LspContext> Backend<T> {
             definition_provider,
             completion_provider: Some(CompletionOptions::default()),
             hover_provider: Some(HoverProviderCapability::Simple(true)),
+            document_symbol_provider: Some(OneOf::Left(true)),
             ..ServerCapabilities::default()
         }
     }
@@ -506,6 +511,11 @@ impl<T: LspContext> Backend<T> {
         self.send_response(new_response(id, self.hover_info(params, initialize_params)));
     }
 
+    /// Offers the symbols defined at the top level of the document.
+    fn document_symbols(&self, id: RequestId, params: DocumentSymbolParams) {
+        self.send_response(new_response(id, self.get_document_symbols(params)));
+    }
+
     /// Get the file contents of a starlark: URI.
     fn get_starlark_file_contents(&self, id: RequestId, params: StarlarkFileContentsParams) {
         let response: anyhow::Result<_> = match params.uri {
@@ -998,6 +1008,21 @@ impl<T: LspContext> Backend<T> {
         })
     }
 
+    /// Get the top level symbols of a document, using the last valid parse.
+    fn get_document_symbols(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> anyhow::Result<DocumentSymbolResponse> {
+        let uri = params.text_document.uri.try_into()?;
+        let symbols = match self.get_ast(&uri) {
+            Some(document) => {
+                get_document_symbols(document.ast.codemap(), document.ast.statement())
+            }
+            None => Vec::new(),
+        };
+        Ok(DocumentSymbolResponse::Nested(symbols))
+    }
+
     /// Get hover information for a given position in a document.
     fn hover_info(
         &self,
@@ -1213,8 +1238,6 @@ impl<T: LspContext> Backend<T> {
         for msg in &self.connection.receiver {
             match msg {
                 Message::Request(req) => {
-                    // TODO(nmj): Also implement DocumentSymbols so that some logic can
-                    //            be handled client side.
                     if let Some(params) = as_request::<GotoDefinition>(&req) {
                         self.goto_definition(req.id, params, &initialize_params);
                     } else if let Some(params) = as_request::<StarlarkFileContentsRequest>(&req) {
@@ -1223,6 +1246,8 @@ impl<T: LspContext> Backend<T> {
                         self.completion(req.id, params, &initialize_params);
                     } else if let Some(params) = as_request::<HoverRequest>(&req) {
                         self.hover(req.id, params, &initialize_params);
+                    } else if let Some(params) = as_request::<DocumentSymbolRequest>(&req) {
+                        self.document_symbols(req.id, params);
                     } else if self.connection.handle_shutdown(&req)? {
                         return Ok(());
                     }
@@ -1371,12 +1396,16 @@ mod tests {
     use anyhow::Context;
     use lsp_server::Request;
     use lsp_server::RequestId;
+    use lsp_types::request::DocumentSymbolRequest;
     use lsp_types::request::GotoDefinition;
+    use lsp_types::DocumentSymbolParams;
+    use lsp_types::DocumentSymbolResponse;
     use lsp_types::GotoDefinitionParams;
     use lsp_types::GotoDefinitionResponse;
     use lsp_types::LocationLink;
     use lsp_types::Position;
     use lsp_types::Range;
+    use lsp_types::SymbolKind;
     use lsp_types::TextDocumentIdentifier;
     use lsp_types::TextDocumentPositionParams;
     use lsp_types::Url;
@@ -2254,6 +2283,52 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn returns_document_symbols() -> anyhow::Result<()> {
+        if is_wasm() {
+            return Ok(());
+        }
+
+        let mut server = TestServer::new()?;
+        let uri = temp_file_uri("file.star");
+        let contents = dedent(
+            r#"
+            def foo():
+                pass
+
+            bar = foo()
+            "#,
+        )
+        .trim()
+        .to_owned();
+        server.open_file(uri.clone(), contents)?;
+
+        let req = server.new_request::<DocumentSymbolRequest>(DocumentSymbolParams {
+            text_document: TextDocumentIdentifier { uri },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+        let request_id = server.send_request(req)?;
+        let response = server.get_response::<DocumentSymbolResponse>(request_id)?;
+        let symbols = match response {
+            DocumentSymbolResponse::Nested(symbols) => symbols,
+            DocumentSymbolResponse::Flat(_) => panic!("Expected nested symbols"),
+        };
+        assert_eq!(
+            vec![("foo", SymbolKind::FUNCTION), ("bar", SymbolKind::VARIABLE)],
+            symbols
+                .iter()
+                .map(|s| (s.name.as_str(), s.kind))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Range::new(Position::new(3, 0), Position::new(3, 3)),
+            symbols[1].selection_range
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn returns_starlark_file_contents() -> anyhow::Result<()> {
         if is_wasm() {
@@ -25,7 +25,12 @@ use crate::hash_value::StarlarkHashValue;
 
 /// A hasher used by Starlark implementation.
 ///
-/// Starlark relies on stable hashing, and this is the hasher.
+/// Starlark relies on stable hashing, and this is the hasher: it is
+/// [FxHash](https://docs.rs/fxhash), which is not randomized, so the same key
+/// has the same hash in every process on the same platform.
+///
+/// The hash values are not guaranteed to stay the same across versions of this crate
+/// or across platforms, so they must not be persisted. Use [`StableHasher`] for that.
 #[derive(Default)]
 pub struct StarlarkHasher(
     // TODO(nga): `FxHasher64` is endian-dependent, this is not right.
@@ -43,9 +48,11 @@ impl StarlarkHasher {
     #[inline]
     pub fn finish_small(&self) -> StarlarkHashValue {
         // NOTE: Here we throw away half the key material we are given,
-        // taking only the lower 32 bits.
-        // Not a problem because `DefaultHasher` produces well-swizzled bits.
-        StarlarkHashValue::new_unchecked(self.finish() as u32)
+        // taking only the upper 32 bits.
+        // FxHash ends with a multiplication, so the lower bits of the result
+        // only depend on the lower bits of the last words: strings like `dep_1234`,
+        // which differ in the upper half of a word, would collide in the lower 32 bits.
+        StarlarkHashValue::new_unchecked((self.finish() >> 32) as u32)
     }
 }
 
@@ -103,3 +110,127 @@ impl BuildHasher for StarlarkHasherBuilder {
         StarlarkHasher::default()
     }
 }
+
+/// A hasher whose output is the same on all platforms and will not change
+/// in future versions of this crate, so can be persisted.
+///
+/// It is 64-bit [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function),
+/// with integers written as little-endian bytes and `usize` written as `u64`.
+/// This makes it slower than [`StarlarkHasher`], and the hash of a value is only stable
+/// as long as its [`Hash`](std::hash::Hash) implementation feeds the same data to the hasher.
+///
+/// ```
+/// use std::hash::Hasher;
+///
+/// use starlark_map::StableHasher;
+///
+/// let mut hasher = StableHasher::new();
+/// hasher.write(b"hello");
+/// assert_eq!(hasher.finish(), 0xa430d84680aabd0b);
+/// ```
+#[derive(Debug, Clone, Copy, Dupe)]
+pub struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    /// Creates a new hasher.
+    #[inline]
+    pub fn new() -> StableHasher {
+        StableHasher(Self::OFFSET_BASIS)
+    }
+}
+
+impl Default for StableHasher {
+    #[inline]
+    fn default() -> Self {
+        StableHasher::new()
+    }
+}
+
+impl Hasher for StableHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ u64::from(*b)).wrapping_mul(Self::PRIME);
+        }
+    }
+
+    #[inline]
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    #[inline]
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    #[inline]
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    #[inline]
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::hash::Hash;
+    use std::hash::Hasher;
+
+    use crate::StableHasher;
+    use crate::StarlarkHasher;
+
+    fn stable_hash(x: impl Hash) -> u64 {
+        let mut hasher = StableHasher::new();
+        x.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_stable_hasher_vectors() {
+        // These values must never change.
+        assert_eq!(0xcbf29ce484222325, stable_hash(()));
+        assert_eq!(0xaf63bd4c8601b7df, stable_hash(0u8));
+        assert_eq!(0xa430d84680aabd0b, {
+            let mut hasher = StableHasher::new();
+            hasher.write(b"hello");
+            hasher.finish()
+        });
+        assert_eq!(stable_hash(1u64), stable_hash(1usize));
+        assert_eq!(stable_hash(0x0102u16), {
+            let mut hasher = StableHasher::new();
+            hasher.write(&[2, 1]);
+            hasher.finish()
+        });
+    }
+
+    #[test]
+    fn test_finish_small_strings_differing_in_upper_half_of_word() {
+        // The digits of `dep_1234` are in the upper half of the first 8-byte word.
+        let hashes: HashSet<_> = (0..10000)
+            .map(|i| {
+                let mut hasher = StarlarkHasher::new();
+                format!("dep_{i}").hash(&mut hasher);
+                hasher.finish_small()
+            })
+            .collect();
+        assert!(hashes.len() > 9900, "{} distinct hashes", hashes.len());
+    }
+}
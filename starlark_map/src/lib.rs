@@ -47,5 +47,6 @@ pub(crate) mod vec_map;
 pub use equivalent::Equivalent;
 pub use hash_value::StarlarkHashValue;
 pub use hashed::Hashed;
+pub use hasher::StableHasher;
 pub use hasher::StarlarkHasher;
 pub use hasher::StarlarkHasherBuilder;
@@ -238,6 +238,18 @@ impl<K, V> SmallMap<K, V> {
     }
 
     /// Query the map by a prehashed key.
+    ///
+    /// The key may be borrowed, e.g. a map with `String` keys can be queried
+    /// with a `Hashed<&str>`, which has the same hash as the `String`:
+    ///
+    /// ```
+    /// use starlark_map::small_map::SmallMap;
+    /// use starlark_map::Hashed;
+    ///
+    /// let mut map = SmallMap::new();
+    /// map.insert_hashed(Hashed::new("x".to_owned()), 1);
+    /// assert_eq!(map.get_hashed(Hashed::new("x")), Some(&1));
+    /// ```
     #[inline]
     pub fn get_hashed<Q>(&self, key: Hashed<&Q>) -> Option<&V>
     where
@@ -685,6 +697,14 @@ impl<K, V> SmallMap<K, V> {
         }
     }
 
+    /// Remove all elements from the map, returning them in order.
+    ///
+    /// Unlike [`clear`](SmallMap::clear), the capacity is not retained.
+    #[inline]
+    pub fn drain(&mut self) -> IntoIter<K, V> {
+        mem::take(self).into_iter()
+    }
+
     /// Basic check the map invariants are hold.
     #[cfg(test)]
     fn state_check(&self) {
@@ -895,6 +915,15 @@ where
         self.or_insert_entry_with(default).1
     }
 
+    /// Modify the value if occupied, before a call to e.g. [`or_insert`](Entry::or_insert).
+    #[inline]
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let Entry::Occupied(e) = &mut self {
+            f(e.get_mut());
+        }
+        self
+    }
+
     /// Insert if vacant.
     #[inline]
     pub fn or_default(self) -> &'a mut V
@@ -1479,4 +1508,61 @@ mod tests {
         assert_eq!(map.get("7"), None);
         assert_eq!(map.get("8"), Some(&11));
     }
+
+    #[test]
+    fn test_entry_across_index_threshold() {
+        let mut map = SmallMap::new();
+        for i in 0..NO_INDEX_THRESHOLD * 2 {
+            // Every key is inserted once and then modified once.
+            for _ in 0..2 {
+                map.entry(i).and_modify(|v| *v += 1).or_insert(0);
+            }
+            assert_eq!(map.len(), i + 1);
+            assert_eq!(map.get(&i), Some(&1));
+        }
+        assert!(map.keys().copied().eq(0..NO_INDEX_THRESHOLD * 2));
+
+        let key = Hashed::new(NO_INDEX_THRESHOLD * 2);
+        assert_eq!(map.get_hashed(key.as_ref()), None);
+        *map.entry_hashed(key).or_insert_with(|| 5) += 1;
+        assert_eq!(map.get_hashed(key.as_ref()), Some(&6));
+        assert_eq!(map.insert_hashed(key, 7), Some(6));
+        assert_eq!(map.last(), Some((&(NO_INDEX_THRESHOLD * 2), &7)));
+    }
+
+    #[test]
+    fn test_mutation_across_index_threshold() {
+        for n in [
+            NO_INDEX_THRESHOLD - 1,
+            NO_INDEX_THRESHOLD,
+            NO_INDEX_THRESHOLD + 1,
+        ] {
+            // Duplicate keys keep their first position, and the last value.
+            let mut map = (0..n)
+                .chain(0..n)
+                .map(|i| (i.to_string(), i))
+                .collect::<SmallMap<_, _>>();
+            map.extend((0..n).map(|i| (i.to_string(), i * 2)));
+            map.assert_invariants();
+            assert!(map.keys().cloned().eq((0..n).map(|i| i.to_string())));
+            assert!(map.values().copied().eq((0..n).map(|i| i * 2)));
+
+            let json = serde_json::to_string(&map).unwrap();
+            let round_trip: SmallMap<String, usize> = serde_json::from_str(&json).unwrap();
+            assert!(map.eq_ordered(&round_trip));
+
+            map.retain(|_, v| *v % 4 == 0);
+            map.assert_invariants();
+            assert!(map.values().copied().eq((0..n).step_by(2).map(|i| i * 2)));
+
+            let drained = map.drain().collect::<Vec<_>>();
+            assert!(map.is_empty());
+            assert!(drained
+                .into_iter()
+                .map(|(_, v)| v)
+                .eq((0..n).step_by(2).map(|i| i * 2)));
+            map.insert("x".to_owned(), 1);
+            map.assert_invariants();
+        }
+    }
 }
@@ -788,4 +788,33 @@ mod tests {
             mp
         );
     }
+
+    #[test]
+    fn test_large_set_order_and_equality() {
+        // Large enough so the underlying map uses an index.
+        let mut a = SmallSet::new();
+        for i in 0..100 {
+            assert!(a.insert(i));
+        }
+        assert!(!a.insert(50));
+        for i in (0..100).step_by(3) {
+            assert!(a.shift_remove(&i));
+        }
+        assert!(a.iter().copied().eq((0..100).filter(|i| i % 3 != 0)));
+        assert!(a.contains(&98) && !a.contains(&99));
+        assert!(a.contains_hashed(Hashed::new(&98)));
+
+        // Equality ignores order, `eq_ordered` and `hash_ordered` do not.
+        let b = SmallSet::from_iter((0..100).rev().filter(|i| i % 3 != 0));
+        assert_eq!(a, b);
+        assert!(!a.eq_ordered(&b));
+        let c = SmallSet::from_iter((0..100).filter(|i| i % 3 != 0));
+        assert!(a.eq_ordered(&c));
+        let hash = |s: &SmallSet<i32>| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            s.hash_ordered(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&a), hash(&c));
+    }
 }
@@ -242,6 +242,88 @@ impl CodeMaps {
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+enum SourceMapError {
+    #[error("Total size of sources in a `SourceMap` exceeds 4GiB when adding `{0}`")]
+    TooLarge(String),
+}
+
+/// Many [`CodeMap`] laid out one after another, as if the files were concatenated,
+/// so a single global [`Pos`] identifies a position in any of them.
+///
+/// Spans within an `AstModule` remain relative to its own [`CodeMap`];
+/// use [`global_span`](SourceMap::global_span) to convert them to global spans,
+/// and [`resolve`](SourceMap::resolve) to convert global spans back.
+#[derive(Clone, Default, Debug, Allocative)]
+pub struct SourceMap {
+    /// Files in order of their start offset.
+    files: Vec<(Pos, CodeMap)>,
+    /// Start offset by file.
+    offsets: HashMap<CodeMapId, Pos>,
+    /// Start offset of the next file added.
+    next: Pos,
+}
+
+impl SourceMap {
+    /// Add a file, returning its start offset.
+    /// Adding a file which is already present returns its existing offset.
+    pub fn add(&mut self, codemap: &CodeMap) -> crate::Result<Pos> {
+        if let Some(offset) = self.offsets.get(&codemap.id()) {
+            return Ok(*offset);
+        }
+        let offset = self.next;
+        // One past the end, so the end of file position is distinct from the start of the next file.
+        self.next = u32::try_from(codemap.source().len())
+            .ok()
+            .and_then(|len| offset.0.checked_add(len)?.checked_add(1))
+            .map(Pos)
+            .ok_or_else(|| {
+                crate::Error::new_other(SourceMapError::TooLarge(codemap.filename().to_owned()))
+            })?;
+        self.files.push((offset, codemap.dupe()));
+        self.offsets.insert(codemap.id(), offset);
+        Ok(offset)
+    }
+
+    /// The files, in the order they were added.
+    pub fn files(&self) -> impl ExactSizeIterator<Item = &CodeMap> {
+        self.files.iter().map(|(_, codemap)| codemap)
+    }
+
+    /// Start offset of a file, if it was added.
+    pub fn offset(&self, codemap: &CodeMap) -> Option<Pos> {
+        self.offsets.get(&codemap.id()).copied()
+    }
+
+    /// Convert a span within a file to a global span.
+    /// Returns [`None`] if the file was not added.
+    pub fn global_span(&self, span: FileSpanRef) -> Option<Span> {
+        let offset = self.offset(span.file)?;
+        Some(Span {
+            begin: offset + span.span.begin.0,
+            end: offset + span.span.end.0,
+        })
+    }
+
+    /// Find the file containing a global span, and the span within it.
+    /// Returns [`None`] if the span is not within a single file.
+    pub fn resolve(&self, span: Span) -> Option<FileSpan> {
+        let index = self
+            .files
+            .partition_point(|(offset, _)| *offset <= span.begin)
+            .checked_sub(1)?;
+        let (offset, codemap) = &self.files[index];
+        let local = Span {
+            begin: span.begin - offset.0,
+            end: span.end.0.checked_sub(offset.0).map(Pos)?,
+        };
+        if local.end.get() as usize > codemap.source().len() {
+            return None;
+        }
+        Some(codemap.file_span(local))
+    }
+}
+
 /// A `CodeMap`'s record of a source file.
 #[derive(Allocative)]
 struct CodeMapData {
@@ -22,7 +22,7 @@ use crate::call_stack::CallStack;
 use crate::codemap::CodeMap;
 use crate::codemap::FileSpan;
 use crate::codemap::Span;
-use crate::span_display::span_display;
+use crate::span_display::span_display_with_context;
 
 /// A value of type `T`, together with some diagnostic information.
 ///
@@ -105,13 +105,18 @@ impl<T: StdError> fmt::Display for WithDiagnostic<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         // Not showing the context trace without `{:#}` or `{:?}` is the same thing that anyhow does
         let with_context = f.alternate() && self.0.t.source().is_some();
-        diagnostic_display(self, false, f, with_context)
+        diagnostic_display(self, &DiagnosticOptions::default(), f, with_context)
     }
 }
 
 impl<T: StdError> fmt::Debug for WithDiagnostic<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        diagnostic_display(self, false, f, /* with_context */ true)
+        diagnostic_display(
+            self,
+            &DiagnosticOptions::default(),
+            f,
+            /* with_context */ true,
+        )
     }
 }
 
@@ -139,16 +144,41 @@ impl Diagnostic {
     fn get_display_list<'a>(
         &'a self,
         annotation_label: &'a str,
-        color: bool,
+        options: &DiagnosticOptions,
     ) -> impl fmt::Display + 'a {
-        span_display(
+        span_display_with_context(
             self.span.as_ref().map(|s| s.as_ref()),
             annotation_label,
-            color,
+            options.color,
+            options.context_lines,
         )
     }
 }
 
+/// How to render an error with its diagnostic information,
+/// see [`Error::display_with`](crate::Error::display_with).
+///
+/// The default matches the `Display` implementation of [`Error`](crate::Error).
+#[derive(Debug, Clone)]
+pub struct DiagnosticOptions {
+    /// Number of source lines to show before and after the lines of the error location.
+    pub context_lines: usize,
+    /// Use ANSI color codes, like [`Error::eprint`](crate::Error::eprint).
+    pub color: bool,
+    /// Include the Starlark call stack.
+    pub call_stack: bool,
+}
+
+impl Default for DiagnosticOptions {
+    fn default() -> DiagnosticOptions {
+        DiagnosticOptions {
+            context_lines: 0,
+            color: false,
+            call_stack: true,
+        }
+    }
+}
+
 /////////////////////////////////////////////////////////////////////
 // DISPLAY RELATED UTILITIES
 // Since formatting these types is difficult, we reuse the Rust compiler
@@ -157,15 +187,15 @@ impl Diagnostic {
 
 pub(crate) fn diagnostic_display<T: fmt::Debug + fmt::Display>(
     d: &WithDiagnostic<T>,
-    color: bool,
+    options: &DiagnosticOptions,
     f: &mut dyn fmt::Write,
     with_context: bool,
 ) -> fmt::Result {
-    write!(f, "{}", d.call_stack())?;
+    if options.call_stack {
+        write!(f, "{}", d.call_stack())?;
+    }
     let annotation_label = format!("{}", d.inner());
-    // I set color to false here to make the comparison easier with tests (coloring
-    // adds in pretty strange unicode chars).
-    let display_list = d.0.diagnostic.get_display_list(&annotation_label, color);
+    let display_list = d.0.diagnostic.get_display_list(&annotation_label, options);
     writeln!(f, "{}", display_list)?;
     // Print out the `Caused by:` trace (if exists) and rust backtrace (if enabled).
     // The trace printed comes from an [`anyhow::Error`] that is not a [`Diagnostic`].
@@ -58,11 +58,23 @@ pub struct Dialect {
     /// Are `for`, `if` and other statements allowed at the top level.
     /// Disabled by default.
     pub enable_top_level_stmt: bool,
+    /// Are expression statements, e.g. bare function calls, allowed at the top level.
+    /// String literals are always allowed, so modules can have docstrings.
+    /// Together with disabling [`enable_top_level_stmt`](Dialect::enable_top_level_stmt),
+    /// disabling this restricts the top level to `load`, `def` and assignments.
+    /// Enabled by default.
+    pub enable_top_level_expressions: bool,
     /// Are `f"{expression}"` strings supported?
     /// Disabled by default.
     ///
     /// [Starlark spec proposal](https://github.com/bazelbuild/starlark/issues/91).
     pub enable_f_strings: bool,
+    /// Are `del x[i]` and `del x[a:b]` statements permitted.
+    /// Disabled by default.
+    pub enable_del: bool,
+    /// Are slice assignments like `x[a:b] = y` permitted.
+    /// Disabled by default.
+    pub enable_slice_assignment: bool,
     /// Like `#[non_exhaustive]`, but allows struct expression.
     ///
     /// [Explanation](https://github.com/rust-lang/rust-clippy/issues/6559).
@@ -91,7 +103,10 @@ impl Dialect {
         enable_types: DialectTypes::Disable,
         enable_load_reexport: true, // But they plan to change it
         enable_top_level_stmt: false,
+        enable_top_level_expressions: true,
         enable_f_strings: false,
+        enable_del: false,
+        enable_slice_assignment: false,
         _non_exhaustive: (),
     };
 
@@ -106,7 +121,10 @@ impl Dialect {
         enable_types: DialectTypes::Enable,
         enable_load_reexport: true,
         enable_top_level_stmt: true,
+        enable_top_level_expressions: true,
         enable_f_strings: false,
+        enable_del: false,
+        enable_slice_assignment: false,
         _non_exhaustive: (),
     };
 
@@ -121,7 +139,10 @@ impl Dialect {
         enable_types: DialectTypes::Enable,
         enable_load_reexport: true,
         enable_top_level_stmt: true,
+        enable_top_level_expressions: true,
         enable_f_strings: true,
+        enable_del: true,
+        enable_slice_assignment: true,
         _non_exhaustive: (),
     };
 }
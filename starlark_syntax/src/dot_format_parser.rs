@@ -46,6 +46,19 @@ pub enum FormatToken<'a> {
         conv: FormatConv,
     },
     Escape(EscapeCurlyBrace),
+    /// Capture with a format spec after the colon, like `{x:>5}`.
+    #[non_exhaustive]
+    CaptureWithSpec {
+        /// Format part inside curly braces before the conversion.
+        capture: &'a str,
+        /// The position of this capture. This does not include the curly braces.
+        pos: usize,
+        /// The conversion to apply to this capture, `None` if not specified.
+        conv: Option<FormatConv>,
+        /// Format spec after the colon, never empty.
+        /// It may contain nested replacement fields like `{}`.
+        spec: &'a str,
+    },
 }
 
 /// Emitted when processing an escape (`{{` or `}}`).
@@ -73,6 +86,41 @@ impl EscapeCurlyBrace {
     }
 }
 
+impl<'a> FormatToken<'a> {
+    /// Capture, position, conversion and format spec of a capture token,
+    /// `None` for other tokens. The spec is empty if not specified.
+    pub fn as_capture(&self) -> Option<(&'a str, usize, Option<FormatConv>, &'a str)> {
+        match *self {
+            FormatToken::Capture { capture, pos, conv } => Some((capture, pos, Some(conv), "")),
+            FormatToken::CaptureWithSpec {
+                capture,
+                pos,
+                conv,
+                spec,
+            } => Some((capture, pos, conv, spec)),
+            FormatToken::Text(_) | FormatToken::Escape(_) => None,
+        }
+    }
+
+    /// A capture, with a spec only if it is not empty.
+    fn capture(capture: &'a str, pos: usize, conv: Option<FormatConv>, spec: &'a str) -> Self {
+        if spec.is_empty() {
+            FormatToken::Capture {
+                capture,
+                pos,
+                conv: conv.unwrap_or(FormatConv::Str),
+            }
+        } else {
+            FormatToken::CaptureWithSpec {
+                capture,
+                pos,
+                conv,
+                spec,
+            }
+        }
+    }
+}
+
 impl<'a> FormatParser<'a> {
     #[inline]
     pub fn new(s: &'a str) -> Self {
@@ -98,13 +146,10 @@ impl<'a> FormatParser<'a> {
                     i = 1;
                     while i < self.view.len() {
                         match self.view.as_bytes()[i] {
-                            b'}' => {
-                                let capture = &self.view.eat(i + 1)[1..i];
-                                return Ok(Some(FormatToken::Capture {
-                                    capture,
-                                    pos,
-                                    conv: FormatConv::Str,
-                                }));
+                            b'}' | b':' => {
+                                let capture = &self.view.eat(i)[1..];
+                                let spec = self.spec()?;
+                                return Ok(Some(FormatToken::capture(capture, pos, None, spec)));
                             }
                             b'!' => {
                                 let capture = &self.view.eat(i + 1)[1..i];
@@ -124,11 +169,16 @@ impl<'a> FormatParser<'a> {
                                     ));
                                 };
                                 self.view.eat(1); // `r` or `s` after the exclamation mark.
-                                if !self.view.starts_with('}') {
+                                if !self.view.starts_with(['}', ':']) {
                                     break;
                                 }
-                                self.view.eat(1); // Closing brace.
-                                return Ok(Some(FormatToken::Capture { capture, pos, conv }));
+                                let spec = self.spec()?;
+                                return Ok(Some(FormatToken::capture(
+                                    capture,
+                                    pos,
+                                    Some(conv),
+                                    spec,
+                                )));
                             }
                             b'{' => {
                                 if i == 1 {
@@ -166,6 +216,38 @@ impl<'a> FormatParser<'a> {
             Ok(Some(FormatToken::Text(mem::take(&mut self.view).rem())))
         }
     }
+
+    /// Parse optional `:spec` and the closing brace of a replacement field.
+    /// The spec may contain replacement fields, but they cannot have specs of their own.
+    fn spec(&mut self) -> anyhow::Result<&'a str> {
+        if self.view.starts_with('}') {
+            self.view.eat(1);
+            return Ok("");
+        }
+        let rem = self.view.rem();
+        let mut nested = false;
+        for (i, c) in rem.bytes().enumerate().skip(1) {
+            match c {
+                b'{' if nested => {
+                    return Err(anyhow::anyhow!(
+                        "Max string recursion exceeded in format string `{}`",
+                        self.view.original()
+                    ));
+                }
+                b'{' => nested = true,
+                b'}' if nested => nested = false,
+                b'}' => {
+                    let spec = &self.view.eat(i + 1)[1..i];
+                    return Ok(spec);
+                }
+                _ => {}
+            }
+        }
+        Err(anyhow::anyhow!(
+            "Unmatched '{{' in format string `{}`",
+            self.view.original()
+        ))
+    }
 }
 
 /// A String and an index pointing into this string. This behaves as if you had just the part
@@ -262,6 +344,46 @@ mod tests {
         assert_eq!(parser.next().unwrap(), None);
     }
 
+    #[test]
+    fn test_parser_spec() {
+        let s = "{:>5}{x!r:{w}.{}f}{0!s:}{:{{}";
+        let mut parser = FormatParser::new(s);
+        assert_eq!(
+            parser.next().unwrap(),
+            Some(FormatToken::CaptureWithSpec {
+                capture: "",
+                pos: 1,
+                conv: None,
+                spec: ">5",
+            })
+        );
+        assert_eq!(
+            parser.next().unwrap(),
+            Some(FormatToken::CaptureWithSpec {
+                capture: "x",
+                pos: 6,
+                conv: Some(FormatConv::Repr),
+                spec: "{w}.{}f",
+            })
+        );
+        assert_eq!(
+            parser.next().unwrap(),
+            Some(FormatToken::Capture {
+                capture: "0",
+                pos: 19,
+                conv: FormatConv::Str,
+            })
+        );
+        assert_eq!(
+            parser.next().unwrap_err().to_string(),
+            "Max string recursion exceeded in format string `{:>5}{x!r:{w}.{}f}{0!s:}{:{{}`"
+        );
+        assert_eq!(
+            FormatParser::new("{:>5").next().unwrap_err().to_string(),
+            "Unmatched '{' in format string `{:>5`"
+        );
+    }
+
     #[test]
     fn test_failure() {
         let s = "}foo";
@@ -23,6 +23,7 @@ use crate::codemap::CodeMap;
 use crate::codemap::FileSpan;
 use crate::codemap::Span;
 use crate::diagnostic::diagnostic_display;
+use crate::diagnostic::DiagnosticOptions;
 use crate::diagnostic::WithDiagnostic;
 
 /// An error produced by starlark.
@@ -115,10 +116,16 @@ impl Error {
         self.0.inner()
     }
 
+    /// The location where the error was raised, if known.
     pub fn span(&self) -> Option<&FileSpan> {
         self.0.span()
     }
 
+    /// The Starlark call stack at the point the error was raised, outermost call first.
+    ///
+    /// Each [`Frame`](crate::frame::Frame) names the called function and the location of the call,
+    /// so the position executing within a frame is the location of the next frame,
+    /// or [`span`](Error::span) for the innermost frame.
     pub fn call_stack(&self) -> &CallStack {
         self.0.call_stack()
     }
@@ -142,13 +149,38 @@ impl Error {
     pub fn eprint(&self) {
         if self.has_diagnostic() {
             let mut stderr = String::new();
-            diagnostic_display(&self.0, true, &mut stderr, true).unwrap();
+            let options = DiagnosticOptions {
+                color: true,
+                ..DiagnosticOptions::default()
+            };
+            diagnostic_display(&self.0, &options, &mut stderr, true).unwrap();
             eprint!("{}", stderr);
         } else {
             eprintln!("{:#}", self)
         }
     }
 
+    /// Returns a value that formats this error like `Display`, but with the given options
+    /// controlling the rendering of the diagnostic information.
+    ///
+    /// Errors without diagnostic information are formatted as by `Display`.
+    pub fn display_with<'a>(&'a self, options: DiagnosticOptions) -> impl fmt::Display + 'a {
+        struct DisplayWith<'a>(&'a Error, DiagnosticOptions);
+
+        impl fmt::Display for DisplayWith<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if self.0.has_diagnostic() {
+                    let with_context = f.alternate() && self.0.kind().source().is_some();
+                    diagnostic_display(&self.0.0, &self.1, f, with_context)
+                } else {
+                    fmt::Display::fmt(&self.0.without_diagnostic(), f)
+                }
+            }
+        }
+
+        DisplayWith(self, options)
+    }
+
     /// Change error kind to internal error.
     pub fn into_internal_error(self) -> Error {
         if let ErrorKind::Internal(_) = self.kind() {
@@ -157,13 +189,64 @@ impl Error {
             Error(self.0.map(ErrorKind::into_internal_error))
         }
     }
+
+    /// Prefix the error message with `prefix`, keeping the error kind and diagnostic.
+    ///
+    /// The message is replaced, so `downcast_ref` no longer finds the original error:
+    /// use [`without_message_prefix`] to get it back.
+    #[cold]
+    pub fn with_message_prefix(self, prefix: impl fmt::Display) -> Error {
+        Error(self.0.map(|kind| {
+            kind.map_anyhow(|error| {
+                anyhow::Error::new(MessagePrefix {
+                    message: format!("{}: {}", prefix, error),
+                    error,
+                })
+            })
+        }))
+    }
+}
+
+/// Error whose message was replaced by [`Error::with_message_prefix`].
+struct MessagePrefix {
+    /// The prefix followed by the message of `error`.
+    message: String,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for MessagePrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl fmt::Debug for MessagePrefix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for MessagePrefix {
+    // The message of `error` is already part of ours, so skip to its cause.
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// The error the message prefixes were added to with [`Error::with_message_prefix`],
+/// or `error` itself if there are none.
+pub fn without_message_prefix(mut error: &anyhow::Error) -> &anyhow::Error {
+    while let Some(prefixed) = error.downcast_ref::<MessagePrefix>() {
+        error = &prefixed.error;
+    }
+    error
 }
 
 fn fmt_impl(this: &Error, is_debug: bool, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     if this.has_diagnostic() {
         // Not showing the context trace without `{:#}` or `{:?}` is the same thing that anyhow does
         let with_context = (f.alternate() || is_debug) && this.kind().source().is_some();
-        diagnostic_display(&this.0, false, f, with_context)
+        diagnostic_display(&this.0, &DiagnosticOptions::default(), f, with_context)
     } else {
         fmt::Display::fmt(&this.without_diagnostic(), f)
     }
@@ -241,12 +324,26 @@ impl ErrorKind {
             | ErrorKind::Other(e) => ErrorKind::Internal(e),
         }
     }
+
+    fn map_anyhow(self, f: impl FnOnce(anyhow::Error) -> anyhow::Error) -> ErrorKind {
+        match self {
+            ErrorKind::Fail(e) => ErrorKind::Fail(f(e)),
+            ErrorKind::StackOverflow(e) => ErrorKind::StackOverflow(f(e)),
+            ErrorKind::Value(e) => ErrorKind::Value(f(e)),
+            ErrorKind::Function(e) => ErrorKind::Function(f(e)),
+            ErrorKind::Scope(e) => ErrorKind::Scope(f(e)),
+            ErrorKind::Parser(e) => ErrorKind::Parser(f(e)),
+            ErrorKind::Internal(e) => ErrorKind::Internal(f(e)),
+            ErrorKind::Native(e) => ErrorKind::Native(f(e)),
+            ErrorKind::Other(e) => ErrorKind::Other(f(e)),
+        }
+    }
 }
 
 impl fmt::Debug for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Fail(s) => write!(f, "fail:{}", s),
+            Self::Fail(s) => write!(f, "fail: {}", s),
             Self::Value(e) => fmt::Debug::fmt(e, f),
             Self::StackOverflow(e) => fmt::Debug::fmt(e, f),
             Self::Function(e) => fmt::Debug::fmt(e, f),
@@ -262,7 +359,7 @@ impl fmt::Debug for ErrorKind {
 impl fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Fail(s) => write!(f, "fail:{}", s),
+            Self::Fail(s) => write!(f, "fail: {}", s),
             Self::StackOverflow(e) => fmt::Display::fmt(e, f),
             Self::Value(e) => fmt::Display::fmt(e, f),
             Self::Function(e) => fmt::Display::fmt(e, f),
@@ -28,7 +28,7 @@ use crate::fast_string::CharIndex;
 pub struct Frame {
     /// The name of the entry on the call-stack.
     pub name: String,
-    /// The location of the definition, or [`None`] for native Rust functions.
+    /// The location this function was called from, or [`None`] if it was called from native Rust code.
     pub location: Option<FileSpan>,
 }
 
@@ -392,11 +392,16 @@ impl<'a> Lexer<'a> {
                 }
                 '\\' => {
                     if raw {
-                        match it.next() {
+                        // The backslash is kept, it only stops the next character
+                        // from terminating the string.
+                        let mut next = it.next();
+                        if next == Some('\r') {
+                            // We ignore `\r` in all modes, so `\` before `\r\n` keeps the `\n`.
+                            next = it.next();
+                        }
+                        match next {
                             Some(c) => {
-                                if c != '\'' && c != '"' {
-                                    res.push('\\');
-                                }
+                                res.push('\\');
                                 res.push(c);
                             }
                             _ => break, // Out of chars
@@ -429,9 +434,10 @@ impl<'a> Lexer<'a> {
         )
     }
 
+    /// Parse the digits of an integer literal, which may contain `_` separators.
     fn int(&self, s: &str, radix: u32) -> Lexeme {
         let span = self.lexer.span();
-        match TokenInt::from_str_radix(s, radix) {
+        match TokenInt::from_str_radix(&s.replace('_', ""), radix) {
             Ok(i) => Ok((span.start, Token::Int(i), span.end)),
             Err(_) => self.err_now(LexemeError::IntParse),
         }
@@ -664,7 +670,6 @@ pub enum Token {
         async|\
         await|\
         class|\
-        del|\
         except|\
         finally|\
         from|\
@@ -685,13 +690,13 @@ pub enum Token {
     , |lex| lex.slice().to_owned())]
     Identifier(String), // An identifier
 
-    #[regex("[0-9]+")]
+    #[regex("[0-9](_?[0-9])*")]
     RawDecInt,
-    #[regex("0[xX][A-Fa-f0-9]+")]
+    #[regex("0[xX](_?[A-Fa-f0-9])+")]
     RawHexInt,
-    #[regex("0[bB][01]+")]
+    #[regex("0[bB](_?[01])+")]
     RawBinInt,
-    #[regex("0[oO][0-7]+")]
+    #[regex("0[oO](_?[0-7])+")]
     RawOctInt,
 
     Int(TokenInt), // An integer literal (123, 0x1, 0b1011, 0o755, ...)
@@ -714,6 +719,8 @@ pub enum Token {
     Continue,
     #[token("def")]
     Def,
+    #[token("del")]
+    Del,
     #[token("elif")]
     Elif,
     #[token("else")]
@@ -881,6 +888,7 @@ impl Display for Token {
             Token::If => write!(f, "keyword 'if'"),
             Token::Or => write!(f, "keyword 'or'"),
             Token::Def => write!(f, "keyword 'def'"),
+            Token::Del => write!(f, "keyword 'del'"),
             Token::In => write!(f, "keyword 'in'"),
             Token::Pass => write!(f, "keyword 'pass'"),
             Token::Elif => write!(f, "keyword 'elif'"),
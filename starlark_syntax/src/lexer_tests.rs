@@ -144,6 +144,18 @@ fn test_int_lit() {
     lexer_fail_golden_test("int_lit", &["x = 01"]);
 }
 
+#[test]
+fn test_int_lit_underscore() {
+    lexer_golden_test(
+        "int_lit_underscore",
+        r#"
+1_000_000 2_147_483_648
+0x_7f 0xdead_beef
+0b1_0 0o7_5_5
+"#,
+    );
+}
+
 #[test]
 fn test_indentation() {
     lexer_golden_test(
@@ -191,7 +203,7 @@ fn test_number_collated_with_keywords_or_identifier() {
 fn test_reserved() {
     lexer_fail_golden_test(
         "reserved",
-        &"as import is class nonlocal del raise except try finally while from with global yield"
+        &"as import is class nonlocal raise except try finally while from with global yield"
             .split_whitespace()
             .collect::<Vec<&str>>(),
     );
@@ -247,7 +259,39 @@ fn test_string_lit() {
     // Raw string
     assert_eq!(
         lex("r'' r\"\" r'\\'' r\"\\\"\" r'\"' r\"'\" r'\\n'"),
-        "\"\" \"\" \"\'\" \"\\\"\" \"\\\"\" \"\'\" \"\\\\n\" \n"
+        "\"\" \"\" \"\\\\'\" \"\\\\\\\"\" \"\\\"\" \"\'\" \"\\\\n\" \n"
+    );
+}
+
+#[test]
+fn test_string_lit_quotes() {
+    lexer_golden_test(
+        "string_lit_quotes",
+        r#"
+"""a"b"""
+"""a""b"""
+""""a"""
+"""a\""""
+'''it's'''
+'''a
+'b'
+c'''
+"""\
+x"""
+r"""a\"b"""
+r"""a\""""
+r'''a\nb
+c\\'''
+r"a\\"
+r"\""
+r'\''
+r"a\
+b"
+"#,
+    );
+    lexer_fail_golden_test(
+        "string_lit_quotes",
+        &[r#""""a"""#, r#""""a\""""#, r#"r"a\""#, r#"r'''a\'''"#],
     );
 }
 
@@ -26,6 +26,7 @@ use annotate_snippets::snippet::Snippet;
 use annotate_snippets::snippet::SourceAnnotation;
 
 use crate::codemap::FileSpanRef;
+use crate::codemap::Span;
 use crate::fast_string;
 
 /// Gets annotated snippets.
@@ -34,7 +35,18 @@ pub fn span_display<'a>(
     annotation_label: &'a str,
     color: bool,
 ) -> impl Display + 'a {
-    fn convert_span_to_slice<'a>(span: FileSpanRef<'a>) -> Slice<'a> {
+    span_display_with_context(span, annotation_label, color, 0)
+}
+
+/// Gets annotated snippets, including up to `context_lines` lines of source
+/// before and after the span.
+pub fn span_display_with_context<'a>(
+    span: Option<FileSpanRef<'a>>,
+    annotation_label: &'a str,
+    color: bool,
+    context_lines: usize,
+) -> impl Display + 'a {
+    fn convert_span_to_slice(span: FileSpanRef<'_>, context_lines: usize) -> Slice<'_> {
         let region = span.resolve_span();
 
         // we want the source_span to capture any whitespace ahead of the diagnostic span to
@@ -42,18 +54,33 @@ pub fn span_display<'a>(
         // on the last line for context.
         let first_line_span = span.file.line_span(region.begin.line);
         let last_line_span = span.file.line_span(region.end.line);
-        let source_span = span.span.merge(first_line_span).merge(last_line_span);
+        let first_line = (region.begin.line.saturating_sub(context_lines)..region.begin.line)
+            .find(|line| span.file.line_span_opt(*line).is_some())
+            .unwrap_or(region.begin.line);
+        let context_before_span = Span::new(
+            span.file.line_span(first_line).begin(),
+            first_line_span.begin(),
+        );
+        let context_after_span = (region.end.line + 1..=region.end.line + context_lines)
+            .map_while(|line| span.file.line_span_opt(line))
+            .fold(last_line_span, Span::merge);
+        let source_span = span
+            .span
+            .merge(context_before_span)
+            .merge(first_line_span)
+            .merge(context_after_span);
         let source = span.file.source_span(source_span);
 
         // We want to highlight the span, which needs to be relative to source, and in
         // characters.
         // Our spans are in terms of bytes, but our resolved spans in terms of characters.
-        let range_start_chars = region.begin.column;
+        let range_start_chars =
+            fast_string::len(span.file.source_span(context_before_span)).0 + region.begin.column;
         let range_len_chars = fast_string::len(span.source_span()).0;
 
         Slice {
             source,
-            line_start: 1 + region.begin.line,
+            line_start: 1 + first_line,
             origin: Some(span.file.filename()),
             fold: false,
             annotations: vec![SourceAnnotation {
@@ -64,7 +91,7 @@ pub fn span_display<'a>(
         }
     }
 
-    let slice = span.map(convert_span_to_slice);
+    let slice = span.map(|span| convert_span_to_slice(span, context_lines));
 
     let snippet = Snippet {
         title: Some(Annotation {
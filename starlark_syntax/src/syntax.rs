@@ -18,6 +18,7 @@
 //! The AST of Starlark as [`AstModule`], along with a [`parse`](AstModule::parse) function.
 
 pub use module::AstModule;
+pub use module::TextEdit;
 pub use parser::AstLoad;
 
 pub use crate::dialect::Dialect;
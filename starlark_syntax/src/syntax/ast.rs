@@ -225,6 +225,15 @@ pub enum AssignTargetP<P: AstPayload> {
     // as these have the same semantics in Starlark.
     Tuple(Vec<AstAssignTargetP<P>>),
     Index(Box<(AstExprP<P>, AstExprP<P>)>),
+    /// `x[start:stop:stride] = ...`, when
+    /// [`Dialect::enable_slice_assignment`](crate::dialect::Dialect::enable_slice_assignment) is set.
+    /// Only used as the whole left-hand side of an assignment, or as a `del` target.
+    Slice(
+        Box<AstExprP<P>>,
+        Option<Box<AstExprP<P>>>,
+        Option<Box<AstExprP<P>>>,
+        Option<Box<AstExprP<P>>>,
+    ),
     Dot(Box<AstExprP<P>>, AstString),
     Identifier(AstAssignIdentP<P>),
 }
@@ -389,6 +398,10 @@ pub enum StmtP<P: AstPayload> {
     Expression(AstExprP<P>),
     Assign(AssignP<P>),
     AssignModify(AstAssignTargetP<P>, AssignOp, Box<AstExprP<P>>),
+    /// `del x[i]` or `del x[a:b]`, when
+    /// [`Dialect::enable_del`](crate::dialect::Dialect::enable_del) is set.
+    /// The target is always an [`AssignTargetP::Index`] or an [`AssignTargetP::Slice`].
+    Del(AstAssignTargetP<P>),
     Statements(Vec<AstStmtP<P>>),
     If(AstExprP<P>, Box<AstStmtP<P>>),
     IfElse(AstExprP<P>, Box<(AstStmtP<P>, AstStmtP<P>)>),
@@ -637,6 +650,20 @@ impl Display for AssignTarget {
                 let (e, i) = &**e_i;
                 write!(f, "{}[{}]", e.node, i.node)
             }
+            AssignTarget::Slice(e, i1, i2, i3) => {
+                write!(f, "{}[", e.node)?;
+                if let Some(x) = i1 {
+                    write!(f, "{}", x.node)?;
+                }
+                f.write_str(":")?;
+                if let Some(x) = i2 {
+                    write!(f, "{}", x.node)?;
+                }
+                if let Some(x) = i3 {
+                    write!(f, ":{}", x.node)?;
+                }
+                f.write_str("]")
+            }
             AssignTarget::Identifier(s) => write!(f, "{}", s.node),
         }
     }
@@ -717,6 +744,7 @@ impl Stmt {
                 writeln!(f, "= {}", rhs.node)
             }
             Stmt::AssignModify(l, op, r) => writeln!(f, "{}{}{}{}", tab, l.node, op, r.node),
+            Stmt::Del(target) => writeln!(f, "{}del {}", tab, target.node),
             Stmt::Statements(v) => {
                 for s in v {
                     s.node.fmt_with_tab(f, tab.clone())?;
@@ -170,6 +170,28 @@ fn test_top_level_statements() {
     assert_eq!(parse("pass"), "pass\n");
 }
 
+#[test]
+fn test_top_level_expressions() {
+    let declarative = Dialect {
+        enable_top_level_stmt: false,
+        enable_top_level_expressions: false,
+        ..Dialect::AllOptionsInternal
+    };
+    parse_fails_with_dialect(
+        "top_level_expressions",
+        &declarative,
+        &["x = 1\nprint(x)\ny = 2", "x = [1]\nx.append(2)"],
+    );
+    assert_eq!(
+        parse_with_dialect(
+            "\"\"\"Docstring\"\"\"\nload(\"a.star\", \"f\")\nx = f()\nx += 1\ndef g():\n  f()\n  for y in []:\n    pass",
+            &declarative
+        ),
+        "\"Docstring\"\nload(\"a.star\", f = \"f\")\nx = f()\nx += 1\ndef g():\n  f()\n  for y in []:\n    pass\n"
+    );
+    assert_eq!(parse("print(1)"), "print(1)\n");
+}
+
 #[test]
 fn test_top_level_def_with_docstring() {
     assert_eq!(
@@ -284,14 +306,28 @@ fn test_comprehension() {
 #[test]
 fn test_lambda() {
     assert_eq!(parse("x = lambda y: y + 1"), "x = (lambda y: (y + 1))\n");
-    parse_fail_with_dialect(
+    let no_lambda = Dialect {
+        enable_lambda: false,
+        ..Dialect::AllOptionsInternal
+    };
+    parse_fails_with_dialect(
         "lambda",
+        &no_lambda,
+        &[
+            "x = lambda y: y + 1",
+            "def f(x = lambda: 1):\n  pass",
+            "def f():\n  return [lambda: x for x in []]",
+        ],
+    );
+    parse_fail_with_dialect(
+        "lambda_no_def",
         &Dialect {
-            enable_lambda: false,
-            ..Dialect::AllOptionsInternal
+            enable_def: false,
+            ..no_lambda.clone()
         },
         "x = lambda y: y + 1",
     );
+    AstModule::parse("x", "def f(y):\n  return y".to_owned(), &no_lambda).unwrap();
     assert_eq!(parse("(lambda y: x == 1)(1)"), "(lambda y: (x == 1))(1)\n");
     assert_eq!(parse("(lambda x: x or 1)(1)"), "(lambda x: (x or 1))(1)\n");
     assert_eq!(
@@ -357,6 +393,29 @@ fn test_bad_assignment() {
     parse_fails("bad_assignment", &["[x or y] = 1", "[x] += 1"]);
 }
 
+#[test]
+fn test_slice_assignment() {
+    assert_eq!(parse("x[1:2] = y"), "x[1:2] = y\n");
+    assert_eq!(parse("x[::2] = y"), "x[::2] = y\n");
+    parse_fails(
+        "slice_assign_bad",
+        &["x[1:2] += y", "x[1:2], y = z", "for x[1:2] in y:\n  pass"],
+    );
+    parse_fail_with_dialect("slice_assign_dialect", &Dialect::Standard, "x[1:2] = y");
+}
+
+#[test]
+fn test_del() {
+    assert_eq!(parse("del x[1]"), "del x[1]\n");
+    parse("del x[1:]");
+    parse("del x.y[::-1]");
+    parse_fails(
+        "bad_del",
+        &["del x", "del x.y", "del (x[1], x[2])", "del x + 1"],
+    );
+    parse_fail_with_dialect("del_dialect", &Dialect::Standard, "del x[1]");
+}
+
 #[test]
 fn test_assignment_type_annotation() {
     // We support the same annotation syntax as Python.
@@ -62,6 +62,10 @@ enum GrammarUtilError {
     InvalidLhs,
     #[error("left-hand-side of modifying assignment cannot be a list or tuple")]
     InvalidModifyLhs,
+    #[error("left-hand-side of modifying assignment cannot be a slice")]
+    InvalidModifySlice,
+    #[error("`del` target must take the form `a[b]` or `a[b:c]`")]
+    InvalidDel,
     #[error("type annotations not allowed on augmented assignments")]
     TypeAnnotationOnAssignOp,
     #[error("type annotations not allowed on multiple assignments")]
@@ -103,6 +107,22 @@ pub fn check_assign(codemap: &CodeMap, x: AstExpr) -> Result<AstAssignTarget, Ev
     })
 }
 
+pub fn check_del(codemap: &CodeMap, x: AstExpr) -> Result<Stmt, EvalException> {
+    let span = x.span;
+    let node = match x.node {
+        Expr::Index(a_b) => AssignTarget::Index(a_b),
+        Expr::Slice(a, b, c, d) => AssignTarget::Slice(a, b, c, d),
+        _ => {
+            return Err(EvalException::new_anyhow(
+                GrammarUtilError::InvalidDel.into(),
+                x.span,
+                codemap,
+            ));
+        }
+    };
+    Ok(Stmt::Del(Spanned { span, node }))
+}
+
 pub fn check_assignment(
     codemap: &CodeMap,
     lhs: AstExpr,
@@ -120,10 +140,24 @@ pub fn check_assignment(
                     codemap,
                 ));
             }
+            Expr::Slice(..) => {
+                return Err(EvalException::new_anyhow(
+                    GrammarUtilError::InvalidModifySlice.into(),
+                    lhs.span,
+                    codemap,
+                ));
+            }
             _ => {}
         }
     }
-    let lhs = check_assign(codemap, lhs)?;
+    let lhs = match lhs.node {
+        // Slices are only valid as the whole left-hand side of a plain assignment.
+        Expr::Slice(a, b, c, d) => Spanned {
+            span: lhs.span,
+            node: AssignTarget::Slice(a, b, c, d),
+        },
+        _ => check_assign(codemap, lhs)?,
+    };
     if let Some(ty) = &ty {
         let err = if op.is_some() {
             Some(GrammarUtilError::TypeAnnotationOnAssignOp)
@@ -219,7 +253,10 @@ pub(crate) fn fstring(
                 // We are producing a format string here so we need to escape this back!
                 format.push_str(e.back_to_escape())
             }
-            Ok(FormatToken::Capture { capture, pos, conv }) => {
+            Ok(token) => {
+                let Some((capture, pos, conv, spec)) = token.as_capture() else {
+                    unreachable!("not a capture: {token:?}")
+                };
                 let capture_begin = begin + content_start_offset + pos;
                 let capture_end = capture_begin + capture.len();
 
@@ -241,11 +278,26 @@ pub(crate) fn fstring(
                 )
                 .ast(capture_begin, capture_end);
                 expressions.push(expr);
+                if spec.contains('{') {
+                    parser_state.error(
+                        Span::new(Pos::new(capture_begin as _), Pos::new(capture_end as _)),
+                        "Nested replacement fields in format spec are not supported in f-strings",
+                    );
+                    continue;
+                }
                 // Positional format.
+                format.push('{');
                 match conv {
-                    FormatConv::Str => format.push_str("{}"),
-                    FormatConv::Repr => format.push_str("{!r}"),
+                    // Without a spec, `str` is the default conversion.
+                    Some(FormatConv::Str) if !spec.is_empty() => format.push_str("!s"),
+                    Some(FormatConv::Str) | None => {}
+                    Some(FormatConv::Repr) => format.push_str("!r"),
+                }
+                if !spec.is_empty() {
+                    format.push(':');
+                    format.push_str(spec);
                 }
+                format.push('}');
             }
             Err(inner) => {
                 // TODO: Reporting the exact position of the error would be better.
@@ -28,16 +28,23 @@ use lalrpop_util as lu;
 use crate::codemap::CodeMap;
 use crate::codemap::FileSpan;
 use crate::codemap::Pos;
+use crate::codemap::SourceMap;
 use crate::codemap::Span;
 use crate::codemap::Spanned;
 use crate::eval_exception::EvalException;
 use crate::lexer::Lexer;
 use crate::lexer::Token;
 use crate::syntax::ast::ArgumentP;
+use crate::syntax::ast::AssignP;
 use crate::syntax::ast::AstExpr;
+use crate::syntax::ast::AstParameter;
 use crate::syntax::ast::AstStmt;
 use crate::syntax::ast::CallArgsP;
+use crate::syntax::ast::Clause;
+use crate::syntax::ast::Expr;
 use crate::syntax::ast::ExprP;
+use crate::syntax::ast::ForClause;
+use crate::syntax::ast::ForP;
 use crate::syntax::ast::IdentP;
 use crate::syntax::ast::LoadArgP;
 use crate::syntax::ast::Stmt;
@@ -45,6 +52,7 @@ use crate::syntax::grammar::StarlarkParser;
 use crate::syntax::lint_suppressions::LintSuppressions;
 use crate::syntax::lint_suppressions::LintSuppressionsBuilder;
 use crate::syntax::state::ParserState;
+use crate::syntax::uniplate::Visit;
 use crate::syntax::validate::validate_module;
 use crate::syntax::AstLoad;
 use crate::syntax::Dialect;
@@ -106,9 +114,28 @@ fn parse_error_add_span(
     )
 }
 
+#[derive(Debug, thiserror::Error)]
+enum TextEditError {
+    #[error("Text edits overlap at `{0}`")]
+    Overlap(String),
+}
+
+/// A change to the source of an [`AstModule`]: replace the text at `span` with `text`.
+///
+/// Produced by [`rename_identifier`](AstModule::rename_identifier)
+/// and applied with [`apply_edits`](AstModule::apply_edits).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Span of the replaced text.
+    pub span: Span,
+    /// Replacement text.
+    pub text: String,
+}
+
 /// A representation of a Starlark module abstract syntax tree.
 ///
-/// Created with either [`parse`](AstModule::parse) or [`parse_file`](AstModule::parse_file),
+/// Created with [`parse`](AstModule::parse), [`parse_file`](AstModule::parse_file)
+/// or [`parse_map`](AstModule::parse_map),
 /// and evaluated with `Evaluator::eval_module`.
 ///
 /// The internal details (statements/expressions) are deliberately omitted, as they change
@@ -210,8 +237,25 @@ impl AstModule {
     /// assert_eq!(span.to_string(), "filename:2:11");
     /// ```
     pub fn parse(filename: &str, content: String, dialect: &Dialect) -> crate::Result<Self> {
-        let typecheck = content.contains("@starlark-rust: typecheck");
+        Self::parse_codemap(CodeMap::new(filename.to_owned(), content), dialect)
+    }
+
+    /// Like [`parse`](AstModule::parse), but also add the source to a [`SourceMap`]
+    /// shared by many files, so spans from any of them can be resolved from a global offset.
+    /// The source is added even if parsing fails, so the spans of syntax errors resolve too.
+    pub fn parse_map(
+        map: &mut SourceMap,
+        filename: &str,
+        content: String,
+        dialect: &Dialect,
+    ) -> crate::Result<Self> {
         let codemap = CodeMap::new(filename.to_owned(), content);
+        map.add(&codemap)?;
+        Self::parse_codemap(codemap, dialect)
+    }
+
+    fn parse_codemap(codemap: CodeMap, dialect: &Dialect) -> crate::Result<Self> {
+        let typecheck = codemap.source().contains("@starlark-rust: typecheck");
         let lexer = Lexer::new(codemap.source(), dialect, codemap.dupe());
         // Store lint suppressions found during parsing
         let mut lint_suppressions_builder = LintSuppressionsBuilder::new();
@@ -360,6 +404,262 @@ impl AstModule {
         self.statement.visit_expr_mut(|x| f(x, replace));
     }
 
+    /// Visit every identifier naming a variable, with its span.
+    ///
+    /// These are the uses of variables, and the places which bind them: assignment targets,
+    /// `def` names and parameters, `lambda` parameters, `for` and comprehension variables.
+    /// Attribute names (`x.name`), argument names (`f(name = 1)`) and `load` statements
+    /// are not visited.
+    pub fn visit_identifiers(&self, mut f: impl FnMut(&str, Span)) {
+        fn params(params: &[AstParameter], f: &mut impl FnMut(&str, Span)) {
+            for p in params {
+                if let (Some(name), _, _) = p.split() {
+                    f(&name.ident, name.span);
+                }
+            }
+        }
+
+        fn stmt(x: &AstStmt, f: &mut impl FnMut(&str, Span)) {
+            match &x.node {
+                Stmt::Assign(assign) => assign.lhs.visit_lvalue(|x| f(&x.ident, x.span)),
+                Stmt::AssignModify(lhs, _, _) => lhs.visit_lvalue(|x| f(&x.ident, x.span)),
+                Stmt::For(ForP { var, .. }) => var.visit_lvalue(|x| f(&x.ident, x.span)),
+                Stmt::Def(def) => {
+                    f(&def.name.ident, def.name.span);
+                    params(&def.params, f);
+                }
+                _ => {}
+            }
+            x.visit_children(|x| match x {
+                Visit::Stmt(x) => stmt(x, f),
+                Visit::Expr(x) => expr(x, f),
+            })
+        }
+
+        fn expr(x: &AstExpr, f: &mut impl FnMut(&str, Span)) {
+            match &x.node {
+                Expr::Identifier(x) => f(&x.ident, x.span),
+                Expr::Lambda(lambda) => params(&lambda.params, f),
+                Expr::ListComprehension(_, for_, clauses)
+                | Expr::DictComprehension(_, for_, clauses) => {
+                    for_.var.visit_lvalue(|x| f(&x.ident, x.span));
+                    for clause in clauses {
+                        if let Clause::For(for_) = clause {
+                            for_.var.visit_lvalue(|x| f(&x.ident, x.span));
+                        }
+                    }
+                }
+                _ => {}
+            }
+            x.visit_expr(|x| expr(x, f))
+        }
+
+        stmt(&self.statement, &mut f)
+    }
+
+    /// Edits renaming the module-level variable `old` to `new`.
+    ///
+    /// Scopes are resolved: parameters, local variables and comprehension variables
+    /// called `old` shadow the module-level variable, so they and their uses are left
+    /// unchanged, as are argument names (`f(old = 1)`), which name parameters.
+    ///
+    /// In `load` statements, both the local names and the loaded symbols called `old`
+    /// are renamed, so renaming a function in the module defining it and in the modules
+    /// loading it gives consistent results. Strings, comments and attributes
+    /// are left unchanged, as is everything else in the source.
+    pub fn rename_identifier(&self, old: &str, new: &str) -> Vec<TextEdit> {
+        /// Is `old` assigned in a function with this body, so local to the function?
+        fn binds(x: &AstStmt, old: &str) -> bool {
+            let mut res = false;
+            match &x.node {
+                Stmt::Assign(AssignP { lhs, .. })
+                | Stmt::AssignModify(lhs, _, _)
+                | Stmt::For(ForP { var: lhs, .. }) => lhs.visit_lvalue(|x| res |= x.ident == old),
+                // The body of a nested function is a separate scope.
+                Stmt::Def(def) => return def.name.ident == old,
+                _ => {}
+            }
+            x.visit_stmt(|x| res |= binds(x, old));
+            res
+        }
+
+        fn params_bind(params: &[AstParameter], old: &str) -> bool {
+            params
+                .iter()
+                .any(|p| p.split().0.is_some_and(|name| name.ident == old))
+        }
+
+        fn ident(name: &str, span: Span, old: &str, shadowed: bool, f: &mut impl FnMut(Span)) {
+            if !shadowed && name == old {
+                f(span);
+            }
+        }
+
+        fn stmt(x: &AstStmt, old: &str, shadowed: bool, f: &mut impl FnMut(Span)) {
+            match &x.node {
+                Stmt::Assign(AssignP { lhs, .. })
+                | Stmt::AssignModify(lhs, _, _)
+                | Stmt::For(ForP { var: lhs, .. }) => {
+                    lhs.visit_lvalue(|x| ident(&x.ident, x.span, old, shadowed, f))
+                }
+                Stmt::Def(def) => {
+                    ident(&def.name.ident, def.name.span, old, shadowed, f);
+                    // Defaults and types are evaluated in the enclosing scope.
+                    for p in &def.params {
+                        p.visit_expr(|x| expr(x, old, shadowed, f));
+                    }
+                    if let Some(ret) = &def.return_type {
+                        expr(&ret.expr, old, shadowed, f);
+                    }
+                    let shadowed =
+                        shadowed || params_bind(&def.params, old) || binds(&def.body, old);
+                    stmt(&def.body, old, shadowed, f);
+                    return;
+                }
+                _ => {}
+            }
+            x.visit_children(|x| match x {
+                Visit::Stmt(x) => stmt(x, old, shadowed, f),
+                Visit::Expr(x) => expr(x, old, shadowed, f),
+            })
+        }
+
+        fn comprehension(
+            for_: &ForClause,
+            clauses: &[Clause],
+            body: &[&AstExpr],
+            old: &str,
+            shadowed: bool,
+            f: &mut impl FnMut(Span),
+        ) {
+            // The first iterable is evaluated in the enclosing scope.
+            expr(&for_.over, old, shadowed, f);
+            let mut shadowed = shadowed;
+            for_.var.visit_lvalue(|x| shadowed |= x.ident == old);
+            for clause in clauses {
+                if let Clause::For(for_) = clause {
+                    for_.var.visit_lvalue(|x| shadowed |= x.ident == old);
+                }
+            }
+            for_.var.visit_expr(|x| expr(x, old, shadowed, f));
+            for clause in clauses {
+                match clause {
+                    Clause::For(for_) => {
+                        for_.var.visit_expr(|x| expr(x, old, shadowed, f));
+                        expr(&for_.over, old, shadowed, f);
+                    }
+                    Clause::If(cond) => expr(cond, old, shadowed, f),
+                }
+            }
+            for x in body {
+                expr(x, old, shadowed, f);
+            }
+        }
+
+        fn expr(x: &AstExpr, old: &str, shadowed: bool, f: &mut impl FnMut(Span)) {
+            match &x.node {
+                Expr::Identifier(x) => ident(&x.ident, x.span, old, shadowed, f),
+                Expr::Lambda(lambda) => {
+                    for p in &lambda.params {
+                        p.visit_expr(|x| expr(x, old, shadowed, f));
+                    }
+                    let shadowed = shadowed || params_bind(&lambda.params, old);
+                    expr(&lambda.body, old, shadowed, f);
+                }
+                Expr::ListComprehension(x, for_, clauses) => {
+                    comprehension(for_, clauses, &[x], old, shadowed, f)
+                }
+                Expr::DictComprehension(k_v, for_, clauses) => {
+                    comprehension(for_, clauses, &[&k_v.0, &k_v.1], old, shadowed, f)
+                }
+                _ => x.visit_expr(|x| expr(x, old, shadowed, f)),
+            }
+        }
+
+        let mut edits = Vec::new();
+        stmt(&self.statement, old, false, &mut |span| {
+            edits.push(TextEdit {
+                span,
+                text: new.to_owned(),
+            })
+        });
+        // `load` statements are only allowed at the top level.
+        let top = match &self.statement.node {
+            Stmt::Statements(stmts) => stmts.as_slice(),
+            _ => std::slice::from_ref(&self.statement),
+        };
+        for x in top {
+            if let Stmt::Load(load) = &x.node {
+                for LoadArgP { local, their, .. } in &load.args {
+                    if their.node == old {
+                        edits.push(self.rename_string(their.span, old, new));
+                    }
+                    if local.span != their.span && local.ident == old {
+                        edits.push(TextEdit {
+                            span: local.span,
+                            text: new.to_owned(),
+                        });
+                    }
+                }
+            }
+        }
+        edits.sort_by_key(|e| e.span.begin());
+        edits
+    }
+
+    /// Edit renaming the string literal at `span` with value `old`,
+    /// keeping its quotes and prefix.
+    fn rename_string(&self, span: Span, old: &str, new: &str) -> TextEdit {
+        let source = self.codemap.source_span(span);
+        // Only quotes follow the value, so the last match is the value,
+        // unless it is written with escapes.
+        match source.rfind(old) {
+            Some(i) => TextEdit {
+                span: Span::new(
+                    span.begin() + i as u32,
+                    span.begin() + (i + old.len()) as u32,
+                ),
+                text: new.to_owned(),
+            },
+            None => {
+                let open = &source[..source.len() - source.trim_start_matches(['"', '\'']).len()];
+                let close = &source[source.trim_end_matches(['"', '\'']).len()..];
+                TextEdit {
+                    span,
+                    text: format!("{open}{new}{close}"),
+                }
+            }
+        }
+    }
+
+    /// Apply `edits` to the source of this module, returning the new source.
+    /// Fails if the edits overlap.
+    pub fn apply_edits(&self, edits: &[TextEdit]) -> crate::Result<String> {
+        let mut edits: Vec<&TextEdit> = edits.iter().collect();
+        edits.sort_by_key(|e| (e.span.begin(), e.span.end()));
+        let source = self.codemap.source();
+        let mut res = String::with_capacity(source.len());
+        let mut pos = 0;
+        for edit in edits {
+            let begin = edit.span.begin().get() as usize;
+            if begin < pos {
+                return Err(crate::Error::new_spanned(
+                    crate::ErrorKind::Other(
+                        TextEditError::Overlap(self.codemap.source_span(edit.span).to_owned())
+                            .into(),
+                    ),
+                    edit.span,
+                    &self.codemap,
+                ));
+            }
+            res.push_str(&source[pos..begin]);
+            res.push_str(&edit.text);
+            pos = edit.span.end().get() as usize;
+        }
+        res.push_str(&source[pos..]);
+        Ok(res)
+    }
+
     /// Check if a given Lint short_name and span is suppressed in this module
     pub fn is_suppressed(&self, issue_short_name: &str, issue_span: Span) -> bool {
         self.lint_suppressions
@@ -369,8 +669,11 @@ impl AstModule {
 
 #[cfg(test)]
 mod tests {
+    use crate::codemap::SourceMap;
+    use crate::dialect::Dialect;
     use crate::slice_vec_ext::SliceExt;
     use crate::syntax::grammar_tests;
+    use crate::syntax::AstModule;
 
     #[test]
     fn test_locations() {
@@ -384,4 +687,104 @@ mod tests {
         assert_eq!(&get("foo"), "1:1-4");
         assert_eq!(&get("foo\ndef x():\n   pass"), "1:1-4 2:1-3:8 3:4-8");
     }
+
+    #[test]
+    fn test_parse_map() {
+        let mut map = SourceMap::default();
+        let a = AstModule::parse_map(&mut map, "a.star", "x = 1\n".to_owned(), &Dialect::Standard)
+            .unwrap();
+        let b = AstModule::parse_map(&mut map, "b.star", "y = 2\n".to_owned(), &Dialect::Standard)
+            .unwrap();
+        let err =
+            AstModule::parse_map(&mut map, "c.star", "z = (\n".to_owned(), &Dialect::Standard)
+                .unwrap_err();
+        assert_eq!(3, map.files().len());
+
+        let mut globals = Vec::new();
+        for span in a
+            .stmt_locations()
+            .iter()
+            .chain(&b.stmt_locations())
+            .chain(err.span())
+        {
+            let global = map.global_span(span.as_ref()).unwrap();
+            assert_eq!(Some(span), map.resolve(global).as_ref());
+            globals.push(global);
+        }
+        let files = globals
+            .iter()
+            .map(|s| map.resolve(*s).unwrap().filename().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a.star", "b.star", "c.star"], files);
+        assert!(globals.windows(2).all(|w| w[0].end() < w[1].begin()));
+    }
+
+    #[test]
+    fn test_rename_identifier() {
+        let program = r#"
+load("lib.star", 'foo', bar = "foo", foo2 = 'baz')
+# Calls foo.
+def foo(n = foo, *args):
+    x = foo(n = n)
+    return [foo for foo in args if foo] + [foo(1)] + x.foo
+def g(foo):
+    return foo(foo = foo)
+def h():
+    y = foo
+    foo = 1
+    return [foo + y for y in foo]
+foo += 1
+xs = [foo for foo in foo if foo]
+f = lambda foo: "foo" + foo
+k = lambda n: foo(n)
+"#;
+        let m = AstModule::parse("a.star", program.to_owned(), &Dialect::Extended).unwrap();
+        let edits = m.rename_identifier("foo", "qux");
+        assert_eq!(
+            m.apply_edits(&edits).unwrap(),
+            r#"
+load("lib.star", 'qux', bar = "qux", foo2 = 'baz')
+# Calls foo.
+def qux(n = qux, *args):
+    x = qux(n = n)
+    return [foo for foo in args if foo] + [qux(1)] + x.foo
+def g(foo):
+    return foo(foo = foo)
+def h():
+    y = foo
+    foo = 1
+    return [foo + y for y in foo]
+qux += 1
+xs = [foo for foo in qux if foo]
+f = lambda foo: "foo" + foo
+k = lambda n: qux(n)
+"#
+        );
+        // Quotes are kept, also when the symbol is written with escapes.
+        let m = AstModule::parse(
+            "a.star",
+            r#"load("lib.star", r'''foo''', "\x66oo")"#.to_owned(),
+            &Dialect::Extended,
+        )
+        .unwrap();
+        assert_eq!(
+            m.apply_edits(&m.rename_identifier("foo", "qux")).unwrap(),
+            r#"load("lib.star", r'''qux''', "qux")"#
+        );
+        assert!(m.rename_identifier("missing", "x").is_empty());
+    }
+
+    #[test]
+    fn test_apply_edits_overlap() {
+        let m = AstModule::parse("a.star", "xyz = 1\n".to_owned(), &Dialect::Standard).unwrap();
+        let edits = m.rename_identifier("xyz", "a");
+        assert_eq!(m.apply_edits(&edits).unwrap(), "a = 1\n");
+        let mut overlapping = edits.clone();
+        overlapping.extend(edits);
+        let err = m.apply_edits(&overlapping).unwrap_err();
+        assert!(
+            err.to_string().contains("Text edits overlap at `xyz`"),
+            "{err}"
+        );
+    }
 }
@@ -127,6 +127,7 @@ impl<A: AstPayload> StmtP<A> {
                 op,
                 Box::new(rhs.into_map_payload(f)),
             ),
+            StmtP::Del(target) => StmtP::Del(target.into_map_payload(f)),
             StmtP::Statements(stmts) => {
                 StmtP::Statements(stmts.into_map(|s| s.into_map_payload(f)))
             }
@@ -277,6 +278,12 @@ impl<A: AstPayload> AssignTargetP<A> {
                     index.into_map_payload(f),
                 )))
             }
+            AssignTargetP::Slice(x, a, b, c) => AssignTargetP::Slice(
+                Box::new(x.into_map_payload(f)),
+                a.map(|e| Box::new(e.into_map_payload(f))),
+                b.map(|e| Box::new(e.into_map_payload(f))),
+                c.map(|e| Box::new(e.into_map_payload(f))),
+            ),
             AssignTargetP::Dot(object, field) => {
                 AssignTargetP::Dot(Box::new(object.into_map_payload(f)), field)
             }
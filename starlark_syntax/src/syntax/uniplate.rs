@@ -137,6 +137,7 @@ impl<P: AstPayload> StmtP<P> {
                 lhs.visit_expr(|x| f(Visit::Expr(x)));
                 f(Visit::Expr(rhs));
             }
+            StmtP::Del(target) => target.visit_expr(|x| f(Visit::Expr(x))),
             StmtP::Load(..) => {}
         }
     }
@@ -192,6 +193,7 @@ impl<P: AstPayload> StmtP<P> {
                 lhs.visit_expr_mut(|x| f(VisitMut::Expr(x)));
                 f(VisitMut::Expr(rhs));
             }
+            StmtP::Del(target) => target.visit_expr_mut(|x| f(VisitMut::Expr(x))),
             StmtP::Load(..) => {}
         }
     }
@@ -600,6 +602,12 @@ impl<P: AstPayload> AssignTargetP<P> {
                     f(a);
                     f(b);
                 }
+                AssignTargetP::Slice(a, b, c, d) => {
+                    f(a);
+                    b.iter().for_each(|x| f(x));
+                    c.iter().for_each(|x| f(x));
+                    d.iter().for_each(|x| f(x));
+                }
                 AssignTargetP::Identifier(..) => {}
             }
         }
@@ -619,6 +627,12 @@ impl<P: AstPayload> AssignTargetP<P> {
                     f(a);
                     f(b);
                 }
+                AssignTargetP::Slice(a, b, c, d) => {
+                    f(a);
+                    b.iter_mut().for_each(|x| f(x));
+                    c.iter_mut().for_each(|x| f(x));
+                    d.iter_mut().for_each(|x| f(x));
+                }
                 AssignTargetP::Identifier(..) => {}
             }
         }
@@ -17,6 +17,8 @@
 
 //! AST for parsed starlark files.
 
+use crate::syntax::ast::AssignP;
+use crate::syntax::ast::AssignTarget;
 use crate::syntax::ast::AstArgument;
 use crate::syntax::ast::AstExpr;
 use crate::syntax::ast::AstLiteral;
@@ -93,7 +95,7 @@ pub(crate) fn validate_module(stmt: &AstStmt, parser_state: &mut ParserState) {
     // Inside a for, we allow continue/break, unless we go beneath a def.
     // Inside a def, we allow return.
     // All load's must occur at the top-level.
-    // At the top-level we only allow for/if when the dialect permits it.
+    // At the top-level we only allow for/if and expressions when the dialect permits it.
     fn f(
         stmt: &AstStmt,
         parser_state: &mut ParserState,
@@ -126,6 +128,17 @@ pub(crate) fn validate_module(stmt: &AstStmt, parser_state: &mut ParserState) {
                         .visit_stmt(|x| f(x, parser_state, false, inside_for, inside_def))
                 }
             }
+            Stmt::Expression(x) => {
+                if top_level
+                    && !parser_state.dialect.enable_top_level_expressions
+                    && !matches!(x.node, Expr::Literal(AstLiteral::String(_)))
+                {
+                    parser_state.error(
+                        span,
+                        "Expression statements cannot be used outside `def` in this dialect",
+                    )
+                }
+            }
             Stmt::Break if !inside_for => {
                 parser_state.error(span, "`break` cannot be used outside of a `for` loop")
             }
@@ -135,6 +148,18 @@ pub(crate) fn validate_module(stmt: &AstStmt, parser_state: &mut ParserState) {
             Stmt::Return(_) if !inside_def => {
                 parser_state.error(span, "`return` cannot be used outside of a `def` function")
             }
+            Stmt::Del(..) => {
+                if !parser_state.dialect.enable_del {
+                    parser_state.error(span, "`del` is not allowed in this dialect");
+                }
+            }
+            Stmt::Assign(AssignP { lhs, .. }) => {
+                if matches!(lhs.node, AssignTarget::Slice(..))
+                    && !parser_state.dialect.enable_slice_assignment
+                {
+                    parser_state.error(lhs.span, "Slice assignment is not allowed in this dialect");
+                }
+            }
             Stmt::Load(..) => {
                 if !top_level {
                     parser_state.error(span, "`load` must only occur at the top of a module");
@@ -158,7 +183,14 @@ pub(crate) fn validate_module(stmt: &AstStmt, parser_state: &mut ParserState) {
             }
             Expr::Lambda(LambdaP { params, .. }) => {
                 if !parser_state.dialect.enable_lambda {
-                    parser_state.error(x.span, "`lambda` is not allowed in this dialect");
+                    if parser_state.dialect.enable_def {
+                        parser_state.error(
+                            x.span,
+                            "`lambda` is not allowed in this dialect, use a `def` instead",
+                        );
+                    } else {
+                        parser_state.error(x.span, "`lambda` is not allowed in this dialect");
+                    }
                 }
                 validate_params(params, parser_state);
             }